@@ -1,23 +1,12 @@
-use alloy_primitives::U256;
-use alloy_providers::tmp::TempProvider;
-use alloy_rpc_types::BlockTransactions;
-use cast::revm::primitives::EnvWithHandlerCfg;
 use clap::Parser;
 use eyre::{Result, WrapErr};
 use foundry_cli::{
-    init_progress,
     opts::RpcOpts,
-    update_progress,
-    utils::{handle_traces, TraceResult},
+    utils::{handle_traces, replay_transaction, ReplayOpts},
 };
-use foundry_common::{is_known_system_sender, SYSTEM_TRANSACTION_TYPE};
 use foundry_compilers::EvmVersion;
 use foundry_config::{find_project_root_path, Config};
-use foundry_evm::{
-    executors::{EvmError, TracingExecutor},
-    opts::EvmOpts,
-    utils::configure_tx_env,
-};
+use foundry_evm::opts::EvmOpts;
 
 /// CLI arguments for `cast run`.
 #[derive(Clone, Debug, Parser)]
@@ -97,125 +86,14 @@ impl RunArgs {
         .build()?;
 
         let tx_hash = self.tx_hash.parse().wrap_err("invalid tx hash")?;
-        let tx = provider
-            .get_transaction_by_hash(tx_hash)
-            .await
-            .wrap_err_with(|| format!("tx not found: {:?}", tx_hash))?;
-
-        // check if the tx is a system transaction
-        if is_known_system_sender(tx.from) ||
-            tx.transaction_type.map(|ty| ty.to::<u64>()) == Some(SYSTEM_TRANSACTION_TYPE)
-        {
-            return Err(eyre::eyre!(
-                "{:?} is a system transaction.\nReplaying system transactions is currently not supported.",
-                tx.hash
-            ));
-        }
-
-        let tx_block_number = tx
-            .block_number
-            .ok_or_else(|| eyre::eyre!("tx may still be pending: {:?}", tx_hash))?
-            .to::<u64>();
-
-        // we need to fork off the parent block
-        config.fork_block_number = Some(tx_block_number - 1);
-
-        let (mut env, fork, chain) = TracingExecutor::get_fork_material(&config, evm_opts).await?;
-
-        let mut executor = TracingExecutor::new(env.clone(), fork, self.evm_version, self.debug);
-
-        env.block.number = U256::from(tx_block_number);
-
-        let block = provider.get_block(tx_block_number.into(), true).await?;
-        if let Some(ref block) = block {
-            env.block.timestamp = block.header.timestamp;
-            env.block.coinbase = block.header.miner;
-            env.block.difficulty = block.header.difficulty;
-            env.block.prevrandao = Some(block.header.mix_hash.unwrap_or_default());
-            env.block.basefee = block.header.base_fee_per_gas.unwrap_or_default();
-            env.block.gas_limit = block.header.gas_limit;
-        }
-
-        let mut env =
-            EnvWithHandlerCfg::new_with_spec_id(Box::new(env.clone()), executor.spec_id());
-
-        // Set the state to the moment right before the transaction
-        if !self.quick {
-            println!("Executing previous transactions from the block.");
-
-            if let Some(block) = block {
-                let pb = init_progress!(block.transactions, "tx");
-                pb.set_position(0);
-
-                let BlockTransactions::Full(txs) = block.transactions else {
-                    return Err(eyre::eyre!("Could not get block txs"))
-                };
-
-                for (index, tx) in txs.into_iter().enumerate() {
-                    // System transactions such as on L2s don't contain any pricing info so
-                    // we skip them otherwise this would cause
-                    // reverts
-                    if is_known_system_sender(tx.from) ||
-                        tx.transaction_type.map(|ty| ty.to::<u64>()) ==
-                            Some(SYSTEM_TRANSACTION_TYPE)
-                    {
-                        update_progress!(pb, index);
-                        continue;
-                    }
-                    if tx.hash == tx_hash {
-                        break;
-                    }
-
-                    configure_tx_env(&mut env, &tx);
-
-                    if let Some(to) = tx.to {
-                        trace!(tx=?tx.hash,?to, "executing previous call transaction");
-                        executor.commit_tx_with_env(env.clone()).wrap_err_with(|| {
-                            format!(
-                                "Failed to execute transaction: {:?} in block {}",
-                                tx.hash, env.block.number
-                            )
-                        })?;
-                    } else {
-                        trace!(tx=?tx.hash, "executing previous create transaction");
-                        if let Err(error) = executor.deploy_with_env(env.clone(), None) {
-                            match error {
-                                // Reverted transactions should be skipped
-                                EvmError::Execution(_) => (),
-                                error => {
-                                    return Err(error).wrap_err_with(|| {
-                                        format!(
-                                            "Failed to deploy transaction: {:?} in block {}",
-                                            tx.hash, env.block.number
-                                        )
-                                    })
-                                }
-                            }
-                        }
-                    }
-
-                    update_progress!(pb, index);
-                }
-            }
-        }
-
-        // Execute our transaction
-        let result = {
-            executor.set_trace_printer(self.trace_printer);
-
-            configure_tx_env(&mut env, &tx);
-
-            if let Some(to) = tx.to {
-                trace!(tx=?tx.hash, to=?to, "executing call transaction");
-                TraceResult::from(executor.commit_tx_with_env(env)?)
-            } else {
-                trace!(tx=?tx.hash, "executing create transaction");
-                match executor.deploy_with_env(env, None) {
-                    Ok(res) => TraceResult::from(res),
-                    Err(err) => TraceResult::try_from(err)?,
-                }
-            }
+        let opts = ReplayOpts {
+            quick: self.quick,
+            debug: self.debug,
+            trace_printer: self.trace_printer,
+            evm_version: self.evm_version,
         };
+        let (result, chain) =
+            replay_transaction(&provider, tx_hash, &mut config, evm_opts, opts).await?;
 
         handle_traces(result, &config, chain, self.label, self.debug).await?;
 
@@ -223,6 +223,14 @@ interface Vm {
         bool reverted;
     }
 
+    /// Overrides of the transaction of the next contract deployment while broadcasting.
+    struct DeployOverrides {
+        /// The value sent to the constructor, replacing the one of the deployment.
+        uint256 value;
+        /// The gas limit of the transaction, or 0 to estimate it.
+        uint64 gasLimit;
+    }
+
     // ======== EVM ========
 
     /// Gets the address for a given private key.
@@ -595,11 +603,11 @@ interface Vm {
     #[cheatcode(group = Testing, safety = Safe)]
     function assume(bool condition) external pure;
 
-    /// Writes a breakpoint to jump to in the debugger.
+    /// Writes a breakpoint to jump to in the debugger, named by a character or a label.
     #[cheatcode(group = Testing, safety = Safe)]
     function breakpoint(string calldata char) external;
 
-    /// Writes a conditional breakpoint to jump to in the debugger.
+    /// Writes a conditional breakpoint to jump to in the debugger, named by a character or a label.
     #[cheatcode(group = Testing, safety = Safe)]
     function breakpoint(string calldata char, bool value) external;
 
@@ -1587,6 +1595,84 @@ interface Vm {
     #[cheatcode(group = Scripting)]
     function stopBroadcast() external;
 
+    /// Deploys raw creation bytecode, e.g. a contract compiled by a vendor, sending `value` to it,
+    /// and returns the address of the deployed contract. Reverts if the deployment fails.
+    /// When broadcasting, the deployment is recorded as a create transaction of the broadcaster.
+    #[cheatcode(group = Scripting)]
+    function deployRawCode(bytes calldata initCode, uint256 value) external returns (address deployed);
+
+    /// Overrides the value and gas limit of the next contract deployment of the broadcast, e.g.
+    /// `new Contract{salt: salt}()`, so that payable constructors can be deployed with precise gas.
+    /// The value is also sent to the constructor in the simulation.
+    /// Reverts the calling function if it returns before a broadcast deployment uses the overrides.
+    #[cheatcode(group = Scripting)]
+    function overrideNextDeployment(DeployOverrides calldata overrides) external;
+
+    // -------- Script Parameters --------
+
+    /// Gets the script parameter `name` as `bool` from the `forge script --params` file.
+    /// Reverts if the parameter is missing or can't be parsed as `bool`.
+    #[cheatcode(group = Scripting)]
+    function paramBool(string calldata name) external view returns (bool value);
+    /// Gets the script parameter `name` as `uint256` from the `forge script --params` file.
+    /// Reverts if the parameter is missing or can't be parsed as `uint256`.
+    #[cheatcode(group = Scripting)]
+    function paramUint(string calldata name) external view returns (uint256 value);
+    /// Gets the script parameter `name` as `int256` from the `forge script --params` file.
+    /// Reverts if the parameter is missing or can't be parsed as `int256`.
+    #[cheatcode(group = Scripting)]
+    function paramInt(string calldata name) external view returns (int256 value);
+    /// Gets the script parameter `name` as `address` from the `forge script --params` file.
+    /// Reverts if the parameter is missing or can't be parsed as `address`.
+    #[cheatcode(group = Scripting)]
+    function paramAddress(string calldata name) external view returns (address value);
+    /// Gets the script parameter `name` as `bytes32` from the `forge script --params` file.
+    /// Reverts if the parameter is missing or can't be parsed as `bytes32`.
+    #[cheatcode(group = Scripting)]
+    function paramBytes32(string calldata name) external view returns (bytes32 value);
+    /// Gets the script parameter `name` as `string` from the `forge script --params` file.
+    /// Reverts if the parameter is missing or can't be parsed as `string`.
+    #[cheatcode(group = Scripting)]
+    function paramString(string calldata name) external view returns (string memory value);
+    /// Gets the script parameter `name` as `bytes` from the `forge script --params` file.
+    /// Reverts if the parameter is missing or can't be parsed as `bytes`.
+    #[cheatcode(group = Scripting)]
+    function paramBytes(string calldata name) external view returns (bytes memory value);
+    /// Gets the script parameter `name` as `bool`, or `defaultValue` if it's not set.
+    /// Reverts if the parameter is set but can't be parsed as `bool`.
+    #[cheatcode(group = Scripting)]
+    function paramOr(string calldata name, bool defaultValue) external view returns (bool value);
+    /// Gets the script parameter `name` as `uint256`, or `defaultValue` if it's not set.
+    /// Reverts if the parameter is set but can't be parsed as `uint256`.
+    #[cheatcode(group = Scripting)]
+    function paramOr(string calldata name, uint256 defaultValue) external view returns (uint256 value);
+    /// Gets the script parameter `name` as `int256`, or `defaultValue` if it's not set.
+    /// Reverts if the parameter is set but can't be parsed as `int256`.
+    #[cheatcode(group = Scripting)]
+    function paramOr(string calldata name, int256 defaultValue) external view returns (int256 value);
+    /// Gets the script parameter `name` as `address`, or `defaultValue` if it's not set.
+    /// Reverts if the parameter is set but can't be parsed as `address`.
+    #[cheatcode(group = Scripting)]
+    function paramOr(string calldata name, address defaultValue) external view returns (address value);
+    /// Gets the script parameter `name` as `bytes32`, or `defaultValue` if it's not set.
+    /// Reverts if the parameter is set but can't be parsed as `bytes32`.
+    #[cheatcode(group = Scripting)]
+    function paramOr(string calldata name, bytes32 defaultValue) external view returns (bytes32 value);
+    /// Gets the script parameter `name` as `string`, or `defaultValue` if it's not set.
+    /// Reverts if the parameter is set but can't be parsed as `string`.
+    #[cheatcode(group = Scripting)]
+    function paramOr(string calldata name, string calldata defaultValue) external view returns (string memory value);
+    /// Gets the script parameter `name` as `bytes`, or `defaultValue` if it's not set.
+    /// Reverts if the parameter is set but can't be parsed as `bytes`.
+    #[cheatcode(group = Scripting)]
+    function paramOr(string calldata name, bytes calldata defaultValue) external view returns (bytes memory value);
+    /// Returns whether the feature flag `name` is enabled, i.e. set to `true` in the `[features]` table
+    /// of the `forge script --params` file, with `forge script --feature <name>` or by the `--env`
+    /// environment. Flags that aren't set are disabled.
+    /// Reverts if the flag is set to something else than a boolean.
+    #[cheatcode(group = Scripting)]
+    function isFeatureEnabled(string calldata name) external view returns (bool enabled);
+
     // ======== Utilities ========
 
     // -------- Strings --------
@@ -1986,6 +2072,14 @@ interface Vm {
     #[cheatcode(group = Utilities)]
     function computeCreate2Address(bytes32 salt, bytes32 initCodeHash) external pure returns (address);
 
+    /// Compute the address an L1 contract sends L1->L2 deposits and messages from on OP stack and Arbitrum chains.
+    #[cheatcode(group = Utilities)]
+    function computeL2Alias(address l1Address) external pure returns (address l2Address);
+
+    /// Compute the L1 contract which sends L1->L2 deposits and messages from the given aliased address.
+    #[cheatcode(group = Utilities)]
+    function undoL2Alias(address l2Address) external pure returns (address l1Address);
+
     /// Encodes a `bytes` value to a base64 string.
     #[cheatcode(group = Utilities)]
     function toBase64(bytes calldata data) external pure returns (string memory);
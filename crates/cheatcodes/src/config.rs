@@ -1,5 +1,8 @@
 use super::Result;
-use crate::{script::ScriptWallets, Vm::Rpc};
+use crate::{
+    script::{ScriptParams, ScriptWallets},
+    Vm::Rpc,
+};
 use alloy_primitives::Address;
 use foundry_common::fs::normalize_path;
 use foundry_compilers::{utils::canonicalize, ProjectPathsConfig};
@@ -40,6 +43,10 @@ pub struct CheatsConfig {
     pub labels: HashMap<Address, String>,
     /// Script wallets
     pub script_wallets: Option<ScriptWallets>,
+    /// Script parameters, passed with `forge script --params <file>`
+    pub script_params: Option<ScriptParams>,
+    /// Whether network access is disabled, in which case forks can't be created.
+    pub offline: bool,
 }
 
 impl CheatsConfig {
@@ -64,6 +71,8 @@ impl CheatsConfig {
             evm_opts,
             labels: config.labels.clone(),
             script_wallets,
+            script_params: None,
+            offline: config.offline,
         }
     }
 
@@ -180,6 +189,8 @@ impl Default for CheatsConfig {
             evm_opts: Default::default(),
             labels: Default::default(),
             script_wallets: None,
+            script_params: None,
+            offline: false,
         }
     }
 }
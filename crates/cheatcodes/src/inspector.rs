@@ -7,7 +7,7 @@ use crate::{
         prank::Prank,
         DealRecord, RecordAccess,
     },
-    script::{Broadcast, ScriptWallets},
+    script::{Broadcast, PendingDeployOverrides, ScriptWallets},
     test::expect::{
         self, ExpectedCallData, ExpectedCallTracker, ExpectedCallType, ExpectedEmit,
         ExpectedRevert, ExpectedRevertKind,
@@ -159,6 +159,10 @@ pub struct Cheatcodes {
     /// Current broadcasting information
     pub broadcast: Option<Broadcast>,
 
+    /// Overrides of the transaction of the next broadcast deployment, set by
+    /// `overrideNextDeployment`
+    pub deploy_overrides: Option<PendingDeployOverrides>,
+
     /// Used to correct the nonce of --sender after the initiating call. For more, check
     /// `docs/scripting`.
     pub corrected_nonce: bool,
@@ -294,6 +298,23 @@ impl Cheatcodes {
             }
         }
     }
+
+    /// Called when a call or create returns to `depth`.
+    ///
+    /// Overrides of the next deployment which weren't used by the time the call which set them
+    /// returns are dropped, and the call reverts, so they can't apply to an unrelated deployment.
+    fn check_deploy_overrides(&mut self, depth: u64, result: &mut InterpreterResult) {
+        if self.deploy_overrides.as_ref().map_or(true, |overrides| depth >= overrides.depth) {
+            return
+        }
+        self.deploy_overrides = None;
+        if result.result.is_ok() {
+            result.result = InstructionResult::Revert;
+            result.output = Error::encode(
+                "overrideNextDeployment: the overrides weren't used by a broadcast deployment",
+            );
+        }
+    }
 }
 
 impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
@@ -973,6 +994,8 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
             }
         }
 
+        self.check_deploy_overrides(ecx.journaled_state.depth(), &mut outcome.result);
+
         // Handle expected reverts
         if let Some(expected_revert) = &self.expected_revert {
             if ecx.journaled_state.depth() <= expected_revert.depth {
@@ -1249,13 +1272,26 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                 ecx.env.tx.caller = broadcast.new_origin;
 
                 if ecx.journaled_state.depth() == broadcast.depth {
+                    let overrides =
+                        self.deploy_overrides.take().map(|overrides| overrides.overrides);
+                    if let Some(overrides) = &overrides {
+                        call.value = overrides.value;
+                    }
                     let (bytecode, to, nonce) = process_broadcast_create(
                         broadcast.new_origin,
                         call.init_code.clone(),
                         ecx,
                         call,
                     );
-                    let is_fixed_gas_limit = check_if_fixed_gas_limit(ecx, call.gas_limit);
+                    let gas = match overrides {
+                        Some(overrides) if overrides.gasLimit != 0 => {
+                            Some(U256::from(overrides.gasLimit))
+                        }
+                        _ if check_if_fixed_gas_limit(ecx, call.gas_limit) => {
+                            Some(U256::from(call.gas_limit))
+                        }
+                        _ => None,
+                    };
 
                     self.broadcastable_transactions.push_back(BroadcastableTransaction {
                         rpc: ecx.db.active_fork_url(),
@@ -1265,11 +1301,7 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                             value: Some(call.value),
                             input: TransactionInput::new(bytecode),
                             nonce: Some(U64::from(nonce)),
-                            gas: if is_fixed_gas_limit {
-                                Some(U256::from(call.gas_limit))
-                            } else {
-                                None
-                            },
+                            gas,
                             ..Default::default()
                         },
                     });
@@ -1377,6 +1409,8 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
             }
         }
 
+        self.check_deploy_overrides(ecx.journaled_state.depth(), &mut outcome.result);
+
         // Handle expected reverts
         if let Some(expected_revert) = &self.expected_revert {
             if ecx.journaled_state.depth() <= expected_revert.depth &&
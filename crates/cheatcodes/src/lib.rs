@@ -34,7 +34,7 @@ mod test;
 mod toml;
 mod utils;
 
-pub use script::ScriptWallets;
+pub use script::{ScriptParams, ScriptWallets};
 pub use test::expect::ExpectedCallTracker;
 
 /// Cheatcode implementation.
@@ -1,12 +1,31 @@
 //! Implementations of [`Scripting`](crate::Group::Scripting) cheatcodes.
 
-use crate::{Cheatcode, CheatsCtxt, DatabaseExt, Result, Vm::*};
-use alloy_primitives::{Address, U256};
+use crate::{
+    string, BroadcastableTransaction, Cheatcode, Cheatcodes, CheatsCtxt, DatabaseExt, Result, Vm::*,
+};
+use alloy_dyn_abi::DynSolType;
+use alloy_primitives::{hex, Address, Bytes, U256, U64};
+use alloy_rpc_types::request::{TransactionInput, TransactionRequest};
 use alloy_signer::{LocalWallet, Signer};
+use alloy_sol_types::SolValue;
+use eyre::WrapErr;
+use foundry_common::fs;
 use foundry_config::Config;
+use foundry_evm_core::utils::new_evm_with_inspector;
 use foundry_wallets::{multi_wallet::MultiWallet, WalletSigner};
 use parking_lot::Mutex;
-use std::sync::Arc;
+use revm::{
+    inspectors::NoOpInspector,
+    primitives::{
+        CreateScheme, EnvWithHandlerCfg, ExecutionResult, Output, ResultAndState, TransactTo,
+    },
+};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+    sync::Arc,
+};
 
 impl Cheatcode for broadcast_0Call {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
@@ -61,6 +80,168 @@ impl Cheatcode for stopBroadcastCall {
     }
 }
 
+impl Cheatcode for deployRawCodeCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { initCode, value } = self;
+        ensure!(!initCode.is_empty(), "init code is empty");
+
+        // Deploy from the broadcaster if this call would have been broadcast.
+        let broadcast = ccx.state.broadcast.clone().filter(|broadcast| {
+            ccx.ecx.journaled_state.depth() == broadcast.depth &&
+                ccx.caller == broadcast.original_caller
+        });
+        let deployer = broadcast.as_ref().map_or(ccx.caller, |broadcast| broadcast.new_origin);
+        let nonce = super::evm::journaled_account(ccx.ecx, deployer)?.info.nonce;
+        let mut value = *value;
+
+        if let Some(broadcast) = broadcast {
+            let overrides = ccx.state.deploy_overrides.take().map(|overrides| overrides.overrides);
+            if let Some(overrides) = &overrides {
+                value = overrides.value;
+            }
+            ccx.state.broadcastable_transactions.push_back(BroadcastableTransaction {
+                rpc: ccx.ecx.db.active_fork_url(),
+                transaction: TransactionRequest {
+                    from: Some(deployer),
+                    to: None,
+                    value: Some(value),
+                    input: TransactionInput::new(initCode.clone()),
+                    nonce: Some(U64::from(nonce)),
+                    gas: overrides
+                        .filter(|overrides| overrides.gasLimit != 0)
+                        .map(|overrides| U256::from(overrides.gasLimit)),
+                    ..Default::default()
+                },
+            });
+            debug!(target: "cheatcodes", tx=?ccx.state.broadcastable_transactions.back().unwrap(), "broadcastable raw create");
+            if broadcast.single_call {
+                ccx.state.broadcast = None;
+            }
+        }
+
+        deploy_raw_code(ccx, deployer, initCode.clone(), value).map(|address| address.abi_encode())
+    }
+}
+
+impl Cheatcode for overrideNextDeploymentCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { overrides } = self;
+        let depth = ccx.ecx.journaled_state.depth();
+        ccx.state.deploy_overrides =
+            Some(PendingDeployOverrides { overrides: overrides.clone(), depth });
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for paramBoolCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name } = self;
+        param(state, name, &DynSolType::Bool)
+    }
+}
+
+impl Cheatcode for paramUintCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name } = self;
+        param(state, name, &DynSolType::Uint(256))
+    }
+}
+
+impl Cheatcode for paramIntCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name } = self;
+        param(state, name, &DynSolType::Int(256))
+    }
+}
+
+impl Cheatcode for paramAddressCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name } = self;
+        param(state, name, &DynSolType::Address)
+    }
+}
+
+impl Cheatcode for paramBytes32Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name } = self;
+        param(state, name, &DynSolType::FixedBytes(32))
+    }
+}
+
+impl Cheatcode for paramStringCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name } = self;
+        param(state, name, &DynSolType::String)
+    }
+}
+
+impl Cheatcode for paramBytesCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name } = self;
+        param(state, name, &DynSolType::Bytes)
+    }
+}
+
+impl Cheatcode for isFeatureEnabledCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name } = self;
+        let enabled = match &state.config.script_params {
+            Some(params) => params.is_feature_enabled(name)?,
+            None => false,
+        };
+        Ok(enabled.abi_encode())
+    }
+}
+
+impl Cheatcode for paramOr_0Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name, defaultValue } = self;
+        param_default(state, name, defaultValue, &DynSolType::Bool)
+    }
+}
+
+impl Cheatcode for paramOr_1Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name, defaultValue } = self;
+        param_default(state, name, defaultValue, &DynSolType::Uint(256))
+    }
+}
+
+impl Cheatcode for paramOr_2Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name, defaultValue } = self;
+        param_default(state, name, defaultValue, &DynSolType::Int(256))
+    }
+}
+
+impl Cheatcode for paramOr_3Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name, defaultValue } = self;
+        param_default(state, name, defaultValue, &DynSolType::Address)
+    }
+}
+
+impl Cheatcode for paramOr_4Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name, defaultValue } = self;
+        param_default(state, name, defaultValue, &DynSolType::FixedBytes(32))
+    }
+}
+
+impl Cheatcode for paramOr_5Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name, defaultValue } = self;
+        param_default(state, name, defaultValue, &DynSolType::String)
+    }
+}
+
+impl Cheatcode for paramOr_6Call {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { name, defaultValue } = self;
+        param_default(state, name, defaultValue, &DynSolType::Bytes)
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Broadcast {
     /// Address of the transaction origin
@@ -75,6 +256,15 @@ pub struct Broadcast {
     pub single_call: bool,
 }
 
+/// Overrides of the transaction of the next broadcast deployment, set by `overrideNextDeployment`.
+#[derive(Clone, Debug)]
+pub struct PendingDeployOverrides {
+    /// The value and gas limit of the deployment.
+    pub overrides: DeployOverrides,
+    /// Depth of the call which set the overrides, they must be used before it returns.
+    pub depth: u64,
+}
+
 /// Contains context for wallet management.
 #[derive(Debug)]
 pub struct ScriptWalletsInner {
@@ -113,6 +303,122 @@ impl ScriptWallets {
     }
 }
 
+/// The prefix of the parameters which are feature flags, i.e. the `[features]` table.
+const FEATURES_PREFIX: &str = "features.";
+
+/// Contains the parameters passed to a script with `forge script --params <file>`.
+#[derive(Debug, Default)]
+pub struct ScriptParamsInner {
+    /// Parameter values by name. Nested tables are flattened with `.`, e.g. `fees.max`.
+    pub values: BTreeMap<String, String>,
+    /// Names of the parameters read by the script so far.
+    pub read: BTreeSet<String>,
+    /// The feature flags queried by the script with `vm.isFeatureEnabled`, and whether they're
+    /// enabled.
+    pub features: BTreeMap<String, bool>,
+}
+
+/// Clonable wrapper around [ScriptParamsInner].
+#[derive(Debug, Clone, Default)]
+pub struct ScriptParams {
+    /// Inner data.
+    pub inner: Arc<Mutex<ScriptParamsInner>>,
+}
+
+impl ScriptParams {
+    #[allow(missing_docs)]
+    pub fn new(values: BTreeMap<String, String>) -> Self {
+        Self { inner: Arc::new(Mutex::new(ScriptParamsInner { values, ..Default::default() })) }
+    }
+
+    /// Reads the parameters from a TOML or JSON file, depending on its extension.
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let value: Value = if path.extension().map_or(false, |ext| ext == "json") {
+            serde_json::from_str(&content)?
+        } else {
+            toml::from_str(&content)?
+        };
+
+        let mut values = BTreeMap::new();
+        flatten_params(None, value, &mut values)
+            .wrap_err_with(|| format!("invalid script parameters in {}", path.display()))?;
+        Ok(Self::new(values))
+    }
+
+    /// Returns the value of the parameter `name`, and marks it as read.
+    pub fn get(&self, name: &str) -> Option<String> {
+        let mut inner = self.inner.lock();
+        let value = inner.values.get(name).cloned()?;
+        inner.read.insert(name.to_string());
+        Some(value)
+    }
+
+    /// Enables the feature flags `names`, on top of the `[features]` table of the parameters.
+    pub fn enable_features(&self, names: &[String]) {
+        let mut inner = self.inner.lock();
+        for name in names {
+            inner.values.insert(format!("{FEATURES_PREFIX}{name}"), true.to_string());
+        }
+    }
+
+    /// Returns whether the feature flag `name` is set to `true`, and records it.
+    pub fn is_feature_enabled(&self, name: &str) -> eyre::Result<bool> {
+        let enabled = match self.get(&format!("{FEATURES_PREFIX}{name}")) {
+            Some(value) => value.parse().map_err(|_| {
+                eyre::eyre!("feature flag {name:?} must be `true` or `false`, got {value:?}")
+            })?,
+            None => false,
+        };
+        self.inner.lock().features.insert(name.to_string(), enabled);
+        Ok(enabled)
+    }
+
+    /// Returns the feature flags queried by the script so far, and whether they're enabled.
+    pub fn features(&self) -> BTreeMap<String, bool> {
+        self.inner.lock().features.clone()
+    }
+
+    /// Returns the names of the parameters that were never read by the script, which usually
+    /// indicates a typo in the parameters file.
+    pub fn unused(&self) -> Vec<String> {
+        let inner = self.inner.lock();
+        inner.values.keys().filter(|name| !inner.read.contains(*name)).cloned().collect()
+    }
+}
+
+fn flatten_params(
+    prefix: Option<&str>,
+    value: Value,
+    values: &mut BTreeMap<String, String>,
+) -> eyre::Result<()> {
+    let name = || prefix.unwrap_or_default().to_string();
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let key = match prefix {
+                    Some(prefix) => format!("{prefix}.{key}"),
+                    None => key,
+                };
+                flatten_params(Some(&key), value, values)?;
+            }
+        }
+        Value::String(s) => {
+            values.insert(name(), s);
+        }
+        Value::Bool(b) => {
+            values.insert(name(), b.to_string());
+        }
+        Value::Number(n) => {
+            values.insert(name(), n.to_string());
+        }
+        Value::Array(_) | Value::Null => {
+            eyre::bail!("parameter {:?} must be a string, number or boolean", name())
+        }
+    }
+    Ok(())
+}
+
 /// Sets up broadcasting from a script using `new_origin` as the sender.
 fn broadcast<DB: DatabaseExt>(
     ccx: &mut CheatsCtxt<DB>,
@@ -191,3 +497,179 @@ pub(super) fn correct_sender_nonce<DB: DatabaseExt>(ccx: &mut CheatsCtxt<DB>) ->
     }
     Ok(())
 }
+
+/// Reads the script parameter `name` as `ty`.
+fn param(state: &Cheatcodes, name: &str, ty: &DynSolType) -> Result {
+    match get_param(state, name) {
+        Some(value) => parse_param(name, &value, ty),
+        None => bail!("script parameter {name:?} not found; pass it with `--params <file>`"),
+    }
+}
+
+/// Reads the script parameter `name` as `ty`, falling back to `default` if it's not set.
+///
+/// Unlike `envOr`, a value that can't be parsed as the type of `default` is an error rather than
+/// silently ignored.
+fn param_default<T: SolValue>(
+    state: &Cheatcodes,
+    name: &str,
+    default: &T,
+    ty: &DynSolType,
+) -> Result {
+    match get_param(state, name) {
+        Some(value) => parse_param(name, &value, ty),
+        None => Ok(default.abi_encode()),
+    }
+}
+
+fn get_param(state: &Cheatcodes, name: &str) -> Option<String> {
+    state.config.script_params.as_ref()?.get(name)
+}
+
+fn parse_param(name: &str, value: &str, ty: &DynSolType) -> Result {
+    string::parse(value, ty).map_err(|e| fmt_err!("invalid script parameter {name:?}: {e}"))
+}
+
+/// Deploys `init_code` from `deployer` on top of the current state.
+///
+/// The init code is run by a separate EVM without the inspectors, so the deployment isn't traced.
+/// Its changes are then applied through the journal as a CREATE of the calling frame would be, so
+/// that they're reverted with that frame. The constructor can only change the deployed contract.
+fn deploy_raw_code<DB: DatabaseExt>(
+    ccx: &mut CheatsCtxt<DB>,
+    deployer: Address,
+    init_code: Bytes,
+    value: U256,
+) -> Result<Address> {
+    let mut env = ccx.ecx.env.clone();
+    env.tx.caller = deployer;
+    env.tx.transact_to = TransactTo::Create(CreateScheme::Create);
+    env.tx.data = init_code;
+    env.tx.value = value;
+    env.tx.nonce = None;
+    env.tx.gas_price = U256::ZERO;
+    env.tx.gas_priority_fee = None;
+    env.tx.gas_limit = env.block.gas_limit.saturating_to();
+    env.block.basefee = U256::ZERO;
+    let env = EnvWithHandlerCfg::new_with_spec_id(env, ccx.ecx.spec_id());
+
+    let state = ccx.ecx.journaled_state.state.clone();
+    let ResultAndState { result, state } = {
+        let mut evm = new_evm_with_inspector(&mut ccx.ecx.db, env, NoOpInspector);
+        evm.context.evm.inner.journaled_state.state = state;
+        evm.transact()?
+    };
+    let (created, logs) = match result {
+        ExecutionResult::Success { output: Output::Create(_, Some(address)), logs, .. } => {
+            (address, logs)
+        }
+        ExecutionResult::Revert { output, .. } => {
+            bail!("raw code deployment reverted: {}", hex::encode_prefixed(output))
+        }
+        result => bail!("raw code deployment failed: {result:?}"),
+    };
+
+    let mut storage = Vec::new();
+    for (address, account) in &state {
+        if !account.is_touched() {
+            continue
+        }
+        let (current, _) = ccx.ecx.load_account(*address)?;
+        let slots = account
+            .storage
+            .iter()
+            .filter(|(slot, value)| {
+                current.storage.get(*slot).map_or(value.is_changed(), |current| {
+                    current.present_value != value.present_value
+                })
+            })
+            .map(|(slot, value)| (*slot, value.present_value))
+            .collect::<Vec<_>>();
+        if *address == created {
+            storage = slots;
+            continue
+        }
+        let info_changed = *address != deployer &&
+            (current.info.balance != account.info.balance ||
+                current.info.nonce != account.info.nonce ||
+                current.info.code_hash != account.info.code_hash);
+        ensure!(
+            !info_changed && slots.is_empty(),
+            "the constructor of raw code can only change the deployed contract, it changed {address}"
+        );
+    }
+    let code = state.get(&created).and_then(|account| account.info.code.clone());
+
+    // The same journal entries as a CREATE: the nonce of the deployer, the new account with its
+    // value, its storage and its code.
+    let ecx = &mut *ccx.ecx;
+    let spec_id = ecx.spec_id();
+    ecx.load_account(deployer)?;
+    ecx.journaled_state.inc_nonce(deployer);
+    ecx.load_account(created)?;
+    let checkpoint = ecx
+        .journaled_state
+        .create_account_checkpoint(deployer, created, value, spec_id)
+        .map_err(|err| fmt_err!("raw code deployment failed: {err:?}"))?;
+    for (slot, value) in storage {
+        if let Err(err) = ecx.sstore(created, slot, value) {
+            ecx.journaled_state.checkpoint_revert(checkpoint);
+            return Err(err.into())
+        }
+    }
+    ecx.journaled_state.set_code(created, code.unwrap_or_default());
+    ecx.journaled_state.logs.extend(logs);
+    ecx.journaled_state.checkpoint_commit();
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flattens_script_params() {
+        let value = serde_json::json!({
+            "fee": 100,
+            "owner": "0x4e59b44847b379578588920ca78fbf26c0b4956c",
+            "limits": { "max": "1 ether", "enabled": true },
+        });
+        let mut values = BTreeMap::new();
+        flatten_params(None, value, &mut values).unwrap();
+        assert_eq!(values["fee"], "100");
+        assert_eq!(values["limits.max"], "1 ether");
+        assert_eq!(values["limits.enabled"], "true");
+
+        let params = ScriptParams::new(values);
+        assert_eq!(params.get("fee").as_deref(), Some("100"));
+        assert_eq!(params.unused(), ["limits.enabled", "limits.max", "owner"]);
+
+        let value = serde_json::json!({ "list": [1, 2] });
+        assert!(flatten_params(None, value, &mut BTreeMap::new()).is_err());
+    }
+
+    #[test]
+    fn resolves_feature_flags() {
+        let value =
+            serde_json::json!({ "features": { "oracle": true, "bridge": false, "bad": 1 } });
+        let mut values = BTreeMap::new();
+        flatten_params(None, value, &mut values).unwrap();
+        let params = ScriptParams::new(values);
+        params.enable_features(&["bridge".to_string(), "staking".to_string()]);
+
+        assert!(params.is_feature_enabled("oracle").unwrap());
+        assert!(params.is_feature_enabled("bridge").unwrap());
+        assert!(params.is_feature_enabled("staking").unwrap());
+        assert!(!params.is_feature_enabled("governance").unwrap());
+        assert!(params.is_feature_enabled("bad").is_err());
+        assert_eq!(
+            params.features(),
+            BTreeMap::from([
+                ("bridge".to_string(), true),
+                ("governance".to_string(), false),
+                ("oracle".to_string(), true),
+                ("staking".to_string(), true),
+            ])
+        );
+    }
+}
@@ -78,18 +78,42 @@ impl Cheatcode for skipCall {
 }
 
 /// Adds or removes the given breakpoint to the state.
+///
+/// Single character breakpoints can be jumped to directly in the debugger, while labels are
+/// reached by cycling through the breakpoints.
 fn breakpoint(state: &mut Cheatcodes, caller: &Address, s: &str, add: bool) -> Result {
-    let mut chars = s.chars();
-    let (Some(point), None) = (chars.next(), chars.next()) else {
-        bail!("breakpoints must be exactly one character");
-    };
-    ensure!(point.is_alphabetic(), "only alphabetic characters are accepted as breakpoints");
+    ensure!(!s.trim().is_empty(), "breakpoints must not be empty");
 
     if add {
-        state.breakpoints.insert(point, (*caller, state.pc));
+        state.breakpoints.insert(s.to_string(), (*caller, state.pc));
     } else {
-        state.breakpoints.remove(&point);
+        state.breakpoints.remove(s);
     }
 
     Ok(Default::default())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_labeled_breakpoints() {
+        let mut state = Cheatcodes { pc: 42, ..Default::default() };
+        let caller = Address::repeat_byte(1);
+
+        breakpoint(&mut state, &caller, "deploy token", true).unwrap();
+        breakpoint(&mut state, &caller, "a", true).unwrap();
+        assert_eq!(state.breakpoints["deploy token"], (caller, 42));
+        assert_eq!(state.breakpoints["a"], (caller, 42));
+
+        // a label set again moves to the new location
+        state.pc = 7;
+        breakpoint(&mut state, &caller, "deploy token", true).unwrap();
+        assert_eq!(state.breakpoints["deploy token"], (caller, 7));
+
+        breakpoint(&mut state, &caller, "deploy token", false).unwrap();
+        assert!(!state.breakpoints.contains_key("deploy token"));
+        assert!(breakpoint(&mut state, &caller, " ", true).is_err());
+    }
+}
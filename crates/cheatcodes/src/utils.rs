@@ -10,7 +10,10 @@ use alloy_signer::{
     LocalWallet, MnemonicBuilder, Signer, SignerSync,
 };
 use alloy_sol_types::SolValue;
-use foundry_evm_core::constants::DEFAULT_CREATE2_DEPLOYER;
+use foundry_evm_core::{
+    constants::DEFAULT_CREATE2_DEPLOYER,
+    utils::{apply_l2_alias, undo_l2_alias},
+};
 use k256::{
     ecdsa::SigningKey,
     elliptic_curve::{sec1::ToEncodedPoint, Curve},
@@ -136,6 +139,20 @@ impl Cheatcode for computeCreate2Address_1Call {
     }
 }
 
+impl Cheatcode for computeL2AliasCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { l1Address } = self;
+        Ok(apply_l2_alias(*l1Address).abi_encode())
+    }
+}
+
+impl Cheatcode for undoL2AliasCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { l2Address } = self;
+        Ok(undo_l2_alias(*l2Address).abi_encode())
+    }
+}
+
 /// Using a given private key, return its public ETH address, its public key affine x and y
 /// coordinates, and its private key (see the 'Wallet' struct)
 ///
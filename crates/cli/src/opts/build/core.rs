@@ -16,7 +16,7 @@ use foundry_config::{
     Config,
 };
 use serde::Serialize;
-use std::path::PathBuf;
+use std::{num::NonZeroUsize, path::PathBuf};
 
 #[derive(Clone, Debug, Default, Serialize, Parser)]
 #[command(next_help_heading = "Build options")]
@@ -58,6 +58,15 @@ pub struct CoreBuildArgs {
     #[serde(skip)]
     pub use_solc: Option<String>,
 
+    /// The maximum number of solc processes to run concurrently.
+    ///
+    /// Sources that need different compiler versions are compiled concurrently. Scripts also
+    /// compile the sources which don't import each other in separate, concurrent solc runs.
+    /// Defaults to the number of logical cores.
+    #[arg(long, help_heading = "Compiler options", value_name = "JOBS")]
+    #[serde(rename = "solc_jobs", skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<NonZeroUsize>,
+
     /// Do not access the network.
     ///
     /// Missing solc versions will not be installed.
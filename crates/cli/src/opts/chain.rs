@@ -1,11 +1,13 @@
 use clap::builder::{PossibleValuesParser, TypedValueParser};
 use eyre::Result;
-use foundry_config::{Chain, NamedChain};
+use foundry_config::{Chain, ChainDefinitions, NamedChain};
 use std::ffi::OsStr;
 use strum::VariantNames;
 
 /// Custom Clap value parser for [`Chain`]s.
 ///
+/// Chains registered with `forge chain add` are also accepted by name.
+///
 /// Displays all possible chains when an invalid chain is provided.
 #[derive(Clone, Debug)]
 pub struct ChainValueParser {
@@ -37,9 +39,13 @@ impl TypedValueParser for ChainValueParser {
             //
             // Parse first as NamedChain, if it fails parse with NamedChain::VARIANTS for displaying
             // the error to the user
-            s.parse()
-                .map_err(|_| self.inner.parse_ref(cmd, arg, value).unwrap_err())
-                .map(Chain::from_named)
+            if let Ok(named) = s.parse() {
+                return Ok(Chain::from_named(named))
+            }
+            match ChainDefinitions::load().get(s) {
+                Some(definition) => Ok(definition.chain()),
+                None => Err(self.inner.parse_ref(cmd, arg, value).unwrap_err()),
+            }
         }
     }
 }
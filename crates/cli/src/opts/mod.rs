@@ -3,9 +3,11 @@ mod chain;
 mod dependency;
 mod ethereum;
 mod transaction;
+mod watch;
 
 pub use build::*;
 pub use chain::*;
 pub use dependency::*;
 pub use ethereum::*;
 pub use transaction::*;
+pub use watch::*;
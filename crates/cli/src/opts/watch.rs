@@ -0,0 +1,45 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+/// CLI arguments of the `--watch` mode of the commands which support it.
+#[derive(Clone, Debug, Default, Parser)]
+#[command(next_help_heading = "Watch options")]
+pub struct WatchArgs {
+    /// Watch the given files or directories for changes.
+    ///
+    /// If no paths are provided, the source and test directories of the project are watched, or
+    /// the source and script directories for `forge script`.
+    #[arg(
+        long,
+        short,
+        num_args(0..),
+        value_name = "PATH",
+    )]
+    pub watch: Option<Vec<PathBuf>>,
+
+    /// Do not restart the command while it's still running.
+    #[arg(long)]
+    pub no_restart: bool,
+
+    /// Explicitly re-run all tests when a change is made.
+    ///
+    /// By default, only the tests of the last modified test file are executed.
+    #[arg(long)]
+    pub run_all: bool,
+
+    /// File update debounce delay.
+    ///
+    /// During the delay, incoming change events are accumulated and
+    /// only once the delay has passed, is an action taken. Note that
+    /// this does not mean a command will be started: if --no-restart is
+    /// given and a command is already running, the outcome of the
+    /// action will be to do nothing.
+    ///
+    /// Defaults to 50ms. Parses as decimal seconds by default, but
+    /// using an integer with the `ms` suffix may be more convenient.
+    ///
+    /// When using --poll mode, you'll want a larger duration, or risk
+    /// overloading disk I/O.
+    #[arg(long, value_name = "DELAY")]
+    pub watch_delay: Option<String>,
+}
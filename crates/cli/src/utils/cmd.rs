@@ -1,4 +1,5 @@
-use alloy_json_abi::JsonAbi;
+use alloy_dyn_abi::{DynSolType, Specifier};
+use alloy_json_abi::{JsonAbi, Param};
 use alloy_primitives::Address;
 use eyre::{Result, WrapErr};
 use foundry_common::{cli_warn, fs, TestFunctionExt};
@@ -7,7 +8,7 @@ use foundry_compilers::{
     cache::{CacheEntry, SolFilesCache},
     info::ContractInfo,
     utils::read_json_file,
-    Artifact, ProjectCompileOutput,
+    Artifact, ProjectCompileOutput, ProjectPathsConfig,
 };
 use foundry_config::{error::ExtractConfigError, figment::Figment, Chain, Config, NamedChain};
 use foundry_debugger::Debugger;
@@ -20,7 +21,15 @@ use foundry_evm::{
         render_trace_arena, CallTraceDecoder, CallTraceDecoderBuilder, TraceKind, Traces,
     },
 };
-use std::{fmt::Write, path::PathBuf, str::FromStr};
+use serde::{de::IgnoredAny, Deserialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    io::BufReader,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 use yansi::Paint;
 
 /// Given a `Project`'s output, removes the matching ABI, Bytecode and
@@ -96,15 +105,64 @@ pub fn get_cached_entry_by_name(
         return Ok(entry);
     }
 
+    eyre::bail!(artifact_not_found(name, &alternatives))
+}
+
+/// The artifact names of the files in the compiler cache, see [`get_cached_source_by_name`].
+#[derive(Deserialize)]
+struct CachedArtifactNames {
+    files: BTreeMap<PathBuf, CachedEntryNames>,
+}
+
+#[derive(Deserialize)]
+struct CachedEntryNames {
+    artifacts: BTreeMap<String, IgnoredAny>,
+}
+
+/// Finds the source file of the contract `name` in the compiler cache of the project.
+///
+/// Unlike [`get_cached_entry_by_name`], this doesn't deserialize the whole cache: the cache file
+/// is streamed and only the artifact names of its entries are kept.
+pub fn get_cached_source_by_name(paths: &ProjectPathsConfig, name: &str) -> Result<PathBuf> {
+    let file = fs::open(&paths.cache)?;
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(file));
+    let cache = CachedArtifactNames::deserialize(&mut deserializer)
+        .wrap_err_with(|| format!("failed to read {}", paths.cache.display()))?;
+
+    let mut cached_source = None;
+    let mut alternatives = Vec::new();
+    for (path, entry) in &cache.files {
+        for artifact_name in entry.artifacts.keys() {
+            if artifact_name == name {
+                if cached_source.is_some() {
+                    eyre::bail!(
+                        "contract with duplicate name `{}`. please pass the path instead",
+                        name
+                    )
+                }
+                cached_source = Some(paths.root.join(path));
+            } else {
+                alternatives.push(artifact_name);
+            }
+        }
+    }
+
+    match cached_source {
+        Some(path) => Ok(path),
+        None => eyre::bail!(artifact_not_found(name, &alternatives)),
+    }
+}
+
+fn artifact_not_found(name: &str, alternatives: &[&String]) -> String {
     let mut err = format!("could not find artifact: `{name}`");
-    if let Some(suggestion) = super::did_you_mean(name, &alternatives).pop() {
+    if let Some(suggestion) = super::did_you_mean(name, alternatives).pop() {
         err = format!(
             r#"{err}
 
         Did you mean `{suggestion}`?"#
         );
     }
-    eyre::bail!(err)
+    err
 }
 
 /// Returns error if constructor has arguments.
@@ -315,6 +373,158 @@ pub fn read_constructor_args_file(constructor_args_path: PathBuf) -> Result<Vec<
     Ok(args)
 }
 
+/// Reads the arguments of a constructor with `inputs` from the given file, and checks that they
+/// match the constructor.
+///
+/// Besides the formats of [read_constructor_args_file], a JSON file can hold an object of the
+/// arguments by name, e.g. `{ "name": "Token", "supply": "1000000" }`, with tuples as arrays or
+/// objects by component name.
+pub fn read_constructor_args(
+    constructor_args_path: &Path,
+    inputs: &[Param],
+) -> Result<Vec<String>> {
+    let is_json = constructor_args_path.extension() == Some(std::ffi::OsStr::new("json"));
+    let args = if is_json && constructor_args_path.exists() {
+        let value: Value = read_json_file(constructor_args_path)?;
+        match value {
+            Value::Object(fields) => named_constructor_args(&fields, inputs),
+            Value::Array(values) => positional_constructor_args(&values, inputs),
+            _ => eyre::bail!("must hold an array or an object of the arguments by name"),
+        }
+    } else {
+        let args = read_constructor_args_file(constructor_args_path.to_path_buf())?;
+        let values = args.into_iter().map(Value::String).collect::<Vec<_>>();
+        positional_constructor_args(&values, inputs)
+    };
+    args.wrap_err_with(|| {
+        format!("Invalid constructor args file \"{}\"", constructor_args_path.display())
+    })
+}
+
+fn named_constructor_args(
+    fields: &serde_json::Map<String, Value>,
+    inputs: &[Param],
+) -> Result<Vec<String>> {
+    if let Some(input) = inputs.iter().find(|input| input.name.is_empty()) {
+        eyre::bail!(
+            "the constructor has an unnamed argument of type `{}`, pass the arguments as an array",
+            input.ty
+        )
+    }
+    if let Some(unknown) =
+        fields.keys().find(|name| !inputs.iter().any(|input| &input.name == *name))
+    {
+        let names = inputs.iter().map(|input| input.name.as_str()).collect::<Vec<_>>();
+        eyre::bail!("unknown field `{unknown}`, the constructor takes: {}", names.join(", "))
+    }
+    inputs
+        .iter()
+        .map(|input| {
+            let value = fields.get(&input.name).ok_or_else(|| {
+                eyre::eyre!("missing field `{}` of type `{}`", input.name, input.ty)
+            })?;
+            constructor_arg(value, input, &input.name)
+        })
+        .collect()
+}
+
+fn positional_constructor_args(values: &[Value], inputs: &[Param]) -> Result<Vec<String>> {
+    if values.len() != inputs.len() {
+        eyre::bail!("expected {} arguments, got {}", inputs.len(), values.len())
+    }
+    values
+        .iter()
+        .zip(inputs)
+        .enumerate()
+        .map(|(i, (value, input))| {
+            let field = if input.name.is_empty() { format!("#{i}") } else { input.name.clone() };
+            constructor_arg(value, input, &field)
+        })
+        .collect()
+}
+
+/// Returns the argument for `input` as a string, and checks that it has the type of the input.
+fn constructor_arg(value: &Value, input: &Param, field: &str) -> Result<String> {
+    let ty = input.resolve()?;
+    let arg = arg_literal(value, &ty, &input.components, false, field)?;
+    ty.coerce_str(&arg).map_err(|err| {
+        eyre::eyre!("field `{field}`: invalid value for type `{}`: {err}", ty.sol_type_name())
+    })?;
+    Ok(arg)
+}
+
+/// Converts a JSON value to its literal for `ty`, as parsed by [DynSolType::coerce_str].
+fn arg_literal(
+    value: &Value,
+    ty: &DynSolType,
+    components: &[Param],
+    nested: bool,
+    field: &str,
+) -> Result<String> {
+    let literal = match (ty, value) {
+        (DynSolType::Array(inner) | DynSolType::FixedArray(inner, _), Value::Array(items)) => {
+            if let DynSolType::FixedArray(_, len) = ty {
+                if items.len() != *len {
+                    eyre::bail!("field `{field}`: expected {len} items, got {}", items.len())
+                }
+            }
+            let items = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    arg_literal(item, inner, components, true, &format!("{field}[{i}]"))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            format!("[{}]", items.join(", "))
+        }
+        (DynSolType::Tuple(types), Value::Array(items)) => {
+            if items.len() != types.len() {
+                eyre::bail!(
+                    "field `{field}`: expected {} components, got {}",
+                    types.len(),
+                    items.len()
+                )
+            }
+            let items = items
+                .iter()
+                .zip(types)
+                .zip(components)
+                .map(|((item, ty), component)| {
+                    let field = format!("{field}.{}", component.name);
+                    arg_literal(item, ty, &component.components, true, &field)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            format!("({})", items.join(", "))
+        }
+        (DynSolType::Tuple(types), Value::Object(fields)) => {
+            if let Some(unknown) =
+                fields.keys().find(|name| !components.iter().any(|c| &c.name == *name))
+            {
+                eyre::bail!("unknown field `{field}.{unknown}`")
+            }
+            let items = types
+                .iter()
+                .zip(components)
+                .map(|(ty, component)| {
+                    let field = format!("{field}.{}", component.name);
+                    let item = fields.get(&component.name).ok_or_else(|| {
+                        eyre::eyre!("missing field `{field}` of type `{}`", component.ty)
+                    })?;
+                    arg_literal(item, ty, &component.components, true, &field)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            format!("({})", items.join(", "))
+        }
+        // Strings in arrays and tuples are quoted, so that they can hold commas.
+        (DynSolType::String, Value::String(s)) if nested => serde_json::to_string(s)?,
+        (_, Value::String(s)) => s.clone(),
+        (_, Value::Number(n)) => n.to_string(),
+        (_, Value::Bool(b)) => b.to_string(),
+        _ => eyre::bail!("field `{field}`: expected `{}`, got {value}", ty.sol_type_name()),
+    };
+    Ok(literal)
+}
+
 /// A slimmed down return from the executor used for returning minimal trace + gas metering info
 #[derive(Debug)]
 pub struct TraceResult {
@@ -442,3 +652,104 @@ pub async fn print_traces(result: &mut TraceResult, decoder: &CallTraceDecoder)
     println!("Gas used: {}", result.gas_used);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_find_cached_source_by_name() {
+        let root = tempfile::tempdir().unwrap();
+        let paths = ProjectPathsConfig::builder().build_with_root(root.path());
+        fs::create_dir_all(paths.cache.parent().unwrap()).unwrap();
+        fs::write(
+            &paths.cache,
+            r#"{
+                "_format": "ethers-rs-sol-cache-3",
+                "paths": { "artifacts": "out", "sources": "src" },
+                "files": {
+                    "src/Counter.sol": {
+                        "contentHash": "0x00",
+                        "imports": [],
+                        "artifacts": { "Counter": { "0.8.23": "Counter.sol/Counter.json" } }
+                    },
+                    "script/Deploy.s.sol": {
+                        "artifacts": { "Deploy": { "0.8.23": "Deploy.s.sol/Deploy.json" } }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let path = get_cached_source_by_name(&paths, "Deploy").unwrap();
+        assert_eq!(path, root.path().join("script/Deploy.s.sol"));
+
+        let err = get_cached_source_by_name(&paths, "Countr").unwrap_err().to_string();
+        assert!(err.contains("Did you mean `Counter`?"));
+    }
+
+    #[test]
+    fn reads_named_constructor_args() {
+        let inputs: Vec<Param> = serde_json::from_str(
+            r#"[
+                { "name": "name", "type": "string" },
+                { "name": "supply", "type": "uint256" },
+                { "name": "owners", "type": "address[]" },
+                {
+                    "name": "config",
+                    "type": "tuple",
+                    "components": [
+                        { "name": "label", "type": "string" },
+                        { "name": "enabled", "type": "bool" }
+                    ]
+                }
+            ]"#,
+        )
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Token.json");
+        let read = |args: &str| {
+            fs::write(&path, args).unwrap();
+            read_constructor_args(&path, &inputs).map_err(|err| format!("{err:#}"))
+        };
+
+        let args = read(
+            r#"{
+                "supply": 1000,
+                "name": "Token, Inc.",
+                "owners": ["0x0000000000000000000000000000000000000001"],
+                "config": { "label": "a, b", "enabled": true }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            [
+                "Token, Inc.",
+                "1000",
+                "[0x0000000000000000000000000000000000000001]",
+                r#"("a, b", true)"#
+            ]
+        );
+
+        let err = read(r#"{ "name": "Token", "owners": [], "config": ["a", true] }"#).unwrap_err();
+        assert!(err.contains("missing field `supply` of type `uint256`"), "{err}");
+        let err = read(
+            r#"{ "name": "Token", "supply": 1, "owners": [], "config": ["a", true], "x": 1 }"#,
+        )
+        .unwrap_err();
+        assert!(err.contains("unknown field `x`"), "{err}");
+        let err =
+            read(r#"{ "name": "Token", "supply": "lots", "owners": [], "config": ["a", true] }"#)
+                .unwrap_err();
+        assert!(err.contains("field `supply`: invalid value for type `uint256`"), "{err}");
+        let err =
+            read(r#"{ "name": "Token", "supply": 1, "owners": [], "config": { "label": "a" } }"#)
+                .unwrap_err();
+        assert!(err.contains("missing field `config.enabled` of type `bool`"), "{err}");
+
+        // Positional arguments are checked too.
+        let err = read(r#"["Token", "1"]"#).unwrap_err();
+        assert!(err.contains("expected 4 arguments, got 2"), "{err}");
+    }
+}
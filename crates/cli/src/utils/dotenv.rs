@@ -0,0 +1,149 @@
+use eyre::{Result, WrapErr};
+use foundry_config::Chain;
+use std::{
+    collections::BTreeMap,
+    env,
+    path::{Path, PathBuf},
+};
+use yansi::Paint;
+
+/// Substrings of variable names whose values are redacted when printed.
+const SECRET_MARKERS: &[&str] =
+    &["KEY", "SECRET", "PASSWORD", "PASSPHRASE", "MNEMONIC", "TOKEN", "PRIVATE", "AUTH"];
+
+/// A variable read from one of the layered `.env` files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DotenvVar {
+    pub value: String,
+    /// The file the value was read from.
+    pub source: PathBuf,
+}
+
+/// The merged variables of a project's layered `.env` files.
+///
+/// Files are merged in ascending precedence order:
+/// 1. `.env`
+/// 2. `.env.<chain>`, by chain id and then by chain name, e.g. `.env.1` and `.env.mainnet`
+/// 3. `.env.local`
+///
+/// Variables set in the environment before Foundry started always take precedence.
+#[derive(Clone, Debug, Default)]
+pub struct DotenvLayers {
+    pub vars: BTreeMap<String, DotenvVar>,
+}
+
+impl DotenvLayers {
+    /// Returns the `.env` files for the given chain, in ascending precedence order.
+    pub fn files(root: &Path, chain: Option<Chain>) -> Vec<PathBuf> {
+        let mut files = vec![root.join(".env")];
+        if let Some(chain) = chain {
+            files.push(root.join(format!(".env.{}", chain.id())));
+            if chain.named().is_some() {
+                files.push(root.join(format!(".env.{chain}")));
+            }
+        }
+        files.push(root.join(".env.local"));
+        files
+    }
+
+    /// Reads and merges the layered `.env` files in `root`. Missing files are skipped.
+    pub fn load(root: &Path, chain: Option<Chain>) -> Result<Self> {
+        let mut vars = BTreeMap::new();
+        for file in Self::files(root, chain).into_iter().filter(|file| file.is_file()) {
+            for (key, value) in read_dotenv(&file)? {
+                vars.insert(key, DotenvVar { value, source: file.clone() });
+            }
+        }
+        Ok(Self { vars })
+    }
+
+    /// Sets the merged variables in the process environment, so they are visible to cheatcodes
+    /// like `vm.envOr`.
+    ///
+    /// The base `.env` is already loaded on startup by [`load_dotenv`](super::load_dotenv), which
+    /// never overrides existing variables. Hence a variable whose current value differs from the
+    /// one in the base `.env` file was set outside of Foundry and is left untouched.
+    ///
+    /// Returns `true` if any variable was changed.
+    pub fn apply(&self, root: &Path) -> Result<bool> {
+        let base = root.join(".env");
+        let base = if base.is_file() { read_dotenv(&base)? } else { Default::default() };
+
+        let mut changed = false;
+        for (key, var) in &self.vars {
+            if let Ok(current) = env::var(key) {
+                if current == var.value || base.get(key) != Some(&current) {
+                    continue
+                }
+            }
+            env::set_var(key, &var.value);
+            changed = true;
+        }
+        Ok(changed)
+    }
+
+    /// Prints the merged variables and their source, with secrets redacted.
+    pub fn print(&self) {
+        println!("{}", Paint::yellow("Environment loaded from .env files:").bold());
+        for (key, var) in &self.vars {
+            let value = if is_secret_env_var(key) { redact(&var.value) } else { var.value.clone() };
+            let overridden = env::var(key).map_or(false, |current| current != var.value);
+            let note = if overridden { " (overridden by environment)" } else { "" };
+            let source = Paint::new(format!("[{}]", var.source.display())).dimmed();
+            println!("  {key}={value} {source}{note}");
+        }
+    }
+}
+
+/// Returns `true` if the variable name suggests that its value is a secret.
+pub fn is_secret_env_var(key: &str) -> bool {
+    let key = key.to_ascii_uppercase();
+    SECRET_MARKERS.iter().any(|marker| key.contains(marker)) ||
+        key.ends_with("_RPC_URL") ||
+        key.ends_with("_PK")
+}
+
+/// Redacts a secret value.
+pub fn redact(value: &str) -> String {
+    "*".repeat(value.len().min(8))
+}
+
+fn read_dotenv(path: &Path) -> Result<BTreeMap<String, String>> {
+    dotenvy::from_path_iter(path)
+        .and_then(|iter| iter.collect::<Result<_, _>>())
+        .wrap_err_with(|| format!("failed to read {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_config::NamedChain;
+
+    #[test]
+    fn can_layer_dotenv_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::write(root.join(".env"), "A=base\nB=base\nC=base").unwrap();
+        std::fs::write(root.join(".env.mainnet"), "B=mainnet\nC=mainnet").unwrap();
+        std::fs::write(root.join(".env.local"), "C=local").unwrap();
+
+        let layers = DotenvLayers::load(root, Some(NamedChain::Mainnet.into())).unwrap();
+        let value = |key: &str| layers.vars[key].value.as_str();
+        assert_eq!(value("A"), "base");
+        assert_eq!(value("B"), "mainnet");
+        assert_eq!(value("C"), "local");
+        assert_eq!(layers.vars["B"].source, root.join(".env.mainnet"));
+
+        let layers = DotenvLayers::load(root, None).unwrap();
+        assert_eq!(layers.vars["B"].value, "base");
+    }
+
+    #[test]
+    fn can_detect_secrets() {
+        assert!(is_secret_env_var("PRIVATE_KEY"));
+        assert!(is_secret_env_var("etherscan_api_key"));
+        assert!(is_secret_env_var("MAINNET_RPC_URL"));
+        assert!(!is_secret_env_var("DEPLOYER"));
+        assert_eq!(redact("0x1234567890"), "********");
+    }
+}
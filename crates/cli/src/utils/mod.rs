@@ -4,7 +4,7 @@ use ethers_core::types::TransactionReceipt;
 use ethers_providers::Middleware;
 use eyre::{ContextCompat, Result};
 use foundry_common::types::ToAlloy;
-use foundry_config::{Chain, Config};
+use foundry_config::{Chain, Config, ExplorerLinks};
 use std::{
     ffi::OsStr,
     future::Future,
@@ -20,6 +20,12 @@ use yansi::Paint;
 mod cmd;
 pub use cmd::*;
 
+mod dotenv;
+pub use dotenv::*;
+
+mod replay;
+pub use replay::*;
+
 mod suggestions;
 pub use suggestions::*;
 
@@ -221,12 +227,15 @@ pub fn enable_paint() {
     }
 }
 
-/// Prints parts of the receipt to stdout
-pub fn print_receipt(chain: Chain, receipt: &TransactionReceipt) {
+/// Prints parts of the receipt to stdout, with the links to the explorer of the chain if any
+pub fn print_receipt(chain: Chain, receipt: &TransactionReceipt, links: Option<&ExplorerLinks>) {
     let gas_used = receipt.gas_used.unwrap_or_default();
     let gas_price = receipt.effective_gas_price.unwrap_or_default();
+    let tx_link = links
+        .map(|links| format!(" ({})", links.tx_url(receipt.transaction_hash.to_alloy())))
+        .unwrap_or_default();
     foundry_common::shell::println(format!(
-        "\n##### {chain}\n{status}Hash: {tx_hash:?}{caddr}\nBlock: {bn}\n{gas}\n",
+        "\n##### {chain}\n{status}Hash: {tx_hash:?}{tx_link}{caddr}\nBlock: {bn}\n{gas}\n",
         status = if receipt.status.map_or(true, |s| s.is_zero()) {
             "❌  [Failed]"
         } else {
@@ -234,7 +243,15 @@ pub fn print_receipt(chain: Chain, receipt: &TransactionReceipt) {
         },
         tx_hash = receipt.transaction_hash,
         caddr = if let Some(addr) = &receipt.contract_address {
-            format!("\nContract Address: {}", addr.to_alloy().to_checksum(None))
+            let addr = addr.to_alloy();
+            match links {
+                Some(links) => format!(
+                    "\nContract Address: {} ({})",
+                    addr.to_checksum(None),
+                    links.address_url(addr)
+                ),
+                None => format!("\nContract Address: {}", addr.to_checksum(None)),
+            }
         } else {
             String::new()
         },
@@ -491,6 +508,28 @@ impl<'a> Git<'a> {
         self.cmd().args(["status", "--porcelain"]).exec().map(|out| out.stdout.is_empty())
     }
 
+    /// Returns the paths of the files with uncommitted changes, including untracked files.
+    pub fn changed_files(self) -> Result<Vec<String>> {
+        self.cmd().args(["status", "--porcelain"]).get_stdout_lossy().map(|stdout| {
+            stdout
+                .lines()
+                .map(|line| {
+                    let line = line.trim_start();
+                    line.split_once(' ').map_or(line, |(_, path)| path.trim_start()).to_string()
+                })
+                .collect()
+        })
+    }
+
+    /// Returns true if the revision is contained in a remote-tracking branch.
+    pub fn is_pushed(self, revision: &str) -> Result<bool> {
+        self.cmd()
+            .args(["branch", "--remotes", "--contains"])
+            .arg(revision)
+            .get_stdout_lossy()
+            .map(|stdout| !stdout.is_empty())
+    }
+
     pub fn has_branch(self, branch: impl AsRef<OsStr>) -> Result<bool> {
         self.cmd()
             .args(["branch", "--list", "--no-color"])
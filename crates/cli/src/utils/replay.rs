@@ -0,0 +1,168 @@
+use super::TraceResult;
+use alloy_primitives::{B256, U256};
+use alloy_providers::tmp::TempProvider;
+use alloy_rpc_types::{BlockTransactions, Transaction};
+use eyre::{Result, WrapErr};
+use foundry_common::{
+    is_known_system_sender, provider::alloy::RetryProvider, SYSTEM_TRANSACTION_TYPE,
+};
+use foundry_compilers::EvmVersion;
+use foundry_config::{Chain, Config};
+use foundry_evm::{
+    executors::{EvmError, TracingExecutor},
+    opts::EvmOpts,
+    revm::primitives::EnvWithHandlerCfg,
+    utils::configure_tx_env,
+};
+
+/// How [replay_transaction] executes a transaction.
+#[derive(Clone, Debug, Default)]
+pub struct ReplayOpts {
+    /// Executes the transaction only with the state from the previous block.
+    ///
+    /// May result in different results than the live execution!
+    pub quick: bool,
+    /// Records the steps of the execution for the debugger.
+    pub debug: bool,
+    /// Prints out opcode traces.
+    pub trace_printer: bool,
+    /// The EVM version to use.
+    pub evm_version: Option<EvmVersion>,
+}
+
+/// Executes a mined transaction again on a fork of its parent block.
+///
+/// Unless `quick` is set, the transactions before it in its block are executed first, so that it
+/// runs against the same state as it did on chain. Returns the result of the transaction and the
+/// chain of the fork.
+pub async fn replay_transaction(
+    provider: &RetryProvider,
+    tx_hash: B256,
+    config: &mut Config,
+    evm_opts: EvmOpts,
+    opts: ReplayOpts,
+) -> Result<(TraceResult, Option<Chain>)> {
+    let tx = provider
+        .get_transaction_by_hash(tx_hash)
+        .await
+        .wrap_err_with(|| format!("tx not found: {:?}", tx_hash))?;
+
+    if is_system_transaction(&tx) {
+        return Err(eyre::eyre!(
+            "{:?} is a system transaction.\nReplaying system transactions is currently not supported.",
+            tx.hash
+        ));
+    }
+
+    let tx_block_number = tx
+        .block_number
+        .ok_or_else(|| eyre::eyre!("tx may still be pending: {:?}", tx_hash))?
+        .to::<u64>();
+
+    // we need to fork off the parent block
+    config.fork_block_number = Some(tx_block_number.checked_sub(1).ok_or_else(|| {
+        eyre::eyre!("{:?} is in the genesis block, which has no parent to fork off", tx_hash)
+    })?);
+
+    let (mut env, fork, chain) = TracingExecutor::get_fork_material(config, evm_opts).await?;
+
+    let mut executor = TracingExecutor::new(env.clone(), fork, opts.evm_version, opts.debug);
+
+    env.block.number = U256::from(tx_block_number);
+
+    let block = provider.get_block(tx_block_number.into(), true).await?;
+    if let Some(ref block) = block {
+        env.block.timestamp = block.header.timestamp;
+        env.block.coinbase = block.header.miner;
+        env.block.difficulty = block.header.difficulty;
+        env.block.prevrandao = Some(block.header.mix_hash.unwrap_or_default());
+        env.block.basefee = block.header.base_fee_per_gas.unwrap_or_default();
+        env.block.gas_limit = block.header.gas_limit;
+    }
+
+    let mut env = EnvWithHandlerCfg::new_with_spec_id(Box::new(env.clone()), executor.spec_id());
+
+    // Set the state to the moment right before the transaction
+    if !opts.quick {
+        println!("Executing previous transactions from the block.");
+
+        if let Some(block) = block {
+            let pb = crate::init_progress!(block.transactions, "tx");
+            pb.set_position(0);
+
+            let BlockTransactions::Full(txs) = block.transactions else {
+                return Err(eyre::eyre!("Could not get block txs"))
+            };
+
+            for (index, tx) in txs.into_iter().enumerate() {
+                // System transactions such as on L2s don't contain any pricing info so
+                // we skip them otherwise this would cause
+                // reverts
+                if is_system_transaction(&tx) {
+                    crate::update_progress!(pb, index);
+                    continue;
+                }
+                if tx.hash == tx_hash {
+                    break;
+                }
+
+                configure_tx_env(&mut env, &tx);
+
+                if let Some(to) = tx.to {
+                    trace!(tx=?tx.hash,?to, "executing previous call transaction");
+                    executor.commit_tx_with_env(env.clone()).wrap_err_with(|| {
+                        format!(
+                            "Failed to execute transaction: {:?} in block {}",
+                            tx.hash, env.block.number
+                        )
+                    })?;
+                } else {
+                    trace!(tx=?tx.hash, "executing previous create transaction");
+                    if let Err(error) = executor.deploy_with_env(env.clone(), None) {
+                        match error {
+                            // Reverted transactions should be skipped
+                            EvmError::Execution(_) => (),
+                            error => {
+                                return Err(error).wrap_err_with(|| {
+                                    format!(
+                                        "Failed to deploy transaction: {:?} in block {}",
+                                        tx.hash, env.block.number
+                                    )
+                                })
+                            }
+                        }
+                    }
+                }
+
+                crate::update_progress!(pb, index);
+            }
+        }
+    }
+
+    // Execute our transaction
+    let result = {
+        executor.set_trace_printer(opts.trace_printer);
+
+        configure_tx_env(&mut env, &tx);
+
+        if let Some(to) = tx.to {
+            trace!(tx=?tx.hash, to=?to, "executing call transaction");
+            TraceResult::from(executor.commit_tx_with_env(env)?)
+        } else {
+            trace!(tx=?tx.hash, "executing create transaction");
+            match executor.deploy_with_env(env, None) {
+                Ok(res) => TraceResult::from(res),
+                Err(err) => TraceResult::try_from(err)?,
+            }
+        }
+    };
+
+    Ok((result, chain))
+}
+
+/// Whether the transaction is a system transaction, e.g. an L1 deposit on an L2, which can't be
+/// executed as it doesn't contain any pricing info.
+pub fn is_system_transaction(tx: &Transaction) -> bool {
+    is_known_system_sender(tx.from) ||
+        tx.transaction_type.map(|ty| ty.to::<u64>()) == Some(SYSTEM_TRANSACTION_TYPE)
+}
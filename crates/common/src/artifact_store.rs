@@ -0,0 +1,331 @@
+//! A content-addressed store of compiler output, shared across branches and worktrees.
+//!
+//! The compiler cache of a project only knows about the last build, so switching git branches
+//! back and forth recompiles everything that differs between them every time. The store keeps a
+//! snapshot of the artifacts and the compiler cache of every build, keyed by the hash of all the
+//! sources and the compiler settings, and restores it when the same sources are built again.
+//!
+//! The least recently used snapshots are removed once the store grows above [DEFAULT_MAX_SIZE].
+
+use crate::fs;
+use alloy_primitives::{keccak256, B256};
+use eyre::{Result, WrapErr};
+use foundry_compilers::{
+    artifacts::Source, cache::SolFilesCache, remappings::RelativeRemapping, Graph, Project,
+};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The size the store is pruned to after a snapshot is saved, 4 GiB.
+pub const DEFAULT_MAX_SIZE: u64 = 4 << 30;
+
+/// The name of the file in the cache directory of a project which holds the key of the snapshot
+/// its artifacts currently match.
+const KEY_FILE_NAME: &str = "artifact-store-key";
+
+/// The name of the copy of the artifacts directory in a snapshot.
+const ARTIFACTS_DIR_NAME: &str = "out";
+
+/// The name of the copy of the compiler cache file in a snapshot.
+const CACHE_FILE_NAME: &str = "solidity-files-cache.json";
+
+/// The name of the file in a snapshot which holds when it was last restored or saved, in seconds
+/// since the unix epoch.
+const LAST_USED_FILE_NAME: &str = "last-used";
+
+/// A snapshot of the compiler output of a project in the store.
+#[derive(Clone, Debug)]
+pub struct ArtifactStore {
+    /// The directory of the snapshot.
+    entry: PathBuf,
+    /// The hash of the sources and the compiler settings.
+    key: B256,
+}
+
+impl ArtifactStore {
+    /// Returns the snapshot of the current sources of `project` in the store at `dir`.
+    pub fn open(dir: &Path, project: &Project) -> Result<Self> {
+        let key = Self::key(project)?;
+        Ok(Self { entry: dir.join(hex::encode(key)), key })
+    }
+
+    /// Hashes the sources of `project`, including the libraries they import, together with the
+    /// compiler settings.
+    ///
+    /// The content hashes of the compiler cache are reused when it's up to date, otherwise the
+    /// sources are resolved and hashed. Paths are hashed relative to the project root, so that
+    /// worktrees of the same repository share their snapshots.
+    pub fn key(project: &Project) -> Result<B256> {
+        let root = project.root();
+        let hashes = match Self::cached_content_hashes(project) {
+            Some(hashes) => hashes,
+            None => {
+                let graph = Graph::resolve(&project.paths)?;
+                let mut hashes = BTreeMap::new();
+                for file in graph.files().keys() {
+                    hashes.insert(file.clone(), Source::read(file)?.content_hash());
+                }
+                hashes
+            }
+        };
+        let sources = hashes
+            .into_iter()
+            .map(|(file, hash)| (file.strip_prefix(root).unwrap_or(&file).to_path_buf(), hash))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut input = Vec::new();
+        for (path, hash) in sources {
+            input.extend_from_slice(path.to_string_lossy().as_bytes());
+            input.extend_from_slice(hash.as_bytes());
+        }
+        input.extend(serde_json::to_vec(&project.solc_config.settings)?);
+        for remapping in &project.paths.remappings {
+            let remapping = RelativeRemapping::new(remapping.clone(), root);
+            input.extend_from_slice(remapping.to_string().as_bytes());
+        }
+        if !project.auto_detect {
+            input.extend_from_slice(project.solc.solc.to_string_lossy().as_bytes());
+        }
+        input.push(project.build_info as u8);
+        Ok(keccak256(input))
+    }
+
+    /// Returns the content hashes of the sources recorded in the compiler cache of `project`, if
+    /// it lists all the input files and none of its files was modified since it was written.
+    ///
+    /// A key computed from a stale cache only costs a useless restore, as the compiler checks the
+    /// content hashes of the restored cache again.
+    fn cached_content_hashes(project: &Project) -> Option<BTreeMap<PathBuf, String>> {
+        let cache = SolFilesCache::read_joined(&project.paths).ok()?;
+        if !project.paths.input_files().iter().all(|file| cache.files.contains_key(file)) {
+            return None
+        }
+        cache
+            .files
+            .iter()
+            .map(|(file, entry)| {
+                let modified = std::fs::metadata(file).ok()?.modified().ok()?;
+                let modified = modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as u64;
+                (modified == entry.last_modification_date)
+                    .then(|| (file.clone(), entry.content_hash.clone()))
+            })
+            .collect()
+    }
+
+    /// Whether the store has a snapshot of the current sources.
+    pub fn exists(&self) -> bool {
+        self.entry.join(CACHE_FILE_NAME).exists()
+    }
+
+    /// Replaces the artifacts and the compiler cache of `project` with the snapshot, unless they
+    /// already match it.
+    ///
+    /// Returns whether the snapshot was restored. The compiler still checks the restored cache, so
+    /// anything missing from the snapshot is compiled as usual.
+    pub fn restore(&self, project: &Project) -> Result<bool> {
+        if !self.exists() {
+            return Ok(false)
+        }
+        if self.is_current(project) {
+            self.touch()?;
+            return Ok(false)
+        }
+
+        let artifacts = &project.paths.artifacts;
+        if artifacts.exists() {
+            fs::remove_dir_all(artifacts)?;
+        }
+        copy_dir(&self.entry.join(ARTIFACTS_DIR_NAME), artifacts)?;
+        if let Some(parent) = project.paths.cache.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(self.entry.join(CACHE_FILE_NAME), &project.paths.cache)?;
+        self.mark_current(project)?;
+        self.touch()?;
+
+        trace!(key=%self.key, "restored compiler output from the artifact store");
+        Ok(true)
+    }
+
+    /// Saves the artifacts and the compiler cache of `project` as the snapshot of the current
+    /// sources, replacing any previous one.
+    pub fn save(&self, project: &Project) -> Result<()> {
+        if !project.paths.cache.exists() {
+            return Ok(())
+        }
+
+        // Write the snapshot next to its final location first, so that concurrent builds in other
+        // worktrees never restore a partial snapshot.
+        let tmp = self.entry.with_extension(format!("tmp-{}", std::process::id()));
+        if tmp.exists() {
+            fs::remove_dir_all(&tmp)?;
+        }
+        if project.paths.artifacts.exists() {
+            copy_dir(&project.paths.artifacts, &tmp.join(ARTIFACTS_DIR_NAME))?;
+        } else {
+            fs::create_dir_all(tmp.join(ARTIFACTS_DIR_NAME))?;
+        }
+        fs::copy(&project.paths.cache, tmp.join(CACHE_FILE_NAME))?;
+
+        if self.entry.exists() {
+            fs::remove_dir_all(&self.entry)?;
+        }
+        std::fs::rename(&tmp, &self.entry).wrap_err_with(|| {
+            format!("failed to move {} to {}", tmp.display(), self.entry.display())
+        })?;
+        self.mark_current(project)?;
+        self.touch()?;
+
+        trace!(key=%self.key, "saved compiler output to the artifact store");
+        Ok(())
+    }
+
+    fn key_file(project: &Project) -> PathBuf {
+        project.paths.cache.with_file_name(KEY_FILE_NAME)
+    }
+
+    /// Whether the artifacts of `project` were last restored from or saved to this snapshot.
+    fn is_current(&self, project: &Project) -> bool {
+        fs::read_to_string(Self::key_file(project)).is_ok_and(|key| key == hex::encode(self.key))
+    }
+
+    fn mark_current(&self, project: &Project) -> Result<()> {
+        fs::write(Self::key_file(project), hex::encode(self.key))?;
+        Ok(())
+    }
+
+    /// Records that the snapshot was used now, see [Self::prune].
+    fn touch(&self) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        fs::write(self.entry.join(LAST_USED_FILE_NAME), now.to_string())?;
+        Ok(())
+    }
+
+    /// Removes the least recently used snapshots of the store at `dir` until it takes at most
+    /// `max_size` bytes.
+    ///
+    /// Returns the number of removed snapshots and the number of bytes freed.
+    pub fn prune(dir: &Path, max_size: u64) -> Result<(usize, u64)> {
+        if !dir.exists() {
+            return Ok((0, 0))
+        }
+
+        let mut snapshots = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            // Skip the snapshots being written, see `save`.
+            if !path.is_dir() || path.extension().is_some() {
+                continue
+            }
+            let last_used = fs::read_to_string(path.join(LAST_USED_FILE_NAME))
+                .ok()
+                .and_then(|last_used| last_used.trim().parse::<u64>().ok())
+                .unwrap_or_default();
+            snapshots.push((last_used, dir_size(&path), path));
+        }
+        snapshots.sort();
+
+        let mut size = snapshots.iter().map(|(_, size, _)| size).sum::<u64>();
+        let (mut removed, mut freed) = (0, 0);
+        for (_, snapshot_size, path) in snapshots {
+            if size <= max_size {
+                break
+            }
+            fs::remove_dir_all(&path)?;
+            size -= snapshot_size;
+            freed += snapshot_size;
+            removed += 1;
+        }
+        if removed > 0 {
+            trace!(removed, freed, "pruned the artifact store");
+        }
+        Ok((removed, freed))
+    }
+}
+
+/// Returns the total size of the files in `dir`, recursively.
+fn dir_size(dir: &Path) -> u64 {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok()?.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Recursively copies the directory `from` to `to`.
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    for entry in walkdir::WalkDir::new(from) {
+        let entry = entry?;
+        let target = to.join(entry.path().strip_prefix(from)?);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_config::Config;
+
+    fn project(root: &Path) -> Project {
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/Counter.sol"), "contract Counter {}").unwrap();
+        Config::with_root(root).project().unwrap()
+    }
+
+    #[test]
+    fn can_share_snapshots_across_worktrees() {
+        let store = tempfile::tempdir().unwrap();
+        let (main_dir, worktree_dir) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let (main, worktree) = (project(main_dir.path()), project(worktree_dir.path()));
+
+        let key = ArtifactStore::key(&main).unwrap();
+        assert_eq!(key, ArtifactStore::key(&worktree).unwrap());
+
+        // pretend `main` was compiled
+        fs::create_dir_all(&main.paths.artifacts).unwrap();
+        fs::write(main.paths.artifacts.join("Counter.json"), "{}").unwrap();
+        fs::create_dir_all(main.paths.cache.parent().unwrap()).unwrap();
+        fs::write(&main.paths.cache, "{}").unwrap();
+        ArtifactStore::open(store.path(), &main).unwrap().save(&main).unwrap();
+
+        let snapshot = ArtifactStore::open(store.path(), &worktree).unwrap();
+        assert!(snapshot.exists());
+        assert!(snapshot.restore(&worktree).unwrap());
+        assert!(worktree.paths.artifacts.join("Counter.json").exists());
+        assert!(worktree.paths.cache.exists());
+        // already restored
+        assert!(!snapshot.restore(&worktree).unwrap());
+
+        fs::write(worktree.root().join("src/Counter.sol"), "contract Counter { uint x; }").unwrap();
+        assert_ne!(key, ArtifactStore::key(&worktree).unwrap());
+        assert!(!ArtifactStore::open(store.path(), &worktree).unwrap().exists());
+    }
+
+    #[test]
+    fn prunes_least_recently_used_snapshots() {
+        let store = tempfile::tempdir().unwrap();
+        for (key, last_used) in [("a", 3), ("b", 1), ("c", 2)] {
+            let snapshot = store.path().join(key);
+            fs::create_dir_all(snapshot.join(ARTIFACTS_DIR_NAME)).unwrap();
+            fs::write(snapshot.join(CACHE_FILE_NAME), [0; 100]).unwrap();
+            fs::write(snapshot.join(LAST_USED_FILE_NAME), last_used.to_string()).unwrap();
+        }
+        // a snapshot being saved
+        fs::create_dir_all(store.path().join("d.tmp-1")).unwrap();
+
+        assert_eq!(ArtifactStore::prune(store.path(), 1_000).unwrap(), (0, 0));
+        assert_eq!(ArtifactStore::prune(store.path(), 250).unwrap(), (1, 101));
+        assert!(!store.path().join("b").exists());
+        assert_eq!(ArtifactStore::prune(store.path(), 0).unwrap(), (2, 202));
+        assert!(store.path().join("d.tmp-1").exists());
+    }
+}
@@ -1,16 +1,24 @@
 //! Support for compiling [foundry_compilers::Project]
 
-use crate::{compact_to_contract, glob::GlobMatcher, term::SpinnerReporter, TestFunctionExt};
+use crate::{
+    artifact_store::{self, ArtifactStore},
+    compact_to_contract,
+    glob::GlobMatcher,
+    term::SpinnerReporter,
+    TestFunctionExt,
+};
 use comfy_table::{presets::ASCII_MARKDOWN, Attribute, Cell, Color, Table};
 use eyre::{Context, Result};
 use foundry_block_explorers::contract::Metadata;
 use foundry_compilers::{
     artifacts::{BytecodeObject, CompactContractBytecode, ContractBytecodeSome},
+    contracts::ArtifactContracts,
     remappings::Remapping,
     report::{BasicStdoutReporter, NoReporter, Report},
     Artifact, ArtifactId, FileFilter, Graph, Project, ProjectCompileOutput, ProjectPathsConfig,
     Solc, SolcConfig,
 };
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, HashMap},
     convert::Infallible,
@@ -48,6 +56,9 @@ pub struct ProjectCompiler {
 
     /// Extra files to include, that are not necessarily in the project's source dir.
     files: Vec<PathBuf>,
+
+    /// The directory of the shared artifact store, if enabled.
+    artifact_store: Option<PathBuf>,
 }
 
 impl Default for ProjectCompiler {
@@ -69,6 +80,7 @@ impl ProjectCompiler {
             bail: None,
             filter: None,
             files: Vec::new(),
+            artifact_store: None,
         }
     }
 
@@ -131,6 +143,13 @@ impl ProjectCompiler {
         self
     }
 
+    /// Sets the directory of the shared artifact store, see [`ArtifactStore`].
+    #[inline]
+    pub fn artifact_store(mut self, dir: Option<PathBuf>) -> Self {
+        self.artifact_store = dir;
+        self
+    }
+
     /// Compiles the project.
     pub fn compile(mut self, project: &Project) -> Result<ProjectCompileOutput> {
         // TODO: Avoid process::exit
@@ -143,7 +162,19 @@ impl ProjectCompiler {
         // Taking is fine since we don't need these in `compile_with`.
         let filter = std::mem::take(&mut self.filter);
         let files = std::mem::take(&mut self.files);
-        self.compile_with(|| {
+
+        // Failing to use the store only costs a full compilation, so it's never an error.
+        let store = std::mem::take(&mut self.artifact_store).and_then(|dir| {
+            let store = ArtifactStore::open(&dir, project)
+                .map_err(|err| warn!(%err, "failed to open the artifact store"))
+                .ok()?;
+            if let Err(err) = store.restore(project) {
+                warn!(%err, "failed to restore compiler output from the artifact store");
+            }
+            Some((dir, store))
+        });
+
+        let output = self.compile_with(|| {
             if !files.is_empty() {
                 project.compile_files(files)
             } else if let Some(filter) = filter {
@@ -152,7 +183,20 @@ impl ProjectCompiler {
                 project.compile()
             }
             .map_err(Into::into)
-        })
+        })?;
+
+        if let Some((dir, store)) = store {
+            if !output.has_compiler_errors() && (!output.is_unchanged() || !store.exists()) {
+                if let Err(err) = store.save(project) {
+                    warn!(%err, "failed to save compiler output to the artifact store");
+                } else if let Err(err) =
+                    ArtifactStore::prune(&dir, artifact_store::DEFAULT_MAX_SIZE)
+                {
+                    warn!(%err, "failed to prune the artifact store");
+                }
+            }
+        }
+        Ok(output)
     }
 
     /// Compiles the project with the given closure
@@ -272,8 +316,28 @@ impl ProjectCompiler {
     }
 }
 
+/// Reads the source files of the artifacts, each at most once, as a source file usually has
+/// several artifacts.
+#[derive(Debug, Default)]
+struct SourceFiles(HashMap<PathBuf, String>);
+
+impl SourceFiles {
+    /// Returns the source of the artifact `id`.
+    fn read(&mut self, root: &Path, id: &ArtifactId) -> Result<String> {
+        let path = root.join(&id.source);
+        if let Some(content) = self.0.get(&path) {
+            return Ok(content.clone())
+        }
+        let content = std::fs::read_to_string(&path).wrap_err_with(|| {
+            format!("failed to read artifact source file for `{}`", id.identifier())
+        })?;
+        self.0.insert(path, content.clone());
+        Ok(content)
+    }
+}
+
 /// Contract source code and bytecode.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ContractSources {
     /// Map over artifacts' contract names -> vector of file IDs
     pub ids_by_name: HashMap<String, Vec<u32>>,
@@ -287,13 +351,11 @@ impl ContractSources {
         output: &ProjectCompileOutput,
         root: &Path,
     ) -> Result<ContractSources> {
+        let mut files = SourceFiles::default();
         let mut sources = ContractSources::default();
         for (id, artifact) in output.artifact_ids() {
             if let Some(file_id) = artifact.id {
-                let abs_path = root.join(&id.source);
-                let source_code = std::fs::read_to_string(abs_path).wrap_err_with(|| {
-                    format!("failed to read artifact source file for `{}`", id.identifier())
-                })?;
+                let source_code = files.read(root, &id)?;
                 let compact = CompactContractBytecode {
                     abi: artifact.abi.clone(),
                     bytecode: artifact.bytecode.clone(),
@@ -308,6 +370,45 @@ impl ContractSources {
         Ok(sources)
     }
 
+    /// Collects the contract sources from the project compile outputs, and returns them together
+    /// with the artifacts for which `keep` returns true.
+    ///
+    /// Unlike [Self::from_project_output], the outputs are consumed as a stream: the raw compiler
+    /// output is dropped up front, and each artifact as soon as it's converted, so that the full
+    /// output and the converted artifacts are never held in memory at the same time.
+    pub fn from_project_output_with_artifacts(
+        outputs: impl IntoIterator<Item = ProjectCompileOutput>,
+        root: &Path,
+        mut keep: impl FnMut(&ArtifactId) -> bool,
+    ) -> Result<(ContractSources, ArtifactContracts)> {
+        let mut files = SourceFiles::default();
+        let mut sources = ContractSources::default();
+        let mut contracts = Vec::new();
+        for (id, artifact) in outputs.into_iter().flat_map(|output| output.into_artifacts()) {
+            let file_id = artifact.id;
+            let contract = artifact.into_contract_bytecode();
+            let kept = keep(&id);
+            let Some(file_id) = file_id else {
+                warn!(id = id.identifier(), "source not found");
+                if kept {
+                    contracts.push((id, contract));
+                }
+                continue
+            };
+
+            let source_code = files.read(root, &id)?;
+            // The kept artifacts are needed by both the sources and the contracts, the others are
+            // moved into the sources.
+            if kept {
+                sources.insert(&id, file_id, source_code, compact_to_contract(contract.clone())?);
+                contracts.push((id, contract));
+            } else {
+                sources.insert(&id, file_id, source_code, compact_to_contract(contract)?);
+            }
+        }
+        Ok((sources, contracts.into_iter().collect()))
+    }
+
     /// Inserts a contract into the sources.
     pub fn insert(
         &mut self,
@@ -435,6 +536,8 @@ pub struct ContractInfo {
 ///
 /// If `verify` and it's a standalone script, throw error. Only allowed for projects.
 ///
+/// If `artifact_store` is set, the compiler output is shared through the store in that directory.
+///
 /// **Note:** this expects the `target_path` to be absolute
 pub fn compile_target_with_filter(
     target_path: &Path,
@@ -442,23 +545,31 @@ pub fn compile_target_with_filter(
     quiet: bool,
     verify: bool,
     skip: Vec<SkipBuildFilter>,
+    artifact_store: Option<PathBuf>,
 ) -> Result<ProjectCompileOutput> {
     let graph = Graph::resolve(&project.paths)?;
 
     // Checking if it's a standalone script, or part of a project.
-    let mut compiler = ProjectCompiler::new().quiet(quiet);
+    let mut compiler = ProjectCompiler::new().quiet(quiet).artifact_store(artifact_store);
     if !skip.is_empty() {
         compiler = compiler.filter(Box::new(SkipBuildFilters::new(skip)?));
     }
     if !graph.files().contains_key(target_path) {
-        if verify {
-            eyre::bail!("You can only verify deployments from inside a project! Make sure it exists with `forge tree`.");
-        }
+        ensure_verifiable(target_path, &graph, verify)?;
         compiler = compiler.files([target_path.into()]);
     }
     compiler.compile(project)
 }
 
+/// Fails if the deployments of the script at `target_path` are to be verified, but the script is
+/// not part of the project resolved in `graph`.
+pub fn ensure_verifiable(target_path: &Path, graph: &Graph, verify: bool) -> Result<()> {
+    if verify && !graph.files().contains_key(target_path) {
+        eyre::bail!("You can only verify deployments from inside a project! Make sure it exists with `forge tree`.");
+    }
+    Ok(())
+}
+
 /// Compiles an Etherscan source from metadata by creating a project.
 /// Returns the artifact_id, the file_id, and the bytecode
 pub async fn compile_from_source(
@@ -637,4 +748,22 @@ mod tests {
         let file = Path::new("/home/script/Contract.sol");
         assert!(!is_match_exclude(&custom("*/script/**"), file));
     }
+
+    #[test]
+    fn reads_source_files_once() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("Counter.sol"), "contract Counter {}").unwrap();
+        let id = |name: &str| ArtifactId {
+            path: PathBuf::new(),
+            name: name.to_string(),
+            source: PathBuf::from("Counter.sol"),
+            version: semver::Version::new(0, 8, 23),
+        };
+
+        let mut files = SourceFiles::default();
+        assert_eq!(files.read(root.path(), &id("Counter")).unwrap(), "contract Counter {}");
+        std::fs::remove_file(root.path().join("Counter.sol")).unwrap();
+        assert_eq!(files.read(root.path(), &id("CounterLib")).unwrap(), "contract Counter {}");
+        assert_eq!(files.0.len(), 1);
+    }
 }
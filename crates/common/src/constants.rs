@@ -27,6 +27,12 @@ pub const NON_ARCHIVE_NODE_WARNING: &str = "\
 It looks like you're trying to fork from an older block with a non-archive node which is not \
 supported. Please try to change your RPC url to an archive node if the issue persists.";
 
+/// Logged when the responses of the RPC are inconsistent with each other.
+pub const UNRELIABLE_RPC_WARNING: &str = "\
+Your RPC is unreliable: its responses are inconsistent with each other, e.g. because it's \
+load-balanced across nodes at different heights or it pruned the state of the forked block. \
+Please try another RPC url, or pin the fork with --fork-block-number.";
+
 /// Arbitrum L1 sender address of the first transaction in every block.
 /// `0x00000000000000000000000000000000000a4b05`
 pub const ARBITRUM_SENDER: Address = address!("00000000000000000000000000000000000a4b05");
@@ -1,24 +1,27 @@
 //! Commonly used contract types and functions.
 
 use alloy_json_abi::{Event, Function, JsonAbi};
-use alloy_primitives::{hex, Address, Selector, B256};
+use alloy_primitives::{hex, keccak256, Address, Bytes, Selector, B256};
 use eyre::Result;
 use foundry_compilers::{
-    artifacts::{CompactContractBytecode, ContractBytecodeSome},
+    artifacts::{BytecodeObject, CompactContractBytecode, ContractBytecodeSome},
     ArtifactId, ProjectPathsConfig,
 };
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashMap},
     fmt,
     ops::{Deref, DerefMut},
     path::PathBuf,
 };
 
-type ArtifactWithContractRef<'a> = (&'a ArtifactId, &'a (JsonAbi, Vec<u8>));
+type ArtifactWithContractRef<'a> = (&'a ArtifactId, &'a (JsonAbi, Bytes));
 
 /// Wrapper type that maps an artifact to a contract ABI and bytecode.
+///
+/// The bytecode is reference counted, so cloning it or sharing it between artifacts with
+/// [BytecodeInterner] doesn't copy it.
 #[derive(Clone, Default)]
-pub struct ContractsByArtifact(pub BTreeMap<ArtifactId, (JsonAbi, Vec<u8>)>);
+pub struct ContractsByArtifact(pub BTreeMap<ArtifactId, (JsonAbi, Bytes)>);
 
 impl fmt::Debug for ContractsByArtifact {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -68,7 +71,7 @@ impl ContractsByArtifact {
 }
 
 impl Deref for ContractsByArtifact {
-    type Target = BTreeMap<ArtifactId, (JsonAbi, Vec<u8>)>;
+    type Target = BTreeMap<ArtifactId, (JsonAbi, Bytes)>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -81,6 +84,34 @@ impl DerefMut for ContractsByArtifact {
     }
 }
 
+/// Deduplicates bytecode by hash, so that identical code, e.g. of clones or of contracts deployed
+/// from the same template, is stored once.
+#[derive(Clone, Debug, Default)]
+pub struct BytecodeInterner(HashMap<B256, Bytes>);
+
+impl BytecodeInterner {
+    /// Replaces `code` with the interned bytecode with the same hash, interning it if it's new.
+    pub fn intern(&mut self, code: &mut Bytes) {
+        let interned = self.0.entry(keccak256(&code[..])).or_insert_with(|| code.clone());
+        *code = interned.clone();
+    }
+
+    /// Interns the bytecode of `object`, if it's linked.
+    pub fn intern_object(&mut self, object: &mut BytecodeObject) {
+        if let BytecodeObject::Bytecode(code) = object {
+            self.intern(code);
+        }
+    }
+
+    /// Interns the creation and deployed bytecode of `contract`.
+    pub fn intern_contract(&mut self, contract: &mut ContractBytecodeSome) {
+        self.intern_object(&mut contract.bytecode.object);
+        if let Some(bytecode) = &mut contract.deployed_bytecode.bytecode {
+            self.intern_object(&mut bytecode.object);
+        }
+    }
+}
+
 /// Wrapper type that maps an address to a contract identifier and contract ABI.
 pub type ContractsByAddress = BTreeMap<Address, (String, JsonAbi)>;
 
@@ -214,4 +245,17 @@ mod tests {
         let a_99 = &b"a".repeat(99)[..];
         assert!(bytecode_diff_score(a_100, a_99) <= 0.01);
     }
+
+    #[test]
+    fn bytecode_interning() {
+        let mut interner = BytecodeInterner::default();
+        let mut a = Bytes::from(vec![0x60, 0x80]);
+        let mut b = Bytes::from(vec![0x60, 0x80]);
+        let mut c = Bytes::from(vec![0x60, 0x40]);
+        interner.intern(&mut a);
+        interner.intern(&mut b);
+        interner.intern(&mut c);
+        assert_eq!(a.as_ptr(), b.as_ptr());
+        assert_ne!(a.as_ptr(), c.as_ptr());
+    }
 }
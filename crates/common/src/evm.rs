@@ -14,8 +14,8 @@ use foundry_config::{
 use serde::Serialize;
 use std::collections::HashMap;
 
-/// Map keyed by breakpoints char to their location (contract address, pc)
-pub type Breakpoints = HashMap<char, (Address, usize)>;
+/// Map keyed by breakpoint labels to their location (contract address, pc)
+pub type Breakpoints = HashMap<String, (Address, usize)>;
 
 /// `EvmArgs` and `EnvArgs` take the highest precedence in the Config/Figment hierarchy.
 /// All vars are opt-in, their default values are expected to be set by the
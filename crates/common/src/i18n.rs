@@ -0,0 +1,189 @@
+//! Localization of user-facing messages.
+//!
+//! Messages are written in English at the call site with [`tr!`](crate::tr) and translated into
+//! the language selected with the `FOUNDRY_LANG` environment variable, e.g. `FOUNDRY_LANG=zh-CN`.
+//! Logs and JSON output are never translated, so they stay stable for machine consumption.
+
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, env, fmt};
+
+/// The environment variable selecting the language of user-facing messages.
+pub const FOUNDRY_LANG: &str = "FOUNDRY_LANG";
+
+/// A language user-facing messages can be translated into.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Lang {
+    /// English, the language messages are written in.
+    #[default]
+    English,
+    /// Simplified Chinese, `zh-CN`.
+    SimplifiedChinese,
+}
+
+impl Lang {
+    /// Parses a language tag like `zh-CN`, `zh_CN.UTF-8` or `en`.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let tag = tag.split('.').next().unwrap_or_default().replace('_', "-").to_lowercase();
+        match tag.as_str() {
+            "en" | "en-us" | "en-gb" | "c" | "posix" => Some(Self::English),
+            "zh" | "zh-cn" | "zh-hans" | "zh-sg" => Some(Self::SimplifiedChinese),
+            _ => None,
+        }
+    }
+
+    /// Returns the language selected with `FOUNDRY_LANG`, English by default.
+    pub fn current() -> Self {
+        static CURRENT: Lazy<Lang> = Lazy::new(|| {
+            env::var(FOUNDRY_LANG).ok().and_then(|tag| Lang::from_tag(&tag)).unwrap_or_default()
+        });
+        *CURRENT
+    }
+
+    fn catalog(self) -> Option<&'static HashMap<&'static str, &'static str>> {
+        static ZH_CN: Lazy<HashMap<&str, &str>> =
+            Lazy::new(|| ZH_CN_MESSAGES.iter().copied().collect());
+        match self {
+            Self::English => None,
+            Self::SimplifiedChinese => Some(&ZH_CN),
+        }
+    }
+}
+
+/// Translates `message` into the current language, and replaces its `{name}` placeholders with
+/// the given arguments.
+///
+/// Messages without a translation are kept in English.
+pub fn translate(message: &'static str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    translate_to(Lang::current(), message, args)
+}
+
+/// Same as [`translate`], for the given language.
+pub fn translate_to(
+    lang: Lang,
+    message: &'static str,
+    args: &[(&str, &dyn fmt::Display)],
+) -> String {
+    let template =
+        lang.catalog().and_then(|catalog| catalog.get(message).copied()).unwrap_or(message);
+    let mut message = template.to_string();
+    for (name, value) in args {
+        message = message.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    message
+}
+
+/// Translates a user-facing message, see the [`i18n`](crate::i18n) module.
+///
+/// # Example
+///
+/// ```
+/// use foundry_common::tr;
+///
+/// let message = tr!("Contract successfully verified: {address}", address = "0x1234");
+/// ```
+#[macro_export]
+macro_rules! tr {
+    ($message:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::i18n::translate(
+            $message,
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),*],
+        )
+    };
+}
+
+/// The Simplified Chinese translations, keyed by the English message.
+const ZH_CN_MESSAGES: &[(&str, &str)] = &[
+    // forge script
+    (
+        "\nSIMULATION COMPLETE. To broadcast these transactions, add --broadcast and wallet configuration(s) to the previous command. See forge script --help for more.",
+        "\n模拟完成。如需广播这些交易，请在上一条命令中添加 --broadcast 和钱包配置。更多信息请参阅 forge script --help。",
+    ),
+    (
+        "\nIf you wish to simulate on-chain transactions pass a RPC URL.",
+        "\n如需模拟链上交易，请提供 RPC URL。",
+    ),
+    ("\nSKIPPING ON CHAIN SIMULATION.", "\n跳过链上模拟。"),
+    ("Script ran successfully.", "脚本运行成功。"),
+    ("Do you wish to continue?", "是否继续？"),
+    ("##\nSending transactions [{first} - {last}].", "##\n正在发送交易 [{first} - {last}]。"),
+    ("##\nWaiting for receipts.", "##\n正在等待交易回执。"),
+    ("##\nChecking previously pending transactions.", "##\n正在检查之前待处理的交易。"),
+    ("\nONCHAIN EXECUTION COMPLETE & SUCCESSFUL.", "\n链上执行已成功完成。"),
+    (
+        "You seem to be using Foundry's default sender. Be sure to set your own --sender.",
+        "您似乎正在使用 Foundry 的默认发送者地址，请通过 --sender 设置您自己的地址。",
+    ),
+    (
+        "No associated wallet for addresses: {addresses}. Unlocked wallets: {wallets}",
+        "以下地址没有关联的钱包：{addresses}。已解锁的钱包：{wallets}",
+    ),
+    ("\nTransactions saved to: {path}\n", "\n交易已保存至：{path}\n"),
+    ("Sensitive values saved to: {path}\n", "敏感信息已保存至：{path}\n"),
+    ("##\nStart verification for ({count}) contracts", "##\n开始验证 ({count}) 个合约"),
+    ("All ({count}) contracts were verified!", "全部 ({count}) 个合约均已验证！"),
+    // forge verify-contract
+    (
+        "Start verifying contract `{address}` deployed on {chain}",
+        "开始验证部署在 {chain} 上的合约 `{address}`",
+    ),
+    ("Checking verification status on {chain}", "正在 {chain} 上检查验证状态"),
+    (
+        "\nContract [{name}] {address} is already verified. Skipping verification.",
+        "\n合约 [{name}] {address} 已经验证过，跳过验证。",
+    ),
+    (
+        "\nSubmitting verification for [{name}] {address}.",
+        "\n正在提交 [{name}] {address} 的验证。",
+    ),
+    (
+        "Submitted contract for verification:\n\tResponse: `{response}`\n\tGUID: `{guid}`",
+        "已提交合约验证：\n\t响应：`{response}`\n\tGUID：`{guid}`",
+    ),
+    (
+        "Submitted contract for verification:\n\tResponse: `{response}`\n\tGUID: `{guid}`\n\tURL: {url}",
+        "已提交合约验证：\n\t响应：`{response}`\n\tGUID：`{guid}`\n\tURL：{url}",
+    ),
+    ("Contract source code already verified", "合约源代码已经验证过"),
+    ("Contract failed to verify.", "合约验证失败。"),
+    ("Contract successfully verified", "合约验证成功"),
+    (
+        "Encountered an error verifying this contract:\nResponse: `{response}`\nDetails: `{details}`",
+        "验证此合约时出错：\n响应：`{response}`\n详情：`{details}`",
+    ),
+    (
+        "Contract verification status:\nResponse: `{response}`\nDetails: `{details}`",
+        "合约验证状态：\n响应：`{response}`\n详情：`{details}`",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_lang_tags() {
+        assert_eq!(Lang::from_tag("zh_CN.UTF-8"), Some(Lang::SimplifiedChinese));
+        assert_eq!(Lang::from_tag("zh-CN"), Some(Lang::SimplifiedChinese));
+        assert_eq!(Lang::from_tag("en"), Some(Lang::English));
+        assert_eq!(Lang::from_tag("xx"), None);
+    }
+
+    #[test]
+    fn can_translate_messages() {
+        let chain = "mainnet";
+        let message = "Checking verification status on {chain}";
+        let args: &[(&str, &dyn fmt::Display)] = &[("chain", &chain)];
+        assert_eq!(
+            translate_to(Lang::English, message, args),
+            "Checking verification status on mainnet"
+        );
+        assert_eq!(
+            translate_to(Lang::SimplifiedChinese, message, args),
+            "正在 mainnet 上检查验证状态"
+        );
+        assert_eq!(
+            translate_to(Lang::SimplifiedChinese, "untranslated {chain}", args),
+            "untranslated mainnet"
+        );
+    }
+}
@@ -8,6 +8,7 @@ extern crate self as foundry_common;
 extern crate tracing;
 
 pub mod abi;
+pub mod artifact_store;
 pub mod calc;
 pub mod compile;
 pub mod constants;
@@ -17,6 +18,7 @@ pub mod evm;
 pub mod fmt;
 pub mod fs;
 pub mod glob;
+pub mod i18n;
 pub mod provider;
 pub mod retry;
 pub mod rpc;
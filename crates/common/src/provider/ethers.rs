@@ -4,7 +4,7 @@ use crate::{
     runtime_client::{RuntimeClient, RuntimeClientBuilder},
     ALCHEMY_FREE_TIER_CUPS, REQUEST_TIMEOUT,
 };
-use ethers_core::types::U256;
+use ethers_core::types::{BlockNumber, U256};
 use ethers_middleware::gas_oracle::{GasCategory, GasOracle, Polygon};
 use ethers_providers::{is_local_endpoint, Middleware, Provider, DEFAULT_LOCAL_POLL_INTERVAL};
 use eyre::{Result, WrapErr};
@@ -308,10 +308,60 @@ fn resolve_path(path: &Path) -> Result<PathBuf, ()> {
     Err(())
 }
 
+/// How many recent blocks the block time of a chain is averaged over.
+const BLOCK_TIME_SAMPLE_SIZE: u64 = 20;
+
+/// The bounds of the polling interval derived from the block time of a chain.
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Estimates the block time of the chain from the timestamps of its recent blocks.
+pub async fn estimate_block_time<M: Middleware>(provider: &M) -> Option<Duration> {
+    let latest = provider.get_block(BlockNumber::Latest).await.ok()??;
+    let number = latest.number?.as_u64();
+    let blocks = number.min(BLOCK_TIME_SAMPLE_SIZE);
+    if blocks == 0 {
+        return None
+    }
+    let earlier =
+        provider.get_block(BlockNumber::Number((number - blocks).into())).await.ok()??;
+    let elapsed = latest.timestamp.saturating_sub(earlier.timestamp).as_u64();
+    Some(Duration::from_millis(elapsed * 1000 / blocks))
+}
+
+/// Returns the interval to poll a chain with the given block time at, which is half of the block
+/// time so that new blocks are seen early without polling more than needed.
+pub fn poll_interval(block_time: Duration) -> Duration {
+    (block_time / 2).clamp(MIN_POLL_INTERVAL, MAX_POLL_INTERVAL)
+}
+
+/// Sets the polling interval of a provider from the block time of its chain, estimated from its
+/// recent blocks, e.g. to wait for transactions. Local endpoints keep their short interval.
+pub async fn with_adaptive_interval(provider: RetryProvider, url: &str) -> RetryProvider {
+    if is_local_endpoint(url) {
+        return provider
+    }
+    match estimate_block_time(&provider).await {
+        Some(block_time) => {
+            trace!(?block_time, "estimated the block time of the chain");
+            provider.interval(poll_interval(block_time))
+        }
+        None => provider,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn polls_at_half_the_block_time() {
+        assert_eq!(poll_interval(Duration::from_secs(12)), Duration::from_secs(6));
+        assert_eq!(poll_interval(Duration::from_secs(2)), Duration::from_secs(1));
+        assert_eq!(poll_interval(Duration::ZERO), MIN_POLL_INTERVAL);
+        assert_eq!(poll_interval(Duration::from_secs(600)), MAX_POLL_INTERVAL);
+    }
+
     #[test]
     fn can_auto_correct_missing_prefix() {
         let builder = ProviderBuilder::new("localhost:8545");
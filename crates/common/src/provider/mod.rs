@@ -3,5 +3,6 @@
 pub mod alloy;
 pub mod ethers;
 pub mod retry;
+pub mod rpc_trace;
 pub mod runtime_transport;
 pub mod tower;
@@ -1,6 +1,6 @@
 //! Runtime transport that connects on first request, which can take either of an HTTP,
 //! WebSocket, or IPC transport and supports retries based on CUPS logic.
-use crate::REQUEST_TIMEOUT;
+use crate::{provider::rpc_trace, REQUEST_TIMEOUT};
 use alloy_json_rpc::{RequestPacket, ResponsePacket};
 use alloy_pubsub::{PubSubConnect, PubSubFrontend};
 use alloy_transport::{
@@ -11,7 +11,7 @@ use alloy_transport_ipc::IpcConnect;
 use alloy_transport_ws::WsConnect;
 use ethers_providers::{JwtAuth, JwtKey};
 use reqwest::header::{HeaderName, HeaderValue};
-use std::{path::PathBuf, str::FromStr, sync::Arc};
+use std::{path::PathBuf, str::FromStr, sync::Arc, time::Instant};
 use thiserror::Error;
 use tokio::sync::RwLock;
 use tower::Service;
@@ -222,8 +222,11 @@ impl RuntimeTransport {
                 inner = this.inner.read().await;
             }
 
+            let traced = rpc_trace::is_enabled()
+                .then(|| (serde_json::to_value(&req).unwrap_or_default(), Instant::now()));
+
             // SAFETY: We just checked that the inner transport exists.
-            match inner.as_ref().expect("must've been initialized") {
+            let res = match inner.as_ref().expect("must've been initialized") {
                 InnerTransport::Http(http) => {
                     let mut http = http;
                     http.call(req)
@@ -237,7 +240,14 @@ impl RuntimeTransport {
                     ipc.call(req)
                 }
             }
-            .await
+            .await;
+
+            if let Some((request, start)) = traced {
+                let response =
+                    res.as_ref().map(rpc_trace::response_to_json).map_err(ToString::to_string);
+                rpc_trace::record(&this.url, request, response, start.elapsed());
+            }
+            res
         })
     }
 
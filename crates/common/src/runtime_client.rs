@@ -1,26 +1,33 @@
 //! Wrap different providers
 // todo: remove
+use crate::provider::rpc_trace;
 use async_trait::async_trait;
 use ethers_core::types::U256;
 use ethers_providers::{
-    Authorization, ConnectionDetails, Http, HttpRateLimitRetryPolicy, Ipc, JsonRpcClient,
-    JsonRpcError, JwtAuth, JwtKey, ProviderError, PubsubClient, RetryClient, RetryClientBuilder,
-    RpcError, Ws,
+    Authorization, ConnectionDetails, Http, HttpClientError, HttpRateLimitRetryPolicy, Ipc,
+    JsonRpcClient, JsonRpcError, JwtAuth, JwtKey, ProviderError, PubsubClient, RetryClient,
+    RetryClientBuilder, RpcError, Ws,
 };
 use reqwest::{
     header::{HeaderName, HeaderValue},
     Url,
 };
-use serde::{de::DeserializeOwned, Serialize};
-use std::{fmt::Debug, path::PathBuf, str::FromStr, sync::Arc, time::Duration};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fmt::Debug,
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use thiserror::Error;
 use tokio::sync::RwLock;
 
 /// Enum representing a the client types supported by the runtime provider
 #[derive(Debug)]
 enum InnerClient {
-    /// HTTP client
-    Http(RetryClient<Http>),
+    /// HTTP client, and the client of the batch requests
+    Http(RetryClient<Http>, RetryClient<BatchHttp>),
     /// WebSocket client
     Ws(Ws),
     /// IPC client
@@ -163,16 +170,9 @@ impl RuntimeClient {
                 let client = client_builder
                     .build()
                     .map_err(|e| RuntimeClientError::ProviderError(e.into()))?;
-                let provider = Http::new_with_client(self.url.clone(), client);
-
-                #[allow(clippy::box_default)]
-                let provider = RetryClientBuilder::default()
-                    .initial_backoff(Duration::from_millis(self.initial_backoff))
-                    .rate_limit_retries(self.max_retry)
-                    .timeout_retries(self.timeout_retry)
-                    .compute_units_per_second(self.compute_units_per_second)
-                    .build(provider, Box::new(HttpRateLimitRetryPolicy));
-                Ok(InnerClient::Http(provider))
+                let provider = Http::new_with_client(self.url.clone(), client.clone());
+                let batch = BatchHttp { client, url: self.url.clone() };
+                Ok(InnerClient::Http(self.retry_client(provider), self.retry_client(batch)))
             }
             "ws" | "wss" => {
                 let auth: Option<Authorization> =
@@ -201,6 +201,182 @@ impl RuntimeClient {
     }
 }
 
+impl RuntimeClient {
+    /// Wraps `transport` so that its requests are rate limited, and retried with backoff.
+    #[allow(clippy::box_default)]
+    fn retry_client<T>(&self, transport: T) -> RetryClient<T>
+    where
+        T: JsonRpcClient<Error = HttpClientError> + 'static,
+    {
+        RetryClientBuilder::default()
+            .initial_backoff(Duration::from_millis(self.initial_backoff))
+            .rate_limit_retries(self.max_retry)
+            .timeout_retries(self.timeout_retry)
+            .compute_units_per_second(self.compute_units_per_second)
+            .build(transport, Box::new(HttpRateLimitRetryPolicy))
+    }
+
+    /// Connects on the first request.
+    async fn ensure_connected(&self) -> Result<(), RuntimeClientError> {
+        if self.client.read().await.is_none() {
+            let mut w = self.client.write().await;
+            *w = Some(
+                self.connect().await.map_err(|e| RuntimeClientError::ProviderError(e.into()))?,
+            );
+        }
+        Ok(())
+    }
+
+    /// Sends a request through the connected client.
+    async fn send<T, R>(&self, method: &str, params: T) -> Result<R, RuntimeClientError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let res = match self.client.read().await.as_ref().unwrap() {
+            InnerClient::Http(http, _) => RetryClient::request(http, method, params)
+                .await
+                .map_err(|e| RuntimeClientError::ProviderError(e.into())),
+            InnerClient::Ws(ws) => JsonRpcClient::request(ws, method, params)
+                .await
+                .map_err(|e| RuntimeClientError::ProviderError(e.into())),
+            InnerClient::Ipc(ipc) => JsonRpcClient::request(ipc, method, params)
+                .await
+                .map_err(|e| RuntimeClientError::ProviderError(e.into())),
+        }?;
+        Ok(res)
+    }
+
+    /// Sends a single JSON-RPC batch request calling `method` once for each of `params`.
+    ///
+    /// Returns the results in the order of `params`, or `None` if the transport or the node don't
+    /// support batch requests. The results which are missing from the response, or which can't be
+    /// decoded, are `None`, so that they can be requested on their own.
+    ///
+    /// The batch is rate limited and retried like a single request.
+    pub async fn batch_request<T, R>(
+        &self,
+        method: &str,
+        params: &[T],
+    ) -> Result<Option<Vec<Option<Result<R, JsonRpcError>>>>, RuntimeClientError>
+    where
+        T: Debug + Serialize + Sync,
+        R: DeserializeOwned,
+    {
+        self.ensure_connected().await?;
+        let client = self.client.read().await;
+        let InnerClient::Http(_, batch) = client.as_ref().unwrap() else { return Ok(None) };
+
+        let requests = params
+            .iter()
+            .enumerate()
+            .map(|(id, params)| BatchRequest { jsonrpc: "2.0", id, method, params })
+            .collect::<Vec<_>>();
+        let start = Instant::now();
+        let res = RetryClient::request::<_, serde_json::Value>(batch, method, &requests).await;
+        if rpc_trace::is_enabled() {
+            let request = serde_json::to_value(&requests).unwrap_or_default();
+            let response = res.as_ref().cloned().map_err(ToString::to_string);
+            rpc_trace::record(&self.url, request, response, start.elapsed());
+        }
+        let err = match res {
+            Ok(responses) => return Ok(batch_results(responses, params.len())),
+            Err(err) => ProviderError::from(err),
+        };
+        // Nodes without batch support answer with a single error object instead of an array.
+        if err.as_error_response().is_some() {
+            return Ok(None)
+        }
+        Err(RuntimeClientError::ProviderError(err))
+    }
+}
+
+/// Matches the `responses` to a batch of `len` requests with the requests by id.
+///
+/// Returns `None` if `responses` isn't a batch response.
+fn batch_results<R: DeserializeOwned>(
+    responses: serde_json::Value,
+    len: usize,
+) -> Option<Vec<Option<Result<R, JsonRpcError>>>> {
+    let responses = serde_json::from_value::<Vec<BatchResponse>>(responses).ok()?;
+    let mut results = Vec::with_capacity(len);
+    results.resize_with(len, || None);
+    for response in responses {
+        let Some(slot) = results.get_mut(response.id) else { continue };
+        *slot = match response.error {
+            Some(err) => Some(Err(err)),
+            None => match serde_json::from_value(response.result.unwrap_or_default()) {
+                Ok(result) => Some(Ok(result)),
+                Err(err) => {
+                    trace!(id = response.id, %err, "undecodable batch response");
+                    None
+                }
+            },
+        };
+    }
+    Some(results)
+}
+
+/// The transport of JSON-RPC batch requests over HTTP.
+///
+/// It's wrapped in a [RetryClient], like the transport of single requests, which sees a batch as a
+/// single request whose params are the requests of the batch.
+#[derive(Debug)]
+struct BatchHttp {
+    client: reqwest::Client,
+    url: Url,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl JsonRpcClient for BatchHttp {
+    type Error = HttpClientError;
+
+    /// Posts the batch of requests `params`.
+    async fn request<T, R>(&self, _method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let res = self.client.post(self.url.clone()).json(&params).send().await?;
+        let body = res.error_for_status()?.bytes().await?;
+        // A single error, e.g. when the whole batch is rate limited, is retried like the error of
+        // a single request.
+        if let Ok(ErrorResponse { error }) = serde_json::from_slice(&body) {
+            return Err(HttpClientError::JsonRpcError(error))
+        }
+        serde_json::from_slice(&body).map_err(|err| HttpClientError::SerdeJson {
+            err,
+            text: String::from_utf8_lossy(&body).to_string(),
+        })
+    }
+}
+
+/// A request of a JSON-RPC batch.
+#[derive(Debug, Serialize)]
+struct BatchRequest<'a, T> {
+    jsonrpc: &'static str,
+    id: usize,
+    method: &'a str,
+    params: &'a T,
+}
+
+/// A response to a request of a JSON-RPC batch.
+#[derive(Deserialize)]
+struct BatchResponse {
+    id: usize,
+    #[serde(default)]
+    result: Option<serde_json::Value>,
+    #[serde(default)]
+    error: Option<JsonRpcError>,
+}
+
+/// The response of a node rejecting a whole JSON-RPC batch.
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: JsonRpcError,
+}
+
 impl RuntimeClientBuilder {
     /// Create new RuntimeClientBuilder
     pub fn new(
@@ -282,25 +458,19 @@ impl JsonRpcClient for RuntimeClient {
         T: Debug + Serialize + Send + Sync,
         R: DeserializeOwned + Send,
     {
-        if self.client.read().await.is_none() {
-            let mut w = self.client.write().await;
-            *w = Some(
-                self.connect().await.map_err(|e| RuntimeClientError::ProviderError(e.into()))?,
-            );
+        self.ensure_connected().await?;
+
+        if rpc_trace::is_enabled() {
+            let request = serde_json::json!({ "method": method, "params": &params });
+            let start = Instant::now();
+            let res = self.send::<T, serde_json::Value>(method, params).await;
+            let response = res.as_ref().cloned().map_err(ToString::to_string);
+            rpc_trace::record(&self.url, request, response, start.elapsed());
+            return serde_json::from_value(res?)
+                .map_err(|e| RuntimeClientError::ProviderError(ProviderError::SerdeJson(e)))
         }
 
-        let res = match self.client.read().await.as_ref().unwrap() {
-            InnerClient::Http(http) => RetryClient::request(http, method, params)
-                .await
-                .map_err(|e| RuntimeClientError::ProviderError(e.into())),
-            InnerClient::Ws(ws) => JsonRpcClient::request(ws, method, params)
-                .await
-                .map_err(|e| RuntimeClientError::ProviderError(e.into())),
-            InnerClient::Ipc(ipc) => JsonRpcClient::request(ipc, method, params)
-                .await
-                .map_err(|e| RuntimeClientError::ProviderError(e.into())),
-        }?;
-        Ok(res)
+        self.send(method, params).await
     }
 }
 
@@ -312,7 +482,7 @@ impl PubsubClient for RuntimeClient {
 
     fn subscribe<T: Into<U256>>(&self, id: T) -> Result<Self::NotificationStream, Self::Error> {
         match self.client.try_read().map_err(|_| RuntimeClientError::LockError)?.as_ref().unwrap() {
-            InnerClient::Http(_) => {
+            InnerClient::Http(..) => {
                 Err(RuntimeClientError::ProviderError(ProviderError::UnsupportedRPC))
             }
             InnerClient::Ws(client) => Ok(PubsubClient::subscribe(client, id)
@@ -325,7 +495,7 @@ impl PubsubClient for RuntimeClient {
     fn unsubscribe<T: Into<U256>>(&self, id: T) -> Result<(), Self::Error> {
         match self.client.try_read().map_err(|_| (RuntimeClientError::LockError))?.as_ref().unwrap()
         {
-            InnerClient::Http(_) => {
+            InnerClient::Http(..) => {
                 Err(RuntimeClientError::ProviderError(ProviderError::UnsupportedRPC))
             }
             InnerClient::Ws(client) => Ok(PubsubClient::unsubscribe(client, id)
@@ -335,3 +505,44 @@ impl PubsubClient for RuntimeClient {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn matches_batch_responses_by_id() {
+        let responses = json!([
+            { "jsonrpc": "2.0", "id": 2, "result": "0x3" },
+            { "jsonrpc": "2.0", "id": 0, "result": "0x1" },
+            { "jsonrpc": "2.0", "id": 1, "error": { "code": -32000, "message": "unknown block" } },
+            { "jsonrpc": "2.0", "id": 7, "result": "0x7" },
+        ]);
+        let results = batch_results::<U256>(responses, 4).unwrap();
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().unwrap().as_ref().unwrap(), &U256::from(1));
+        assert_eq!(results[1].as_ref().unwrap().as_ref().unwrap_err().code, -32000);
+        assert_eq!(results[2].as_ref().unwrap().as_ref().unwrap(), &U256::from(3));
+        // missing from the response
+        assert!(results[3].is_none());
+    }
+
+    #[test]
+    fn skips_malformed_batch_responses() {
+        let responses = json!([
+            { "jsonrpc": "2.0", "id": 0, "result": "0x1" },
+            { "jsonrpc": "2.0", "id": 1, "result": { "not": "a number" } },
+        ]);
+        let results = batch_results::<U256>(responses, 2).unwrap();
+        assert!(results[0].is_some());
+        assert!(results[1].is_none());
+
+        let error = json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": { "code": -32600, "message": "batch not supported" },
+        });
+        assert!(batch_results::<U256>(error, 2).is_none());
+    }
+}
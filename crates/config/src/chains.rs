@@ -0,0 +1,148 @@
+//! Custom chains, registered with `forge chain add`.
+
+use crate::{resolve::interpolate, Chain, Config, UnresolvedEnvVarError};
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// The file name of the custom chains, in the foundry directory `~/.foundry`.
+pub const CHAINS_FILE_NAME: &str = "chains.toml";
+
+/// A chain which isn't known by name, so that it can be selected with `--chain <name>`.
+///
+/// ```toml
+/// [xlayer-testnet]
+/// id = 195
+/// rpc = "https://testrpc.xlayer.tech"
+/// explorer_api = "https://www.oklink.com/api/v5/explorer/contract/verify-source-code-plugin/XLAYER_TESTNET"
+/// explorer_url = "https://www.oklink.com/xlayer-test"
+/// verifier = "oklink"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainDefinition {
+    pub id: u64,
+    /// The RPC URL of the chain, which may reference environment variables, e.g. `${XLAYER_RPC}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc: Option<String>,
+    /// The API URL of the block explorer, used to verify contracts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explorer_api: Option<String>,
+    /// The URL of the block explorer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
+    /// The verification provider of the explorer, e.g. `oklink`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifier: Option<String>,
+}
+
+impl ChainDefinition {
+    pub fn chain(&self) -> Chain {
+        Chain::from_id(self.id)
+    }
+
+    /// Returns the RPC URL, with its environment variables resolved.
+    pub fn resolved_rpc(&self) -> Option<Result<String, UnresolvedEnvVarError>> {
+        self.rpc.as_deref().map(interpolate)
+    }
+}
+
+/// The custom chains, by name.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ChainDefinitions(BTreeMap<String, ChainDefinition>);
+
+impl ChainDefinitions {
+    /// Returns the path of the custom chains file, `~/.foundry/chains.toml`.
+    pub fn path() -> Option<PathBuf> {
+        Config::foundry_dir().map(|dir| dir.join(CHAINS_FILE_NAME))
+    }
+
+    /// Loads the custom chains, warning about an invalid file.
+    pub fn load() -> Self {
+        let Some(path) = Self::path().filter(|path| path.exists()) else { return Self::default() };
+        Self::load_from(&path).unwrap_or_else(|err| {
+            warn!(%err, "failed to load the custom chains");
+            Self::default()
+        })
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default())
+        }
+        let content = std::fs::read_to_string(path)?;
+        toml::from_str(&content).wrap_err_with(|| format!("Invalid chains file {}", path.display()))
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ChainDefinition> {
+        self.0.get(name)
+    }
+
+    /// Returns the name and the definition of the custom chain with the chain id.
+    pub fn find(&self, chain: Chain) -> Option<(&str, &ChainDefinition)> {
+        self.0
+            .iter()
+            .find(|(_, definition)| definition.id == chain.id())
+            .map(|(name, definition)| (name.as_str(), definition))
+    }
+
+    /// Adds or replaces the chain `name`, and returns the previous definition.
+    pub fn insert(&mut self, name: String, definition: ChainDefinition) -> Option<ChainDefinition> {
+        self.0.insert(name, definition)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<ChainDefinition> {
+        self.0.remove(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ChainDefinition)> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saves_and_loads_chains() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CHAINS_FILE_NAME);
+        assert!(ChainDefinitions::load_from(&path).unwrap().is_empty());
+
+        let mut chains = ChainDefinitions::default();
+        let definition = ChainDefinition {
+            id: 195,
+            rpc: Some("https://testrpc.xlayer.tech".to_string()),
+            verifier: Some("oklink".to_string()),
+            ..Default::default()
+        };
+        assert!(chains.insert("xlayer-testnet".to_string(), definition.clone()).is_none());
+        chains.save_to(&path).unwrap();
+
+        let loaded = ChainDefinitions::load_from(&path).unwrap();
+        assert_eq!(loaded, chains);
+        assert_eq!(loaded.get("xlayer-testnet"), Some(&definition));
+        assert_eq!(loaded.find(Chain::from_id(195)), Some(("xlayer-testnet", &definition)));
+        assert!(loaded.find(Chain::from_id(196)).is_none());
+
+        std::fs::write(&path, "[bad]\nid = 1\nunknown = true\n").unwrap();
+        assert!(ChainDefinitions::load_from(&path).is_err());
+    }
+}
@@ -1,7 +1,10 @@
 //! Support for multiple RPC-endpoints
 
 use crate::resolve::{interpolate, UnresolvedEnvVarError, RE_PLACEHOLDER};
-use serde::{ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+use serde::{
+    ser::{SerializeMap, SerializeSeq},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 use std::{
     collections::BTreeMap,
     fmt,
@@ -234,6 +237,9 @@ pub struct RpcEndpointConfig {
     ///
     /// See also <https://docs.alchemy.com/reference/compute-units#what-are-cups-compute-units-per-second>
     pub compute_units_per_second: Option<u64>,
+
+    /// Additional endpoints of the same chain to fail over to, in order of preference.
+    pub fallbacks: Vec<RpcEndpoint>,
 }
 
 impl RpcEndpointConfig {
@@ -241,11 +247,22 @@ impl RpcEndpointConfig {
     pub fn resolve(self) -> Result<String, UnresolvedEnvVarError> {
         self.endpoint.resolve()
     }
+
+    /// Returns the url of the endpoint followed by the urls of all fallbacks
+    pub fn resolve_all(self) -> Result<Vec<String>, UnresolvedEnvVarError> {
+        std::iter::once(self.endpoint).chain(self.fallbacks).map(RpcEndpoint::resolve).collect()
+    }
 }
 
 impl fmt::Display for RpcEndpointConfig {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let RpcEndpointConfig { endpoint, retries, retry_backoff, compute_units_per_second } = self;
+        let RpcEndpointConfig {
+            endpoint,
+            retries,
+            retry_backoff,
+            compute_units_per_second,
+            fallbacks,
+        } = self;
 
         write!(f, "{}", endpoint)?;
 
@@ -261,6 +278,10 @@ impl fmt::Display for RpcEndpointConfig {
             write!(f, ", compute_units_per_second={}", compute_units_per_second)?;
         }
 
+        for fallback in fallbacks {
+            write!(f, ", fallback={}", fallback)?;
+        }
+
         Ok(())
     }
 }
@@ -274,14 +295,25 @@ impl Serialize for RpcEndpointConfig {
             self.retry_backoff.is_none() &&
             self.compute_units_per_second.is_none()
         {
-            // serialize as endpoint if there's no additional config
-            self.endpoint.serialize(serializer)
+            if self.fallbacks.is_empty() {
+                // serialize as endpoint if there's no additional config
+                self.endpoint.serialize(serializer)
+            } else {
+                // serialize as list of endpoints if there are only fallbacks
+                let mut seq = serializer.serialize_seq(Some(self.fallbacks.len() + 1))?;
+                seq.serialize_element(&self.endpoint)?;
+                for fallback in &self.fallbacks {
+                    seq.serialize_element(fallback)?;
+                }
+                seq.end()
+            }
         } else {
-            let mut map = serializer.serialize_map(Some(4))?;
+            let mut map = serializer.serialize_map(Some(5))?;
             map.serialize_entry("endpoint", &self.endpoint)?;
             map.serialize_entry("retries", &self.retries)?;
             map.serialize_entry("retry_backoff", &self.retry_backoff)?;
             map.serialize_entry("compute_units_per_second", &self.compute_units_per_second)?;
+            map.serialize_entry("fallbacks", &self.fallbacks)?;
             map.end()
         }
     }
@@ -300,6 +332,16 @@ impl<'de> Deserialize<'de> for RpcEndpointConfig {
             });
         }
 
+        if value.is_array() {
+            let mut endpoints: Vec<RpcEndpoint> =
+                serde_json::from_value(value).map_err(serde::de::Error::custom)?;
+            if endpoints.is_empty() {
+                return Err(serde::de::Error::custom("empty list of rpc endpoints"));
+            }
+            let endpoint = endpoints.remove(0);
+            return Ok(Self { endpoint, fallbacks: endpoints, ..Default::default() });
+        }
+
         #[derive(Deserialize)]
         struct RpcEndpointConfigInner {
             #[serde(alias = "url")]
@@ -307,12 +349,25 @@ impl<'de> Deserialize<'de> for RpcEndpointConfig {
             retries: Option<u32>,
             retry_backoff: Option<u64>,
             compute_units_per_second: Option<u64>,
+            #[serde(default)]
+            fallbacks: Vec<RpcEndpoint>,
         }
 
-        let RpcEndpointConfigInner { endpoint, retries, retry_backoff, compute_units_per_second } =
-            serde_json::from_value(value).map_err(serde::de::Error::custom)?;
-
-        Ok(RpcEndpointConfig { endpoint, retries, retry_backoff, compute_units_per_second })
+        let RpcEndpointConfigInner {
+            endpoint,
+            retries,
+            retry_backoff,
+            compute_units_per_second,
+            fallbacks,
+        } = serde_json::from_value(value).map_err(serde::de::Error::custom)?;
+
+        Ok(RpcEndpointConfig {
+            endpoint,
+            retries,
+            retry_backoff,
+            compute_units_per_second,
+            fallbacks,
+        })
     }
 }
 
@@ -329,6 +384,7 @@ impl Default for RpcEndpointConfig {
             retries: None,
             retry_backoff: None,
             compute_units_per_second: None,
+            fallbacks: vec![],
         }
     }
 }
@@ -384,6 +440,7 @@ mod tests {
                 retries: Some(5),
                 retry_backoff: Some(250),
                 compute_units_per_second: Some(100),
+                fallbacks: vec![],
             }
         );
 
@@ -396,7 +453,20 @@ mod tests {
                 retries: None,
                 retry_backoff: None,
                 compute_units_per_second: None,
+                fallbacks: vec![],
+            }
+        );
+
+        let s = r#"["http://localhost:8545", "${_FALLBACK_RPC}"]"#;
+        let config: RpcEndpointConfig = serde_json::from_str(s).unwrap();
+        assert_eq!(
+            config,
+            RpcEndpointConfig {
+                endpoint: RpcEndpoint::Url("http://localhost:8545".to_string()),
+                fallbacks: vec![RpcEndpoint::Env("${_FALLBACK_RPC}".to_string())],
+                ..Default::default()
             }
         );
+        assert_eq!(serde_json::to_string(&config).unwrap().replace(' ', ""), s.replace(' ', ""));
     }
 }
@@ -0,0 +1,89 @@
+//! Configuration of the links to the pages of the block explorers.
+
+use crate::Chain;
+use alloy_primitives::{Address, B256};
+use serde::{Deserialize, Serialize};
+
+/// The explorers of OKLink of the chains which Etherscan doesn't index, by chain id.
+const OKLINK_EXPLORERS: &[(u64, &str)] =
+    &[(196, "https://www.oklink.com/xlayer"), (195, "https://www.oklink.com/xlayer-test")];
+
+/// The URL templates of the address and transaction pages of the block explorer of a chain, in
+/// which `{address}` and `{hash}` are replaced with the address and the transaction hash.
+///
+/// ```toml
+/// [explorer_links.xlayer]
+/// address = "https://www.oklink.com/xlayer/address/{address}"
+/// tx = "https://www.oklink.com/xlayer/tx/{hash}"
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExplorerLinks {
+    pub address: String,
+    pub tx: String,
+}
+
+impl ExplorerLinks {
+    /// Returns the links of an explorer with `/address/<address>` and `/tx/<hash>` pages, as
+    /// Etherscan, Blockscout and OKLink have.
+    pub fn from_base_url(url: &str) -> Self {
+        let url = url.trim_end_matches('/');
+        Self { address: format!("{url}/address/{{address}}"), tx: format!("{url}/tx/{{hash}}") }
+    }
+
+    /// Returns the links of the OKLink explorer of the chain, if it's one of the chains only
+    /// OKLink indexes.
+    pub fn oklink(chain: Chain) -> Option<Self> {
+        OKLINK_EXPLORERS
+            .iter()
+            .find(|(id, _)| *id == chain.id())
+            .map(|(_, url)| Self::from_base_url(url))
+    }
+
+    pub fn address_url(&self, address: Address) -> String {
+        self.address.replace("{address}", &address.to_checksum(None))
+    }
+
+    pub fn tx_url(&self, hash: B256) -> String {
+        self.tx.replace("{hash}", &hash.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use alloy_primitives::address;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn renders_links() {
+        let address = address!("5FbDB2315678afecb367f032d93F642f64180aa3");
+        let links = ExplorerLinks::from_base_url("https://etherscan.io/");
+        assert_eq!(
+            links.address_url(address),
+            "https://etherscan.io/address/0x5FbDB2315678afecb367f032d93F642f64180aa3"
+        );
+        assert_eq!(
+            links.tx_url(B256::repeat_byte(0xab)),
+            format!("https://etherscan.io/tx/0x{}", "ab".repeat(32))
+        );
+
+        let links = ExplorerLinks::oklink(Chain::from_id(196)).unwrap();
+        assert_eq!(links.tx, "https://www.oklink.com/xlayer/tx/{hash}");
+        assert!(ExplorerLinks::oklink(Chain::mainnet()).is_none());
+    }
+
+    #[test]
+    fn resolves_configured_links() {
+        let links = ExplorerLinks::from_base_url("https://explorer.example");
+        let config = Config {
+            explorer_links: BTreeMap::from([("196".to_string(), links.clone())]),
+            ..Default::default()
+        };
+        assert_eq!(config.get_explorer_links(Chain::from_id(196)), Some(links));
+        assert_eq!(
+            config.get_explorer_links(Chain::mainnet()).unwrap().address,
+            "https://etherscan.io/address/{address}"
+        );
+    }
+}
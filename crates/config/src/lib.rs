@@ -31,7 +31,7 @@ use semver::Version;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs,
     path::{Path, PathBuf},
     str::FromStr,
@@ -66,9 +66,27 @@ pub use error::SolidityErrorCode;
 pub mod doc;
 pub use doc::DocConfig;
 
+pub mod precompiles;
+pub use precompiles::PrecompileConfig;
+
+pub mod verifier_plugins;
+pub use verifier_plugins::VerifierPluginConfig;
+
+pub mod script_hooks;
+pub use script_hooks::{HookCommand, ScriptHooksConfig};
+
+pub mod chains;
+pub use chains::{ChainDefinition, ChainDefinitions};
+
+pub mod explorer_links;
+pub use explorer_links::ExplorerLinks;
+
 mod warning;
 pub use warning::*;
 
+mod strict;
+pub use strict::StrictConfigIssue;
+
 // helpers for fixing configuration warnings
 pub mod fix;
 
@@ -158,6 +176,9 @@ pub struct Config {
     pub cache: bool,
     /// where the cache is stored if enabled
     pub cache_path: PathBuf,
+    /// whether to share compiler output across branches and worktrees, see
+    /// [`Config::foundry_artifacts_cache_dir`]
+    pub artifact_store: bool,
     /// where the broadcast logs are stored
     pub broadcast: PathBuf,
     /// additional solc allow paths for `--allow-paths`
@@ -183,6 +204,12 @@ pub struct Config {
     pub solc: Option<SolcReq>,
     /// whether to autodetect the solc compiler version to use
     pub auto_detect_solc: bool,
+    /// The maximum number of solc processes to run concurrently, defaults to the number of logical
+    /// cores.
+    ///
+    /// Sources that need different compiler versions are compiled concurrently. Scripts also
+    /// compile the sources which don't import each other in separate, concurrent solc runs.
+    pub solc_jobs: Option<usize>,
     /// Offline mode, if set, network access (downloading solc) is disallowed.
     ///
     /// Relationship with `auto_detect_solc`:
@@ -211,6 +238,22 @@ pub struct Config {
     /// Multiple etherscan api configs and their aliases
     #[serde(default, skip_serializing_if = "EtherscanConfigs::is_empty")]
     pub etherscan: EtherscanConfigs,
+    /// The contract verification provider to use when `--verifier` is not passed, e.g. `oklink`
+    pub verifier: Option<String>,
+    /// The verifier URL to use when `--verifier-url` is not passed
+    pub verifier_url: Option<String>,
+    /// Verification providers implemented by plugins, by name, which `verifier` and
+    /// `--verifier-plugin` can refer to
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub verifier_plugins: BTreeMap<String, VerifierPluginConfig>,
+    /// Commands run by `forge script` before broadcasting, after each transaction and after the
+    /// run
+    #[serde(default, skip_serializing_if = "ScriptHooksConfig::is_empty")]
+    pub script_hooks: ScriptHooksConfig,
+    /// The links to the address and transaction pages of the block explorers, by chain name or
+    /// id, which are printed next to the addresses and transaction hashes
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub explorer_links: BTreeMap<String, ExplorerLinks>,
     /// list of solidity error codes to always silence in the compiler output
     pub ignored_error_codes: Vec<SolidityErrorCode>,
     /// list of file paths to ignore
@@ -385,6 +428,22 @@ pub struct Config {
     /// Address labels
     pub labels: HashMap<Address, String>,
 
+    /// Script aliases, run with `forge script <alias>`.
+    ///
+    /// Each alias expands to the script path and arguments it's mapped to, e.g.
+    /// `deploy-prod = "script/Deploy.s.sol --chain mainnet --verify --slow"`.
+    pub scripts: BTreeMap<String, String>,
+
+    /// Precompiles and system contracts of the chain which are mocked when simulating scripts, as
+    /// the local EVM doesn't have them.
+    ///
+    /// Usually set in the chain profile of an L2.
+    pub precompiles: Vec<PrecompileConfig>,
+
+    /// Chain ids which scripts only broadcast to once the name of the chain is typed, or the
+    /// chain is confirmed with `--yes-chain`, e.g. `[1, 196]` for production chains.
+    pub confirm_chain_ids: Vec<u64>,
+
     /// The root path where the config detection started from, `Config::with_root`
     #[doc(hidden)]
     //  We're skipping serialization here, so it won't be included in the [`Config::to_string()`]
@@ -427,8 +486,19 @@ impl Config {
     pub const PROFILE_SECTION: &'static str = "profile";
 
     /// Standalone sections in the config which get integrated into the selected profile
-    pub const STANDALONE_SECTIONS: &'static [&'static str] =
-        &["rpc_endpoints", "etherscan", "fmt", "doc", "fuzz", "invariant", "labels"];
+    pub const STANDALONE_SECTIONS: &'static [&'static str] = &[
+        "rpc_endpoints",
+        "etherscan",
+        "fmt",
+        "doc",
+        "fuzz",
+        "invariant",
+        "labels",
+        "scripts",
+        "verifier_plugins",
+        "script_hooks",
+        "explorer_links",
+    ];
 
     /// File name of config toml file
     pub const FILE_NAME: &'static str = "foundry.toml";
@@ -661,7 +731,7 @@ impl Config {
     }
 
     fn create_project(&self, cached: bool, no_artifacts: bool) -> Result<Project, SolcError> {
-        let mut project = Project::builder()
+        let mut builder = Project::builder()
             .artifacts(self.configured_artifacts_handler())
             .paths(self.project_paths())
             .allowed_path(&self.__root.0)
@@ -680,8 +750,11 @@ impl Config {
             .set_offline(self.offline)
             .set_cached(cached && !self.build_info)
             .set_build_info(!no_artifacts && self.build_info)
-            .set_no_artifacts(no_artifacts)
-            .build()?;
+            .set_no_artifacts(no_artifacts);
+        if let Some(jobs) = self.solc_jobs.filter(|jobs| *jobs > 0) {
+            builder = builder.solc_jobs(jobs);
+        }
+        let mut project = builder.build()?;
 
         if self.force {
             project.cleanup()?;
@@ -845,7 +918,11 @@ impl Config {
     /// # }
     /// ```
     pub fn get_rpc_url(&self) -> Option<Result<Cow<str>, UnresolvedEnvVarError>> {
-        let maybe_alias = self.eth_rpc_url.as_ref().or(self.etherscan_api_key.as_ref())?;
+        let Some(maybe_alias) = self.eth_rpc_url.as_ref().or(self.etherscan_api_key.as_ref())
+        else {
+            // the RPC of the custom chain, if one is selected
+            return self.chain_definition(None)?.resolved_rpc().map(|rpc| rpc.map(Cow::Owned))
+        };
         if let Some(alias) = self.get_rpc_url_with_alias(maybe_alias) {
             Some(alias)
         } else {
@@ -873,7 +950,67 @@ impl Config {
         maybe_alias: &str,
     ) -> Option<Result<Cow<str>, UnresolvedEnvVarError>> {
         let mut endpoints = self.rpc_endpoints.clone().resolved();
-        Some(endpoints.remove(maybe_alias)?.map(Cow::Owned))
+        if let Some(endpoint) = endpoints.remove(maybe_alias) {
+            return Some(endpoint.map(Cow::Owned))
+        }
+        // the name of a custom chain
+        ChainDefinitions::load().get(maybe_alias)?.resolved_rpc().map(|rpc| rpc.map(Cow::Owned))
+    }
+
+    /// Returns the definition of the custom chain `chain`, or of the configured chain, if it was
+    /// registered with `forge chain add`.
+    pub fn chain_definition(&self, chain: Option<Chain>) -> Option<ChainDefinition> {
+        let chain = chain.or(self.chain)?;
+        ChainDefinitions::load().find(chain).map(|(_, definition)| definition.clone())
+    }
+
+    /// Returns the links to the block explorer of `chain`, from
+    ///  - the `[explorer_links]` entry of the chain name or id
+    ///  - the explorer of the custom chain, if the chain was registered with `forge chain add`
+    ///  - the browser URL of the `[etherscan]` entry of the chain
+    ///  - the known explorers, OKLink for the chains only it indexes
+    pub fn get_explorer_links(&self, chain: Chain) -> Option<ExplorerLinks> {
+        let configured = self
+            .explorer_links
+            .get(&chain.to_string())
+            .or_else(|| self.explorer_links.get(&chain.id().to_string()));
+        if let Some(links) = configured {
+            return Some(links.clone())
+        }
+        if let Some(url) = self.chain_definition(Some(chain)).and_then(|chain| chain.explorer_url) {
+            return Some(ExplorerLinks::from_base_url(&url))
+        }
+        let browser_url = self
+            .get_etherscan_config_with_chain(Some(chain))
+            .ok()
+            .flatten()
+            .and_then(|etherscan| etherscan.browser_url);
+        browser_url
+            .map(|url| ExplorerLinks::from_base_url(&url))
+            .or_else(|| ExplorerLinks::oklink(chain))
+            .or_else(|| chain.etherscan_urls().map(|(_, url)| ExplorerLinks::from_base_url(url)))
+    }
+
+    /// Returns the urls of the `rpc_endpoints` entry matching the given alias or any of its
+    /// resolved urls, with the primary url first, followed by its fallbacks.
+    ///
+    /// Returns just the given url if it doesn't match any endpoint. Fallbacks referencing unset
+    /// env vars are skipped.
+    pub fn get_rpc_urls_with_fallbacks(&self, alias_or_url: &str) -> Vec<String> {
+        self.rpc_endpoints
+            .iter()
+            .map(|(alias, endpoint)| {
+                let urls = std::iter::once(&endpoint.endpoint)
+                    .chain(&endpoint.fallbacks)
+                    .filter_map(|endpoint| endpoint.clone().resolve().ok())
+                    .collect::<Vec<_>>();
+                (alias, urls)
+            })
+            .find(|(alias, urls)| {
+                *alias == alias_or_url || urls.iter().any(|url| url == alias_or_url)
+            })
+            .map(|(_, urls)| urls)
+            .unwrap_or_else(|| vec![alias_or_url.to_string()])
     }
 
     /// Returns the configured rpc, or the fallback url
@@ -920,6 +1057,7 @@ impl Config {
     ///    an alias
     ///  - the matching `ResolvedEtherscanConfig` of the `etherscan` table if a `chain` is
     ///    configured. an alias
+    ///  - the explorer of the custom chain, if the chain was registered with `forge chain add`
     ///  - the Mainnet  `ResolvedEtherscanConfig` if `etherscan_api_key` is set, `None` otherwise
     ///
     /// # Example
@@ -974,6 +1112,18 @@ impl Config {
             }
         }
 
+        // the explorer of a custom chain
+        if let Some(definition) = self.chain_definition(chain) {
+            if let Some(api_url) = definition.explorer_api.clone() {
+                return Ok(Some(ResolvedEtherscanConfig {
+                    api_url,
+                    browser_url: definition.explorer_url.clone(),
+                    key: self.etherscan_api_key.clone().unwrap_or_default(),
+                    chain: Some(definition.chain()),
+                }))
+            }
+        }
+
         // etherscan fallback via API key
         if let Some(key) = self.etherscan_api_key.as_ref() {
             let chain = chain.or(self.chain).unwrap_or_default();
@@ -1265,6 +1415,42 @@ impl Config {
         })
     }
 
+    /// Sets the `[etherscan]` entry of the given chain inside a `foundry.toml` file but only if it
+    /// exists
+    pub fn update_etherscan(&self, chain: Chain, key: &str, url: Option<&str>) -> eyre::Result<()> {
+        self.update(|doc| {
+            let mut entry = toml_edit::InlineTable::new();
+            entry.insert("key", key.into());
+            if let Some(url) = url {
+                entry.insert("url", url.into());
+            }
+            if chain.named().is_none() {
+                entry.insert("chain", (chain.id() as i64).into());
+            }
+            doc["etherscan"][&chain.to_string()] = toml_edit::value(entry);
+            true
+        })
+    }
+
+    /// Sets the given keys of the `[profile.chain.<chain>]` section inside a `foundry.toml` file
+    /// but only if it exists, see [`ChainProfileProvider`]
+    pub fn update_chain_profile<'a>(
+        &self,
+        chain: Chain,
+        values: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> eyre::Result<()> {
+        self.update(|doc| {
+            let chains = &mut doc[Config::PROFILE_SECTION][ChainProfileProvider::CHAIN_KEY];
+            for (key, value) in values {
+                chains[&chain.to_string()][key] = toml_edit::value(value);
+            }
+            if let Some(chains) = chains.as_table_mut() {
+                chains.set_implicit(true);
+            }
+            true
+        })
+    }
+
     /// Serialize the config type as a String of TOML.
     ///
     /// This serializes to a table with the name of the profile
@@ -1331,6 +1517,19 @@ impl Config {
         Self::foundry_dir().map(|p| p.join("cache"))
     }
 
+    /// Returns the path to foundry's shared compiler output store `~/.foundry/cache/artifacts`
+    pub fn foundry_artifacts_cache_dir() -> Option<PathBuf> {
+        Some(Self::foundry_cache_dir()?.join("artifacts"))
+    }
+
+    /// Returns the directory of the shared compiler output store if `artifact_store` is enabled.
+    pub fn artifact_store_dir(&self) -> Option<PathBuf> {
+        if !self.cache || !self.artifact_store {
+            return None
+        }
+        Self::foundry_artifacts_cache_dir()
+    }
+
     /// Returns the path to foundry rpc cache dir `~/.foundry/cache/rpc`
     pub fn foundry_rpc_cache_dir() -> Option<PathBuf> {
         Some(Self::foundry_cache_dir()?.join("rpc"))
@@ -1845,6 +2044,7 @@ impl Default for Config {
             libs: vec!["lib".into()],
             cache: true,
             cache_path: "cache".into(),
+            artifact_store: false,
             broadcast: "broadcast".into(),
             allow_paths: vec![],
             include_paths: vec![],
@@ -1854,6 +2054,7 @@ impl Default for Config {
             gas_reports_ignore: vec![],
             solc: None,
             auto_detect_solc: true,
+            solc_jobs: None,
             offline: false,
             optimizer: true,
             optimizer_runs: 200,
@@ -1910,6 +2111,11 @@ impl Default for Config {
             rpc_storage_caching: Default::default(),
             rpc_endpoints: Default::default(),
             etherscan: Default::default(),
+            verifier: None,
+            verifier_url: None,
+            verifier_plugins: Default::default(),
+            script_hooks: Default::default(),
+            explorer_links: Default::default(),
             no_storage_caching: false,
             no_rpc_rate_limit: false,
             use_literal_content: false,
@@ -1922,6 +2128,9 @@ impl Default for Config {
             fmt: Default::default(),
             doc: Default::default(),
             labels: Default::default(),
+            scripts: Default::default(),
+            precompiles: Default::default(),
+            confirm_chain_ids: vec![],
             __non_exhaustive: (),
             __warnings: vec![],
         }
@@ -2964,6 +3173,35 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_can_update_verifier_config() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                src = "src"
+            "#,
+            )?;
+
+            let config = Config::load();
+            let chain = Chain::from(NamedChain::Mainnet);
+            config.update_etherscan(chain, "${ETHERSCAN_API_KEY}", None).unwrap();
+            config.update_chain_profile(chain, [("verifier", "oklink")]).unwrap();
+
+            let content = fs::read_to_string(config.get_config_path()).unwrap();
+            assert!(content.contains("[profile.chain.mainnet]"), "{content}");
+            assert!(!content.contains("[profile.chain]\n"), "{content}");
+
+            let config = Config::load();
+            assert!(config.etherscan.contains_key("mainnet"));
+            let overrides =
+                ChainProfileProvider::new(config.get_config_path(), chain).overrides()?.unwrap();
+            assert_eq!(overrides["verifier"], "oklink".into());
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_large_gas_limit() {
         figment::Jail::expect_with(|jail| {
@@ -3139,6 +3377,82 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_chain_profile_overrides() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                eth_rpc_url = "http://localhost:8545"
+                gas_limit = 1000
+
+                [profile.chain.sepolia]
+                eth_rpc_url = "https://sepolia.example.com/"
+
+                [profile.chain.10]
+                gas-limit = 42
+            "#,
+            )?;
+
+            let path = jail.directory().join(Config::FILE_NAME);
+            let load = |chain: NamedChain| {
+                Config::from_provider(
+                    Config::figment().merge(ChainProfileProvider::new(&path, chain.into())),
+                )
+            };
+
+            let config = load(NamedChain::Sepolia);
+            assert_eq!(config.eth_rpc_url, Some("https://sepolia.example.com/".to_string()));
+            assert_eq!(config.gas_limit, 1000.into());
+
+            let config = load(NamedChain::Optimism);
+            assert_eq!(config.eth_rpc_url, Some("http://localhost:8545".to_string()));
+            assert_eq!(config.gas_limit, 42.into());
+
+            let config = load(NamedChain::Mainnet);
+            assert_eq!(config.eth_rpc_url, Some("http://localhost:8545".to_string()));
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_resolve_rpc_url_fallbacks() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [rpc_endpoints]
+                optimism = "https://example.com/"
+                mainnet = ["https://a.example.com/", "${_CONFIG_FALLBACK}", "${_CONFIG_UNSET}"]
+            "#,
+            )?;
+            jail.set_env("_CONFIG_FALLBACK", "https://b.example.com/");
+
+            let config = Config::load();
+            let urls =
+                vec!["https://a.example.com/".to_string(), "https://b.example.com/".to_string()];
+            assert_eq!(config.get_rpc_urls_with_fallbacks("mainnet"), urls);
+            assert_eq!(config.get_rpc_urls_with_fallbacks("https://a.example.com/"), urls);
+            assert_eq!(config.get_rpc_urls_with_fallbacks("https://b.example.com/"), urls);
+            assert_eq!(
+                config.get_rpc_urls_with_fallbacks("optimism"),
+                vec!["https://example.com/".to_string()]
+            );
+            assert_eq!(
+                config.get_rpc_urls_with_fallbacks("http://localhost:8545"),
+                vec!["http://localhost:8545".to_string()]
+            );
+            assert_eq!(
+                config.get_rpc_url_with_alias("mainnet").unwrap().unwrap(),
+                "https://a.example.com/"
+            );
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_resolve_rpc_url() {
         figment::Jail::expect_with(|jail| {
@@ -3273,6 +3587,7 @@ mod tests {
                             retries: Some(3),
                             retry_backoff: Some(1000),
                             compute_units_per_second: Some(1000),
+                            fallbacks: vec![],
                         })
                     ),
                 ]),
@@ -0,0 +1,25 @@
+//! Configuration of the precompiles and system contracts of a chain.
+
+use alloy_primitives::{Address, Bytes, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A contract which a chain provides natively at a fixed address, e.g. a precompile or an L2
+/// system contract, and which is mocked when simulating on a local EVM which doesn't have it.
+///
+/// ```toml
+/// [[profile.chain.arbitrum.precompiles]]
+/// address = "0x0000000000000000000000000000000000000064"
+/// functions = { "0xa3b1b31d" = "0x0000000000000000000000000000000000000000000000000000000000000001" }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrecompileConfig {
+    /// The address of the contract.
+    pub address: Address,
+    /// The data returned by calls which don't match any of the `functions`.
+    #[serde(default)]
+    pub returns: Bytes,
+    /// The data returned by calls, by function selector.
+    #[serde(default)]
+    pub functions: BTreeMap<Selector, Bytes>,
+}
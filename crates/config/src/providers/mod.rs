@@ -1,8 +1,12 @@
 use crate::{Config, Warning, DEPRECATIONS};
+use alloy_chains::Chain;
 use figment::{
+    providers::{Format, Toml},
     value::{Dict, Map, Value},
     Error, Figment, Metadata, Profile, Provider,
 };
+use inflector::Inflector;
+use std::path::PathBuf;
 
 /// Remappings provider
 pub mod remappings;
@@ -143,3 +147,57 @@ impl<P: Provider> Provider for FallbackProfileProvider<P> {
         Some(self.profile.clone())
     }
 }
+
+/// Provides the `[profile.chain.<chain>]` overrides of a `foundry.toml` file for the selected
+/// profile.
+///
+/// The chain can be referred to by its name or its id, e.g. `[profile.chain.mainnet]` or
+/// `[profile.chain.1]`.
+pub struct ChainProfileProvider {
+    path: PathBuf,
+    chain: Chain,
+}
+
+impl ChainProfileProvider {
+    /// The key under `[profile]` holding the per-chain overrides.
+    pub const CHAIN_KEY: &'static str = "chain";
+
+    /// Creates a new provider for the given `foundry.toml` file and chain.
+    pub fn new(path: impl Into<PathBuf>, chain: Chain) -> Self {
+        Self { path: path.into(), chain }
+    }
+
+    /// Returns the overrides for the chain, if any.
+    pub fn overrides(&self) -> Result<Option<Dict>, Error> {
+        if !self.path.exists() {
+            return Ok(None)
+        }
+        let data = Toml::file(&self.path).nested().data()?;
+        let Some(chains) = data
+            .get(&Profile::new(Config::PROFILE_SECTION))
+            .and_then(|profiles| profiles.get(Self::CHAIN_KEY))
+            .and_then(Value::as_dict)
+        else {
+            return Ok(None)
+        };
+
+        Ok(chains
+            .iter()
+            .find(|(key, _)| key.parse::<Chain>().map_or(false, |chain| chain == self.chain))
+            .and_then(|(_, overrides)| overrides.as_dict())
+            .map(|overrides| {
+                overrides.iter().map(|(k, v)| (k.to_snake_case(), v.clone())).collect()
+            }))
+    }
+}
+
+impl Provider for ChainProfileProvider {
+    fn metadata(&self) -> Metadata {
+        Metadata::from(format!("Chain profile `{}`", self.chain), self.path.as_path())
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        let overrides = self.overrides()?.unwrap_or_default();
+        Ok(Map::from([(Config::selected_profile(), overrides)]))
+    }
+}
@@ -0,0 +1,49 @@
+//! Configuration of the commands run by `forge script` around broadcasting.
+
+use serde::{Deserialize, Serialize};
+
+/// Commands run at points of a broadcast, with a JSON payload on their stdin.
+///
+/// ```toml
+/// [script_hooks]
+/// pre_broadcast = { command = "scripts/approve.sh", args = ["--team", "infra"] }
+/// post_tx = { command = "deploy-db", args = ["record-tx"] }
+/// post_run = { command = "deploy-db", args = ["record-run"] }
+/// chain_halt = { command = "scripts/page.sh" }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptHooksConfig {
+    /// Run with the transactions to broadcast before sending any of them. The broadcast is
+    /// aborted if it fails, e.g. to require an approval.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_broadcast: Option<HookCommand>,
+    /// Run with the receipt of each transaction once it's confirmed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_tx: Option<HookCommand>,
+    /// Run with the broadcast transactions and their receipts once the run is complete.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_run: Option<HookCommand>,
+    /// Run when the chain stops producing blocks while broadcasting with `--halt-after`, and
+    /// when it resumes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_halt: Option<HookCommand>,
+}
+
+impl ScriptHooksConfig {
+    pub fn is_empty(&self) -> bool {
+        self.pre_broadcast.is_none() &&
+            self.post_tx.is_none() &&
+            self.post_run.is_none() &&
+            self.chain_halt.is_none()
+    }
+}
+
+/// An external command run by a hook.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HookCommand {
+    /// The executable, looked up in `PATH` or relative to the project root.
+    pub command: String,
+    /// The arguments the executable is run with.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
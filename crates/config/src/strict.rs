@@ -0,0 +1,271 @@
+//! Strict validation of the `foundry.toml` file, see [`Config::strict_issues`]
+
+use crate::{providers::ChainProfileProvider, resolve::RE_PLACEHOLDER, Config};
+use figment::{
+    providers::{Format, Toml},
+    value::{Dict, Value},
+    Error, Profile, Provider,
+};
+use inflector::Inflector;
+use std::{collections::BTreeSet, env, fmt};
+
+/// The keys of an `[etherscan]` entry.
+const ETHERSCAN_KEYS: &[&str] = &["key", "url", "chain"];
+
+/// Profile keys that are valid but not part of the serialized default config.
+const UNSERIALIZED_KEYS: &[&str] = &[
+    "etherscan",
+    "rpc_endpoints",
+    "root",
+    "chain",
+    "script_hooks",
+    "verifier_plugins",
+    "explorer_links",
+];
+
+/// A mistake in the config file that is reported in strict mode.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StrictConfigIssue {
+    /// A key that isn't recognized, e.g. a typo.
+    UnknownKey {
+        /// The section of the key, e.g. `profile.default`
+        section: String,
+        /// The unknown key
+        key: String,
+    },
+    /// A `${VAR}` reference to an environment variable that is not set.
+    UnsetEnvVar {
+        /// The section of the reference
+        section: String,
+        /// The name of the environment variable
+        var: String,
+    },
+    /// The `libraries` setting can't be parsed.
+    InvalidLibraries(String),
+}
+
+impl fmt::Display for StrictConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownKey { section, key } => write!(f, "unknown key `{key}` in [{section}]"),
+            Self::UnsetEnvVar { section, var } => {
+                write!(f, "env var `{var}` referenced in [{section}] is not set")
+            }
+            Self::InvalidLibraries(err) => write!(f, "invalid `libraries`: {err}"),
+        }
+    }
+}
+
+impl Config {
+    /// Checks the sections of `foundry.toml` used by the selected profile for unknown keys, for
+    /// `${VAR}` references to environment variables that are not set, and for malformed
+    /// `libraries`.
+    ///
+    /// `chain_keys` are the keys accepted in `[profile.chain.<chain>]` on top of the config keys.
+    pub fn strict_issues(&self, chain_keys: &[&str]) -> Result<Vec<StrictConfigIssue>, Error> {
+        let mut issues = BTreeSet::new();
+        if let Err(err) = self.parsed_libraries() {
+            issues.insert(StrictConfigIssue::InvalidLibraries(err.to_string()));
+        }
+
+        let path = self.get_config_path();
+        if !path.exists() {
+            return Ok(issues.into_iter().collect())
+        }
+        let data = Toml::file(&path).nested().data()?;
+
+        let mut known = Config::default()
+            .data()?
+            .into_values()
+            .flat_map(|dict| dict.into_keys())
+            .collect::<BTreeSet<_>>();
+        known.extend(UNSERIALIZED_KEYS.iter().map(|key| key.to_string()));
+
+        let mut profiles = vec![Config::DEFAULT_PROFILE];
+        if self.profile != Config::DEFAULT_PROFILE {
+            profiles.push(self.profile.clone());
+        }
+        let sections = data.get(&Profile::new(Config::PROFILE_SECTION));
+        for name in profiles {
+            let Some(profile) = sections
+                .and_then(|sections| sections.iter().find(|(key, _)| Profile::new(key) == name))
+                .and_then(|(_, profile)| profile.as_dict())
+            else {
+                continue
+            };
+
+            let section = format!("{}.{name}", Config::PROFILE_SECTION);
+            check_keys(&section, profile, |key| known.contains(key), &mut issues);
+            find_unset_env_vars(&section, &Value::from(profile.clone()), &mut issues);
+
+            if let Some(etherscan) = profile.get("etherscan").and_then(Value::as_dict) {
+                check_etherscan(&format!("{section}.etherscan"), etherscan, &mut issues);
+            }
+        }
+
+        // `[profile.chain.<chain>]` is parsed as the `chain` profile, see [ChainProfileProvider]
+        let chains = sections
+            .and_then(|sections| sections.get(ChainProfileProvider::CHAIN_KEY))
+            .and_then(Value::as_dict);
+        for (chain, overrides) in chains.into_iter().flatten() {
+            if let Some(overrides) = overrides.as_dict() {
+                let section = format!(
+                    "{}.{}.{chain}",
+                    Config::PROFILE_SECTION,
+                    ChainProfileProvider::CHAIN_KEY
+                );
+                check_keys(
+                    &section,
+                    overrides,
+                    |key| known.contains(key) || chain_keys.contains(&key),
+                    &mut issues,
+                );
+                find_unset_env_vars(&section, &Value::from(overrides.clone()), &mut issues);
+            }
+        }
+
+        if let Some(etherscan) = data.get(&Profile::new("etherscan")) {
+            check_etherscan("etherscan", etherscan, &mut issues);
+            find_unset_env_vars("etherscan", &Value::from(etherscan.clone()), &mut issues);
+        }
+        if let Some(endpoints) = data.get(&Profile::new("rpc_endpoints")) {
+            find_unset_env_vars("rpc_endpoints", &Value::from(endpoints.clone()), &mut issues);
+        }
+
+        Ok(issues.into_iter().collect())
+    }
+}
+
+fn check_keys(
+    section: &str,
+    dict: &Dict,
+    is_known: impl Fn(&str) -> bool,
+    issues: &mut BTreeSet<StrictConfigIssue>,
+) {
+    for key in dict.keys() {
+        if !is_known(&key.to_snake_case()) {
+            issues.insert(StrictConfigIssue::UnknownKey {
+                section: section.to_string(),
+                key: key.clone(),
+            });
+        }
+    }
+}
+
+fn check_etherscan(section: &str, configs: &Dict, issues: &mut BTreeSet<StrictConfigIssue>) {
+    for (alias, config) in configs {
+        if let Some(config) = config.as_dict() {
+            check_keys(
+                &format!("{section}.{alias}"),
+                config,
+                |key| ETHERSCAN_KEYS.contains(&key),
+                issues,
+            );
+        }
+    }
+}
+
+fn find_unset_env_vars(section: &str, value: &Value, issues: &mut BTreeSet<StrictConfigIssue>) {
+    match value {
+        Value::String(_, value) => {
+            for caps in RE_PLACEHOLDER.captures_iter(value) {
+                let var = &caps["inner"];
+                if env::var(var).is_err() {
+                    issues.insert(StrictConfigIssue::UnsetEnvVar {
+                        section: section.to_string(),
+                        var: var.to_string(),
+                    });
+                }
+            }
+        }
+        Value::Dict(_, dict) => {
+            dict.values().for_each(|value| find_unset_env_vars(section, value, issues))
+        }
+        Value::Array(_, values) => {
+            values.iter().for_each(|value| find_unset_env_vars(section, value, issues))
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_find_strict_issues() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                src = "src"
+                oklink_api_kay = "abc"
+                libraries = ["src/Lib.sol:Lib:0x1234567890123456789012345678901234567890"]
+                verifier_plugins = { tronscan = { command = "tronscan-verify" } }
+                explorer_links = { xlayer = { address = "/address/{address}", tx = "/tx/{hash}" } }
+
+                [profile.chain.mainnet]
+                verifier = "oklink"
+                verifer_url = "https://example.com"
+
+                [etherscan]
+                mainnet = { key = "${STRICT_UNSET_KEY}", chian = 1 }
+
+                [rpc_endpoints]
+                mainnet = "https://eth.example.com/${STRICT_RPC_KEY}"
+            "#,
+            )?;
+            jail.set_env("STRICT_RPC_KEY", "secret");
+
+            let issues = Config::load().strict_issues(&["verifier"]).unwrap();
+            let unknown = |section: &str, key: &str| StrictConfigIssue::UnknownKey {
+                section: section.to_string(),
+                key: key.to_string(),
+            };
+            assert_eq!(
+                issues,
+                vec![
+                    unknown("etherscan.mainnet", "chian"),
+                    unknown("profile.default", "oklink_api_kay"),
+                    unknown("profile.chain.mainnet", "verifer_url"),
+                    StrictConfigIssue::UnsetEnvVar {
+                        section: "etherscan".to_string(),
+                        var: "STRICT_UNSET_KEY".to_string(),
+                    },
+                ]
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn validates_applied_chain_overrides() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                src = "src"
+
+                [profile.chain.mainnet]
+                optimizer_rnus = 200
+                verifier = "oklink"
+
+                [profile.chain.196]
+                confirmations = 2
+            "#,
+            )?;
+
+            let issues = Config::load().strict_issues(&["verifier", "confirmations"]).unwrap();
+            assert_eq!(
+                issues,
+                vec![StrictConfigIssue::UnknownKey {
+                    section: "profile.chain.mainnet".to_string(),
+                    key: "optimizer_rnus".to_string(),
+                }]
+            );
+            Ok(())
+        });
+    }
+}
@@ -0,0 +1,23 @@
+//! Configuration of the verification provider plugins.
+
+use serde::{Deserialize, Serialize};
+
+/// A verification provider implemented out-of-tree by an executable, for explorers which aren't
+/// supported natively.
+///
+/// The executable is run with the `args` and the JSON request of an action on its stdin, and
+/// writes the JSON response of the action on its stdout.
+///
+/// ```toml
+/// [verifier_plugins.tronscan]
+/// command = "forge-verify-tronscan"
+/// args = ["--network", "nile"]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifierPluginConfig {
+    /// The executable of the plugin, looked up in `PATH` or relative to the project root.
+    pub command: String,
+    /// The arguments the executable is run with.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
@@ -19,6 +19,8 @@ pub struct DebuggerBuilder {
     sources: ContractSources,
     /// Map of the debugger breakpoints.
     breakpoints: Breakpoints,
+    /// Whether to start at the last step instead of the first one.
+    start_at_end: bool,
 }
 
 impl DebuggerBuilder {
@@ -84,10 +86,20 @@ impl DebuggerBuilder {
         self
     }
 
+    /// Starts the debugger at the end of the execution instead of its first step: at the revert
+    /// in the call it originates from if the execution reverted, or else at the last step.
+    #[inline]
+    pub fn start_at_end(mut self, yes: bool) -> Self {
+        self.start_at_end = yes;
+        self
+    }
+
     /// Builds the debugger.
     #[inline]
     pub fn build(self) -> Debugger {
-        let Self { debug_arena, identified_contracts, sources, breakpoints } = self;
-        Debugger::new(debug_arena, identified_contracts, sources, breakpoints)
+        let Self { debug_arena, identified_contracts, sources, breakpoints, start_at_end } = self;
+        let mut debugger = Debugger::new(debug_arena, identified_contracts, sources, breakpoints);
+        debugger.start_at_end = start_at_end;
+        debugger
     }
 }
@@ -3,7 +3,8 @@
 use crate::{Debugger, ExitReason};
 use alloy_primitives::Address;
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
-use foundry_evm_core::debug::{DebugNodeFlat, DebugStep};
+use foundry_evm_core::debug::{DebugNodeFlat, DebugStep, Instruction};
+use revm::interpreter::opcode;
 use revm_inspectors::tracing::types::CallKind;
 use std::{cell::RefCell, ops::ControlFlow};
 
@@ -83,6 +84,11 @@ impl<'a> DebuggerContext<'a> {
     }
 
     pub(crate) fn init(&mut self) {
+        if self.debugger.start_at_end && !self.debug_arena().is_empty() {
+            self.draw_memory.inner_call_index =
+                revert_origin(self.debug_arena()).unwrap_or_else(|| self.debug_arena().len() - 1);
+            self.current_step = self.debug_steps().len().saturating_sub(1);
+        }
         self.gen_opcode_list();
     }
 
@@ -289,6 +295,10 @@ impl DebuggerContext<'_> {
                 }
                 self.key_buffer.clear();
             }
+            // Go to next breakpoint
+            KeyCode::Char('n') => self.goto_adjacent_breakpoint(true),
+            // Go to previous breakpoint
+            KeyCode::Char('N') => self.goto_adjacent_breakpoint(false),
             // toggle stack labels
             KeyCode::Char('t') => self.stack_labels = !self.stack_labels,
             // toggle memory utf8 decoding
@@ -305,22 +315,70 @@ impl DebuggerContext<'_> {
     }
 
     fn handle_breakpoint(&mut self, c: char) {
-        // Find the location of the called breakpoint in the whole debug arena (at this address with
-        // this pc)
-        if let Some((caller, pc)) = self.debugger.breakpoints.get(&c) {
-            for (i, node) in self.debug_arena().iter().enumerate() {
-                if node.address == *caller {
-                    if let Some(step) = node.steps.iter().position(|step| step.pc == *pc) {
-                        self.draw_memory.inner_call_index = i;
-                        self.current_step = step;
-                        break;
-                    }
-                }
+        if let Some((caller, pc)) = self.debugger.breakpoints.get(&c.to_string()) {
+            if let Some((call, step)) = self.breakpoint_location(caller, *pc) {
+                self.draw_memory.inner_call_index = call;
+                self.current_step = step;
             }
         }
         self.key_buffer.clear();
     }
 
+    /// Jumps to the next breakpoint in execution order, or to the previous one if `forward` is
+    /// false, wrapping around at the last and first ones.
+    fn goto_adjacent_breakpoint(&mut self, forward: bool) {
+        let current = (self.draw_memory.inner_call_index, self.current_step);
+        let locations = self.breakpoint_locations();
+        let location = if forward {
+            locations.iter().find(|(call, step, _)| (*call, *step) > current).or(locations.first())
+        } else {
+            locations
+                .iter()
+                .rev()
+                .find(|(call, step, _)| (*call, *step) < current)
+                .or(locations.last())
+        };
+        if let Some(&(call, step, _)) = location {
+            self.draw_memory.inner_call_index = call;
+            self.current_step = step;
+        }
+        self.key_buffer.clear();
+    }
+
+    /// Returns the label of the breakpoint at the current step, if any.
+    pub(crate) fn current_breakpoint(&self) -> Option<&str> {
+        let current = (self.draw_memory.inner_call_index, self.current_step);
+        self.breakpoint_locations()
+            .into_iter()
+            .find(|(call, step, _)| (*call, *step) == current)
+            .map(|(_, _, label)| label)
+    }
+
+    /// Returns the locations of the breakpoints as (call index, step, label), in execution order.
+    fn breakpoint_locations(&self) -> Vec<(usize, usize, &str)> {
+        let mut locations = self
+            .debugger
+            .breakpoints
+            .iter()
+            .filter_map(|(label, (caller, pc))| {
+                let (call, step) = self.breakpoint_location(caller, *pc)?;
+                Some((call, step, label.as_str()))
+            })
+            .collect::<Vec<_>>();
+        locations.sort_unstable();
+        locations
+    }
+
+    /// Finds the location of a breakpoint in the whole debug arena (at this address with this pc).
+    fn breakpoint_location(&self, caller: &Address, pc: usize) -> Option<(usize, usize)> {
+        self.debug_arena().iter().enumerate().find_map(|(i, node)| {
+            if node.address != *caller {
+                return None
+            }
+            node.steps.iter().position(|step| step.pc == pc).map(|step| (i, step))
+        })
+    }
+
     fn handle_mouse_event(&mut self, event: MouseEvent) -> ControlFlow<ExitReason> {
         match event.kind {
             MouseEventKind::ScrollUp => {
@@ -357,3 +415,118 @@ fn buffer_as_number(s: &str, default_value: usize) -> usize {
         _ => default_value,
     }
 }
+
+/// Returns the index of the call the revert at the end of the execution originates from.
+///
+/// The calls are in execution order, and a call which reverts with the data of the call that
+/// reverted right before it only bubbles up its revert. Returns `None` if the execution doesn't
+/// end with a revert.
+fn revert_origin(arena: &[DebugNodeFlat]) -> Option<usize> {
+    let mut origin = arena.len().checked_sub(1)?;
+    let mut data = revert_data(&arena[origin])?;
+    while let Some(previous) = origin.checked_sub(1) {
+        match revert_data(&arena[previous]) {
+            Some(previous_data) if previous_data == data => {
+                origin = previous;
+                data = previous_data;
+            }
+            _ => break,
+        }
+    }
+    Some(origin)
+}
+
+/// Returns the data the call reverted with, if its last step is a revert.
+fn revert_data(node: &DebugNodeFlat) -> Option<&[u8]> {
+    let step = node.steps.last()?;
+    match step.instruction {
+        Instruction::OpCode(opcode::REVERT) => {
+            let [.., size, offset] = step.stack[..] else { return None };
+            let len = step.memory.len();
+            let start = offset.saturating_to::<usize>().min(len);
+            let end = start.saturating_add(size.saturating_to()).min(len);
+            Some(&step.memory[start..end])
+        }
+        Instruction::OpCode(opcode::INVALID) => Some(&[]),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+
+    fn node(op: u8, memory: &[u8]) -> DebugNodeFlat {
+        let step = DebugStep {
+            stack: vec![U256::from(memory.len()), U256::ZERO],
+            memory: memory.to_vec(),
+            instruction: Instruction::OpCode(op),
+            ..Default::default()
+        };
+        DebugNodeFlat::new(Address::ZERO, CallKind::Call, vec![step])
+    }
+
+    #[test]
+    fn goes_to_adjacent_breakpoints() {
+        let step = |pc| DebugStep { pc, ..Default::default() };
+        let a = Address::repeat_byte(1);
+        let b = Address::repeat_byte(2);
+        let arena = vec![
+            DebugNodeFlat::new(a, CallKind::Call, vec![step(0), step(1), step(2)]),
+            DebugNodeFlat::new(b, CallKind::Call, vec![step(5), step(6)]),
+        ];
+        let breakpoints = [
+            ("last".to_string(), (b, 6)),
+            ("first".to_string(), (a, 1)),
+            ("second".to_string(), (a, 2)),
+            ("missing".to_string(), (b, 9)),
+        ]
+        .into();
+        let mut debugger =
+            Debugger::new(arena, Default::default(), Default::default(), breakpoints);
+        let mut context = DebuggerContext::new(&mut debugger);
+        let location = |context: &DebuggerContext<'_>| {
+            (context.draw_memory.inner_call_index, context.current_step)
+        };
+
+        // in execution order, whatever the order of the labels
+        context.goto_adjacent_breakpoint(true);
+        assert_eq!(location(&context), (0, 1));
+        assert_eq!(context.current_breakpoint(), Some("first"));
+        context.goto_adjacent_breakpoint(true);
+        assert_eq!(location(&context), (0, 2));
+        context.goto_adjacent_breakpoint(true);
+        assert_eq!(location(&context), (1, 1));
+        assert_eq!(context.current_breakpoint(), Some("last"));
+
+        // wrapping around at both ends
+        context.goto_adjacent_breakpoint(true);
+        assert_eq!(location(&context), (0, 1));
+        context.goto_adjacent_breakpoint(false);
+        assert_eq!(location(&context), (1, 1));
+        context.goto_adjacent_breakpoint(false);
+        assert_eq!(location(&context), (0, 2));
+    }
+
+    #[test]
+    fn finds_revert_origin() {
+        assert_eq!(revert_origin(&[]), None);
+        assert_eq!(revert_origin(&[node(opcode::STOP, &[])]), None);
+
+        // a caught revert, a call which reverts and the caller bubbling it up
+        let arena = [
+            node(opcode::CALL, &[]),
+            node(opcode::REVERT, &[1]),
+            node(opcode::CALL, &[]),
+            node(opcode::REVERT, &[2]),
+            node(opcode::REVERT, &[2]),
+        ];
+        assert_eq!(revert_origin(&arena), Some(3));
+
+        // the caller reverts with its own data
+        let arena =
+            [node(opcode::CALL, &[]), node(opcode::REVERT, &[1]), node(opcode::REVERT, &[2])];
+        assert_eq!(revert_origin(&arena), Some(2));
+    }
+}
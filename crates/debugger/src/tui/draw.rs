@@ -185,7 +185,7 @@ impl DebuggerContext<'_> {
 
     fn draw_footer(&self, f: &mut Frame<'_>, area: Rect) {
         let l1 = "[q]: quit | [k/j]: prev/next op | [a/s]: prev/next jump | [c/C]: prev/next call | [g/G]: start/end | [b]: cycle memory/calldata/returndata buffers";
-        let l2 = "[t]: stack labels | [m]: buffer decoding | [shift + j/k]: scroll stack | [ctrl + j/k]: scroll buffer | ['<char>]: goto breakpoint | [h] toggle help";
+        let l2 = "[t]: stack labels | [m]: buffer decoding | [shift + j/k]: scroll stack | [ctrl + j/k]: scroll buffer | ['<char>]: goto breakpoint | [n/N]: next/prev breakpoint | [h] toggle help";
         let dimmed = Style::new().add_modifier(Modifier::DIM);
         let lines =
             vec![Line::from(Span::styled(l1, dimmed)), Line::from(Span::styled(l2, dimmed))];
@@ -439,12 +439,15 @@ impl DebuggerContext<'_> {
         // Add one more "phantom" line so we see line where current segment execution ends
         add_new_line(self.opcode_list.len());
 
-        let title = format!(
+        let mut title = format!(
             "Address: {} | PC: {} | Gas used in call: {}",
             self.address(),
             self.current_step().pc,
             self.current_step().total_gas_used,
         );
+        if let Some(label) = self.current_breakpoint() {
+            title.push_str(&format!(" | Breakpoint: {label}"));
+        }
         let block = Block::default().title(title).borders(Borders::ALL);
         let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
         f.render_widget(paragraph, area);
@@ -49,6 +49,8 @@ pub struct Debugger {
     /// A mapping of source -> (PC -> IC map for deploy code, PC -> IC map for runtime code)
     pc_ic_maps: BTreeMap<String, (PcIcMap, PcIcMap)>,
     breakpoints: Breakpoints,
+    /// Whether to start at the last step instead of the first one.
+    start_at_end: bool,
 }
 
 impl Debugger {
@@ -77,7 +79,14 @@ impl Debugger {
                 ))
             })
             .collect();
-        Self { debug_arena, identified_contracts, contracts_sources, pc_ic_maps, breakpoints }
+        Self {
+            debug_arena,
+            identified_contracts,
+            contracts_sources,
+            pc_ic_maps,
+            breakpoints,
+            start_at_end: false,
+        }
     }
 
     /// Starts the debugger TUI. Terminates the current process on failure or user exit.
@@ -47,6 +47,8 @@ pub enum DatabaseError {
          For a test environment, you can use `etch` to place the required bytecode at that address."
     )]
     MissingCreate2Deployer,
+    #[error("inconsistent RPC response: {0}")]
+    InconsistentRpc(String),
     #[error("{0}")]
     Other(String),
 }
@@ -78,6 +80,7 @@ impl DatabaseError {
             Self::Message(_) |
             Self::BlockNotFound(_) |
             Self::TransactionNotFound(_) |
+            Self::InconsistentRpc(_) |
             Self::MissingCreate2Deployer => None,
             DatabaseError::Other(_) => None,
         }
@@ -92,6 +95,34 @@ impl DatabaseError {
             .map(|err| err.to_string().to_lowercase().contains(GETH_MESSAGE))
             .unwrap_or(false)
     }
+
+    /// Whether the error is caused by responses of the RPC which are inconsistent with each
+    /// other, e.g. from nodes at different heights behind a load-balancer, or by the RPC missing
+    /// the state of the forked block.
+    pub fn is_unreliable_rpc_error(&self) -> bool {
+        match self {
+            Self::InconsistentRpc(_) => true,
+            _ => self.get_rpc_error().map_or(false, is_unreliable_rpc_response),
+        }
+    }
+}
+
+/// Messages of RPC errors about blocks or state which the node doesn't have, although they're
+/// older than the forked block.
+const UNRELIABLE_RPC_MESSAGES: [&str; 6] = [
+    "header not found",
+    "unknown block",
+    "state not available",
+    "state is not available",
+    "state histories haven't been fully indexed",
+    "inconsistent rpc response",
+];
+
+/// Whether an RPC error indicates that the RPC is unreliable, see
+/// [DatabaseError::is_unreliable_rpc_error].
+pub(crate) fn is_unreliable_rpc_response(err: &eyre::Error) -> bool {
+    let message = format!("{err:#}").to_lowercase();
+    UNRELIABLE_RPC_MESSAGES.iter().any(|unreliable| message.contains(unreliable))
 }
 
 impl From<tokio::task::JoinError> for DatabaseError {
@@ -30,6 +30,7 @@ mod diagnostic;
 pub use diagnostic::RevertDiagnostic;
 
 mod error;
+pub(crate) use error::is_unreliable_rpc_response;
 pub use error::{DatabaseError, DatabaseResult};
 
 mod cow;
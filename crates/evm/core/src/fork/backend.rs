@@ -5,9 +5,9 @@ use crate::{
 };
 use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
 use alloy_providers::tmp::TempProvider;
-use alloy_rpc_types::{Block, BlockId, Transaction};
+use alloy_rpc_types::{Block, BlockId, BlockNumberOrTag, Transaction};
 use eyre::WrapErr;
-use foundry_common::NON_ARCHIVE_NODE_WARNING;
+use foundry_common::{NON_ARCHIVE_NODE_WARNING, UNRELIABLE_RPC_WARNING};
 use futures::{
     channel::mpsc::{channel, Receiver, Sender},
     stream::Stream,
@@ -119,6 +119,14 @@ where
         }
     }
 
+    /// Returns the number of the block the data is fetched from, if it's pinned to a number.
+    fn pinned_number(&self) -> Option<u64> {
+        match self.block_id {
+            Some(BlockId::Number(BlockNumberOrTag::Number(number))) => Some(number),
+            _ => None,
+        }
+    }
+
     /// handle the request in queue in the future.
     ///
     /// We always check:
@@ -222,6 +230,14 @@ where
         let fut = Box::pin(async move {
             let block =
                 provider.get_block(number, true).await.wrap_err("could not fetch block {number:?}");
+            let block = block.and_then(|block| {
+                if let (BlockId::Number(BlockNumberOrTag::Number(requested)), Some(block)) =
+                    (number, &block)
+                {
+                    check_block_number(block, requested)?;
+                }
+                Ok(block)
+            });
             (sender, block, number)
         });
 
@@ -252,6 +268,7 @@ where
                 trace!(target: "backendhandler", number, "preparing block hash request");
                 entry.insert(vec![listener]);
                 let provider = self.provider.clone();
+                let pinned = self.pinned_number();
                 let fut = Box::pin(async move {
                     let block = provider
                         .get_block_by_number(number.into(), false)
@@ -259,10 +276,20 @@ where
                         .wrap_err("failed to get block");
 
                     let block_hash = match block {
-                        Ok(Some(block)) => Ok(block
-                            .header
-                            .hash
-                            .expect("empty block hash on mined block, this should never happen")),
+                        Ok(Some(block)) => check_block_number(&block, number).map(|_| {
+                            block
+                                .header
+                                .hash
+                                .expect("empty block hash on mined block, this should never happen")
+                        }),
+                        // blocks up to the forked one must exist
+                        Ok(None) if pinned.map_or(false, |pinned| number <= pinned) => {
+                            Err(eyre::eyre!(
+                                "inconsistent RPC response: block {number} was not found, but the \
+                                 fork is at block {}",
+                                pinned.unwrap_or_default()
+                            ))
+                        }
                         Ok(None) => {
                             warn!(target: "backendhandler", ?number, "block not found");
                             // if no block was returned then the block does not exist, in which case
@@ -282,6 +309,16 @@ where
     }
 }
 
+/// Fails if the RPC returned another block than the requested one.
+fn check_block_number(block: &Block, requested: u64) -> eyre::Result<()> {
+    match block.header.number {
+        Some(number) if number != U256::from(requested) => Err(eyre::eyre!(
+            "inconsistent RPC response: requested block {requested}, but got block {number}"
+        )),
+        _ => Ok(()),
+    }
+}
+
 impl<P> Future for BackendHandler<P>
 where
     P: TempProvider + Clone + Unpin + 'static,
@@ -621,6 +658,19 @@ impl SharedBackend {
     }
 
     /// Flushes the DB to disk if caching is enabled
+    /// Logs why a request failed if the RPC is the likely cause, and makes the error say so if
+    /// the RPC is unreliable.
+    fn diagnose(err: DatabaseError) -> DatabaseError {
+        if err.is_possibly_non_archive_node_error() {
+            error!(target: "sharedbackend", "{NON_ARCHIVE_NODE_WARNING}");
+        }
+        if err.is_unreliable_rpc_error() {
+            error!(target: "sharedbackend", "{UNRELIABLE_RPC_WARNING}");
+            return DatabaseError::InconsistentRpc(format!("{err}\n{UNRELIABLE_RPC_WARNING}"));
+        }
+        err
+    }
+
     pub(crate) fn flush_cache(&self) {
         self.cache.0.flush();
     }
@@ -633,10 +683,7 @@ impl DatabaseRef for SharedBackend {
         trace!(target: "sharedbackend", %address, "request basic");
         self.do_get_basic(address).map_err(|err| {
             error!(target: "sharedbackend", %err, %address, "Failed to send/recv `basic`");
-            if err.is_possibly_non_archive_node_error() {
-                error!(target: "sharedbackend", "{NON_ARCHIVE_NODE_WARNING}");
-            }
-            err
+            Self::diagnose(err)
         })
     }
 
@@ -648,10 +695,7 @@ impl DatabaseRef for SharedBackend {
         trace!(target: "sharedbackend", "request storage {:?} at {:?}", address, index);
         self.do_get_storage(address, index).map_err(|err| {
             error!(target: "sharedbackend", %err, %address, %index, "Failed to send/recv `storage`");
-            if err.is_possibly_non_archive_node_error() {
-                error!(target: "sharedbackend", "{NON_ARCHIVE_NODE_WARNING}");
-            }
-          err
+            Self::diagnose(err)
         })
     }
 
@@ -664,10 +708,7 @@ impl DatabaseRef for SharedBackend {
         trace!(target: "sharedbackend", "request block hash for number {:?}", number);
         self.do_get_block_hash(number).map_err(|err| {
             error!(target: "sharedbackend", %err, %number, "Failed to send/recv `block_hash`");
-            if err.is_possibly_non_archive_node_error() {
-                error!(target: "sharedbackend", "{NON_ARCHIVE_NODE_WARNING}");
-            }
-            err
+            Self::diagnose(err)
         })
     }
 }
@@ -686,6 +727,20 @@ mod tests {
 
     const ENDPOINT: Option<&str> = option_env!("ETH_RPC_URL");
 
+    #[test]
+    fn diagnoses_unreliable_rpcs() {
+        let address = Address::ZERO;
+        let err = DatabaseError::GetAccount(address, Arc::new(eyre::eyre!("header not found")));
+        assert!(err.is_unreliable_rpc_error());
+        let err = SharedBackend::diagnose(err);
+        assert!(matches!(err, DatabaseError::InconsistentRpc(_)));
+        assert!(err.to_string().contains(UNRELIABLE_RPC_WARNING));
+
+        let err = DatabaseError::GetAccount(address, Arc::new(eyre::eyre!("rate limited")));
+        assert!(!err.is_unreliable_rpc_error());
+        assert!(matches!(SharedBackend::diagnose(err), DatabaseError::GetAccount(..)));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn shared_backend() {
         let Some(endpoint) = ENDPOINT else { return };
@@ -1,9 +1,11 @@
-use crate::utils::apply_chain_and_block_specific_env_changes;
+use crate::{
+    backend::is_unreliable_rpc_response, utils::apply_chain_and_block_specific_env_changes,
+};
 use alloy_primitives::{Address, U256};
 use alloy_providers::tmp::TempProvider;
 use alloy_rpc_types::{Block, BlockNumberOrTag};
 use eyre::WrapErr;
-use foundry_common::NON_ARCHIVE_NODE_WARNING;
+use foundry_common::{NON_ARCHIVE_NODE_WARNING, UNRELIABLE_RPC_WARNING};
 
 use revm::primitives::{BlockEnv, CfgEnv, Env, TxEnv};
 
@@ -28,11 +30,35 @@ pub async fn environment<P: TempProvider>(
         provider.get_gas_price(),
         provider.get_chain_id(),
         provider.get_block_by_number(BlockNumberOrTag::Number(block_number), false)
-    )?;
+    )
+    .map_err(|err| {
+        let err = eyre::Report::from(err);
+        if is_unreliable_rpc_response(&err) {
+            err.wrap_err(format!("Failed to get block {block_number}\n{UNRELIABLE_RPC_WARNING}"))
+        } else {
+            err
+        }
+    })?;
     let block = if let Some(block) = block {
+        if block.header.number != Some(U256::from(block_number)) {
+            eyre::bail!(
+                "Requested block {block_number}, but the RPC returned block {:?}\n\
+                 {UNRELIABLE_RPC_WARNING}",
+                block.header.number
+            );
+        }
         block
     } else {
         if let Ok(latest_block) = provider.get_block_number().await {
+            // The block number was just returned by the RPC as its latest, if it doesn't have the
+            // block it's serving the requests from nodes at different heights.
+            if pin_block.is_none() {
+                eyre::bail!(
+                    "The RPC returned block {block_number} as its latest block, but not the \
+                     block itself, and now returns {latest_block} as its latest block\n\
+                     {UNRELIABLE_RPC_WARNING}"
+                );
+            }
             // If the `eth_getBlockByNumber` call succeeds, but returns null instead of
             // the block, and the block number is less than equal the latest block, then
             // the user is forking from a non-archive node with an older block number.
@@ -1,5 +1,5 @@
 use alloy_json_abi::{Function, JsonAbi};
-use alloy_primitives::{FixedBytes, U256};
+use alloy_primitives::{Address, FixedBytes, U256};
 use alloy_rpc_types::{Block, Transaction};
 use eyre::ContextCompat;
 use foundry_config::NamedChain;
@@ -91,6 +91,20 @@ pub fn configure_tx_env(env: &mut revm::primitives::Env, tx: &Transaction) {
     env.tx.transact_to = tx.to.map(TransactTo::Call).unwrap_or_else(TransactTo::create)
 }
 
+/// Returns the address an L1 contract sends L1→L2 messages from, on OP stack and Arbitrum chains.
+pub fn apply_l2_alias(address: Address) -> Address {
+    let aliased = U256::from_be_slice(address.as_slice())
+        .wrapping_add(U256::from_be_slice(crate::constants::L2_ALIAS_OFFSET.as_slice()));
+    Address::from_word(aliased.into())
+}
+
+/// Returns the L1 contract which sends L1→L2 messages from the given address.
+pub fn undo_l2_alias(address: Address) -> Address {
+    let unaliased = U256::from_be_slice(address.as_slice())
+        .wrapping_sub(U256::from_be_slice(crate::constants::L2_ALIAS_OFFSET.as_slice()));
+    Address::from_word(unaliased.into())
+}
+
 /// Get the gas used, accounting for refunds
 pub fn gas_used(spec: SpecId, spent: u64, refunded: u64) -> u64 {
     let refund_quotient = if SpecId::enabled(spec, SpecId::LONDON) { 5 } else { 2 };
@@ -148,4 +162,13 @@ mod tests {
         let result = evm.transact().unwrap();
         assert!(result.result.is_success());
     }
+
+    #[test]
+    fn aliases_l1_addresses() {
+        let l1 = alloy_primitives::address!("ffffffffffffffffffffffffffffffffffffffff");
+        let l2 = apply_l2_alias(l1);
+        // The offset wraps around.
+        assert_eq!(l2, alloy_primitives::address!("1111000000000000000000000000000000001110"));
+        assert_eq!(undo_l2_alias(l2), l1);
+    }
 }
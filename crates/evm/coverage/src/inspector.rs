@@ -1,4 +1,5 @@
 use crate::{HitMap, HitMaps};
+use alloy_primitives::keccak256;
 use revm::{interpreter::Interpreter, Database, EvmContext, Inspector};
 
 #[derive(Clone, Debug, Default)]
@@ -10,6 +11,11 @@ pub struct CoverageCollector {
 impl<DB: Database> Inspector<DB> for CoverageCollector {
     #[inline]
     fn initialize_interp(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        // The init code of `CREATE` frames isn't hashed, which would make all of them share the
+        // same hit map, so hash it once here for `step` to use.
+        if interp.contract.hash.is_zero() {
+            interp.contract.hash = keccak256(interp.contract.bytecode.original_bytecode());
+        }
         let hash = interp.contract.hash;
         self.maps
             .entry(hash)
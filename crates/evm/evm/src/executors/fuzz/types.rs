@@ -18,7 +18,7 @@ pub struct CaseOutcome {
     pub coverage: Option<HitMaps>,
     /// The debug nodes of the call
     pub debug: Option<DebugArena>,
-    /// Breakpoints char pc map
+    /// The breakpoints hit during the call, by label
     pub breakpoints: Breakpoints,
 }
 
@@ -31,7 +31,7 @@ pub struct CounterExampleOutcome {
     pub exit_reason: InstructionResult,
     /// The debug nodes of the call
     pub debug: Option<DebugArena>,
-    /// Breakpoints char pc map
+    /// The breakpoints hit during the call, by label
     pub breakpoints: Breakpoints,
 }
 
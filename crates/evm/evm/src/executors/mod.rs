@@ -381,6 +381,7 @@ impl Executor {
             labels,
             traces,
             debug,
+            state_changeset,
             env,
             coverage,
             ..
@@ -435,7 +436,17 @@ impl Executor {
 
         trace!(address=?address, "deployed contract");
 
-        Ok(DeployResult { address, gas_used, gas_refunded, logs, traces, debug, env, coverage })
+        Ok(DeployResult {
+            address,
+            gas_used,
+            gas_refunded,
+            logs,
+            traces,
+            debug,
+            state_changeset,
+            env,
+            coverage,
+        })
     }
 
     /// Deploys a contract and commits the new state to the underlying database.
@@ -637,6 +648,8 @@ pub struct DeployResult {
     pub traces: Option<CallTraceArena>,
     /// The debug nodes of the call
     pub debug: Option<DebugArena>,
+    /// The changeset of the state, which was committed to the database.
+    pub state_changeset: Option<StateChangeset>,
     /// The `revm::Env` after deployment
     pub env: EnvWithHandlerCfg,
     /// The coverage info collected during the deployment
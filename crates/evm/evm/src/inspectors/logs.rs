@@ -9,6 +9,25 @@ use revm::{
     interpreter::{CallInputs, CallOutcome, Gas, InstructionResult, InterpreterResult},
     Database, EvmContext, Inspector,
 };
+use std::{fmt, sync::Arc};
+
+/// A callback which is invoked with each `console.log` message as soon as it's logged, e.g. to
+/// stream the logs of a long running execution.
+#[derive(Clone)]
+pub struct ConsoleListener(Arc<dyn Fn(&str) + Send + Sync>);
+
+impl ConsoleListener {
+    /// Creates a new listener from the given callback.
+    pub fn new(f: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+}
+
+impl fmt::Debug for ConsoleListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ConsoleListener").finish_non_exhaustive()
+    }
+}
 
 /// An inspector that collects logs during execution.
 ///
@@ -17,6 +36,8 @@ use revm::{
 pub struct LogCollector {
     /// The collected logs. Includes both `LOG` opcodes and Hardhat-style logs.
     pub logs: Vec<Log>,
+    /// The listener notified of each Hardhat-style log.
+    pub listener: Option<ConsoleListener>,
 }
 
 impl LogCollector {
@@ -31,7 +52,11 @@ impl LogCollector {
         };
 
         // Convert the decoded call to a DS `log(string)` event
-        self.logs.push(convert_hh_log_to_event(decoded));
+        let fmt = decoded.fmt(Default::default());
+        if let Some(listener) = &self.listener {
+            (listener.0)(&fmt);
+        }
+        self.logs.push(convert_hh_log_to_event(fmt));
 
         (InstructionResult::Continue, Bytes::new())
     }
@@ -66,10 +91,10 @@ impl<DB: Database> Inspector<DB> for LogCollector {
     }
 }
 
-/// Converts a call to Hardhat's `console.log` to a DSTest `log(string)` event.
-fn convert_hh_log_to_event(call: HardhatConsole::HardhatConsoleCalls) -> Log {
-    // Convert the parameters of the call to their string representation using `ConsoleFmt`.
-    let fmt = call.fmt(Default::default());
+/// Converts the message of a call to Hardhat's `console.log` to a DSTest `log(string)` event.
+///
+/// The message is the string representation of the parameters of the call, using `ConsoleFmt`.
+fn convert_hh_log_to_event(fmt: String) -> Log {
     Log::new(Address::default(), vec![Console::log::SIGNATURE_HASH], fmt.abi_encode().into())
         .unwrap_or_else(|| Log { ..Default::default() })
 }
@@ -14,7 +14,7 @@ mod debugger;
 pub use debugger::Debugger;
 
 mod logs;
-pub use logs::LogCollector;
+pub use logs::{ConsoleListener, LogCollector};
 
 mod printer;
 pub use printer::TracePrinter;
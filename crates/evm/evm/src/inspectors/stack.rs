@@ -1,6 +1,6 @@
 use super::{
-    Cheatcodes, CheatsConfig, ChiselState, CoverageCollector, Debugger, Fuzzer, LogCollector,
-    StackSnapshotType, TracePrinter, TracingInspector, TracingInspectorConfig,
+    Cheatcodes, CheatsConfig, ChiselState, ConsoleListener, CoverageCollector, Debugger, Fuzzer,
+    LogCollector, StackSnapshotType, TracePrinter, TracingInspector, TracingInspectorConfig,
 };
 use alloy_primitives::{Address, Bytes, Log, U256};
 use foundry_evm_core::{backend::DatabaseExt, debug::DebugArena};
@@ -39,6 +39,8 @@ pub struct InspectorStackBuilder {
     pub debug: Option<bool>,
     /// Whether logs should be collected.
     pub logs: Option<bool>,
+    /// The listener notified of each `console.log` message, if logs are collected.
+    pub console_listener: Option<ConsoleListener>,
     /// Whether coverage info should be collected.
     pub coverage: Option<bool>,
     /// Whether to print all opcode traces into the console. Useful for debugging the EVM.
@@ -100,6 +102,13 @@ impl InspectorStackBuilder {
         self
     }
 
+    /// Set the listener to notify of each `console.log` message as soon as it's logged.
+    #[inline]
+    pub fn console_listener(mut self, listener: ConsoleListener) -> Self {
+        self.console_listener = Some(listener);
+        self
+    }
+
     /// Set whether to collect coverage information.
     #[inline]
     pub fn coverage(mut self, yes: bool) -> Self {
@@ -148,6 +157,7 @@ impl InspectorStackBuilder {
             trace,
             debug,
             logs,
+            console_listener,
             coverage,
             print,
             chisel_state,
@@ -167,6 +177,9 @@ impl InspectorStackBuilder {
         }
         stack.collect_coverage(coverage.unwrap_or(false));
         stack.collect_logs(logs.unwrap_or(true));
+        if let Some(log_collector) = &mut stack.log_collector {
+            log_collector.listener = console_listener;
+        }
         stack.enable_debugger(debug.unwrap_or(false));
         stack.print(print.unwrap_or(false));
         stack.tracing(trace.unwrap_or(false));
@@ -10,6 +10,7 @@ use alloy_primitives::{Address, LogData, Selector, B256};
 use foundry_common::{
     abi::get_indexed_event, fmt::format_token, ContractsByArtifact, SELECTOR_LEN,
 };
+use foundry_compilers::ArtifactId;
 use foundry_evm_core::{
     abi::{Console, HardhatConsole, Vm, HARDHAT_CONSOLE_SELECTOR_PATCHES},
     constants::{
@@ -20,7 +21,10 @@ use foundry_evm_core::{
 };
 use itertools::Itertools;
 use once_cell::sync::OnceCell;
-use std::collections::{hash_map::Entry, BTreeMap, HashMap};
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap},
+    sync::Arc,
+};
 
 mod precompiles;
 
@@ -62,6 +66,16 @@ impl CallTraceDecoderBuilder {
         self
     }
 
+    /// Add known contracts to the decoder, collecting the ABI of an address identified as one of
+    /// them only when a call, event or error of the address is decoded.
+    ///
+    /// See [LazyAbis].
+    #[inline]
+    pub fn with_lazy_known_contracts(mut self, contracts: Arc<ContractsByArtifact>) -> Self {
+        self.decoder.lazy_abis = Some(LazyAbis::new(contracts));
+        self
+    }
+
     /// Add known contracts to the decoder from a `LocalTraceIdentifier`.
     #[inline]
     pub fn with_local_identifier_abis(self, identifier: &LocalTraceIdentifier<'_>) -> Self {
@@ -113,6 +127,8 @@ pub struct CallTraceDecoder {
     pub events: BTreeMap<(B256, usize), Vec<Event>>,
     /// Revert decoder. Contains all known custom errors.
     pub revert_decoder: RevertDecoder,
+    /// ABIs of the addresses identified as known contracts, collected on first use.
+    pub lazy_abis: Option<LazyAbis>,
 
     /// A signature identifier for events and functions.
     pub signature_identifier: Option<SingleSignaturesIdentifier>,
@@ -177,6 +193,7 @@ impl CallTraceDecoder {
                 .map(|event| ((event.selector(), indexed_inputs(&event)), vec![event]))
                 .collect(),
             revert_decoder: Default::default(),
+            lazy_abis: None,
 
             signature_identifier: None,
             verbosity: 0,
@@ -193,6 +210,9 @@ impl CallTraceDecoder {
         }
 
         self.receive_contracts.clear();
+        if let Some(lazy_abis) = &mut self.lazy_abis {
+            lazy_abis.addresses.clear();
+        }
     }
 
     /// Identify unknown addresses in the specified call trace using the specified identifier.
@@ -254,7 +274,7 @@ impl CallTraceDecoder {
         }
 
         trace!(target: "evm::traces", len=identities.len(), "collecting address identities");
-        for AddressIdentity { address, label, contract, abi, artifact_id: _ } in identities {
+        for AddressIdentity { address, label, contract, abi, artifact_id } in identities {
             let _span = trace_span!(target: "evm::traces", "identity", ?contract, ?label).entered();
 
             if let Some(contract) = contract {
@@ -266,7 +286,15 @@ impl CallTraceDecoder {
             }
 
             if let Some(abi) = abi {
-                self.collect_abi(&abi, Some(&address));
+                let deferred = match (&mut self.lazy_abis, &artifact_id) {
+                    (Some(lazy_abis), Some(id)) => lazy_abis.insert(address, id),
+                    _ => false,
+                };
+                if !deferred {
+                    self.collect_abi(&abi, Some(&address));
+                } else if abi.receive.is_some() {
+                    self.receive_contracts.push(address);
+                }
             }
         }
     }
@@ -319,7 +347,7 @@ impl CallTraceDecoder {
         if cdata.len() >= SELECTOR_LEN {
             let selector = &cdata[..SELECTOR_LEN];
             let mut functions = Vec::new();
-            let functions = match self.functions.get(selector) {
+            let functions = match self.function(&trace.address, selector) {
                 Some(fs) => fs,
                 None => {
                     if let Some(identifier) = &self.signature_identifier {
@@ -350,7 +378,10 @@ impl CallTraceDecoder {
             DecodedCallTrace {
                 label,
                 return_data: if !trace.success {
-                    Some(self.revert_decoder.decode(&trace.output, Some(trace.status)))
+                    Some(
+                        self.revert_decoder(&trace.address, &trace.output)
+                            .decode(&trace.output, Some(trace.status)),
+                    )
                 } else {
                     None
                 },
@@ -384,7 +415,9 @@ impl CallTraceDecoder {
     /// Custom decoding for cheatcode inputs.
     fn decode_cheatcode_inputs(&self, func: &Function, data: &[u8]) -> Option<Vec<String>> {
         match func.name.as_str() {
-            "expectRevert" => Some(vec![self.revert_decoder.decode(data, None)]),
+            "expectRevert" => {
+                Some(vec![self.revert_decoder(&CHEATCODE_ADDRESS, data).decode(data, None)])
+            }
             "addr" | "createWallet" | "deriveKey" | "rememberKey" => {
                 // Redact private key in all cases
                 Some(vec!["<pk>".to_string()])
@@ -511,7 +544,7 @@ impl CallTraceDecoder {
 
             None
         } else {
-            Some(self.revert_decoder.decode(data, Some(trace.status)))
+            Some(self.revert_decoder(&trace.address, data).decode(data, Some(trace.status)))
         }
     }
 
@@ -527,12 +560,16 @@ impl CallTraceDecoder {
         .map(Into::into)
     }
 
-    /// Decodes an event.
-    pub async fn decode_event<'a>(&self, log: &'a LogData) -> DecodedCallLog<'a> {
+    /// Decodes an event emitted by `address`.
+    pub async fn decode_event<'a>(
+        &self,
+        address: &Address,
+        log: &'a LogData,
+    ) -> DecodedCallLog<'a> {
         let &[t0, ..] = log.topics() else { return DecodedCallLog::Raw(log) };
 
         let mut events = Vec::new();
-        let events = match self.events.get(&(t0, log.topics().len() - 1)) {
+        let events = match self.event(address, &(t0, log.topics().len() - 1)) {
             Some(es) => es,
             None => {
                 if let Some(identifier) = &self.signature_identifier {
@@ -580,13 +617,51 @@ impl CallTraceDecoder {
             .filter_map(|n| match n.trace.address.0 .0 {
                 DEFAULT_CREATE2_DEPLOYER_BYTES => None,
                 [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01..=0x0a] => None,
-                _ => n.trace.data.get(..SELECTOR_LEN),
+                _ => n.trace.data.get(..SELECTOR_LEN).map(|selector| (&n.trace.address, selector)),
             })
-            .filter(|v| !self.functions.contains_key(*v))
+            .filter(|(address, selector)| self.function(address, selector).is_none())
+            .map(|(_, selector)| selector)
             .unique();
         identifier.write().await.identify_functions(funcs_it).await;
     }
 
+    /// Returns the known functions with the given selector, looking in the ABI of `address` first
+    /// if it's collected lazily.
+    pub fn function(&self, address: &Address, selector: &[u8]) -> Option<&Vec<Function>> {
+        self.lazy_abis
+            .as_ref()
+            .and_then(|lazy_abis| lazy_abis.get(address)?.functions.get(selector))
+            .or_else(|| self.functions.get(selector))
+    }
+
+    /// Returns the known events with the given selector and number of indexed inputs, looking in
+    /// the ABI of `address` first if it's collected lazily.
+    fn event(&self, address: &Address, key: &(B256, usize)) -> Option<&Vec<Event>> {
+        self.lazy_abis
+            .as_ref()
+            .and_then(|lazy_abis| lazy_abis.get(address)?.events.get(key))
+            .or_else(|| self.events.get(key))
+    }
+
+    /// Returns the revert decoder for the given revert data of a call to `address`.
+    ///
+    /// The errors of the lazily collected ABIs are only used if the decoder doesn't know the
+    /// selector of the error itself, so that errors pushed after the collection are still used.
+    /// Errors bubble up from nested calls, so the ABIs of the other identified addresses are
+    /// searched if the one of `address` doesn't have the error.
+    fn revert_decoder(&self, address: &Address, data: &[u8]) -> &RevertDecoder {
+        if let (Some(lazy_abis), Some(selector)) = (&self.lazy_abis, data.get(..SELECTOR_LEN)) {
+            if !self.revert_decoder.errors.contains_key(selector) {
+                let has_error =
+                    |abi: &CollectedAbi| abi.revert_decoder.errors.contains_key(selector);
+                if let Some(abi) = lazy_abis.find(address, has_error) {
+                    return &abi.revert_decoder
+                }
+            }
+        }
+        &self.revert_decoder
+    }
+
     fn apply_label(&self, value: &DynSolValue) -> String {
         if let DynSolValue::Address(addr) = value {
             if let Some(label) = self.labels.get(addr) {
@@ -597,6 +672,87 @@ impl CallTraceDecoder {
     }
 }
 
+/// ABIs of the addresses identified as known contracts, which are only collected when the
+/// decoder needs them.
+///
+/// Collecting the ABIs of every contract of a large project takes a while, and most of them are
+/// not needed: the artifact of each identified address is recorded, and its ABI is only collected
+/// the first time a call, event or error of the address is decoded, then cached for the address.
+#[derive(Clone, Debug)]
+pub struct LazyAbis {
+    contracts: Arc<ContractsByArtifact>,
+    addresses: HashMap<Address, Arc<LazyAbi>>,
+}
+
+/// The artifact of an identified address, and its ABI once collected.
+#[derive(Debug)]
+struct LazyAbi {
+    artifact: ArtifactId,
+    collected: OnceCell<CollectedAbi>,
+}
+
+#[derive(Debug, Default)]
+struct CollectedAbi {
+    functions: HashMap<Selector, Vec<Function>>,
+    events: BTreeMap<(B256, usize), Vec<Event>>,
+    revert_decoder: RevertDecoder,
+}
+
+impl LazyAbis {
+    /// Creates a new instance which collects the ABIs of `contracts` on first use.
+    pub fn new(contracts: Arc<ContractsByArtifact>) -> Self {
+        Self { contracts, addresses: Default::default() }
+    }
+
+    /// Whether the ABI of `address` was collected.
+    pub fn is_collected(&self, address: &Address) -> bool {
+        self.addresses.get(address).map_or(false, |lazy| lazy.collected.get().is_some())
+    }
+
+    /// Records that `address` is an instance of `artifact`.
+    ///
+    /// Returns `false` if the artifact isn't one of the known contracts.
+    fn insert(&mut self, address: Address, artifact: &ArtifactId) -> bool {
+        if !self.contracts.contains_key(artifact) {
+            return false
+        }
+        self.addresses.entry(address).or_insert_with(|| {
+            Arc::new(LazyAbi { artifact: artifact.clone(), collected: OnceCell::new() })
+        });
+        true
+    }
+
+    /// Returns the ABI of `address`, collecting it on first use.
+    fn get(&self, address: &Address) -> Option<&CollectedAbi> {
+        let lazy = self.addresses.get(address)?;
+        Some(lazy.collected.get_or_init(|| {
+            let artifact = lazy.artifact.identifier();
+            trace!(target: "evm::traces", %address, %artifact, "collecting ABI");
+            let mut collected = CollectedAbi::default();
+            let Some((abi, _)) = self.contracts.get(&lazy.artifact) else { return collected };
+            for function in abi.functions() {
+                collected.functions.entry(function.selector()).or_default().push(function.clone());
+            }
+            for event in abi.events() {
+                let key = (event.selector(), indexed_inputs(event));
+                collected.events.entry(key).or_default().push(event.clone());
+            }
+            for error in abi.errors() {
+                collected.revert_decoder.push_error(error.clone());
+            }
+            collected
+        }))
+    }
+
+    /// Returns the first ABI matching `f`, among the one of `address` and then the ones of the
+    /// other identified addresses.
+    fn find(&self, address: &Address, f: impl Fn(&CollectedAbi) -> bool) -> Option<&CollectedAbi> {
+        self.get(address).filter(|abi| f(abi)).or_else(|| {
+            self.addresses.keys().filter_map(|address| self.get(address)).find(|abi| f(abi))
+        })
+    }
+}
+
 /// Restore the order of the params of a decoded event,
 /// as Alloy returns the indexed and unindexed params separately.
 fn reconstruct_params(event: &Event, decoded: &DecodedEvent) -> Vec<DynSolValue> {
@@ -624,6 +780,7 @@ fn indexed_inputs(event: &Event) -> usize {
 mod tests {
     use super::*;
     use alloy_primitives::hex;
+    use std::borrow::Cow;
 
     #[test]
     fn test_should_redact_pk() {
@@ -703,4 +860,80 @@ mod tests {
             assert_eq!(result, expected, "Output case failed for: {}", function_signature);
         }
     }
+
+    fn artifact_id(name: &str) -> ArtifactId {
+        ArtifactId {
+            path: format!("out/{name}.sol/{name}.json").into(),
+            name: name.to_string(),
+            source: format!("src/{name}.sol").into(),
+            version: "0.8.23".parse().unwrap(),
+        }
+    }
+
+    /// Returns a decoder collecting the ABIs lazily, with `address` identified as `abi`.
+    fn lazy_decoder(address: Address, abi: JsonAbi) -> CallTraceDecoder {
+        let id = artifact_id("Token");
+        let contracts =
+            ContractsByArtifact([(id.clone(), (abi.clone(), Default::default()))].into());
+        let mut decoder =
+            CallTraceDecoderBuilder::new().with_lazy_known_contracts(Arc::new(contracts)).build();
+        decoder.collect_identities(vec![AddressIdentity {
+            address,
+            label: Some(id.name.clone()),
+            contract: Some(id.identifier()),
+            abi: Some(Cow::Owned(abi)),
+            artifact_id: Some(id),
+        }]);
+        decoder
+    }
+
+    #[test]
+    fn collects_known_abis_lazily() {
+        let function = Function::parse("transfer(address,uint256)").unwrap();
+        let mut abi = JsonAbi::default();
+        abi.functions.insert(function.name.clone(), vec![function.clone()]);
+        let token = Address::repeat_byte(1);
+
+        let decoder = lazy_decoder(token, abi);
+        assert!(decoder.functions.get(function.selector().as_slice()).is_none());
+        let lazy_abis = decoder.lazy_abis.as_ref().unwrap();
+        assert!(!lazy_abis.is_collected(&token));
+
+        // only the ABI of the called address is collected, once
+        let other = Address::repeat_byte(2);
+        assert!(decoder.function(&other, function.selector().as_slice()).is_none());
+        assert!(!lazy_abis.is_collected(&token));
+        assert_eq!(
+            decoder.function(&token, function.selector().as_slice()),
+            Some(&vec![function.clone()])
+        );
+        assert!(lazy_abis.is_collected(&token));
+        assert!(decoder.function(&token, &[0; 4]).is_none());
+
+        // clearing the addresses drops their ABIs
+        let mut decoder = decoder.clone();
+        decoder.clear_addresses();
+        assert!(decoder.function(&token, function.selector().as_slice()).is_none());
+    }
+
+    #[test]
+    fn decodes_errors_pushed_after_collecting_abis() {
+        let known = Error::parse("Unauthorized()").unwrap();
+        let mut abi = JsonAbi::default();
+        abi.errors.insert(known.name.clone(), vec![known.clone()]);
+        let token = Address::repeat_byte(1);
+
+        let mut decoder = lazy_decoder(token, abi);
+        let data = known.selector().to_vec();
+        assert_eq!(decoder.revert_decoder(&token, &data).decode(&data, None), "Unauthorized()");
+        assert!(decoder.lazy_abis.as_ref().unwrap().is_collected(&token));
+        // the error bubbles up to the callers of the address
+        let caller = Address::repeat_byte(2);
+        assert_eq!(decoder.revert_decoder(&caller, &data).decode(&data, None), "Unauthorized()");
+
+        let pushed = Error::parse("Paused()").unwrap();
+        decoder.push_error(pushed.clone());
+        let data = pushed.selector().to_vec();
+        assert_eq!(decoder.revert_decoder(&token, &data).decode(&data, None), "Paused()");
+    }
 }
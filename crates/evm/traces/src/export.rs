@@ -0,0 +1,256 @@
+//! Exports of decoded call traces, as structured call trees and as flamegraphs.
+
+use crate::{CallKind, CallTraceArena, CallTraceDecoder, DecodedCallData};
+use alloy_primitives::{Address, U256};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A decoded call and its subcalls.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedCall {
+    pub kind: &'static str,
+    pub address: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// The function signature, or the selector if it couldn't be decoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    pub args: Vec<String>,
+    pub value: U256,
+    pub gas_used: u64,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    pub calls: Vec<ExportedCall>,
+}
+
+impl ExportedCall {
+    /// The name of the call in flamegraphs, e.g. `Counter::increment`.
+    pub fn frame_name(&self) -> String {
+        let target = match &self.label {
+            Some(label) => label.clone(),
+            None => self.address.to_checksum(None),
+        };
+        match &self.function {
+            Some(function) => {
+                format!("{target}::{}", function.split('(').next().unwrap_or(function))
+            }
+            None if self.kind.starts_with("CREATE") => format!("new {target}"),
+            None => target,
+        }
+    }
+}
+
+/// Decodes the calls of `arena` into a call tree.
+///
+/// Returns `None` if the arena is empty.
+pub async fn export_trace_arena(
+    arena: &CallTraceArena,
+    decoder: &CallTraceDecoder,
+) -> Option<ExportedCall> {
+    decoder.prefetch_signatures(arena.nodes()).await;
+
+    let mut calls = Vec::with_capacity(arena.nodes().len());
+    for node in arena.nodes() {
+        let trace = &node.trace;
+        let decoded = decoder.decode_function(trace).await;
+        let (function, args) = match decoded.func {
+            Some(DecodedCallData { signature, args }) => (Some(signature), args),
+            None if trace.kind.is_any_create() => (None, Vec::new()),
+            None if trace.data.len() < 4 => (None, Vec::new()),
+            None => (Some(hex::encode_prefixed(&trace.data[..4])), Vec::new()),
+        };
+        calls.push(ExportedCall {
+            kind: call_kind_name(trace.kind),
+            address: trace.address,
+            label: decoded.label,
+            function,
+            args,
+            value: trace.value,
+            gas_used: trace.gas_used,
+            success: trace.success,
+            output: decoded.return_data,
+            calls: Vec::new(),
+        });
+    }
+
+    fn build(
+        arena: &CallTraceArena,
+        calls: &mut [Option<ExportedCall>],
+        idx: usize,
+    ) -> ExportedCall {
+        let mut call = calls[idx].take().expect("each node is visited once");
+        call.calls =
+            arena.nodes()[idx].children.iter().map(|child| build(arena, calls, *child)).collect();
+        call
+    }
+
+    let mut calls = calls.into_iter().map(Some).collect::<Vec<_>>();
+    (!calls.is_empty()).then(|| build(arena, &mut calls, 0))
+}
+
+fn call_kind_name(kind: CallKind) -> &'static str {
+    match kind {
+        CallKind::Call => "CALL",
+        CallKind::StaticCall => "STATICCALL",
+        CallKind::CallCode => "CALLCODE",
+        CallKind::DelegateCall => "DELEGATECALL",
+        CallKind::Create => "CREATE",
+        CallKind::Create2 => "CREATE2",
+    }
+}
+
+/// A flamegraph of call trees in the [speedscope](https://www.speedscope.app) file format, where
+/// the weight of each call is its gas usage.
+#[derive(Debug, Default, Serialize)]
+pub struct Speedscope {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    name: String,
+    exporter: &'static str,
+    shared: SpeedscopeShared,
+    profiles: Vec<SpeedscopeProfile>,
+    #[serde(skip)]
+    frame_ids: HashMap<String, usize>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct SpeedscopeShared {
+    frames: Vec<SpeedscopeFrame>,
+}
+
+#[derive(Debug, Serialize)]
+struct SpeedscopeFrame {
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpeedscopeProfile {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    name: String,
+    unit: &'static str,
+    start_value: u64,
+    end_value: u64,
+    events: Vec<SpeedscopeEvent>,
+}
+
+#[derive(Debug, Serialize)]
+struct SpeedscopeEvent {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    frame: usize,
+    at: u64,
+}
+
+impl Speedscope {
+    /// Creates an empty flamegraph file.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            schema: "https://www.speedscope.app/file-format-schema.json",
+            name: name.into(),
+            exporter: "forge",
+            ..Default::default()
+        }
+    }
+
+    /// Adds a profile of the call tree `call`.
+    ///
+    /// Subcalls are laid out one after the other from the start of their parent, as the trace
+    /// doesn't record when each of them started.
+    pub fn add_profile(&mut self, name: impl Into<String>, call: &ExportedCall) {
+        let mut events = Vec::new();
+        self.add_call(call, 0, call.gas_used, &mut events);
+        self.profiles.push(SpeedscopeProfile {
+            kind: "evented",
+            name: name.into(),
+            unit: "none",
+            start_value: 0,
+            end_value: call.gas_used,
+            events,
+        });
+    }
+
+    fn add_call(
+        &mut self,
+        call: &ExportedCall,
+        at: u64,
+        max: u64,
+        events: &mut Vec<SpeedscopeEvent>,
+    ) {
+        let frame = self.frame_id(call.frame_name());
+        let end = at.saturating_add(call.gas_used).min(max);
+        events.push(SpeedscopeEvent { kind: "O", frame, at });
+        let mut child_at = at;
+        for child in &call.calls {
+            self.add_call(child, child_at, end, events);
+            child_at = child_at.saturating_add(child.gas_used).min(end);
+        }
+        events.push(SpeedscopeEvent { kind: "C", frame, at: end });
+    }
+
+    fn frame_id(&mut self, name: String) -> usize {
+        if let Some(id) = self.frame_ids.get(&name) {
+            return *id
+        }
+        let id = self.shared.frames.len();
+        self.shared.frames.push(SpeedscopeFrame { name: name.clone() });
+        self.frame_ids.insert(name, id);
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(label: &str, function: &str, gas_used: u64, calls: Vec<ExportedCall>) -> ExportedCall {
+        ExportedCall {
+            kind: "CALL",
+            address: Address::ZERO,
+            label: Some(label.to_string()),
+            function: Some(function.to_string()),
+            args: Vec::new(),
+            value: U256::ZERO,
+            gas_used,
+            success: true,
+            output: None,
+            calls,
+        }
+    }
+
+    #[test]
+    fn can_build_speedscope_profiles() {
+        let tree = call(
+            "Deploy",
+            "run()",
+            100,
+            vec![
+                call("Token", "mint(address,uint256)", 40, vec![]),
+                call("Token", "mint(address,uint256)", 80, vec![]),
+            ],
+        );
+
+        let mut speedscope = Speedscope::new("Deploy.s.sol");
+        speedscope.add_profile("execution", &tree);
+        assert_eq!(
+            speedscope.shared.frames.iter().map(|frame| frame.name.as_str()).collect::<Vec<_>>(),
+            ["Deploy::run", "Token::mint"]
+        );
+
+        let profile = &speedscope.profiles[0];
+        assert_eq!(profile.end_value, 100);
+        let events = profile
+            .events
+            .iter()
+            .map(|event| (event.kind, event.frame, event.at))
+            .collect::<Vec<_>>();
+        // the second subcall is clamped to the end of its parent
+        assert_eq!(
+            events,
+            [("O", 0, 0), ("O", 1, 0), ("C", 1, 40), ("O", 1, 40), ("C", 1, 100), ("C", 0, 100)]
+        );
+    }
+}
@@ -7,7 +7,7 @@
 #[macro_use]
 extern crate tracing;
 
-use alloy_primitives::LogData;
+use alloy_primitives::{Address, LogData};
 use foundry_common::contracts::{ContractsByAddress, ContractsByArtifact};
 use foundry_evm_core::constants::CHEATCODE_ADDRESS;
 use futures::{future::BoxFuture, FutureExt};
@@ -24,6 +24,8 @@ use identifier::LocalTraceIdentifier;
 mod decoder;
 pub use decoder::{CallTraceDecoder, CallTraceDecoderBuilder};
 
+pub mod export;
+
 use revm_inspectors::tracing::types::LogCallOrder;
 pub use revm_inspectors::tracing::{
     types::{CallKind, CallTrace, CallTraceNode},
@@ -94,7 +96,9 @@ pub async fn render_trace_arena(
             for child in &node.ordering {
                 match child {
                     LogCallOrder::Log(index) => {
-                        let log = render_trace_log(&node.logs[*index], decoder).await?;
+                        let log =
+                            render_trace_log(&node.trace.address, &node.logs[*index], decoder)
+                                .await?;
 
                         // Prepend our tree structure symbols to each line of the displayed log
                         log.lines().enumerate().try_for_each(|(i, line)| {
@@ -216,11 +220,12 @@ pub async fn render_trace(
 
 /// Render a trace log.
 async fn render_trace_log(
+    address: &Address,
     log: &LogData,
     decoder: &CallTraceDecoder,
 ) -> Result<String, std::fmt::Error> {
     let mut s = String::new();
-    let decoded = decoder.decode_event(log).await;
+    let decoded = decoder.decode_event(address, log).await;
 
     match decoded {
         DecodedCallLog::Raw(log) => {
@@ -1,4 +1,7 @@
-use super::{install, watch::WatchArgs};
+use super::{
+    install,
+    watch::{self, WatchArgs},
+};
 use clap::Parser;
 use eyre::Result;
 use foundry_cli::{opts::CoreBuildArgs, utils::LoadConfig};
@@ -91,7 +94,8 @@ impl BuildArgs {
             .print_names(self.names)
             .print_sizes(self.sizes)
             .quiet(self.format_json)
-            .bail(!self.format_json);
+            .bail(!self.format_json)
+            .artifact_store(config.artifact_store_dir());
         if let Some(skip) = self.skip {
             if !skip.is_empty() {
                 compiler = compiler.filter(Box::new(SkipBuildFilters::new(skip)?));
@@ -124,7 +128,7 @@ impl BuildArgs {
     /// bootstrap a new [`watchexe::Watchexec`] loop.
     pub(crate) fn watchexec_config(&self) -> Result<(InitConfig, RuntimeConfig)> {
         // use the path arguments or if none where provided the `src` dir
-        self.watch.watchexec_config(|| {
+        watch::watchexec_config(&self.watch, || {
             let config = Config::from(self);
             vec![config.src, config.test, config.script]
         })
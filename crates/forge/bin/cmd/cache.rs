@@ -4,6 +4,7 @@ use clap::{
     Arg, Command, Parser, Subcommand,
 };
 use eyre::Result;
+use foundry_common::artifact_store::{self, ArtifactStore};
 use foundry_config::{cache, Chain, Config, NamedChain};
 use std::{ffi::OsStr, str::FromStr};
 use strum::VariantNames;
@@ -22,6 +23,10 @@ pub enum CacheSubcommands {
 
     /// Shows cached data from the global foundry directory.
     Ls(LsArgs),
+
+    /// Removes the least recently used snapshots of the shared compiler output store, see
+    /// `artifact_store` in the config.
+    PruneArtifacts(PruneArtifactsArgs),
 }
 
 /// CLI arguments for `forge clean`.
@@ -106,6 +111,27 @@ impl LsArgs {
     }
 }
 
+/// CLI arguments for `forge cache prune-artifacts`.
+#[derive(Debug, Parser)]
+pub struct PruneArtifactsArgs {
+    /// The size to shrink the store to, in MiB.
+    ///
+    /// Builds shrink the store to the default size after saving a snapshot.
+    #[arg(long, value_name = "MIB", default_value_t = artifact_store::DEFAULT_MAX_SIZE >> 20)]
+    max_size: u64,
+}
+
+impl PruneArtifactsArgs {
+    pub fn run(self) -> Result<()> {
+        let Some(dir) = Config::foundry_artifacts_cache_dir() else {
+            eyre::bail!("failed to get the artifact store directory")
+        };
+        let (removed, freed) = ArtifactStore::prune(&dir, self.max_size << 20)?;
+        println!("Removed {removed} snapshots, freed {} MiB", freed >> 20);
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum ChainOrAll {
     NamedChain(NamedChain),
@@ -185,4 +211,11 @@ mod tests {
         let args: CacheArgs = CacheArgs::parse_from(["cache", "ls"]);
         assert!(matches!(args.sub, CacheSubcommands::Ls(_)));
     }
+
+    #[test]
+    fn can_parse_cache_prune_artifacts() {
+        let args = CacheArgs::parse_from(["cache", "prune-artifacts", "--max-size", "512"]);
+        let CacheSubcommands::PruneArtifacts(args) = args.sub else { panic!("expected prune") };
+        assert_eq!(args.max_size, 512);
+    }
 }
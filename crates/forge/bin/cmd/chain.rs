@@ -0,0 +1,152 @@
+use clap::{Parser, Subcommand};
+use eyre::Result;
+use forge_verify::provider::VerificationProviderType;
+use foundry_config::{ChainDefinition, ChainDefinitions, NamedChain};
+use std::path::PathBuf;
+
+/// CLI arguments for `forge chain`.
+#[derive(Clone, Debug, Parser)]
+pub struct ChainArgs {
+    #[command(subcommand)]
+    pub sub: ChainSubcommands,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum ChainSubcommands {
+    /// Register a custom chain, so that it can be selected by name with `--chain`.
+    Add(AddChainArgs),
+
+    /// List the custom chains.
+    #[command(visible_alias = "ls")]
+    List,
+
+    /// Remove a custom chain.
+    #[command(visible_alias = "rm")]
+    Remove {
+        /// The name of the chain.
+        name: String,
+    },
+}
+
+impl ChainArgs {
+    pub fn run(self) -> Result<()> {
+        let path = chains_path()?;
+        let mut chains = ChainDefinitions::load_from(&path)?;
+        match self.sub {
+            ChainSubcommands::Add(args) => {
+                let name = args.name.clone();
+                let replaced = chains.insert(name.clone(), args.into_definition()?).is_some();
+                chains.save_to(&path)?;
+                let action = if replaced { "Updated" } else { "Added" };
+                println!("{action} chain `{name}` in {}", path.display());
+            }
+            ChainSubcommands::List => {
+                if chains.is_empty() {
+                    println!("No custom chains, add one with `forge chain add`");
+                }
+                for (name, definition) in chains.iter() {
+                    println!("{name} ({})", definition.id);
+                    let fields = [
+                        ("rpc", &definition.rpc),
+                        ("explorer api", &definition.explorer_api),
+                        ("explorer", &definition.explorer_url),
+                        ("verifier", &definition.verifier),
+                    ];
+                    for (field, value) in fields {
+                        if let Some(value) = value {
+                            println!("  {field}: {value}");
+                        }
+                    }
+                }
+            }
+            ChainSubcommands::Remove { name } => {
+                if chains.remove(&name).is_none() {
+                    eyre::bail!("Unknown chain `{name}`")
+                }
+                chains.save_to(&path)?;
+                println!("Removed chain `{name}`");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// CLI arguments for `forge chain add`.
+#[derive(Clone, Debug, Parser)]
+pub struct AddChainArgs {
+    /// The name the chain is selected with, e.g. `xlayer-testnet`.
+    #[arg(long)]
+    pub name: String,
+
+    /// The chain ID.
+    #[arg(long)]
+    pub id: u64,
+
+    /// The RPC URL, which may reference environment variables, e.g. `${XLAYER_RPC}`.
+    #[arg(long, value_name = "URL")]
+    pub rpc: Option<String>,
+
+    /// The API URL of the block explorer, used to verify contracts.
+    #[arg(long, value_name = "URL")]
+    pub explorer_api: Option<String>,
+
+    /// The URL of the block explorer.
+    #[arg(long, value_name = "URL")]
+    pub explorer_url: Option<String>,
+
+    /// The verification provider of the block explorer.
+    #[arg(long, value_name = "VERIFIER")]
+    pub verifier: Option<VerificationProviderType>,
+}
+
+impl AddChainArgs {
+    fn into_definition(self) -> Result<ChainDefinition> {
+        let Self { name, id, rpc, explorer_api, explorer_url, verifier } = self;
+        if name.parse::<u64>().is_ok() || name.parse::<NamedChain>().is_ok() {
+            eyre::bail!("`{name}` is already a known chain")
+        }
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || "-_".contains(c)) {
+            eyre::bail!("Invalid chain name `{name}`")
+        }
+        Ok(ChainDefinition {
+            id,
+            rpc,
+            explorer_api,
+            explorer_url,
+            verifier: verifier.map(|verifier| verifier.to_string()),
+        })
+    }
+}
+
+fn chains_path() -> Result<PathBuf> {
+    ChainDefinitions::path().ok_or_else(|| eyre::eyre!("Failed to find the home directory"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_chain_add() {
+        let args = ChainArgs::parse_from([
+            "chain",
+            "add",
+            "--name",
+            "xlayer-testnet",
+            "--id",
+            "195",
+            "--rpc",
+            "https://testrpc.xlayer.tech",
+            "--verifier",
+            "oklink",
+        ]);
+        let ChainSubcommands::Add(args) = args.sub else { panic!("expected chain add") };
+        let definition = args.into_definition().unwrap();
+        assert_eq!(definition.id, 195);
+        assert_eq!(definition.verifier.as_deref(), Some("oklink"));
+
+        let args = ChainArgs::parse_from(["chain", "add", "--name", "sepolia", "--id", "195"]);
+        let ChainSubcommands::Add(args) = args.sub else { panic!("expected chain add") };
+        assert!(args.into_definition().is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use clap::{Parser, ValueEnum};
+use clap_complete::Shell;
+use eyre::Result;
+use foundry_cli::{opts::ProjectPathsArgs, utils::LoadConfig};
+use foundry_compilers::cache::SolFilesCache;
+use std::collections::BTreeSet;
+
+/// CLI arguments for `forge __complete`.
+///
+/// This is called by the completion scripts of `forge completions` to complete values which depend
+/// on the current project, based on the compiler cache.
+#[derive(Clone, Debug, Parser)]
+pub struct CompleteArgs {
+    /// The kind of values to complete.
+    #[arg(value_enum)]
+    kind: CompletionKind,
+
+    /// The word being completed.
+    #[arg(default_value = "", allow_hyphen_values = true)]
+    current: String,
+
+    #[command(flatten)]
+    opts: ProjectPathsArgs,
+}
+
+foundry_config::impl_figment_convert!(CompleteArgs, opts);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CompletionKind {
+    /// Script paths and the names of the contracts in them.
+    Scripts,
+    /// Contract names.
+    Contracts,
+}
+
+impl CompleteArgs {
+    pub fn run(self) -> Result<()> {
+        // Completions are best effort, so there's nothing to complete if the config is invalid or
+        // the project wasn't compiled yet.
+        let Ok(config) = self.try_load_config() else { return Ok(()) };
+        let paths = config.project_paths();
+        let Ok(cache) = SolFilesCache::read_joined(&paths) else { return Ok(()) };
+
+        let mut candidates = BTreeSet::new();
+        for (path, entry) in &cache.files {
+            if self.kind == CompletionKind::Scripts {
+                if !path.starts_with(&paths.scripts) {
+                    continue
+                }
+                let path = path.strip_prefix(&paths.root).unwrap_or(path);
+                candidates.insert(path.display().to_string());
+            }
+            candidates.extend(entry.artifacts.keys().cloned());
+        }
+
+        for candidate in candidates.iter().filter(|c| c.starts_with(&self.current)) {
+            println!("{candidate}");
+        }
+        Ok(())
+    }
+}
+
+/// Returns the shell code which completes `forge script <PATH>` and `forge script --tc <NAME>`
+/// with `forge __complete`, to be appended to the completions generated by clap.
+pub fn dynamic_completions(shell: Shell) -> Option<&'static str> {
+    match shell {
+        Shell::Bash => Some(BASH),
+        Shell::Zsh => Some(ZSH),
+        Shell::Fish => Some(FISH),
+        _ => None,
+    }
+}
+
+const BASH: &str = r#"
+_forge_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" prev="${COMP_WORDS[COMP_CWORD-1]}"
+    if [[ "${COMP_WORDS[1]}" == "script" ]]; then
+        if [[ "$prev" == "--tc" || "$prev" == "--target-contract" ]]; then
+            COMPREPLY=($(forge __complete contracts "$cur" 2>/dev/null))
+            return 0
+        elif [[ $COMP_CWORD -eq 2 && "$cur" != -* ]]; then
+            COMPREPLY=($(forge __complete scripts "$cur" 2>/dev/null))
+            return 0
+        fi
+    fi
+    _forge "$@"
+}
+
+complete -F _forge_dynamic -o bashdefault -o default forge
+"#;
+
+const ZSH: &str = r#"
+_forge_dynamic() {
+    if [[ "${words[2]}" == "script" ]]; then
+        if [[ "${words[CURRENT-1]}" == (--tc|--target-contract) ]]; then
+            compadd -- ${(f)"$(forge __complete contracts "${words[CURRENT]}" 2>/dev/null)"}
+            return
+        elif [[ $CURRENT -eq 3 && "${words[CURRENT]}" != -* ]]; then
+            compadd -- ${(f)"$(forge __complete scripts "${words[CURRENT]}" 2>/dev/null)"}
+            return
+        fi
+    fi
+    _forge "$@"
+}
+
+compdef _forge_dynamic forge
+"#;
+
+const FISH: &str = r#"
+complete -c forge -n "__fish_seen_subcommand_from script; and __fish_prev_arg_in --tc --target-contract" -f -a "(forge __complete contracts (commandline -ct) 2>/dev/null)"
+complete -c forge -n "__fish_seen_subcommand_from script; and test (count (commandline -opc)) -eq 2; and not string match -q -- '-*' (commandline -ct)" -a "(forge __complete scripts (commandline -ct) 2>/dev/null)"
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_complete_args() {
+        let args = CompleteArgs::parse_from(["foundry-cli", "scripts", "script/De"]);
+        assert_eq!(args.kind, CompletionKind::Scripts);
+        assert_eq!(args.current, "script/De");
+
+        // the word being completed may be empty or look like a flag
+        let args = CompleteArgs::parse_from(["foundry-cli", "contracts"]);
+        assert_eq!(args.kind, CompletionKind::Contracts);
+        assert_eq!(args.current, "");
+        let args = CompleteArgs::parse_from(["foundry-cli", "scripts", "-"]);
+        assert_eq!(args.current, "-");
+    }
+
+    #[test]
+    fn completes_script_paths_only_in_the_positional_slot() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = dynamic_completions(shell).unwrap();
+            assert!(script.contains("forge __complete scripts"), "{shell}");
+            assert!(script.contains("forge __complete contracts"), "{shell}");
+        }
+        assert!(BASH.contains("$COMP_CWORD -eq 2"));
+        assert!(ZSH.contains("$CURRENT -eq 3"));
+        assert!(FISH.contains("(count (commandline -opc)) -eq 2"));
+        assert!(dynamic_completions(Shell::PowerShell).is_none());
+    }
+}
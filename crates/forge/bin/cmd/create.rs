@@ -16,7 +16,7 @@ use eyre::{Context, Result};
 use forge_verify::RetryArgs;
 use foundry_cli::{
     opts::{CoreBuildArgs, EthereumOpts, EtherscanOpts, OKLinkOpts, TransactionOpts},
-    utils::{self, read_constructor_args_file, remove_contract, LoadConfig},
+    utils::{self, read_constructor_args, remove_contract, LoadConfig},
 };
 use foundry_common::{
     compile::ProjectCompiler,
@@ -44,6 +44,9 @@ pub struct CreateArgs {
     constructor_args: Vec<String>,
 
     /// The path to a file containing the constructor arguments.
+    ///
+    /// A JSON file can hold an object of the arguments by name, e.g. `{"supply": "1000"}`,
+    /// which are checked against the constructor.
     #[arg(
         long,
         value_hint = ValueHint::FilePath,
@@ -123,7 +126,7 @@ impl CreateArgs {
             Some(ref v) => {
                 let constructor_args =
                     if let Some(ref constructor_args_path) = self.constructor_args_path {
-                        read_constructor_args_file(constructor_args_path.to_path_buf())?
+                        read_constructor_args(constructor_args_path, &v.inputs)?
                     } else {
                         self.constructor_args.clone()
                     };
@@ -347,7 +350,7 @@ impl CreateArgs {
             show_standard_json_input: self.show_standard_json_input,
             guess_constructor_args: false,
         };
-        println!("Waiting for {} to detect contract deployment...", verify.verifier.verifier);
+        println!("Waiting for {} to detect contract deployment...", verify.verifier.verifier());
         verify.run().await
     }
 
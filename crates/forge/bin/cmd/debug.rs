@@ -47,7 +47,6 @@ impl DebugArgs {
             args: self.args,
             target_contract: self.target_contract,
             sig: self.sig,
-            gas_estimate_multiplier: 130,
             opts: self.opts,
             evm_opts: self.evm_opts,
             debug: true,
@@ -42,6 +42,8 @@
 pub mod bind;
 pub mod build;
 pub mod cache;
+pub mod chain;
+pub mod complete;
 pub mod config;
 pub mod coverage;
 pub mod create;
@@ -1,4 +1,8 @@
-use super::{install, test::filter::ProjectPathsAwareFilter, watch::WatchArgs};
+use super::{
+    install,
+    test::filter::ProjectPathsAwareFilter,
+    watch::{self, WatchArgs},
+};
 use alloy_primitives::U256;
 use clap::Parser;
 use eyre::Result;
@@ -164,7 +168,9 @@ impl TestArgs {
         let mut filter = self.filter(&config);
         trace!(target: "forge::test", ?filter, "using filter");
 
-        let mut compiler = ProjectCompiler::new().quiet_if(self.json || self.opts.silent);
+        let mut compiler = ProjectCompiler::new()
+            .quiet_if(self.json || self.opts.silent)
+            .artifact_store(config.artifact_store_dir());
         if config.sparse_mode {
             compiler = compiler.filter(Box::new(filter.clone()));
         }
@@ -496,7 +502,7 @@ impl TestArgs {
     /// Returns the [`watchexec::InitConfig`] and [`watchexec::RuntimeConfig`] necessary to
     /// bootstrap a new [`watchexe::Watchexec`] loop.
     pub(crate) fn watchexec_config(&self) -> Result<(InitConfig, RuntimeConfig)> {
-        self.watch.watchexec_config(|| {
+        watch::watchexec_config(&self.watch, || {
             let config = Config::from(self);
             vec![config.src, config.test]
         })
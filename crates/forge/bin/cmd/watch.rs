@@ -1,7 +1,8 @@
 use super::{build::BuildArgs, snapshot::SnapshotArgs, test::TestArgs};
-use clap::Parser;
-use eyre::Result;
-use foundry_cli::utils::{self, FoundryPathExt};
+use eyre::{OptionExt, Result};
+use forge_script::{AnvilFork, ScriptArgs};
+pub use foundry_cli::opts::WatchArgs;
+use foundry_cli::utils::{self, FoundryPathExt, LoadConfig};
 use foundry_config::Config;
 use std::{collections::HashSet, convert::Infallible, path::PathBuf, sync::Arc};
 use watchexec::{
@@ -15,68 +16,25 @@ use watchexec::{
     Watchexec,
 };
 
-#[derive(Clone, Debug, Default, Parser)]
-#[command(next_help_heading = "Watch options")]
-pub struct WatchArgs {
-    /// Watch the given files or directories for changes.
-    ///
-    /// If no paths are provided, the source and test directories of the project are watched.
-    #[arg(
-        long,
-        short,
-        num_args(0..),
-        value_name = "PATH",
-    )]
-    pub watch: Option<Vec<PathBuf>>,
-
-    /// Do not restart the command while it's still running.
-    #[arg(long)]
-    pub no_restart: bool,
-
-    /// Explicitly re-run all tests when a change is made.
-    ///
-    /// By default, only the tests of the last modified test file are executed.
-    #[arg(long)]
-    pub run_all: bool,
-
-    /// File update debounce delay.
-    ///
-    /// During the delay, incoming change events are accumulated and
-    /// only once the delay has passed, is an action taken. Note that
-    /// this does not mean a command will be started: if --no-restart is
-    /// given and a command is already running, the outcome of the
-    /// action will be to do nothing.
-    ///
-    /// Defaults to 50ms. Parses as decimal seconds by default, but
-    /// using an integer with the `ms` suffix may be more convenient.
-    ///
-    /// When using --poll mode, you'll want a larger duration, or risk
-    /// overloading disk I/O.
-    #[arg(long, value_name = "DELAY")]
-    pub watch_delay: Option<String>,
-}
-
-impl WatchArgs {
-    /// Returns new [InitConfig] and [RuntimeConfig] based on the [WatchArgs]
-    ///
-    /// If paths were provided as arguments the these will be used as the watcher's pathset,
-    /// otherwise the path the closure returns will be used
-    pub fn watchexec_config(
-        &self,
-        f: impl FnOnce() -> Vec<PathBuf>,
-    ) -> Result<(InitConfig, RuntimeConfig)> {
-        let init = init()?;
-        let mut runtime = runtime(self)?;
-
-        // contains all the arguments `--watch p1, p2, p3`
-        let has_paths = self.watch.as_ref().map(|paths| !paths.is_empty()).unwrap_or_default();
-
-        if !has_paths {
-            // use alternative pathset, but only those that exists
-            runtime.pathset(f().into_iter().filter(|p| p.exists()));
-        }
-        Ok((init, runtime))
+/// Returns new [InitConfig] and [RuntimeConfig] based on the [WatchArgs]
+///
+/// If paths were provided as arguments the these will be used as the watcher's pathset,
+/// otherwise the path the closure returns will be used
+pub fn watchexec_config(
+    args: &WatchArgs,
+    f: impl FnOnce() -> Vec<PathBuf>,
+) -> Result<(InitConfig, RuntimeConfig)> {
+    let init = init()?;
+    let mut runtime = runtime(args)?;
+
+    // contains all the arguments `--watch p1, p2, p3`
+    let has_paths = args.watch.as_ref().map(|paths| !paths.is_empty()).unwrap_or_default();
+
+    if !has_paths {
+        // use alternative pathset, but only those that exists
+        runtime.pathset(f().into_iter().filter(|p| p.exists()));
     }
+    Ok((init, runtime))
 }
 
 /// Executes a [`Watchexec`] that listens for changes in the project's src dir and reruns `forge
@@ -117,6 +75,54 @@ pub async fn watch_snapshot(args: SnapshotArgs) -> Result<()> {
     Ok(())
 }
 
+/// Executes a [`Watchexec`] that listens for changes in the project's src and script dirs and
+/// re-simulates `forge script`, showing the changes to the planned transactions
+///
+/// If the script has an RPC URL, it's simulated against a local Anvil fork of its chain which is
+/// started once and kept for all the runs, so the state of the chain is only fetched once.
+pub async fn watch_script(mut args: ScriptArgs) -> Result<()> {
+    let watched = args.watch.watch.as_ref().map(|paths| paths.len()).unwrap_or_default();
+    // `--watch` takes any number of paths, so it also took the script in `--watch Foo.s.sol`
+    if args.path.is_empty() {
+        let path = args.watch.watch.as_mut().and_then(|paths| paths.pop());
+        args.path = path.ok_or_eyre("no script to run was given")?.to_string_lossy().into_owned();
+    }
+
+    let (init, mut runtime) = watchexec_config(&args.watch, || {
+        let config = Config::from(&args);
+        vec![config.src, config.script]
+    })?;
+
+    let (_, evm_opts) = args.load_config_and_evm_opts()?;
+    let anvil = match &evm_opts.fork_url {
+        Some(fork_url) => Some(AnvilFork::spawn(fork_url, evm_opts.fork_block_number).await?),
+        None => None,
+    };
+
+    let mut cmd = clean_fork_args(cmd_args(watched));
+    // insert the flags right after the subcommand, since trailing args are passed to the script
+    let pos = cmd.iter().position(|arg| arg == "script").map_or(cmd.len(), |pos| pos + 1);
+    cmd.insert(pos, "--diff-plan".to_string());
+    if let Some(anvil) = &anvil {
+        cmd.splice(pos..pos, ["--fork-url".to_string(), anvil.url.clone()]);
+    }
+    if watched > args.watch.watch.as_ref().map(|paths| paths.len()).unwrap_or_default() {
+        cmd.insert(pos, args.path.clone());
+    }
+
+    trace!("watch script cmd={:?}", cmd);
+    runtime.command(watch_command(cmd.clone()));
+    let wx = Watchexec::new(init, runtime.clone())?;
+
+    on_action(args.watch, runtime, Arc::clone(&wx), cmd, (), |_| {});
+
+    // start executing the command immediately
+    wx.send_event(Event::default(), Priority::default()).await?;
+    wx.main().await??;
+
+    Ok(())
+}
+
 /// Executes a [`Watchexec`] that listens for changes in the project's src dir and reruns `forge
 /// test`
 pub async fn watch_test(args: TestArgs) -> Result<()> {
@@ -299,6 +305,17 @@ fn clean_cmd_args(num: usize, mut cmd_args: Vec<String>) -> Vec<String> {
     cmd_args
 }
 
+/// Removes the RPC URL of the script from the args, to pass the one of the Anvil fork instead
+fn clean_fork_args(mut cmd_args: Vec<String>) -> Vec<String> {
+    const FLAGS: [&str; 3] = ["--fork-url", "--rpc-url", "-f"];
+    while let Some(pos) = cmd_args.iter().position(|arg| FLAGS.contains(&arg.as_str())) {
+        let end = (pos + 1).min(cmd_args.len() - 1);
+        cmd_args.drain(pos..=end);
+    }
+    cmd_args.retain(|arg| !FLAGS.iter().any(|flag| arg.starts_with(&format!("{flag}="))));
+    cmd_args
+}
+
 /// Returns the Initialisation configuration for [`Watchexec`].
 pub fn init() -> Result<InitConfig> {
     let mut config = InitConfig::default();
@@ -451,4 +468,12 @@ mod tests {
         let cleaned = clean_cmd_args(0, args);
         assert_eq!(cleaned, vec!["-v".to_string()]);
     }
+
+    #[test]
+    fn parse_fork_args() {
+        let args =
+            ["forge", "script", "S.s.sol", "-f", "a", "--rpc-url=b", "--fork-url", "c", "-vv"];
+        let cleaned = clean_fork_args(args.into_iter().map(String::from).collect());
+        assert_eq!(cleaned, ["forge", "script", "S.s.sol", "-vv"]);
+    }
 }
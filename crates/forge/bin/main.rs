@@ -9,7 +9,7 @@ use foundry_cli::{handler, utils};
 mod cmd;
 mod opts;
 
-use cmd::{cache::CacheSubcommands, generate::GenerateSubcommands, watch};
+use cmd::{cache::CacheSubcommands, complete, generate::GenerateSubcommands, watch};
 use opts::{Forge, ForgeSubcommand};
 
 fn main() -> Result<()> {
@@ -29,11 +29,21 @@ fn main() -> Result<()> {
             }
         }
         ForgeSubcommand::Script(cmd) => {
+            if let Some(command) = cmd.command {
+                return utils::block_on(command.run())
+            }
+            let cmd = cmd.resolve_alias()?;
+            if cmd.is_watch() {
+                return utils::block_on(watch::watch_script(cmd))
+            }
             // install the shell before executing the command
             foundry_common::shell::set_shell(foundry_common::shell::Shell::from_args(
                 cmd.opts.silent,
                 cmd.json,
             ))?;
+            if cmd.is_chaos() {
+                return utils::block_on(cmd.run_chaos())
+            }
             utils::block_on(cmd.run_script())
         }
         ForgeSubcommand::Coverage(cmd) => utils::block_on(cmd.run()),
@@ -48,10 +58,14 @@ fn main() -> Result<()> {
         ForgeSubcommand::Debug(cmd) => utils::block_on(cmd.run()),
         ForgeSubcommand::VerifyContract(args) => utils::block_on(args.run()),
         ForgeSubcommand::VerifyCheck(args) => utils::block_on(args.run()),
+        ForgeSubcommand::VerifySetup(args) => utils::block_on(args.run()),
+        ForgeSubcommand::VerifyDeployments(args) => utils::block_on(args.run()),
         ForgeSubcommand::Cache(cmd) => match cmd.sub {
             CacheSubcommands::Clean(cmd) => cmd.run(),
             CacheSubcommands::Ls(cmd) => cmd.run(),
+            CacheSubcommands::PruneArtifacts(cmd) => cmd.run(),
         },
+        ForgeSubcommand::Chain(cmd) => cmd.run(),
         ForgeSubcommand::Create(cmd) => utils::block_on(cmd.run()),
         ForgeSubcommand::Update(cmd) => cmd.run(),
         ForgeSubcommand::Install(cmd) => cmd.run(),
@@ -60,8 +74,12 @@ fn main() -> Result<()> {
         ForgeSubcommand::Init(cmd) => cmd.run(),
         ForgeSubcommand::Completions { shell } => {
             generate(shell, &mut Forge::command(), "forge", &mut std::io::stdout());
+            if let Some(script) = complete::dynamic_completions(shell) {
+                print!("{script}");
+            }
             Ok(())
         }
+        ForgeSubcommand::Complete(cmd) => cmd.run(),
         ForgeSubcommand::GenerateFigSpec => {
             clap_complete::generate(
                 clap_complete_fig::Fig,
@@ -1,12 +1,12 @@
 use crate::cmd::{
-    bind::BindArgs, build::BuildArgs, cache::CacheArgs, config, coverage, create::CreateArgs,
-    debug::DebugArgs, doc::DocArgs, flatten, fmt::FmtArgs, geiger, generate, init::InitArgs,
-    inspect, install::InstallArgs, remappings::RemappingArgs, remove::RemoveArgs,
-    selectors::SelectorsSubcommands, snapshot, test, tree, update,
+    bind::BindArgs, build::BuildArgs, cache::CacheArgs, chain::ChainArgs, complete::CompleteArgs,
+    config, coverage, create::CreateArgs, debug::DebugArgs, doc::DocArgs, flatten, fmt::FmtArgs,
+    geiger, generate, init::InitArgs, inspect, install::InstallArgs, remappings::RemappingArgs,
+    remove::RemoveArgs, selectors::SelectorsSubcommands, snapshot, test, tree, update,
 };
 use clap::{Parser, Subcommand, ValueHint};
 use forge_script::ScriptArgs;
-use forge_verify::{VerifyArgs, VerifyCheckArgs};
+use forge_verify::{VerifyArgs, VerifyCheckArgs, VerifyDeploymentsArgs, VerifySetupArgs};
 use std::path::PathBuf;
 
 const VERSION_MESSAGE: &str = concat!(
@@ -84,6 +84,12 @@ pub enum ForgeSubcommand {
     #[command(visible_alias = "vc")]
     VerifyCheck(VerifyCheckArgs),
 
+    /// Detect the block explorers of a chain and configure contract verification for it.
+    VerifySetup(VerifySetupArgs),
+
+    /// Verify the unverified contracts of the deployments registry of a chain.
+    VerifyDeployments(VerifyDeploymentsArgs),
+
     /// Deploy a smart contract.
     #[command(visible_alias = "c")]
     Create(CreateArgs),
@@ -98,6 +104,10 @@ pub enum ForgeSubcommand {
         shell: clap_complete::Shell,
     },
 
+    /// Complete values of the current project, used by the completion scripts.
+    #[command(name = "__complete", hide = true)]
+    Complete(CompleteArgs),
+
     /// Generate Fig autocompletion spec.
     #[command(visible_alias = "fig")]
     GenerateFigSpec,
@@ -116,6 +126,9 @@ pub enum ForgeSubcommand {
     /// Manage the Foundry cache.
     Cache(CacheArgs),
 
+    /// Manage the custom chains which can be selected by name with `--chain`.
+    Chain(ChainArgs),
+
     /// Create a snapshot of each test's gas usage.
     #[command(visible_alias = "s")]
     Snapshot(snapshot::SnapshotArgs),
@@ -333,7 +333,7 @@ impl MultiContractRunnerBuilder {
                 .map(|b| b.into_owned())
                 .filter(|b| !b.is_empty())
             else {
-                known_contracts.insert(id.clone(), (abi.clone(), vec![]));
+                known_contracts.insert(id.clone(), (abi.clone(), Default::default()));
                 continue;
             };
 
@@ -345,7 +345,7 @@ impl MultiContractRunnerBuilder {
             }
 
             if let Some(bytes) = linked_contract.get_deployed_bytecode_bytes() {
-                known_contracts.insert(id.clone(), (abi.clone(), bytes.into_owned().into()));
+                known_contracts.insert(id.clone(), (abi.clone(), bytes.into_owned()));
             }
         }
 
@@ -378,7 +378,7 @@ pub struct TestResult {
 
     pub duration: Duration,
 
-    /// pc breakpoint char map
+    /// The breakpoints hit during the test, by label
     pub breakpoints: Breakpoints,
 }
 
@@ -1636,3 +1636,35 @@ forgetest_init!(can_inspect_counter_pretty, |prj, cmd| {
 }"
     );
 });
+
+// checks that `__complete` lists the scripts and contracts of the compiled project
+forgetest!(can_complete_scripts_and_contracts, |prj, cmd| {
+    prj.add_source("Counter.sol", "contract Counter {}").unwrap();
+    prj.add_script("Deploy.s.sol", "contract Deploy {}\ncontract DeployHelper {}").unwrap();
+
+    // nothing to complete before the project is compiled
+    cmd.args(["__complete", "scripts"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "");
+
+    cmd.forge_fuse().arg("build");
+    cmd.assert_non_empty_stdout();
+
+    cmd.forge_fuse().args(["__complete", "scripts", "script/"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "script/Deploy.s.sol");
+    cmd.forge_fuse().args(["__complete", "scripts", "Dep"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "Deploy\nDeployHelper");
+    cmd.forge_fuse().args(["__complete", "scripts", "Co"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "");
+    cmd.forge_fuse().args(["__complete", "contracts", "Co"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "Counter");
+});
+
+// checks that the completions of `forge script` call `forge __complete`
+forgetest!(completions_complete_scripts_dynamically, |_prj, cmd| {
+    for shell in ["bash", "zsh", "fish"] {
+        cmd.forge_fuse().args(["completions", shell]);
+        let output = cmd.stdout_lossy();
+        assert!(output.contains("forge __complete scripts"), "{shell}: {output}");
+        assert!(output.contains("forge __complete contracts"), "{shell}: {output}");
+    }
+});
@@ -34,6 +34,7 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         libs: vec!["lib-test".into()],
         cache: true,
         cache_path: "test-cache".into(),
+        artifact_store: true,
         broadcast: "broadcast".into(),
         force: true,
         evm_version: EvmVersion::Byzantium,
@@ -41,6 +42,7 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         gas_reports_ignore: vec![],
         solc: Some(SolcReq::Local(PathBuf::from("custom-solc"))),
         auto_detect_solc: false,
+        solc_jobs: Some(2),
         auto_detect_remappings: true,
         offline: true,
         optimizer: false,
@@ -91,6 +93,10 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         eth_rpc_jwt: None,
         etherscan_api_key: None,
         etherscan: Default::default(),
+        verifier: Some("oklink".to_string()),
+        verifier_url: None,
+        verifier_plugins: Default::default(),
+        script_hooks: Default::default(),
         verbosity: 4,
         remappings: vec![Remapping::from_str("forge-std=lib/forge-std/").unwrap().into()],
         libraries: vec![
@@ -121,6 +127,9 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         doc: Default::default(),
         fs_permissions: Default::default(),
         labels: Default::default(),
+        scripts: Default::default(),
+        precompiles: Default::default(),
+        confirm_chain_ids: vec![],
         cancun: true,
         isolate: true,
         __non_exhaustive: (),
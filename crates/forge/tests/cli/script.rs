@@ -107,6 +107,37 @@ contract Demo {
     );
 });
 
+// Tests that sources which don't import each other are compiled in separate solc runs
+forgetest!(can_execute_script_command_with_jobs, |prj, cmd| {
+    let script = prj
+        .add_source(
+            "Foo",
+            r#"
+contract Demo {
+    event log_string(string);
+    function run() external {
+        emit log_string("script ran");
+    }
+}
+   "#,
+        )
+        .unwrap();
+    prj.add_source(
+        "Other",
+        r#"
+contract Other {}
+   "#,
+    )
+    .unwrap();
+
+    cmd.arg("script").arg(script).args(["--jobs", "2"]);
+    assert!(cmd.stdout_lossy().contains("script ran"));
+
+    // each unit has a compiler cache of its own
+    let units = prj.paths().cache.with_file_name("script-units");
+    assert_eq!(std::fs::read_dir(units).unwrap().count(), 2);
+});
+
 static FAILING_SCRIPT: &str = r#"
 import "forge-std/Script.sol";
 
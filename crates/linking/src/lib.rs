@@ -192,6 +192,33 @@ impl Linker {
     ) -> Result<ArtifactContracts, LinkerError> {
         self.contracts.keys().map(|id| Ok((id.clone(), self.link(id, libraries)?))).collect()
     }
+
+    /// Returns the artifacts which reference a library whose address differs between `previous`
+    /// and `libraries`, i.e. the only ones which have to be linked again when switching from
+    /// `previous` to `libraries`.
+    ///
+    /// Both sets should have stripped file prefixes, like the libraries of [LinkOutput].
+    pub fn artifacts_to_relink<'a>(
+        &'a self,
+        previous: &Libraries,
+        libraries: &Libraries,
+    ) -> Vec<&'a ArtifactId> {
+        let address = |libraries: &Libraries, file: &str, name: &str| {
+            let address = libraries.libs.get(Path::new(file))?.get(name)?;
+            Address::from_str(address).ok()
+        };
+
+        self.contracts
+            .iter()
+            .filter(|(_, contract)| {
+                contract.all_link_references().iter().any(|(file, libs)| {
+                    libs.keys()
+                        .any(|name| address(previous, file, name) != address(libraries, file, name))
+                })
+            })
+            .map(|(id, _)| id)
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -325,6 +352,34 @@ mod tests {
         });
     }
 
+    #[test]
+    fn relink_only_dependents() {
+        let test = LinkerTest::new("../../testdata/linking/simple", true);
+        let linker = &test.linker;
+        let target = linker
+            .contracts
+            .keys()
+            .find(|id| id.name == "SimpleLibraryLinkingTest")
+            .expect("target not found");
+
+        let link = |nonce| {
+            linker
+                .link_with_nonce_or_address(Default::default(), Address::default(), nonce, target)
+                .unwrap()
+                .libraries
+        };
+        let (previous, libraries) = (link(1), link(2));
+        assert!(linker.artifacts_to_relink(&previous, &previous).is_empty());
+
+        let mut names = linker
+            .artifacts_to_relink(&previous, &libraries)
+            .into_iter()
+            .map(|id| id.name.as_str())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        assert_eq!(names, ["LibraryConsumer", "SimpleLibraryLinkingTest"]);
+    }
+
     #[test]
     fn link_nested() {
         link_test("../../testdata/linking/nested", |linker| {
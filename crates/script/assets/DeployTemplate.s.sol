@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Script} from "forge-std/Script.sol";
+import {Counter} from "../../src/Counter.sol";
+
+/// Deploys the project in stages, with the values of each chain read from its params file.
+///
+/// Preview the transactions and how they differ from the previous run with
+/// `forge script plan-<chain>`, then broadcast and verify them with `forge script deploy-<chain>`.
+/// Once they're mined, the deployed addresses are recorded in `deployments/<chain id>.json`.
+contract DeployScript is Script {
+    function run() public {
+        Counter counter = deploy();
+        configure(counter);
+    }
+
+    /// Stage 1: deploys the contracts.
+    function deploy() internal returns (Counter counter) {
+        vm.startBroadcast();
+        counter = new Counter();
+        vm.stopBroadcast();
+    }
+
+    /// Stage 2: configures the deployed contracts with the params of the chain.
+    function configure(Counter counter) internal {
+        uint256 number = vm.paramOr("initial_number", uint256(0));
+        vm.startBroadcast();
+        counter.setNumber(number);
+        vm.stopBroadcast();
+    }
+}
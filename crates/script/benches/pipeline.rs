@@ -0,0 +1,184 @@
+//! Benchmarks of the build, link and plan steps of `forge script`, on a generated project whose
+//! script deploys many contracts linked against external libraries.
+
+use alloy_primitives::{keccak256, Address};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use ethers_core::types::{transaction::eip2718::TypedTransaction, Eip1559TransactionRequest};
+use forge_script::{BuildData, LinkedBuildData, ScriptPlan};
+use foundry_common::compile::ProjectCompiler;
+use foundry_compilers::{artifacts::Libraries, contracts::ArtifactContracts, ArtifactId, Project};
+use foundry_config::Config;
+use foundry_linking::Linker;
+use std::{fmt::Write, fs, path::Path};
+use tempfile::TempDir;
+
+const LIBRARIES: usize = 16;
+const CONTRACTS: usize = 64;
+const LIBRARIES_PER_CONTRACT: usize = 4;
+const PLAN_TRANSACTIONS: usize = 1_000;
+
+/// A compiled project with a `Deploy` script creating all of its contracts.
+struct Fixture {
+    _root: TempDir,
+    project: Project,
+    contracts: ArtifactContracts,
+    target: ArtifactId,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let root = tempfile::tempdir().unwrap();
+        write_sources(root.path());
+
+        let project = Config::with_root(root.path()).project().unwrap();
+        let output = ProjectCompiler::new().quiet(true).compile(&project).unwrap();
+        let contracts: ArtifactContracts = output
+            .into_artifacts()
+            .map(|(id, artifact)| (id, artifact.into_contract_bytecode()))
+            .collect();
+        let target = contracts.keys().find(|id| id.name == "Deploy").unwrap().clone();
+
+        Self { _root: root, project, contracts, target }
+    }
+
+    fn build_data(&self) -> BuildData {
+        BuildData {
+            linker: Linker::new(self.project.root(), self.contracts.clone()),
+            target: self.target.clone(),
+            sources: Default::default(),
+            git: None,
+        }
+    }
+}
+
+/// Writes `LIBRARIES` libraries, `CONTRACTS` contracts calling some of them, and a script
+/// deploying all the contracts.
+fn write_sources(root: &Path) {
+    let (src, script) = (root.join("src"), root.join("script"));
+    fs::create_dir_all(&src).unwrap();
+    fs::create_dir_all(&script).unwrap();
+    let header = "// SPDX-License-Identifier: MIT\npragma solidity ^0.8.0;\n";
+
+    for i in 0..LIBRARIES {
+        let source = format!(
+            "{header}\nlibrary Lib{i} {{\n    function f(uint256 x) public pure returns (uint256) \
+             {{\n        return x + {i};\n    }}\n}}\n"
+        );
+        fs::write(src.join(format!("Lib{i}.sol")), source).unwrap();
+    }
+
+    for i in 0..CONTRACTS {
+        let libraries = (0..LIBRARIES_PER_CONTRACT).map(|k| (i + k) % LIBRARIES);
+        let mut source = header.to_string();
+        for lib in libraries.clone() {
+            writeln!(source, "import {{Lib{lib}}} from \"./Lib{lib}.sol\";").unwrap();
+        }
+        let calls = libraries.map(|lib| format!("Lib{lib}.f(x)")).collect::<Vec<_>>();
+        write!(
+            source,
+            "\ncontract C{i} {{\n    function f(uint256 x) external pure returns (uint256) {{\n        \
+             return {};\n    }}\n}}\n",
+            calls.join(" + ")
+        )
+        .unwrap();
+        fs::write(src.join(format!("C{i}.sol")), source).unwrap();
+    }
+
+    let mut source = header.to_string();
+    for i in 0..CONTRACTS {
+        writeln!(source, "import {{C{i}}} from \"../src/C{i}.sol\";").unwrap();
+    }
+    source.push_str("\ncontract Deploy {\n    function run() external {\n");
+    for i in 0..CONTRACTS {
+        writeln!(source, "        new C{i}();").unwrap();
+    }
+    source.push_str("    }\n}\n");
+    fs::write(script.join("Deploy.s.sol"), source).unwrap();
+}
+
+/// A plan with `PLAN_TRANSACTIONS` contract creations on a single chain.
+fn plan() -> ScriptPlan {
+    let mut plan =
+        ScriptPlan { args: vec!["script/Deploy.s.sol".to_string()], ..Default::default() };
+    plan.sequences.push(Default::default());
+    let sequence = &mut plan.sequences[0];
+    sequence.chain = 1;
+    for i in 0..PLAN_TRANSACTIONS {
+        sequence.transactions.push_back(Default::default());
+        let tx = sequence.transactions.back_mut().unwrap();
+        tx.contract_name = Some(format!("C{}", i % CONTRACTS));
+        tx.contract_address = Some(Address::from_word(keccak256(i.to_be_bytes())));
+        tx.transaction = TypedTransaction::Eip1559(
+            Eip1559TransactionRequest::new()
+                .from(ethers_core::types::Address::repeat_byte(1))
+                .data(vec![0x60; 2_048])
+                .gas(1_000_000)
+                .nonce(i as u64),
+        );
+    }
+    plan
+}
+
+fn script_pipeline_benchmark(c: &mut Criterion) {
+    let fixture = Fixture::new();
+
+    let mut group = c.benchmark_group("forge script");
+    group.sample_size(10);
+
+    group.bench_function("compile from cache", |b| {
+        b.iter(|| ProjectCompiler::new().quiet(true).compile(&fixture.project).unwrap());
+    });
+
+    let key = keccak256("bench");
+    fixture.build_data().save_cached(&fixture.project, key).unwrap();
+    group.bench_function("load build cache", |b| {
+        b.iter(|| BuildData::load_cached(&fixture.project, key).unwrap());
+    });
+
+    let build_data = fixture.build_data();
+    group.bench_function("link", |b| {
+        b.iter(|| {
+            build_data
+                .linker
+                .link_with_nonce_or_address(
+                    Libraries::default(),
+                    Address::ZERO,
+                    1,
+                    &build_data.target,
+                )
+                .unwrap()
+        });
+    });
+
+    group.bench_function("LinkedBuildData::new", |b| {
+        b.iter_batched(
+            || {
+                let build_data = fixture.build_data();
+                let output = build_data
+                    .linker
+                    .link_with_nonce_or_address(
+                        Libraries::default(),
+                        Address::ZERO,
+                        1,
+                        &build_data.target,
+                    )
+                    .unwrap();
+                (output, build_data)
+            },
+            |(output, build_data)| LinkedBuildData::new(output, build_data).unwrap(),
+            BatchSize::LargeInput,
+        );
+    });
+
+    let plan = plan();
+    let json = serde_json::to_vec(&plan).unwrap();
+    group.bench_function("serialize plan", |b| {
+        b.iter(|| serde_json::to_vec(&plan).unwrap());
+    });
+    group.bench_function("deserialize plan", |b| {
+        b.iter(|| serde_json::from_slice::<ScriptPlan>(&json).unwrap());
+    });
+}
+
+criterion_group!(benches, script_pipeline_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,227 @@
+use crate::{
+    broadcast::BundledState, sequence::ScriptSequence, transaction::TransactionWithMetadata,
+};
+use alloy_primitives::{keccak256, Address, B256};
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::{
+    provider::ethers::get_http_provider,
+    shell,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_evm::{constants::DEFAULT_CREATE2_DEPLOYER, traces::CallKind};
+use std::fmt;
+use yansi::Paint;
+
+/// How the address of a contract created by the script is derived.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Derivation {
+    Create {
+        sender: Address,
+        nonce: u64,
+    },
+    Create2 {
+        deployer: Address,
+        salt: B256,
+        init_code_hash: B256,
+    },
+    /// Created by a contract called by the transaction, whose nonce or salt isn't recorded.
+    Nested {
+        kind: CallKind,
+        init_code_hash: B256,
+    },
+}
+
+impl Derivation {
+    /// Returns the address derived from the inputs, if they are all known.
+    fn address(&self) -> Option<Address> {
+        match self {
+            Self::Create { sender, nonce } => Some(sender.create(*nonce)),
+            Self::Create2 { deployer, salt, init_code_hash } => {
+                Some(deployer.create2(*salt, *init_code_hash))
+            }
+            Self::Nested { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Derivation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Create { sender, nonce } => write!(f, "CREATE by {sender} with nonce {nonce}"),
+            Self::Create2 { deployer, salt, init_code_hash } => write!(
+                f,
+                "CREATE2 by {deployer} with salt {salt} and init code hash {init_code_hash}"
+            ),
+            Self::Nested { kind, init_code_hash } => write!(
+                f,
+                "{} in a call of the transaction, init code hash {init_code_hash}",
+                if *kind == CallKind::Create2 { "CREATE2" } else { "CREATE" }
+            ),
+        }
+    }
+}
+
+/// The address of a contract which a transaction of the script will create.
+#[derive(Clone, Debug)]
+pub struct PredictedAddress {
+    pub chain: u64,
+    /// The index of the transaction in its sequence.
+    pub transaction: usize,
+    pub address: Address,
+    pub contract_name: Option<String>,
+    pub derivation: Derivation,
+}
+
+impl PredictedAddress {
+    /// Returns whether the address the inputs derive to isn't the one of the simulation, e.g.
+    /// because the nonce of the sender changed in between.
+    pub fn is_stale(&self) -> bool {
+        self.derivation.address().map_or(false, |address| address != self.address)
+    }
+}
+
+/// Lists the contracts which the transactions of `sequence` which weren't broadcast yet will
+/// create, with the derivation of their address.
+pub fn predict_addresses(sequence: &ScriptSequence) -> Vec<PredictedAddress> {
+    sequence
+        .transactions
+        .iter()
+        .enumerate()
+        .skip(sequence.receipts.len())
+        .flat_map(|(i, tx)| {
+            let predicted = |address, contract_name, derivation| PredictedAddress {
+                chain: sequence.chain,
+                transaction: i,
+                address,
+                contract_name,
+                derivation,
+            };
+            let created = tx.contract_address.zip(derivation(tx)).map(|(address, derivation)| {
+                predicted(address, tx.contract_name.clone(), derivation)
+            });
+            let nested = tx.additional_contracts.iter().map(move |contract| {
+                let derivation = Derivation::Nested {
+                    kind: contract.opcode,
+                    init_code_hash: keccak256(&contract.init_code),
+                };
+                predicted(contract.address, None, derivation)
+            });
+            created.into_iter().chain(nested).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Returns the derivation of the address of the contract created by `tx`, if it creates one.
+fn derivation(tx: &TransactionWithMetadata) -> Option<Derivation> {
+    let data = tx.transaction.data()?;
+    match tx.opcode {
+        CallKind::Create => Some(Derivation::Create {
+            sender: tx.transaction.from()?.to_alloy(),
+            nonce: tx.transaction.nonce()?.as_u64(),
+        }),
+        CallKind::Create2 if data.len() >= 32 => Some(Derivation::Create2 {
+            deployer: DEFAULT_CREATE2_DEPLOYER,
+            salt: B256::from_slice(&data[..32]),
+            init_code_hash: keccak256(&data[32..]),
+        }),
+        _ => None,
+    }
+}
+
+impl BundledState {
+    /// Lists the addresses of the contracts the transactions will create, and fails if any of
+    /// them already has code on its chain, as the transaction would revert or deploy elsewhere.
+    pub async fn check_predicted_addresses(&self) -> Result<()> {
+        let mut collisions = Vec::new();
+        let mut printed_header = false;
+        for sequence in self.sequence.sequences() {
+            let predicted = predict_addresses(sequence);
+            if predicted.is_empty() {
+                continue
+            }
+            if !printed_header {
+                shell::println("\n==========================")?;
+                shell::println("Contracts to be created:\n")?;
+                printed_header = true;
+            }
+
+            let provider = get_http_provider(sequence.rpc_url());
+            for prediction in predicted {
+                let name = prediction.contract_name.as_deref().unwrap_or("unknown contract");
+                shell::println(format!(
+                    "[chain {}] transaction {}: {name} at {}\n    {}",
+                    prediction.chain,
+                    prediction.transaction,
+                    prediction.address,
+                    prediction.derivation
+                ))?;
+                if prediction.is_stale() {
+                    shell::println(Paint::yellow(format!(
+                        "    Warning: the derivation gives {}, the state changed since the \
+                         simulation",
+                        prediction.derivation.address().unwrap_or_default()
+                    )))?;
+                }
+
+                let code = provider.get_code(prediction.address.to_ethers(), None).await?;
+                if !code.is_empty() {
+                    collisions.push(prediction);
+                }
+            }
+        }
+
+        if !collisions.is_empty() {
+            let collisions = collisions
+                .iter()
+                .map(|prediction| {
+                    format!(
+                        "{} (transaction {} on chain {})",
+                        prediction.address, prediction.transaction, prediction.chain
+                    )
+                })
+                .collect::<Vec<_>>();
+            eyre::bail!(
+                "Contracts already exist at the following addresses, the script may have already \
+                 been run:\n{}\nChange the salt or the sender to deploy new contracts.",
+                collisions.join("\n")
+            )
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn derives_create_addresses() {
+        let derivation = Derivation::Create {
+            sender: address!("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0"),
+            nonce: 1,
+        };
+        assert_eq!(
+            derivation.address(),
+            Some(address!("343c43a37d37dff08ae8c4a11544c718abb4fcf8"))
+        );
+    }
+
+    #[test]
+    fn derives_create2_addresses() {
+        // EIP-1014 example 0
+        let derivation = Derivation::Create2 {
+            deployer: Address::ZERO,
+            salt: B256::ZERO,
+            init_code_hash: keccak256([0]),
+        };
+        assert_eq!(
+            derivation.address(),
+            Some(address!("4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38"))
+        );
+
+        let nested = Derivation::Nested { kind: CallKind::Create2, init_code_hash: keccak256([0]) };
+        assert_eq!(nested.address(), None);
+    }
+}
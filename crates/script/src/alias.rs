@@ -0,0 +1,103 @@
+use crate::ScriptArgs;
+use clap::Parser;
+use eyre::Result;
+use foundry_cli::utils::LoadConfig;
+use std::{env, iter, path::Path};
+
+impl ScriptArgs {
+    /// Expands the script path if it's an alias declared in the `[scripts]` section of the config,
+    /// e.g. `deploy-prod = "script/Deploy.s.sol --chain mainnet --verify"`.
+    ///
+    /// The arguments passed on the command line are kept, and take precedence over the ones of the
+    /// alias. Aliases are not expanded recursively.
+    pub fn resolve_alias(self) -> Result<Self> {
+        if Path::new(&self.path).exists() {
+            return Ok(self)
+        }
+        let config = self.try_load_config()?;
+        let Some(alias) = config.scripts.get(&self.path) else { return Ok(self) };
+
+        let args = env::args().collect::<Vec<_>>();
+        let Some(command) = args.iter().position(|arg| arg == "script") else { return Ok(self) };
+        self.expand_alias(alias, &args[command + 1..])
+    }
+
+    /// Parses the arguments of `forge script` again with the script path replaced by the
+    /// expansion of its alias.
+    ///
+    /// The arguments of the alias come first, so that the ones passed on the command line override
+    /// them, see `args_override_self`.
+    fn expand_alias(self, alias: &str, args: &[String]) -> Result<Self> {
+        let Some(position) = args.iter().rposition(|arg| *arg == self.path) else {
+            return Ok(self)
+        };
+
+        let expanded = split_args(alias)?;
+        trace!(target: "script", alias = self.path, ?expanded, "expanding script alias");
+
+        let args = expanded.iter().chain(&args[..position]).chain(&args[position + 1..]).cloned();
+        Self::try_parse_from(iter::once("forge script".to_string()).chain(args)).map_err(|err| {
+            eyre::eyre!("Invalid arguments for script alias `{}`:\n{err}", self.path)
+        })
+    }
+}
+
+/// Splits a command line into arguments, honoring single and double quotes and backslash escapes.
+fn split_args(line: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.get_or_insert_with(String::new).push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            (None, '\\') => {
+                if let Some(c) = chars.next() {
+                    current.get_or_insert_with(String::new).push(c);
+                }
+            }
+            (None, c) if c.is_whitespace() => args.extend(current.take()),
+            (None, c) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        eyre::bail!("Unterminated quote in script alias `{line}`")
+    }
+    args.extend(current);
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_config::{Chain, NamedChain};
+
+    #[test]
+    fn command_line_args_override_alias() {
+        let cli = ["deploy-prod", "--chain", "sepolia", "--slow"].map(String::from);
+        let args = ScriptArgs::parse_from(["forge script", "deploy-prod"]);
+        let args = args.expand_alias("script/Deploy.s.sol --chain mainnet --verify", &cli).unwrap();
+        assert_eq!(args.path, "script/Deploy.s.sol");
+        assert_eq!(args.evm_opts.env.chain, Some(Chain::from(NamedChain::Sepolia)));
+        assert!(args.verify);
+        assert!(args.slow);
+    }
+
+    #[test]
+    fn can_split_alias_args() {
+        assert_eq!(
+            split_args("script/Deploy.s.sol  --chain mainnet --verify").unwrap(),
+            ["script/Deploy.s.sol", "--chain", "mainnet", "--verify"]
+        );
+        assert_eq!(
+            split_args(r#"Deploy --sig "run(string)" 'a b' "" c\ d"#).unwrap(),
+            ["Deploy", "--sig", "run(string)", "a b", "", "c d"]
+        );
+        assert!(split_args("Deploy --sig 'run(").is_err());
+    }
+}
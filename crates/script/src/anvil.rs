@@ -0,0 +1,53 @@
+use ethers_providers::Middleware;
+use eyre::{Context, Result};
+use foundry_common::provider::ethers::get_http_provider;
+use std::{
+    net::TcpListener,
+    process::{Child, Command, Stdio},
+    time::Duration,
+};
+
+/// An Anvil node forking the chain of the script, killed when dropped.
+///
+/// Requires `anvil` to be installed. The senders of the script are impersonated.
+#[derive(Debug)]
+pub struct AnvilFork {
+    child: Child,
+    /// The HTTP endpoint of the node.
+    pub url: String,
+}
+
+impl AnvilFork {
+    /// Starts a node forking `fork_url` at `fork_block`, or at its latest block, and waits until
+    /// it serves requests.
+    pub async fn spawn(fork_url: &str, fork_block: Option<u64>) -> Result<Self> {
+        let port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+        let mut cmd = Command::new("anvil");
+        cmd.args(["--fork-url", fork_url, "--port", &port.to_string()])
+            .args(["--auto-impersonate", "--silent"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        if let Some(block) = fork_block {
+            cmd.args(["--fork-block-number", &block.to_string()]);
+        }
+        let child = cmd.spawn().wrap_err("failed to start `anvil`, is it installed?")?;
+        let anvil = Self { child, url: format!("http://127.0.0.1:{port}") };
+
+        let provider = get_http_provider(&anvil.url);
+        for _ in 0..60 {
+            if let Ok(chain_id) = provider.get_chainid().await {
+                trace!(target: "script", %chain_id, url = %anvil.url, "anvil fork started");
+                return Ok(anvil)
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        eyre::bail!("Anvil didn't start within 30 seconds")
+    }
+}
+
+impl Drop for AnvilFork {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
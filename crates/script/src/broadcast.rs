@@ -1,14 +1,27 @@
 use crate::{
     build::LinkedBuildData,
+    chain_guard, chain_metadata,
+    deposits::L1ToL2Deposit,
     execute::{ExecutionArtifacts, ExecutionData},
+    failover::FailoverProvider,
+    fee_currency::{self, FEE_CURRENCY_INTRINSIC_GAS},
+    funding::{self, FundingSource},
+    git_status,
+    hooks::{self, Hook, RunPayload},
+    metrics::BroadcastMetrics,
+    pending,
+    reconciliation::{self, Reconciliation},
+    registry,
     sequence::ScriptSequenceKind,
+    vanity,
     verify::BroadcastedState,
+    watchdog::ChainWatchdog,
     ScriptArgs, ScriptConfig,
 };
 
 use super::receipts;
 use alloy_primitives::{utils::format_units, Address, TxHash, U256};
-use ethers_core::types::{transaction::eip2718::TypedTransaction, BlockId};
+use ethers_core::types::{transaction::eip2718::TypedTransaction, BlockId, BlockNumber};
 use ethers_providers::{JsonRpcClient, Middleware, Provider};
 use ethers_signers::Signer;
 use eyre::{bail, Context, Result};
@@ -16,23 +29,24 @@ use forge_verify::provider::VerificationProviderType;
 use foundry_cheatcodes::ScriptWallets;
 use foundry_cli::{
     init_progress, update_progress,
-    utils::{has_batch_support, has_different_gas_calc},
+    utils::{has_batch_support, has_different_gas_calc, now},
 };
 use foundry_common::{
     provider::ethers::{
-        estimate_eip1559_fees, get_http_provider, try_get_http_provider, RetryProvider,
+        estimate_eip1559_fees, get_http_provider, with_adaptive_interval, RetryProvider,
     },
-    shell,
+    shell, tr,
     types::{ToAlloy, ToEthers},
 };
 use foundry_config::Config;
-use foundry_wallets::WalletSigner;
+use foundry_wallets::{utils::create_private_key_signer, WalletSigner};
 use futures::{future::join_all, StreamExt};
 use itertools::Itertools;
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
 };
+use yansi::Paint;
 
 pub async fn estimate_gas<T>(
     tx: &mut TypedTransaction,
@@ -68,6 +82,7 @@ pub async fn next_nonce(
     res.try_into().map_err(Into::into)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn send_transaction(
     provider: Arc<RetryProvider>,
     mut tx: TypedTransaction,
@@ -76,6 +91,7 @@ pub async fn send_transaction(
     is_fixed_gas_limit: bool,
     estimate_via_rpc: bool,
     estimate_multiplier: u64,
+    fee_currency: Option<Address>,
 ) -> Result<TxHash> {
     let from = tx.from().expect("no sender");
 
@@ -94,6 +110,24 @@ pub async fn send_transaction(
         estimate_gas(&mut tx, &provider, estimate_multiplier).await?;
     }
 
+    if let Some(fee_currency) = fee_currency {
+        if !is_fixed_gas_limit {
+            let gas = tx.gas().copied().unwrap_or_default();
+            tx.set_gas(gas + FEE_CURRENCY_INTRINSIC_GAS);
+        }
+        let tx = fee_currency::eip1559(&tx)?;
+        return match kind {
+            SendTransactionKind::Unlocked(addr) => {
+                debug!("sending transaction from unlocked account {:?}: {:?}", addr, tx);
+                fee_currency::send_unlocked(&provider, tx, fee_currency).await
+            }
+            SendTransactionKind::Raw(signer) => {
+                debug!("sending transaction: {:?}", tx);
+                fee_currency::send_raw(&provider, signer, tx, fee_currency).await
+            }
+        }
+    }
+
     let pending = match kind {
         SendTransactionKind::Unlocked(addr) => {
             debug!("sending transaction from unlocked account {:?}: {:?}", addr, tx);
@@ -154,6 +188,14 @@ impl SendTransactionsKind {
         }
     }
 
+    /// Returns the local signer for the given address, if there is one.
+    pub fn signer(&self, addr: &Address) -> Option<&WalletSigner> {
+        match self {
+            SendTransactionsKind::Unlocked(_) => None,
+            SendTransactionsKind::Raw(wallets) => wallets.get(addr),
+        }
+    }
+
     /// How many signers are set
     pub fn signers_count(&self) -> usize {
         match self {
@@ -176,15 +218,42 @@ pub struct BundledState {
 }
 
 impl BundledState {
+    /// Shows how the transactions differ from the previous run, and writes the HTML report and the
+    /// simulated events, as requested by the arguments.
+    ///
+    /// Returns the L1→L2 deposits of the transactions, which aren't checked again when resuming.
+    pub async fn report(&self) -> Result<Vec<L1ToL2Deposit>> {
+        if self.args.diff_plan {
+            self.show_plan_diff()?;
+        }
+
+        let mut deposits = Vec::new();
+        if !self.args.resume {
+            deposits = self.check_deposits().await?;
+            if let Some(path) = &self.args.html_report {
+                self.write_html_report(path, &deposits)?;
+            }
+        }
+
+        if self.args.export_events {
+            self.export_events()?;
+        }
+        Ok(deposits)
+    }
+
     pub async fn wait_for_pending(mut self) -> Result<Self> {
+        let confirmations = self.script_config.policy.confirmations;
+        let config = &self.script_config.config;
         let futs = self
             .sequence
             .sequences_mut()
             .iter_mut()
             .map(|sequence| async move {
                 let rpc_url = sequence.rpc_url();
-                let provider = Arc::new(get_http_provider(rpc_url));
-                receipts::wait_for_pending(provider, sequence).await
+                let provider = with_adaptive_interval(get_http_provider(rpc_url), rpc_url).await;
+                let provider = Arc::new(provider);
+                let links = config.get_explorer_links(sequence.chain.into());
+                receipts::wait_for_pending(provider, sequence, confirmations, links.as_ref()).await
             })
             .collect::<Vec<_>>();
 
@@ -199,6 +268,16 @@ impl BundledState {
         Ok(self)
     }
 
+    /// Fails if broadcasting at the unix `timestamp` is frozen by the policy, unless overridden
+    /// with `--freeze-override`.
+    fn check_freeze_windows(&self, timestamp: u64) -> Result<()> {
+        self.script_config.policy.check_freeze_windows(
+            self.sequence.sequences().iter().map(|sequence| sequence.chain),
+            timestamp,
+            self.args.freeze_override.as_deref(),
+        )
+    }
+
     /// Broadcasts transactions from all sequences.
     pub async fn broadcast(mut self) -> Result<BroadcastedState> {
         let required_addresses = self
@@ -212,10 +291,29 @@ impl BundledState {
             })
             .collect::<HashSet<_>>();
 
+        self.script_config.policy.check(&self.sequence)?;
+        // a scheduled broadcast is checked against its target time before waiting for it
+        let now = now().as_secs();
+        self.check_freeze_windows(self.args.broadcast_at.map_or(now, |at| at.earliest_start(now)))?;
+        self.check_predicted_addresses().await?;
+        self.check_cross_chain_addresses()?;
+        chain_guard::confirm_chains(
+            self.sequence.sequences().iter().map(|sequence| sequence.chain),
+            &self.script_config.config.confirm_chain_ids,
+            &self.args.yes_chain,
+            self.args.non_interactive,
+        )?;
+        git_status::check_git_status(
+            self.build_data.build_data.git.as_ref(),
+            self.sequence.sequences().iter().map(|sequence| sequence.chain),
+            &self.script_config.config.confirm_chain_ids,
+            self.script_config.policy.require_clean_git,
+        )?;
+
         if required_addresses.contains(&Config::DEFAULT_SENDER) {
-            eyre::bail!(
+            eyre::bail!(tr!(
                 "You seem to be using Foundry's default sender. Be sure to set your own --sender."
-            );
+            ));
         }
 
         let send_kind = if self.args.unlocked {
@@ -231,30 +329,156 @@ impl BundledState {
             }
 
             if !missing_addresses.is_empty() {
-                eyre::bail!(
-                    "No associated wallet for addresses: {:?}. Unlocked wallets: {:?}",
-                    missing_addresses,
-                    signers.keys().collect::<Vec<_>>()
-                );
+                eyre::bail!(tr!(
+                    "No associated wallet for addresses: {addresses}. Unlocked wallets: {wallets}",
+                    addresses = format!("{missing_addresses:?}"),
+                    wallets = format!("{:?}", signers.keys().collect::<Vec<_>>()),
+                ));
             }
 
             SendTransactionsKind::Raw(signers)
         };
 
+        // Make sure we can sign the manifest before sending anything.
+        if let Some(manifest_signer) = &self.args.sign_manifest {
+            if send_kind.signer(manifest_signer).is_none() {
+                eyre::bail!("No associated wallet for manifest signer {manifest_signer}.");
+            }
+        }
+
+        if let Some(at) = self.args.broadcast_at {
+            at.wait(&self.sequence).await?;
+            // the broadcast may start later than expected, e.g. after waiting for a block
+            self.check_freeze_windows(now().as_secs())?;
+        }
+
+        let funding = self
+            .args
+            .fund_senders
+            .map(|_| {
+                FundingSource::new(
+                    self.args.faucet_url.as_deref(),
+                    self.args.funding_key.as_deref(),
+                )
+            })
+            .transpose()?;
+        if funding.is_some() {
+            funding::check_testnets(
+                self.sequence.sequences().iter().map(|sequence| sequence.chain),
+            )?;
+        }
+        if let Some(FundingSource::Key(funder)) = &funding {
+            if required_addresses.contains(&funder.address().to_alloy()) {
+                eyre::bail!(
+                    "The funding account can't be a sender of the script, its nonce would change."
+                )
+            }
+        }
+
+        // The ephemeral deployer is funded by, and swept back to, the account of `--funding-key`.
+        let ephemeral = match (&self.script_config.ephemeral_deployer, &self.args.funding_key) {
+            (Some(deployer), Some(key)) => {
+                let funder = create_private_key_signer(key)?;
+                if required_addresses.contains(&funder.address().to_alloy()) {
+                    eyre::bail!(
+                        "The funding account can't be a sender of the script, its nonce would \
+                         change."
+                    )
+                }
+                Some((deployer.clone(), funder))
+            }
+            _ => None,
+        };
+        let mut swept = true;
+
+        let root = self.script_config.config.__root.0.clone();
+        let hooks = self.script_config.config.script_hooks.clone();
+        let script = hooks::script_name(&self.build_data.build_data.target);
+        if let Some(command) = &hooks.pre_broadcast {
+            let payload = RunPayload {
+                hook: Hook::PreBroadcast,
+                script: &script,
+                sequences: self.sequence.sequences(),
+            };
+            hooks::run_hook(&root, Hook::PreBroadcast, command, &payload)?;
+        }
+
+        let metrics = self.args.metrics_addr.map(BroadcastMetrics::serve).transpose()?;
+        let fee_currency = self.args.fee_currency;
+
         for i in 0..self.sequence.sequences().len() {
             let mut sequence = self.sequence.sequences_mut().get_mut(i).unwrap();
 
-            let provider = Arc::new(try_get_http_provider(sequence.rpc_url())?);
+            let rpc_urls =
+                self.script_config.config.get_rpc_urls_with_fallbacks(sequence.rpc_url());
+            let mut failover = FailoverProvider::new(rpc_urls, sequence.chain).await?;
+            let provider = failover.provider();
             let already_broadcasted = sequence.receipts.len();
+            let links = self.script_config.config.get_explorer_links(sequence.chain.into());
+
+            // The chain id may have been cached when simulating.
+            if already_broadcasted < sequence.transactions.len() {
+                chain_metadata::check_chain_id(sequence.rpc_url(), &provider, sequence.chain)
+                    .await?;
+            }
+
+            if let (Some(funding), Some(amount)) = (&funding, self.args.fund_senders) {
+                if already_broadcasted < sequence.transactions.len() {
+                    let senders = sequence
+                        .typed_transactions()
+                        .filter_map(|tx| tx.from())
+                        .map(|from| from.to_alloy())
+                        .unique()
+                        .collect::<Vec<_>>();
+                    funding.fund(&provider, sequence.chain, &senders, amount).await?;
+                }
+            }
+
+            let reconciled =
+                if self.args.reconcile && already_broadcasted < sequence.transactions.len() {
+                    let accounts = reconciliation::accounts(sequence, already_broadcasted);
+                    let before = reconciliation::snapshot(&provider, &accounts).await?;
+                    Some((accounts, before))
+                } else {
+                    None
+                };
+
+            let watchdog = match self.args.halt_after {
+                Some(blocks) => {
+                    let watchdog = ChainWatchdog::new(&provider, sequence.chain, blocks).await;
+                    if watchdog.is_none() {
+                        warn!(chain = sequence.chain, "can't watch for halts without a block time");
+                    }
+                    match (watchdog, &hooks.chain_halt) {
+                        (Some(watchdog), Some(command)) => {
+                            Some(watchdog.with_hook(root.clone(), command.clone(), script.clone()))
+                        }
+                        (watchdog, _) => watchdog,
+                    }
+                }
+                None => None,
+            };
+
+            if self.args.simulate_pending && already_broadcasted < sequence.transactions.len() {
+                pending::simulate_pending(&provider, sequence, already_broadcasted).await?;
+            }
 
             if already_broadcasted < sequence.transactions.len() {
                 // Make a one-time gas price estimation
                 let (gas_price, eip1559_fees) = match self.args.with_gas_price {
                     None => match sequence.transactions.front().unwrap().typed_tx() {
                         TypedTransaction::Eip1559(_) => {
-                            let mut fees = estimate_eip1559_fees(&provider, Some(sequence.chain))
-                                .await
-                                .wrap_err("Failed to estimate EIP1559 fees. This chain might not support EIP1559, try adding --legacy to your command.")?;
+                            let mut fees = match fee_currency {
+                                // The suggested gas price in the fee currency covers the tip.
+                                Some(fee_currency) => {
+                                    let price =
+                                        fee_currency::gas_price(&provider, fee_currency).await?;
+                                    (price, price)
+                                }
+                                None => estimate_eip1559_fees(&provider, Some(sequence.chain))
+                                    .await
+                                    .wrap_err("Failed to estimate EIP1559 fees. This chain might not support EIP1559, try adding --legacy to your command.")?,
+                            };
 
                             if let Some(priority_gas_price) = self.args.priority_gas_price {
                                 fees.1 = priority_gas_price.to_ethers();
@@ -267,6 +491,13 @@ impl BundledState {
                     Some(gas_price) => (Some(gas_price.to_ethers()), None),
                 };
 
+                if let Some((deployer, funder)) = &ephemeral {
+                    let price = gas_price.or(eip1559_fees.map(|fees| fees.0)).unwrap_or_default();
+                    deployer
+                        .fund(&provider, funder, sequence, already_broadcasted, price.to_alloy())
+                        .await?;
+                }
+
                 // Iterate through transactions, matching the `from` field with the associated
                 // wallet. Then send the transaction. Panics if we find a unknown `from`
                 let transactions = sequence
@@ -309,6 +540,10 @@ impl BundledState {
                     })
                     .collect::<Result<Vec<_>>>()?;
 
+                if let Some(metrics) = &metrics {
+                    metrics.add_queued(transactions.len());
+                }
+
                 let estimate_via_rpc =
                     has_different_gas_calc(sequence.chain) || self.args.skip_simulation;
 
@@ -317,8 +552,10 @@ impl BundledState {
                 // their order otherwise.
                 // Or if the chain does not support batched transactions (eg. Arbitrum).
                 // Or if we need to invoke eth_estimateGas before sending transactions.
+                // Or if the deployments are checked before the next transactions are sent.
                 let sequential_broadcast = estimate_via_rpc ||
                     self.args.slow ||
+                    !self.script_config.deploy_checks.is_empty() ||
                     send_kind.signers_count() != 1 ||
                     !has_batch_support(sequence.chain);
 
@@ -327,27 +564,39 @@ impl BundledState {
                 // We send transactions and wait for receipts in batches of 100, since some networks
                 // cannot handle more than that.
                 let batch_size = if sequential_broadcast { 1 } else { 100 };
+                let estimate_multiplier = self.args.gas_estimate_multiplier();
                 let mut index = already_broadcasted;
 
                 for (batch_number, batch) in
                     transactions.chunks(batch_size).map(|f| f.to_vec()).enumerate()
                 {
                     let mut pending_transactions = vec![];
-
-                    shell::println(format!(
-                        "##\nSending transactions [{} - {}].",
-                        batch_number * batch_size,
-                        batch_number * batch_size + std::cmp::min(batch_size, batch.len()) - 1
+                    let batch_start = index;
+                    let batch_url = failover.url().to_string();
+
+                    shell::println(tr!(
+                        "##\nSending transactions [{first} - {last}].",
+                        first = batch_number * batch_size,
+                        last =
+                            batch_number * batch_size + std::cmp::min(batch_size, batch.len()) - 1
                     ))?;
-                    for (tx, kind, is_fixed_gas_limit) in batch.into_iter() {
+                    if let Some(watchdog) = &watchdog {
+                        watchdog.wait_while_halted(&failover.provider()).await?;
+                    }
+                    if let Some(chaos) = &self.args.chaos_plan {
+                        let total = sequence.transactions.len();
+                        chaos.before_send(&failover.provider(), batch_start, total).await?;
+                    }
+                    for (tx, kind, is_fixed_gas_limit) in batch.iter().cloned() {
                         let tx_hash = send_transaction(
-                            provider.clone(),
+                            failover.provider(),
                             tx,
                             kind,
                             sequential_broadcast,
                             is_fixed_gas_limit,
                             estimate_via_rpc,
-                            self.args.gas_estimate_multiplier,
+                            estimate_multiplier,
+                            fee_currency,
                         );
                         pending_transactions.push(tx_hash);
                     }
@@ -356,8 +605,39 @@ impl BundledState {
                         let mut buffer = futures::stream::iter(pending_transactions).buffered(7);
 
                         while let Some(tx_hash) = buffer.next().await {
+                            // Fail over to the next RPC endpoint, if any, when sending failed.
+                            let (tx_hash, accepted_by) = match tx_hash {
+                                Ok(tx_hash) => (Ok(tx_hash), batch_url.clone()),
+                                Err(err) => {
+                                    let (tx, kind, is_fixed_gas_limit) =
+                                        &batch[index - batch_start];
+                                    let tx_hash = failover
+                                        .retry(&batch_url, err, |provider| {
+                                            send_transaction(
+                                                provider,
+                                                tx.clone(),
+                                                kind.clone(),
+                                                sequential_broadcast,
+                                                *is_fixed_gas_limit,
+                                                estimate_via_rpc,
+                                                estimate_multiplier,
+                                                fee_currency,
+                                            )
+                                        })
+                                        .await;
+                                    (tx_hash, failover.url().to_string())
+                                }
+                            };
+                            if let Some(metrics) = &metrics {
+                                match &tx_hash {
+                                    Ok(_) => metrics.add_sent(1),
+                                    Err(_) => metrics.add_dropped(1),
+                                }
+                            }
                             let tx_hash = tx_hash.wrap_err("Failed to send transaction")?;
                             sequence.add_pending(index, tx_hash);
+                            // Record the endpoint which accepted the transaction.
+                            sequence.transactions[index].rpc = accepted_by;
 
                             // Checkpoint save
                             self.sequence.save(true, false)?;
@@ -371,8 +651,53 @@ impl BundledState {
                         self.sequence.save(true, false)?;
                         sequence = self.sequence.sequences_mut().get_mut(i).unwrap();
 
-                        shell::println("##\nWaiting for receipts.")?;
-                        receipts::clear_pendings(provider.clone(), sequence, None).await?;
+                        shell::println(tr!("##\nWaiting for receipts."))?;
+                        let pending = sequence.pending.len();
+                        let confirmed = sequence.receipts.len();
+                        let result = receipts::clear_pendings(
+                            failover.provider(),
+                            sequence,
+                            None,
+                            self.script_config.policy.confirmations,
+                            links.as_ref(),
+                        )
+                        .await;
+
+                        if let Some(command) = &hooks.post_tx {
+                            hooks::run_post_tx_hooks(
+                                &root,
+                                command,
+                                &script,
+                                sequence,
+                                &sequence.receipts[confirmed..],
+                                links.as_ref(),
+                            );
+                        }
+
+                        if let Some(metrics) = &metrics {
+                            let receipts = &sequence.receipts[confirmed..];
+                            let succeeded = receipts
+                                .iter()
+                                .filter(|receipt| receipt.status.map_or(true, |s| s.as_u64() == 1))
+                                .count();
+                            metrics.add_confirmed(succeeded);
+                            metrics.add_failed(pending.saturating_sub(succeeded));
+
+                            let provider = failover.provider();
+                            if let Ok(Some(block)) = provider.get_block(BlockNumber::Latest).await {
+                                metrics.set_base_fee(
+                                    block.base_fee_per_gas.unwrap_or_default().low_u64(),
+                                );
+                            }
+                        }
+
+                        result?;
+
+                        let checks = &self.script_config.deploy_checks;
+                        if !checks.is_empty() {
+                            let params = self.script_config.script_params.as_ref();
+                            checks.run(&failover.provider(), sequence, confirmed, params).await?;
+                        }
                     }
                     // Checkpoint save
                     self.sequence.save(true, false)?;
@@ -381,7 +706,7 @@ impl BundledState {
             }
 
             shell::println("\n\n==========================")?;
-            shell::println("\nONCHAIN EXECUTION COMPLETE & SUCCESSFUL.")?;
+            shell::println(tr!("\nONCHAIN EXECUTION COMPLETE & SUCCESSFUL."))?;
 
             let (total_gas, total_gas_price, total_paid) = sequence.receipts.iter().fold(
                 (U256::ZERO, U256::ZERO, U256::ZERO),
@@ -402,6 +727,33 @@ impl BundledState {
                 total_gas,
                 avg_gas_price.trim_end_matches('0').trim_end_matches('.')
             ))?;
+
+            if let Some((accounts, before)) = reconciled {
+                let after = reconciliation::snapshot(&failover.provider(), &accounts).await?;
+                Reconciliation::new(sequence, already_broadcasted, &before, &after)
+                    .write(sequence)?;
+            }
+
+            if let Some((deployer, funder)) = &ephemeral {
+                let to = funder.address().to_alloy();
+                if let Err(err) = deployer.sweep(&failover.provider(), sequence.chain, to).await {
+                    warn!(%err, chain = sequence.chain, "failed to sweep the ephemeral deployer");
+                    swept = false;
+                }
+            }
+        }
+
+        if let Some((deployer, _)) = &ephemeral {
+            if swept {
+                deployer.forget()?;
+            } else {
+                shell::println(Paint::yellow(format!(
+                    "The funds of the ephemeral deployer {} weren't all swept back, its key is kept \
+                     in {}",
+                    deployer.address(),
+                    deployer.path().display()
+                )))?;
+            }
         }
 
         Ok(BroadcastedState {
@@ -411,12 +763,101 @@ impl BundledState {
             execution_data: self.execution_data,
             execution_artifacts: self.execution_artifacts,
             sequence: self.sequence,
+            send_kind,
+            verification_report: None,
         })
     }
 
+    /// Waits for pending transactions and broadcasts the others, then verifies the deployed
+    /// contracts and signs the manifest if requested.
+    pub async fn broadcast_and_verify(self) -> Result<()> {
+        // Exit early if something is wrong with verification options.
+        if self.args.verify {
+            self.verify_preflight_check()?;
+        }
+
+        let superseded = self.check_registry()?;
+
+        let lock = self.lock_run().await?;
+        let mut broadcasted = self.wait_for_pending().await?.broadcast().await?;
+        lock.release().await?;
+
+        broadcasted.write_deployment_report()?;
+
+        // Runs against the Anvil fork of `--chaos` leave the deployments of the project alone.
+        if broadcasted.args.chaos_plan.is_some() {
+            return Ok(())
+        }
+
+        broadcasted.record_layouts()?;
+
+        let root = &broadcasted.script_config.config.__root.0;
+        for sequence in broadcasted.sequence.sequences() {
+            if let Some(path) = registry::record_deployments(root, sequence)? {
+                shell::println(format!("\nDeployments recorded in: {}", path.display()))?;
+            }
+        }
+
+        if !superseded.is_empty() {
+            let root = &broadcasted.script_config.config.__root.0;
+            let path = registry::record_superseded(root, &superseded)?;
+            shell::println(format!("\nSuperseded deployments recorded in: {}", path.display()))?;
+        }
+
+        if let Some(vanity) = &broadcasted.script_config.vanity {
+            let salts = broadcasted
+                .sequence
+                .sequences()
+                .iter()
+                .flat_map(|sequence| vanity::broadcast_salts(sequence, &vanity.salts))
+                .collect::<Vec<_>>();
+            if !salts.is_empty() {
+                let root = &broadcasted.script_config.config.__root.0;
+                let path = vanity::record_salts(root, &salts)?;
+                shell::println(format!("\nVanity salts recorded in: {}", path.display()))?;
+            }
+        }
+
+        if broadcasted.args.export_events {
+            broadcasted.export_events().await?;
+        }
+
+        if broadcasted.args.diff_onchain {
+            broadcasted.diff_onchain_traces().await?;
+        }
+
+        if broadcasted.args.verify {
+            broadcasted.verify().await?;
+        }
+
+        if let Err(err) = broadcasted.record_history() {
+            warn!(%err, "failed to record the run in the history database");
+        }
+
+        match broadcasted.write_changelog() {
+            Ok(Some(path)) => {
+                shell::println(format!("\nChangelog entry saved to: {}", path.display()))?
+            }
+            Ok(None) => {}
+            Err(err) => warn!(%err, "failed to write the changelog entry of the run"),
+        }
+
+        if let Err(err) = broadcasted.run_post_run_hook() {
+            warn!(%err, "post_run hook failed");
+        }
+
+        broadcasted.verify_ownership().await?;
+
+        if broadcasted.args.sign_manifest.is_some() {
+            broadcasted.sign_manifest().await?;
+        }
+
+        Ok(())
+    }
+
     pub fn verify_preflight_check(&self) -> Result<()> {
         for sequence in self.sequence.sequences() {
-            if self.args.verifier.verifier == VerificationProviderType::Etherscan &&
+            if self.args.verifier.verifier() == VerificationProviderType::Etherscan &&
                 self.script_config
                     .config
                     .get_etherscan_api_key(Some(sequence.chain.into()))
@@ -1,22 +1,32 @@
-use crate::{execute::LinkedState, ScriptArgs, ScriptConfig};
+use crate::{
+    execute::LinkedState, git_status::GitStatus, size_advisor::print_size_advice, units,
+    ScriptArgs, ScriptConfig,
+};
 
 use alloy_primitives::{Address, Bytes};
 use eyre::{Context, OptionExt, Result};
 use foundry_cheatcodes::ScriptWallets;
-use foundry_cli::utils::get_cached_entry_by_name;
+use foundry_cli::utils::get_cached_source_by_name;
 use foundry_common::{
-    compile::{self, ContractSources, ProjectCompiler},
-    ContractsByArtifact,
+    compile::{self, ContractSources, ProjectCompiler, SkipBuildFilters},
+    fs, BytecodeInterner, ContractsByArtifact, CONTRACT_MAX_SIZE,
 };
 use foundry_compilers::{
-    artifacts::{BytecodeObject, ContractBytecode, ContractBytecodeSome, Libraries},
-    cache::SolFilesCache,
+    artifacts::{
+        BytecodeObject, CompactContractBytecode, ContractBytecode, ContractBytecodeSome, Libraries,
+    },
     contracts::ArtifactContracts,
     info::ContractInfo,
-    ArtifactId,
+    ArtifactId, ConfigurableContractArtifact, Graph, Project, ProjectCompileOutput,
 };
+use foundry_config::Config;
 use foundry_linking::{LinkOutput, Linker};
-use std::str::FromStr;
+use std::{
+    collections::HashSet,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 /// Container for the compiled contracts.
 pub struct BuildData {
@@ -26,6 +36,8 @@ pub struct BuildData {
     pub target: ArtifactId,
     /// Source files of the contracts. Used by debugger.
     pub sources: ContractSources,
+    /// The status of the git repository of the project when it was compiled.
+    pub git: Option<GitStatus>,
 }
 
 impl BuildData {
@@ -42,19 +54,6 @@ impl BuildData {
 
         LinkedBuildData::new(link_output, self)
     }
-
-    /// Links the build data with the given libraries. Expects supplied libraries set being enough
-    /// to fully link target contract.
-    pub fn link_with_libraries(self, libraries: Libraries) -> Result<LinkedBuildData> {
-        let link_output =
-            self.linker.link_with_nonce_or_address(libraries, Address::ZERO, 0, &self.target)?;
-
-        if !link_output.libs_to_deploy.is_empty() {
-            eyre::bail!("incomplete libraries set");
-        }
-
-        LinkedBuildData::new(link_output, self)
-    }
 }
 
 /// Container for the linked contracts and their dependencies
@@ -71,16 +70,12 @@ pub struct LinkedBuildData {
 
 impl LinkedBuildData {
     pub fn new(link_output: LinkOutput, build_data: BuildData) -> Result<Self> {
+        let mut interner = BytecodeInterner::default();
         let highlevel_known_contracts = build_data
             .linker
             .get_linked_artifacts(&link_output.libraries)?
-            .iter()
-            .filter_map(|(id, contract)| {
-                ContractBytecodeSome::try_from(ContractBytecode::from(contract.clone()))
-                    .ok()
-                    .map(|tc| (id.clone(), tc))
-            })
-            .filter(|(_, tc)| tc.bytecode.object.is_non_empty_bytecode())
+            .into_iter()
+            .filter_map(|(id, contract)| Some((id, highlevel_contract(contract, &mut interner)?)))
             .collect();
 
         Ok(Self {
@@ -91,7 +86,61 @@ impl LinkedBuildData {
         })
     }
 
-    /// Flattens the contracts into  (`id` -> (`JsonAbi`, `Vec<u8>`)) pairs
+    /// Relinks the contracts with given libraries, using sender and nonce to compute addresses of
+    /// missing libraries.
+    ///
+    /// Only the contracts referencing a library whose address changed are linked again.
+    pub fn relink(self, known_libraries: Libraries, sender: Address, nonce: u64) -> Result<Self> {
+        let build_data = &self.build_data;
+        let link_output = build_data.linker.link_with_nonce_or_address(
+            known_libraries,
+            sender,
+            nonce,
+            &build_data.target,
+        )?;
+
+        self.update_libraries(link_output)
+    }
+
+    /// Relinks the contracts with the given libraries. Expects supplied libraries set being enough
+    /// to fully link target contract.
+    ///
+    /// Only the contracts referencing a library whose address changed are linked again.
+    pub fn relink_with_libraries(self, libraries: Libraries) -> Result<Self> {
+        let build_data = &self.build_data;
+        let link_output = build_data.linker.link_with_nonce_or_address(
+            libraries,
+            Address::ZERO,
+            0,
+            &build_data.target,
+        )?;
+
+        if !link_output.libs_to_deploy.is_empty() {
+            eyre::bail!("incomplete libraries set");
+        }
+
+        self.update_libraries(link_output)
+    }
+
+    fn update_libraries(mut self, link_output: LinkOutput) -> Result<Self> {
+        let linker = &self.build_data.linker;
+        let mut interner = BytecodeInterner::default();
+        let relink = linker.artifacts_to_relink(&self.libraries, &link_output.libraries);
+        trace!(target: "script", len=relink.len(), "relinking contracts");
+        for id in relink {
+            let contract = linker.link(id, &link_output.libraries)?;
+            match highlevel_contract(contract, &mut interner) {
+                Some(contract) => self.highlevel_known_contracts.0.insert(id.clone(), contract),
+                None => self.highlevel_known_contracts.0.remove(id),
+            };
+        }
+
+        self.libraries = link_output.libraries;
+        self.predeploy_libraries = link_output.libs_to_deploy;
+        Ok(self)
+    }
+
+    /// Flattens the contracts into (`id` -> (`JsonAbi`, `Bytes`)) pairs, sharing their bytecode
     pub fn get_flattened_contracts(&self, deployed_code: bool) -> ContractsByArtifact {
         ContractsByArtifact(
             self.highlevel_known_contracts
@@ -102,7 +151,7 @@ impl LinkedBuildData {
                     } else {
                         c.bytecode.bytes()
                     };
-                    bytecode.cloned().map(|code| (id.clone(), (c.abi.clone(), code.into())))
+                    bytecode.cloned().map(|code| (id.clone(), (c.abi.clone(), code)))
                 })
                 .collect(),
         )
@@ -117,6 +166,19 @@ impl LinkedBuildData {
     }
 }
 
+/// Converts a linked contract to a [ContractBytecodeSome], if it has bytecode.
+fn highlevel_contract(
+    contract: CompactContractBytecode,
+    interner: &mut BytecodeInterner,
+) -> Option<ContractBytecodeSome> {
+    let mut contract = ContractBytecodeSome::try_from(ContractBytecode::from(contract)).ok()?;
+    if !contract.bytecode.object.is_non_empty_bytecode() {
+        return None
+    }
+    interner.intern_contract(&mut contract);
+    Some(contract)
+}
+
 /// First state basically containing only inputs of the user.
 pub struct PreprocessedState {
     pub args: ScriptArgs,
@@ -129,7 +191,49 @@ impl PreprocessedState {
     /// After compilation, finds exact [ArtifactId] of the target contract.
     pub fn compile(self) -> Result<CompiledState> {
         let Self { args, script_config, script_wallets } = self;
-        let project = script_config.config.project()?;
+        // the state diff labels the changed slots with the storage layout of the contracts, and
+        // the upgrades of proxies are checked against the layout of their implementations
+        let storage_layout = args.state_diff || args.check_upgrades;
+        let project = script_project(&script_config.config, storage_layout)?;
+
+        let cache_key = BuildData::cache_key(&project, &args).unwrap_or_else(|err| {
+            warn!(%err, "failed to compute the script build cache key");
+            None
+        });
+        let git = GitStatus::read(project.root());
+        if let Some(mut build_data) =
+            cache_key.and_then(|key| BuildData::load_cached(&project, key))
+        {
+            if !args.opts.silent {
+                println!("No files changed, compilation skipped");
+            }
+            // the cached build doesn't know how it was compiled, check the target like
+            // `compile_target_with_filter` does
+            if args.verify {
+                let graph = Graph::resolve(&project.paths)?;
+                compile::ensure_verifiable(&build_data.target.source, &graph, args.verify)?;
+            }
+            build_data.git = git;
+            return Ok(CompiledState { args, script_config, script_wallets, build_data })
+        }
+
+        let mut build_data = Self::compile_project(&args, &script_config, &project)?;
+        if let Some(key) = cache_key {
+            if let Err(err) = build_data.save_cached(&project, key) {
+                warn!(%err, "failed to write the script build cache");
+            }
+        }
+        build_data.git = git;
+
+        Ok(CompiledState { args, script_config, script_wallets, build_data })
+    }
+
+    /// Compiles the project and finds the target contract.
+    fn compile_project(
+        args: &ScriptArgs,
+        script_config: &ScriptConfig,
+        project: &Project,
+    ) -> Result<BuildData> {
         let filters = args.skip.clone().unwrap_or_default();
 
         let mut target_name = args.target_contract.clone();
@@ -151,30 +255,36 @@ impl PreprocessedState {
 
         // If we've found target path above, only compile it.
         // Otherwise, compile everything to match contract by name later.
-        let output = if let Some(target_path) = target_path.clone() {
-            compile::compile_target_with_filter(
-                &target_path,
-                &project,
-                args.opts.silent,
-                args.verify,
-                filters,
-            )
+        let outputs = if let Some(target_path) = &target_path {
+            if let Some(outputs) =
+                Self::compile_units(args, &script_config.config, project, target_path)?
+            {
+                outputs
+            } else {
+                vec![compile::compile_target_with_filter(
+                    target_path,
+                    project,
+                    args.opts.silent,
+                    args.verify,
+                    filters,
+                    script_config.config.artifact_store_dir(),
+                )?]
+            }
         } else if !project.paths.has_input_files() {
-            Err(eyre::eyre!("The project doesn't have any input files. Make sure the `script` directory is configured properly in foundry.toml. Otherwise, provide the path to the file."))
+            eyre::bail!("The project doesn't have any input files. Make sure the `script` directory is configured properly in foundry.toml. Otherwise, provide the path to the file.")
         } else {
-            ProjectCompiler::new().compile(&project)
-        }?;
+            vec![ProjectCompiler::new()
+                .artifact_store(script_config.config.artifact_store_dir())
+                .compile(project)?]
+        };
 
         // If we still don't have target path, find it by name in the compilation cache.
         let target_path = if let Some(target_path) = target_path {
             target_path
         } else {
             let target_name = target_name.clone().expect("was set above");
-            let cache = SolFilesCache::read_joined(&project.paths)
-                .wrap_err("Could not open compiler cache")?;
-            let (path, _) = get_cached_entry_by_name(&cache, &target_name)
-                .wrap_err("Could not find target contract in cache")?;
-            path
+            get_cached_source_by_name(&project.paths, &target_name)
+                .wrap_err("Could not find target contract in cache")?
         };
 
         let target_path = project.root().join(target_path);
@@ -182,7 +292,11 @@ impl PreprocessedState {
         let mut target_id: Option<ArtifactId> = None;
 
         // Find target artfifact id by name and path in compilation artifacts.
-        for (id, contract) in output.artifact_ids().filter(|(id, _)| id.source == target_path) {
+        for (id, contract) in outputs
+            .iter()
+            .flat_map(|output| output.artifact_ids())
+            .filter(|(id, _)| id.source == target_path)
+        {
             if let Some(name) = &target_name {
                 if id.name != *name {
                     continue;
@@ -211,17 +325,183 @@ impl PreprocessedState {
             target_id = Some(id);
         }
 
-        let sources = ContractSources::from_project_output(&output, project.root())?;
-        let contracts = output.into_artifacts().collect();
         let target = target_id.ok_or_eyre("Could not find target contract")?;
+
+        // Only the artifacts the script can deploy are needed for linking.
+        let used_sources = script_sources(
+            project,
+            &target_path,
+            outputs.iter().flat_map(|output| output.artifact_ids()),
+        )
+        .unwrap_or_else(|err| {
+            warn!(%err, "failed to resolve the sources used by the script");
+            None
+        });
+        let (sources, contracts) =
+            ContractSources::from_project_output_with_artifacts(outputs, project.root(), |id| {
+                used_sources.as_ref().map_or(true, |used| used.contains(&id.source))
+            })?;
+        trace!(target: "script", len=contracts.len(), "collected script artifacts");
         let linker = Linker::new(project.root(), contracts);
 
-        Ok(CompiledState {
-            args,
-            script_config,
-            script_wallets,
-            build_data: BuildData { linker, target, sources },
-        })
+        Ok(BuildData { linker, target, sources, git: None })
+    }
+
+    /// Compiles the independent compilation units of the project concurrently, bounded by
+    /// `--jobs`, see [units::compile_units].
+    ///
+    /// Returns `None` if the project has to be compiled in a single run.
+    fn compile_units(
+        args: &ScriptArgs,
+        config: &Config,
+        project: &Project,
+        target_path: &Path,
+    ) -> Result<Option<Vec<ProjectCompileOutput>>> {
+        // verification reads the compiler versions of the contracts from the compiler cache of
+        // the project, which the units don't update
+        if args.verify {
+            return Ok(None)
+        }
+        let graph = Graph::resolve(&project.paths)?;
+        // standalone scripts are compiled on their own anyway
+        if !graph.files().contains_key(target_path) {
+            return Ok(None)
+        }
+
+        let filter = match args.skip.clone().filter(|skip| !skip.is_empty()) {
+            Some(skip) => Some(SkipBuildFilters::new(skip)?),
+            None => None,
+        };
+        let jobs = config
+            .solc_jobs
+            .filter(|jobs| *jobs > 0)
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, NonZeroUsize::get));
+        // the jobs are spent on the units, each of them runs one solc process at a time
+        let unit_config = Config { solc_jobs: Some(1), ..config.clone() };
+        let storage_layout = args.state_diff || args.check_upgrades;
+        let Some(outputs) = units::compile_units(project, &graph, filter.as_ref(), jobs, || {
+            script_project(&unit_config, storage_layout)
+        })?
+        else {
+            return Ok(None)
+        };
+
+        if !args.opts.silent {
+            if outputs.iter().all(|output| output.is_unchanged()) {
+                println!("No files changed, compilation skipped");
+            }
+            for output in outputs.iter().filter(|output| !output.is_unchanged()) {
+                println!("{output}");
+            }
+        }
+        Ok(Some(outputs))
+    }
+}
+
+/// Returns the project of `config` to compile scripts with, whose artifacts include the storage
+/// layouts of the contracts if `storage_layout` is set.
+fn script_project(config: &Config, storage_layout: bool) -> Result<Project> {
+    let mut project = config.project()?;
+    if storage_layout {
+        project.artifacts.additional_values.storage_layout = true;
+        let output_selection = project.artifacts.output_selection();
+        project.solc_config.settings.push_all(output_selection);
+    }
+    Ok(project)
+}
+
+/// Cheatcodes which deploy or read the code of an artifact given by its path.
+const CODE_CHEATCODES: [&str; 3] = ["deployCode", "getCode", "getDeployedCode"];
+
+/// Returns the source files whose contracts the script at `target_path` can deploy: the files it
+/// imports, and the files of the artifacts passed to `deployCode` and friends as string literals,
+/// together with their imports.
+///
+/// Returns `None` if the script is not part of the project, or if it passes a path which isn't a
+/// string literal to `deployCode` and friends, since it can then deploy any artifact. The
+/// libraries, e.g. `forge-std`, are not searched, as they only wrap the cheatcodes and forward the
+/// paths they're given by the script.
+fn script_sources<'a>(
+    project: &Project,
+    target_path: &Path,
+    artifact_ids: impl Iterator<Item = (ArtifactId, &'a ConfigurableContractArtifact)>,
+) -> Result<Option<HashSet<PathBuf>>> {
+    let graph = Graph::resolve(&project.paths)?;
+    if !graph.files().contains_key(target_path) {
+        return Ok(None)
+    }
+
+    let mut used = graph.imports(target_path).into_iter().cloned().collect::<HashSet<_>>();
+    used.insert(target_path.to_path_buf());
+
+    let mut targets = Vec::new();
+    for path in used.iter().filter(|path| !project.paths.has_library_ancestor(path)) {
+        let source = fs::read_to_string(path)?;
+        let Some(literals) = code_cheatcode_targets(&source) else { return Ok(None) };
+        targets.extend(literals.into_iter().map(str::to_string));
+    }
+    if targets.is_empty() {
+        return Ok(Some(used))
+    }
+    let targets = targets.iter().map(|target| ArtifactPath::parse(target)).collect::<Vec<_>>();
+
+    let deployed = artifact_ids
+        .map(|(id, _)| id)
+        .filter(|id| targets.iter().any(|target| target.matches(id)))
+        .map(|id| id.source)
+        .collect::<HashSet<_>>();
+    for path in deployed {
+        used.extend(graph.imports(&path).into_iter().cloned());
+        used.insert(path);
+    }
+    Ok(Some(used))
+}
+
+/// Returns the string literals passed to the [CODE_CHEATCODES] in `source`, or `None` if one of
+/// them is passed something else, e.g. a variable.
+fn code_cheatcode_targets(source: &str) -> Option<Vec<&str>> {
+    let mut targets = Vec::new();
+    for cheatcode in CODE_CHEATCODES {
+        for (start, _) in source.match_indices(cheatcode) {
+            if source[..start].trim_end().ends_with("function") {
+                continue
+            }
+            let args = source[start + cheatcode.len()..].trim_start();
+            let Some(args) = args.strip_prefix('(').map(str::trim_start) else { continue };
+            let quote = args.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+            let (target, _) = args[1..].split_once(quote)?;
+            targets.push(target);
+        }
+    }
+    Some(targets)
+}
+
+/// An artifact path as accepted by `deployCode`, e.g. `Counter.sol:Counter`, `Counter.sol` or
+/// `out/Counter.sol/Counter.json`.
+struct ArtifactPath<'a> {
+    file: Option<&'a str>,
+    name: Option<&'a str>,
+}
+
+impl<'a> ArtifactPath<'a> {
+    fn parse(path: &'a str) -> Self {
+        if let Some((file, name)) = path.split_once(':') {
+            Self { file: Some(file), name: Some(name) }
+        } else if let Some(json) = path.strip_suffix(".json") {
+            // <out>/<file>/<name>.json
+            let (dir, name) = json.rsplit_once('/').unwrap_or(("", json));
+            let file = dir.rsplit('/').next().filter(|file| !file.is_empty());
+            Self { file, name: Some(name.split('.').next().unwrap_or(name)) }
+        } else if path.ends_with(".sol") {
+            Self { file: Some(path), name: None }
+        } else {
+            Self { file: None, name: Some(path) }
+        }
+    }
+
+    fn matches(&self, id: &ArtifactId) -> bool {
+        self.file.map_or(true, |file| id.source.ends_with(file)) &&
+            self.name.map_or(true, |name| id.name.split('.').next() == Some(name))
     }
 }
 
@@ -243,6 +523,54 @@ impl CompiledState {
         let known_libraries = script_config.config.libraries_with_remappings()?;
         let build_data = build_data.link(known_libraries, sender, nonce)?;
 
+        let size_limit = script_config.evm_opts.env.code_size_limit.unwrap_or(CONTRACT_MAX_SIZE);
+        print_size_advice(&build_data.size_advice(&script_config.config, size_limit))?;
+
         Ok(LinkedState { args, script_config, script_wallets, build_data })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semver::Version;
+
+    #[test]
+    fn can_find_code_cheatcode_targets() {
+        let source = r#"
+            address a = deployCode("Counter.sol:Counter");
+            bytes memory code = vm.getCode( 'out/Token.sol/Token.json');
+            deployCode("Vault.sol", abi.encode(1));
+        "#;
+        let mut targets = code_cheatcode_targets(source).unwrap();
+        targets.sort_unstable();
+        assert_eq!(targets, ["Counter.sol:Counter", "Vault.sol", "out/Token.sol/Token.json"]);
+
+        // any artifact can be deployed from a path which isn't known statically
+        let source = r#"
+            function deployCode(string memory what) internal returns (address) {}
+            deployCode("Counter.sol:Counter");
+            vm.getDeployedCode(path);
+        "#;
+        assert_eq!(code_cheatcode_targets(source), None);
+        assert_eq!(
+            code_cheatcode_targets(&source[..source.find("vm.").unwrap()]),
+            Some(vec!["Counter.sol:Counter"])
+        );
+
+        let id = |source: &str, name: &str| ArtifactId {
+            path: PathBuf::new(),
+            name: name.to_string(),
+            source: PathBuf::from(source),
+            version: Version::new(0, 8, 23),
+        };
+        let counter = id("/root/src/Counter.sol", "Counter");
+        let token = id("/root/src/Token.sol", "Token.0.8.23");
+        assert!(ArtifactPath::parse("Counter.sol:Counter").matches(&counter));
+        assert!(ArtifactPath::parse("src/Counter.sol").matches(&counter));
+        assert!(ArtifactPath::parse("Counter").matches(&counter));
+        assert!(!ArtifactPath::parse("Counter.sol:Other").matches(&counter));
+        assert!(ArtifactPath::parse("out/Token.sol/Token.json").matches(&token));
+        assert!(!ArtifactPath::parse("out/Token.sol/Token.json").matches(&counter));
+    }
+}
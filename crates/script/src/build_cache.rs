@@ -0,0 +1,173 @@
+use crate::{build::BuildData, ScriptArgs};
+use alloy_primitives::{keccak256, B256};
+use eyre::{Result, WrapErr};
+use foundry_common::{artifact_store::ArtifactStore, compile::ContractSources, fs};
+use foundry_compilers::{artifacts::CompactContractBytecode, ArtifactId, Project};
+use foundry_linking::Linker;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{io::BufWriter, path::PathBuf};
+
+/// The name of the build cache file, in the cache directory of the project.
+const BUILD_CACHE_FILE_NAME: &str = "script-build-cache.cbor";
+
+/// A compact binary copy of the [BuildData] of the last script run.
+///
+/// Loading the JSON artifacts of a large project takes a lot of time and memory, even when
+/// nothing needs to be recompiled. The build cache only holds the bytecode, ABIs and sources the
+/// script needs, and is read instead of compiling the project when its sources didn't change.
+#[derive(Serialize, Deserialize)]
+struct BuildCache {
+    /// The hash of the sources, the compiler settings and the script target.
+    key: B256,
+    target: CachedArtifactId,
+    contracts: Vec<(CachedArtifactId, CompactContractBytecode)>,
+    sources: ContractSources,
+}
+
+/// Serializable copy of an [ArtifactId].
+#[derive(Serialize, Deserialize)]
+struct CachedArtifactId {
+    path: PathBuf,
+    name: String,
+    source: PathBuf,
+    version: String,
+}
+
+impl From<&ArtifactId> for CachedArtifactId {
+    fn from(id: &ArtifactId) -> Self {
+        Self {
+            path: id.path.clone(),
+            name: id.name.clone(),
+            source: id.source.clone(),
+            version: id.version.to_string(),
+        }
+    }
+}
+
+impl TryFrom<CachedArtifactId> for ArtifactId {
+    type Error = semver::Error;
+
+    fn try_from(id: CachedArtifactId) -> Result<Self, Self::Error> {
+        Ok(Self {
+            path: id.path,
+            name: id.name,
+            source: id.source,
+            version: Version::parse(&id.version)?,
+        })
+    }
+}
+
+impl BuildData {
+    /// Returns the key of the build cache of `args` in `project`, or `None` if the build cache
+    /// can't be used.
+    pub fn cache_key(project: &Project, args: &ScriptArgs) -> Result<Option<B256>> {
+        if !project.cached || !project.paths.artifacts.exists() {
+            return Ok(None)
+        }
+
+        let mut input = ArtifactStore::key(project)?.to_vec();
+        input.extend_from_slice(args.path.as_bytes());
+        input.extend(args.target_contract.iter().flat_map(|name| name.as_bytes()));
+        input.extend(format!("{:?}", args.skip).into_bytes());
+        // standalone scripts are not part of the sources of the project
+        if let Ok(path) = dunce::canonicalize(&args.path) {
+            input.extend_from_slice(keccak256(fs::read(path)?).as_slice());
+        }
+        Ok(Some(keccak256(input)))
+    }
+
+    /// Loads the build data from the build cache of `project`, if it was written with `key`.
+    pub fn load_cached(project: &Project, key: B256) -> Option<Self> {
+        let path = cache_file(project);
+        let bytes = fs::read(&path).ok()?;
+        let cache: BuildCache = match ciborium::from_reader(bytes.as_slice()) {
+            Ok(cache) => cache,
+            Err(err) => {
+                warn!(%err, ?path, "failed to read the script build cache");
+                return None
+            }
+        };
+        if cache.key != key {
+            return None
+        }
+
+        let contracts = cache
+            .contracts
+            .into_iter()
+            .map(|(id, contract)| Ok((id.try_into()?, contract)))
+            .collect::<Result<_, semver::Error>>()
+            .ok()?;
+
+        trace!(?path, "loaded build data from the script build cache");
+        Some(Self {
+            linker: Linker::new(project.root(), contracts),
+            target: cache.target.try_into().ok()?,
+            sources: cache.sources,
+            git: None,
+        })
+    }
+
+    /// Writes the build data to the build cache of `project`.
+    pub fn save_cached(&self, project: &Project, key: B256) -> Result<()> {
+        let cache = BuildCache {
+            key,
+            target: (&self.target).into(),
+            contracts: self
+                .linker
+                .contracts
+                .iter()
+                .map(|(id, contract)| (id.into(), contract.clone()))
+                .collect(),
+            sources: self.sources.clone(),
+        };
+
+        // Replace the file instead of truncating it, as it may be read by another run.
+        let path = cache_file(project);
+        let tmp = path.with_extension(format!("tmp-{}", std::process::id()));
+        ciborium::into_writer(&cache, BufWriter::new(fs::create_file(&tmp)?))
+            .wrap_err("failed to write the script build cache")?;
+        std::fs::rename(&tmp, &path)
+            .wrap_err_with(|| format!("failed to move {} to {}", tmp.display(), path.display()))
+    }
+}
+
+fn cache_file(project: &Project) -> PathBuf {
+    project.paths.cache.with_file_name(BUILD_CACHE_FILE_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_config::Config;
+
+    #[test]
+    fn can_roundtrip_build_cache() {
+        let root = tempfile::tempdir().unwrap();
+        let project = Config::with_root(root.path()).project().unwrap();
+        fs::create_dir_all(project.paths.cache.parent().unwrap()).unwrap();
+
+        let target = ArtifactId {
+            path: root.path().join("out/Deploy.s.sol/Deploy.json"),
+            name: "Deploy".to_string(),
+            source: root.path().join("script/Deploy.s.sol"),
+            version: Version::new(0, 8, 23),
+        };
+        let contracts =
+            [(target.clone(), CompactContractBytecode::default())].into_iter().collect();
+        let build_data = BuildData {
+            linker: Linker::new(root.path(), contracts),
+            target,
+            sources: Default::default(),
+            git: None,
+        };
+
+        let key = keccak256("sources");
+        build_data.save_cached(&project, key).unwrap();
+        let loaded = BuildData::load_cached(&project, key).unwrap();
+        assert_eq!(loaded.target, build_data.target);
+        assert_eq!(loaded.linker.contracts.len(), 1);
+
+        assert!(BuildData::load_cached(&project, keccak256("changed")).is_none());
+    }
+}
@@ -0,0 +1,178 @@
+use alloy_primitives::Address;
+use clap::ValueEnum;
+use eyre::Result;
+use foundry_cli::utils::now;
+use foundry_common::{fs, get_contract_name};
+use foundry_evm::{
+    constants::{CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS},
+    traces::{CallTraceArena, CallTraceDecoder, DecodedCallData},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+/// The format of the call graph of the simulated transactions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CallGraphFormat {
+    /// A Graphviz DOT graph.
+    Dot,
+    /// A Mermaid flowchart.
+    Mermaid,
+}
+
+impl CallGraphFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Dot => "dot",
+            Self::Mermaid => "mmd",
+        }
+    }
+}
+
+/// The contracts taking part in the simulated transactions, and the calls between them.
+#[derive(Debug, Default)]
+pub struct CallGraph {
+    /// The name of each account, and whether it was deployed by the script.
+    nodes: BTreeMap<Address, (String, bool)>,
+    /// The functions called by an account on another one, by caller and callee.
+    edges: BTreeMap<(Address, Address), BTreeSet<String>>,
+}
+
+impl CallGraph {
+    /// Adds the accounts and the calls of `arena` to the graph.
+    pub async fn add(&mut self, arena: &CallTraceArena, decoder: &CallTraceDecoder) {
+        decoder.prefetch_signatures(arena.nodes()).await;
+
+        let nodes = arena.nodes();
+        for node in nodes {
+            let trace = &node.trace;
+            if trace.address == CHEATCODE_ADDRESS || trace.address == HARDHAT_CONSOLE_ADDRESS {
+                continue
+            }
+
+            let decoded = decoder.decode_function(trace).await;
+            let name = match (&decoded.contract, &decoded.label) {
+                (Some(contract), _) => get_contract_name(contract).to_string(),
+                (None, Some(label)) => label.clone(),
+                (None, None) => trace.address.to_string(),
+            };
+            let deployed = trace.kind.is_any_create() && trace.success;
+            let entry = self.nodes.entry(trace.address).or_insert((name, false));
+            entry.1 |= deployed;
+
+            // the top-level calls are made by the sender of the transaction
+            let caller = match node.parent {
+                Some(parent) => nodes[parent].trace.address,
+                None => {
+                    self.nodes.entry(trace.caller).or_insert_with(|| {
+                        let name = decoder.labels.get(&trace.caller).cloned();
+                        (name.unwrap_or_else(|| format!("sender {}", trace.caller)), false)
+                    });
+                    trace.caller
+                }
+            };
+            let function = match decoded.func {
+                _ if trace.kind.is_any_create() => "new".to_string(),
+                Some(DecodedCallData { signature, .. }) => {
+                    signature.split('(').next().unwrap_or(&signature).to_string()
+                }
+                None if trace.data.len() < 4 => "fallback".to_string(),
+                None => hex::encode_prefixed(&trace.data[..4]),
+            };
+            self.edges.entry((caller, trace.address)).or_default().insert(function);
+        }
+    }
+
+    /// Renders the graph in `format`, highlighting the contracts deployed by the script.
+    pub fn render(&self, format: CallGraphFormat) -> String {
+        let mut out = String::new();
+        let id = |address: &Address| format!("n{}", hex::encode(&address[..]));
+        match format {
+            CallGraphFormat::Dot => {
+                out.push_str("digraph calls {\n    node [shape=box];\n");
+                for (address, (name, deployed)) in &self.nodes {
+                    let style = if *deployed { ", style=bold" } else { "" };
+                    let _ =
+                        writeln!(out, "    {} [label=\"{name}\\n{address}\"{style}];", id(address));
+                }
+                for ((from, to), functions) in &self.edges {
+                    let label = functions.iter().cloned().collect::<Vec<_>>().join("\\n");
+                    let _ = writeln!(out, "    {} -> {} [label=\"{label}\"];", id(from), id(to));
+                }
+                out.push_str("}\n");
+            }
+            CallGraphFormat::Mermaid => {
+                out.push_str("flowchart LR\n");
+                for (address, (name, deployed)) in &self.nodes {
+                    let (open, close) = if *deployed { ("[[", "]]") } else { ("[", "]") };
+                    let _ =
+                        writeln!(out, "    {}{open}\"{name}<br/>{address}\"{close}", id(address));
+                }
+                for ((from, to), functions) in &self.edges {
+                    let label = functions.iter().cloned().collect::<Vec<_>>().join("<br/>");
+                    let _ = writeln!(out, "    {} -->|\"{label}\"| {}", id(from), id(to));
+                }
+            }
+        }
+        out
+    }
+
+    /// Writes the graph in `format` to the `call-graphs` directory of the project cache, and
+    /// returns the path of the file.
+    pub fn save(
+        &self,
+        format: CallGraphFormat,
+        cache_path: &Path,
+        contract: &str,
+    ) -> Result<PathBuf> {
+        let dir = cache_path.join("call-graphs");
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{contract}-{}.{}", now().as_secs(), format.extension()));
+        fs::write(&path, self.render(format))?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> CallGraph {
+        let (sender, factory, token) =
+            (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+        let mut graph = CallGraph::default();
+        graph.nodes.insert(sender, (format!("sender {sender}"), false));
+        graph.nodes.insert(factory, ("Factory".to_string(), false));
+        graph.nodes.insert(token, ("Token".to_string(), true));
+        graph.edges.insert((sender, factory), BTreeSet::from(["deploy".to_string()]));
+        graph
+            .edges
+            .insert((factory, token), BTreeSet::from(["new".to_string(), "mint".to_string()]));
+        graph
+    }
+
+    #[test]
+    fn can_render_dot() {
+        let dot = graph().render(CallGraphFormat::Dot);
+        let factory = format!("n{}", "02".repeat(20));
+        let token = format!("n{}", "03".repeat(20));
+        assert!(dot.starts_with("digraph calls {"));
+        assert!(dot.contains(&format!(
+            "{token} [label=\"Token\\n{}\", style=bold];",
+            Address::repeat_byte(3)
+        )));
+        assert!(dot.contains(&format!("{factory} -> {token} [label=\"mint\\nnew\"];")));
+    }
+
+    #[test]
+    fn can_render_mermaid() {
+        let mermaid = graph().render(CallGraphFormat::Mermaid);
+        let factory = format!("n{}", "02".repeat(20));
+        let token = format!("n{}", "03".repeat(20));
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains(&format!("{token}[[\"Token<br/>{}\"]]", Address::repeat_byte(3))));
+        assert!(mermaid.contains(&format!("{factory} -->|\"mint<br/>new\"| {token}")));
+    }
+}
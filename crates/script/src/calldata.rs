@@ -0,0 +1,211 @@
+use crate::{
+    l1_fees::L1FeeModel, reconciliation::format_ether, sequence::ScriptSequence,
+    transaction::TransactionWithMetadata,
+};
+use alloy_primitives::U256;
+use comfy_table::{presets::ASCII_MARKDOWN, Attribute, Cell, Color, Table};
+use foundry_common::provider::ethers::try_get_http_provider;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The gas charged for a zero byte of calldata.
+const ZERO_BYTE_GAS: u64 = 4;
+/// The gas charged for a non-zero byte of calldata, since EIP-2028.
+const NON_ZERO_BYTE_GAS: u64 = 16;
+
+/// Smaller calldata isn't worth optimizing.
+const MIN_HINTED_SIZE: usize = 256;
+/// Calldata with at least this percentage of zero bytes is mostly the padding of small arguments.
+const PADDED_PERCENT: usize = 50;
+/// Calldata costing at least this percentage of the gas of its transaction dominates its cost.
+const HEAVY_PERCENT: u64 = 25;
+/// Calldata at least this large may be cheaper to post in a blob.
+const BLOB_CANDIDATE_SIZE: usize = 16 * 1024;
+
+/// The size and cost of the calldata of a transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalldataCost {
+    pub chain: u64,
+    /// The index of the transaction in its sequence.
+    pub transaction: usize,
+    /// The contract deployed or the function called by the transaction.
+    pub label: String,
+    pub size: usize,
+    pub zero_bytes: usize,
+    /// The gas charged for the calldata.
+    pub gas: u64,
+    /// The gas limit of the transaction.
+    pub gas_limit: u64,
+    /// The fee charged for posting the transaction to L1, on rollups, in wei.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_fee: Option<U256>,
+}
+
+impl CalldataCost {
+    pub fn new(chain: u64, transaction: usize, tx: &TransactionWithMetadata) -> Self {
+        let data = tx.typed_tx().data().map(|data| data.to_vec()).unwrap_or_default();
+        let zero_bytes = data.iter().filter(|byte| **byte == 0).count();
+        let non_zero_bytes = (data.len() - zero_bytes) as u64;
+        let label = tx
+            .contract_name
+            .clone()
+            .filter(|name| !name.is_empty())
+            .or_else(|| tx.function.clone())
+            .unwrap_or_default();
+        Self {
+            chain,
+            transaction,
+            label,
+            size: data.len(),
+            zero_bytes,
+            gas: zero_bytes as u64 * ZERO_BYTE_GAS + non_zero_bytes * NON_ZERO_BYTE_GAS,
+            gas_limit: tx.typed_tx().gas().map_or(0, |gas| gas.as_u64()),
+            l1_fee: None,
+        }
+    }
+
+    /// Returns the share of the gas of the transaction spent on its calldata, in percent.
+    pub fn gas_percent(&self) -> u64 {
+        if self.gas_limit == 0 {
+            return 0
+        }
+        self.gas * 100 / self.gas_limit
+    }
+
+    /// Returns how the transaction could be made cheaper.
+    pub fn hints(&self) -> Vec<String> {
+        let mut hints = Vec::new();
+        if self.size < MIN_HINTED_SIZE {
+            return hints
+        }
+        if self.zero_bytes * 100 >= self.size * PADDED_PERCENT {
+            hints.push(format!(
+                "{}% of the calldata are zero bytes, packing the arguments into fewer words would \
+                 shrink it",
+                self.zero_bytes * 100 / self.size
+            ));
+        }
+        if self.gas_percent() >= HEAVY_PERCENT {
+            hints.push(format!("the calldata costs {}% of the gas", self.gas_percent()));
+        }
+        if self.size >= BLOB_CANDIDATE_SIZE {
+            hints.push(format!(
+                "the calldata is {} KiB, posting the data in a blob would be cheaper if it's only \
+                 needed off-chain",
+                self.size / 1024
+            ));
+        }
+        hints
+    }
+}
+
+/// Analyzes the calldata of the transactions of the sequences, with their L1 data fees on
+/// rollups.
+pub async fn analyze(sequences: &[ScriptSequence]) -> Vec<CalldataCost> {
+    let mut costs = Vec::new();
+    for sequence in sequences {
+        let provider =
+            sequence.transactions.front().and_then(|tx| try_get_http_provider(&tx.rpc).ok());
+        let model = match &provider {
+            Some(provider) => L1FeeModel::detect(provider, sequence.chain).await,
+            None => None,
+        };
+        for (i, tx) in sequence.transactions.iter().enumerate() {
+            let mut cost = CalldataCost::new(sequence.chain, i, tx);
+            if let (Some(provider), Some(model)) = (&provider, model) {
+                match model.estimate(provider, tx.typed_tx()).await {
+                    Ok(fee) => cost.l1_fee = Some(fee),
+                    Err(err) => trace!("L1 fee estimation failed: {err}"),
+                }
+            }
+            costs.push(cost);
+        }
+    }
+    costs
+}
+
+/// The calldata costs of the transactions, as a table.
+pub struct CalldataCosts<'a>(pub &'a [CalldataCost]);
+
+impl fmt::Display for CalldataCosts<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.load_preset(ASCII_MARKDOWN);
+        table.set_header([
+            Cell::new("Chain").add_attribute(Attribute::Bold),
+            Cell::new("#").add_attribute(Attribute::Bold),
+            Cell::new("Transaction").add_attribute(Attribute::Bold),
+            Cell::new("bytes").add_attribute(Attribute::Bold),
+            Cell::new("zero bytes").add_attribute(Attribute::Bold),
+            Cell::new("calldata gas").add_attribute(Attribute::Bold).fg(Color::Yellow),
+            Cell::new("% of gas").add_attribute(Attribute::Bold).fg(Color::Yellow),
+            Cell::new("L1 fee (ETH)").add_attribute(Attribute::Bold).fg(Color::Red),
+        ]);
+        for cost in self.0 {
+            table.add_row([
+                Cell::new(cost.chain.to_string()),
+                Cell::new(cost.transaction.to_string()),
+                Cell::new(&cost.label),
+                Cell::new(cost.size.to_string()),
+                Cell::new(cost.zero_bytes.to_string()),
+                Cell::new(cost.gas.to_string()).fg(Color::Yellow),
+                Cell::new(cost.gas_percent().to_string()).fg(Color::Yellow),
+                Cell::new(cost.l1_fee.map(format_ether).unwrap_or_default()).fg(Color::Red),
+            ]);
+        }
+        writeln!(f, "{table}")?;
+        for cost in self.0 {
+            for hint in cost.hints() {
+                writeln!(f, "Transaction {} on chain {}: {hint}", cost.transaction, cost.chain)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, Bytes, U64};
+    use alloy_rpc_types::request::TransactionRequest;
+
+    fn tx(data: Vec<u8>, gas: u64) -> TransactionWithMetadata {
+        let mut tx = TransactionWithMetadata::from_tx_request(TransactionRequest {
+            from: Some(Address::with_last_byte(1)),
+            to: Some(Address::with_last_byte(2)),
+            input: Some(Bytes::from(data)).into(),
+            gas: Some(U256::from(gas)),
+            nonce: Some(U64::ZERO),
+            ..Default::default()
+        });
+        tx.function = Some("store(uint256[])".to_string());
+        tx
+    }
+
+    #[test]
+    fn analyzes_calldata() {
+        let cost = CalldataCost::new(1, 0, &tx(vec![0, 0, 1, 2], 21_100));
+        assert_eq!(cost.size, 4);
+        assert_eq!(cost.zero_bytes, 2);
+        assert_eq!(cost.gas, 2 * 4 + 2 * 16);
+        assert_eq!(cost.label, "store(uint256[])");
+        assert!(cost.hints().is_empty());
+
+        // Small arguments padded to words.
+        let data = (0..64u8).flat_map(|i| [vec![0; 31], vec![i + 1]].concat()).collect();
+        let cost = CalldataCost::new(1, 1, &tx(data, 100_000));
+        assert_eq!(cost.size, 2048);
+        assert_eq!(cost.gas, 1984 * 4 + 64 * 16);
+        assert_eq!(cost.gas_percent(), 8);
+        let hints = cost.hints();
+        assert_eq!(hints.len(), 1);
+        assert!(hints[0].starts_with("96% of the calldata are zero bytes"), "{hints:?}");
+
+        let cost = CalldataCost::new(1, 2, &tx(vec![0xff; 20 * 1024], 400_000));
+        let hints = cost.hints();
+        assert_eq!(hints.len(), 2, "{hints:?}");
+        assert!(hints[0].starts_with("the calldata costs 81% of the gas"));
+        assert!(hints[1].starts_with("the calldata is 20 KiB"));
+    }
+}
@@ -0,0 +1,73 @@
+use dialoguer::Input;
+use eyre::Result;
+use foundry_common::shell;
+use foundry_config::Chain;
+use std::io::IsTerminal;
+use yansi::Paint;
+
+/// Returns the chains of the script which are listed in `confirm_chain_ids` and weren't confirmed
+/// with `--yes-chain`.
+fn unconfirmed_chains(
+    chains: impl IntoIterator<Item = u64>,
+    confirm_chain_ids: &[u64],
+    confirmed: &[u64],
+) -> Vec<u64> {
+    let mut unconfirmed = chains
+        .into_iter()
+        .filter(|chain| confirm_chain_ids.contains(chain) && !confirmed.contains(chain))
+        .collect::<Vec<_>>();
+    unconfirmed.sort_unstable();
+    unconfirmed.dedup();
+    unconfirmed
+}
+
+/// Requires the name of each chain listed in `confirm_chain_ids` to be typed before broadcasting
+/// to it, unless it's confirmed with `--yes-chain`.
+///
+/// Fails without prompting if the session isn't interactive.
+pub fn confirm_chains(
+    chains: impl IntoIterator<Item = u64>,
+    confirm_chain_ids: &[u64],
+    confirmed: &[u64],
+    non_interactive: bool,
+) -> Result<()> {
+    for id in unconfirmed_chains(chains, confirm_chain_ids, confirmed) {
+        let name = Chain::from(id).to_string();
+        if non_interactive || !std::io::stdin().is_terminal() {
+            eyre::bail!(
+                "Broadcasting to {name} (chain id {id}) must be confirmed. Pass `--yes-chain {id}` \
+                 to confirm it without a prompt."
+            )
+        }
+
+        shell::println(Paint::red(format!(
+            "\nYou are about to broadcast transactions to {name} (chain id {id})."
+        )))?;
+        let typed: String =
+            Input::new().with_prompt(format!("Type `{name}` to continue")).interact_text()?;
+        if !typed.trim().eq_ignore_ascii_case(&name) {
+            eyre::bail!("Broadcasting to {name} wasn't confirmed.")
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_listed_chains_need_confirmation() {
+        assert_eq!(unconfirmed_chains([1, 11155111, 1], &[1, 196], &[]), vec![1]);
+        assert_eq!(unconfirmed_chains([1, 196], &[1, 196], &[196]), vec![1]);
+        assert!(unconfirmed_chains([11155111], &[1, 196], &[]).is_empty());
+        assert!(unconfirmed_chains([1], &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn fails_without_confirmation_when_non_interactive() {
+        let err = confirm_chains([1], &[1], &[], true).unwrap_err();
+        assert!(err.to_string().contains("--yes-chain 1"), "{err}");
+        confirm_chains([1], &[1], &[1], true).unwrap();
+    }
+}
@@ -0,0 +1,222 @@
+use alloy_primitives::{keccak256, U256};
+use ethers_core::types::BlockNumber;
+use ethers_providers::{Middleware, Provider};
+use eyre::Result;
+use foundry_common::{
+    fs, provider::ethers::RetryProvider, runtime_client::RuntimeClient, types::ToAlloy,
+};
+use foundry_config::Config;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The name of the file, in the RPC cache directory, which holds the metadata of the chains
+/// scripts were simulated against.
+const CHAIN_METADATA_FILE_NAME: &str = "script-chain-metadata.json";
+
+/// How long the chain id and the EIP-1559 support of a chain are reused, in seconds.
+const CHAIN_MAX_AGE: u64 = 24 * 60 * 60;
+
+/// How long the fees and the block gas limit of a chain are reused, in seconds.
+const FEES_MAX_AGE: u64 = 60;
+
+/// The metadata of a chain which is discovered before simulating transactions on it.
+///
+/// It's only used to prepare and estimate the simulated transactions: the fees are estimated again
+/// before signing, and the chain id is checked against the endpoint by [check_chain_id].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainMetadata {
+    pub chain_id: u64,
+    /// Whether the latest block has a base fee.
+    pub supports_eip1559: bool,
+    /// The estimated max fee and priority fee, if the chain supports EIP-1559.
+    pub eip1559_fees: Option<(U256, U256)>,
+    pub block_gas_limit: U256,
+    /// When the chain id was fetched.
+    chain_timestamp: u64,
+    /// When the fees and the block gas limit were fetched.
+    fees_timestamp: u64,
+}
+
+impl ChainMetadata {
+    /// Returns the metadata of the chain behind `rpc`.
+    ///
+    /// The metadata of the previous runs is reused while it's recent enough, so that consecutive
+    /// runs against the same chain skip the discovery requests.
+    pub async fn get(rpc: &str, provider: &Provider<RuntimeClient>) -> Result<Self> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let path = ChainMetadataCache::path().filter(|_| !is_local_rpc(rpc));
+        let mut cache: ChainMetadataCache =
+            path.as_ref().and_then(|path| fs::read_json_file(path).ok()).unwrap_or_default();
+
+        let cached = cache.get(rpc, now);
+        if let Some(metadata) = cached.filter(|metadata| metadata.fees_are_fresh(now)) {
+            trace!(target: "script", chain = metadata.chain_id, "reusing cached chain metadata");
+            return Ok(metadata.clone())
+        }
+
+        let chain = cached.map(|metadata| (metadata.chain_id, metadata.chain_timestamp));
+        let metadata = Self::fetch(provider, chain, now).await?;
+        if let Some(path) = path {
+            cache.insert(rpc, metadata.clone());
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write_json_file(&path, &cache)?;
+        }
+        Ok(metadata)
+    }
+
+    async fn fetch(
+        provider: &Provider<RuntimeClient>,
+        chain: Option<(u64, u64)>,
+        now: u64,
+    ) -> Result<Self> {
+        let (chain_id, chain_timestamp) = match chain {
+            Some(chain) => chain,
+            None => (provider.get_chainid().await?.as_u64(), now),
+        };
+        let block = provider
+            .get_block(BlockNumber::Latest)
+            .await?
+            .ok_or_else(|| eyre::eyre!("Failed to get the latest block"))?;
+
+        let supports_eip1559 = block.base_fee_per_gas.is_some();
+        let eip1559_fees = if supports_eip1559 {
+            provider
+                .estimate_eip1559_fees(None)
+                .await
+                .ok()
+                .map(|(max_fee, priority_fee)| (max_fee.to_alloy(), priority_fee.to_alloy()))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            chain_id,
+            supports_eip1559,
+            eip1559_fees,
+            block_gas_limit: block.gas_limit.to_alloy(),
+            chain_timestamp,
+            fees_timestamp: now,
+        })
+    }
+
+    fn fees_are_fresh(&self, now: u64) -> bool {
+        self.eip1559_fees.is_some() == self.supports_eip1559 &&
+            now.saturating_sub(self.fees_timestamp) <= FEES_MAX_AGE
+    }
+}
+
+/// Fails if the endpoint the sequence was simulated against with `rpc` doesn't serve `chain`
+/// anymore, e.g. if it was pointed to another network since its chain id was cached, so that no
+/// transaction is signed for the wrong chain.
+///
+/// The cached metadata of `rpc` is dropped then.
+pub async fn check_chain_id(rpc: &str, provider: &RetryProvider, chain: u64) -> Result<()> {
+    let live = provider.get_chainid().await?.as_u64();
+    if live == chain {
+        return Ok(())
+    }
+    if let Some(path) = ChainMetadataCache::path() {
+        let mut cache: ChainMetadataCache = fs::read_json_file(&path).unwrap_or_default();
+        if cache.remove(rpc) {
+            fs::write_json_file(&path, &cache)?;
+        }
+    }
+    eyre::bail!(
+        "The RPC endpoint of chain {chain} now serves chain {live}, nothing was broadcast. Run the \
+         script again to simulate it against the current chain."
+    )
+}
+
+/// The metadata of the chains of the previous runs, by hash of their RPC URL so that API keys
+/// aren't written to disk.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ChainMetadataCache(BTreeMap<String, ChainMetadata>);
+
+impl ChainMetadataCache {
+    fn path() -> Option<PathBuf> {
+        Some(Config::foundry_rpc_cache_dir()?.join(CHAIN_METADATA_FILE_NAME))
+    }
+
+    fn key(url: &str) -> String {
+        keccak256(url).to_string()
+    }
+
+    /// Returns the metadata of the chain behind `url`, if its chain id is recent enough.
+    fn get(&self, url: &str, now: u64) -> Option<&ChainMetadata> {
+        let metadata = self.0.get(&Self::key(url))?;
+        (now.saturating_sub(metadata.chain_timestamp) <= CHAIN_MAX_AGE).then_some(metadata)
+    }
+
+    fn insert(&mut self, url: &str, metadata: ChainMetadata) {
+        self.0.insert(Self::key(url), metadata);
+    }
+
+    /// Removes the metadata of the chain behind `url`, and returns whether there was any.
+    fn remove(&mut self, url: &str) -> bool {
+        self.0.remove(&Self::key(url)).is_some()
+    }
+}
+
+/// Whether `rpc` is served by the local machine, e.g. by anvil, whose chain can be restarted with
+/// a different configuration at any time.
+fn is_local_rpc(rpc: &str) -> bool {
+    let rest = rpc.split_once("://").map_or(rpc, |(_, rest)| rest);
+    let authority = rest.split(['/', '?']).next().unwrap_or_default();
+    let authority = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = match authority.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => authority,
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "0.0.0.0" | "[::1]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(chain_timestamp: u64, fees_timestamp: u64) -> ChainMetadata {
+        ChainMetadata {
+            chain_id: 1,
+            supports_eip1559: true,
+            eip1559_fees: Some((U256::from(2_000_000_000u64), U256::from(1_000_000_000u64))),
+            block_gas_limit: U256::from(30_000_000),
+            chain_timestamp,
+            fees_timestamp,
+        }
+    }
+
+    #[test]
+    fn can_reuse_recent_chain_metadata() {
+        let mut cache = ChainMetadataCache::default();
+        let url = "https://eth-mainnet.example.com/v2/secret";
+        assert!(cache.get(url, 1_000).is_none());
+
+        cache.insert(url, metadata(1_000, 1_000));
+        let cached = cache.get(url, 1_000 + FEES_MAX_AGE).unwrap();
+        assert!(cached.fees_are_fresh(1_000 + FEES_MAX_AGE));
+        assert!(!cached.fees_are_fresh(1_001 + FEES_MAX_AGE));
+        assert!(cache.get(url, 1_000 + CHAIN_MAX_AGE).is_some());
+        assert!(cache.get(url, 1_001 + CHAIN_MAX_AGE).is_none());
+
+        let json = serde_json::to_string(&cache).unwrap();
+        assert!(!json.contains("secret"));
+
+        assert!(cache.remove(url));
+        assert!(cache.get(url, 1_000).is_none());
+        assert!(!cache.remove(url));
+    }
+
+    #[test]
+    fn skips_local_rpcs() {
+        assert!(is_local_rpc("http://localhost:8545"));
+        assert!(is_local_rpc("http://127.0.0.1:8545/"));
+        assert!(is_local_rpc("ws://[::1]:8546"));
+        assert!(!is_local_rpc("https://eth-mainnet.example.com/v2/localhost"));
+    }
+}
@@ -0,0 +1,289 @@
+use crate::{
+    history::format_timestamp, sequence::ScriptSequence, upgrades::decode_upgrade,
+    verify::BroadcastedState,
+};
+use alloy_primitives::{Address, B256};
+use eyre::Result;
+use foundry_common::{fs, shell, types::ToAlloy};
+use foundry_config::{Chain, ExplorerLinks};
+use foundry_evm::traces::CallKind;
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf};
+
+/// A contract deployed by a run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangelogDeployment {
+    pub contract: String,
+    pub address: Address,
+    pub tx_hash: B256,
+}
+
+/// A proxy upgraded by a run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangelogUpgrade {
+    pub proxy: Address,
+    pub implementation: Address,
+    /// The name of the implementation, if it was deployed by the run.
+    pub contract: Option<String>,
+    pub tx_hash: B256,
+}
+
+/// What a run changed on a chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangelogChain {
+    pub chain: u64,
+    /// The links to the block explorer of the chain.
+    pub explorer: Option<ExplorerLinks>,
+    pub deployments: Vec<ChangelogDeployment>,
+    pub upgrades: Vec<ChangelogUpgrade>,
+}
+
+impl ChangelogChain {
+    /// Collects the deployments and upgrades of the mined transactions of the sequence.
+    pub fn from_sequence(sequence: &ScriptSequence, explorer: Option<ExplorerLinks>) -> Self {
+        let mined = |hash: &B256| {
+            sequence.receipts.iter().any(|receipt| {
+                receipt.transaction_hash.to_alloy() == *hash &&
+                    receipt.status.map_or(true, |status| status.as_u64() == 1)
+            })
+        };
+
+        let mut deployments = Vec::new();
+        let mut upgrades = Vec::new();
+        for tx in &sequence.transactions {
+            let Some(tx_hash) = tx.hash.filter(mined) else { continue };
+            if matches!(tx.opcode, CallKind::Create | CallKind::Create2) {
+                if let (Some(contract), Some(address)) = (&tx.contract_name, tx.contract_address) {
+                    deployments.push(ChangelogDeployment {
+                        contract: contract.clone(),
+                        address,
+                        tx_hash,
+                    });
+                }
+                continue
+            }
+            let (Some(to), Some(data)) = (tx.transaction.to_addr(), tx.transaction.data()) else {
+                continue
+            };
+            if let Some((proxy, implementation)) = decode_upgrade(to.to_alloy(), data) {
+                upgrades.push(ChangelogUpgrade { proxy, implementation, contract: None, tx_hash });
+            }
+        }
+        for upgrade in &mut upgrades {
+            upgrade.contract = deployments
+                .iter()
+                .find(|deployment| deployment.address == upgrade.implementation)
+                .map(|deployment| deployment.contract.clone());
+        }
+
+        Self { chain: sequence.chain, explorer, deployments, upgrades }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.deployments.is_empty() && self.upgrades.is_empty()
+    }
+
+    fn address_link(&self, address: Address) -> String {
+        match &self.explorer {
+            Some(explorer) => format!("[`{address}`]({})", explorer.address_url(address)),
+            None => format!("`{address}`"),
+        }
+    }
+
+    fn tx_link(&self, hash: B256) -> String {
+        let short = format!("{}…", &hash.to_string()[..10]);
+        match &self.explorer {
+            Some(explorer) => format!("[`{short}`]({})", explorer.tx_url(hash)),
+            None => format!("`{short}`"),
+        }
+    }
+}
+
+/// A Markdown summary of a run, to be appended to a changelog or a release page.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Changelog {
+    pub script: String,
+    pub timestamp: u64,
+    pub commit: Option<String>,
+    pub chains: Vec<ChangelogChain>,
+    pub arguments: Vec<String>,
+    /// The parameters read by the script.
+    pub params: BTreeMap<String, String>,
+}
+
+impl Changelog {
+    pub fn is_empty(&self) -> bool {
+        self.chains.iter().all(ChangelogChain::is_empty)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "## {} ({} UTC)\n", self.script, format_timestamp(self.timestamp));
+        if let Some(commit) = &self.commit {
+            let _ = writeln!(out, "Commit: `{commit}`\n");
+        }
+
+        for chain in self.chains.iter().filter(|chain| !chain.is_empty()) {
+            let name = Chain::from(chain.chain);
+            if name.named().is_some() {
+                let _ = writeln!(out, "### {name} ({})\n", chain.chain);
+            } else {
+                let _ = writeln!(out, "### Chain {}\n", chain.chain);
+            }
+
+            if !chain.deployments.is_empty() {
+                out.push_str("| Contract | Address | Transaction |\n| --- | --- | --- |\n");
+                for deployment in &chain.deployments {
+                    let _ = writeln!(
+                        out,
+                        "| {} | {} | {} |",
+                        deployment.contract,
+                        chain.address_link(deployment.address),
+                        chain.tx_link(deployment.tx_hash)
+                    );
+                }
+                out.push('\n');
+            }
+
+            if !chain.upgrades.is_empty() {
+                out.push_str("Upgrades:\n\n");
+                for upgrade in &chain.upgrades {
+                    let implementation = match &upgrade.contract {
+                        Some(contract) => {
+                            format!("{contract} at {}", chain.address_link(upgrade.implementation))
+                        }
+                        None => chain.address_link(upgrade.implementation),
+                    };
+                    let _ = writeln!(
+                        out,
+                        "- {} upgraded to {implementation} in {}",
+                        chain.address_link(upgrade.proxy),
+                        chain.tx_link(upgrade.tx_hash)
+                    );
+                }
+                out.push('\n');
+            }
+        }
+
+        if !self.arguments.is_empty() || !self.params.is_empty() {
+            out.push_str("### Parameters\n\n");
+            for (i, argument) in self.arguments.iter().enumerate() {
+                let _ = writeln!(out, "- argument {i}: `{argument}`");
+            }
+            for (name, value) in &self.params {
+                let _ = writeln!(out, "- `{name}`: `{value}`");
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl BroadcastedState {
+    /// Writes the changelog fragment of the run next to its broadcast artifact, and appends it to
+    /// the `--changelog` file.
+    pub fn write_changelog(&self) -> Result<Option<PathBuf>> {
+        let config = &self.script_config.config;
+        let Some(broadcast_path) = self.sequence.broadcast_path() else { return Ok(None) };
+        let sequences = self.sequence.sequences();
+
+        let params = self
+            .script_config
+            .script_params
+            .as_ref()
+            .map(|params| {
+                let params = params.inner.lock();
+                params
+                    .values
+                    .iter()
+                    .filter(|(name, _)| params.read.contains(*name))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let changelog = Changelog {
+            script: self
+                .build_data
+                .build_data
+                .target
+                .source
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            timestamp: sequences.iter().map(|sequence| sequence.timestamp).max().unwrap_or(0),
+            commit: sequences.iter().find_map(|sequence| sequence.commit.clone()),
+            chains: sequences
+                .iter()
+                .map(|sequence| {
+                    let explorer = config.get_explorer_links(sequence.chain.into());
+                    ChangelogChain::from_sequence(sequence, explorer)
+                })
+                .collect(),
+            arguments: self.args.args.clone(),
+            params,
+        };
+        if changelog.is_empty() {
+            return Ok(None)
+        }
+
+        let markdown = changelog.to_markdown();
+        let path = broadcast_path.with_extension("changelog.md");
+        fs::write(&path, &markdown)?;
+        if let Some(changelog_file) = &self.args.changelog {
+            let mut existing = std::fs::read_to_string(changelog_file).unwrap_or_default();
+            if !existing.is_empty() && !existing.ends_with("\n\n") {
+                existing.push_str(if existing.ends_with('\n') { "\n" } else { "\n\n" });
+            }
+            existing.push_str(&markdown);
+            fs::write(changelog_file, existing)?;
+            shell::println(format!("Changelog entry appended to: {}", changelog_file.display()))?;
+        }
+        Ok(Some(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    const TOKEN: Address = address!("5FbDB2315678afecb367f032d93F642f64180aa3");
+    const PROXY: Address = address!("e7f1725E7734CE288F8367e1Bb143E90bb3F0512");
+
+    #[test]
+    fn writes_markdown() {
+        let changelog = Changelog {
+            script: "Deploy.s.sol".to_string(),
+            timestamp: 1_710_599_400,
+            commit: Some("1c8d776".to_string()),
+            chains: vec![ChangelogChain {
+                chain: 1,
+                explorer: Some(ExplorerLinks::from_base_url("https://etherscan.io")),
+                deployments: vec![ChangelogDeployment {
+                    contract: "Token".to_string(),
+                    address: TOKEN,
+                    tx_hash: B256::repeat_byte(0xab),
+                }],
+                upgrades: vec![ChangelogUpgrade {
+                    proxy: PROXY,
+                    implementation: TOKEN,
+                    contract: Some("Token".to_string()),
+                    tx_hash: B256::repeat_byte(0xcd),
+                }],
+            }],
+            arguments: vec![],
+            params: BTreeMap::from([("fees.max".to_string(), "100".to_string())]),
+        };
+        assert!(!changelog.is_empty());
+
+        let markdown = changelog.to_markdown();
+        assert!(markdown.starts_with("## Deploy.s.sol (2024-03-16 14:30 UTC)\n"));
+        assert!(markdown.contains("Commit: `1c8d776`"));
+        assert!(markdown.contains("### mainnet (1)"));
+        assert!(markdown.contains(&format!(
+            "| Token | [`{TOKEN}`](https://etherscan.io/address/{TOKEN}) | [`0xabababab…`]"
+        )));
+        assert!(markdown.contains(&format!("upgraded to Token at [`{TOKEN}`]")));
+        assert!(markdown.contains("- `fees.max`: `100`"));
+    }
+}
@@ -0,0 +1,265 @@
+use crate::{anvil::AnvilFork, ScriptArgs};
+use alloy_primitives::TxHash;
+use ethers_core::types::U256;
+use ethers_providers::Middleware;
+use eyre::{Context, Result};
+use foundry_cli::utils::now;
+use foundry_common::{
+    fs,
+    provider::ethers::{get_http_provider, RetryProvider},
+    shell,
+    types::ToEthers,
+};
+use serde_json::Value;
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+use yansi::Paint;
+
+/// Returns the index of the first transaction which is rolled back, and the index of the
+/// transaction before which the broadcast is interrupted, or `None` if there are less than two
+/// transactions to interrupt.
+///
+/// Nothing is rolled back if both indexes are the same.
+fn chaos_points(seed: u64, total: usize) -> Option<(usize, usize)> {
+    if total < 2 {
+        return None
+    }
+    let interrupt_at = 1 + (seed % (total as u64 - 1)) as usize;
+    let reorg_from = ((seed >> 32) % (interrupt_at as u64 + 1)) as usize;
+    Some((reorg_from, interrupt_at))
+}
+
+#[derive(Debug, Default)]
+struct ChaosState {
+    /// The index of the transaction before which the broadcast was interrupted.
+    interrupted_at: Option<usize>,
+    /// The index of the first transaction to roll back, and the Anvil snapshot taken before it.
+    snapshot: Option<(usize, U256)>,
+}
+
+/// The settings of a run of the script under `--chaos`.
+#[derive(Debug)]
+pub(crate) struct ChaosPlan {
+    /// The seed picking where the broadcast is interrupted, or `None` when resuming it.
+    seed: Option<u64>,
+    /// The directory which the broadcast artifacts and the cache are redirected to, so the ones
+    /// of the forked chain are left untouched.
+    pub dir: PathBuf,
+    state: Mutex<ChaosState>,
+}
+
+impl ChaosPlan {
+    fn new(seed: Option<u64>, dir: PathBuf) -> Self {
+        Self { seed, dir, state: Default::default() }
+    }
+
+    /// Called before sending the transaction at `index` of a sequence of `total` transactions.
+    ///
+    /// Snapshots the fork before the first transaction which will be rolled back, and fails
+    /// before the one at which the broadcast is interrupted.
+    pub async fn before_send(
+        &self,
+        provider: &RetryProvider,
+        index: usize,
+        total: usize,
+    ) -> Result<()> {
+        let Some(seed) = self.seed else { return Ok(()) };
+        let Some((reorg_from, interrupt_at)) = chaos_points(seed, total) else { return Ok(()) };
+
+        if index == reorg_from && reorg_from < interrupt_at {
+            let id: U256 = provider.request("evm_snapshot", ()).await?;
+            self.state.lock().unwrap().snapshot = Some((reorg_from, id));
+        }
+        if index == interrupt_at {
+            self.state.lock().unwrap().interrupted_at = Some(index);
+            eyre::bail!("Chaos: interrupted the broadcast before transaction {index}.")
+        }
+        Ok(())
+    }
+}
+
+/// Returns the broadcast artifacts written under `dir`, skipping the dry runs.
+fn broadcast_artifacts(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut artifacts = Vec::new();
+    if !dir.is_dir() {
+        return Ok(artifacts)
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if path.file_name().map_or(false, |name| name != crate::sequence::DRY_RUN_DIR) {
+                artifacts.extend(broadcast_artifacts(&path)?);
+            }
+        } else if path.to_string_lossy().ends_with("-latest.json") {
+            artifacts.push(path);
+        }
+    }
+    Ok(artifacts)
+}
+
+/// Returns the transactions recorded as broadcast in the artifacts under `dir` which aren't
+/// mined on the fork, or reverted.
+async fn missing_transactions(provider: &RetryProvider, dir: &Path) -> Result<Vec<String>> {
+    let mut missing = Vec::new();
+    for path in broadcast_artifacts(dir)? {
+        let artifact: Value = fs::read_json_file(&path)?;
+        let transactions = artifact["transactions"].as_array().cloned().unwrap_or_default();
+        for (index, tx) in transactions.iter().enumerate() {
+            let name = tx["contractName"].as_str().or(tx["function"].as_str()).unwrap_or("");
+            let Some(hash) = tx["hash"].as_str().and_then(|hash| TxHash::from_str(hash).ok())
+            else {
+                missing.push(format!("transaction {index} {name} was never sent"));
+                continue
+            };
+            let receipt = provider.get_transaction_receipt(hash.to_ethers()).await?;
+            match receipt {
+                None => missing.push(format!("transaction {index} {name} ({hash}) isn't mined")),
+                Some(receipt) if receipt.status != Some(1.into()) => {
+                    missing.push(format!("transaction {index} {name} ({hash}) reverted"))
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    Ok(missing)
+}
+
+impl ScriptArgs {
+    /// Returns whether `ScriptArgs` was configured with `--chaos`.
+    pub fn is_chaos(&self) -> bool {
+        self.chaos
+    }
+
+    /// Returns the arguments of a run of the script against the Anvil fork.
+    fn chaos_run(&self, anvil: &AnvilFork, chain_id: u64, plan: ChaosPlan) -> Self {
+        let mut args = self.clone();
+        args.chaos = false;
+        args.resume = plan.seed.is_none();
+        args.chaos_plan = Some(Arc::new(plan));
+        args.evm_opts.fork_url = Some(anvil.url.clone());
+        args.broadcast = true;
+        // Sending one transaction at a time lets the broadcast stop at any of them.
+        args.slow = true;
+        args.unlocked = true;
+        args.non_interactive = true;
+        args.yes_chain.push(chain_id);
+        args.verify = false;
+        args.sign_manifest = None;
+        args
+    }
+
+    /// Broadcasts the script to a local Anvil fork of its chain, interrupts the broadcast and
+    /// rolls back part of it at random, then resumes it with `--resume` and checks that all the
+    /// transactions of the broadcast artifact ended up mined.
+    ///
+    /// Only scripts broadcasting to a single chain are supported.
+    pub async fn run_chaos(self) -> Result<()> {
+        let (_, evm_opts) = self.load_config_and_evm_opts()?;
+        let Some(fork_url) = evm_opts.fork_url.clone() else {
+            eyre::bail!("--chaos needs the RPC URL of the chain to fork, pass it with --fork-url")
+        };
+        let seed = self.chaos_seed.unwrap_or_else(|| now().as_nanos() as u64);
+        let dir = std::env::temp_dir().join(format!("forge-chaos-{seed}"));
+
+        let anvil = AnvilFork::spawn(&fork_url, evm_opts.fork_block_number).await?;
+        let provider = get_http_provider(&anvil.url);
+        let chain_id = provider.get_chainid().await?.as_u64();
+        shell::println(format!(
+            "Broadcasting to an Anvil fork of chain {chain_id} with chaos seed {seed}."
+        ))?;
+
+        let result = self.run_chaos_phases(&anvil, &provider, chain_id, seed, &dir).await;
+        let _ = std::fs::remove_dir_all(&dir);
+        result
+    }
+
+    async fn run_chaos_phases(
+        &self,
+        anvil: &AnvilFork,
+        provider: &RetryProvider,
+        chain_id: u64,
+        seed: u64,
+        dir: &Path,
+    ) -> Result<()> {
+        let interrupted = self.chaos_run(anvil, chain_id, ChaosPlan::new(Some(seed), dir.into()));
+        let plan = interrupted.chaos_plan.clone().unwrap();
+        let result = interrupted.run_script().await;
+        let state = std::mem::take(&mut *plan.state.lock().unwrap());
+        let Some(interrupted_at) = state.interrupted_at else {
+            result.wrap_err("The script failed before its broadcast could be interrupted")?;
+            shell::println("The script has less than two transactions, nothing to interrupt.")?;
+            return Ok(())
+        };
+
+        shell::println(Paint::yellow(format!(
+            "\nChaos: interrupted the broadcast before transaction {interrupted_at}."
+        )))?;
+        if let Some((reorg_from, id)) = state.snapshot {
+            let reverted: bool = provider.request("evm_revert", [id]).await?;
+            if !reverted {
+                eyre::bail!("failed to roll back the Anvil fork")
+            }
+            shell::println(Paint::yellow(format!(
+                "Chaos: rolled back the confirmed transactions {reorg_from} to {}.",
+                interrupted_at - 1
+            )))?;
+        }
+
+        shell::println("\nResuming the broadcast.")?;
+        let resumed = self.chaos_run(anvil, chain_id, ChaosPlan::new(None, dir.into()));
+        let resumed = resumed.run_script().await;
+
+        shell::println("\n==========================")?;
+        shell::println(format!("Chaos (seed {seed}):\n"))?;
+        if let Err(err) = resumed {
+            shell::println(Paint::red(format!("FAIL: resuming the broadcast failed: {err:#}")))?;
+            eyre::bail!("The script can't be resumed safely, rerun with `--chaos-seed {seed}`.")
+        }
+        let missing = missing_transactions(provider, &dir.join("broadcast")).await?;
+        if !missing.is_empty() {
+            for transaction in &missing {
+                shell::println(Paint::red(format!("FAIL: {transaction}")))?;
+            }
+            eyre::bail!("The script can't be resumed safely, rerun with `--chaos-seed {seed}`.")
+        }
+        shell::println(Paint::green(
+            "The broadcast was resumed and all its transactions are mined.",
+        ))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_chaos_points_within_the_sequence() {
+        assert_eq!(chaos_points(7, 0), None);
+        assert_eq!(chaos_points(7, 1), None);
+        for seed in [0, 1, 42, u64::MAX, 0xdead_beef_0000_0003] {
+            for total in 2..20 {
+                let (reorg_from, interrupt_at) = chaos_points(seed, total).unwrap();
+                assert!(interrupt_at >= 1 && interrupt_at < total);
+                assert!(reorg_from <= interrupt_at);
+            }
+        }
+        assert_eq!(chaos_points(0x0000_0002_0000_0004, 10), Some((2, 5)));
+    }
+
+    #[test]
+    fn finds_broadcast_artifacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let run = dir.path().join("Deploy.s.sol/1");
+        fs::create_dir_all(run.join(crate::sequence::DRY_RUN_DIR)).unwrap();
+        fs::write(run.join("run-latest.json"), "{}").unwrap();
+        fs::write(run.join("run-1.json"), "{}").unwrap();
+        fs::write(run.join(crate::sequence::DRY_RUN_DIR).join("run-latest.json"), "{}").unwrap();
+
+        assert_eq!(broadcast_artifacts(dir.path()).unwrap(), vec![run.join("run-latest.json")]);
+        assert!(broadcast_artifacts(&dir.path().join("missing")).unwrap().is_empty());
+    }
+}
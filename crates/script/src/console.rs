@@ -0,0 +1,82 @@
+use foundry_common::shell;
+use foundry_evm::inspectors::ConsoleListener;
+use serde::Serialize;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use yansi::Paint;
+
+/// The level of a `console.log` message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    /// Infers the level of a message from its prefix, e.g. `warn:` or `[ERROR]`.
+    ///
+    /// `console.sol` only has `log` functions, so `console.warn` and `console.error` style helpers
+    /// are expected to prefix their messages with the level.
+    pub fn of(message: &str) -> Self {
+        let message = message.trim_start();
+        let (bracketed, message) = match message.strip_prefix('[') {
+            Some(message) => (true, message),
+            None => (false, message),
+        };
+        let end = message.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(message.len());
+        let (word, rest) = message.split_at(end);
+        let terminated = if bracketed { rest.starts_with(']') } else { rest.starts_with(':') };
+        if !terminated {
+            return Self::Info
+        }
+        match word.to_ascii_lowercase().as_str() {
+            "warn" | "warning" => Self::Warn,
+            "error" | "err" => Self::Error,
+            _ => Self::Info,
+        }
+    }
+}
+
+/// Returns a listener which prints the `console.log` messages of the script as soon as they are
+/// logged, rather than once it's done executing.
+///
+/// With `json`, each message is printed as a JSON line with its level, so that CI can collate
+/// them with the rest of its logs.
+pub fn console_listener(json: bool) -> ConsoleListener {
+    let printed_header = AtomicBool::new(false);
+    ConsoleListener::new(move |message| {
+        let level = LogLevel::of(message);
+        let _ = if json {
+            shell::println(json!({ "type": "console", "level": level, "message": message }))
+        } else {
+            if !printed_header.swap(true, Ordering::Relaxed) {
+                let _ = shell::println("\n== Logs ==");
+            }
+            match level {
+                LogLevel::Info => shell::println(format!("  {message}")),
+                LogLevel::Warn => shell::println(format!("  {}", Paint::yellow(message))),
+                LogLevel::Error => shell::println(format!("  {}", Paint::red(message))),
+            }
+        };
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_infer_log_level() {
+        assert_eq!(LogLevel::of("deployed at 0x01"), LogLevel::Info);
+        assert_eq!(LogLevel::of("warn: no owner set"), LogLevel::Warn);
+        assert_eq!(LogLevel::of("Warning: no owner set"), LogLevel::Warn);
+        assert_eq!(LogLevel::of("[WARN] no owner set"), LogLevel::Warn);
+        assert_eq!(LogLevel::of("  error: deployment failed"), LogLevel::Error);
+        assert_eq!(LogLevel::of("[error] deployment failed"), LogLevel::Error);
+        assert_eq!(LogLevel::of("errors: 0"), LogLevel::Info);
+        assert_eq!(LogLevel::of("[warn: unbalanced"), LogLevel::Info);
+    }
+}
@@ -0,0 +1,259 @@
+use crate::simulate::PreSimulationState;
+use eyre::Result;
+use foundry_cli::utils::now;
+use foundry_common::{bytecode_diff_score, compile::ContractSources, fs, shell};
+use foundry_compilers::{
+    artifacts::{CompactBytecode, ContractBytecodeSome},
+    ArtifactContracts, ArtifactId,
+};
+use foundry_evm::{
+    coverage::{HitMap, HitMaps},
+    revm::primitives::SpecId,
+    utils::PcIcMap,
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+/// The lines executed while running the script, by source file.
+///
+/// Only the file of the script and the files of the contracts it deployed are reported. All the
+/// executable lines of the script and of the constructors are reported, while only the executed
+/// lines of the runtime code of the deployed contracts are, e.g. those of their initializers.
+#[derive(Debug, Default)]
+pub struct ScriptCoverage {
+    files: BTreeMap<PathBuf, FileCoverage>,
+}
+
+#[derive(Debug, Default)]
+struct FileCoverage {
+    source: String,
+    /// Line number -> number of times the line was executed
+    lines: BTreeMap<usize, u64>,
+}
+
+impl FileCoverage {
+    fn hit_lines(&self) -> usize {
+        self.lines.values().filter(|hits| **hits > 0).count()
+    }
+}
+
+impl ScriptCoverage {
+    /// Maps the instructions hit while running `script` to the lines of the sources.
+    pub fn new(
+        hit_maps: &HitMaps,
+        contracts: &ArtifactContracts<ContractBytecodeSome>,
+        sources: &ContractSources,
+        script: &ArtifactId,
+        spec: SpecId,
+    ) -> Self {
+        // the path of a source file is only known through the artifacts of the contracts it
+        // defines, as long as their name is unique
+        let mut file_paths = HashMap::new();
+        for (id, _) in contracts.iter() {
+            if let Some([file_id]) = sources.ids_by_name.get(&id.name).map(Vec::as_slice) {
+                file_paths.insert(*file_id, id.source.as_path());
+            }
+        }
+
+        let executed = hit_maps
+            .values()
+            .filter_map(|hit_map| {
+                let (id, bytecode, is_create) = find_bytecode(contracts, hit_map)?;
+                Some((id, bytecode, is_create, hit_map))
+            })
+            .collect::<Vec<_>>();
+        let reported = executed
+            .iter()
+            .filter(|(_, _, is_create, _)| *is_create)
+            .map(|(id, ..)| id.source.as_path())
+            .chain([script.source.as_path()])
+            .collect::<HashSet<_>>();
+
+        let mut coverage = Self::default();
+        let mut line_starts = HashMap::new();
+        for (id, bytecode, is_create, hit_map) in executed {
+            let (Some(code), Some(Ok(source_map))) = (bytecode.bytes(), bytecode.source_map())
+            else {
+                continue
+            };
+            // the lines which were not executed are only of interest in the script itself and in
+            // the constructors of the contracts
+            let all_lines = id == script || is_create;
+
+            for (pc, ic) in PcIcMap::new(spec, code).inner {
+                let Some(element) = source_map.get(ic) else { continue };
+                let Some(file_id) = element.index else { continue };
+                let Some(path) = file_paths.get(&file_id).filter(|path| reported.contains(*path))
+                else {
+                    continue
+                };
+                let hits = hit_map.hits.get(&pc).copied().unwrap_or_default();
+                if hits == 0 && !(all_lines && *path == id.source) {
+                    continue
+                }
+                let Some((source, _)) = sources.sources_by_id.get(&file_id) else { continue };
+
+                let starts = line_starts.entry(file_id).or_insert_with(|| {
+                    let newlines = source.match_indices('\n').map(|(i, _)| i + 1);
+                    [0].into_iter().chain(newlines).collect::<Vec<_>>()
+                });
+                let line = starts.partition_point(|start| *start <= element.offset);
+                let file = coverage.files.entry(path.to_path_buf()).or_insert_with(|| {
+                    FileCoverage { source: source.clone(), lines: BTreeMap::new() }
+                });
+                let count = file.lines.entry(line).or_default();
+                *count = (*count).max(hits);
+            }
+        }
+        coverage
+    }
+
+    /// Renders the share of executed lines of each file, and the lines which were never executed.
+    pub fn report(&self, root: &Path) -> String {
+        let mut out = String::from("Script coverage:\n");
+        for (path, file) in &self.files {
+            let (hit, total) = (file.hit_lines(), file.lines.len());
+            let _ = writeln!(
+                out,
+                "  {}: {hit}/{total} lines ({:.2}%)",
+                relative(path, root).display(),
+                hit as f64 * 100.0 / total.max(1) as f64
+            );
+        }
+
+        for (path, file) in &self.files {
+            let mut missed =
+                file.lines.iter().filter(|(_, hits)| **hits == 0).map(|(line, _)| *line).peekable();
+            if missed.peek().is_none() {
+                continue
+            }
+            let _ = writeln!(
+                out,
+                "\nLines of {} which were not executed:",
+                relative(path, root).display()
+            );
+            let lines = file.source.lines().collect::<Vec<_>>();
+            for line in missed {
+                let text = lines.get(line - 1).copied().unwrap_or_default();
+                let _ = writeln!(out, "  {line:>5} | {}", text.trim_end());
+            }
+        }
+        out
+    }
+
+    /// Renders the coverage in the LCOV format, with paths relative to `root`.
+    pub fn lcov(&self, root: &Path) -> String {
+        let mut out = String::new();
+        for (path, file) in &self.files {
+            let _ = writeln!(out, "TN:\nSF:{}", relative(path, root).display());
+            for (line, hits) in &file.lines {
+                let _ = writeln!(out, "DA:{line},{hits}");
+            }
+            let _ =
+                writeln!(out, "LF:{}\nLH:{}\nend_of_record", file.lines.len(), file.hit_lines());
+        }
+        out
+    }
+
+    /// Writes the LCOV report to the `coverage` directory of the project cache, and returns the
+    /// path of the file.
+    pub fn save(&self, root: &Path, cache_path: &Path, contract: &str) -> Result<PathBuf> {
+        let dir = cache_path.join("coverage");
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{contract}-{}.lcov", now().as_secs()));
+        fs::write(&path, self.lcov(root))?;
+        Ok(path)
+    }
+}
+
+/// Finds the artifact of the code of `hit_map`, and returns its bytecode and whether it is the
+/// creation code.
+///
+/// Creation code is followed by the constructor arguments, and runtime code has its immutables
+/// filled in, so neither can be compared exactly.
+fn find_bytecode<'a>(
+    contracts: &'a ArtifactContracts<ContractBytecodeSome>,
+    hit_map: &HitMap,
+) -> Option<(&'a ArtifactId, &'a CompactBytecode, bool)> {
+    let code = &hit_map.bytecode[..];
+    contracts
+        .iter()
+        .find_map(|(id, contract)| {
+            let creation = contract.bytecode.bytes()?;
+            (!creation.is_empty() && code.starts_with(creation)).then_some((
+                id,
+                &contract.bytecode,
+                true,
+            ))
+        })
+        .or_else(|| {
+            contracts.iter().find_map(|(id, contract)| {
+                let bytecode = contract.deployed_bytecode.bytecode.as_ref()?;
+                let runtime = bytecode.bytes()?;
+                (runtime.len() == code.len() && bytecode_diff_score(runtime, code) < 0.1)
+                    .then_some((id, bytecode, false))
+            })
+        })
+}
+
+fn relative<'a>(path: &'a Path, root: &Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
+
+impl PreSimulationState {
+    /// Prints the lines executed by the script, and writes them as an LCOV report.
+    pub fn show_coverage(&self) -> Result<()> {
+        let Some(hit_maps) = &self.execution_result.coverage else { return Ok(()) };
+        let config = &self.script_config.config;
+        let build_data = &self.build_data.build_data;
+        let coverage = ScriptCoverage::new(
+            hit_maps,
+            &self.build_data.highlevel_known_contracts,
+            &build_data.sources,
+            &build_data.target,
+            config.evm_spec_id(),
+        );
+
+        let root = &config.__root.0;
+        shell::println(format!("\n{}", coverage.report(root)))?;
+        let path = coverage.save(root, &config.cache_path, &build_data.target.name)?;
+        shell::println(format!("LCOV report saved to: {}", path.display()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coverage() -> ScriptCoverage {
+        let source = "contract Deploy {\n    function run() {\n        if (prod) {\n            \
+                      deploy();\n        }\n    }\n}\n";
+        let file = FileCoverage {
+            source: source.to_string(),
+            lines: BTreeMap::from([(2, 1), (3, 1), (4, 0)]),
+        };
+        ScriptCoverage {
+            files: BTreeMap::from([(PathBuf::from("/root/script/Deploy.s.sol"), file)]),
+        }
+    }
+
+    #[test]
+    fn reports_lines_not_executed() {
+        let report = coverage().report(Path::new("/root"));
+        assert!(report.contains("  script/Deploy.s.sol: 2/3 lines (66.67%)"));
+        assert!(report.contains("Lines of script/Deploy.s.sol which were not executed:\n"));
+        assert!(report.contains(&format!("\n      4 | {}deploy();\n", " ".repeat(12))));
+    }
+
+    #[test]
+    fn can_write_lcov() {
+        assert_eq!(
+            coverage().lcov(Path::new("/root")),
+            "TN:\nSF:script/Deploy.s.sol\nDA:2,1\nDA:3,1\nDA:4,0\nLF:3\nLH:2\nend_of_record\n"
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{
+    broadcast::BundledState,
+    sequence::{ScriptSequence, ScriptSequenceKind},
+    verify::BroadcastedState,
+};
+use alloy_primitives::{Address, B256};
+use eyre::Result;
+use foundry_common::{fs, shell};
+use foundry_config::Chain;
+use foundry_evm::traces::CallKind;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+use yansi::Paint;
+
+/// The file the report of a multi-chain deployment is written to, next to its broadcast
+/// artifact.
+const REPORT_FILE: &str = "deployments.json";
+
+/// A contract deployed by a top-level transaction on one chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainDeployment {
+    pub address: Address,
+    pub create2: bool,
+    pub transaction_hash: Option<B256>,
+}
+
+/// The contracts deployed by a multi-chain script, by contract name and chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeploymentReport {
+    pub contracts: BTreeMap<String, BTreeMap<u64, Vec<ChainDeployment>>>,
+}
+
+impl DeploymentReport {
+    pub fn new(sequences: &[ScriptSequence]) -> Self {
+        let mut report = Self::default();
+        for sequence in sequences {
+            for tx in sequence.transactions.iter().filter(|tx| tx.opcode.is_any_create()) {
+                let (Some(name), Some(address)) = (&tx.contract_name, tx.contract_address) else {
+                    continue
+                };
+                if name.is_empty() {
+                    continue
+                }
+                report
+                    .contracts
+                    .entry(name.clone())
+                    .or_default()
+                    .entry(sequence.chain)
+                    .or_default()
+                    .push(ChainDeployment {
+                        address,
+                        create2: tx.opcode == CallKind::Create2,
+                        transaction_hash: tx.hash,
+                    });
+            }
+        }
+        report
+    }
+
+    /// Returns the contracts deployed with CREATE2 on several chains which don't land at the same
+    /// addresses everywhere, with their addresses on each chain.
+    pub fn create2_mismatches(&self) -> BTreeMap<&str, BTreeMap<u64, Vec<Address>>> {
+        self.contracts
+            .iter()
+            .filter_map(|(name, chains)| {
+                let addresses = chains
+                    .iter()
+                    .map(|(chain, deployments)| {
+                        let addresses = deployments
+                            .iter()
+                            .filter(|deployment| deployment.create2)
+                            .map(|deployment| deployment.address)
+                            .collect::<Vec<_>>();
+                        (*chain, addresses)
+                    })
+                    .filter(|(_, addresses)| !addresses.is_empty())
+                    .collect::<BTreeMap<_, _>>();
+                let mut distinct = addresses.values().cloned().collect::<Vec<_>>();
+                distinct.dedup();
+                (addresses.len() > 1 && distinct.len() > 1).then_some((name.as_str(), addresses))
+            })
+            .collect()
+    }
+}
+
+/// Formats the CREATE2 address mismatches, a line per chain.
+fn format_mismatches(mismatches: &BTreeMap<&str, BTreeMap<u64, Vec<Address>>>) -> String {
+    let mut lines = Vec::new();
+    for (name, chains) in mismatches {
+        lines.push(format!("  {name}"));
+        for (chain, addresses) in chains {
+            let addresses = addresses.iter().map(|address| address.to_string()).collect::<Vec<_>>();
+            lines.push(format!("    {}: {}", Chain::from(*chain), addresses.join(", ")));
+        }
+    }
+    lines.join("\n")
+}
+
+impl BundledState {
+    /// Fails if a contract deployed with CREATE2 by a multi-chain script doesn't land at the same
+    /// address on every chain, unless `--allow-address-mismatch` is passed.
+    pub fn check_cross_chain_addresses(&self) -> Result<()> {
+        let ScriptSequenceKind::Multi(sequence) = &self.sequence else { return Ok(()) };
+        let report = DeploymentReport::new(&sequence.deployments);
+        let mismatches = report.create2_mismatches();
+        if mismatches.is_empty() {
+            return Ok(())
+        }
+
+        let mismatches = format_mismatches(&mismatches);
+        if !self.args.allow_address_mismatch {
+            eyre::bail!(
+                "Contracts deployed with CREATE2 land at different addresses across chains:\n\
+                 {mismatches}\nCheck the salts and the constructor arguments, or pass \
+                 --allow-address-mismatch if this is intended."
+            )
+        }
+        shell::println(Paint::yellow(format!(
+            "\nContracts deployed with CREATE2 land at different addresses across chains:\n\
+             {mismatches}"
+        )))?;
+        Ok(())
+    }
+}
+
+impl BroadcastedState {
+    /// Prints the contracts deployed on each chain by a multi-chain script, and writes them to
+    /// `deployments.json` next to the broadcast artifact.
+    pub fn write_deployment_report(&self) -> Result<Option<PathBuf>> {
+        let ScriptSequenceKind::Multi(sequence) = &self.sequence else { return Ok(None) };
+        let report = DeploymentReport::new(&sequence.deployments);
+        if report.contracts.is_empty() {
+            return Ok(None)
+        }
+
+        shell::println("\n==========================")?;
+        shell::println("Multi-chain deployments:\n")?;
+        let mismatches = report.create2_mismatches();
+        for (name, chains) in &report.contracts {
+            shell::println(name)?;
+            for (chain, deployments) in chains {
+                for deployment in deployments {
+                    let line = format!("  {:<16} {}", Chain::from(*chain), deployment.address);
+                    if mismatches.contains_key(name.as_str()) && deployment.create2 {
+                        shell::println(Paint::yellow(line))?;
+                    } else {
+                        shell::println(line)?;
+                    }
+                }
+            }
+        }
+
+        let path = sequence.path.with_file_name(REPORT_FILE);
+        fs::write_json_file(&path, &report)?;
+        shell::println(format!("\nDeployment report written to: {}", path.display()))?;
+        Ok(Some(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TransactionWithMetadata;
+    use alloy_primitives::address;
+
+    fn sequence(chain: u64, deployments: &[(&str, CallKind, Address)]) -> ScriptSequence {
+        ScriptSequence {
+            chain,
+            transactions: deployments
+                .iter()
+                .map(|(name, opcode, address)| TransactionWithMetadata {
+                    opcode: *opcode,
+                    contract_name: Some(name.to_string()),
+                    contract_address: Some(*address),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn finds_create2_mismatches() {
+        let a = address!("00000000000000000000000000000000000000a1");
+        let b = address!("00000000000000000000000000000000000000b2");
+        let report = DeploymentReport::new(&[
+            sequence(1, &[("Token", CallKind::Create2, a), ("Vault", CallKind::Create2, a)]),
+            sequence(196, &[("Token", CallKind::Create2, a), ("Vault", CallKind::Create2, b)]),
+            sequence(10, &[("Token", CallKind::Create, b), ("Router", CallKind::Create2, b)]),
+        ]);
+
+        assert_eq!(report.contracts.len(), 3);
+        assert_eq!(report.contracts["Token"].len(), 3);
+        let mismatches = report.create2_mismatches();
+        assert_eq!(
+            mismatches,
+            BTreeMap::from([("Vault", BTreeMap::from([(1, vec![a]), (196, vec![b])]))])
+        );
+        assert!(format_mismatches(&mismatches).contains("mainnet: 0x"));
+    }
+}
@@ -0,0 +1,320 @@
+use crate::policy::TxPolicy;
+use alloy_primitives::{keccak256, Address, U256};
+use eyre::{Result, WrapErr};
+use foundry_common::shell;
+use foundry_evm::traces::{CallKind, Traces};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+};
+use yansi::Paint;
+
+/// File the overrides of the deny lists are appended to, in the broadcast directory.
+pub const OVERRIDES_FILE_NAME: &str = "deny-list-overrides.jsonl";
+
+/// Directory of the cache the deny lists fetched over HTTP are kept in, for offline runs.
+const CACHE_DIR: &str = "deny-lists";
+
+/// Where a deny list of the policy is read from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DenyListSource {
+    File(PathBuf),
+    Url(String),
+}
+
+impl DenyListSource {
+    /// Parses a source of a policy file, with paths relative to the directory of the file.
+    pub fn new(source: &str, dir: &Path) -> Self {
+        if source.starts_with("http://") || source.starts_with("https://") {
+            Self::Url(source.to_string())
+        } else {
+            Self::File(dir.join(source))
+        }
+    }
+
+    /// Reads the deny list. Lists fetched over HTTP are cached in `cache_dir`, and only the
+    /// cached copy is read when `offline`.
+    async fn fetch(&self, cache_dir: &Path, offline: bool) -> Result<String> {
+        let url = match self {
+            Self::File(path) => return Ok(foundry_common::fs::read_to_string(path)?),
+            Self::Url(url) => url,
+        };
+        if offline {
+            return Self::read_cached(cache_dir, url)
+        }
+        let cached = Self::cache_path(cache_dir, url);
+        let content = reqwest::get(url).await?.error_for_status()?.text().await?;
+        if let Err(err) =
+            std::fs::create_dir_all(cache_dir).and_then(|_| std::fs::write(&cached, &content))
+        {
+            warn!(target: "script", %url, %err, "failed to cache deny list");
+        }
+        Ok(content)
+    }
+
+    /// Reads the cached copy of the deny list of `url`.
+    fn read_cached(cache_dir: &Path, url: &str) -> Result<String> {
+        foundry_common::fs::read_to_string(Self::cache_path(cache_dir, url)).map_err(|_| {
+            eyre::eyre!(
+                "it can't be fetched in offline mode and it wasn't cached by a previous run, run \
+                 once without --offline to cache it"
+            )
+        })
+    }
+
+    /// Returns the path of the cached copy of the deny list of `url`.
+    fn cache_path(cache_dir: &Path, url: &str) -> PathBuf {
+        cache_dir.join(format!("{}.txt", hex::encode(keccak256(url.as_bytes()))))
+    }
+}
+
+impl std::fmt::Display for DenyListSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Url(url) => f.write_str(url),
+        }
+    }
+}
+
+/// Parses the addresses of a deny list, either a JSON array or an address per line.
+///
+/// Lines may have `#` comments, and anything after the address, e.g. `0x… OFAC SDN`.
+fn parse_addresses(content: &str) -> Result<Vec<Address>> {
+    if content.trim_start().starts_with('[') {
+        return Ok(serde_json::from_str(content)?)
+    }
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let address = line.split(|c: char| c.is_whitespace() || c == ',').next();
+            let address = address.unwrap_or_default();
+            address.parse().map_err(|_| eyre::eyre!("invalid address `{address}`"))
+        })
+        .collect()
+}
+
+/// An interaction of a simulated transaction with a denied address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeniedInteraction {
+    pub transaction: usize,
+    pub chain: u64,
+    pub address: Address,
+    pub value: U256,
+    /// The deny list the address is on.
+    pub list: String,
+}
+
+impl std::fmt::Display for DeniedInteraction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = if self.value.is_zero() {
+            "calls".to_string()
+        } else {
+            format!("sends {} wei to", self.value)
+        };
+        write!(
+            f,
+            "transaction {} on chain {} {action} {}, which is on the deny list {}",
+            self.transaction, self.chain, self.address, self.list
+        )
+    }
+}
+
+/// An override of the deny lists, as recorded for audit.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DenyListOverride {
+    pub timestamp: u64,
+    pub script: String,
+    pub chain: u64,
+    pub transaction: usize,
+    pub address: Address,
+    pub list: String,
+    pub reason: String,
+}
+
+impl TxPolicy {
+    /// Reads the deny lists of the policy, which can be fetched over HTTP.
+    ///
+    /// The lists fetched over HTTP are cached in the `deny-lists` directory of `cache_path`, and
+    /// only read from there when `offline`.
+    pub async fn load_deny_lists(&mut self, cache_path: &Path, offline: bool) -> Result<()> {
+        let cache_dir = cache_path.join(CACHE_DIR);
+        for source in &self.deny_list_sources {
+            let content = source
+                .fetch(&cache_dir, offline)
+                .await
+                .wrap_err_with(|| format!("Failed to read deny list {source}"))?;
+            let addresses = parse_addresses(&content)
+                .wrap_err_with(|| format!("Failed to parse deny list {source}"))?;
+            for address in addresses {
+                self.denied_addresses.entry(address).or_insert_with(|| source.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the interactions of the traces of the transaction at index `i` with denied
+    /// addresses: calls into them, and transfers of value to them. Static calls only read their
+    /// state, so they're allowed.
+    pub fn denied_interactions(
+        &self,
+        i: usize,
+        chain: u64,
+        traces: &Traces,
+    ) -> Vec<DeniedInteraction> {
+        if self.denied_addresses.is_empty() {
+            return Vec::new()
+        }
+        traces
+            .iter()
+            .flat_map(|(_, arena)| arena.nodes())
+            .filter(|node| node.trace.kind != CallKind::StaticCall)
+            .filter_map(|node| {
+                let list = self.denied_addresses.get(&node.trace.address)?;
+                Some(DeniedInteraction {
+                    transaction: i,
+                    chain,
+                    address: node.trace.address,
+                    value: node.trace.value,
+                    list: list.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Fails on the interactions with denied addresses which aren't allowed with `--allow-denied`,
+/// and appends the allowed ones to the overrides file along with the reason.
+pub fn check_denied_interactions(
+    interactions: &[DeniedInteraction],
+    allowed: &[Address],
+    reason: Option<&str>,
+    script: &str,
+    overrides_path: &Path,
+    timestamp: u64,
+) -> Result<()> {
+    if interactions.is_empty() {
+        return Ok(())
+    }
+    let (overridden, denied): (Vec<_>, Vec<_>) =
+        interactions.iter().partition(|interaction| allowed.contains(&interaction.address));
+    if !denied.is_empty() {
+        eyre::bail!(
+            "Transactions interact with addresses of the deny lists of the policy:\n  - {}\n\
+             Pass --allow-denied <ADDRESS> with --deny-override-reason to override, the override \
+             is recorded in {}.",
+            denied
+                .iter()
+                .map(|interaction| interaction.to_string())
+                .collect::<Vec<_>>()
+                .join("\n  - "),
+            overrides_path.display()
+        )
+    }
+
+    let reason = reason.ok_or_else(|| eyre::eyre!("--deny-override-reason is required"))?;
+    if let Some(dir) = overrides_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(overrides_path)?;
+    let mut overrides = BTreeMap::new();
+    for interaction in overridden {
+        let entry = DenyListOverride {
+            timestamp,
+            script: script.to_string(),
+            chain: interaction.chain,
+            transaction: interaction.transaction,
+            address: interaction.address,
+            list: interaction.list.clone(),
+            reason: reason.to_string(),
+        };
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        overrides.entry(interaction.address).or_insert(interaction);
+    }
+    for interaction in overrides.values() {
+        shell::println(Paint::yellow(format!("Warning: overridden, {interaction}")))?;
+    }
+    shell::println(format!("Deny list overrides recorded in {}", overrides_path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    const DENIED: Address = address!("8589427373D6D84E98730D7795D8f6f8731FDA16");
+
+    #[test]
+    fn parses_deny_lists() {
+        let list = "# OFAC\n0x8589427373D6D84E98730D7795D8f6f8731FDA16 Tornado Cash\n\n\
+                    0x722122dF12D4e14e13Ac3b6895a86e84145b6967, Tornado Cash # router\n";
+        assert_eq!(
+            parse_addresses(list).unwrap(),
+            vec![DENIED, address!("722122dF12D4e14e13Ac3b6895a86e84145b6967")]
+        );
+        assert_eq!(
+            parse_addresses(r#"["0x8589427373D6D84E98730D7795D8f6f8731FDA16"]"#).unwrap(),
+            vec![DENIED]
+        );
+        assert!(parse_addresses("not an address").is_err());
+    }
+
+    #[test]
+    fn resolves_sources() {
+        let dir = Path::new("/project");
+        assert_eq!(
+            DenyListSource::new("https://example.com/ofac.json", dir),
+            DenyListSource::Url("https://example.com/ofac.json".to_string())
+        );
+        assert_eq!(
+            DenyListSource::new("lists/ofac.txt", dir),
+            DenyListSource::File(dir.join("lists/ofac.txt"))
+        );
+    }
+
+    #[test]
+    fn reads_cached_lists_offline() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = "https://example.com/ofac.txt";
+
+        let err = DenyListSource::read_cached(dir.path(), url).unwrap_err().to_string();
+        assert!(err.contains("offline mode"), "{err}");
+
+        std::fs::write(DenyListSource::cache_path(dir.path(), url), "0x00").unwrap();
+        assert_eq!(DenyListSource::read_cached(dir.path(), url).unwrap(), "0x00");
+    }
+
+    #[test]
+    fn records_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broadcast").join(OVERRIDES_FILE_NAME);
+        let interactions = vec![DeniedInteraction {
+            transaction: 2,
+            chain: 1,
+            address: DENIED,
+            value: U256::from(1),
+            list: "ofac.txt".to_string(),
+        }];
+
+        let err = check_denied_interactions(&interactions, &[], None, "Deploy", &path, 0)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("transaction 2 on chain 1 sends 1 wei to"), "{err}");
+        assert!(!path.exists());
+
+        check_denied_interactions(&interactions, &[DENIED], Some("refund"), "Deploy", &path, 7)
+            .unwrap();
+        let log = std::fs::read_to_string(&path).unwrap();
+        let entry: DenyListOverride = serde_json::from_str(log.trim()).unwrap();
+        assert_eq!(entry.address, DENIED);
+        assert_eq!(entry.reason, "refund");
+        assert_eq!(entry.timestamp, 7);
+    }
+}
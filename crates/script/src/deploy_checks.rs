@@ -0,0 +1,219 @@
+use crate::sequence::ScriptSequence;
+use alloy_dyn_abi::{DynSolType, DynSolValue, FunctionExt, JsonAbiExt};
+use alloy_json_abi::Function;
+use alloy_primitives::Address;
+use ethers_core::types::{transaction::eip2718::TypedTransaction, TransactionRequest};
+use ethers_providers::Middleware;
+use eyre::{Result, WrapErr};
+use foundry_common::{
+    abi::get_func,
+    provider::ethers::RetryProvider,
+    shell,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_evm::inspectors::cheatcodes::ScriptParams;
+use std::fmt::Write;
+use yansi::Paint;
+
+/// The prefix of the parameters which are read-after-write checks, i.e. the `[checks]` table.
+const CHECKS_PREFIX: &str = "checks.";
+
+/// A view function called on a contract once its deployment is confirmed, and the parameter with
+/// the value it must return.
+///
+/// ```toml
+/// [checks.Token]
+/// "owner()(address)" = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+/// "decimals()(uint8)" = 18
+/// ```
+#[derive(Clone, Debug)]
+pub struct DeployCheck {
+    pub contract: String,
+    pub function: Function,
+    /// The name of the parameter with the expected value, which may reference deployments.
+    pub param: String,
+}
+
+/// The read-after-write checks of the deployments of the script.
+#[derive(Clone, Debug, Default)]
+pub struct DeployChecks {
+    checks: Vec<DeployCheck>,
+}
+
+impl DeployChecks {
+    /// Reads the checks from the `[checks]` table of the parameters.
+    pub fn load(params: Option<&ScriptParams>) -> Result<Self> {
+        let Some(params) = params else { return Ok(Self::default()) };
+        let names = params
+            .inner
+            .lock()
+            .values
+            .keys()
+            .filter(|name| name.starts_with(CHECKS_PREFIX))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut checks = Vec::new();
+        for param in names {
+            let Some((contract, signature)) = param[CHECKS_PREFIX.len()..].split_once('.') else {
+                eyre::bail!("check {param:?} must be in a `[checks.<CONTRACT_NAME>]` table")
+            };
+            let function =
+                get_func(signature).wrap_err_with(|| format!("invalid check {param:?}"))?;
+            if !function.inputs.is_empty() || function.outputs.len() != 1 {
+                eyre::bail!(
+                    "check {param:?} must call a function without arguments that returns one \
+                     value, e.g. `owner()(address)`"
+                )
+            }
+            // The checks are only read by the broadcast, so don't report them as unused.
+            params.get(&param);
+            checks.push(DeployCheck { contract: contract.to_string(), function, param });
+        }
+        Ok(Self { checks })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty()
+    }
+
+    /// Calls the view functions of the contracts deployed by the confirmed transactions of the
+    /// sequence from `start`, and fails if any returns something else than expected.
+    pub async fn run(
+        &self,
+        provider: &RetryProvider,
+        sequence: &ScriptSequence,
+        start: usize,
+        params: Option<&ScriptParams>,
+    ) -> Result<()> {
+        let deployments = sequence.receipts[start..]
+            .iter()
+            .filter_map(|receipt| receipt.contract_address.map(|address| address.to_alloy()))
+            .filter_map(|address| {
+                let tx =
+                    sequence.transactions.iter().find(|tx| tx.contract_address == Some(address))?;
+                Some((tx.contract_name.clone()?, address))
+            });
+
+        let mut failures = Vec::new();
+        for (contract, address) in deployments {
+            for check in self.checks.iter().filter(|check| check.contract == contract) {
+                let expected = params
+                    .and_then(|params| params.get(&check.param))
+                    .ok_or_else(|| eyre::eyre!("check {:?} has no value", check.param))?;
+                let calldata = check.function.abi_encode_input(&[])?;
+                let tx: TypedTransaction =
+                    TransactionRequest::new().to(address.to_ethers()).data(calldata).into();
+                let output = provider.call(&tx, None).await;
+                if let Some(failure) = check.verify(address, output.ok().as_deref(), &expected)? {
+                    failures.push(failure);
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            return Ok(())
+        }
+        let mut message = String::new();
+        for failure in &failures {
+            shell::println(Paint::red(format!("FAIL: {failure}")))?;
+            writeln!(message, "\n  {failure}")?;
+        }
+        eyre::bail!(
+            "{} read-after-write checks failed on chain {}, the rest of the transactions weren't \
+             sent:{message}",
+            failures.len(),
+            sequence.chain
+        )
+    }
+}
+
+impl DeployCheck {
+    /// Compares what the function returned when called on the contract at `address` to
+    /// `expected`, and returns the failure if they differ.
+    fn verify(
+        &self,
+        address: Address,
+        output: Option<&[u8]>,
+        expected: &str,
+    ) -> Result<Option<String>> {
+        let signature = self.function.signature();
+        let ty = DynSolType::parse(&self.function.outputs[0].ty)?;
+        let expected = ty
+            .coerce_str(expected)
+            .wrap_err_with(|| format!("invalid expected value of check {:?}", self.param))?;
+        let actual = output.and_then(|output| {
+            self.function.abi_decode_output(output, false).ok()?.into_iter().next()
+        });
+        let failure = match actual {
+            Some(actual) if actual == expected => return Ok(None),
+            Some(actual) => format!(
+                "{}.{signature} at {address} returned {}, expected {}",
+                self.contract,
+                format_value(&actual),
+                format_value(&expected)
+            ),
+            None => format!("{}.{signature} at {address} reverted", self.contract),
+        };
+        Ok(Some(failure))
+    }
+}
+
+fn format_value(value: &DynSolValue) -> String {
+    match value {
+        DynSolValue::Address(address) => address.to_checksum(None),
+        DynSolValue::String(s) => format!("{s:?}"),
+        value => foundry_common::fmt::format_token_raw(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, U256};
+
+    fn script_params(values: &[(&str, &str)]) -> ScriptParams {
+        ScriptParams::new(
+            values.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect(),
+        )
+    }
+
+    #[test]
+    fn loads_checks() {
+        let params = script_params(&[
+            ("checks.Token.decimals()(uint8)", "18"),
+            ("checks.Token.owner()(address)", "${deployments.Safe}"),
+            ("fee", "100"),
+        ]);
+        let checks = DeployChecks::load(Some(&params)).unwrap();
+        assert_eq!(checks.checks.len(), 2);
+        assert_eq!(checks.checks[0].contract, "Token");
+        assert_eq!(checks.checks[1].function.signature(), "owner()");
+        assert_eq!(params.unused(), ["fee"]);
+
+        let params = script_params(&[("checks.Token.balanceOf(address)(uint256)", "0")]);
+        assert!(DeployChecks::load(Some(&params)).is_err());
+        let params = script_params(&[("checks.owner()(address)", "0x")]);
+        assert!(DeployChecks::load(Some(&params)).is_err());
+        assert!(DeployChecks::load(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn verifies_outputs() {
+        let params = script_params(&[("checks.Token.decimals()(uint8)", "18")]);
+        let checks = DeployChecks::load(Some(&params)).unwrap();
+        let check = &checks.checks[0];
+        let address = address!("5FbDB2315678afecb367f032d93F642f64180aa3");
+
+        let output = DynSolValue::Uint(U256::from(18), 8).abi_encode();
+        assert_eq!(check.verify(address, Some(&output), "18").unwrap(), None);
+
+        let output = DynSolValue::Uint(U256::from(6), 8).abi_encode();
+        let failure = check.verify(address, Some(&output), "18").unwrap().unwrap();
+        assert!(failure.contains("returned 6, expected 18"), "{failure}");
+
+        let failure = check.verify(address, None, "18").unwrap().unwrap();
+        assert!(failure.ends_with("reverted"), "{failure}");
+        assert!(check.verify(address, None, "eighteen").is_err());
+    }
+}
@@ -0,0 +1,350 @@
+//! L1→L2 deposits, sent by the transactions of a script to the portal of an OP stack chain or to
+//! the inbox of an Arbitrum chain.
+
+use crate::{
+    broadcast::BundledState, reconciliation::format_ether, sequence::ScriptSequence,
+    transaction::TransactionWithMetadata,
+};
+use alloy_dyn_abi::DynSolValue;
+use alloy_json_abi::Function;
+use alloy_primitives::{Address, Bytes, U256};
+use ethers_core::types::NameOrAddress;
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::{
+    provider::ethers::{try_get_http_provider, RetryProvider},
+    shell,
+    types::{ToAlloy, ToEthers},
+};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use yansi::Paint;
+
+/// `OptimismPortal.depositTransaction(to, value, gasLimit, isCreation, data)`
+const DEPOSIT_TRANSACTION: &str = "depositTransaction(address,uint256,uint64,bool,bytes)";
+
+/// `Inbox.createRetryableTicket(to, l2CallValue, maxSubmissionCost, excessFeeRefundAddress,
+/// callValueRefundAddress, gasLimit, maxFeePerGas, data)`
+const CREATE_RETRYABLE_TICKET: &str =
+    "createRetryableTicket(address,uint256,uint256,address,address,uint256,uint256,bytes)";
+
+/// The bridge an L1→L2 deposit is sent through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DepositKind {
+    /// A deposit transaction of the `OptimismPortal` of an OP stack chain.
+    OpStack,
+    /// A retryable ticket of the `Inbox` of an Arbitrum chain.
+    ArbitrumRetryable,
+}
+
+impl std::fmt::Display for DepositKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OpStack => f.write_str("OP stack deposit"),
+            Self::ArbitrumRetryable => f.write_str("Arbitrum retryable ticket"),
+        }
+    }
+}
+
+/// The L2 transaction of an L1→L2 deposit.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1ToL2Deposit {
+    pub kind: DepositKind,
+    /// The L1 chain.
+    pub chain: u64,
+    /// The index of the L1 transaction in its sequence.
+    pub index: usize,
+    /// The portal or inbox the deposit is sent to.
+    pub bridge: Address,
+    /// The sender of the L2 transaction. Transactions of scripts are sent by EOAs, whose address
+    /// isn't aliased on L2.
+    pub l2_sender: Address,
+    /// The recipient of the L2 transaction, `None` for contract creations.
+    pub to: Option<Address>,
+    /// The ETH sent with the L1 transaction, which is minted on L2.
+    pub mint: U256,
+    /// The value of the L2 transaction.
+    pub value: U256,
+    pub gas_limit: u64,
+    pub data: Bytes,
+    /// The ETH a retryable ticket needs to be redeemed automatically: the submission cost, the
+    /// value and the gas of the L2 transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required: Option<U256>,
+    /// The outcome of the L2 transaction, simulated on the L2 with `--l2-rpc-url`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<L2Outcome>,
+}
+
+/// The predicted outcome of the L2 transaction of a deposit.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L2Outcome {
+    pub success: bool,
+    /// The contract created by the transaction, for contract creations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<Address>,
+    /// Why the transaction reverts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Decodes the arguments of a call of `signature`.
+fn decode_call(signature: &str, data: &[u8]) -> Option<Vec<DynSolValue>> {
+    let function = Function::parse(signature).ok()?;
+    let input = data.strip_prefix(function.selector().as_slice())?;
+    function.abi_decode_input(input, false).ok()
+}
+
+impl L1ToL2Deposit {
+    /// Decodes the deposit sent by the `index`th transaction of a sequence on `chain`, if any.
+    pub fn decode(tx: &TransactionWithMetadata, chain: u64, index: usize) -> Option<Self> {
+        let tx = tx.typed_tx();
+        let Some(NameOrAddress::Address(bridge)) = tx.to() else { return None };
+        let data = tx.data()?;
+        let mint = tx.value().copied().unwrap_or_default().to_alloy();
+        let mut deposit = Self {
+            kind: DepositKind::OpStack,
+            chain,
+            index,
+            bridge: bridge.to_alloy(),
+            l2_sender: tx.from()?.to_alloy(),
+            to: None,
+            mint,
+            value: U256::ZERO,
+            gas_limit: 0,
+            data: Bytes::new(),
+            required: None,
+            outcome: None,
+        };
+
+        if let Some(args) = decode_call(DEPOSIT_TRANSACTION, data) {
+            let [to, value, gas_limit, is_creation, data] = &args[..] else { return None };
+            deposit.to = (!is_creation.as_bool()?).then_some(to.as_address()?);
+            deposit.value = value.as_uint()?.0;
+            deposit.gas_limit = gas_limit.as_uint()?.0.saturating_to();
+            deposit.data = data.as_bytes()?.to_vec().into();
+        } else if let Some(args) = decode_call(CREATE_RETRYABLE_TICKET, data) {
+            let [to, value, submission_cost, _, _, gas_limit, max_fee_per_gas, data] = &args[..]
+            else {
+                return None
+            };
+            let (gas_limit, max_fee_per_gas) =
+                (gas_limit.as_uint()?.0, max_fee_per_gas.as_uint()?.0);
+            deposit.kind = DepositKind::ArbitrumRetryable;
+            deposit.to = Some(to.as_address()?);
+            deposit.value = value.as_uint()?.0;
+            deposit.gas_limit = gas_limit.saturating_to();
+            deposit.data = data.as_bytes()?.to_vec().into();
+            deposit.required = Some(
+                submission_cost
+                    .as_uint()?
+                    .0
+                    .saturating_add(deposit.value)
+                    .saturating_add(gas_limit.saturating_mul(max_fee_per_gas)),
+            );
+        } else {
+            return None
+        }
+        Some(deposit)
+    }
+
+    /// Returns the deposits sent by the transactions of the sequence.
+    pub fn find(sequence: &ScriptSequence) -> Vec<Self> {
+        sequence
+            .transactions
+            .iter()
+            .enumerate()
+            .filter_map(|(index, tx)| Self::decode(tx, sequence.chain, index))
+            .collect()
+    }
+
+    /// Whether the retryable ticket isn't funded enough to be redeemed automatically, and has to
+    /// be redeemed manually on L2.
+    pub fn is_underfunded(&self) -> bool {
+        self.required.is_some_and(|required| self.mint < required)
+    }
+
+    /// Simulates the L2 transaction at the latest block of the L2, with the minted ETH credited
+    /// to the sender. Deposits are simulated independently of each other.
+    pub async fn simulate(&self, provider: &RetryProvider) -> Result<L2Outcome> {
+        let sender = self.l2_sender.to_ethers();
+        let balance = provider.get_balance(sender, None).await?.to_alloy();
+        let mut call = serde_json::json!({
+            "from": self.l2_sender,
+            "value": self.value,
+            "gas": U256::from(self.gas_limit),
+            "input": self.data,
+        });
+        if let Some(to) = self.to {
+            call["to"] = serde_json::to_value(to)?;
+        }
+        let overrides = serde_json::json!({
+            self.l2_sender.to_string(): { "balance": balance.saturating_add(self.mint) }
+        });
+
+        let created = match self.to {
+            Some(_) => None,
+            None => {
+                let nonce = provider.get_transaction_count(sender, None).await?;
+                Some(self.l2_sender.create(nonce.as_u64()))
+            }
+        };
+        Ok(match provider.request::<_, Bytes>("eth_call", (call, "latest", overrides)).await {
+            Ok(_) => L2Outcome { success: true, created, error: None },
+            Err(err) => L2Outcome { success: false, created: None, error: Some(err.to_string()) },
+        })
+    }
+
+    fn describe(&self) -> String {
+        let to = self.to.map_or_else(|| "a new contract".to_string(), |to| to.to_string());
+        format!(
+            "#{} {} through {}: {} -> {to}, {} ETH minted, {} ETH sent, gas limit {}",
+            self.index,
+            self.kind,
+            self.bridge,
+            self.l2_sender,
+            format_ether(self.mint),
+            format_ether(self.value),
+            self.gas_limit
+        )
+    }
+}
+
+impl BundledState {
+    /// Finds the L1→L2 deposits of the transactions, and predicts their outcomes on the L2 at
+    /// `--l2-rpc-url`, if set.
+    pub async fn check_deposits(&self) -> Result<Vec<L1ToL2Deposit>> {
+        let mut deposits =
+            self.sequence.sequences().iter().flat_map(L1ToL2Deposit::find).collect::<Vec<_>>();
+        if deposits.is_empty() {
+            return Ok(deposits)
+        }
+
+        let provider = match &self.args.l2_rpc_url {
+            Some(url) => {
+                let config = &self.script_config.config;
+                let url = config
+                    .get_rpc_url_with_alias(url)
+                    .transpose()?
+                    .unwrap_or(Cow::Borrowed(url.as_str()));
+                Some(try_get_http_provider(url)?)
+            }
+            None => None,
+        };
+
+        shell::println("\n==========================")?;
+        shell::println("\nL1→L2 deposits:")?;
+        for deposit in &mut deposits {
+            shell::println(format!("  {}", deposit.describe()))?;
+            if deposit.is_underfunded() {
+                shell::println(Paint::yellow(format!(
+                    "    needs {} ETH to be redeemed automatically, redeem it manually on L2",
+                    format_ether(deposit.required.unwrap_or_default())
+                )))?;
+            }
+            let Some(provider) = &provider else { continue };
+            let outcome = deposit.simulate(provider).await?;
+            match (&outcome.error, outcome.created) {
+                (Some(error), _) => {
+                    shell::println(Paint::red(format!("    reverts on L2: {error}")))?
+                }
+                (None, Some(created)) => {
+                    shell::println(Paint::green(format!("    deploys {created} on L2")))?
+                }
+                (None, None) => shell::println(Paint::green("    succeeds on L2"))?,
+            }
+            deposit.outcome = Some(outcome);
+        }
+        if provider.is_none() {
+            shell::println("Pass `--l2-rpc-url` to predict their outcomes on the L2.")?;
+        }
+        Ok(deposits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U64;
+    use alloy_rpc_types::request::TransactionRequest;
+
+    fn deposit_tx(
+        to: Address,
+        value: u64,
+        signature: &str,
+        args: Vec<DynSolValue>,
+    ) -> TransactionWithMetadata {
+        let function = Function::parse(signature).unwrap();
+        let data = [function.selector().as_slice(), &DynSolValue::Tuple(args).abi_encode_params()]
+            .concat();
+        TransactionWithMetadata::from_tx_request(TransactionRequest {
+            from: Some(Address::with_last_byte(1)),
+            to: Some(to),
+            value: Some(U256::from(value)),
+            input: Some(Bytes::from(data)).into(),
+            nonce: Some(U64::ZERO),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn decodes_op_stack_deposits() {
+        let portal = Address::with_last_byte(0x42);
+        let tx = deposit_tx(
+            portal,
+            1000,
+            DEPOSIT_TRANSACTION,
+            vec![
+                DynSolValue::Address(Address::ZERO),
+                DynSolValue::Uint(U256::from(10), 256),
+                DynSolValue::Uint(U256::from(200_000), 64),
+                DynSolValue::Bool(true),
+                DynSolValue::Bytes(vec![0x60, 0x00]),
+            ],
+        );
+        let deposit = L1ToL2Deposit::decode(&tx, 1, 3).unwrap();
+        assert_eq!(deposit.kind, DepositKind::OpStack);
+        assert_eq!(deposit.index, 3);
+        assert_eq!(deposit.bridge, portal);
+        assert_eq!(deposit.l2_sender, Address::with_last_byte(1));
+        assert_eq!(deposit.to, None);
+        assert_eq!(deposit.mint, U256::from(1000));
+        assert_eq!(deposit.value, U256::from(10));
+        assert_eq!(deposit.gas_limit, 200_000);
+        assert_eq!(deposit.data, Bytes::from(vec![0x60, 0x00]));
+        assert!(!deposit.is_underfunded());
+    }
+
+    #[test]
+    fn decodes_arbitrum_retryables() {
+        let args = |max_fee_per_gas: u64| {
+            vec![
+                DynSolValue::Address(Address::with_last_byte(2)),
+                DynSolValue::Uint(U256::from(10), 256),
+                DynSolValue::Uint(U256::from(100), 256),
+                DynSolValue::Address(Address::with_last_byte(1)),
+                DynSolValue::Address(Address::with_last_byte(1)),
+                DynSolValue::Uint(U256::from(1000), 256),
+                DynSolValue::Uint(U256::from(max_fee_per_gas), 256),
+                DynSolValue::Bytes(vec![]),
+            ]
+        };
+        let inbox = Address::with_last_byte(0x43);
+        let tx = deposit_tx(inbox, 1110, CREATE_RETRYABLE_TICKET, args(1));
+        let deposit = L1ToL2Deposit::decode(&tx, 1, 0).unwrap();
+        assert_eq!(deposit.kind, DepositKind::ArbitrumRetryable);
+        assert_eq!(deposit.to, Some(Address::with_last_byte(2)));
+        assert_eq!(deposit.required, Some(U256::from(1110)));
+        assert!(!deposit.is_underfunded());
+
+        let tx = deposit_tx(inbox, 1110, CREATE_RETRYABLE_TICKET, args(2));
+        assert!(L1ToL2Deposit::decode(&tx, 1, 0).unwrap().is_underfunded());
+
+        // Other calls aren't deposits.
+        let tx = deposit_tx(inbox, 0, "transfer(address,uint256)", args(1)[..2].to_vec());
+        assert_eq!(L1ToL2Deposit::decode(&tx, 1, 0), None);
+    }
+}
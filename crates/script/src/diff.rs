@@ -0,0 +1,153 @@
+use crate::{
+    broadcast::BundledState,
+    multi_sequence::MultiChainSequence,
+    sequence::{ScriptSequence, ScriptSequenceKind},
+};
+use eyre::Result;
+use foundry_common::{shell, types::ToAlloy};
+use foundry_compilers::utils::read_json_file;
+use similar::{ChangeTag, TextDiff};
+use std::fmt::Write;
+use yansi::Paint;
+
+impl BundledState {
+    /// Prints how the planned transactions differ from the ones of the previous run, which are
+    /// still on disk until the new sequence is saved.
+    pub fn show_plan_diff(&self) -> Result<()> {
+        let previous = match &self.sequence {
+            ScriptSequenceKind::Single(sequence) => sequence
+                .paths
+                .as_ref()
+                .and_then(|(path, _)| read_json_file::<ScriptSequence>(path).ok())
+                .map(|sequence| vec![sequence]),
+            ScriptSequenceKind::Multi(sequence) => {
+                read_json_file::<MultiChainSequence>(&sequence.path)
+                    .ok()
+                    .map(|sequence| sequence.deployments)
+            }
+        };
+
+        shell::println("\n== Plan diff ==")?;
+        let Some(previous) = previous else {
+            shell::println("No previous run to compare the planned transactions with.")?;
+            return Ok(())
+        };
+
+        let Some(changes) = plan_diff(&previous, self.sequence.sequences()) else {
+            shell::println("The planned transactions didn't change.")?;
+            return Ok(())
+        };
+
+        for (tag, line) in changes {
+            let line = match tag {
+                ChangeTag::Delete => Paint::red(format!("- {line}")).to_string(),
+                ChangeTag::Insert => Paint::green(format!("+ {line}")).to_string(),
+                ChangeTag::Equal => format!("  {line}"),
+            };
+            shell::println(line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the lines of the plans of `previous` and `current` tagged with how they changed, or
+/// `None` if the plans are the same.
+fn plan_diff(
+    previous: &[ScriptSequence],
+    current: &[ScriptSequence],
+) -> Option<Vec<(ChangeTag, String)>> {
+    let old = plan(previous);
+    let new = plan(current);
+    if old == new {
+        return None
+    }
+    let changes = TextDiff::from_lines(&old, &new)
+        .iter_all_changes()
+        .map(|change| (change.tag(), change.value().trim_end().to_string()))
+        .collect();
+    Some(changes)
+}
+
+/// Renders the transactions of the sequences one per line, leaving out gas and nonces which
+/// change between runs without the plan changing.
+fn plan(sequences: &[ScriptSequence]) -> String {
+    let mut plan = String::new();
+    for sequence in sequences {
+        let _ = writeln!(plan, "Chain {}", sequence.chain);
+        for tx in &sequence.transactions {
+            let name = tx.contract_name.as_deref().unwrap_or_default();
+            let _ = write!(plan, "  {} {name}", tx.opcode);
+            if let Some(function) = tx.function.as_deref().filter(|f| !f.is_empty()) {
+                let args = tx.arguments.as_deref().unwrap_or_default().join(", ");
+                let _ = write!(plan, " {function} ({args})");
+            }
+            if let Some(address) = tx.contract_address {
+                let _ = write!(plan, " at {address}");
+            }
+            if let Some(value) = tx.typed_tx().value().filter(|value| !value.is_zero()) {
+                let _ = write!(plan, " value {}", value.to_alloy());
+            }
+            plan.push('\n');
+        }
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TransactionWithMetadata;
+    use alloy_primitives::{address, Address};
+    use foundry_evm::traces::CallKind;
+
+    fn tx(
+        opcode: CallKind,
+        name: &str,
+        function: Option<&str>,
+        at: Address,
+    ) -> TransactionWithMetadata {
+        TransactionWithMetadata {
+            opcode,
+            contract_name: Some(name.to_string()),
+            contract_address: Some(at),
+            function: function.map(str::to_string),
+            arguments: function.map(|_| vec!["1".to_string()]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn diffs_plans() {
+        let token = address!("00000000000000000000000000000000000000a1");
+        let vault = address!("00000000000000000000000000000000000000b2");
+        let sequence = |transactions: Vec<TransactionWithMetadata>| ScriptSequence {
+            chain: 1,
+            transactions: transactions.into(),
+            ..Default::default()
+        };
+        let previous = [sequence(vec![
+            tx(CallKind::Create, "Token", None, token),
+            tx(CallKind::Call, "Token", Some("mint(uint256)"), token),
+        ])];
+        let current = [sequence(vec![
+            tx(CallKind::Create, "Token", None, token),
+            tx(CallKind::Create, "Vault", None, vault),
+            tx(CallKind::Call, "Token", Some("mint(uint256)"), token),
+        ])];
+
+        assert_eq!(plan_diff(&previous, &previous), None);
+        let changes = plan_diff(&previous, &current).unwrap();
+        assert_eq!(
+            changes,
+            [
+                (ChangeTag::Equal, "Chain 1".to_string()),
+                (ChangeTag::Equal, format!("  {} Token at {token}", CallKind::Create)),
+                (ChangeTag::Insert, format!("  {} Vault at {vault}", CallKind::Create)),
+                (
+                    ChangeTag::Equal,
+                    format!("  {} Token mint(uint256) (1) at {token}", CallKind::Call)
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use crate::runner::ScriptRunner;
+use alloy_primitives::{Address, Bytes, U256};
+use eyre::Result;
+use foundry_common::fs;
+use foundry_evm::revm::{
+    db::{AccountState, CacheDB},
+    primitives::BlockEnv,
+    DatabaseRef,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// The state of the chain after the simulation, in the format of `anvil --dump-state`, so that
+/// it can be loaded with `anvil --load-state`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AnvilState {
+    pub block: Option<BlockEnv>,
+    pub accounts: BTreeMap<Address, AnvilAccount>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnvilAccount {
+    pub nonce: u64,
+    pub balance: U256,
+    pub code: Bytes,
+    pub storage: BTreeMap<U256, U256>,
+}
+
+impl AnvilState {
+    /// Collects the accounts of the state of `runner`.
+    ///
+    /// When forking, only the accounts and slots the script loaded or wrote are known, which are
+    /// the ones its contracts need to work against the dumped state.
+    pub fn new(runner: &ScriptRunner) -> Result<Self> {
+        let backend = &runner.executor.backend;
+        let accounts = match backend.active_fork_db() {
+            Some(db) => accounts(db)?,
+            None => accounts(backend.mem_db())?,
+        };
+        Ok(Self { block: Some(runner.executor.env.block.clone()), accounts })
+    }
+
+    /// Writes the state to `path`.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write_json_file(path, self)?;
+        Ok(())
+    }
+}
+
+fn accounts<DB>(db: &CacheDB<DB>) -> Result<BTreeMap<Address, AnvilAccount>>
+where
+    DB: DatabaseRef,
+    DB::Error: fmt::Display,
+{
+    let mut accounts = BTreeMap::new();
+    for (address, account) in &db.accounts {
+        if account.account_state == AccountState::NotExisting {
+            continue
+        }
+        let code = match &account.info.code {
+            Some(code) => code.clone(),
+            None => {
+                db.code_by_hash_ref(account.info.code_hash).map_err(|err| eyre::eyre!("{err}"))?
+            }
+        };
+        accounts.insert(
+            *address,
+            AnvilAccount {
+                nonce: account.info.nonce,
+                balance: account.info.balance,
+                code: code.original_bytes(),
+                storage: account.storage.iter().map(|(slot, value)| (*slot, *value)).collect(),
+            },
+        );
+    }
+    Ok(accounts)
+}
+
+/// Returns the path of the dump of the chain `chain`, which is `path` itself unless the script
+/// runs on several chains, each of which gets its own dump next to it.
+pub fn dump_path(path: &Path, chain: u64, multi_chain: bool) -> PathBuf {
+    if !multi_chain {
+        return path.to_path_buf()
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    match path.extension() {
+        Some(extension) => {
+            path.with_file_name(format!("{stem}-{chain}.{}", extension.to_string_lossy()))
+        }
+        None => path.with_file_name(format!("{stem}-{chain}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use foundry_evm::revm::{
+        db::EmptyDB,
+        primitives::{AccountInfo, Bytecode},
+    };
+
+    #[test]
+    fn dumps_accounts_in_anvil_format() {
+        let token = address!("00000000000000000000000000000000000000aa");
+        let mut db = CacheDB::new(EmptyDB::default());
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00]));
+        db.insert_account_info(
+            token,
+            AccountInfo {
+                nonce: 1,
+                balance: U256::from(5),
+                code_hash: code.hash_slow(),
+                code: None,
+            },
+        );
+        db.contracts.insert(code.hash_slow(), code);
+        db.insert_account_storage(token, U256::from(1), U256::from(7)).unwrap();
+
+        let accounts = accounts(&db).unwrap();
+        assert_eq!(
+            accounts[&token],
+            AnvilAccount {
+                nonce: 1,
+                balance: U256::from(5),
+                code: Bytes::from_static(&[0x60, 0x00]),
+                storage: BTreeMap::from([(U256::from(1), U256::from(7))]),
+            }
+        );
+        let state = AnvilState { block: None, accounts };
+        let json = serde_json::to_value(&state).unwrap();
+        let (_, account) = json["accounts"].as_object().unwrap().iter().next().unwrap();
+        assert_eq!(account["code"], "0x6000");
+    }
+
+    #[test]
+    fn suffixes_dumps_of_multi_chain_scripts() {
+        let path = Path::new("out/state.json");
+        assert_eq!(dump_path(path, 1, false), Path::new("out/state.json"));
+        assert_eq!(dump_path(path, 10, true), Path::new("out/state-10.json"));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::ScriptArgs;
+use alloy_primitives::Address;
+use eyre::{Result, WrapErr};
+use foundry_config::{
+    figment::{
+        providers::{Format, Toml},
+        Figment,
+    },
+    Chain,
+};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// File name of the deployment environments, looked up in the project root.
+pub const ENVIRONMENTS_FILE_NAME: &str = "environments.toml";
+
+/// A deployment environment, selected with `--env`.
+///
+/// ```toml
+/// [staging]
+/// chain = "sepolia"
+/// rpc = "sepolia"
+/// sender = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+/// params = "params/staging.toml"
+/// policy = "policies/staging.toml"
+/// features = ["oracle"]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Environment {
+    pub chain: Option<Chain>,
+    /// An RPC URL, or the name of an endpoint of `[rpc_endpoints]`.
+    pub rpc: Option<String>,
+    pub sender: Option<Address>,
+    /// The parameters file of the script, relative to the project root.
+    pub params: Option<PathBuf>,
+    /// The transaction policy file, relative to the project root.
+    pub policy: Option<PathBuf>,
+    /// The feature flags enabled for the script.
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+impl Environment {
+    /// Loads the environment `name` of the `environments.toml` file of the project.
+    pub fn load(root: &Path, name: &str) -> Result<Self> {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || "-_".contains(c)) {
+            eyre::bail!("Invalid environment name `{name}`")
+        }
+        let path = root.join(ENVIRONMENTS_FILE_NAME);
+        if !path.exists() {
+            eyre::bail!("--env {name} requires {ENVIRONMENTS_FILE_NAME} in the project root")
+        }
+
+        let mut environments: BTreeMap<String, Self> =
+            Figment::from(Toml::file(&path))
+                .extract()
+                .wrap_err_with(|| format!("Failed to load environments {}", path.display()))?;
+        let Some(mut environment) = environments.remove(name) else {
+            let names = environments.into_keys().collect::<Vec<_>>();
+            eyre::bail!("Unknown environment `{name}`, expected one of: {}", names.join(", "))
+        };
+        environment.params = environment.params.map(|params| root.join(params));
+        environment.policy = environment.policy.map(|policy| root.join(policy));
+        Ok(environment)
+    }
+}
+
+impl ScriptArgs {
+    /// Applies the values of the environment, unless they were already set on the command line.
+    pub(crate) fn apply_environment(&mut self, environment: Environment) {
+        if self.evm_opts.env.chain.is_none() {
+            self.evm_opts.env.chain = environment.chain;
+        }
+        if self.evm_opts.fork_url.is_none() {
+            self.evm_opts.fork_url = environment.rpc;
+        }
+        if self.evm_opts.sender.is_none() {
+            self.evm_opts.sender = environment.sender;
+        }
+        if self.params.is_none() {
+            self.params = environment.params;
+        }
+        if self.policy.is_none() {
+            self.policy = environment.policy;
+        }
+        if self.features.is_empty() {
+            self.features = environment.features;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use foundry_config::NamedChain;
+
+    #[test]
+    fn loads_environments() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(
+            root.path().join(ENVIRONMENTS_FILE_NAME),
+            r#"
+[staging]
+chain = "sepolia"
+rpc = "sepolia"
+sender = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+params = "params/staging.toml"
+
+features = ["oracle"]
+
+[prod]
+chain = 1
+policy = "policies/prod.toml"
+"#,
+        )
+        .unwrap();
+
+        let staging = Environment::load(root.path(), "staging").unwrap();
+        assert_eq!(staging.chain, Some(NamedChain::Sepolia.into()));
+        assert_eq!(staging.params, Some(root.path().join("params/staging.toml")));
+        assert!(staging.policy.is_none());
+
+        let mut args = ScriptArgs::default();
+        args.evm_opts.fork_url = Some("http://localhost:8545".to_string());
+        args.apply_environment(staging);
+        assert_eq!(args.evm_opts.fork_url.as_deref(), Some("http://localhost:8545"));
+        assert_eq!(
+            args.evm_opts.sender,
+            Some(address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266"))
+        );
+        assert_eq!(args.params, Some(root.path().join("params/staging.toml")));
+        assert_eq!(args.features, ["oracle"]);
+
+        let prod = Environment::load(root.path(), "prod").unwrap();
+        assert_eq!(prod.chain, Some(NamedChain::Mainnet.into()));
+        assert!(prod.features.is_empty());
+        assert!(Environment::load(root.path(), "dev").is_err());
+        assert!(Environment::load(root.path(), "../prod").is_err());
+    }
+}
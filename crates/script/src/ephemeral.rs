@@ -0,0 +1,226 @@
+use crate::{
+    funding::{self, TRANSFER_GAS},
+    reconciliation::format_ether,
+    sequence::ScriptSequence,
+};
+use alloy_primitives::{hex, Address, B256, U256};
+use ethers_providers::Middleware;
+use ethers_signers::Signer;
+use eyre::{Result, WrapErr};
+use foundry_common::{
+    fs,
+    provider::ethers::RetryProvider,
+    shell,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_wallets::WalletSigner;
+use std::{
+    fmt,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// The directory of the cache the keys of the ephemeral deployers are kept in until their funds
+/// are swept back.
+pub const KEYS_DIR: &str = "ephemeral-deployers";
+
+/// Added to the cost of the transactions when funding, in percent, as their gas may be estimated
+/// again when they're sent.
+const FUNDING_MARGIN_PERCENT: u64 = 20;
+
+/// A key generated for a single run of a script with `--ephemeral-deployer`, which deploys its
+/// contracts from nonce 0 and is funded and swept by the funding account.
+#[derive(Clone)]
+pub struct EphemeralDeployer {
+    key: B256,
+    address: Address,
+    /// Where the key is kept until the funds are swept back.
+    path: PathBuf,
+}
+
+impl fmt::Debug for EphemeralDeployer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EphemeralDeployer").field("address", &self.address).finish()
+    }
+}
+
+impl EphemeralDeployer {
+    /// Generates a deployer, whose key is kept in the `cache` directory once it's saved.
+    pub fn generate(cache: &Path) -> Self {
+        let (key, address) = loop {
+            // Almost all 32 byte values are valid keys.
+            let key = B256::random();
+            if let Ok(signer) = WalletSigner::from_private_key(key) {
+                break (key, signer.address().to_alloy())
+            }
+        };
+        Self { key, address, path: key_path(cache, address) }
+    }
+
+    /// Writes the key to the `cache` directory, before its funds are broadcast.
+    pub fn save(&self) -> Result<()> {
+        fs::create_dir_all(self.path.parent().unwrap_or(&self.path))?;
+        // The key is only ever readable by the owner, even while it's written.
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options
+            .open(&self.path)
+            .wrap_err_with(|| format!("Failed to create {}", self.path.display()))?;
+        file.write_all(hex::encode_prefixed(self.key).as_bytes())?;
+        Ok(())
+    }
+
+    /// Loads the deployer of `address` recorded in the broadcast sequence, to resume its run.
+    pub fn load(cache: &Path, address: Address) -> Result<Self> {
+        let path = key_path(cache, address);
+        let key = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("No key of the ephemeral deployer {address} to resume"))?
+            .trim()
+            .parse::<B256>()
+            .wrap_err_with(|| format!("Invalid ephemeral deployer key in {}", path.display()))?;
+        if WalletSigner::from_private_key(key)?.address().to_alloy() != address {
+            eyre::bail!("The key in {} isn't the key of {address}", path.display())
+        }
+        Ok(Self { key, address, path })
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub fn key(&self) -> B256 {
+        self.key
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Transfers what the transactions of the sequence from `start` cost the deployer at
+    /// `gas_price`, minus its balance, from `funder`.
+    pub async fn fund(
+        &self,
+        provider: &RetryProvider,
+        funder: &WalletSigner,
+        sequence: &ScriptSequence,
+        start: usize,
+        gas_price: U256,
+    ) -> Result<()> {
+        let required = self.required_funds(sequence, start, gas_price);
+        let balance = provider.get_balance(self.address.to_ethers(), None).await?.to_alloy();
+        if balance >= required {
+            return Ok(())
+        }
+        let deficit = required - balance;
+        shell::println(format!(
+            "Funding the ephemeral deployer {} with {} ETH on chain {}",
+            self.address,
+            format_ether(deficit),
+            sequence.chain
+        ))?;
+        let funder_price = provider.get_gas_price().await?.to_alloy();
+        funding::transfer(provider, funder, sequence.chain, self.address, deficit, funder_price)
+            .await
+    }
+
+    /// Returns what the transactions of the sequence from `start` sent by the deployer cost at
+    /// `gas_price`, with a margin.
+    fn required_funds(&self, sequence: &ScriptSequence, start: usize, gas_price: U256) -> U256 {
+        let cost = sequence
+            .typed_transactions()
+            .skip(start)
+            .filter(|tx| tx.from().map(|from| from.to_alloy()) == Some(self.address))
+            .fold(U256::ZERO, |cost, tx| {
+                let gas = tx.gas().map(|gas| gas.to_alloy()).unwrap_or_default();
+                let value = tx.value().map(|value| value.to_alloy()).unwrap_or_default();
+                cost + value + gas * gas_price
+            });
+        cost * U256::from(100 + FUNDING_MARGIN_PERCENT) / U256::from(100)
+    }
+
+    /// Transfers the remaining balance of the deployer on the chain back to `to`.
+    pub async fn sweep(&self, provider: &RetryProvider, chain: u64, to: Address) -> Result<()> {
+        let balance = provider.get_balance(self.address.to_ethers(), None).await?.to_alloy();
+        let gas_price = provider.get_gas_price().await?.to_alloy();
+        let fee = gas_price * U256::from(TRANSFER_GAS);
+        if balance <= fee {
+            return Ok(())
+        }
+        let value = balance - fee;
+        shell::println(format!(
+            "Sweeping {} ETH from the ephemeral deployer {} back to {to} on chain {chain}",
+            format_ether(value),
+            self.address
+        ))?;
+        let signer = WalletSigner::from_private_key(self.key)?;
+        funding::transfer(provider, &signer, chain, to, value, gas_price).await
+    }
+
+    /// Removes the key, once the funds are swept back.
+    pub fn forget(&self) -> Result<()> {
+        Ok(fs::remove_file(&self.path)?)
+    }
+}
+
+fn key_path(cache: &Path, address: Address) -> PathBuf {
+    cache.join(KEYS_DIR).join(format!("{address}.key"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TransactionWithMetadata;
+    use alloy_primitives::{Bytes, U64};
+    use alloy_rpc_types::request::TransactionRequest;
+
+    fn tx(from: Address, value: u64, gas: u64) -> TransactionWithMetadata {
+        TransactionWithMetadata::from_tx_request(TransactionRequest {
+            from: Some(from),
+            value: Some(U256::from(value)),
+            input: Some(Bytes::from_static(&[0x60, 0x80])).into(),
+            gas: Some(U256::from(gas)),
+            nonce: Some(U64::ZERO),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn generates_and_resumes_deployers() {
+        let cache = tempfile::tempdir().unwrap();
+        let deployer = EphemeralDeployer::generate(cache.path());
+        assert!(deployer.path().starts_with(cache.path().join(KEYS_DIR)));
+        // The key is only written once saved.
+        assert!(EphemeralDeployer::load(cache.path(), deployer.address()).is_err());
+
+        deployer.save().unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = deployer.path().metadata().unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let resumed = EphemeralDeployer::load(cache.path(), deployer.address()).unwrap();
+        assert_eq!(resumed.address(), deployer.address());
+        assert_eq!(resumed.key(), deployer.key());
+
+        deployer.forget().unwrap();
+        assert!(EphemeralDeployer::load(cache.path(), deployer.address()).is_err());
+    }
+
+    #[test]
+    fn computes_required_funds() {
+        let cache = tempfile::tempdir().unwrap();
+        let deployer = EphemeralDeployer::generate(cache.path());
+        let mut sequence = ScriptSequence::default();
+        sequence.transactions.push_back(tx(deployer.address(), 0, 1_000));
+        sequence.transactions.push_back(tx(Address::with_last_byte(1), 5, 1_000));
+        sequence.transactions.push_back(tx(deployer.address(), 100, 2_000));
+
+        // Other senders pay for their own transactions.
+        assert_eq!(deployer.required_funds(&sequence, 0, U256::from(10)), U256::from(36_120));
+        // Broadcast transactions are paid for already.
+        assert_eq!(deployer.required_funds(&sequence, 1, U256::from(10)), U256::from(24_120));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{broadcast::BundledState, sequence::ScriptSequenceKind, verify::BroadcastedState};
+use alloy_primitives::{Address, Bytes, Log, LogData, B256};
+use ethers_core::types::TransactionReceipt;
+use eyre::Result;
+use foundry_common::{fs, get_contract_name, shell, types::ToAlloy};
+use foundry_evm::traces::{CallTraceDecoder, DecodedCallLog};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// The name of the file, next to the broadcast artifact, which holds the events of the run.
+const EVENTS_FILE_NAME: &str = "events.json";
+
+/// An event emitted by a transaction of the script, either during the simulation or on-chain.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptEvent {
+    /// Either `simulation` or `broadcast`.
+    pub stage: &'static str,
+    /// The chain the transaction was broadcast to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain: Option<u64>,
+    /// The index of the simulated transaction among the transactions of the script.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_hash: Option<B256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_index: Option<u64>,
+    pub address: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract: Option<String>,
+    /// The name of the event, if it could be decoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    pub params: Vec<EventParam>,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+}
+
+/// A decoded parameter of an event.
+#[derive(Clone, Debug, Serialize)]
+pub struct EventParam {
+    pub name: String,
+    pub value: String,
+}
+
+/// The events of a script run, in the order they were emitted.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct ScriptEvents(pub Vec<ScriptEvent>);
+
+/// Where an event was emitted.
+struct EventOrigin {
+    stage: &'static str,
+    chain: Option<u64>,
+    transaction: Option<usize>,
+    transaction_hash: Option<B256>,
+    block_number: Option<u64>,
+    log_index: Option<u64>,
+}
+
+impl ScriptEvents {
+    /// Adds the events emitted by the simulated transaction at index `transaction`.
+    pub async fn add_simulated(
+        &mut self,
+        transaction: usize,
+        logs: &[Log],
+        decoder: &CallTraceDecoder,
+    ) {
+        for log in logs {
+            let origin = EventOrigin {
+                stage: "simulation",
+                chain: None,
+                transaction: Some(transaction),
+                transaction_hash: None,
+                block_number: None,
+                log_index: None,
+            };
+            self.add(origin, log.address, &log.data, decoder).await;
+        }
+    }
+
+    /// Adds the events of the transactions of `receipts`, broadcast to `chain`.
+    pub async fn add_receipts(
+        &mut self,
+        chain: u64,
+        receipts: &[TransactionReceipt],
+        decoder: &CallTraceDecoder,
+    ) {
+        for log in receipts.iter().flat_map(|receipt| &receipt.logs) {
+            let origin = EventOrigin {
+                stage: "broadcast",
+                chain: Some(chain),
+                transaction: None,
+                transaction_hash: log.transaction_hash.map(|hash| hash.to_alloy()),
+                block_number: log.block_number.map(|number| number.as_u64()),
+                log_index: log.log_index.map(|index| index.as_u64()),
+            };
+            let topics = log.topics.iter().map(|topic| topic.to_alloy()).collect();
+            let data = LogData::new_unchecked(topics, log.data.clone().to_alloy());
+            self.add(origin, log.address.to_alloy(), &data, decoder).await;
+        }
+    }
+
+    async fn add(
+        &mut self,
+        origin: EventOrigin,
+        address: Address,
+        log: &LogData,
+        decoder: &CallTraceDecoder,
+    ) {
+        let (event, params) = match decoder.decode_event(&address, log).await {
+            DecodedCallLog::Decoded(name, params) => (
+                Some(name),
+                params.into_iter().map(|(name, value)| EventParam { name, value }).collect(),
+            ),
+            DecodedCallLog::Raw(_) => (None, Vec::new()),
+        };
+        let contract = decoder
+            .contracts
+            .get(&address)
+            .map(|id| get_contract_name(id).to_string())
+            .or_else(|| decoder.labels.get(&address).cloned());
+
+        self.0.push(ScriptEvent {
+            stage: origin.stage,
+            chain: origin.chain,
+            transaction: origin.transaction,
+            transaction_hash: origin.transaction_hash,
+            block_number: origin.block_number,
+            log_index: origin.log_index,
+            address,
+            contract,
+            event,
+            params,
+            topics: log.topics().to_vec(),
+            data: log.data.clone(),
+        });
+    }
+
+    /// Writes the events next to the broadcast artifact of `sequence`, and returns the path of
+    /// the file.
+    fn save(&self, sequence: &ScriptSequenceKind) -> Result<Option<PathBuf>> {
+        let Some(dir) = sequence.broadcast_path().and_then(|path| path.parent()) else {
+            return Ok(None)
+        };
+        let path = dir.join(EVENTS_FILE_NAME);
+        fs::write_json_file(&path, self)?;
+        Ok(Some(path))
+    }
+}
+
+impl BundledState {
+    /// Writes the events emitted by the simulated transactions.
+    pub fn export_events(&self) -> Result<()> {
+        if let Some(path) = self.execution_artifacts.events.save(&self.sequence)? {
+            shell::println(format!("\nEvents saved to: {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+impl BroadcastedState {
+    /// Adds the events of the broadcast transactions to the simulated ones, and writes them.
+    pub async fn export_events(&mut self) -> Result<()> {
+        let artifacts = &mut self.execution_artifacts;
+        for sequence in self.sequence.sequences() {
+            artifacts
+                .events
+                .add_receipts(sequence.chain, &sequence.receipts, &artifacts.decoder)
+                .await;
+        }
+        if let Some(path) = artifacts.events.save(&self.sequence)? {
+            shell::println(format!("\nEvents saved to: {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256};
+    use foundry_cli::utils::block_on;
+
+    #[test]
+    fn keeps_undecoded_events() {
+        let mut events = ScriptEvents::default();
+        let log = Log {
+            address: address!("00000000000000000000000000000000000000aa"),
+            data: LogData::new_unchecked(
+                vec![b256!("00000000000000000000000000000000000000000000000000000000000000ff")],
+                Bytes::from_static(&[1, 2]),
+            ),
+        };
+        block_on(events.add_simulated(3, &[log], &CallTraceDecoder::default()));
+
+        let json = serde_json::to_value(&events).unwrap();
+        assert_eq!(json[0]["stage"], "simulation");
+        assert_eq!(json[0]["transaction"], 3);
+        assert_eq!(json[0]["data"], "0x0102");
+        assert!(json[0].get("event").is_none());
+        assert!(json[0].get("chain").is_none());
+    }
+}
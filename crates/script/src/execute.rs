@@ -1,6 +1,16 @@
 use crate::{
-    build::{CompiledState, LinkedBuildData},
+    build::LinkedBuildData,
+    console::console_listener,
+    events::ScriptEvents,
+    governance::RehearsedStep,
+    onchain_diff::SimulatedExecutions,
     simulate::PreSimulationState,
+    state_diff::SlotChange,
+    templates::{
+        predict_deployments, print_deployment_templates, resolve_templates, MAX_TEMPLATE_PASSES,
+    },
+    trace_export::{export_traces, TraceFormat},
+    vanity::print_vanity_salts,
     ScriptArgs, ScriptConfig,
 };
 
@@ -17,7 +27,7 @@ use foundry_cli::utils::{ensure_clean_constructor, needs_setup};
 use foundry_common::{
     fmt::{format_token, format_token_raw},
     provider::ethers::{get_http_provider, RpcUrl},
-    shell, ContractsByArtifact,
+    shell, tr, ContractsByArtifact,
 };
 use foundry_compilers::artifacts::ContractBytecodeSome;
 use foundry_config::{Config, NamedChain};
@@ -32,7 +42,10 @@ use foundry_evm::{
 };
 use futures::future::join_all;
 use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 use yansi::Paint;
 
 /// State after linking, contains the linked build data along with library addresses and optional
@@ -98,24 +111,38 @@ impl PreExecutionState {
     pub async fn execute(mut self) -> Result<ExecutedState> {
         let mut runner = self
             .script_config
-            .get_runner_with_cheatcodes(self.script_wallets.clone(), self.args.debug)
+            .get_runner_with_cheatcodes(
+                self.script_wallets.clone(),
+                self.args.debug,
+                self.args.coverage,
+                console_listener(self.args.json),
+            )
             .await?;
         let mut result = self.execute_with_runner(&mut runner).await?;
+        // The backend is reused, reverted, if the script is executed again or simulated.
+        self.script_config
+            .backends
+            .release(self.script_config.evm_opts.fork_url.as_ref(), runner.executor.backend);
 
         // If we have a new sender from execution, we need to use it to deploy libraries and relink
         // contracts.
         if let Some(new_sender) = self.maybe_new_sender(result.transactions.as_ref())? {
             self.script_config.update_sender(new_sender).await?;
 
-            // Rollback to rerun linking with the new sender.
-            let state = CompiledState {
+            // Relink the contracts depending on libraries deployed by the new sender.
+            let known_libraries = self.script_config.config.libraries_with_remappings()?;
+            let state = LinkedState {
+                build_data: self.build_data.relink(
+                    known_libraries,
+                    new_sender,
+                    self.script_config.sender_nonce,
+                )?,
                 args: self.args,
                 script_config: self.script_config,
                 script_wallets: self.script_wallets,
-                build_data: self.build_data.build_data,
             };
 
-            return state.link()?.prepare_execution().await?.execute().await;
+            return state.prepare_execution().await?.execute().await;
         }
 
         // Add library deployment transactions to broadcastable transactions list.
@@ -139,6 +166,68 @@ impl PreExecutionState {
             );
         }
 
+        // Arguments and parameters referencing the deployments of the script are resolved with
+        // the addresses of this execution and the salts mined for them, and the script is executed
+        // again until they settle.
+        if !self.script_config.deployment_templates.is_empty() {
+            let deployments = predict_deployments(result.transactions.as_ref(), &self.build_data);
+            let salts = match &mut self.script_config.vanity {
+                Some(vanity) => {
+                    vanity.mine_salts(result.transactions.as_ref(), &self.build_data)?
+                }
+                None => Default::default(),
+            };
+            let changed = resolve_templates(
+                &mut self.script_config.deployment_templates,
+                &deployments,
+                &salts,
+                &mut self.args.args,
+                self.script_config.script_params.as_ref(),
+            )?;
+            if changed {
+                self.script_config.template_passes += 1;
+                if self.script_config.template_passes >= MAX_TEMPLATE_PASSES {
+                    eyre::bail!(
+                        "The addresses of the deployment templates didn't settle after {} \
+                         executions, they likely depend on each other",
+                        MAX_TEMPLATE_PASSES
+                    )
+                }
+                let state = LinkedState {
+                    args: self.args,
+                    script_config: self.script_config,
+                    script_wallets: self.script_wallets,
+                    build_data: self.build_data,
+                };
+                return state.prepare_execution().await?.execute().await;
+            }
+            print_deployment_templates(&self.script_config.deployment_templates)?;
+            if let Some(vanity) = &self.script_config.vanity {
+                print_vanity_salts(&vanity.salts)?;
+            }
+        }
+
+        // Parameters that the script never read are most likely misspelled.
+        if let Some(params) = &self.script_config.script_params {
+            let features = params.features();
+            if !features.is_empty() {
+                let features = features
+                    .iter()
+                    .map(|(name, enabled)| {
+                        format!("{name} ({})", if *enabled { "enabled" } else { "disabled" })
+                    })
+                    .collect::<Vec<_>>();
+                shell::println(format!("Feature flags: {}", features.join(", ")))?;
+            }
+            let unused = params.unused();
+            if !unused.is_empty() {
+                shell::println(Paint::yellow(format!(
+                    "Warning: script parameters not read by the script: {}",
+                    unused.join(", ")
+                )))?;
+            }
+        }
+
         Ok(ExecutedState {
             args: self.args,
             script_config: self.script_config,
@@ -171,6 +260,7 @@ impl PreExecutionState {
             setup_result.labeled_addresses.extend(script_result.labeled_addresses);
             setup_result.returned = script_result.returned;
             setup_result.breakpoints = script_result.breakpoints;
+            setup_result.merge_coverage(script_result.coverage);
 
             match (&mut setup_result.transactions, script_result.transactions) {
                 (Some(txs), Some(new_txs)) => {
@@ -275,13 +365,24 @@ For more information, please see https://eips.ethereum.org/EIPS/eip-3855",
 /// Container for data being collected after execution.
 pub struct ExecutionArtifacts {
     /// Mapping from contract to its runtime code.
-    pub known_contracts: ContractsByArtifact,
+    pub known_contracts: Arc<ContractsByArtifact>,
     /// Trace decoder used to decode traces.
     pub decoder: CallTraceDecoder,
     /// Return values from the execution result.
     pub returns: HashMap<String, NestedValue>,
     /// Information about RPC endpoints used during script execution.
     pub rpc_data: RpcData,
+    /// Events emitted by the transactions of the script, exported with `--export-events`.
+    pub events: ScriptEvents,
+    /// The simulated executions of the transactions, compared with `--diff-onchain`.
+    pub simulated_executions: SimulatedExecutions,
+    /// The governance steps rehearsed with `--rehearse-governance`.
+    pub governance_rehearsal: Vec<RehearsedStep>,
+    /// The labeled storage changes of the simulated transactions, with `--state-diff` or
+    /// `--html-report`.
+    pub state_diff: Vec<SlotChange>,
+    /// The warnings of the simulation, e.g. the findings of the security checks.
+    pub warnings: Vec<String>,
 }
 
 /// State after the script has been executed.
@@ -299,7 +400,7 @@ impl ExecutedState {
     pub async fn prepare_simulation(self) -> Result<PreSimulationState> {
         let returns = self.get_returns()?;
 
-        let known_contracts = self.build_data.get_flattened_contracts(true);
+        let known_contracts = Arc::new(self.build_data.get_flattened_contracts(true));
         let decoder = self.build_trace_decoder(&known_contracts)?;
 
         let txs = self.execution_result.transactions.clone().unwrap_or_default();
@@ -327,19 +428,29 @@ impl ExecutedState {
             build_data: self.build_data,
             execution_data: self.execution_data,
             execution_result: self.execution_result,
-            execution_artifacts: ExecutionArtifacts { known_contracts, decoder, returns, rpc_data },
+            execution_artifacts: ExecutionArtifacts {
+                known_contracts,
+                decoder,
+                returns,
+                rpc_data,
+                events: Default::default(),
+                simulated_executions: Default::default(),
+                governance_rehearsal: Vec::new(),
+                state_diff: Vec::new(),
+                warnings: Vec::new(),
+            },
         })
     }
 
     /// Builds [CallTraceDecoder] from the execution result and known contracts.
     fn build_trace_decoder(
         &self,
-        known_contracts: &ContractsByArtifact,
+        known_contracts: &Arc<ContractsByArtifact>,
     ) -> Result<CallTraceDecoder> {
         let mut decoder = CallTraceDecoderBuilder::new()
             .with_labels(self.execution_result.labeled_addresses.clone())
             .with_verbosity(self.script_config.evm_opts.verbosity)
-            .with_known_contracts(known_contracts)
+            .with_lazy_known_contracts(known_contracts.clone())
             .with_signature_identifier(SignaturesIdentifier::new(
                 Config::foundry_cache_dir(),
                 self.script_config.config.offline,
@@ -353,8 +464,12 @@ impl ExecutedState {
 
         // Decoding traces using etherscan is costly as we run into rate limits,
         // causing scripts to run for a very long time unnecessarily.
-        // Therefore, we only try and use etherscan if the user has provided an API key.
-        let should_use_etherscan_traces = self.script_config.config.etherscan_api_key.is_some();
+        // Therefore, we only try and use etherscan if the user has provided an API key, and only
+        // if the execution traces are going to be rendered, or the decoded calls of the
+        // transactions are going to be recorded in the broadcast sequence.
+        let should_use_etherscan_traces = self.script_config.config.etherscan_api_key.is_some() &&
+            !self.script_config.config.offline &&
+            (self.renders_traces() || self.records_transactions());
         if !should_use_etherscan_traces {
             identifier.etherscan = None;
         }
@@ -366,6 +481,21 @@ impl ExecutedState {
         Ok(decoder)
     }
 
+    /// Whether the traces of the execution are rendered, either in the terminal or in the
+    /// debugger.
+    fn renders_traces(&self) -> bool {
+        let verbosity = self.script_config.evm_opts.verbosity;
+        self.args.debug ||
+            self.args.trace_format != TraceFormat::Pretty ||
+            (!self.args.json && (!self.execution_result.success || verbosity > 3))
+    }
+
+    /// Whether the execution produced transactions, which are recorded in the broadcast sequence
+    /// along with the functions and arguments they call.
+    fn records_transactions(&self) -> bool {
+        self.execution_result.transactions.as_ref().map_or(false, |txs| !txs.is_empty())
+    }
+
     /// Collects the return values from the execution result.
     fn get_returns(&self) -> Result<HashMap<String, NestedValue>> {
         let mut returns = HashMap::new();
@@ -454,8 +584,29 @@ impl PreSimulationState {
             shell::println(String::new())?;
         }
 
+        if self.args.trace_format != TraceFormat::Pretty {
+            let traces = result.traces.iter().map(|(kind, trace)| {
+                let name = match kind {
+                    TraceKind::Deployment => "deployment",
+                    TraceKind::Setup => "setup",
+                    TraceKind::Execution => "execution",
+                };
+                (name.to_string(), trace)
+            });
+            let path = export_traces(
+                traces,
+                decoder,
+                self.args.trace_format,
+                &self.script_config.config,
+                &self.build_data.build_data.target.name,
+                "execution",
+            )
+            .await?;
+            shell::println(format!("Traces saved to: {}\n", path.display()))?;
+        }
+
         if result.success {
-            shell::println(format!("{}", Paint::green("Script ran successfully.")))?;
+            shell::println(format!("{}", Paint::green(tr!("Script ran successfully."))))?;
         }
 
         if self.script_config.evm_opts.fork_url.is_none() {
@@ -491,14 +642,7 @@ impl PreSimulationState {
             }
         }
 
-        let console_logs = decode_console_logs(&result.logs);
-        if !console_logs.is_empty() {
-            shell::println("\n== Logs ==")?;
-            for log in console_logs {
-                shell::println(format!("  {log}"))?;
-            }
-        }
-
+        // the console logs were already printed while the script was executing
         if !result.success {
             return Err(eyre::eyre!(
                 "script failed: {}",
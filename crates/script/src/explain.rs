@@ -0,0 +1,291 @@
+use crate::{build::PreprocessedState, ScriptArgs};
+use eyre::Result;
+use foundry_cli::utils::redact;
+use foundry_config::{
+    figment::{value::Dict, Figment},
+    providers::ChainProfileProvider,
+    Chain, Config,
+};
+use std::env;
+use yansi::Paint;
+
+/// Name of the metadata of the provider holding the default values, see `impl Provider for
+/// Config`.
+const DEFAULTS_PROVIDER: &str = "Foundry Config";
+
+/// A resolved setting and where its value comes from.
+struct Setting {
+    name: &'static str,
+    value: String,
+    source: String,
+}
+
+impl PreprocessedState {
+    /// Prints the configuration the script would run with and the source of each value.
+    pub fn explain_config(&self) -> Result<()> {
+        let Self { args, script_config, .. } = self;
+        let config = &script_config.config;
+        let evm_opts = &script_config.evm_opts;
+
+        let chain = if config.offline {
+            config.chain
+        } else {
+            config.chain.or_else(|| evm_opts.get_remote_chain_id())
+        };
+        let chain_profile =
+            chain.map(|chain| ChainProfileProvider::new(config.get_config_path(), chain));
+        let overrides = chain_profile.as_ref().map(|p| p.overrides()).transpose()?.flatten();
+        let figment = match chain_profile {
+            Some(chain_profile) => args.figment_with_chain_profile(chain_profile),
+            None => Figment::from(args),
+        };
+        let sources = Sources { figment: &figment, chain, overrides: overrides.as_ref() };
+
+        let mut settings = vec![
+            Setting {
+                name: "profile",
+                value: Config::selected_profile().to_string(),
+                source: env_source("FOUNDRY_PROFILE"),
+            },
+            Setting {
+                name: "chain",
+                value: chain.map_or_else(|| "unknown".to_string(), |chain| chain.to_string()),
+                source: if config.chain.is_some() {
+                    sources.config("chain")
+                } else {
+                    "RPC url".to_string()
+                },
+            },
+            Setting {
+                name: "rpc_url",
+                value: evm_opts.fork_url.as_deref().map_or_else(|| "none".to_string(), redact_url),
+                source: sources.config("eth_rpc_url"),
+            },
+            Setting {
+                name: "sender",
+                value: evm_opts.sender.to_string(),
+                source: if args.evm_opts.sender.is_none() &&
+                    evm_opts.sender != Config::DEFAULT_SENDER &&
+                    args.wallets.private_keys()?.map_or(false, |keys| keys.len() == 1)
+                {
+                    "private key".to_string()
+                } else {
+                    sources.config("sender")
+                },
+            },
+            Setting {
+                name: "libraries",
+                value: if config.libraries.is_empty() {
+                    "none".to_string()
+                } else {
+                    config.libraries.join(", ")
+                },
+                source: sources.config("libraries"),
+            },
+            Setting {
+                name: "verifier",
+                value: args.verifier.verifier().to_string(),
+                source: sources.arg("verifier", args.verifier.verifier.is_none(), None),
+            },
+            Setting {
+                name: "verifier_url",
+                value: args.verifier.verifier_url.clone().unwrap_or_else(|| "none".to_string()),
+                source: sources.arg(
+                    "verifier_url",
+                    args.verifier.verifier_url.is_none(),
+                    Some("VERIFIER_URL"),
+                ),
+            },
+        ];
+
+        if let Some(etherscan) = config.get_etherscan_config_with_chain(chain).ok().flatten() {
+            let source = sources.config(if config.etherscan.is_empty() {
+                "etherscan_api_key"
+            } else {
+                "etherscan"
+            });
+            settings.extend([
+                Setting { name: "etherscan_url", value: etherscan.api_url, source: source.clone() },
+                Setting { name: "etherscan_api_key", value: redact(&etherscan.key), source },
+            ]);
+        }
+
+        settings.extend([
+            Setting {
+                name: "gas_limit",
+                value: config.gas_limit.0.to_string(),
+                source: sources.config("gas_limit"),
+            },
+            Setting {
+                name: "gas_price",
+                value: args
+                    .with_gas_price
+                    .map_or_else(|| "estimated".to_string(), |price| price.to_string()),
+                source: sources.arg(
+                    "with_gas_price",
+                    args.with_gas_price.is_none(),
+                    Some("ETH_GAS_PRICE"),
+                ),
+            },
+            Setting {
+                name: "priority_gas_price",
+                value: args
+                    .priority_gas_price
+                    .map_or_else(|| "estimated".to_string(), |price| price.to_string()),
+                source: sources.arg(
+                    "priority_gas_price",
+                    args.priority_gas_price.is_none(),
+                    Some("ETH_PRIORITY_GAS_PRICE"),
+                ),
+            },
+            Setting {
+                name: "gas_estimate_multiplier",
+                value: format!("{}%", args.gas_estimate_multiplier()),
+                source: sources.arg(
+                    "gas_estimate_multiplier",
+                    args.gas_estimate_multiplier.is_none(),
+                    None,
+                ),
+            },
+            Setting {
+                name: "legacy",
+                value: args.legacy.to_string(),
+                source: sources.arg("legacy", !args.legacy, None),
+            },
+            Setting {
+                name: "fee_currency",
+                value: args
+                    .fee_currency
+                    .map_or_else(|| "native".to_string(), |token| token.to_string()),
+                source: sources.arg("fee_currency", args.fee_currency.is_none(), None),
+            },
+            Setting {
+                name: "slow",
+                value: args.slow.to_string(),
+                source: sources.arg("slow", !args.slow, None),
+            },
+            Setting {
+                name: "wallets",
+                value: wallet_backends(args)?,
+                source: "command line".to_string(),
+            },
+        ]);
+
+        println!("{}", Paint::yellow("Effective configuration:").bold());
+        let width = settings.iter().map(|setting| setting.name.len()).max().unwrap_or_default();
+        for Setting { name, value, source } in settings {
+            let source = Paint::new(format!("[{source}]")).dimmed();
+            println!("  {name:<width$}  {value} {source}");
+        }
+
+        Ok(())
+    }
+}
+
+/// Looks up where the values of the figment and the script arguments come from.
+struct Sources<'a> {
+    figment: &'a Figment,
+    chain: Option<Chain>,
+    overrides: Option<&'a Dict>,
+}
+
+impl Sources<'_> {
+    /// Returns the source of a [Config] key, as recorded by the figment.
+    fn config(&self, key: &str) -> String {
+        match self.figment.find_metadata(key) {
+            Some(metadata) if metadata.name != DEFAULTS_PROVIDER => match &metadata.source {
+                Some(source) => format!("{}: {source}", metadata.name),
+                None => metadata.name.to_string(),
+            },
+            _ => "default".to_string(),
+        }
+    }
+
+    /// Returns the source of a script argument that can also be set by the chain profile or by
+    /// the environment variable `env`.
+    fn arg(&self, key: &str, is_default: bool, env: Option<&str>) -> String {
+        if is_default {
+            return "default".to_string()
+        }
+        if self.overrides.map_or(false, |overrides| overrides.contains_key(key)) {
+            if let Some(chain) = self.chain {
+                return format!("chain profile `{chain}`")
+            }
+        }
+        match env {
+            Some(env) if env::var_os(env).is_some() => env_source(env),
+            _ => "command line".to_string(),
+        }
+    }
+}
+
+fn env_source(key: &str) -> String {
+    if env::var_os(key).is_some() {
+        format!("`{key}` environment variable")
+    } else {
+        "default".to_string()
+    }
+}
+
+/// Lists the wallet backends provided on the command line.
+fn wallet_backends(args: &ScriptArgs) -> Result<String> {
+    let wallets = &args.wallets;
+    let mut backends = Vec::new();
+    if args.unlocked {
+        backends.push("unlocked".to_string());
+    }
+    if let Some(keys) = wallets.private_keys()? {
+        backends.push(format!("{} private key(s)", keys.len()));
+    }
+    if let Some(mnemonics) = &wallets.mnemonics {
+        backends.push(format!("{} mnemonic(s)", mnemonics.len()));
+    }
+    let keystores = wallets.keystore_paths.as_ref().map_or(0, Vec::len) +
+        wallets.keystore_account_names.as_ref().map_or(0, Vec::len);
+    if keystores > 0 {
+        backends.push(format!("{keystores} keystore(s)"));
+    }
+    if wallets.interactives > 0 {
+        backends.push(format!("{} interactive", wallets.interactives));
+    }
+    if wallets.ledger {
+        backends.push("ledger".to_string());
+    }
+    if wallets.trezor {
+        backends.push("trezor".to_string());
+    }
+    if wallets.aws {
+        backends.push("aws".to_string());
+    }
+    if backends.is_empty() {
+        backends.push("none".to_string());
+    }
+    Ok(backends.join(", "))
+}
+
+/// Strips everything after the host from an RPC url, since the path or query often contains an
+/// API key.
+pub(crate) fn redact_url(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else { return redact(url) };
+    match rest.find(&['/', '?'][..]) {
+        Some(end) if end + 1 < rest.len() => {
+            let (host, path) = rest.split_at(end + 1);
+            format!("{scheme}://{host}{}", redact(path))
+        }
+        _ => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_rpc_urls() {
+        assert_eq!(redact_url("http://localhost:8545"), "http://localhost:8545");
+        assert_eq!(
+            redact_url("https://eth-mainnet.g.alchemy.com/v2/abcdef123"),
+            "https://eth-mainnet.g.alchemy.com/********"
+        );
+    }
+}
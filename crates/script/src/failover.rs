@@ -0,0 +1,209 @@
+use ethers_providers::{is_local_endpoint, Middleware};
+use eyre::{bail, Result};
+use foundry_common::{
+    provider::ethers::{
+        estimate_block_time, get_http_provider, poll_interval, try_get_http_provider, RetryProvider,
+    },
+    shell,
+};
+use futures::future::join_all;
+use std::{
+    future::Future,
+    io::ErrorKind,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use yansi::Paint;
+
+/// How many blocks an endpoint may lag behind the most up-to-date one before it's deprioritized.
+const MAX_BLOCK_LAG: u64 = 5;
+
+/// How long to wait for an endpoint to respond to the health check.
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Health of a single RPC endpoint.
+struct EndpointHealth {
+    url: String,
+    latency: Duration,
+    block: u64,
+}
+
+/// The RPC endpoints of a chain, ordered by preference, and the provider of the one in use.
+///
+/// If more than one endpoint is configured for the chain (see `[rpc_endpoints]`), they're
+/// health-checked on creation, and the next one is used when sending to the current one fails.
+pub struct FailoverProvider {
+    urls: Vec<String>,
+    current: usize,
+    provider: Arc<RetryProvider>,
+    /// The interval the providers poll at, from the block time of the chain.
+    interval: Option<Duration>,
+}
+
+impl FailoverProvider {
+    /// Creates a new provider for the given endpoints of `chain`.
+    pub async fn new(urls: Vec<String>, chain: u64) -> Result<Self> {
+        let urls = if urls.len() > 1 { rank_endpoints(urls, chain).await? } else { urls };
+        let Some(url) = urls.first() else { bail!("No RPC endpoint for chain {chain}") };
+        let mut provider = try_get_http_provider(url)?;
+        // Receipts are polled more often on chains with short block times, and less often on
+        // slower ones, than the default interval.
+        let interval = if is_local_endpoint(url) {
+            None
+        } else {
+            estimate_block_time(&provider).await.map(poll_interval)
+        };
+        if let Some(interval) = interval {
+            provider = provider.interval(interval);
+        }
+        Ok(Self { urls, current: 0, provider: Arc::new(provider), interval })
+    }
+
+    /// Returns the provider of the endpoint in use.
+    pub fn provider(&self) -> Arc<RetryProvider> {
+        self.provider.clone()
+    }
+
+    /// Returns the url of the endpoint in use.
+    pub fn url(&self) -> &str {
+        &self.urls[self.current]
+    }
+
+    /// Switches to the next endpoint. Returns `false` if there is none left.
+    pub fn fail_over(&mut self) -> bool {
+        if self.current + 1 >= self.urls.len() {
+            return false
+        }
+        self.current += 1;
+        let mut provider = get_http_provider(self.url());
+        if let Some(interval) = self.interval {
+            provider = provider.interval(interval);
+        }
+        self.provider = Arc::new(provider);
+        let _ = shell::println(Paint::yellow(format!(
+            "Failing over to RPC endpoint #{} of {}.",
+            self.current + 1,
+            self.urls.len()
+        )));
+        true
+    }
+
+    /// Retries an operation that failed with `err` on the endpoint `failed_url`, failing over to
+    /// the next endpoints until it succeeds, as long as it fails to reach them.
+    ///
+    /// Other errors, e.g. a transaction rejected by the node, are returned as is, as another
+    /// endpoint would reject it as well. If the endpoint in use already changed since the
+    /// operation was started, it's retried against the current endpoint first. On success, the
+    /// endpoint in use is the one the operation succeeded on.
+    pub async fn retry<T, F, Fut>(&mut self, failed_url: &str, err: eyre::Report, f: F) -> Result<T>
+    where
+        F: Fn(Arc<RetryProvider>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut err = err;
+        if !is_connection_error(&err) || (self.url() == failed_url && !self.fail_over()) {
+            return Err(err)
+        }
+        loop {
+            match f(self.provider()).await {
+                Ok(value) => return Ok(value),
+                Err(e) => err = e,
+            }
+            if !is_connection_error(&err) || !self.fail_over() {
+                return Err(err)
+            }
+        }
+    }
+}
+
+/// Messages of errors which mean that the endpoint couldn't be reached or didn't respond in time.
+const CONNECTION_ERROR_MESSAGES: [&str; 6] = [
+    "error sending request",
+    "error trying to connect",
+    "connection refused",
+    "connection reset",
+    "timed out",
+    "timeout",
+];
+
+/// Whether the error means that the endpoint couldn't be reached or didn't respond in time, as
+/// opposed to an error returned by the node, which any endpoint would return.
+fn is_connection_error(err: &eyre::Report) -> bool {
+    for cause in err.chain() {
+        if let Some(err) = cause.downcast_ref::<reqwest::Error>() {
+            return err.is_connect() || err.is_timeout()
+        }
+        if let Some(err) = cause.downcast_ref::<std::io::Error>() {
+            return matches!(
+                err.kind(),
+                ErrorKind::ConnectionRefused |
+                    ErrorKind::ConnectionReset |
+                    ErrorKind::ConnectionAborted |
+                    ErrorKind::TimedOut
+            )
+        }
+    }
+    // The transport errors of the providers are mostly transparent, so their causes are lost.
+    let message = format!("{err:#}").to_lowercase();
+    CONNECTION_ERROR_MESSAGES.iter().any(|connection| message.contains(connection))
+}
+
+/// Health-checks the endpoints and orders the healthy ones by preference: endpoints in sync with
+/// the chain tip first, then by latency.
+async fn rank_endpoints(urls: Vec<String>, chain: u64) -> Result<Vec<String>> {
+    let checks = join_all(urls.iter().map(|url| check_endpoint(url, chain))).await;
+
+    let mut healthy = Vec::with_capacity(urls.len());
+    for (i, check) in checks.into_iter().enumerate() {
+        match check {
+            Ok(health) => healthy.push(health),
+            // Don't print the url, since it may contain an API key.
+            Err(err) => shell::println(Paint::yellow(format!(
+                "RPC endpoint #{} for chain {chain} is unhealthy: {err}",
+                i + 1
+            )))?,
+        }
+    }
+
+    let Some(tip) = healthy.iter().map(|health| health.block).max() else {
+        bail!("None of the {} RPC endpoints for chain {chain} are healthy.", urls.len())
+    };
+    healthy.sort_by_key(|health| (tip - health.block > MAX_BLOCK_LAG, health.latency));
+
+    Ok(healthy.into_iter().map(|health| health.url).collect())
+}
+
+async fn check_endpoint(url: &str, chain: u64) -> Result<EndpointHealth> {
+    let provider = try_get_http_provider(url)?;
+
+    let start = Instant::now();
+    let (chain_id, block) = tokio::time::timeout(HEALTH_CHECK_TIMEOUT, async {
+        futures::try_join!(provider.get_chainid(), provider.get_block_number())
+    })
+    .await??;
+    let latency = start.elapsed();
+
+    if chain_id.as_u64() != chain {
+        bail!("expected chain {chain}, but the endpoint is on chain {chain_id}")
+    }
+
+    Ok(EndpointHealth { url: url.to_string(), latency, block: block.as_u64() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_fails_over_on_connection_errors() {
+        let refused = std::io::Error::from(ErrorKind::ConnectionRefused);
+        assert!(is_connection_error(&eyre::Report::new(refused)));
+        assert!(is_connection_error(&eyre::eyre!(
+            "(code: -32603, message: error sending request for url (http://localhost:8545/))"
+        )));
+
+        let invalid = std::io::Error::from(ErrorKind::InvalidData);
+        assert!(!is_connection_error(&eyre::Report::new(invalid)));
+        assert!(!is_connection_error(&eyre::eyre!("(code: -32000, message: nonce too low)")));
+    }
+}
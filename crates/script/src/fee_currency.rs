@@ -0,0 +1,145 @@
+//! CIP-64 transactions, which pay their fees in an ERC-20 fee currency on Celo-style chains.
+
+use alloy_primitives::{keccak256, Address, TxHash};
+use ethers_core::{
+    types::{
+        transaction::eip2718::TypedTransaction, Bytes, Eip1559TransactionRequest, NameOrAddress,
+        Signature, H256, U256,
+    },
+    utils::rlp::RlpStream,
+};
+use ethers_providers::Middleware;
+use eyre::{Result, WrapErr};
+use foundry_common::{
+    provider::ethers::RetryProvider,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_wallets::WalletSigner;
+
+/// The type of CIP-64 transactions.
+pub const CIP64_TX_TYPE: u8 = 0x7b;
+
+/// The gas the chain charges on top of the execution for debiting and crediting the fee currency,
+/// which the simulation doesn't account for.
+pub const FEE_CURRENCY_INTRINSIC_GAS: u64 = 50_000;
+
+/// Returns the EIP-1559 transaction which a CIP-64 transaction extends with its fee currency.
+pub fn eip1559(tx: &TypedTransaction) -> Result<&Eip1559TransactionRequest> {
+    match tx {
+        TypedTransaction::Eip1559(tx) => Ok(tx),
+        _ => eyre::bail!(
+            "Paying fees in a fee currency requires EIP-1559 transactions, remove `--legacy`"
+        ),
+    }
+}
+
+/// Encodes a CIP-64 transaction, with its signature if any.
+pub fn encode(
+    tx: &Eip1559TransactionRequest,
+    fee_currency: Address,
+    signature: Option<&Signature>,
+) -> Bytes {
+    let mut rlp = RlpStream::new();
+    rlp.begin_list(if signature.is_some() { 13 } else { 10 });
+    rlp.append(&tx.chain_id.unwrap_or_default());
+    rlp.append(&tx.nonce.unwrap_or_default());
+    rlp.append(&tx.max_priority_fee_per_gas.unwrap_or_default());
+    rlp.append(&tx.max_fee_per_gas.unwrap_or_default());
+    rlp.append(&tx.gas.unwrap_or_default());
+    match &tx.to {
+        Some(NameOrAddress::Address(to)) => rlp.append(to),
+        _ => rlp.append(&""),
+    };
+    rlp.append(&tx.value.unwrap_or_default());
+    rlp.append(&tx.data.clone().unwrap_or_default());
+    rlp.append(&tx.access_list);
+    rlp.append(&fee_currency.to_ethers());
+    if let Some(signature) = signature {
+        // The signature holds the y parity instead of `v`.
+        let y_parity = if signature.v >= 27 { signature.v - 27 } else { signature.v };
+        rlp.append(&y_parity);
+        rlp.append(&signature.r);
+        rlp.append(&signature.s);
+    }
+    [&[CIP64_TX_TYPE][..], rlp.out().as_ref()].concat().into()
+}
+
+/// Signs a CIP-64 transaction with `signer` and sends it.
+pub async fn send_raw(
+    provider: &RetryProvider,
+    signer: &WalletSigner,
+    tx: &Eip1559TransactionRequest,
+    fee_currency: Address,
+) -> Result<TxHash> {
+    let hash = keccak256(encode(tx, fee_currency, None));
+    let signature = signer
+        .sign_hash(&hash)
+        .await
+        .wrap_err("Failed to sign transaction, fee currencies require a local wallet")?;
+    let pending = provider.send_raw_transaction(encode(tx, fee_currency, Some(&signature))).await?;
+    Ok(pending.tx_hash().to_alloy())
+}
+
+/// Sends a CIP-64 transaction from an account unlocked on the node.
+pub async fn send_unlocked(
+    provider: &RetryProvider,
+    tx: &Eip1559TransactionRequest,
+    fee_currency: Address,
+) -> Result<TxHash> {
+    let mut request = serde_json::to_value(tx)?;
+    request["type"] = format!("{CIP64_TX_TYPE:#x}").into();
+    request["feeCurrency"] = fee_currency.to_string().into();
+    let hash: H256 = provider.request("eth_sendTransaction", [request]).await?;
+    Ok(hash.to_alloy())
+}
+
+/// Returns the gas price in units of the fee currency.
+pub async fn gas_price(provider: &RetryProvider, fee_currency: Address) -> Result<U256> {
+    provider
+        .request("eth_gasPrice", [fee_currency.to_ethers()])
+        .await
+        .wrap_err_with(|| format!("Failed to get the gas price in fee currency {fee_currency}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use ethers_core::utils::rlp::Rlp;
+    use ethers_signers::{LocalWallet, Signer};
+
+    #[test]
+    fn encodes_cip64_transactions() {
+        let fee_currency = address!("765DE816845861e75A25fCA122bb6898B8B1282a");
+        let wallet = "0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"
+            .parse::<LocalWallet>()
+            .unwrap();
+        let tx = Eip1559TransactionRequest::new()
+            .from(wallet.address())
+            .to(Address::with_last_byte(1).to_ethers())
+            .value(1u64)
+            .nonce(2u64)
+            .gas(21_000u64)
+            .max_fee_per_gas(10u64)
+            .max_priority_fee_per_gas(1u64)
+            .chain_id(42220u64);
+
+        let unsigned = encode(&tx, fee_currency, None);
+        assert_eq!(unsigned[0], CIP64_TX_TYPE);
+        let fields = Rlp::new(&unsigned[1..]);
+        assert_eq!(fields.item_count().unwrap(), 10);
+        assert_eq!(
+            fields.val_at::<ethers_core::types::Address>(9).unwrap(),
+            fee_currency.to_ethers()
+        );
+
+        let hash = keccak256(&unsigned);
+        let signature = wallet.sign_hash(H256::from(hash.0)).unwrap();
+        assert_eq!(signature.recover(H256::from(hash.0)).unwrap(), wallet.address());
+
+        let signed = encode(&tx, fee_currency, Some(&signature));
+        let fields = Rlp::new(&signed[1..]);
+        assert_eq!(fields.item_count().unwrap(), 13);
+        assert!(fields.val_at::<u64>(10).unwrap() <= 1);
+    }
+}
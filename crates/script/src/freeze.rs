@@ -0,0 +1,198 @@
+use alloy_primitives::{keccak256, B256};
+use chrono::{DateTime, Datelike, Timelike};
+use eyre::Result;
+use std::{collections::BTreeSet, fmt, path::PathBuf, str::FromStr};
+
+/// A cron expression, `minute hour day-of-month month day-of-week`, evaluated in UTC.
+///
+/// Fields are `*`, values, ranges `a-b`, steps `*/n` or `a-b/n` and lists of those separated by
+/// `,`. Months and days of the week can also be written as `jan` or `sat`, and Sunday as 0 or 7.
+/// As with cron, when both the day of the month and the day of the week are restricted, a day
+/// matching either is matched.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronExpr {
+    source: String,
+    minutes: BTreeSet<u32>,
+    hours: BTreeSet<u32>,
+    days: BTreeSet<u32>,
+    months: BTreeSet<u32>,
+    weekdays: BTreeSet<u32>,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+const MONTHS: [&str; 12] =
+    ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Parses a field of a cron expression, with the names of its values starting at `min`.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<BTreeSet<u32>> {
+    let value = |value: &str| -> Result<u32> {
+        let lower = value.to_lowercase();
+        let value = match names.iter().position(|name| *name == lower) {
+            Some(i) => i as u32 + min,
+            None => value.parse().map_err(|_| eyre::eyre!("invalid value `{value}`"))?,
+        };
+        if value < min || value > max {
+            eyre::bail!("value {value} is out of range {min}-{max}")
+        }
+        Ok(value)
+    };
+
+    let mut values = BTreeSet::new();
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step.parse::<u32>().ok().filter(|step| *step > 0);
+                (range, step.ok_or_else(|| eyre::eyre!("invalid step in `{part}`"))?)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (value(start)?, value(end)?)
+        } else {
+            let start = value(range)?;
+            (start, if part.contains('/') { max } else { start })
+        };
+        if start > end {
+            eyre::bail!("invalid range `{range}`")
+        }
+        values.extend((start..=end).step_by(step as usize));
+    }
+    Ok(values)
+}
+
+impl FromStr for CronExpr {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let fields = s.split_whitespace().collect::<Vec<_>>();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            eyre::bail!("invalid cron expression `{s}`, expected 5 fields")
+        };
+        let parse = |field: &str, min, max, names: &[&str]| {
+            parse_field(field, min, max, names)
+                .map_err(|err| eyre::eyre!("invalid cron expression `{s}`: {err}"))
+        };
+        let weekday_values =
+            parse(weekdays, 0, 7, &WEEKDAYS)?.into_iter().map(|weekday| weekday % 7).collect();
+        Ok(Self {
+            source: s.to_string(),
+            minutes: parse(minutes, 0, 59, &[])?,
+            hours: parse(hours, 0, 23, &[])?,
+            days: parse(days, 1, 31, &[])?,
+            months: parse(months, 1, 12, &MONTHS)?,
+            weekdays: weekday_values,
+            any_day: days == "*",
+            any_weekday: weekdays == "*",
+        })
+    }
+}
+
+impl fmt::Display for CronExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl CronExpr {
+    /// Returns whether the minute of the unix `timestamp` matches the expression.
+    pub fn matches(&self, timestamp: u64) -> bool {
+        let Some(time) =
+            i64::try_from(timestamp).ok().and_then(|ts| DateTime::from_timestamp(ts, 0))
+        else {
+            return false
+        };
+        let (day, weekday) = (time.day(), time.weekday().num_days_from_sunday());
+
+        let day_matches = match (self.any_day, self.any_weekday) {
+            (false, false) => self.days.contains(&day) || self.weekdays.contains(&weekday),
+            _ => self.days.contains(&day) && self.weekdays.contains(&weekday),
+        };
+        self.minutes.contains(&time.minute()) &&
+            self.hours.contains(&time.hour()) &&
+            self.months.contains(&time.month()) &&
+            day_matches
+    }
+}
+
+/// A window of the policy during which broadcasting is frozen.
+#[derive(Clone, Debug)]
+pub struct FreezeWindow {
+    pub name: String,
+    pub cron: CronExpr,
+    /// The ids of the chains the window applies to, or all of them.
+    pub chains: Option<BTreeSet<u64>>,
+    pub reason: Option<String>,
+    /// The hash of the token which overrides the windows of the policy file.
+    pub override_hash: Option<B256>,
+    /// The policy file the window was defined in.
+    pub file: PathBuf,
+}
+
+impl FreezeWindow {
+    /// Returns whether broadcasting to `chain` at `timestamp` is frozen by the window, unless
+    /// `token` overrides it.
+    pub fn freezes(&self, chain: u64, timestamp: u64, token: Option<&str>) -> bool {
+        if self.chains.as_ref().map_or(false, |chains| !chains.contains(&chain)) {
+            return false
+        }
+        if let (Some(hash), Some(token)) = (self.override_hash, token) {
+            if keccak256(token) == hash {
+                return false
+            }
+        }
+        self.cron.matches(timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Saturday 2024-03-16 14:30:00 UTC.
+    const SATURDAY: u64 = 1_710_599_400;
+
+    #[test]
+    fn matches_cron_expressions() {
+        let weekend: CronExpr = "* * * * sat,sun".parse().unwrap();
+        assert!(weekend.matches(SATURDAY));
+        assert!(weekend.matches(SATURDAY + 86_400));
+        assert!(!weekend.matches(SATURDAY + 2 * 86_400));
+        assert!("* * * * 6,7".parse::<CronExpr>().unwrap().matches(SATURDAY + 86_400));
+
+        let afternoon: CronExpr = "*/15 14-16 * * 1-5".parse().unwrap();
+        assert!(!afternoon.matches(SATURDAY));
+        assert!(afternoon.matches(SATURDAY + 2 * 86_400));
+        assert!(!afternoon.matches(SATURDAY + 2 * 86_400 + 60));
+
+        // The 16th of the month or any Monday.
+        let unlock: CronExpr = "* * 16 mar 1".parse().unwrap();
+        assert!(unlock.matches(SATURDAY));
+        assert!(unlock.matches(SATURDAY + 2 * 86_400));
+        assert!(!unlock.matches(SATURDAY + 86_400));
+
+        assert!("* * * *".parse::<CronExpr>().is_err());
+        assert!("60 * * * *".parse::<CronExpr>().is_err());
+        assert!("* * * * */0".parse::<CronExpr>().is_err());
+        assert!("* 5-1 * * *".parse::<CronExpr>().is_err());
+    }
+
+    #[test]
+    fn override_token_lifts_the_window() {
+        let window = FreezeWindow {
+            name: "weekend".to_string(),
+            cron: "* * * * 6,0".parse().unwrap(),
+            chains: Some(BTreeSet::from([1])),
+            reason: None,
+            override_hash: Some(keccak256("incident-42")),
+            file: PathBuf::from("policy.toml"),
+        };
+        assert!(window.freezes(1, SATURDAY, None));
+        assert!(window.freezes(1, SATURDAY, Some("wrong")));
+        assert!(!window.freezes(1, SATURDAY, Some("incident-42")));
+        assert!(!window.freezes(10, SATURDAY, None));
+    }
+}
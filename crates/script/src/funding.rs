@@ -0,0 +1,214 @@
+use alloy_primitives::{utils::format_units, Address, U256};
+use ethers_core::types::{transaction::eip2718::TypedTransaction, BlockNumber, TransactionRequest};
+use ethers_providers::Middleware;
+use ethers_signers::Signer;
+use eyre::{Result, WrapErr};
+use foundry_common::{
+    provider::ethers::RetryProvider,
+    shell,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_config::NamedChain;
+use foundry_wallets::{utils::create_private_key_signer, WalletSigner};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Parts of the names of the chains `--fund-senders` is allowed on.
+const TESTNET_MARKERS: [&str; 11] = [
+    "testnet",
+    "goerli",
+    "sepolia",
+    "holesky",
+    "mumbai",
+    "amoy",
+    "fuji",
+    "chapel",
+    "alfajores",
+    "dev",
+    "anvil",
+];
+
+/// How long to wait for a faucet to send the requested funds.
+const FAUCET_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How often the balances are polled while waiting for a faucet.
+const FAUCET_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// The gas of a plain transfer of ether.
+pub const TRANSFER_GAS: u64 = 21_000;
+
+/// Returns whether `chain` is a known testnet or development chain.
+pub fn is_testnet(chain: u64) -> bool {
+    NamedChain::try_from(chain).map_or(false, |chain| {
+        let name = chain.to_string().to_lowercase();
+        TESTNET_MARKERS.iter().any(|marker| name.contains(marker))
+    })
+}
+
+/// Fails if any of the chains isn't a known testnet.
+pub fn check_testnets(chains: impl IntoIterator<Item = u64>) -> Result<()> {
+    for chain in chains {
+        if !is_testnet(chain) {
+            eyre::bail!("--fund-senders is only allowed on testnets, chain {chain} isn't one")
+        }
+    }
+    Ok(())
+}
+
+/// Where the senders are funded from with `--fund-senders`.
+pub enum FundingSource {
+    /// An HTTP faucet, which is sent a JSON [FaucetRequest] per sender.
+    Faucet(String),
+    /// A funded account, which transfers the missing amounts.
+    Key(WalletSigner),
+}
+
+/// The request sent to a faucet.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FaucetRequest {
+    pub address: Address,
+    pub chain_id: u64,
+    /// The requested amount, in wei.
+    pub amount: String,
+}
+
+impl FundingSource {
+    pub fn new(faucet_url: Option<&str>, funding_key: Option<&str>) -> Result<Self> {
+        match (faucet_url, funding_key) {
+            (_, Some(key)) => Ok(Self::Key(create_private_key_signer(key)?)),
+            (Some(url), None) => Ok(Self::Faucet(url.to_string())),
+            (None, None) => eyre::bail!("--fund-senders requires --faucet-url or --funding-key"),
+        }
+    }
+
+    /// Tops up the balances of `senders` on `chain` to `amount`.
+    pub async fn fund(
+        &self,
+        provider: &RetryProvider,
+        chain: u64,
+        senders: &[Address],
+        amount: U256,
+    ) -> Result<()> {
+        let mut missing = Vec::new();
+        for sender in senders {
+            let balance = provider.get_balance(sender.to_ethers(), None).await?.to_alloy();
+            if balance < amount {
+                missing.push((*sender, amount - balance));
+            }
+        }
+        if missing.is_empty() {
+            return Ok(())
+        }
+
+        for (sender, deficit) in &missing {
+            shell::println(format!(
+                "Funding {sender} with {} ETH on chain {chain}",
+                format_units(*deficit, 18).unwrap_or_else(|_| deficit.to_string())
+            ))?;
+            match self {
+                Self::Faucet(url) => request_faucet(url, chain, *sender, *deficit).await?,
+                Self::Key(funder) => {
+                    let gas_price = provider.get_gas_price().await?.to_alloy();
+                    transfer(provider, funder, chain, *sender, *deficit, gas_price).await?
+                }
+            }
+        }
+
+        if let Self::Faucet(_) = self {
+            let start = Instant::now();
+            for (sender, _) in &missing {
+                loop {
+                    let balance = provider.get_balance(sender.to_ethers(), None).await?.to_alloy();
+                    if balance >= amount {
+                        break
+                    }
+                    if start.elapsed() > FAUCET_TIMEOUT {
+                        eyre::bail!(
+                            "The faucet didn't fund {sender} within {}s",
+                            FAUCET_TIMEOUT.as_secs()
+                        )
+                    }
+                    tokio::time::sleep(FAUCET_POLL_INTERVAL).await;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn request_faucet(url: &str, chain: u64, address: Address, amount: U256) -> Result<()> {
+    let request = FaucetRequest { address, chain_id: chain, amount: amount.to_string() };
+    reqwest::Client::new()
+        .post(url)
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()
+        .wrap_err_with(|| format!("The faucet refused to fund {address}"))?;
+    Ok(())
+}
+
+/// Transfers `value` from `funder` to `to` at `gas_price`, and waits for the receipt.
+pub async fn transfer(
+    provider: &RetryProvider,
+    funder: &WalletSigner,
+    chain: u64,
+    to: Address,
+    value: U256,
+    gas_price: U256,
+) -> Result<()> {
+    let from = funder.address();
+    let nonce = provider.get_transaction_count(from, Some(BlockNumber::Pending.into())).await?;
+    let tx: TypedTransaction = TransactionRequest::new()
+        .from(from)
+        .to(to.to_ethers())
+        .value(value.to_ethers())
+        .nonce(nonce)
+        .gas(TRANSFER_GAS)
+        .gas_price(gas_price.to_ethers())
+        .chain_id(chain)
+        .into();
+
+    let signature = funder.sign_transaction(&tx).await.wrap_err("Failed to sign transaction")?;
+    let receipt = provider
+        .send_raw_transaction(tx.rlp_signed(&signature))
+        .await?
+        .await?
+        .ok_or_else(|| eyre::eyre!("The funding transaction to {to} was dropped"))?;
+    if receipt.status != Some(1u64.into()) {
+        eyre::bail!("The funding transaction {:?} to {to} failed", receipt.transaction_hash)
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_testnets() {
+        assert!(is_testnet(11155111));
+        assert!(is_testnet(80001));
+        assert!(is_testnet(31337));
+        assert!(!is_testnet(1));
+        assert!(!is_testnet(137));
+        assert!(!is_testnet(123_456_789));
+        assert!(check_testnets([11155111, 31337]).is_ok());
+        assert!(check_testnets([11155111, 1]).is_err());
+    }
+
+    #[test]
+    fn requires_a_source() {
+        assert!(FundingSource::new(None, None).is_err());
+        assert!(matches!(
+            FundingSource::new(Some("https://faucet.example.com"), None).unwrap(),
+            FundingSource::Faucet(_)
+        ));
+        let key = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+        assert!(matches!(
+            FundingSource::new(Some("https://faucet.example.com"), Some(key)).unwrap(),
+            FundingSource::Key(_)
+        ));
+    }
+}
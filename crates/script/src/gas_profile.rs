@@ -0,0 +1,149 @@
+use comfy_table::{presets::ASCII_MARKDOWN, Attribute, Cell, Color, Table};
+use eyre::Result;
+use foundry_cli::utils::now;
+use foundry_common::{fs, get_contract_name};
+use foundry_evm::{
+    constants::{CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS},
+    traces::{CallTraceArena, CallTraceDecoder, DecodedCallData},
+};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// The gas used by the simulated transactions, by contract and function.
+#[derive(Debug, Default, Serialize)]
+pub struct GasProfile {
+    /// Contract name -> function signature -> gas usage
+    pub contracts: BTreeMap<String, BTreeMap<String, FunctionGas>>,
+}
+
+/// The gas used by the calls to a function.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionGas {
+    pub calls: u64,
+    /// The gas used by the calls, including their subcalls.
+    pub total_gas: u64,
+    /// The gas used by the calls themselves, excluding their subcalls.
+    pub self_gas: u64,
+    /// The gas used by the most expensive call.
+    pub max_gas: u64,
+}
+
+impl GasProfile {
+    /// Adds the calls of `arena` to the profile.
+    ///
+    /// Contract creations are reported as calls to their `constructor`, and calls to unknown
+    /// functions by their selector.
+    pub async fn analyze(&mut self, arena: &CallTraceArena, decoder: &CallTraceDecoder) {
+        decoder.prefetch_signatures(arena.nodes()).await;
+
+        let nodes = arena.nodes();
+        for node in nodes {
+            let trace = &node.trace;
+            if trace.address == CHEATCODE_ADDRESS || trace.address == HARDHAT_CONSOLE_ADDRESS {
+                continue
+            }
+
+            let decoded = decoder.decode_function(trace).await;
+            let contract = match (&decoded.contract, &decoded.label) {
+                (Some(contract), _) => get_contract_name(contract).to_string(),
+                (None, Some(label)) => label.clone(),
+                (None, None) => trace.address.to_string(),
+            };
+            let function = match decoded.func {
+                _ if trace.kind.is_any_create() => "constructor".to_string(),
+                Some(DecodedCallData { signature, .. }) => signature,
+                None if trace.data.len() < 4 => "fallback".to_string(),
+                None => hex::encode_prefixed(&trace.data[..4]),
+            };
+
+            let children_gas =
+                node.children.iter().map(|child| nodes[*child].trace.gas_used).sum::<u64>();
+            let gas = self.contracts.entry(contract).or_default().entry(function).or_default();
+            gas.calls += 1;
+            gas.total_gas += trace.gas_used;
+            gas.self_gas += trace.gas_used.saturating_sub(children_gas);
+            gas.max_gas = gas.max_gas.max(trace.gas_used);
+        }
+    }
+
+    /// Writes the profile as JSON to the `gas-profiles` directory of the project cache, and
+    /// returns the path of the file.
+    pub fn save(&self, cache_path: &Path, contract: &str) -> Result<PathBuf> {
+        let dir = cache_path.join("gas-profiles");
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{contract}-{}.json", now().as_secs()));
+        fs::write_json_file(&path, self)?;
+        Ok(path)
+    }
+
+    /// The functions of the profile, the ones using the most gas themselves first.
+    fn rows(&self) -> Vec<(&str, &str, &FunctionGas)> {
+        let mut rows = self
+            .contracts
+            .iter()
+            .flat_map(|(contract, functions)| {
+                functions
+                    .iter()
+                    .map(move |(function, gas)| (contract.as_str(), function.as_str(), gas))
+            })
+            .collect::<Vec<_>>();
+        rows.sort_by(|(_, _, a), (_, _, b)| b.self_gas.cmp(&a.self_gas));
+        rows
+    }
+}
+
+impl fmt::Display for GasProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.load_preset(ASCII_MARKDOWN);
+        table.set_header([
+            Cell::new("Contract").add_attribute(Attribute::Bold),
+            Cell::new("Function").add_attribute(Attribute::Bold),
+            Cell::new("# calls").add_attribute(Attribute::Bold),
+            Cell::new("self gas").add_attribute(Attribute::Bold).fg(Color::Red),
+            Cell::new("total gas").add_attribute(Attribute::Bold).fg(Color::Yellow),
+            Cell::new("max gas").add_attribute(Attribute::Bold).fg(Color::Yellow),
+        ]);
+        for (contract, function, gas) in self.rows() {
+            table.add_row([
+                Cell::new(contract).add_attribute(Attribute::Bold),
+                Cell::new(function),
+                Cell::new(gas.calls.to_string()),
+                Cell::new(gas.self_gas.to_string()).fg(Color::Red),
+                Cell::new(gas.total_gas.to_string()).fg(Color::Yellow),
+                Cell::new(gas.max_gas.to_string()).fg(Color::Yellow),
+            ]);
+        }
+        write!(f, "{table}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_rows_by_self_gas() {
+        let mut profile = GasProfile::default();
+        let gas = |self_gas| FunctionGas { calls: 1, total_gas: 100, self_gas, max_gas: 100 };
+        let token = profile.contracts.entry("Token".to_string()).or_default();
+        token.insert("constructor".to_string(), gas(10));
+        token.insert("mint(address,uint256)".to_string(), gas(60));
+        profile
+            .contracts
+            .entry("Vault".to_string())
+            .or_default()
+            .insert("init()".to_string(), gas(30));
+
+        let rows = profile.rows().into_iter().map(|(c, f, _)| (c, f)).collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [("Token", "mint(address,uint256)"), ("Vault", "init()"), ("Token", "constructor")]
+        );
+    }
+}
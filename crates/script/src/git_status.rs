@@ -0,0 +1,123 @@
+use eyre::Result;
+use foundry_cli::utils::Git;
+use foundry_common::shell;
+use foundry_config::Chain;
+use std::path::Path;
+use yansi::Paint;
+
+/// The number of changed files listed in the warning.
+const MAX_LISTED_FILES: usize = 10;
+
+/// The state of the git repository of the project when the script was compiled.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GitStatus {
+    /// The short hash of the checked out commit.
+    pub commit: String,
+    /// The files with uncommitted changes, including untracked files.
+    pub changed_files: Vec<String>,
+    /// Whether the commit is on a remote branch.
+    pub pushed: bool,
+}
+
+impl GitStatus {
+    /// Reads the status of the repository of `root`, or returns `None` if it's not in one.
+    pub fn read(root: &Path) -> Option<Self> {
+        let git = Git::new(root);
+        let commit = git.commit_hash(true, "HEAD").ok()?;
+        let changed_files = git.changed_files().unwrap_or_else(|err| {
+            warn!(%err, "failed to list the changed files");
+            Vec::new()
+        });
+        let pushed = git.is_pushed("HEAD").unwrap_or_else(|err| {
+            warn!(%err, "failed to check whether the commit is pushed");
+            false
+        });
+        Some(Self { commit, changed_files, pushed })
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        !self.changed_files.is_empty()
+    }
+
+    /// Returns why the artifacts can't be reproduced from the repository, if they can't.
+    fn issues(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+        if self.is_dirty() {
+            let mut files =
+                self.changed_files.iter().take(MAX_LISTED_FILES).cloned().collect::<Vec<_>>();
+            if self.changed_files.len() > MAX_LISTED_FILES {
+                files.push(format!("{} more", self.changed_files.len() - MAX_LISTED_FILES));
+            }
+            issues.push(format!(
+                "the working tree has {} uncommitted changes: {}",
+                self.changed_files.len(),
+                files.join(", ")
+            ));
+        }
+        if !self.pushed {
+            issues.push(format!("commit {} isn't pushed to a remote", self.commit));
+        }
+        issues
+    }
+}
+
+/// Warns if the artifacts broadcast to any of the production chains, the ones listed in
+/// `confirm_chain_ids`, were compiled from a dirty tree or from a commit which isn't pushed, as
+/// their verification and provenance can't be reproduced then.
+///
+/// Fails instead if the policy requires a clean tree.
+pub fn check_git_status(
+    status: Option<&GitStatus>,
+    chains: impl IntoIterator<Item = u64>,
+    confirm_chain_ids: &[u64],
+    require_clean: bool,
+) -> Result<()> {
+    let Some(status) = status else { return Ok(()) };
+    let mut production = chains
+        .into_iter()
+        .filter(|chain| confirm_chain_ids.contains(chain))
+        .map(|chain| Chain::from(chain).to_string())
+        .collect::<Vec<_>>();
+    production.sort();
+    production.dedup();
+    let issues = status.issues();
+    if production.is_empty() || issues.is_empty() {
+        return Ok(())
+    }
+
+    let message = format!(
+        "The script broadcasts to {} but was compiled from a tree which isn't committed and \
+         pushed:\n  - {}\nThe verification and provenance of the deployments can't be reproduced \
+         from the repository.",
+        production.join(", "),
+        issues.join("\n  - ")
+    );
+    if require_clean {
+        eyre::bail!("{message}\nThe policy requires a clean and pushed tree.")
+    }
+    shell::println(Paint::yellow(format!("\nWarning: {message}")))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_git_status() {
+        let clean =
+            GitStatus { commit: "1c8d776".to_string(), changed_files: vec![], pushed: true };
+        check_git_status(Some(&clean), [1], &[1], true).unwrap();
+
+        let dirty =
+            GitStatus { changed_files: vec!["src/Token.sol".to_string()], pushed: false, ..clean };
+        let err = check_git_status(Some(&dirty), [1, 10], &[1], true).unwrap_err().to_string();
+        assert!(err.contains("broadcasts to mainnet but"), "{err}");
+        assert!(err.contains("1 uncommitted changes: src/Token.sol"), "{err}");
+        assert!(err.contains("commit 1c8d776 isn't pushed"), "{err}");
+
+        // Only production chains are checked.
+        check_git_status(Some(&dirty), [11155111], &[1], true).unwrap();
+        check_git_status(None, [1], &[1], true).unwrap();
+    }
+}
@@ -0,0 +1,392 @@
+use crate::{
+    runner::ScriptRunner, simulate::PreSimulationState, transaction::TransactionWithMetadata,
+};
+use alloy_dyn_abi::{DynSolType, DynSolValue};
+use alloy_primitives::{fixed_bytes, keccak256, Address, Bytes, Selector, U256};
+use eyre::Result;
+use foundry_common::{provider::ethers::RpcUrl, shell, types::ToAlloy};
+use foundry_evm::decode::RevertDecoder;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+use yansi::Paint;
+
+/// `transferOwnership(address)`
+const TRANSFER_OWNERSHIP: Selector = fixed_bytes!("f2fde38b");
+/// `acceptOwnership()`
+const ACCEPT_OWNERSHIP: Selector = fixed_bytes!("79ba5097");
+/// `pendingOwner()`
+const PENDING_OWNER: Selector = fixed_bytes!("e30c3978");
+/// `schedule(address,uint256,bytes,bytes32,bytes32,uint256)` of OpenZeppelin's
+/// `TimelockController`.
+const SCHEDULE: Selector = fixed_bytes!("01d5062a");
+/// `scheduleBatch(address[],uint256[],bytes[],bytes32,bytes32,uint256)`
+const SCHEDULE_BATCH: Selector = fixed_bytes!("8f2a0bb0");
+/// `queueTransaction(address,uint256,string,bytes,uint256)` of Compound's `Timelock`.
+const QUEUE_TRANSACTION: Selector = fixed_bytes!("3a66f901");
+
+/// When a call scheduled on a timelock can be executed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ready {
+    /// A delay in seconds after the proposal.
+    After(u64),
+    /// A timestamp.
+    At(u64),
+}
+
+impl Ready {
+    /// The earliest timestamp from `now` at which the call can be executed, if it was proposed at
+    /// `proposed_at`.
+    ///
+    /// Delays are counted from the proposal, so that the calls of a batch, which share one delay,
+    /// don't wait for each other.
+    pub fn timestamp(self, proposed_at: U256, now: U256) -> U256 {
+        let ready = match self {
+            Self::After(delay) => proposed_at + U256::from(delay),
+            Self::At(eta) => U256::from(eta),
+        };
+        now.max(ready)
+    }
+}
+
+/// A step governance takes to complete a proposal of the script, which is rehearsed in the
+/// simulation by impersonating the governance contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GovernanceStep {
+    /// Accepting the ownership of an `Ownable2Step` contract which is transferred to governance.
+    AcceptOwnership { contract: Address },
+    /// Executing a call scheduled on the timelock.
+    Execute { target: Address, value: U256, data: Bytes, ready: Ready },
+}
+
+/// The outcome of a rehearsed governance step.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RehearsedStep {
+    pub chain: u64,
+    pub governance: Address,
+    pub description: String,
+    pub to: Address,
+    pub value: U256,
+    pub data: Bytes,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+fn decode_params(types: Vec<DynSolType>, data: &[u8]) -> Option<Vec<DynSolValue>> {
+    match DynSolType::Tuple(types).abi_decode_params(data.get(4..)?).ok()? {
+        DynSolValue::Tuple(values) => Some(values),
+        _ => None,
+    }
+}
+
+/// Returns the steps governance has to take after the call of a proposal transaction, e.g. the
+/// execution of the calls it schedules on the timelock.
+pub fn follow_ups(governance: Address, to: Address, data: &[u8]) -> Vec<GovernanceStep> {
+    let Some(selector) = data.get(..4) else { return Vec::new() };
+    let uint = || DynSolType::Uint(256);
+    let word = || DynSolType::FixedBytes(32);
+
+    if selector == TRANSFER_OWNERSHIP.as_slice() && to != governance {
+        let owner = decode_params(vec![DynSolType::Address], data)
+            .and_then(|values| values[0].as_address());
+        if owner == Some(governance) {
+            return vec![GovernanceStep::AcceptOwnership { contract: to }]
+        }
+    }
+    if to != governance {
+        return Vec::new()
+    }
+
+    if selector == SCHEDULE.as_slice() {
+        let types = vec![DynSolType::Address, uint(), DynSolType::Bytes, word(), word(), uint()];
+        if let Some(values) = decode_params(types, data) {
+            if let (Some(target), Some((value, _)), Some(data), Some((delay, _))) = (
+                values[0].as_address(),
+                values[1].as_uint(),
+                values[2].as_bytes(),
+                values[5].as_uint(),
+            ) {
+                return vec![GovernanceStep::Execute {
+                    target,
+                    value,
+                    data: Bytes::copy_from_slice(data),
+                    ready: Ready::After(delay.saturating_to()),
+                }]
+            }
+        }
+    } else if selector == SCHEDULE_BATCH.as_slice() {
+        let types = vec![
+            DynSolType::Array(Box::new(DynSolType::Address)),
+            DynSolType::Array(Box::new(uint())),
+            DynSolType::Array(Box::new(DynSolType::Bytes)),
+            word(),
+            word(),
+            uint(),
+        ];
+        if let Some(values) = decode_params(types, data) {
+            let (Some(targets), Some(amounts), Some(payloads), Some((delay, _))) = (
+                values[0].as_array(),
+                values[1].as_array(),
+                values[2].as_array(),
+                values[5].as_uint(),
+            ) else {
+                return Vec::new()
+            };
+            return targets
+                .iter()
+                .zip(amounts)
+                .zip(payloads)
+                .filter_map(|((target, value), data)| {
+                    Some(GovernanceStep::Execute {
+                        target: target.as_address()?,
+                        value: value.as_uint()?.0,
+                        data: Bytes::copy_from_slice(data.as_bytes()?),
+                        ready: Ready::After(delay.saturating_to()),
+                    })
+                })
+                .collect()
+        }
+    } else if selector == QUEUE_TRANSACTION.as_slice() {
+        let types =
+            vec![DynSolType::Address, uint(), DynSolType::String, DynSolType::Bytes, uint()];
+        if let Some(values) = decode_params(types, data) {
+            if let (Some(target), Some((value, _)), Some(signature), Some(data), Some((eta, _))) = (
+                values[0].as_address(),
+                values[1].as_uint(),
+                values[2].as_str(),
+                values[3].as_bytes(),
+                values[4].as_uint(),
+            ) {
+                // As with Compound's timelock, the selector of the signature prefixes the data.
+                let data = if signature.is_empty() {
+                    data.to_vec()
+                } else {
+                    [&keccak256(signature)[..4], data].concat()
+                };
+                return vec![GovernanceStep::Execute {
+                    target,
+                    value,
+                    data: data.into(),
+                    ready: Ready::At(eta.saturating_to()),
+                }]
+            }
+        }
+    }
+    Vec::new()
+}
+
+/// Executes the step on the runner from the governance contract, and returns its outcome, or
+/// `None` if the step turns out not to be needed.
+///
+/// `proposed_at` is the timestamp of the proposal the step follows up, which the delays of the
+/// timelock are counted from.
+fn rehearse(
+    runner: &mut ScriptRunner,
+    governance: Address,
+    step: &GovernanceStep,
+    proposed_at: U256,
+) -> Result<Option<RehearsedStep>> {
+    let executor = &mut runner.executor;
+    let (description, to, value, data) = match step {
+        GovernanceStep::AcceptOwnership { contract } => {
+            // Only `Ownable2Step` contracts have a pending owner, the others are already owned.
+            let pending = executor.call_raw(
+                governance,
+                *contract,
+                PENDING_OWNER.to_vec().into(),
+                U256::ZERO,
+            )?;
+            let pending = (!pending.reverted && pending.result.len() == 32)
+                .then(|| Address::from_slice(&pending.result[12..]));
+            if pending != Some(governance) {
+                return Ok(None)
+            }
+            (
+                format!("acceptOwnership() on {contract}"),
+                *contract,
+                U256::ZERO,
+                Bytes::from(ACCEPT_OWNERSHIP.to_vec()),
+            )
+        }
+        GovernanceStep::Execute { target, value, data, ready } => {
+            let now = executor.env.block.timestamp;
+            executor.env.block.timestamp = ready.timestamp(proposed_at, now);
+            let selector = data.get(..4).map(hex::encode_prefixed).unwrap_or_default();
+            (format!("execute {selector} on {target}"), *target, *value, data.clone())
+        }
+    };
+
+    let result = executor.call_raw_committing(governance, to, data.clone(), value)?;
+    Ok(Some(RehearsedStep {
+        chain: executor.env.cfg.chain_id,
+        governance,
+        description,
+        to,
+        value,
+        data,
+        success: !result.reverted,
+        reason: result.reverted.then(|| RevertDecoder::new().decode(&result.result, None)),
+    }))
+}
+
+impl PreSimulationState {
+    /// Rehearses the steps the governance contract takes to complete the proposals of the script,
+    /// by impersonating it once the transactions are simulated, and fails if any of them reverts.
+    ///
+    /// The steps are only simulated, they're not broadcast.
+    pub fn rehearse_governance(
+        &mut self,
+        governance: Address,
+        transactions: &VecDeque<TransactionWithMetadata>,
+        runners: &HashMap<RpcUrl, Arc<RwLock<ScriptRunner>>>,
+    ) -> Result<()> {
+        let mut rehearsed = Vec::new();
+        for tx in transactions {
+            let (Some(to), Some(data)) = (tx.transaction.to_addr(), tx.transaction.data()) else {
+                continue
+            };
+            let mut runner = runners.get(&tx.rpc).expect("invalid rpc url").write();
+            let proposed_at = runner.executor.env.block.timestamp;
+            for step in follow_ups(governance, to.to_alloy(), data) {
+                rehearsed.extend(rehearse(&mut runner, governance, &step, proposed_at)?);
+            }
+        }
+
+        shell::println("\n==========================")?;
+        shell::println(format!("Governance rehearsal of {governance}:\n"))?;
+        if rehearsed.is_empty() {
+            shell::println("The script doesn't propose anything to governance.")?;
+        }
+        for step in &rehearsed {
+            match &step.reason {
+                None => shell::println(Paint::green(format!("  OK   {}", step.description)))?,
+                Some(reason) => {
+                    shell::println(Paint::red(format!("  FAIL {}: {reason}", step.description)))?
+                }
+            }
+        }
+        let failed = rehearsed.iter().filter(|step| !step.success).count();
+        self.execution_artifacts.governance_rehearsal = rehearsed;
+        if failed > 0 {
+            eyre::bail!("{failed} governance steps failed in the rehearsal.")
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    const GOVERNANCE: Address = address!("00000000000000000000000000000000000000aa");
+    const TOKEN: Address = address!("00000000000000000000000000000000000000bb");
+
+    fn calldata(selector: Selector, params: Vec<DynSolValue>) -> Vec<u8> {
+        [selector.as_slice(), &DynSolValue::Tuple(params).abi_encode_params()].concat()
+    }
+
+    #[test]
+    fn finds_ownership_transfers() {
+        let data = calldata(TRANSFER_OWNERSHIP, vec![DynSolValue::Address(GOVERNANCE)]);
+        assert_eq!(
+            follow_ups(GOVERNANCE, TOKEN, &data),
+            vec![GovernanceStep::AcceptOwnership { contract: TOKEN }]
+        );
+
+        let data = calldata(TRANSFER_OWNERSHIP, vec![DynSolValue::Address(TOKEN)]);
+        assert!(follow_ups(GOVERNANCE, TOKEN, &data).is_empty());
+    }
+
+    #[test]
+    fn finds_scheduled_calls() {
+        let call = vec![0xde, 0xad, 0xbe, 0xef];
+        let data = calldata(
+            SCHEDULE,
+            vec![
+                DynSolValue::Address(TOKEN),
+                DynSolValue::Uint(U256::from(1), 256),
+                DynSolValue::Bytes(call.clone()),
+                DynSolValue::FixedBytes(Default::default(), 32),
+                DynSolValue::FixedBytes(Default::default(), 32),
+                DynSolValue::Uint(U256::from(86_400), 256),
+            ],
+        );
+        let step = GovernanceStep::Execute {
+            target: TOKEN,
+            value: U256::from(1),
+            data: call.clone().into(),
+            ready: Ready::After(86_400),
+        };
+        assert_eq!(follow_ups(GOVERNANCE, GOVERNANCE, &data), vec![step]);
+        // Only calls scheduled on the governance contract are followed up.
+        assert!(follow_ups(GOVERNANCE, TOKEN, &data).is_empty());
+
+        let data = calldata(
+            QUEUE_TRANSACTION,
+            vec![
+                DynSolValue::Address(TOKEN),
+                DynSolValue::Uint(U256::ZERO, 256),
+                DynSolValue::String("pause()".to_string()),
+                DynSolValue::Bytes(Vec::new()),
+                DynSolValue::Uint(U256::from(1_700_000_000), 256),
+            ],
+        );
+        assert_eq!(
+            follow_ups(GOVERNANCE, GOVERNANCE, &data),
+            vec![GovernanceStep::Execute {
+                target: TOKEN,
+                value: U256::ZERO,
+                data: keccak256("pause()")[..4].to_vec().into(),
+                ready: Ready::At(1_700_000_000),
+            }]
+        );
+    }
+
+    #[test]
+    fn finds_scheduled_batches() {
+        let data = calldata(
+            SCHEDULE_BATCH,
+            vec![
+                DynSolValue::Array(vec![DynSolValue::Address(TOKEN), DynSolValue::Address(TOKEN)]),
+                DynSolValue::Array(vec![
+                    DynSolValue::Uint(U256::ZERO, 256),
+                    DynSolValue::Uint(U256::ZERO, 256),
+                ]),
+                DynSolValue::Array(vec![DynSolValue::Bytes(vec![1]), DynSolValue::Bytes(vec![2])]),
+                DynSolValue::FixedBytes(Default::default(), 32),
+                DynSolValue::FixedBytes(Default::default(), 32),
+                DynSolValue::Uint(U256::from(60), 256),
+            ],
+        );
+        let steps = follow_ups(GOVERNANCE, GOVERNANCE, &data);
+        assert_eq!(steps.len(), 2);
+        assert_eq!(
+            steps[1],
+            GovernanceStep::Execute {
+                target: TOKEN,
+                value: U256::ZERO,
+                data: vec![2].into(),
+                ready: Ready::After(60),
+            }
+        );
+        assert!(follow_ups(GOVERNANCE, GOVERNANCE, &[0x01]).is_empty());
+    }
+
+    #[test]
+    fn counts_delays_from_the_proposal() {
+        let proposed_at = U256::from(1_000);
+        let now = Ready::After(60).timestamp(proposed_at, proposed_at);
+        assert_eq!(now, U256::from(1_060));
+        // The next call of the batch is ready as well, its delay doesn't stack.
+        assert_eq!(Ready::After(60).timestamp(proposed_at, now), U256::from(1_060));
+        assert_eq!(Ready::At(1_500).timestamp(proposed_at, now), U256::from(1_500));
+        // Time never goes back.
+        assert_eq!(Ready::At(500).timestamp(proposed_at, now), U256::from(1_060));
+    }
+}
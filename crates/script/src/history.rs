@@ -0,0 +1,797 @@
+use crate::verify::{BroadcastedState, VerificationReport};
+use alloy_primitives::{utils::format_units, Address, B256, U256};
+use clap::{Parser, ValueHint};
+use comfy_table::{presets::ASCII_MARKDOWN, Attribute, Cell, Table};
+use eyre::{Result, WrapErr};
+use foundry_common::{fs, shell};
+use foundry_config::{Chain, Config};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+/// File of the history database, in the cache directory of the project.
+///
+/// The database is an index of the broadcast directory, so it's rebuilt from it when missing, and
+/// the artifacts which were added or changed since they were indexed are indexed before querying.
+pub const DB_FILE_NAME: &str = "history.sqlite";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    chain INTEGER NOT NULL,
+    script TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    git_commit TEXT,
+    transactions INTEGER NOT NULL,
+    cost TEXT NOT NULL,
+    first_tx TEXT NOT NULL,
+    file TEXT,
+    UNIQUE (chain, script, first_tx)
+);
+CREATE TABLE IF NOT EXISTS deployments (
+    run_id INTEGER NOT NULL REFERENCES runs (id) ON DELETE CASCADE,
+    contract TEXT NOT NULL,
+    address TEXT NOT NULL,
+    tx_hash TEXT,
+    verified INTEGER
+);
+CREATE INDEX IF NOT EXISTS deployments_contract ON deployments (contract);
+CREATE TABLE IF NOT EXISTS indexed_files (
+    file TEXT PRIMARY KEY,
+    modified INTEGER NOT NULL
+);
+";
+
+/// A contract deployed by a run.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedDeployment {
+    pub contract: String,
+    pub address: Address,
+    pub tx_hash: Option<B256>,
+    /// Whether the contract was verified, if a verification report covers it.
+    pub verified: Option<bool>,
+}
+
+/// A broadcast run of a script on a chain, as indexed in the history database.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunRecord {
+    pub chain: u64,
+    /// The file name of the script, e.g. `Deploy.s.sol`.
+    pub script: String,
+    pub timestamp: u64,
+    pub commit: Option<String>,
+    /// The number of mined transactions.
+    pub transactions: u64,
+    /// The fees paid for the transactions, in wei.
+    pub cost: U256,
+    /// The hash of the first transaction, which identifies the run across the checkpoints of its
+    /// broadcast artifact.
+    pub first_tx: B256,
+    pub deployments: Vec<RecordedDeployment>,
+    /// The broadcast artifact the run was indexed from.
+    pub file: Option<PathBuf>,
+}
+
+/// The fields of a saved [ScriptSequence] the history is built from.
+///
+/// [ScriptSequence]: crate::sequence::ScriptSequence
+#[derive(Deserialize)]
+struct StoredSequence {
+    transactions: Vec<StoredTransaction>,
+    receipts: Vec<StoredReceipt>,
+    #[serde(default)]
+    timestamp: u64,
+    chain: u64,
+    #[serde(default)]
+    commit: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredTransaction {
+    hash: Option<B256>,
+    transaction_type: String,
+    contract_name: Option<String>,
+    contract_address: Option<Address>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredReceipt {
+    transaction_hash: B256,
+    gas_used: Option<U256>,
+    effective_gas_price: Option<U256>,
+}
+
+#[derive(Deserialize)]
+struct StoredMultiSequence {
+    deployments: Vec<serde_json::Value>,
+}
+
+impl RunRecord {
+    /// Builds the record of a serialized sequence, or `None` if none of its transactions were
+    /// mined.
+    pub fn from_sequence(
+        sequence: serde_json::Value,
+        script: &str,
+        file: Option<&Path>,
+    ) -> Result<Option<Self>> {
+        let sequence: StoredSequence = serde_json::from_value(sequence)?;
+        let Some(first_tx) = sequence
+            .transactions
+            .iter()
+            .find_map(|tx| tx.hash)
+            .or_else(|| sequence.receipts.first().map(|receipt| receipt.transaction_hash))
+        else {
+            return Ok(None)
+        };
+        if sequence.receipts.is_empty() {
+            return Ok(None)
+        }
+
+        let cost = sequence
+            .receipts
+            .iter()
+            .map(|receipt| {
+                receipt.gas_used.unwrap_or_default() *
+                    receipt.effective_gas_price.unwrap_or_default()
+            })
+            .fold(U256::ZERO, |total, fee| total.saturating_add(fee));
+        let mined =
+            |hash: &B256| sequence.receipts.iter().any(|receipt| receipt.transaction_hash == *hash);
+        let deployments = sequence
+            .transactions
+            .iter()
+            .filter(|tx| matches!(tx.transaction_type.as_str(), "CREATE" | "CREATE2"))
+            .filter(|tx| tx.hash.as_ref().map_or(false, mined))
+            .filter_map(|tx| {
+                Some(RecordedDeployment {
+                    contract: tx.contract_name.clone()?,
+                    address: tx.contract_address?,
+                    tx_hash: tx.hash,
+                    verified: None,
+                })
+            })
+            .collect();
+
+        Ok(Some(Self {
+            chain: sequence.chain,
+            script: script.to_string(),
+            timestamp: sequence.timestamp,
+            commit: sequence.commit,
+            transactions: sequence.receipts.len() as u64,
+            cost,
+            first_tx,
+            deployments,
+            file: file.map(Path::to_path_buf),
+        }))
+    }
+
+    /// Sets the verification status of the deployments covered by the reports.
+    pub fn apply_verification(&mut self, reports: &[VerificationReport]) {
+        let Some(report) = reports.iter().find(|report| report.chain == self.chain) else { return };
+        for deployment in &mut self.deployments {
+            if report.verified.iter().any(|contract| contract.address == deployment.address) {
+                deployment.verified = Some(true);
+            } else if report.unverifiable.contains(&deployment.address) {
+                deployment.verified = Some(false);
+            }
+        }
+    }
+
+    /// Returns the number of verified deployments, and of those with a known status.
+    fn verification(&self) -> Option<(usize, usize)> {
+        let known = self.deployments.iter().filter(|d| d.verified.is_some()).count();
+        let verified = self.deployments.iter().filter(|d| d.verified == Some(true)).count();
+        (known > 0).then_some((verified, known))
+    }
+}
+
+/// Filters of the runs returned by [RunHistory::query].
+#[derive(Clone, Debug, Default)]
+pub struct HistoryFilter {
+    pub chain: Option<u64>,
+    pub script: Option<String>,
+    /// Only the runs which deployed a contract of this name.
+    pub contract: Option<String>,
+    pub limit: usize,
+}
+
+/// The history of the broadcast runs of a project.
+pub struct RunHistory {
+    conn: Connection,
+}
+
+impl RunHistory {
+    /// Opens the database at `path`, creating it if needed.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let conn = Connection::open(path)
+            .wrap_err_with(|| format!("Failed to open the history database {}", path.display()))?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Records a run, replacing the one with the same first transaction, e.g. an earlier
+    /// checkpoint of a resumed broadcast, unless that one is more recent.
+    pub fn record(&mut self, run: &RunRecord) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        let recorded: Option<i64> = tx
+            .query_row(
+                "SELECT timestamp FROM runs WHERE chain = ?1 AND script = ?2 AND first_tx = ?3",
+                params![run.chain as i64, run.script, run.first_tx.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if recorded.map_or(false, |timestamp| timestamp as u64 > run.timestamp) {
+            return Ok(())
+        }
+        tx.execute(
+            "DELETE FROM runs WHERE chain = ?1 AND script = ?2 AND first_tx = ?3",
+            params![run.chain as i64, run.script, run.first_tx.to_string()],
+        )?;
+        tx.execute(
+            "INSERT INTO runs (chain, script, timestamp, git_commit, transactions, cost, first_tx, \
+             file) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                run.chain as i64,
+                run.script,
+                run.timestamp as i64,
+                run.commit,
+                run.transactions as i64,
+                run.cost.to_string(),
+                run.first_tx.to_string(),
+                run.file.as_ref().map(|file| file.to_string_lossy().into_owned()),
+            ],
+        )?;
+        let run_id = tx.last_insert_rowid();
+        for deployment in &run.deployments {
+            tx.execute(
+                "INSERT INTO deployments (run_id, contract, address, tx_hash, verified) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    run_id,
+                    deployment.contract,
+                    deployment.address.to_string(),
+                    deployment.tx_hash.map(|hash| hash.to_string()),
+                    deployment.verified,
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Returns the runs matching the filter, the most recent first.
+    pub fn query(&self, filter: &HistoryFilter) -> Result<Vec<RunRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, chain, script, timestamp, git_commit, transactions, cost, first_tx, file \
+             FROM runs \
+             WHERE (?1 IS NULL OR chain = ?1) AND (?2 IS NULL OR script = ?2) \
+             AND (?3 IS NULL OR id IN (SELECT run_id FROM deployments WHERE contract = ?3)) \
+             ORDER BY timestamp DESC, id DESC LIMIT ?4",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                filter.chain.map(|chain| chain as i64),
+                filter.script,
+                filter.contract,
+                filter.limit as i64
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, i64>(5)?,
+                    row.get::<_, String>(6)?,
+                    row.get::<_, String>(7)?,
+                    row.get::<_, Option<String>>(8)?,
+                ))
+            },
+        )?;
+
+        let mut runs = Vec::new();
+        for row in rows {
+            let (id, chain, script, timestamp, commit, transactions, cost, first_tx, file) = row?;
+            runs.push(RunRecord {
+                chain: chain as u64,
+                script,
+                timestamp: timestamp as u64,
+                commit,
+                transactions: transactions as u64,
+                cost: cost.parse()?,
+                first_tx: first_tx.parse()?,
+                deployments: self.deployments(id)?,
+                file: file.map(PathBuf::from),
+            });
+        }
+        Ok(runs)
+    }
+
+    fn deployments(&self, run_id: i64) -> Result<Vec<RecordedDeployment>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT contract, address, tx_hash, verified FROM deployments WHERE run_id = ?1 \
+             ORDER BY rowid",
+        )?;
+        let rows = stmt.query_map(params![run_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<bool>>(3)?,
+            ))
+        })?;
+        let mut deployments = Vec::new();
+        for row in rows {
+            let (contract, address, tx_hash, verified) = row?;
+            deployments.push(RecordedDeployment {
+                contract,
+                address: address.parse()?,
+                tx_hash: tx_hash.map(|hash| hash.parse()).transpose()?,
+                verified,
+            });
+        }
+        Ok(deployments)
+    }
+
+    /// Returns whether no run is recorded yet.
+    pub fn is_empty(&self) -> Result<bool> {
+        let id: Option<i64> =
+            self.conn.query_row("SELECT id FROM runs LIMIT 1", [], |row| row.get(0)).optional()?;
+        Ok(id.is_none())
+    }
+
+    /// Returns the modification time of the broadcast artifact if it wasn't indexed since it was
+    /// last modified.
+    fn needs_indexing(&self, file: &Path) -> Result<Option<i64>> {
+        let modified = std::fs::metadata(file)?
+            .modified()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |modified| modified.as_millis() as i64);
+        let file = file.to_string_lossy();
+        let indexed: Option<i64> = self
+            .conn
+            .query_row("SELECT modified FROM indexed_files WHERE file = ?1", params![file], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        Ok((indexed != Some(modified)).then_some(modified))
+    }
+
+    /// Indexes the broadcast artifacts of the project which were added or modified since they
+    /// were last indexed, or all of them if `reindex` is set, and returns the number of runs
+    /// found.
+    ///
+    /// Dry runs and the `-latest` copies of the artifacts are skipped, and artifacts which can't
+    /// be parsed are reported and skipped.
+    pub fn index_broadcast_dir(&mut self, broadcast: &Path, reindex: bool) -> Result<usize> {
+        if reindex {
+            self.conn.execute("DELETE FROM indexed_files", [])?;
+        }
+        let mut runs = Vec::new();
+        let mut indexed = Vec::new();
+        for (script, dir) in subdirs(broadcast)? {
+            if script == "multi" {
+                for (name, dir) in subdirs(&dir)? {
+                    let Some((script, ts)) = name.rsplit_once('-') else { continue };
+                    if ts == "latest" || ts.parse::<u64>().is_err() {
+                        continue
+                    }
+                    let latest = dir.with_file_name(format!("{script}-latest"));
+                    for file in json_files(&dir)? {
+                        let Some(modified) = self.needs_indexing(&file)? else { continue };
+                        indexed.push((file.clone(), modified));
+                        let report = latest.join(file.file_name().unwrap_or_default());
+                        let report = report.with_extension("verification.json");
+                        let result = read_multi(&file, script).map(|found| (found, report));
+                        collect_runs(&mut runs, &file, result);
+                    }
+                }
+                continue
+            }
+            for (chain, dir) in subdirs(&dir)? {
+                if chain.parse::<u64>().is_err() {
+                    continue
+                }
+                let report = dir.join("run-latest.verification.json");
+                for file in json_files(&dir)? {
+                    let name = file.file_stem().unwrap_or_default().to_string_lossy();
+                    let is_run =
+                        name.strip_prefix("run-").map_or(false, |ts| ts.parse::<u64>().is_ok());
+                    if !is_run {
+                        continue
+                    }
+                    let Some(modified) = self.needs_indexing(&file)? else { continue };
+                    indexed.push((file.clone(), modified));
+                    let result = fs::read_json_file::<serde_json::Value>(&file)
+                        .map_err(Into::into)
+                        .and_then(|sequence| {
+                            RunRecord::from_sequence(sequence, &script, Some(&file))
+                        })
+                        .map(|run| (run.into_iter().collect(), report.clone()));
+                    collect_runs(&mut runs, &file, result);
+                }
+            }
+        }
+
+        // The checkpoints of a run replace each other, so only the last one is kept.
+        runs.sort_by_key(|run| std::cmp::Reverse(run.timestamp));
+        let mut seen = HashSet::new();
+        runs.retain(|run| seen.insert((run.chain, run.script.clone(), run.first_tx)));
+        for run in runs.iter().rev() {
+            self.record(run)?;
+        }
+        for (file, modified) in indexed {
+            self.conn.execute(
+                "INSERT OR REPLACE INTO indexed_files (file, modified) VALUES (?1, ?2)",
+                params![file.to_string_lossy(), modified],
+            )?;
+        }
+        Ok(runs.len())
+    }
+}
+
+/// Returns the runs of the sequences of a multi-chain broadcast artifact.
+fn read_multi(file: &Path, script: &str) -> Result<Vec<RunRecord>> {
+    let multi: StoredMultiSequence = fs::read_json_file(file)?;
+    let mut runs = Vec::new();
+    for sequence in multi.deployments {
+        runs.extend(RunRecord::from_sequence(sequence, script, Some(file))?);
+    }
+    Ok(runs)
+}
+
+/// Adds the runs read from a broadcast artifact, with the verification report next to it.
+fn collect_runs(runs: &mut Vec<RunRecord>, file: &Path, result: Result<(Vec<RunRecord>, PathBuf)>) {
+    match result {
+        Ok((found, report)) => {
+            let reports = read_reports(&report);
+            runs.extend(found.into_iter().map(|mut run| {
+                run.apply_verification(&reports);
+                run
+            }));
+        }
+        Err(err) => warn!(?err, file = %file.display(), "skipping unreadable broadcast artifact"),
+    }
+}
+
+fn read_reports(path: &Path) -> Vec<VerificationReport> {
+    if !path.exists() {
+        return Vec::new()
+    }
+    fs::read_json_file(path).unwrap_or_default()
+}
+
+/// Returns the subdirectories of `dir` and their names, if it exists.
+fn subdirs(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new())
+    }
+    let mut dirs = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            dirs.push((name, path));
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Returns the JSON files of `dir`, except the verification reports.
+fn json_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_file() && name.ends_with(".json") && !name.ends_with(".verification.json") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Formats a unix timestamp as a UTC date and time.
+pub(crate) fn format_timestamp(timestamp: u64) -> String {
+    i64::try_from(timestamp)
+        .ok()
+        .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+impl BroadcastedState {
+    /// Records the broadcast runs in the history database of the project.
+    pub fn record_history(&self) -> Result<()> {
+        let config = &self.script_config.config;
+        let script = self
+            .build_data
+            .build_data
+            .target
+            .source
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let reports = self.verification_report.as_deref().map(read_reports).unwrap_or_default();
+        let file = self.sequence.broadcast_path();
+
+        let mut history = RunHistory::open(&config.cache_path.join(DB_FILE_NAME))?;
+        for sequence in self.sequence.sequences() {
+            let Some(mut run) =
+                RunRecord::from_sequence(serde_json::to_value(sequence)?, &script, file)?
+            else {
+                continue
+            };
+            run.apply_verification(&reports);
+            history.record(&run)?;
+        }
+        Ok(())
+    }
+}
+
+/// CLI arguments for `forge script history`.
+#[derive(Clone, Debug, Parser)]
+pub struct HistoryArgs {
+    /// Only the runs on this chain.
+    #[arg(long)]
+    chain: Option<Chain>,
+
+    /// Only the runs of this script, e.g. `Deploy.s.sol`.
+    #[arg(long, value_name = "SCRIPT")]
+    script: Option<PathBuf>,
+
+    /// Only the runs which deployed a contract of this name.
+    #[arg(long, value_name = "CONTRACT_NAME")]
+    contract: Option<String>,
+
+    /// The maximum number of runs to show.
+    #[arg(long, default_value = "20")]
+    limit: usize,
+
+    /// Indexes all the broadcast artifacts again, instead of only the ones which were added or
+    /// changed since they were indexed.
+    #[arg(long)]
+    reindex: bool,
+
+    /// Prints the runs as JSON.
+    #[arg(long)]
+    json: bool,
+
+    /// The root of the project.
+    #[arg(long, value_hint = ValueHint::DirPath, default_value = ".", value_name = "PATH")]
+    root: PathBuf,
+}
+
+impl HistoryArgs {
+    pub fn run(self) -> Result<()> {
+        let config = Config::load_with_root(&self.root).sanitized();
+        let path = config.cache_path.join(DB_FILE_NAME);
+        let mut history = RunHistory::open(&path)?;
+        let count = history.index_broadcast_dir(&config.broadcast, self.reindex)?;
+        if count > 0 || self.reindex {
+            if !self.json {
+                shell::println(format!(
+                    "Indexed {count} runs of {} into {}",
+                    config.broadcast.display(),
+                    path.display()
+                ))?;
+            }
+        }
+
+        let filter = HistoryFilter {
+            chain: self.chain.map(|chain| chain.id()),
+            script: self
+                .script
+                .as_ref()
+                .and_then(|script| script.file_name())
+                .map(|name| name.to_string_lossy().into_owned()),
+            contract: self.contract,
+            limit: self.limit,
+        };
+        let runs = history.query(&filter)?;
+        if self.json {
+            return shell::println(serde_json::to_string_pretty(&runs)?)
+        }
+        if runs.is_empty() {
+            return shell::println("No runs found.")
+        }
+
+        let mut table = Table::new();
+        table.load_preset(ASCII_MARKDOWN);
+        table.set_header(
+            [
+                "Date (UTC)",
+                "Chain",
+                "Script",
+                "Commit",
+                "Txs",
+                "Cost (ETH)",
+                "Verified",
+                "Deployed",
+            ]
+            .map(|header| Cell::new(header).add_attribute(Attribute::Bold)),
+        );
+        for run in &runs {
+            let verified = match run.verification() {
+                Some((verified, known)) => format!("{verified}/{known}"),
+                None => "-".to_string(),
+            };
+            let deployed = run
+                .deployments
+                .iter()
+                .map(|deployment| format!("{} {}", deployment.contract, deployment.address))
+                .collect::<Vec<_>>()
+                .join("\n");
+            table.add_row([
+                format_timestamp(run.timestamp),
+                run.chain.to_string(),
+                run.script.clone(),
+                run.commit.clone().unwrap_or_default(),
+                run.transactions.to_string(),
+                format_units(run.cost, 18).unwrap_or_else(|_| run.cost.to_string()),
+                verified,
+                deployed,
+            ]);
+        }
+        shell::println(table.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verify::VerifiedContract;
+    use alloy_primitives::address;
+
+    const TOKEN: Address = address!("5FbDB2315678afecb367f032d93F642f64180aa3");
+
+    fn sequence(timestamp: u64, receipts: usize) -> serde_json::Value {
+        let hash = |i: u8| B256::repeat_byte(i + 1);
+        serde_json::json!({
+            "transactions": [
+                {
+                    "hash": hash(0),
+                    "transactionType": "CREATE",
+                    "contractName": "Token",
+                    "contractAddress": TOKEN,
+                },
+                {
+                    "hash": hash(1),
+                    "transactionType": "CALL",
+                    "contractName": "Token",
+                    "contractAddress": TOKEN,
+                }
+            ],
+            "receipts": (0..receipts as u8).map(|i| serde_json::json!({
+                "transactionHash": hash(i),
+                "gasUsed": "0x5208",
+                "effectiveGasPrice": "0x3b9aca00",
+            })).collect::<Vec<_>>(),
+            "libraries": [],
+            "pending": [],
+            "returns": {},
+            "timestamp": timestamp,
+            "chain": 196,
+            "commit": "abc1234",
+        })
+    }
+
+    #[test]
+    fn builds_records_of_sequences() {
+        let run = RunRecord::from_sequence(sequence(10, 2), "Deploy.s.sol", None).unwrap().unwrap();
+        assert_eq!(run.chain, 196);
+        assert_eq!(run.transactions, 2);
+        // 2 * 21000 gas at 1 gwei.
+        assert_eq!(run.cost, U256::from(42_000_000_000_000u64));
+        assert_eq!(run.deployments.len(), 1);
+        assert_eq!(run.deployments[0].address, TOKEN);
+
+        assert!(RunRecord::from_sequence(sequence(10, 0), "Deploy.s.sol", None).unwrap().is_none());
+    }
+
+    #[test]
+    fn records_and_queries_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut history = RunHistory::open(&dir.path().join(DB_FILE_NAME)).unwrap();
+        assert!(history.is_empty().unwrap());
+
+        let mut first =
+            RunRecord::from_sequence(sequence(10, 1), "Deploy.s.sol", None).unwrap().unwrap();
+        history.record(&first).unwrap();
+
+        // A later checkpoint of the same run replaces it.
+        let mut run =
+            RunRecord::from_sequence(sequence(20, 2), "Deploy.s.sol", None).unwrap().unwrap();
+        run.apply_verification(&[VerificationReport {
+            chain: 196,
+            verified: vec![VerifiedContract { address: TOKEN, contract: "Token".to_string() }],
+            unverifiable: vec![],
+        }]);
+        history.record(&run).unwrap();
+
+        first.script = "Upgrade.s.sol".to_string();
+        first.timestamp = 5;
+        history.record(&first).unwrap();
+
+        let filter = HistoryFilter { limit: 10, ..Default::default() };
+        let runs = history.query(&filter).unwrap();
+        assert_eq!(runs, vec![run.clone(), first]);
+        assert_eq!(runs[0].verification(), Some((1, 1)));
+
+        let filter = HistoryFilter {
+            chain: Some(196),
+            script: Some("Deploy.s.sol".to_string()),
+            contract: Some("Token".to_string()),
+            limit: 10,
+        };
+        assert_eq!(history.query(&filter).unwrap(), vec![run]);
+
+        let filter = HistoryFilter { contract: Some("Vault".to_string()), limit: 10, ..filter };
+        assert!(history.query(&filter).unwrap().is_empty());
+    }
+
+    fn write(path: &Path, value: &serde_json::Value) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write_json_file(path, value).unwrap();
+    }
+
+    #[test]
+    fn indexes_broadcast_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let broadcast = dir.path().join("broadcast");
+        let chain = broadcast.join("Deploy.s.sol").join("196");
+        write(&chain.join("run-10.json"), &sequence(10, 1));
+        write(&chain.join("run-20.json"), &sequence(20, 2));
+        write(&chain.join("run-latest.json"), &sequence(20, 2));
+        write(&chain.join("dry-run").join("run-5.json"), &sequence(5, 1));
+        let multi = serde_json::json!({ "deployments": [sequence(30, 1)], "timestamp": 30 });
+        write(&broadcast.join("multi").join("Bridge.s.sol-30").join("run.json"), &multi);
+        write(&broadcast.join("multi").join("Bridge.s.sol-latest").join("run.json"), &multi);
+
+        let mut history = RunHistory::open(&dir.path().join(DB_FILE_NAME)).unwrap();
+        assert_eq!(history.index_broadcast_dir(&broadcast, false).unwrap(), 2);
+
+        let runs = history.query(&HistoryFilter { limit: 10, ..Default::default() }).unwrap();
+        let runs = runs.iter().map(|run| (run.script.as_str(), run.timestamp)).collect::<Vec<_>>();
+        assert_eq!(runs, vec![("Bridge.s.sol", 30), ("Deploy.s.sol", 20)]);
+
+        // Only the new artifacts are indexed once runs are recorded.
+        assert_eq!(history.index_broadcast_dir(&broadcast, false).unwrap(), 0);
+        let mut upgrade = sequence(40, 1);
+        upgrade["transactions"][0]["hash"] = serde_json::json!(B256::repeat_byte(9));
+        upgrade["receipts"][0]["transactionHash"] = serde_json::json!(B256::repeat_byte(9));
+        write(&broadcast.join("Upgrade.s.sol").join("196").join("run-40.json"), &upgrade);
+        assert_eq!(history.index_broadcast_dir(&broadcast, false).unwrap(), 1);
+        assert_eq!(
+            history.query(&HistoryFilter { limit: 10, ..Default::default() }).unwrap().len(),
+            3
+        );
+
+        // An earlier checkpoint doesn't replace the recorded run.
+        write(&chain.join("run-15.json"), &sequence(15, 1));
+        assert_eq!(history.index_broadcast_dir(&broadcast, false).unwrap(), 1);
+        let runs =
+            history.query(&HistoryFilter { chain: Some(196), limit: 10, ..Default::default() });
+        assert!(runs.unwrap().iter().any(|run| run.timestamp == 20));
+        assert_eq!(history.index_broadcast_dir(&broadcast, true).unwrap(), 3);
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(format_timestamp(1_710_599_400), "2024-03-16 14:30");
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{
+    sequence::ScriptSequence, transaction::TransactionWithMetadata, verify::BroadcastedState,
+};
+use ethers_core::types::TransactionReceipt;
+use eyre::{Result, WrapErr};
+use foundry_common::types::ToAlloy;
+use foundry_compilers::ArtifactId;
+use foundry_config::{ExplorerLinks, HookCommand};
+use serde::Serialize;
+use std::{
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// The points of a broadcast at which the `[script_hooks]` of the config are run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Hook {
+    PreBroadcast,
+    PostTx,
+    PostRun,
+    ChainHalt,
+}
+
+impl Hook {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PreBroadcast => "pre_broadcast",
+            Self::PostTx => "post_tx",
+            Self::PostRun => "post_run",
+            Self::ChainHalt => "chain_halt",
+        }
+    }
+}
+
+/// The JSON payload written to the stdin of the `pre_broadcast` and `post_run` hooks.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunPayload<'a> {
+    pub hook: Hook,
+    pub script: &'a str,
+    /// The sequences of the run, as in the broadcast artifacts.
+    pub sequences: &'a [ScriptSequence],
+}
+
+/// The JSON payload written to the stdin of the `post_tx` hook.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxPayload<'a> {
+    pub hook: Hook,
+    pub script: &'a str,
+    pub chain: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<&'a TransactionWithMetadata>,
+    pub receipt: &'a TransactionReceipt,
+    /// The page of the transaction on the explorer of the chain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_url: Option<String>,
+    /// The page of the deployed contract on the explorer of the chain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_url: Option<String>,
+}
+
+/// Returns the file name of the script, e.g. `Deploy.s.sol`.
+pub fn script_name(target: &ArtifactId) -> String {
+    target.source.file_name().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+/// Runs the command of a hook in the project root, with the payload on its stdin.
+///
+/// The stdout and stderr of the command are inherited, and the name of the hook is set in the
+/// `FOUNDRY_HOOK` environment variable.
+pub fn run_hook(
+    root: &Path,
+    hook: Hook,
+    command: &HookCommand,
+    payload: &impl Serialize,
+) -> Result<()> {
+    trace!(target: "script", hook = hook.as_str(), command = %command.command, "running hook");
+
+    // Paths are resolved from the project root, other commands from `PATH`.
+    let program = Path::new(&command.command);
+    let program =
+        if program.components().count() > 1 { root.join(program) } else { program.to_path_buf() };
+    let mut child = Command::new(program)
+        .args(&command.args)
+        .current_dir(root)
+        .env("FOUNDRY_HOOK", hook.as_str())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .wrap_err_with(|| {
+            format!("Failed to run the {} hook ({})", hook.as_str(), command.command)
+        })?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    // The command may exit without reading its input.
+    let _ = stdin.write_all(&serde_json::to_vec(payload)?);
+    drop(stdin);
+
+    let status = child.wait()?;
+    if !status.success() {
+        eyre::bail!("The {} hook ({}) failed with {status}", hook.as_str(), command.command)
+    }
+    Ok(())
+}
+
+/// Runs the `post_tx` hook for each of the receipts, warning about the failures since the
+/// transactions are already sent.
+pub fn run_post_tx_hooks(
+    root: &Path,
+    command: &HookCommand,
+    script: &str,
+    sequence: &ScriptSequence,
+    receipts: &[TransactionReceipt],
+    links: Option<&ExplorerLinks>,
+) {
+    for receipt in receipts {
+        let hash = receipt.transaction_hash;
+        let transaction = sequence.transactions.iter().find(|tx| tx.hash == Some(hash.to_alloy()));
+        let payload = TxPayload {
+            hook: Hook::PostTx,
+            script,
+            chain: sequence.chain,
+            transaction,
+            receipt,
+            tx_url: links.map(|links| links.tx_url(hash.to_alloy())),
+            contract_url: links
+                .zip(receipt.contract_address)
+                .map(|(links, address)| links.address_url(address.to_alloy())),
+        };
+        if let Err(err) = run_hook(root, Hook::PostTx, command, &payload) {
+            warn!(%err, tx = ?hash, "post_tx hook failed");
+        }
+    }
+}
+
+impl BroadcastedState {
+    /// Runs the `post_run` hook, if one is configured.
+    pub fn run_post_run_hook(&self) -> Result<()> {
+        let config = &self.script_config.config;
+        let Some(command) = &config.script_hooks.post_run else { return Ok(()) };
+        let script = script_name(&self.build_data.build_data.target);
+        let payload = RunPayload {
+            hook: Hook::PostRun,
+            script: &script,
+            sequences: self.sequence.sequences(),
+        };
+        run_hook(&config.__root.0, Hook::PostRun, command, &payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_hooks() {
+        let root = tempfile::tempdir().unwrap();
+        let sequences = [ScriptSequence { chain: 1, ..Default::default() }];
+        let payload =
+            RunPayload { hook: Hook::PreBroadcast, script: "Deploy.s.sol", sequences: &sequences };
+
+        let command = HookCommand {
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                "cat > payload.json; test \"$FOUNDRY_HOOK\" = pre_broadcast".to_string(),
+            ],
+        };
+        run_hook(root.path(), Hook::PreBroadcast, &command, &payload).unwrap();
+        let written: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(root.path().join("payload.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(written["hook"], "pre_broadcast");
+        assert_eq!(written["script"], "Deploy.s.sol");
+        assert_eq!(written["sequences"][0]["chain"], 1);
+
+        let rejecting = HookCommand { command: "false".to_string(), args: vec![] };
+        assert!(run_hook(root.path(), Hook::PreBroadcast, &rejecting, &payload).is_err());
+    }
+}
@@ -0,0 +1,373 @@
+use crate::{
+    addresses::{predict_addresses, PredictedAddress},
+    broadcast::BundledState,
+    deposits::L1ToL2Deposit,
+    hooks,
+    reconciliation::format_ether,
+    sequence::ScriptSequence,
+    state_diff::SlotChange,
+};
+use alloy_primitives::{Address, U256};
+use ethers_core::types::NameOrAddress;
+use eyre::Result;
+use foundry_common::{fs, shell, types::ToAlloy};
+use foundry_config::ExplorerLinks;
+use std::{collections::BTreeMap, fmt::Write, path::Path};
+
+/// The styles of the report, inlined so that it's a single file which can be attached anywhere.
+const STYLE: &str = "
+body { font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; margin: 2em; color: #1f2328; }
+h1 { font-size: 1.6em; } h2 { font-size: 1.25em; margin-top: 2em; border-bottom: 1px solid #d0d7de; }
+table { border-collapse: collapse; width: 100%; font-size: 0.9em; }
+th, td { border: 1px solid #d0d7de; padding: 4px 8px; text-align: left; vertical-align: top; }
+th { background: #f6f8fa; }
+code, .mono { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; word-break: break-all; }
+.warning { color: #9a6700; } .error { color: #cf222e; } .muted { color: #656d76; }
+";
+
+/// A self-contained HTML report of a simulated run, for reviewers who don't use the CLI.
+pub struct HtmlReport<'a> {
+    pub script: &'a str,
+    pub sig: &'a str,
+    /// When the report was generated, as displayed.
+    pub generated_at: String,
+    pub sequences: &'a [ScriptSequence],
+    pub state_diff: &'a [SlotChange],
+    pub deposits: &'a [L1ToL2Deposit],
+    pub warnings: &'a [String],
+    /// The links to the explorers of the chains, by chain id.
+    pub explorers: BTreeMap<u64, ExplorerLinks>,
+}
+
+impl HtmlReport<'_> {
+    pub fn render(&self) -> String {
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Dry run of {script}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n\
+             <h1>Dry run of {script}</h1>\n<p class=\"muted\">Function <code>{sig}</code>, \
+             simulated at {generated_at}. The transactions were not broadcast.</p>\n",
+            script = escape(self.script),
+            sig = escape(self.sig),
+            generated_at = escape(&self.generated_at),
+        );
+        self.render_summary(&mut html);
+        self.render_warnings(&mut html);
+        self.render_transactions(&mut html);
+        self.render_predicted_addresses(&mut html);
+        self.render_state_diff(&mut html);
+        self.render_deposits(&mut html);
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    fn render_summary(&self, html: &mut String) {
+        html.push_str("<h2>Summary</h2>\n<table>\n<tr><th>Chain</th><th>Transactions</th>");
+        html.push_str("<th>Gas limit</th><th>Estimated cost (ETH)</th></tr>\n");
+        for sequence in self.sequences {
+            let (gas, cost) =
+                sequence.typed_transactions().fold((U256::ZERO, U256::ZERO), |(gas, cost), tx| {
+                    let limit = tx.gas().copied().unwrap_or_default().to_alloy();
+                    let price = tx.gas_price().unwrap_or_default().to_alloy();
+                    (gas + limit, cost + limit * price)
+                });
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{gas}</td><td>{}</td></tr>",
+                sequence.chain,
+                sequence.transactions.len(),
+                format_ether(cost)
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    fn render_warnings(&self, html: &mut String) {
+        let mut warnings = self.warnings.iter().map(|warning| escape(warning)).collect::<Vec<_>>();
+        for deposit in self.deposits.iter().filter(|deposit| deposit.is_underfunded()) {
+            warnings.push(format!(
+                "Transaction {} on chain {} sends a retryable ticket which isn't funded enough to \
+                 be redeemed automatically.",
+                deposit.index, deposit.chain
+            ));
+        }
+        for sequence in self.sequences {
+            for address in sequence.raw_deployments() {
+                warnings.push(format!(
+                    "{address} on chain {} is deployed from raw bytecode and isn't verified \
+                     automatically.",
+                    sequence.chain
+                ));
+            }
+        }
+
+        html.push_str("<h2>Warnings</h2>\n");
+        if warnings.is_empty() {
+            html.push_str("<p class=\"muted\">None.</p>\n");
+            return
+        }
+        html.push_str("<ul>\n");
+        for warning in warnings {
+            let _ = writeln!(html, "<li class=\"warning\">{warning}</li>");
+        }
+        html.push_str("</ul>\n");
+    }
+
+    fn render_transactions(&self, html: &mut String) {
+        html.push_str("<h2>Transactions</h2>\n<table>\n<tr><th>#</th><th>Chain</th>");
+        html.push_str("<th>From</th><th>To</th><th>Call</th><th>Value (ETH)</th>");
+        html.push_str("<th>Gas limit</th></tr>\n");
+        for sequence in self.sequences {
+            for (i, tx) in sequence.transactions.iter().enumerate() {
+                let typed = tx.typed_tx();
+                let from = typed
+                    .from()
+                    .map(|from| self.link_address(sequence.chain, from.to_alloy()))
+                    .unwrap_or_default();
+                let to = match (tx.opcode.is_any_create(), typed.to()) {
+                    (true, _) => format!(
+                        "new {} at {}",
+                        escape(
+                            tx.contract_name
+                                .as_deref()
+                                .filter(|name| !name.is_empty())
+                                .unwrap_or("contract")
+                        ),
+                        self.link_address(sequence.chain, tx.contract_address.unwrap_or_default())
+                    ),
+                    (false, Some(NameOrAddress::Address(to))) => {
+                        let to = self.link_address(sequence.chain, to.to_alloy());
+                        match tx.contract_name.as_deref().filter(|name| !name.is_empty()) {
+                            Some(name) => format!("{} ({to})", escape(name)),
+                            None => to,
+                        }
+                    }
+                    _ => String::new(),
+                };
+                let call = match &tx.function {
+                    Some(function) if !function.is_empty() => {
+                        let arguments = tx.arguments.as_deref().unwrap_or_default().join(", ");
+                        format!(
+                            "{}<br><span class=\"mono muted\">{}</span>",
+                            escape(function),
+                            escape(&arguments)
+                        )
+                    }
+                    _ => {
+                        let data = typed.data().map(|data| data.len()).unwrap_or_default();
+                        format!("<span class=\"muted\">{data} bytes of calldata</span>")
+                    }
+                };
+                let value = typed.value().copied().unwrap_or_default().to_alloy();
+                let gas = typed.gas().copied().unwrap_or_default();
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{i}</td><td>{}</td><td class=\"mono\">{from}</td>\
+                     <td class=\"mono\">{to}</td><td>{call}</td><td>{}</td><td>{gas}</td></tr>",
+                    sequence.chain,
+                    format_ether(value)
+                );
+            }
+        }
+        html.push_str("</table>\n");
+    }
+
+    fn render_predicted_addresses(&self, html: &mut String) {
+        let predicted = self.sequences.iter().flat_map(predict_addresses).collect::<Vec<_>>();
+        if predicted.is_empty() {
+            return
+        }
+        html.push_str("<h2>Predicted addresses</h2>\n<table>\n<tr><th>Chain</th><th>#</th>");
+        html.push_str("<th>Contract</th><th>Address</th><th>Derivation</th></tr>\n");
+        for PredictedAddress { chain, transaction, address, contract_name, derivation } in predicted
+        {
+            let _ = writeln!(
+                html,
+                "<tr><td>{chain}</td><td>{transaction}</td><td>{}</td><td class=\"mono\">\
+                 {}</td><td class=\"mono\">{}</td></tr>",
+                escape(contract_name.as_deref().unwrap_or_default()),
+                self.link_address(chain, address),
+                escape(&derivation.to_string())
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    fn render_state_diff(&self, html: &mut String) {
+        if self.state_diff.is_empty() {
+            return
+        }
+        html.push_str("<h2>State diff</h2>\n<table>\n<tr><th>#</th><th>Contract</th>");
+        html.push_str("<th>Slot</th><th>Before</th><th>After</th></tr>\n");
+        for change in self.state_diff {
+            let contract = match &change.contract {
+                Some(name) => format!("{name} ({})", change.address),
+                None => change.address.to_string(),
+            };
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"mono\">{}</td><td class=\"mono\">{}</td>\
+                 <td class=\"mono\">0x{:x}</td><td class=\"mono\">0x{:x}</td></tr>",
+                change.transaction,
+                escape(&contract),
+                escape(&change.label),
+                change.previous,
+                change.present
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    fn render_deposits(&self, html: &mut String) {
+        if self.deposits.is_empty() {
+            return
+        }
+        html.push_str("<h2>L1→L2 deposits</h2>\n<table>\n<tr><th>Chain</th><th>#</th>");
+        html.push_str("<th>Kind</th><th>L2 sender</th><th>To</th><th>Minted (ETH)</th>");
+        html.push_str("<th>Gas limit</th><th>L2 outcome</th></tr>\n");
+        for deposit in self.deposits {
+            let to = deposit.to.map_or_else(|| "new contract".to_string(), |to| to.to_string());
+            let outcome = match &deposit.outcome {
+                Some(outcome) => match (&outcome.error, outcome.created) {
+                    (Some(error), _) => {
+                        format!("<span class=\"error\">reverts: {}</span>", escape(error))
+                    }
+                    (None, Some(created)) => format!("deploys {created}"),
+                    (None, None) => "succeeds".to_string(),
+                },
+                None => "<span class=\"muted\">not simulated</span>".to_string(),
+            };
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"mono\">{}</td>\
+                 <td class=\"mono\">{to}</td><td>{}</td><td>{}</td><td>{outcome}</td></tr>",
+                deposit.chain,
+                deposit.index,
+                deposit.kind,
+                deposit.l2_sender,
+                format_ether(deposit.mint),
+                deposit.gas_limit
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    /// Returns the address, linked to its page on the explorer of the chain if there's one.
+    fn link_address(&self, chain: u64, address: Address) -> String {
+        match self.explorers.get(&chain) {
+            Some(links) => {
+                format!("<a href=\"{}\">{address}</a>", escape(&links.address_url(address)))
+            }
+            None => address.to_string(),
+        }
+    }
+}
+
+/// Escapes text for HTML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl BundledState {
+    /// Writes the HTML report of the simulated run to `path`.
+    pub fn write_html_report(&self, path: &Path, deposits: &[L1ToL2Deposit]) -> Result<()> {
+        let report = HtmlReport {
+            script: &hooks::script_name(&self.build_data.build_data.target),
+            sig: &self.args.sig,
+            generated_at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            sequences: self.sequence.sequences(),
+            state_diff: &self.execution_artifacts.state_diff,
+            deposits,
+            warnings: &self.execution_artifacts.warnings,
+            explorers: self
+                .sequence
+                .sequences()
+                .iter()
+                .filter_map(|sequence| {
+                    let links =
+                        self.script_config.config.get_explorer_links(sequence.chain.into())?;
+                    Some((sequence.chain, links))
+                })
+                .collect(),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, report.render())?;
+        shell::println(format!("\nHTML report written to: {}", path.display()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TransactionWithMetadata;
+    use alloy_primitives::{Address, U64};
+    use alloy_rpc_types::request::TransactionRequest;
+    use foundry_evm::traces::CallKind;
+
+    #[test]
+    fn renders_reports() {
+        let mut tx = TransactionWithMetadata::from_tx_request(TransactionRequest {
+            from: Some(Address::with_last_byte(1)),
+            to: Some(Address::with_last_byte(2)),
+            gas: Some(U256::from(50_000)),
+            nonce: Some(U64::ZERO),
+            ..Default::default()
+        });
+        tx.opcode = CallKind::Call;
+        tx.contract_name = Some("Vault".to_string());
+        tx.function = Some("setName(string)".to_string());
+        tx.arguments = Some(vec!["<script>".to_string()]);
+        let sequences =
+            [ScriptSequence { chain: 1, transactions: [tx].into(), ..Default::default() }];
+        let state_diff = [SlotChange {
+            transaction: 0,
+            address: Address::with_last_byte(2),
+            contract: Some("Vault".to_string()),
+            label: "Vault.name".to_string(),
+            previous: U256::ZERO,
+            present: U256::from(1),
+        }];
+        let warnings = ["[ownership] Vault is owned by the deployer".to_string()];
+
+        let html = HtmlReport {
+            script: "Deploy.s.sol",
+            sig: "run()",
+            generated_at: "2024-01-01 00:00:00 UTC".to_string(),
+            sequences: &sequences,
+            state_diff: &state_diff,
+            deposits: &[],
+            warnings: &warnings,
+            explorers: BTreeMap::from([(1, ExplorerLinks::from_base_url("https://etherscan.io"))]),
+        }
+        .render();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Dry run of Deploy.s.sol</title>"));
+        let vault = Address::with_last_byte(2);
+        assert!(html.contains(&format!(
+            "Vault (<a href=\"https://etherscan.io/address/{vault}\">{vault}</a>)"
+        )));
+        assert!(html.contains("<td>50000</td>"));
+        assert!(html.contains("Vault.name"));
+        assert!(html.contains("[ownership] Vault is owned by the deployer"));
+        // The arguments are escaped.
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("L1→L2 deposits"));
+    }
+}
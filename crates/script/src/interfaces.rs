@@ -0,0 +1,375 @@
+use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
+use alloy_json_abi::{Function, JsonAbi};
+use alloy_primitives::{fixed_bytes, Address, Selector, B256, U256};
+use eyre::Result;
+use foundry_common::{get_contract_name, ContractsByArtifact};
+use foundry_evm::executors::Executor;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+/// `supportsInterface(bytes4)`
+const SUPPORTS_INTERFACE: Selector = fixed_bytes!("01ffc9a7");
+/// `diamondCut((address,uint8,bytes4[])[],address,bytes)`
+const DIAMOND_CUT: Selector = fixed_bytes!("1f931c1c");
+
+/// Calls `supportsInterface` on `address`, which is considered not to support the interface if the
+/// call fails.
+pub fn supports_interface(
+    executor: &Executor,
+    from: Address,
+    address: Address,
+    id: Selector,
+) -> bool {
+    let calldata =
+        [SUPPORTS_INTERFACE.as_slice(), B256::right_padding_from(id.as_slice()).as_slice()]
+            .concat();
+    executor.call_raw(from, address, calldata.into(), U256::ZERO).map_or(false, |result| {
+        !result.reverted && result.result.get(..32).map_or(false, |word| word[31] == 1)
+    })
+}
+
+/// An interface of the project, i.e. a contract without code, whose ERC-165 identifier is the
+/// XOR of the selectors of its functions.
+#[derive(Clone, Debug)]
+pub struct Interface {
+    pub name: String,
+    pub id: Selector,
+    /// Selector -> signature
+    pub functions: BTreeMap<Selector, String>,
+}
+
+impl Interface {
+    fn new(name: &str, abi: &JsonAbi) -> Option<Self> {
+        let functions = abi
+            .functions()
+            .map(|function| (function.selector(), function.signature()))
+            .collect::<BTreeMap<_, _>>();
+        if functions.is_empty() {
+            return None
+        }
+        let id = functions.keys().fold(Selector::ZERO, |id, selector| id ^ *selector);
+        Some(Self { name: name.to_string(), id, functions })
+    }
+
+    /// Collects the interfaces of the project.
+    pub fn all(contracts: &ContractsByArtifact) -> Vec<Self> {
+        let mut ids = BTreeSet::new();
+        contracts
+            .iter()
+            .filter(|(_, (_, code))| code.is_empty())
+            .filter_map(|(id, (abi, _))| Self::new(get_contract_name(&id.identifier()), abi))
+            .filter(|interface| ids.insert(interface.id))
+            .collect()
+    }
+}
+
+/// A deployed contract or diamond routing which doesn't match the interfaces it claims.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum InterfaceIssue {
+    /// A contract reports support for an ERC-165 interface without implementing all of it.
+    UnimplementedInterface {
+        contract: String,
+        address: Address,
+        interface: String,
+        interface_id: Selector,
+        missing: Vec<String>,
+    },
+    /// A diamond cut adds a selector which is already routed to a facet.
+    SelectorCollision { diamond: Address, selector: Selector, facet: Address, existing: Address },
+    /// A diamond cut routes a selector to a facet which doesn't implement it.
+    UnknownSelector { diamond: Address, selector: Selector, facet: Address },
+}
+
+impl fmt::Display for InterfaceIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnimplementedInterface {
+                contract,
+                address,
+                interface,
+                interface_id,
+                missing,
+            } => {
+                write!(
+                    f,
+                    "{contract} ({address}) declares support for {interface} ({interface_id}) \
+                     but doesn't implement {}",
+                    missing.join(", ")
+                )
+            }
+            Self::SelectorCollision { diamond, selector, facet, existing } => write!(
+                f,
+                "diamond {diamond}: selector {selector} of facet {facet} is already routed to \
+                 facet {existing}"
+            ),
+            Self::UnknownSelector { diamond, selector, facet } => write!(
+                f,
+                "diamond {diamond}: selector {selector} is routed to facet {facet}, which doesn't \
+                 implement it"
+            ),
+        }
+    }
+}
+
+/// Checks the contracts deployed by the script against the ERC-165 interfaces they declare, and
+/// the diamond cuts of the script for selector collisions.
+#[derive(Debug, Default)]
+pub struct InterfaceAudit {
+    /// Diamond -> selector -> facet, for the selectors cut by the script.
+    routes: BTreeMap<Address, BTreeMap<Selector, Address>>,
+    issues: BTreeSet<InterfaceIssue>,
+}
+
+impl InterfaceAudit {
+    /// Checks a deployed contract which implements ERC-165 against the interfaces of the
+    /// project.
+    ///
+    /// `supports` calls `supportsInterface` on the contract with the given interface identifier.
+    pub fn add_contract(
+        &mut self,
+        address: Address,
+        contract: &str,
+        abi: &JsonAbi,
+        interfaces: &[Interface],
+        supports: impl Fn(Selector) -> bool,
+    ) {
+        let selectors = abi.functions().map(|function| function.selector()).collect::<Vec<_>>();
+        if !selectors.contains(&SUPPORTS_INTERFACE) {
+            return
+        }
+        for interface in interfaces {
+            let missing = interface
+                .functions
+                .iter()
+                .filter(|(selector, _)| !selectors.contains(selector))
+                .map(|(_, signature)| signature.clone())
+                .collect::<Vec<_>>();
+            if !missing.is_empty() && supports(interface.id) {
+                self.issues.insert(InterfaceIssue::UnimplementedInterface {
+                    contract: contract.to_string(),
+                    address,
+                    interface: interface.name.clone(),
+                    interface_id: interface.id,
+                    missing,
+                });
+            }
+        }
+    }
+
+    /// Adds a call of the script, keeping track of the selectors it routes if it's a
+    /// `diamondCut`.
+    ///
+    /// `facet_abi` returns the ABI of a facet, if it's known.
+    pub fn add_call<'a>(
+        &mut self,
+        to: Address,
+        data: &[u8],
+        facet_abi: impl Fn(Address) -> Option<&'a JsonAbi>,
+    ) {
+        let Some(cuts) = decode_diamond_cut(data) else { return };
+        let routes = self.routes.entry(to).or_default();
+        for (facet, action, selectors) in cuts {
+            let abi = facet_abi(facet);
+            for selector in selectors {
+                match action {
+                    // Add
+                    0 => {
+                        if let Some(existing) = routes.get(&selector) {
+                            self.issues.insert(InterfaceIssue::SelectorCollision {
+                                diamond: to,
+                                selector,
+                                facet,
+                                existing: *existing,
+                            });
+                            continue
+                        }
+                        routes.insert(selector, facet);
+                    }
+                    // Replace
+                    1 => {
+                        routes.insert(selector, facet);
+                    }
+                    // Remove
+                    _ => {
+                        routes.remove(&selector);
+                        continue
+                    }
+                }
+                if abi.is_some_and(|abi| abi.functions().all(|f| f.selector() != selector)) {
+                    self.issues.insert(InterfaceIssue::UnknownSelector {
+                        diamond: to,
+                        selector,
+                        facet,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Fails with a report of the issues found, if any.
+    pub fn finish(self) -> Result<()> {
+        if self.issues.is_empty() {
+            return Ok(())
+        }
+        let issues = self.issues.iter().map(|issue| format!("  - {issue}")).collect::<Vec<_>>();
+        eyre::bail!(
+            "The deployed contracts don't match their interfaces:\n{}\n\nRun without \
+             --security-checks to broadcast anyway.",
+            issues.join("\n")
+        )
+    }
+}
+
+/// Decodes the facet, action and selectors of the cuts of a `diamondCut` call.
+fn decode_diamond_cut(data: &[u8]) -> Option<Vec<(Address, u8, Vec<Selector>)>> {
+    if data.get(..4)? != DIAMOND_CUT.as_slice() {
+        return None
+    }
+    let function = Function::parse("diamondCut((address,uint8,bytes4[])[],address,bytes)").ok()?;
+    let values = function.abi_decode_input(&data[4..], false).ok()?;
+    let DynSolValue::Array(cuts) = values.first()? else { return None };
+    cuts.iter()
+        .map(|cut| {
+            let DynSolValue::Tuple(cut) = cut else { return None };
+            let [facet, action, selectors] = cut.as_slice() else { return None };
+            let facet = facet.as_address()?;
+            let (action, _) = action.as_uint()?;
+            let selectors = selectors
+                .as_array()?
+                .iter()
+                .map(|selector| {
+                    selector.as_fixed_bytes().map(|(word, _)| Selector::from_slice(&word[..4]))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some((facet, action.to::<u8>(), selectors))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    const DIAMOND: Address = address!("00000000000000000000000000000000000000d1");
+    const FACET_A: Address = address!("00000000000000000000000000000000000000fa");
+    const FACET_B: Address = address!("00000000000000000000000000000000000000fb");
+
+    fn abi(signatures: &[&str]) -> JsonAbi {
+        let mut abi = JsonAbi::default();
+        for signature in signatures {
+            let function = Function::parse(signature).unwrap();
+            abi.functions.entry(function.name.clone()).or_default().push(function);
+        }
+        abi
+    }
+
+    fn diamond_cut(cuts: &[(Address, u8, &[Selector])]) -> Vec<u8> {
+        let cuts = cuts
+            .iter()
+            .map(|(facet, action, selectors)| {
+                DynSolValue::Tuple(vec![
+                    DynSolValue::Address(*facet),
+                    DynSolValue::Uint(U256::from(*action), 8),
+                    DynSolValue::Array(
+                        selectors
+                            .iter()
+                            .map(|selector| {
+                                DynSolValue::FixedBytes(
+                                    B256::right_padding_from(selector.as_slice()),
+                                    4,
+                                )
+                            })
+                            .collect(),
+                    ),
+                ])
+            })
+            .collect();
+        let function =
+            Function::parse("diamondCut((address,uint8,bytes4[])[],address,bytes)").unwrap();
+        function
+            .abi_encode_input(&[
+                DynSolValue::Array(cuts),
+                DynSolValue::Address(Address::ZERO),
+                DynSolValue::Bytes(vec![]),
+            ])
+            .unwrap()
+    }
+
+    #[test]
+    fn flags_unimplemented_interfaces() {
+        let interface =
+            Interface::new("IVault", &abi(&["deposit(uint256)", "withdraw(uint256)"])).unwrap();
+        let contract = abi(&["supportsInterface(bytes4)", "deposit(uint256)"]);
+
+        let mut audit = InterfaceAudit::default();
+        audit.add_contract(DIAMOND, "Vault", &contract, &[interface.clone()], |_| false);
+        assert!(audit.issues.is_empty());
+
+        audit.add_contract(DIAMOND, "Vault", &contract, &[interface.clone()], |id| {
+            id == interface.id
+        });
+        assert_eq!(
+            audit.issues.into_iter().collect::<Vec<_>>(),
+            vec![InterfaceIssue::UnimplementedInterface {
+                contract: "Vault".to_string(),
+                address: DIAMOND,
+                interface: "IVault".to_string(),
+                interface_id: interface.id,
+                missing: vec!["withdraw(uint256)".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn flags_diamond_selector_collisions() {
+        let facet_a = abi(&["owner()", "transferOwnership(address)"]);
+        let facet_b = abi(&["owner()"]);
+        let owner = facet_b.functions().next().unwrap().selector();
+        let transfer = Selector::from_slice(&[0xf2, 0xfd, 0xe3, 0x8b]);
+        let abis = |facet| {
+            if facet == FACET_A {
+                Some(&facet_a)
+            } else if facet == FACET_B {
+                Some(&facet_b)
+            } else {
+                None
+            }
+        };
+
+        let mut audit = InterfaceAudit::default();
+        audit.add_call(DIAMOND, &diamond_cut(&[(FACET_A, 0, &[owner, transfer])]), abis);
+        audit.add_call(DIAMOND, &diamond_cut(&[(FACET_B, 0, &[owner, transfer])]), abis);
+        assert_eq!(
+            audit.issues.into_iter().collect::<Vec<_>>(),
+            vec![
+                InterfaceIssue::SelectorCollision {
+                    diamond: DIAMOND,
+                    selector: owner,
+                    facet: FACET_B,
+                    existing: FACET_A,
+                },
+                InterfaceIssue::SelectorCollision {
+                    diamond: DIAMOND,
+                    selector: transfer,
+                    facet: FACET_B,
+                    existing: FACET_A,
+                },
+            ]
+        );
+
+        // replacing a selector with a facet which doesn't implement it
+        let mut audit = InterfaceAudit::default();
+        audit.add_call(DIAMOND, &diamond_cut(&[(FACET_B, 1, &[transfer])]), abis);
+        assert_eq!(
+            audit.issues.into_iter().collect::<Vec<_>>(),
+            vec![InterfaceIssue::UnknownSelector {
+                diamond: DIAMOND,
+                selector: transfer,
+                facet: FACET_B,
+            }]
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{
+    scaffold::DEPLOYMENTS_DIR,
+    verify::{BroadcastedState, VerificationReport},
+};
+use alloy_primitives::Address;
+use eyre::{Result, WrapErr};
+use foundry_common::{fs, shell};
+use foundry_compilers::ArtifactId;
+use foundry_config::Chain;
+use foundry_evm::traces::CallKind;
+use reqwest::multipart::{Form, Part};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+/// The IPFS CIDs of the metadata of a contract and of its sources.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedMetadata {
+    pub metadata: String,
+    /// The CIDs of the sources which aren't embedded in the metadata, by path.
+    pub sources: BTreeMap<String, String>,
+}
+
+/// The response of the `add` endpoint of the IPFS HTTP API.
+#[derive(Deserialize)]
+struct AddResponse {
+    #[serde(rename = "Hash")]
+    hash: String,
+}
+
+/// A client of the HTTP API of an IPFS node, or of a pinning service exposing it.
+pub struct IpfsClient {
+    client: reqwest::Client,
+    api: String,
+    token: Option<String>,
+}
+
+impl IpfsClient {
+    pub fn new(api: &str, token: Option<String>) -> Self {
+        Self { client: reqwest::Client::new(), api: api.trim_end_matches('/').to_string(), token }
+    }
+
+    /// Adds and pins a file, and returns its CID.
+    ///
+    /// The files are added as CIDv0 with the default chunker, as the compiler does to compute the
+    /// hash of the metadata embedded in the bytecode, so that the CIDs match.
+    pub async fn add(&self, name: &str, content: Vec<u8>) -> Result<String> {
+        let form = Form::new().part("file", Part::bytes(content).file_name(name.to_string()));
+        let mut request = self
+            .client
+            .post(format!("{}/api/v0/add", self.api))
+            .query(&[("cid-version", "0"), ("pin", "true")])
+            .multipart(form);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await?
+            .error_for_status()
+            .wrap_err_with(|| format!("Failed to add {name} to IPFS"))?;
+        Ok(response.json::<AddResponse>().await?.hash)
+    }
+
+    /// Pins the metadata of the contract of the artifact, and the sources it references.
+    pub async fn pin_metadata(&self, root: &Path, artifact: &Path) -> Result<PinnedMetadata> {
+        let artifact: Value = fs::read_json_file(&root.join(artifact))?;
+        // The raw metadata is the one hashed by the compiler, the parsed one may be formatted
+        // differently.
+        let raw = match (&artifact["rawMetadata"], &artifact["metadata"]) {
+            (Value::String(raw), _) => raw.clone(),
+            (_, Value::Object(_)) => serde_json::to_string(&artifact["metadata"])?,
+            _ => eyre::bail!("The artifact has no metadata"),
+        };
+        let metadata: Value = serde_json::from_str(&raw)?;
+
+        let mut pinned = PinnedMetadata::default();
+        for (path, source) in metadata["sources"].as_object().into_iter().flatten() {
+            if source.get("content").is_some() {
+                continue
+            }
+            let content = std::fs::read(root.join(path))
+                .wrap_err_with(|| format!("Failed to read source {path}"))?;
+            let cid = self.add(path, content).await?;
+            if let Some(expected) = expected_cid(source) {
+                if expected != cid {
+                    warn!(%path, %expected, %cid, "the source changed since it was compiled");
+                }
+            }
+            pinned.sources.insert(path.clone(), cid);
+        }
+        pinned.metadata = self.add("metadata.json", raw.into_bytes()).await?;
+        Ok(pinned)
+    }
+}
+
+/// Returns the CID of a source of the metadata, from its `dweb:/ipfs/<cid>` URL.
+fn expected_cid(source: &Value) -> Option<&str> {
+    source["urls"].as_array()?.iter().find_map(|url| url.as_str()?.strip_prefix("dweb:/ipfs/"))
+}
+
+/// Records the CIDs of the metadata of a deployed contract in its file of the deployments
+/// registry, `deployments/<chain>/<name>.json`.
+pub fn record_cids(
+    root: &Path,
+    chain: u64,
+    name: &str,
+    address: Address,
+    pinned: &PinnedMetadata,
+) -> Result<PathBuf> {
+    let dir = root.join(DEPLOYMENTS_DIR);
+    let named = dir.join(Chain::from(chain).to_string());
+    let chain_dir = [named.clone(), dir.join(chain.to_string())]
+        .into_iter()
+        .find(|dir| dir.is_dir())
+        .unwrap_or(named);
+    fs::create_dir_all(&chain_dir)?;
+
+    let path = chain_dir.join(format!("{name}.json"));
+    let mut deployment = if path.is_file() {
+        fs::read_json_file::<Value>(&path)?
+    } else {
+        serde_json::json!({ "address": address })
+    };
+    let Some(object) = deployment.as_object_mut() else {
+        eyre::bail!("{} isn't a JSON object", path.display())
+    };
+    object.insert("ipfs".to_string(), serde_json::to_value(pinned)?);
+    fs::write_json_file(&path, &deployment)?;
+    Ok(path)
+}
+
+impl BroadcastedState {
+    /// Pins the metadata and sources of the contracts deployed by the script which were verified
+    /// to IPFS, and records their CIDs in the deployments registry.
+    pub async fn pin_to_ipfs(&self, api: &str, reports: &[VerificationReport]) -> Result<()> {
+        let root = &self.script_config.config.__root.0;
+        let client = IpfsClient::new(api, self.args.ipfs_token.clone());
+
+        shell::println("\nPinning the metadata of the verified contracts to IPFS")?;
+        for sequence in self.sequence.sequences() {
+            let verified = reports
+                .iter()
+                .filter(|report| report.chain == sequence.chain)
+                .flat_map(|report| report.verified.iter().map(|contract| contract.address))
+                .collect::<HashSet<_>>();
+            for tx in &sequence.transactions {
+                if !matches!(tx.opcode, CallKind::Create | CallKind::Create2) {
+                    continue
+                }
+                let (Some(name), Some(address)) = (&tx.contract_name, tx.contract_address) else {
+                    continue
+                };
+                if !verified.contains(&address) {
+                    continue
+                }
+                let Some(artifact) = self.artifact(name) else { continue };
+
+                let pinned = client.pin_metadata(root, &artifact.path).await?;
+                let path = record_cids(root, sequence.chain, name, address, &pinned)?;
+                shell::println(format!(
+                    "  {name} at {address}: ipfs://{} (recorded in {})",
+                    pinned.metadata,
+                    path.strip_prefix(root).unwrap_or(&path).display()
+                ))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn artifact(&self, name: &str) -> Option<&ArtifactId> {
+        self.build_data.highlevel_known_contracts.keys().find(|id| id.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn records_cids() {
+        let root = tempfile::tempdir().unwrap();
+        let address = address!("5FbDB2315678afecb367f032d93F642f64180aa3");
+        let pinned = PinnedMetadata {
+            metadata: "QmMetadata".to_string(),
+            sources: BTreeMap::from([("src/Token.sol".to_string(), "QmToken".to_string())]),
+        };
+
+        let path = record_cids(root.path(), 1, "Token", address, &pinned).unwrap();
+        assert_eq!(path, root.path().join("deployments/mainnet/Token.json"));
+        let deployment: Value = fs::read_json_file(&path).unwrap();
+        assert_eq!(deployment["ipfs"]["metadata"], "QmMetadata");
+        assert_eq!(deployment["ipfs"]["sources"]["src/Token.sol"], "QmToken");
+
+        // Existing files of the registry are kept.
+        std::fs::create_dir_all(root.path().join("deployments/31337")).unwrap();
+        std::fs::write(
+            root.path().join("deployments/31337/Token.json"),
+            r#"{"address":"0x5FbDB2315678afecb367f032d93F642f64180aa3","owner":"0x01"}"#,
+        )
+        .unwrap();
+        let path = record_cids(root.path(), 31337, "Token", address, &pinned).unwrap();
+        let deployment: Value = fs::read_json_file(&path).unwrap();
+        assert_eq!(deployment["owner"], "0x01");
+        assert_eq!(deployment["ipfs"]["metadata"], "QmMetadata");
+    }
+
+    #[test]
+    fn reads_source_cids() {
+        let source = serde_json::json!({
+            "keccak256": "0x00",
+            "urls": ["bzz-raw://00", "dweb:/ipfs/QmSource"],
+        });
+        assert_eq!(expected_cid(&source), Some("QmSource"));
+        assert_eq!(expected_cid(&serde_json::json!({})), None);
+    }
+}
@@ -0,0 +1,146 @@
+use alloy_dyn_abi::DynSolValue;
+use alloy_primitives::{address, fixed_bytes, Address, Selector, U256};
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, NameOrAddress, TransactionRequest,
+};
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::types::{ToAlloy, ToEthers};
+use foundry_config::{Chain, NamedChain};
+
+/// The `GasPriceOracle` predeploy of OP stack chains.
+const GAS_PRICE_ORACLE: Address = address!("420000000000000000000000000000000000000F");
+/// `getL1Fee(bytes)`
+const GET_L1_FEE: Selector = fixed_bytes!("49948e0e");
+
+/// The `NodeInterface` precompile of Arbitrum chains, which is only reachable with `eth_call`.
+const NODE_INTERFACE: Address = address!("00000000000000000000000000000000000000C8");
+/// `gasEstimateL1Component(address,bool,bytes)`
+const GAS_ESTIMATE_L1_COMPONENT: Selector = fixed_bytes!("77d488a2");
+
+/// How a rollup charges for posting the data of its transactions to L1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum L1FeeModel {
+    /// The L1 data fee is charged on top of the L2 execution gas, and quoted by the
+    /// `GasPriceOracle` predeploy.
+    OpStack,
+    /// The L1 data fee is charged as extra L2 gas, which `eth_estimateGas` already includes, and
+    /// is quoted by the `NodeInterface` precompile.
+    Arbitrum,
+}
+
+impl L1FeeModel {
+    /// Returns the L1 fee model of the chain, if it's a rollup with one.
+    ///
+    /// OP stack chains are recognized by the code of their `GasPriceOracle` predeploy.
+    pub async fn detect<M: Middleware>(provider: &M, chain: u64) -> Option<Self> {
+        if matches!(
+            Chain::from(chain).named(),
+            Some(
+                NamedChain::Arbitrum |
+                    NamedChain::ArbitrumNova |
+                    NamedChain::ArbitrumGoerli |
+                    NamedChain::ArbitrumSepolia
+            )
+        ) {
+            return Some(Self::Arbitrum)
+        }
+        let code = provider.get_code(GAS_PRICE_ORACLE.to_ethers(), None).await.ok()?;
+        (!code.is_empty()).then_some(Self::OpStack)
+    }
+
+    /// Whether the L1 fee is paid on top of the gas of the transaction.
+    pub fn is_extra(&self) -> bool {
+        matches!(self, Self::OpStack)
+    }
+
+    /// Returns the L1 data fee of the transaction, in wei.
+    pub async fn estimate<M: Middleware>(&self, provider: &M, tx: &TypedTransaction) -> Result<U256>
+    where
+        M::Error: 'static,
+    {
+        let (to, calldata) = match self {
+            Self::OpStack => (GAS_PRICE_ORACLE, op_stack_calldata(tx)),
+            Self::Arbitrum => (NODE_INTERFACE, arbitrum_calldata(tx)),
+        };
+        let call: TypedTransaction =
+            TransactionRequest::new().to(to.to_ethers()).data(calldata).into();
+        let output = provider.call(&call, None).await?;
+        match self {
+            Self::OpStack => decode_word(&output, 0),
+            Self::Arbitrum => {
+                let gas = decode_word(&output, 0)?;
+                let base_fee = decode_word(&output, 1)?;
+                Ok(gas.saturating_mul(base_fee))
+            }
+        }
+    }
+}
+
+fn op_stack_calldata(tx: &TypedTransaction) -> Vec<u8> {
+    let encoded = DynSolValue::Tuple(vec![DynSolValue::Bytes(tx.rlp().to_vec())]);
+    [GET_L1_FEE.as_slice(), &encoded.abi_encode_params()].concat()
+}
+
+fn arbitrum_calldata(tx: &TypedTransaction) -> Vec<u8> {
+    let to = match tx.to() {
+        Some(NameOrAddress::Address(to)) => Some(to.to_alloy()),
+        _ => None,
+    };
+    let data = tx.data().map(|data| data.to_vec()).unwrap_or_default();
+    let encoded = DynSolValue::Tuple(vec![
+        DynSolValue::Address(to.unwrap_or_default()),
+        DynSolValue::Bool(to.is_none()),
+        DynSolValue::Bytes(data),
+    ]);
+    [GAS_ESTIMATE_L1_COMPONENT.as_slice(), &encoded.abi_encode_params()].concat()
+}
+
+/// Decodes the `index`th word of an ABI encoded output.
+fn decode_word(output: &[u8], index: usize) -> Result<U256> {
+    let word = output
+        .get(index * 32..(index + 1) * 32)
+        .ok_or_else(|| eyre::eyre!("unexpected L1 fee oracle output: 0x{}", hex::encode(output)))?;
+    Ok(U256::from_be_slice(word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_oracle_calls() {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(GAS_PRICE_ORACLE.to_ethers())
+            .data(vec![0xab; 3])
+            .nonce(1)
+            .into();
+
+        let calldata = op_stack_calldata(&tx);
+        let rlp = tx.rlp();
+        assert_eq!(&calldata[..4], GET_L1_FEE.as_slice());
+        assert_eq!(U256::from_be_slice(&calldata[4..36]), U256::from(32));
+        assert_eq!(U256::from_be_slice(&calldata[36..68]), U256::from(rlp.len()));
+        assert_eq!(&calldata[68..68 + rlp.len()], rlp.as_ref());
+
+        let calldata = arbitrum_calldata(&tx);
+        assert_eq!(&calldata[..4], GAS_ESTIMATE_L1_COMPONENT.as_slice());
+        assert_eq!(&calldata[16..36], GAS_PRICE_ORACLE.as_slice());
+        assert_eq!(calldata[67], 0);
+        assert_eq!(U256::from_be_slice(&calldata[100..132]), U256::from(3));
+        assert_eq!(&calldata[132..135], &[0xab; 3]);
+
+        let creation: TypedTransaction = TransactionRequest::new().data(vec![0x60]).into();
+        assert_eq!(arbitrum_calldata(&creation)[67], 1);
+    }
+
+    #[test]
+    fn decodes_words() {
+        let mut output = vec![0; 64];
+        output[31] = 7;
+        output[63] = 9;
+        assert_eq!(decode_word(&output, 0).unwrap(), U256::from(7));
+        assert_eq!(decode_word(&output, 1).unwrap(), U256::from(9));
+        assert!(decode_word(&output, 2).is_err());
+    }
+}
@@ -7,21 +7,31 @@ use self::transaction::AdditionalContract;
 use crate::runner::ScriptRunner;
 use alloy_json_abi::{Function, JsonAbi};
 use alloy_primitives::{Address, Bytes, Log, U256};
+pub use anvil::AnvilFork;
 use broadcast::next_nonce;
 use build::PreprocessedState;
-use clap::{Parser, ValueHint};
+pub use build::{BuildData, LinkedBuildData};
+pub use call_graph::CallGraphFormat;
+use chaos::ChaosPlan;
+use clap::{Parser, Subcommand, ValueEnum, ValueHint};
+use deploy_checks::DeployChecks;
 use dialoguer::Confirm;
+use environments::Environment;
+use ephemeral::EphemeralDeployer;
 use ethers_signers::Signer;
 use eyre::{ContextCompat, Result, WrapErr};
-use forge_verify::RetryArgs;
-use foundry_cli::{opts::CoreBuildArgs, utils::LoadConfig};
+use forge_verify::{provider::VerificationProviderType, RetryArgs};
+use foundry_cli::{
+    opts::{CoreBuildArgs, WatchArgs},
+    utils::{DotenvLayers, LoadConfig},
+};
 use foundry_common::{
     abi::{encode_function_args, get_func},
     compile::SkipBuildFilter,
     errors::UnlinkedByteCode,
     evm::{Breakpoints, EvmArgs},
-    provider::ethers::RpcUrl,
-    shell,
+    provider::rpc_trace,
+    shell, tr,
     types::ToAlloy,
     CONTRACT_MAX_SIZE, SELECTOR_LEN,
 };
@@ -30,52 +40,166 @@ use foundry_config::{
     figment,
     figment::{
         value::{Dict, Map},
-        Metadata, Profile, Provider,
+        Figment, Metadata, Profile, Provider,
     },
+    providers::ChainProfileProvider,
     Config,
 };
 use foundry_evm::{
-    backend::Backend,
     constants::DEFAULT_CREATE2_DEPLOYER,
+    coverage::HitMaps,
     debug::DebugArena,
     executors::ExecutorBuilder,
     inspectors::{
-        cheatcodes::{BroadcastableTransactions, ScriptWallets},
-        CheatsConfig,
+        cheatcodes::{BroadcastableTransactions, ScriptParams, ScriptWallets},
+        CheatsConfig, ConsoleListener,
     },
     opts::EvmOpts,
     traces::Traces,
+    utils::StateChangeset,
 };
 use foundry_wallets::MultiWalletOpts;
+pub use history::HistoryArgs;
+pub use plan::{ExecuteArgs, PlanArgs, ScriptPlan};
+use policy::TxPolicy;
+use pool::BackendPool;
+use registry::Registry;
+pub use replay::ReplayTxArgs;
+pub use scaffold::InitDeployArgs;
+use schedule::BroadcastAt;
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+use templates::DeploymentTemplate;
+use tokens::{print_token_amounts, TokenAmount, TokenList};
+pub use trace_export::TraceFormat;
+use vanity::{VanityMiner, VanityPrefix, VanitySalt};
 use yansi::Paint;
 
+mod addresses;
+mod alias;
+mod anvil;
 mod artifacts;
 mod broadcast;
 mod build;
+mod build_cache;
+mod call_graph;
+mod calldata;
+mod chain_guard;
+mod chain_metadata;
+mod changelog;
+mod chaos;
+mod console;
+mod coverage;
+mod cross_chain;
+mod deny_list;
+mod deploy_checks;
+mod deposits;
+mod diff;
+mod dump_state;
+mod environments;
+mod ephemeral;
+mod events;
 mod execute;
+mod explain;
+mod failover;
+mod fee_currency;
+mod freeze;
+mod funding;
+mod gas_profile;
+mod git_status;
+mod governance;
+mod history;
+mod hooks;
+mod html_report;
+mod interfaces;
+mod ipfs;
+mod l1_fees;
+mod libraries;
+mod lock;
+mod manifest;
+mod metrics;
 mod multi_sequence;
+mod offline;
+mod onchain_diff;
+mod ownership;
+mod pending;
+mod plan;
+mod policy;
+mod pool;
+mod precompiles;
 mod providers;
 mod receipts;
+mod reconciliation;
+mod registry;
+mod replay;
 mod resume;
 mod runner;
+mod scaffold;
+mod schedule;
+mod security;
+mod selection;
 mod sequence;
 mod simulate;
+mod size_advisor;
+mod state_diff;
+mod templates;
+mod tokens;
+mod trace_export;
 mod transaction;
+mod units;
+mod upgrades;
+mod vanity;
 mod verify;
+mod warm_fork;
+mod watchdog;
 
 // Loads project's figment and merges the build cli arguments into it
 foundry_config::merge_impl_figment_convert!(ScriptArgs, opts, evm_opts);
 
+/// Default relative percentage to multiply gas estimates by.
+const DEFAULT_GAS_ESTIMATE_MULTIPLIER: u64 = 130;
+
+/// Script settings of a `[profile.chain.<chain>]` section that are not part of [Config].
+#[derive(Debug, Default, Deserialize)]
+struct ScriptChainProfile {
+    verifier: Option<String>,
+    verifier_url: Option<String>,
+    legacy: Option<bool>,
+    slow: Option<bool>,
+    gas_estimate_multiplier: Option<u64>,
+    fee_currency: Option<Address>,
+}
+
+impl ScriptChainProfile {
+    /// The keys of the settings.
+    const KEYS: &'static [&'static str] =
+        &["verifier", "verifier_url", "legacy", "slow", "gas_estimate_multiplier", "fee_currency"];
+}
+
 /// CLI arguments for `forge script`.
 #[derive(Clone, Debug, Default, Parser)]
+#[command(
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true,
+    args_override_self = true
+)]
 pub struct ScriptArgs {
+    #[command(subcommand)]
+    pub command: Option<ScriptSubcommand>,
+
     /// The contract you want to run. Either the file path or contract name.
     ///
     /// If multiple contracts exist in the same file you must specify the target contract with
     /// --target-contract.
-    #[arg(value_hint = ValueHint::FilePath)]
+    ///
+    /// Since `--watch` takes any number of paths, `forge script --watch Foo.s.sol` runs
+    /// `Foo.s.sol` and watches the default directories.
+    #[arg(value_hint = ValueHint::FilePath, required_unless_present = "watch", default_value = "")]
     pub path: String,
 
     /// Arguments to pass to the script function.
@@ -104,17 +228,47 @@ pub struct ScriptArgs {
     #[arg(long)]
     pub legacy: bool,
 
+    /// Pays the fees in this ERC-20 token instead of the native currency, on chains which support
+    /// fee currencies with CIP-64 transactions, e.g. Celo.
+    ///
+    /// Usually set with `fee_currency` in the `[profile.chain.<chain>]` section.
+    #[arg(long, conflicts_with = "legacy", value_name = "TOKEN")]
+    pub fee_currency: Option<Address>,
+
     /// Broadcasts the transactions.
-    #[arg(long)]
+    #[arg(long, conflicts_with = "watch")]
     pub broadcast: bool,
 
+    /// Waits until the given time before broadcasting the signed transactions, e.g. for a
+    /// governance window to open.
+    ///
+    /// Either a unix timestamp (`1700000000` or `timestamp:1700000000`), or a block number
+    /// (`block:19000000`) in which the first transaction can be included. Nonces are re-validated
+    /// and gas prices estimated when the window opens.
+    #[arg(long, requires = "broadcast", value_name = "TIMESTAMP|block:NUMBER")]
+    pub broadcast_at: Option<BroadcastAt>,
+
     /// Skips on-chain simulation.
     #[arg(long)]
     pub skip_simulation: bool,
 
+    /// Forks from the block of the previous simulation against the same RPC if it's at most
+    /// SECONDS old, so that the state it fetched is read from the on-disk RPC cache instead of
+    /// being downloaded again.
+    ///
+    /// Meant for iterating on a script, so it can't be used when broadcasting.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        conflicts_with_all = &["broadcast", "resume", "fork_block_number"]
+    )]
+    pub reuse_fork_block: Option<u64>,
+
     /// Relative percentage to multiply gas estimates by.
-    #[arg(long, short, default_value = "130")]
-    pub gas_estimate_multiplier: u64,
+    ///
+    /// Defaults to the `gas_estimate_multiplier` of the chain profile, or 130.
+    #[arg(long, short)]
+    pub gas_estimate_multiplier: Option<u64>,
 
     /// Send via `eth_sendTransaction` using the `--from` argument or `$ETH_FROM` as sender
     #[arg(
@@ -130,7 +284,7 @@ pub struct ScriptArgs {
     ///
     /// Example: If transaction N has a nonce of 22, then the account should have a nonce of 22,
     /// otherwise it fails.
-    #[arg(long)]
+    #[arg(long, conflicts_with = "watch")]
     pub resume: bool,
 
     /// If present, --resume or --verify will be assumed to be a multi chain deployment.
@@ -143,17 +297,270 @@ pub struct ScriptArgs {
     #[arg(long)]
     pub debug: bool,
 
+    /// Open the debugger at the failing step when a transaction reverts during the on-chain
+    /// simulation.
+    #[arg(long)]
+    pub debug_on_failure: bool,
+
+    /// Prints the storage slots changed by each simulated transaction.
+    ///
+    /// Slots of the script's contracts are labeled with their state variable, e.g.
+    /// `Vault.totalAssets` or `Vault.owners[0x…]`, using the storage layout output of the
+    /// compiler.
+    #[arg(long)]
+    pub state_diff: bool,
+
+    /// Prints the gas used by the simulated transactions, by contract and function.
+    ///
+    /// The profile is also written as JSON to the `gas-profiles` directory of the cache.
+    #[arg(long)]
+    pub gas_profile: bool,
+
+    /// Writes a graph of the contracts deployed and called by the simulated transactions to the
+    /// `call-graphs` directory of the cache.
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub call_graph: Option<CallGraphFormat>,
+
+    /// Writes the state of the chain after the simulated transactions to a file, which can be
+    /// loaded with `anvil --load-state` to test against the deployed system.
+    ///
+    /// Scripts running on several chains get a file per chain, suffixed with its chain id.
+    #[arg(long, value_name = "PATH")]
+    pub dump_state: Option<PathBuf>,
+
+    /// Writes a self-contained HTML report of the simulated run to this path: its transactions
+    /// and decoded calls, state diff, gas, predicted addresses and warnings, e.g. to attach to a
+    /// change request for reviewers who don't use the CLI.
+    #[arg(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    pub html_report: Option<PathBuf>,
+
+    /// Exports the events emitted by the simulated and the broadcast transactions to an
+    /// `events.json` file next to the broadcast artifact.
+    ///
+    /// The events are decoded with the ABIs of all the known contracts.
+    #[arg(long)]
+    pub export_events: bool,
+
+    /// Traces the broadcast transactions with `debug_traceTransaction` once they are mined, and
+    /// reports where they wrote different storage slots or used a different amount of gas than
+    /// in the simulation, which hints at a state drift or a chain specific behavior.
+    ///
+    /// Requires an RPC which supports the `prestateTracer`.
+    #[arg(long, requires = "broadcast")]
+    pub diff_onchain: bool,
+
+    /// Impersonates the governance contract, e.g. a Safe or a timelock, in the simulation to take
+    /// the steps which complete the proposals of the script: accepting the ownership of the
+    /// contracts transferred to it, and executing the calls scheduled on it once their delay
+    /// passed.
+    ///
+    /// The steps are only simulated, only the proposals are broadcast.
+    #[arg(long, value_name = "ADDRESS", conflicts_with = "skip_simulation")]
+    pub rehearse_governance: Option<Address>,
+
+    /// Reports the lines of the script, and of the constructors and initializers of the contracts
+    /// it deploys, which were executed, and lists the ones which were not.
+    ///
+    /// An LCOV report is also written to the `coverage` directory of the cache.
+    #[arg(long)]
+    pub coverage: bool,
+
+    /// Logs every JSON-RPC request of the fork, simulation and broadcast stages, with its response
+    /// and latency, to the given file as JSON lines.
+    ///
+    /// API keys in the RPC URLs, signed transactions and signing payloads are redacted.
+    #[arg(long, value_name = "FILE")]
+    pub rpc_trace: Option<PathBuf>,
+
+    /// Checks the simulated transactions for risky patterns, such as contracts left owned by a
+    /// sender or proxies which are never initialized.
+    ///
+    /// Individual checks can be silenced with `ignored_checks` in the policy file. The calls to
+    /// functions unknown to the project are decoded with the signature database, unless
+    /// `--offline` is passed.
+    ///
+    /// Also audits the deployed contracts, which fails the simulation when a contract reports
+    /// support for an ERC-165 interface it doesn't implement, or when a diamond cut of the script
+    /// routes a selector which is already routed or which the facet doesn't implement.
+    #[arg(long)]
+    pub security_checks: bool,
+
+    /// Checks that the proxies upgraded by the script support the storage layout of their new
+    /// implementation, and fails before broadcasting when a variable of the current
+    /// implementation is moved or retyped.
+    ///
+    /// Requests the storage layout output from the compiler, and records the layouts of the
+    /// deployed contracts in the deployments registry after the broadcast.
+    #[arg(long)]
+    pub check_upgrades: bool,
+
     /// Makes sure a transaction is sent,
     /// only after its previous one has been confirmed and succeeded.
     #[arg(long)]
     pub slow: bool,
 
+    /// Serves broadcast progress metrics in the Prometheus format on `http://<ADDR>/metrics`.
+    ///
+    /// Useful to monitor long-running broadcasts.
+    #[arg(long, requires = "broadcast", value_name = "ADDR")]
+    pub metrics_addr: Option<std::net::SocketAddr>,
+
+    /// Snapshots the balances of the senders and of the recipients of value before and after
+    /// broadcasting, and writes a reconciliation report of the gas spent, the value moved and the
+    /// unexpected balance changes next to the broadcast logs.
+    #[arg(long, requires = "broadcast")]
+    pub reconcile: bool,
+
+    /// The RPC URL or alias of the L2 which the L1→L2 deposits of the script are sent to, to
+    /// predict their outcomes on it.
+    ///
+    /// Deposits to OP stack portals and Arbitrum inboxes are simulated on the latest block of the
+    /// L2, with the ETH they mint credited to their senders.
+    #[arg(long, value_name = "URL")]
+    pub l2_rpc_url: Option<String>,
+
+    /// Simulates the transactions in order atop the `pending` block before broadcasting them, to
+    /// catch conflicts with in-flight transactions, e.g. other pending transactions of the
+    /// senders.
+    ///
+    /// The whole bundle is simulated with `eth_simulateV1` when the RPC supports it, otherwise
+    /// only the nonces of the senders are checked against their pending ones.
+    #[arg(long, requires = "broadcast")]
+    pub simulate_pending: bool,
+
+    /// Pauses sending when the chain produced no block for this many times its expected block
+    /// time, e.g. during an outage of the sequencer of an L2, and resumes once blocks flow again.
+    ///
+    /// The `chain_halt` hook of `[script_hooks]` is run when the chain halts and resumes.
+    #[arg(long, requires = "broadcast", value_name = "BLOCKS")]
+    pub halt_after: Option<u32>,
+
+    /// Removes the locks of other runs broadcasting the script from the same senders, e.g. left
+    /// by a killed job.
+    ///
+    /// Locks are also taken over once their holder stopped refreshing them for 2 minutes.
+    #[arg(long, requires = "broadcast")]
+    pub force_unlock: bool,
+
+    /// The URL of a lock server shared by the machines which broadcast the script, e.g. CI
+    /// runners, in addition to the lock files of the project.
+    ///
+    /// Locks are taken with `PUT <URL>/<script>-<chain>-<sender>` and released with `DELETE`.
+    #[arg(long, env = "FOUNDRY_LOCK_URL", value_name = "URL")]
+    pub lock_url: Option<String>,
+
     /// Disables interactive prompts that might appear when deploying big contracts.
     ///
     /// For more info on the contract size limit, see EIP-170: <https://eips.ethereum.org/EIPS/eip-170>
     #[arg(long)]
     pub non_interactive: bool,
 
+    /// Confirms broadcasting to a chain listed in `confirm_chain_ids`, instead of typing its name.
+    #[arg(long, value_name = "CHAIN_ID")]
+    pub yes_chain: Vec<u64>,
+
+    /// Deploys again contracts which are already in the `deployments` registry of the chain.
+    ///
+    /// The replaced addresses are recorded in `deployments/superseded.json`.
+    #[arg(long, conflicts_with = "upgrade")]
+    pub redeploy: bool,
+
+    /// Like `--redeploy`, but records the new deployments as upgrades of the replaced ones.
+    #[arg(long)]
+    pub upgrade: bool,
+
+    /// The token which overrides the freeze windows of the policy.
+    #[arg(long, value_name = "TOKEN")]
+    pub freeze_override: Option<String>,
+
+    /// Lets the transactions interact with an address of the deny lists of the policy.
+    ///
+    /// Each override is recorded with its reason in `deny-list-overrides.jsonl` in the broadcast
+    /// directory.
+    #[arg(long, value_name = "ADDRESS", requires = "deny_override_reason")]
+    pub allow_denied: Vec<Address>,
+
+    /// Why the addresses passed with `--allow-denied` may be interacted with.
+    #[arg(long, value_name = "REASON")]
+    pub deny_override_reason: Option<String>,
+
+    /// Tops up the balances of the senders to this amount before broadcasting, e.g. `0.5ether`.
+    ///
+    /// Only allowed on testnets. The funds come from the faucet of `--faucet-url` or the account
+    /// of `--funding-key`.
+    #[arg(long, value_parser = foundry_cli::utils::parse_ether_value, value_name = "AMOUNT")]
+    pub fund_senders: Option<U256>,
+
+    /// The faucet `--fund-senders` requests funds from.
+    ///
+    /// It's sent a POST request per sender with a JSON body of its `address`, the `chainId` and
+    /// the `amount` in wei, and the balance is polled until the funds arrive.
+    #[arg(long, env = "FAUCET_URL", value_name = "URL")]
+    pub faucet_url: Option<String>,
+
+    /// The private key of the account `--fund-senders` and `--ephemeral-deployer` transfer funds
+    /// from.
+    #[arg(long, env = "FUNDING_PRIVATE_KEY", hide_env_values = true, value_name = "KEY")]
+    pub funding_key: Option<String>,
+
+    /// Sends the transactions from a key generated for this run, which starts at nonce 0 on every
+    /// chain, so that the addresses of its CREATE deployments are deterministic without a vanity
+    /// key.
+    ///
+    /// Before broadcasting, the key is funded with the cost of its transactions by the account of
+    /// `--funding-key`, and its remaining funds are swept back to that account at the end. The key
+    /// is kept in `cache/ephemeral-deployers` until then, and the one recorded in the broadcast
+    /// sequence is reused with `--resume`.
+    #[arg(long, requires = "funding_key", conflicts_with_all = ["sender", "fund_senders"])]
+    pub ephemeral_deployer: bool,
+
+    /// Mines the CREATE2 salts of the contracts of `--vanity-contract`, so that they're deployed
+    /// at addresses starting with this hex prefix, e.g. `0x0000`.
+    ///
+    /// The script reads the salt of each contract from a `${salts.<contract>}` argument or
+    /// parameter, which is resolved like `${deployments.<contract>}`. The salts are recorded in
+    /// the plan and, once broadcast, in `deployments/salts.json`.
+    #[arg(long, value_name = "HEX", requires = "vanity_contracts")]
+    pub vanity_prefix: Option<VanityPrefix>,
+
+    /// A contract whose CREATE2 salt is mined with `--vanity-prefix`.
+    #[arg(long = "vanity-contract", value_name = "CONTRACT", requires = "vanity_prefix")]
+    pub vanity_contracts: Vec<String>,
+
+    /// How long to mine each salt for, in seconds, before giving up.
+    #[arg(long, value_name = "SECONDS", default_value = "60")]
+    pub vanity_timeout: u64,
+
+    /// How many salts to try for each contract before giving up.
+    #[arg(long, value_name = "ATTEMPTS")]
+    pub vanity_max_attempts: Option<u64>,
+
+    /// Set to the salts of the plan broadcast by `forge script execute`, which are reused instead
+    /// of mining new ones.
+    #[arg(skip)]
+    pub(crate) planned_salts: Vec<VanitySalt>,
+
+    /// Broadcasts a multi-chain script even if contracts deployed with CREATE2 land at different
+    /// addresses across its chains.
+    #[arg(long)]
+    pub allow_address_mismatch: bool,
+
+    /// Checks that the script can be resumed safely: it's broadcast to a local Anvil fork of its
+    /// chain, interrupted and partly rolled back at random, then resumed with `--resume`.
+    ///
+    /// Requires `anvil`. The transactions are sent from the impersonated senders, and the
+    /// broadcast artifacts are written to a temporary directory.
+    #[arg(long, conflicts_with_all = ["broadcast", "resume", "verify", "watch"])]
+    pub chaos: bool,
+
+    /// The seed picking where `--chaos` interrupts the broadcast, to reproduce a failed run.
+    #[arg(long, requires = "chaos", value_name = "SEED")]
+    pub chaos_seed: Option<u64>,
+
+    /// Set on the runs of the script against the Anvil fork of `--chaos`.
+    #[arg(skip)]
+    pub(crate) chaos_plan: Option<Arc<ChaosPlan>>,
+
     /// The Etherscan (or equivalent) API key
     #[arg(long, env = "ETHERSCAN_API_KEY", value_name = "KEY")]
     pub etherscan_api_key: Option<String>,
@@ -162,10 +569,111 @@ pub struct ScriptArgs {
     #[arg(long)]
     pub verify: bool,
 
+    /// Signs a manifest of the broadcast artifact and verification report with the wallet of the
+    /// given address.
+    ///
+    /// The manifest contains the hash of each file and an EIP-191 signature over their combined
+    /// digest.
+    #[arg(long, requires = "broadcast", conflicts_with = "unlocked", value_name = "ADDRESS")]
+    pub sign_manifest: Option<Address>,
+
+    /// Appends the changelog entry of the run to this Markdown file, e.g. `CHANGELOG.md`.
+    ///
+    /// The entry, with the deployed contracts, the upgraded proxies, the parameters and the git
+    /// commit of the run, is always written next to the broadcast artifact.
+    #[arg(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    pub changelog: Option<PathBuf>,
+
+    /// With `--verify`, pins the metadata and sources of the verified contracts to IPFS through
+    /// the HTTP API of this node or pinning service, e.g. `http://127.0.0.1:5001`.
+    ///
+    /// The CIDs are recorded in the `deployments` directory, so that the sources can be retrieved
+    /// from the metadata hash of the bytecode without a block explorer.
+    #[arg(long, env = "IPFS_API_URL", value_name = "URL")]
+    pub ipfs_api: Option<String>,
+
+    /// The bearer token of the IPFS pinning service.
+    #[arg(long, env = "IPFS_API_TOKEN", hide_env_values = true, value_name = "TOKEN")]
+    pub ipfs_token: Option<String>,
+
     /// Output results in JSON format.
     #[arg(long)]
     pub json: bool,
 
+    /// The format of the traces.
+    ///
+    /// The `json` and `flamegraph` formats write the traces of the execution and of the
+    /// simulation to the `traces` directory of the cache, as decoded call trees and as
+    /// speedscope flamegraphs of the gas usage respectively.
+    #[arg(long, value_enum, default_value_t, value_name = "FORMAT")]
+    pub trace_format: TraceFormat,
+
+    /// Prints the variables loaded from the `.env`, `.env.<chain>` and `.env.local` files, with
+    /// secrets redacted.
+    #[arg(long)]
+    pub print_env: bool,
+
+    /// A TOML or JSON file with parameters for the script.
+    ///
+    /// The values are read in the script with the `vm.param*` and `vm.paramOr` cheatcodes, which
+    /// revert if a value doesn't match the requested type.
+    ///
+    /// Values, like the script arguments, can reference the address of a contract deployed by the
+    /// script with `${deployments.<CONTRACT_NAME>}`. The script is executed again with the
+    /// predicted addresses until they settle.
+    ///
+    /// The `[checks.<CONTRACT_NAME>]` tables map view functions, e.g. `"owner()(address)"`, to
+    /// the values they must return once the contract is deployed. They are called after each
+    /// deployment is confirmed, and the broadcast stops if any returns something else.
+    #[arg(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    pub params: Option<PathBuf>,
+
+    /// Enables a feature flag of the script, read with `vm.isFeatureEnabled`, on top of the
+    /// `[features]` table of the parameters file.
+    ///
+    /// Flags that aren't set are disabled. The flags read by the script are recorded in the plan.
+    #[arg(long = "feature", value_name = "NAME")]
+    pub features: Vec<String>,
+
+    /// A deployment environment of the `environments.toml` file of the project, e.g. `staging`,
+    /// which sets the chain, RPC URL, sender, parameters file and policy of the script.
+    ///
+    /// Values passed on the command line take precedence. The broadcast artifacts are written to
+    /// a directory of the environment, e.g. `broadcast/staging`.
+    #[arg(long = "env", value_name = "NAME")]
+    pub environment: Option<String>,
+
+    /// Prints the fully resolved configuration the script would run with, and where each value
+    /// comes from, then exits without compiling or running the script.
+    #[arg(long)]
+    pub explain_config: bool,
+
+    /// Fails before compiling if `foundry.toml` has unknown keys, e.g. `oklink_api_kay`, or
+    /// references environment variables that are not set.
+    #[arg(long)]
+    pub strict_config: bool,
+
+    /// A transaction policy file which is enforced before broadcasting, on top of the global
+    /// `~/.foundry/policy.toml`.
+    ///
+    /// Defaults to `policy.toml` in the project root, if it exists.
+    #[arg(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    pub policy: Option<PathBuf>,
+
+    /// A token list which lets the script arguments and parameters express amounts of tokens,
+    /// e.g. `"1000 USDC"`, resolved with the decimals of the token on the chain of the script.
+    ///
+    /// Defaults to `tokens.toml` in the project root, if it exists.
+    #[arg(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    pub tokens: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub watch: WatchArgs,
+
+    /// Show how the planned transactions differ from the ones of the previous run.
+    #[arg(long)]
+    pub diff_plan: bool,
+
     /// Gas price for legacy transactions, or max fee per gas for EIP1559 transactions.
     #[arg(
         long,
@@ -197,33 +705,276 @@ pub struct ScriptArgs {
     pub retry: RetryArgs,
 }
 
+/// Subcommands of `forge script`, which take the place of the script path.
+#[derive(Clone, Debug, Subcommand)]
+pub enum ScriptSubcommand {
+    /// Scaffold a deployment layout in the project: a deploy script, per-chain params files, a
+    /// deployments registry and the verification settings.
+    InitDeploy(InitDeployArgs),
+
+    /// Simulate a script and write its transactions to a plan, without signing them.
+    Plan(PlanArgs),
+
+    /// Sign, broadcast and verify the transactions of a plan written by `forge script plan`.
+    Execute(ExecuteArgs),
+
+    /// Replay a mined transaction, e.g. a deployment, on a fork of its block and decode it with
+    /// the local artifacts, to inspect it in the debugger.
+    ReplayTx(ReplayTxArgs),
+
+    /// Query the history of the broadcast runs of the project, e.g. when a contract was last
+    /// deployed on a chain and what it cost.
+    History(HistoryArgs),
+}
+
+impl ScriptSubcommand {
+    pub async fn run(self) -> Result<()> {
+        match self {
+            Self::InitDeploy(cmd) => cmd.run(),
+            Self::Plan(cmd) => cmd.run().await,
+            Self::Execute(cmd) => cmd.run().await,
+            Self::ReplayTx(cmd) => cmd.run().await,
+            Self::History(cmd) => cmd.run(),
+        }
+    }
+}
+
 // === impl ScriptArgs ===
 
 impl ScriptArgs {
-    async fn preprocess(self) -> Result<PreprocessedState> {
+    async fn preprocess(mut self) -> Result<PreprocessedState> {
+        if let Some(path) = &self.rpc_trace {
+            rpc_trace::init(path).wrap_err_with(|| {
+                format!("failed to open the RPC trace file {}", path.display())
+            })?;
+        }
+
+        let (mut config, mut evm_opts) = self.load_config_and_evm_opts_emit_warnings()?;
+        if let Some(name) = &self.environment {
+            let environment = Environment::load(&config.__root.0, name)?;
+            self.apply_environment(environment);
+            (config, evm_opts) = self.load_config_and_evm_opts()?;
+        }
+
+        // Fail before anything reaches the network, and only detect the chain of the RPC when
+        // online.
+        if config.offline {
+            self.check_offline(&evm_opts)?;
+        }
+        let chain = if config.offline {
+            config.chain
+        } else {
+            config.chain.or_else(|| evm_opts.get_remote_chain_id())
+        };
+
+        // Layer `.env`, `.env.<chain>` and `.env.local` on top of the environment loaded on
+        // startup, and reload the config in case it references any of the new values.
+        let dotenv = DotenvLayers::load(&config.__root.0, chain)?;
+        if dotenv.apply(&config.__root.0)? {
+            (config, evm_opts) = self.load_config_and_evm_opts()?;
+        }
+        if self.print_env {
+            dotenv.print();
+        }
+
+        // Apply the `[profile.chain.<chain>]` overrides, if the chain is known.
+        if let Some(chain) = chain {
+            let chain_profile = ChainProfileProvider::new(config.get_config_path(), chain);
+            if let Some(overrides) = chain_profile.overrides()? {
+                trace!(target: "script", %chain, "applying chain profile");
+                self.apply_chain_profile(overrides)?;
+                (config, evm_opts) = self.load_config_with_chain_profile(chain_profile)?;
+            }
+        }
+
+        // Default to the verifier of the profile, unless one was passed on the command line.
+        self.verifier.apply_config(&config)?;
+
+        if self.strict_config {
+            let issues = config.strict_issues(ScriptChainProfile::KEYS)?;
+            if !issues.is_empty() {
+                let issues = issues.iter().map(|issue| format!("\n  - {issue}"));
+                eyre::bail!("Invalid config in strict mode:{}", issues.collect::<String>());
+            }
+        }
+
+        // Check again, as the `.env` files and the chain profile may have set an RPC url.
+        if config.offline {
+            self.check_offline(&evm_opts)?;
+        }
+
+        // The deployer of a resumed run is loaded from its broadcast sequence, and the key is only
+        // kept when it's funded by a broadcast.
+        let ephemeral_deployer = if self.ephemeral_deployer && !self.resume {
+            let deployer = EphemeralDeployer::generate(&config.cache_path);
+            if self.broadcast {
+                deployer.save()?;
+                shell::println(format!(
+                    "Ephemeral deployer: {}, its key is kept in {} until its funds are swept back.",
+                    deployer.address(),
+                    deployer.path().display()
+                ))?;
+            } else {
+                shell::println(format!("Ephemeral deployer: {}", deployer.address()))?;
+            }
+            self.evm_opts.sender = Some(deployer.address());
+            Some(deployer)
+        } else {
+            None
+        };
+
+        // Wallets are loaded after the offline check, as remote signers need the network.
         let script_wallets =
             ScriptWallets::new(self.wallets.get_multi_wallet().await?, self.evm_opts.sender);
 
-        let (config, mut evm_opts) = self.load_config_and_evm_opts_emit_warnings()?;
-
         if let Some(sender) = self.maybe_load_private_key()? {
             evm_opts.sender = sender;
         }
+        if let Some(deployer) = &ephemeral_deployer {
+            script_wallets.add_signer(deployer.key())?;
+            evm_opts.sender = deployer.address();
+        }
 
-        let script_config = ScriptConfig::new(config, evm_opts).await?;
+        if let Some(max_age) = self.reuse_fork_block {
+            warm_fork::pin_fork_block(&mut evm_opts, max_age).await?;
+        }
+
+        // Keep the broadcast artifacts of the environments apart.
+        if let Some(name) = &self.environment {
+            config.broadcast = config.broadcast.join(name);
+        }
+
+        let mut script_config = ScriptConfig::new(config, evm_opts).await?;
+        script_config.ephemeral_deployer = ephemeral_deployer;
+        let script_params = match &self.params {
+            Some(params) => ScriptParams::load(params)?,
+            None => ScriptParams::default(),
+        };
+        script_params.enable_features(&self.features);
+        script_config.script_params = Some(script_params);
+        script_config.deploy_checks = DeployChecks::load(script_config.script_params.as_ref())?;
+        script_config.registry = Registry::load_all(&script_config.config.__root.0)?;
+        script_config.policy =
+            TxPolicy::load(&script_config.config.__root.0, self.policy.as_deref())?;
+        // The deny lists are only enforced on the transactions which are broadcast.
+        if self.broadcast {
+            let config = &script_config.config;
+            script_config.policy.load_deny_lists(&config.cache_path, config.offline).await?;
+        }
+        let tokens = TokenList::load(&script_config.config.__root.0, self.tokens.as_deref())?;
+        if !tokens.is_empty() {
+            script_config.token_amounts = self.resolve_token_amounts(
+                &tokens,
+                chain.map(|chain| chain.id()),
+                script_config.script_params.as_ref(),
+            )?;
+            print_token_amounts(&script_config.token_amounts)?;
+        }
+        script_config.deployment_templates =
+            self.prepare_deployment_templates(script_config.script_params.as_ref())?;
+        script_config.vanity = self.vanity_prefix.clone().map(|prefix| VanityMiner {
+            prefix,
+            contracts: self.vanity_contracts.clone(),
+            timeout: Duration::from_secs(self.vanity_timeout),
+            max_attempts: self.vanity_max_attempts,
+            salts: self.planned_salts.clone(),
+        });
 
         Ok(PreprocessedState { args: self, script_config, script_wallets })
     }
 
+    /// Replaces the amounts of tokens of the script arguments and parameters, e.g. `1000 USDC`,
+    /// with their raw values, and returns how they were resolved.
+    fn resolve_token_amounts(
+        &mut self,
+        tokens: &TokenList,
+        chain: Option<u64>,
+        params: Option<&ScriptParams>,
+    ) -> Result<Vec<TokenAmount>> {
+        // Amounts can only be resolved once the chain of the script is known.
+        let Some(chain) = chain else { return Ok(Vec::new()) };
+
+        let mut inputs = self
+            .args
+            .iter_mut()
+            .enumerate()
+            .map(|(i, arg)| (format!("argument {i}"), arg))
+            .collect::<Vec<_>>();
+        let mut params = params.map(|params| params.inner.lock());
+        if let Some(params) = &mut params {
+            inputs.extend(
+                params.values.iter_mut().map(|(name, value)| (format!("param {name}"), value)),
+            );
+        }
+
+        let mut amounts = Vec::new();
+        for (source, input) in inputs {
+            if let Some(amount) = tokens.resolve(&source, input, chain)? {
+                *input = amount.amount.to_string();
+                amounts.push(amount);
+            }
+        }
+        Ok(amounts)
+    }
+
+    /// Returns the relative percentage to multiply gas estimates by, 130 unless another one was
+    /// passed or configured.
+    pub fn gas_estimate_multiplier(&self) -> u64 {
+        self.gas_estimate_multiplier.unwrap_or(DEFAULT_GAS_ESTIMATE_MULTIPLIER)
+    }
+
+    /// Returns whether `ScriptArgs` was configured with `--watch`
+    pub fn is_watch(&self) -> bool {
+        self.watch.watch.is_some()
+    }
+
     /// Executes the script
     pub async fn run_script(self) -> Result<()> {
         trace!(target: "script", "executing script command");
 
+        let Some(pre_simulation) = self.pre_simulation().await? else { return Ok(()) };
+
+        // Move from `PreSimulationState` to `BundledState` either by resuming or simulating
+        // transactions.
+        let bundled = if pre_simulation.args.resume ||
+            (pre_simulation.args.verify && !pre_simulation.args.broadcast)
+        {
+            pre_simulation.resume().await?
+        } else {
+            pre_simulation.args.check_contract_sizes(
+                &pre_simulation.execution_result,
+                &pre_simulation.build_data.highlevel_known_contracts,
+            )?;
+
+            pre_simulation.fill_metadata().await?.bundle().await?
+        };
+
+        bundled.report().await?;
+
+        // Exit early in case user didn't provide any broadcast/verify related flags.
+        if !bundled.args.broadcast && !bundled.args.resume && !bundled.args.verify {
+            shell::println(tr!("\nSIMULATION COMPLETE. To broadcast these transactions, add --broadcast and wallet configuration(s) to the previous command. See forge script --help for more."))?;
+            return Ok(());
+        }
+
+        bundled.broadcast_and_verify().await
+    }
+
+    /// Compiles and executes the script, up to the point at which we have everything needed for
+    /// simulation/resuming.
+    ///
+    /// Returns `None` if there is nothing to simulate or broadcast.
+    async fn pre_simulation(self) -> Result<Option<PreSimulationState>> {
+        let preprocessed = self.preprocess().await?;
+        if preprocessed.args.explain_config {
+            preprocessed.explain_config()?;
+            return Ok(None);
+        }
+
         // Drive state machine to point at which we have everything needed for simulation/resuming.
-        let pre_simulation = self
-            .preprocess()
-            .await?
-            .compile()?
+        let compiled = preprocessed.compile()?;
+        compiled.script_config.check_libraries(&compiled.build_data).await?;
+        let pre_simulation = compiled
             .link()?
             .prepare_execution()
             .await?
@@ -242,50 +993,75 @@ impl ScriptArgs {
             pre_simulation.show_traces().await?;
         }
 
+        if pre_simulation.args.coverage {
+            pre_simulation.show_coverage()?;
+        }
+
         // Ensure that we have transactions to simulate/broadcast, otherwise exit early to avoid
         // hard error.
         if pre_simulation.execution_result.transactions.as_ref().map_or(true, |txs| txs.is_empty())
         {
-            return Ok(());
+            return Ok(None);
         }
 
         // Check if there are any missing RPCs and exit early to avoid hard error.
         if pre_simulation.execution_artifacts.rpc_data.missing_rpc {
-            shell::println("\nIf you wish to simulate on-chain transactions pass a RPC URL.")?;
-            return Ok(());
+            shell::println(tr!("\nIf you wish to simulate on-chain transactions pass a RPC URL."))?;
+            return Ok(None);
         }
 
-        // Move from `PreSimulationState` to `BundledState` either by resuming or simulating
-        // transactions.
-        let bundled = if pre_simulation.args.resume ||
-            (pre_simulation.args.verify && !pre_simulation.args.broadcast)
-        {
-            pre_simulation.resume().await?
-        } else {
-            pre_simulation.args.check_contract_sizes(
-                &pre_simulation.execution_result,
-                &pre_simulation.build_data.highlevel_known_contracts,
-            )?;
+        Ok(Some(pre_simulation))
+    }
 
-            pre_simulation.fill_metadata().await?.bundle().await?
-        };
+    /// Returns the figment with the chain profile merged on top of the `foundry.toml` and
+    /// environment values, but below the values provided on the command line.
+    fn figment_with_chain_profile(&self, chain_profile: ChainProfileProvider) -> Figment {
+        Figment::from(self).merge(chain_profile).merge(&self.opts).merge(&self.evm_opts).merge(self)
+    }
 
-        // Exit early in case user didn't provide any broadcast/verify related flags.
-        if !bundled.args.broadcast && !bundled.args.resume && !bundled.args.verify {
-            shell::println("\nSIMULATION COMPLETE. To broadcast these transactions, add --broadcast and wallet configuration(s) to the previous command. See forge script --help for more.")?;
-            return Ok(());
-        }
+    /// Reloads the config with the chain profile applied, see
+    /// [`Self::figment_with_chain_profile`].
+    fn load_config_with_chain_profile(
+        &self,
+        chain_profile: ChainProfileProvider,
+    ) -> Result<(Config, EvmOpts)> {
+        let figment = self.figment_with_chain_profile(chain_profile);
 
-        // Exit early if something is wrong with verification options.
-        if bundled.args.verify {
-            bundled.verify_preflight_check()?;
+        let mut evm_opts = figment.extract::<EvmOpts>()?;
+        let config = Config::try_from(figment)?.sanitized();
+
+        if let Some(fork_url) = config.get_rpc_url() {
+            evm_opts.fork_url = Some(fork_url?.into_owned());
         }
 
-        // Wait for pending txes and broadcast others.
-        let broadcasted = bundled.wait_for_pending().await?.broadcast().await?;
+        Ok((config, evm_opts))
+    }
 
-        if broadcasted.args.verify {
-            broadcasted.verify().await?;
+    /// Applies the script-specific keys of a chain profile, unless they were already set on the
+    /// command line.
+    fn apply_chain_profile(&mut self, overrides: Dict) -> Result<()> {
+        let profile: ScriptChainProfile = figment::value::Value::from(overrides).deserialize()?;
+
+        if let Some(verifier) = profile.verifier {
+            if self.verifier.verifier.is_none() && self.verifier.verifier_plugin.is_none() {
+                // Names which aren't built-in verifiers refer to plugins, which are resolved
+                // along with the config.
+                match VerificationProviderType::from_str(&verifier, true) {
+                    Ok(verifier) => self.verifier.verifier = Some(verifier),
+                    Err(_) => self.verifier.verifier_plugin = Some(verifier),
+                }
+            }
+        }
+        if self.verifier.verifier_url.is_none() {
+            self.verifier.verifier_url = profile.verifier_url;
+        }
+        self.legacy |= profile.legacy.unwrap_or_default();
+        self.slow |= profile.slow.unwrap_or_default();
+        if self.gas_estimate_multiplier.is_none() {
+            self.gas_estimate_multiplier = profile.gas_estimate_multiplier;
+        }
+        if self.fee_currency.is_none() {
+            self.fee_currency = profile.fee_currency;
         }
 
         Ok(())
@@ -429,7 +1205,7 @@ impl ScriptArgs {
         // Only prompt if we're broadcasting and we've not disabled interactivity.
         if prompt_user &&
             !self.non_interactive &&
-            !Confirm::new().with_prompt("Do you wish to continue?".to_string()).interact()?
+            !Confirm::new().with_prompt(tr!("Do you wish to continue?")).interact()?
         {
             eyre::bail!("User canceled the script.");
         }
@@ -453,6 +1229,12 @@ impl Provider for ScriptArgs {
                 figment::value::Value::from(etherscan_api_key.to_string()),
             );
         }
+        if let Some(plan) = &self.chaos_plan {
+            for (key, dir) in [("broadcast", "broadcast"), ("cache_path", "cache")] {
+                let dir = plan.dir.join(dir).to_string_lossy().to_string();
+                dict.insert(key.to_string(), figment::value::Value::from(dir));
+            }
+        }
         Ok(Map::from([(Config::selected_profile(), dict)]))
     }
 }
@@ -469,9 +1251,21 @@ pub struct ScriptResult {
     pub returned: Bytes,
     pub address: Option<Address>,
     pub breakpoints: Breakpoints,
+    /// The changes to the state made by the execution.
+    pub state_changeset: Option<StateChangeset>,
+    /// The instructions hit during the execution, if coverage was collected.
+    pub coverage: Option<HitMaps>,
 }
 
 impl ScriptResult {
+    /// Adds the instructions hit by another execution to the coverage of this one.
+    pub fn merge_coverage(&mut self, coverage: Option<HitMaps>) {
+        self.coverage = match (self.coverage.take(), coverage) {
+            (Some(hits), Some(other)) => Some(hits.merge(other)),
+            (hits, other) => hits.or(other),
+        };
+    }
+
     pub fn get_created_contracts(&self) -> Vec<AdditionalContract> {
         self.traces
             .iter()
@@ -506,11 +1300,31 @@ pub struct NestedValue {
 
 #[derive(Clone, Debug)]
 pub struct ScriptConfig {
-    pub config: Config,
+    /// The project config, shared by the states of the script and the runners.
+    pub config: Arc<Config>,
     pub evm_opts: EvmOpts,
     pub sender_nonce: u64,
-    /// Maps a rpc url to a backend
-    pub backends: HashMap<RpcUrl, Backend>,
+    /// Backends shared by the script runners
+    pub backends: BackendPool,
+    /// Parameters passed with `--params`
+    pub script_params: Option<ScriptParams>,
+    /// The transaction policy enforced before broadcasting
+    pub policy: TxPolicy,
+    /// The amounts of tokens of the arguments and parameters, resolved with the token list
+    pub token_amounts: Vec<TokenAmount>,
+    /// The arguments and parameters which reference the addresses of deployments of the script
+    pub deployment_templates: Vec<DeploymentTemplate>,
+    /// How many times the script was executed to resolve the deployment templates
+    pub template_passes: usize,
+    /// The key generated for the run with `--ephemeral-deployer`
+    pub ephemeral_deployer: Option<EphemeralDeployer>,
+    /// Mines the salts of the CREATE2 deployments with `--vanity-prefix`
+    pub vanity: Option<VanityMiner>,
+    /// The read-after-write checks of the `[checks]` table of the parameters
+    pub deploy_checks: DeployChecks,
+    /// The deployments registry as it was before the script ran, by chain id, as the script may
+    /// write to it
+    pub registry: BTreeMap<u64, Registry>,
 }
 
 impl ScriptConfig {
@@ -521,7 +1335,21 @@ impl ScriptConfig {
             // dapptools compatibility
             1
         };
-        Ok(Self { config, evm_opts, sender_nonce, backends: HashMap::new() })
+        Ok(Self {
+            config: Arc::new(config),
+            evm_opts,
+            sender_nonce,
+            backends: Default::default(),
+            script_params: None,
+            policy: Default::default(),
+            token_amounts: Vec::new(),
+            deployment_templates: Vec::new(),
+            template_passes: 0,
+            ephemeral_deployer: None,
+            vanity: None,
+            deploy_checks: Default::default(),
+            registry: Default::default(),
+        })
     }
 
     pub async fn update_sender(&mut self, sender: Address) -> Result<()> {
@@ -535,70 +1363,66 @@ impl ScriptConfig {
         Ok(())
     }
 
-    async fn get_runner(&mut self) -> Result<ScriptRunner> {
-        self._get_runner(None, false).await
+    async fn get_runner(&self, debug: bool) -> Result<ScriptRunner> {
+        self._get_runner(None, debug, false, None).await
     }
 
     async fn get_runner_with_cheatcodes(
-        &mut self,
+        &self,
         script_wallets: ScriptWallets,
         debug: bool,
+        coverage: bool,
+        console_listener: ConsoleListener,
     ) -> Result<ScriptRunner> {
-        self._get_runner(Some(script_wallets), debug).await
+        self._get_runner(Some(script_wallets), debug, coverage, Some(console_listener)).await
     }
 
     async fn _get_runner(
-        &mut self,
+        &self,
         script_wallets: Option<ScriptWallets>,
         debug: bool,
+        coverage: bool,
+        console_listener: Option<ConsoleListener>,
     ) -> Result<ScriptRunner> {
         trace!("preparing script runner");
         let env = self.evm_opts.evm_env().await?;
 
-        let db = if let Some(fork_url) = self.evm_opts.fork_url.as_ref() {
-            match self.backends.get(fork_url) {
-                Some(db) => db.clone(),
-                None => {
-                    let fork = self.evm_opts.get_fork(&self.config, env.clone());
-                    let backend = Backend::spawn(fork);
-                    self.backends.insert(fork_url.clone(), backend.clone());
-                    backend
-                }
-            }
-        } else {
-            // It's only really `None`, when we don't pass any `--fork-url`. And if so, there is
-            // no need to cache it, since there won't be any onchain simulation that we'd need
-            // to cache the backend for.
-            Backend::spawn(None)
-        };
+        let db = self.backends.get(self.evm_opts.fork_url.as_ref(), &env, || {
+            self.evm_opts.get_fork(&self.config, env.clone())
+        });
 
         // We need to enable tracing to decode contract names: local or external.
         let mut builder = ExecutorBuilder::new()
-            .inspectors(|stack| stack.trace(true))
+            .inspectors(|stack| stack.trace(true).debug(debug).coverage(coverage))
             .spec(self.config.evm_spec_id())
             .gas_limit(self.evm_opts.gas_limit());
 
+        if let Some(console_listener) = console_listener {
+            builder = builder.inspectors(|stack| stack.console_listener(console_listener));
+        }
+
         if let Some(script_wallets) = script_wallets {
             builder = builder.inspectors(|stack| {
                 stack
-                    .debug(debug)
                     .cheatcodes(
-                        CheatsConfig::new(
-                            &self.config,
-                            self.evm_opts.clone(),
-                            Some(script_wallets),
-                        )
+                        CheatsConfig {
+                            script_params: self.script_params.clone(),
+                            ..CheatsConfig::new(
+                                &self.config,
+                                self.evm_opts.clone(),
+                                Some(script_wallets),
+                            )
+                        }
                         .into(),
                     )
                     .enable_isolation(self.evm_opts.isolate)
             });
         }
 
-        Ok(ScriptRunner::new(
-            builder.build(env, db),
-            self.evm_opts.initial_balance,
-            self.evm_opts.sender,
-        ))
+        let mut executor = builder.build(env, db);
+        precompiles::install_precompiles(&mut executor, &self.config.precompiles)?;
+
+        Ok(ScriptRunner::new(executor, self.evm_opts.initial_balance, self.evm_opts.sender))
     }
 }
 
@@ -640,6 +1464,72 @@ mod tests {
         assert!(args.is_err());
     }
 
+    #[test]
+    fn can_parse_watch() {
+        let args = ScriptArgs::parse_from([
+            "foundry-cli",
+            "Contract.sol",
+            "--watch",
+            "src",
+            "--watch-delay",
+            "1s",
+            "--no-restart",
+        ]);
+        assert!(args.is_watch());
+        assert_eq!(args.path, "Contract.sol");
+        assert_eq!(args.watch.watch, Some(vec![PathBuf::from("src")]));
+        assert_eq!(args.watch.watch_delay.as_deref(), Some("1s"));
+        assert!(args.watch.no_restart);
+
+        // the script is taken by `--watch`, and is picked back from its paths when watching
+        let args = ScriptArgs::parse_from(["foundry-cli", "--watch", "Contract.sol"]);
+        assert!(args.path.is_empty());
+        assert_eq!(args.watch.watch, Some(vec![PathBuf::from("Contract.sol")]));
+
+        assert!(ScriptArgs::try_parse_from(["foundry-cli"]).is_err());
+        let args = ["foundry-cli", "Contract.sol", "--watch", "--broadcast"];
+        assert!(ScriptArgs::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn can_parse_sign_manifest() {
+        let signer = "0x4e59b44847b379578588920ca78fbf26c0b4956c";
+        let args = ScriptArgs::parse_from([
+            "foundry-cli",
+            "Contract.sol",
+            "--broadcast",
+            "--sign-manifest",
+            signer,
+        ]);
+        assert_eq!(args.sign_manifest, Some(signer.parse().unwrap()));
+
+        let args =
+            ScriptArgs::try_parse_from(["foundry-cli", "Contract.sol", "--sign-manifest", signer]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn chain_profile_keeps_explicit_gas_estimate_multiplier() {
+        let overrides =
+            Dict::from([("gas_estimate_multiplier".to_string(), figment::value::Value::from(200))]);
+
+        let mut args = ScriptArgs::parse_from(["foundry-cli", "Contract.sol"]);
+        args.apply_chain_profile(overrides.clone()).unwrap();
+        assert_eq!(args.gas_estimate_multiplier(), 200);
+
+        let mut args = ScriptArgs::parse_from([
+            "foundry-cli",
+            "Contract.sol",
+            "--gas-estimate-multiplier",
+            "130",
+        ]);
+        args.apply_chain_profile(overrides).unwrap();
+        assert_eq!(args.gas_estimate_multiplier(), 130);
+
+        let args = ScriptArgs::parse_from(["foundry-cli", "Contract.sol"]);
+        assert_eq!(args.gas_estimate_multiplier(), DEFAULT_GAS_ESTIMATE_MULTIPLIER);
+    }
+
     #[test]
     fn can_merge_script_config() {
         let args = ScriptArgs::parse_from([
@@ -0,0 +1,146 @@
+use crate::{build::BuildData, ScriptConfig};
+use alloy_primitives::Address;
+use ethers_core::types::BlockId;
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::{provider::ethers::try_get_http_provider, types::ToEthers};
+use foundry_compilers::artifacts::Libraries;
+use std::{slice, str::FromStr};
+
+impl ScriptConfig {
+    /// Checks the `libraries` of the config once the project is compiled, so that all problems
+    /// are reported at once instead of failing while linking or executing.
+    ///
+    /// Every entry must parse, its library must be among the compiled artifacts of its file, and
+    /// its address must have code on the target chain if an RPC is available.
+    pub async fn check_libraries(&self, build_data: &BuildData) -> Result<()> {
+        if self.config.libraries.is_empty() {
+            return Ok(())
+        }
+
+        let paths = self.config.project_paths();
+        let artifacts = build_data.linker.contracts.keys();
+        let provider = self.evm_opts.fork_url.as_deref().map(try_get_http_provider).transpose()?;
+        let block = self.evm_opts.fork_block_number.map(BlockId::from);
+
+        let mut issues = Vec::new();
+        for entry in &self.config.libraries {
+            let libraries = match Libraries::parse(slice::from_ref(entry)) {
+                Ok(libraries) => libraries.with_applied_remappings(&paths),
+                Err(err) => {
+                    issues.push(format!("`{entry}` can't be parsed: {err}"));
+                    continue
+                }
+            };
+
+            for (file, libraries) in libraries.libs {
+                let path = paths.root.join(&file);
+                if !path.exists() {
+                    issues.push(format!("`{entry}`: {} doesn't exist", file.display()));
+                    continue
+                }
+
+                for (name, address) in libraries {
+                    // Standalone scripts are compiled without the other files of the project, and
+                    // only the artifacts the script can deploy are kept.
+                    let mut compiled = artifacts.clone().filter(|id| id.source == path).peekable();
+                    if compiled.peek().is_some() &&
+                        !compiled.any(|id| id.name.split('.').next() == Some(name.as_str()))
+                    {
+                        issues.push(format!(
+                            "`{entry}`: there is no library {name} in {}",
+                            file.display()
+                        ));
+                    }
+
+                    let Ok(address) = Address::from_str(&address) else {
+                        issues.push(format!("`{entry}`: invalid address `{address}`"));
+                        continue
+                    };
+                    if let Some(provider) = &provider {
+                        match provider.get_code(address.to_ethers(), block).await {
+                            Ok(code) if code.is_empty() => issues.push(format!(
+                                "`{entry}`: there is no code at {address} on the target chain"
+                            )),
+                            Ok(_) => {}
+                            Err(err) => issues.push(format!(
+                                "`{entry}`: failed to get the code of {address}: {err}"
+                            )),
+                        }
+                    }
+                }
+            }
+        }
+
+        if !issues.is_empty() {
+            eyre::bail!("Invalid `libraries` in config:\n  - {}", issues.join("\n  - "))
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_cli::utils::block_on;
+    use foundry_compilers::{artifacts::CompactContractBytecode, ArtifactId};
+    use foundry_config::Config;
+    use foundry_evm::opts::EvmOpts;
+    use foundry_linking::Linker;
+
+    #[test]
+    fn can_report_all_library_issues() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("src")).unwrap();
+        std::fs::write(root.path().join("src/Lib.sol"), "library Lib {}").unwrap();
+
+        let config = Config {
+            libraries: vec![
+                "src/Lib.sol:Lib:0x8De6DDbCd5053d32292AAA0D2105A32d108484a6".to_string(),
+                "src/Lib.sol:Lib".to_string(),
+                "src/Missing.sol:Missing:0x8De6DDbCd5053d32292AAA0D2105A32d108484a6".to_string(),
+                "src/Lib.sol:Other:0x1234".to_string(),
+                "src/Lib.sol:Other:0x8De6DDbCd5053d32292AAA0D2105A32d108484a6".to_string(),
+            ],
+            ..Config::with_root(root.path())
+        };
+        let script_config = ScriptConfig {
+            config: config.into(),
+            evm_opts: EvmOpts::default(),
+            sender_nonce: 0,
+            backends: Default::default(),
+            script_params: None,
+            policy: Default::default(),
+            token_amounts: Vec::new(),
+            deployment_templates: Vec::new(),
+            template_passes: 0,
+            ephemeral_deployer: None,
+            vanity: None,
+            deploy_checks: Default::default(),
+            registry: Default::default(),
+        };
+
+        let lib = ArtifactId {
+            path: root.path().join("out/Lib.sol/Lib.json"),
+            name: "Lib".to_string(),
+            source: root.path().join("src/Lib.sol"),
+            version: "0.8.23".parse().unwrap(),
+        };
+        let build_data = BuildData {
+            linker: Linker::new(
+                root.path(),
+                [(lib.clone(), CompactContractBytecode::default())].into_iter().collect(),
+            ),
+            target: lib,
+            sources: Default::default(),
+            git: None,
+        };
+
+        let err = block_on(script_config.check_libraries(&build_data)).unwrap_err().to_string();
+        assert!(!err.contains("`src/Lib.sol:Lib:0x8De6"));
+        assert!(err.contains("`src/Lib.sol:Lib` can't be parsed"));
+        assert!(err.contains("src/Missing.sol doesn't exist"));
+        assert!(err.contains("invalid address `0x1234`"));
+        assert!(err.contains("there is no library Other in src/Lib.sol"));
+    }
+}
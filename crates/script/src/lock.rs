@@ -0,0 +1,388 @@
+use crate::{broadcast::BundledState, hooks};
+use alloy_primitives::Address;
+use eyre::{Result, WrapErr};
+use foundry_cli::utils::now;
+use foundry_common::{fs, types::ToAlloy};
+use itertools::Itertools;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::task::JoinHandle;
+
+/// Directory of the lock files, in the cache directory of the project.
+pub const LOCKS_DIR: &str = "locks";
+
+/// How often the holder of a lock confirms it's still broadcasting.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long after its last heartbeat a lock is considered abandoned, e.g. by a killed job.
+const STALE_AFTER: Duration = Duration::from_secs(120);
+
+/// A lock on broadcasting a script from a sender on a chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockInfo {
+    /// The run holding the lock.
+    pub owner: String,
+    pub script: String,
+    pub chain: u64,
+    pub sender: Address,
+    pub host: String,
+    pub pid: u32,
+    /// When the lock was acquired, in seconds since the epoch.
+    pub acquired_at: u64,
+    /// When the holder last confirmed it's broadcasting, in seconds since the epoch.
+    pub heartbeat: u64,
+}
+
+impl LockInfo {
+    pub fn new(owner: &str, script: &str, chain: u64, sender: Address) -> Self {
+        let now = now().as_secs();
+        Self {
+            owner: owner.to_string(),
+            script: script.to_string(),
+            chain,
+            sender,
+            host: hostname(),
+            pid: std::process::id(),
+            acquired_at: now,
+            heartbeat: now,
+        }
+    }
+
+    /// The key of the lock, shared by the runs which would use the same nonces.
+    pub fn key(&self) -> String {
+        format!("{}-{}-{}", self.script, self.chain, self.sender)
+    }
+
+    pub fn is_stale(&self, now: u64) -> bool {
+        now.saturating_sub(self.heartbeat) > STALE_AFTER.as_secs()
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{} is being broadcast from {} on chain {} by process {} on {}, since {}s",
+            self.script,
+            self.sender,
+            self.chain,
+            self.pid,
+            self.host,
+            now().as_secs().saturating_sub(self.acquired_at)
+        )
+    }
+}
+
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Takes the lock file of `info` in `dir`.
+///
+/// The lock is written to a temporary file and linked in place, so that other runs never read a
+/// partly written lock. A lock held by another run fails, unless it's stale or `force` is set.
+pub fn acquire_local(dir: &Path, info: &LockInfo, force: bool) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.lock", info.key()));
+    let temp = write_temp(&path, info)?;
+    let result = link_lock(&path, &temp, force);
+    let _ = std::fs::remove_file(&temp);
+    result.map(|()| path)
+}
+
+fn link_lock(path: &Path, temp: &Path, force: bool) -> Result<()> {
+    loop {
+        match std::fs::hard_link(temp, path) {
+            Ok(()) => return Ok(()),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                // Locks are written atomically, so an unreadable one wasn't written by a run.
+                let holder = fs::read_json_file::<LockInfo>(path).ok();
+                match &holder {
+                    Some(holder) if !force && !holder.is_stale(now().as_secs()) => {
+                        eyre::bail!(
+                            "{}.\nWait for it to complete, or remove the lock with \
+                             `--force-unlock` if it was abandoned: {}",
+                            holder.describe(),
+                            path.display()
+                        )
+                    }
+                    Some(holder) => {
+                        warn!(holder = %holder.owner, "removing the lock {}", path.display())
+                    }
+                    None => warn!("removing the invalid lock {}", path.display()),
+                }
+                // Only the lock which was checked is removed, not one which replaced it since.
+                remove_lock(path, temp, |current| current == holder.as_ref())?;
+            }
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("Failed to lock {}", path.display()))
+            }
+        }
+    }
+}
+
+/// Writes `info` to a temporary file of its owner next to the lock file `path`.
+fn write_temp(path: &Path, info: &LockInfo) -> Result<PathBuf> {
+    let temp = path.with_extension(format!("{}.tmp", info.owner));
+    std::fs::write(&temp, serde_json::to_vec_pretty(info)?)
+        .wrap_err_with(|| format!("Failed to write {}", temp.display()))?;
+    Ok(temp)
+}
+
+/// Removes the lock file if `is_removed` holds for its holder, and returns whether it did.
+///
+/// The lock is moved aside before it's checked, and moved back if it's kept, so that a lock taken
+/// by another run in the meantime is never removed.
+fn remove_lock(
+    path: &Path,
+    temp: &Path,
+    is_removed: impl FnOnce(Option<&LockInfo>) -> bool,
+) -> Result<bool> {
+    let aside = temp.with_extension("removed");
+    match std::fs::rename(path, &aside) {
+        Ok(()) => {}
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(false),
+        Err(err) => {
+            return Err(err)
+                .wrap_err_with(|| format!("Failed to remove the lock {}", path.display()))
+        }
+    }
+    let removed = is_removed(fs::read_json_file::<LockInfo>(&aside).ok().as_ref());
+    if !removed {
+        // Unless yet another run took the lock since, which then holds it.
+        let _ = std::fs::hard_link(&aside, path);
+    }
+    std::fs::remove_file(&aside)?;
+    Ok(removed)
+}
+
+/// Refreshes the heartbeat of the lock file, if it's still held by `info.owner`.
+fn refresh_local(path: &Path, info: &LockInfo) -> Result<()> {
+    if fs::read_json_file::<LockInfo>(path).is_ok_and(|holder| holder.owner == info.owner) {
+        let info = LockInfo { heartbeat: now().as_secs(), ..info.clone() };
+        // The lock is replaced atomically, so that other runs never read a partly written lock.
+        let temp = write_temp(path, &info)?;
+        std::fs::rename(&temp, path)?;
+    }
+    Ok(())
+}
+
+/// Removes the lock file, if it's still held by `owner`.
+fn release_local(path: &Path, owner: &str) {
+    let temp = path.with_extension(format!("{owner}.tmp"));
+    let _ = remove_lock(path, &temp, |holder| holder.is_some_and(|holder| holder.owner == owner));
+}
+
+/// A lock server shared by the machines broadcasting a script, e.g. the CI runners.
+///
+/// The locks are resources at `<url>/<key>`:
+/// - `PUT` with a [LockInfo] takes or refreshes the lock, and fails with `409 Conflict` and the
+///   [LockInfo] of the holder if another owner holds it.
+/// - `DELETE` releases it, only if held by the `owner` query parameter when set.
+#[derive(Clone)]
+pub struct RemoteLock {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl RemoteLock {
+    pub fn new(url: &str) -> Self {
+        Self { client: reqwest::Client::new(), url: url.trim_end_matches('/').to_string() }
+    }
+
+    fn lock_url(&self, info: &LockInfo) -> String {
+        format!("{}/{}", self.url, info.key())
+    }
+
+    pub async fn acquire(&self, info: &LockInfo, force: bool) -> Result<()> {
+        if force {
+            self.delete(info, None).await?;
+        }
+        for _ in 0..2 {
+            let response = self.client.put(self.lock_url(info)).json(info).send().await?;
+            if response.status() != StatusCode::CONFLICT {
+                response.error_for_status().wrap_err("Failed to take the remote lock")?;
+                return Ok(())
+            }
+            match response.json::<LockInfo>().await {
+                Ok(holder) if holder.is_stale(now().as_secs()) => {
+                    warn!(holder = %holder.owner, "removing the stale remote lock {}", info.key());
+                    self.delete(info, Some(&holder.owner)).await?;
+                }
+                Ok(holder) => eyre::bail!(
+                    "{}.\nWait for it to complete, or remove the lock with `--force-unlock` if it \
+                     was abandoned.",
+                    holder.describe()
+                ),
+                Err(_) => eyre::bail!("The remote lock {} is held by another run", info.key()),
+            }
+        }
+        eyre::bail!("Failed to take the remote lock {}", info.key())
+    }
+
+    async fn refresh(&self, info: &LockInfo) -> Result<()> {
+        let info = LockInfo { heartbeat: now().as_secs(), ..info.clone() };
+        self.client.put(self.lock_url(&info)).json(&info).send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    async fn delete(&self, info: &LockInfo, owner: Option<&str>) -> Result<()> {
+        let mut request = self.client.delete(self.lock_url(info));
+        if let Some(owner) = owner {
+            request = request.query(&[("owner", owner)]);
+        }
+        let response = request.send().await?;
+        if response.status() != StatusCode::NOT_FOUND {
+            response.error_for_status().wrap_err("Failed to remove the remote lock")?;
+        }
+        Ok(())
+    }
+}
+
+/// The locks held by a broadcast run, released when it completes.
+///
+/// If the run fails, the local locks are released when dropped, and the remote ones become stale.
+pub struct RunLock {
+    locks: Vec<(LockInfo, PathBuf)>,
+    remote: Option<RemoteLock>,
+    heartbeat: JoinHandle<()>,
+}
+
+impl RunLock {
+    /// Takes the locks of `infos`, locally in `dir` and on the lock server at `url` if any.
+    pub async fn acquire(
+        dir: &Path,
+        url: Option<&str>,
+        infos: Vec<LockInfo>,
+        force: bool,
+    ) -> Result<Self> {
+        let remote = url.map(RemoteLock::new);
+        let mut locks = Vec::with_capacity(infos.len());
+        for info in infos {
+            let path = acquire_local(dir, &info, force)?;
+            // Keep the lock in the list first, so that it's released if the remote one fails.
+            locks.push((info, path));
+            if let Some(remote) = &remote {
+                let (info, _) = locks.last().unwrap();
+                if let Err(err) = remote.acquire(info, force).await {
+                    for (info, path) in &locks {
+                        release_local(path, &info.owner);
+                    }
+                    return Err(err)
+                }
+            }
+        }
+
+        let heartbeat = {
+            let (locks, remote) = (locks.clone(), remote.clone());
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+                    for (info, path) in &locks {
+                        if let Err(err) = refresh_local(path, info) {
+                            warn!(%err, "failed to refresh the lock {}", path.display());
+                        }
+                        if let Some(remote) = &remote {
+                            if let Err(err) = remote.refresh(info).await {
+                                warn!(%err, "failed to refresh the remote lock {}", info.key());
+                            }
+                        }
+                    }
+                }
+            })
+        };
+
+        Ok(Self { locks, remote, heartbeat })
+    }
+
+    /// Releases the locks.
+    pub async fn release(self) -> Result<()> {
+        self.heartbeat.abort();
+        if let Some(remote) = &self.remote {
+            for (info, _) in &self.locks {
+                remote.delete(info, Some(&info.owner)).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        self.heartbeat.abort();
+        for (info, path) in &self.locks {
+            release_local(path, &info.owner);
+        }
+    }
+}
+
+impl BundledState {
+    /// Locks broadcasting the script from each of its senders on each of its chains, so that
+    /// concurrent runs can't send transactions with the same nonces.
+    pub async fn lock_run(&self) -> Result<RunLock> {
+        let script = hooks::script_name(&self.build_data.build_data.target);
+        let owner = format!("{}-{}-{}", hostname(), std::process::id(), now().as_millis());
+        let infos = self
+            .sequence
+            .sequences()
+            .iter()
+            .flat_map(|sequence| {
+                sequence
+                    .typed_transactions()
+                    .filter_map(|tx| tx.from())
+                    .map(|from| (sequence.chain, from.to_alloy()))
+            })
+            .unique()
+            .map(|(chain, sender)| LockInfo::new(&owner, &script, chain, sender))
+            .collect::<Vec<_>>();
+
+        let dir = self.script_config.config.cache_path.join(LOCKS_DIR);
+        RunLock::acquire(&dir, self.args.lock_url.as_deref(), infos, self.args.force_unlock).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locks_locally() {
+        let dir = tempfile::tempdir().unwrap();
+        let sender = Address::with_last_byte(1);
+        let first = LockInfo::new("first", "Deploy.s.sol", 1, sender);
+        let second = LockInfo::new("second", "Deploy.s.sol", 1, sender);
+
+        let path = acquire_local(dir.path(), &first, false).unwrap();
+        assert!(acquire_local(dir.path(), &second, false).is_err());
+        // Other senders and chains don't share the nonces.
+        let other = LockInfo::new("second", "Deploy.s.sol", 10, sender);
+        assert!(acquire_local(dir.path(), &other, false).is_ok());
+
+        // Only the holder releases the lock.
+        release_local(&path, "second");
+        assert!(path.exists());
+        release_local(&path, "first");
+        assert!(!path.exists());
+
+        acquire_local(dir.path(), &first, false).unwrap();
+        let path = acquire_local(dir.path(), &second, true).unwrap();
+        assert_eq!(fs::read_json_file::<LockInfo>(&path).unwrap().owner, "second");
+
+        // Abandoned locks are taken over.
+        let stale = LockInfo { heartbeat: 0, ..first.clone() };
+        std::fs::write(&path, serde_json::to_vec(&stale).unwrap()).unwrap();
+        acquire_local(dir.path(), &first, false).unwrap();
+        assert!(stale.is_stale(now().as_secs()));
+
+        // Only the lock file is left behind.
+        refresh_local(&path, &first).unwrap();
+        assert_eq!(fs::read_json_file::<LockInfo>(&path).unwrap().owner, "first");
+        let files = std::fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(files, 2);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::verify::BroadcastedState;
+use alloy_primitives::{keccak256, Address, Bytes, B256};
+use ethers_core::types::Signature;
+use ethers_signers::Signer;
+use eyre::{Context, ContextCompat, Result};
+use foundry_cli::utils::now;
+use foundry_common::{fs, shell, types::ToAlloy};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A file covered by a [DeploymentManifest].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Path of the file, relative to the project root.
+    pub path: PathBuf,
+    /// `keccak256` of the file contents.
+    pub hash: B256,
+}
+
+/// Signed record of a deployment.
+///
+/// `digest` is the `keccak256` of the concatenated entry hashes, and `signature` is the EIP-191
+/// signature of `digest` by `signer`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeploymentManifest {
+    pub entries: Vec<ManifestEntry>,
+    pub digest: B256,
+    pub signer: Address,
+    pub signature: Bytes,
+    pub timestamp: u64,
+}
+
+impl DeploymentManifest {
+    /// Hashes the given files and computes the digest to be signed.
+    fn new(root: &Path, files: &[PathBuf], signer: Address) -> Result<Self> {
+        let entries = files
+            .iter()
+            .map(|file| {
+                let contents = fs::read(file)?;
+                let path = file.strip_prefix(root).unwrap_or(file).to_path_buf();
+                Ok(ManifestEntry { path, hash: keccak256(contents) })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let digest = Self::digest(&entries);
+
+        Ok(Self { entries, digest, signer, signature: Bytes::new(), timestamp: now().as_secs() })
+    }
+
+    /// Returns the digest covering all entries.
+    pub fn digest(entries: &[ManifestEntry]) -> B256 {
+        let hashes = entries.iter().flat_map(|entry| entry.hash.0).collect::<Vec<_>>();
+        keccak256(hashes)
+    }
+
+    /// Signs the digest with `signer`.
+    async fn sign<S: Signer>(&mut self, signer: &S) -> Result<()>
+    where
+        S::Error: 'static,
+    {
+        self.signature = signer
+            .sign_message(self.digest.as_slice())
+            .await
+            .wrap_err("Failed to sign deployment manifest")?
+            .to_vec()
+            .into();
+        Ok(())
+    }
+
+    /// Returns the address which signed the digest.
+    pub fn recover(&self) -> Result<Address> {
+        let signature = Signature::try_from(self.signature.as_ref())?;
+        Ok(signature.recover(self.digest.as_slice())?.to_alloy())
+    }
+}
+
+impl BroadcastedState {
+    /// Writes a manifest covering the broadcast artifact and verification report, signed by the
+    /// `--sign-manifest` wallet.
+    pub async fn sign_manifest(self) -> Result<()> {
+        let Self { args, script_config, sequence, send_kind, verification_report, .. } = self;
+
+        let signer_address = args.sign_manifest.wrap_err("No manifest signer provided.")?;
+        let signer =
+            send_kind.signer(&signer_address).wrap_err("No wallet found for manifest signer.")?;
+
+        let broadcast_path =
+            sequence.broadcast_path().wrap_err("Broadcast artifact was not saved.")?.to_path_buf();
+
+        // Dropping the sequence writes the final broadcast artifact, so we only hash it afterwards.
+        drop(sequence);
+
+        let mut files = vec![broadcast_path.clone()];
+        files.extend(verification_report);
+
+        let mut manifest =
+            DeploymentManifest::new(&script_config.config.__root.0, &files, signer_address)?;
+        manifest.sign(signer).await?;
+
+        let path = broadcast_path.with_extension("manifest.json");
+        fs::write_json_file(&path, &manifest)?;
+        shell::println(format!("\nSigned manifest saved to: {}\n", path.display()))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256};
+    use ethers_signers::LocalWallet;
+
+    #[test]
+    fn digest_is_stable() {
+        let entry =
+            |path: &str, byte| ManifestEntry { path: path.into(), hash: B256::repeat_byte(byte) };
+        let entries = [
+            entry("broadcast/run-latest.json", 1),
+            entry("broadcast/run-latest.verification.json", 2),
+        ];
+        assert_eq!(
+            DeploymentManifest::digest(&entries),
+            b256!("346d8c96a2454213fcc0daff3c96ad0398148181b9fa6488f7ae2c0af5b20aa0")
+        );
+        // the digest depends on the order of the entries
+        let reversed = [entries[1].clone(), entries[0].clone()];
+        assert_eq!(
+            DeploymentManifest::digest(&reversed),
+            b256!("5cd53f16d699e84453c6f769b9a5bc1e927c618e3b612143cd5ae7dee1ce3087")
+        );
+    }
+
+    #[test]
+    fn recovers_signer() {
+        let root = tempfile::tempdir().unwrap();
+        let file = root.path().join("run-latest.json");
+        fs::write(&file, "{}").unwrap();
+        let wallet: LocalWallet =
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse().unwrap();
+        let signer = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+
+        let mut manifest = DeploymentManifest::new(root.path(), &[file], signer).unwrap();
+        assert_eq!(manifest.entries[0].path, PathBuf::from("run-latest.json"));
+        assert_eq!(manifest.entries[0].hash, keccak256("{}"));
+        futures::executor::block_on(manifest.sign(&wallet)).unwrap();
+        assert_eq!(manifest.recover().unwrap(), signer);
+
+        // a tampered digest recovers another address
+        manifest.digest = B256::ZERO;
+        assert_ne!(manifest.recover().unwrap(), signer);
+    }
+}
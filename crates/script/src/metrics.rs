@@ -0,0 +1,196 @@
+use axum::{extract::State, routing::get, Router};
+use eyre::Result;
+use foundry_common::shell;
+use std::{
+    fmt::Write,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+/// Broadcast progress counters, exposed in the Prometheus text format on `/metrics`.
+#[derive(Debug)]
+pub struct BroadcastMetrics {
+    /// Transactions ever queued, which the ETA is computed from.
+    total: AtomicU64,
+    queued: AtomicU64,
+    sent: AtomicU64,
+    confirmed: AtomicU64,
+    failed: AtomicU64,
+    base_fee: AtomicU64,
+    started: Instant,
+}
+
+impl Default for BroadcastMetrics {
+    fn default() -> Self {
+        Self {
+            total: Default::default(),
+            queued: Default::default(),
+            sent: Default::default(),
+            confirmed: Default::default(),
+            failed: Default::default(),
+            base_fee: Default::default(),
+            started: Instant::now(),
+        }
+    }
+}
+
+impl BroadcastMetrics {
+    /// Starts serving the metrics on `addr` in the background.
+    pub fn serve(addr: SocketAddr) -> Result<Arc<Self>> {
+        let metrics = Arc::new(Self::default());
+
+        let app = Router::new().route("/metrics", get(handle)).with_state(metrics.clone());
+        let server = axum::Server::try_bind(&addr)?.serve(app.into_make_service());
+        tokio::spawn(async move {
+            if let Err(err) = server.await {
+                error!(?err, "metrics server failed");
+            }
+        });
+
+        shell::println(format!("Serving broadcast metrics on: http://{addr}/metrics"))?;
+
+        Ok(metrics)
+    }
+
+    pub fn add_queued(&self, count: usize) {
+        self.total.fetch_add(count as u64, Ordering::Relaxed);
+        self.queued.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// Counts queued transactions which were sent.
+    pub fn add_sent(&self, count: usize) {
+        self.queued.fetch_sub(count as u64, Ordering::Relaxed);
+        self.sent.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// Counts queued transactions which failed to send, and are dropped.
+    pub fn add_dropped(&self, count: usize) {
+        self.queued.fetch_sub(count as u64, Ordering::Relaxed);
+        self.failed.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// Counts sent transactions with a successful receipt.
+    pub fn add_confirmed(&self, count: usize) {
+        self.confirmed.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// Counts sent transactions which reverted or never got a receipt.
+    pub fn add_failed(&self, count: usize) {
+        self.failed.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn set_base_fee(&self, base_fee: u64) {
+        self.base_fee.store(base_fee, Ordering::Relaxed);
+    }
+
+    /// Estimated seconds until all queued transactions are confirmed, based on the average
+    /// confirmation rate so far.
+    fn eta(&self, elapsed: f64) -> f64 {
+        let total = self.total.load(Ordering::Relaxed);
+        let done = self.confirmed.load(Ordering::Relaxed) + self.failed.load(Ordering::Relaxed);
+        if done == 0 {
+            return f64::NAN
+        }
+        elapsed / done as f64 * total.saturating_sub(done) as f64
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed) as f64;
+        let metrics = [
+            (
+                "transactions_queued",
+                "gauge",
+                "Transactions queued for broadcast which are not sent yet.",
+                load(&self.queued),
+            ),
+            (
+                "transactions_sent_total",
+                "counter",
+                "Transactions sent to the RPC.",
+                load(&self.sent),
+            ),
+            (
+                "transactions_confirmed_total",
+                "counter",
+                "Transactions with a successful receipt.",
+                load(&self.confirmed),
+            ),
+            (
+                "transactions_failed_total",
+                "counter",
+                "Transactions that failed to send, reverted or were dropped.",
+                load(&self.failed),
+            ),
+            ("base_fee_wei", "gauge", "Base fee of the latest block.", load(&self.base_fee)),
+            ("elapsed_seconds", "gauge", "Seconds since the broadcast started.", elapsed),
+            (
+                "eta_seconds",
+                "gauge",
+                "Estimated seconds until all transactions are confirmed.",
+                self.eta(elapsed),
+            ),
+        ];
+
+        let mut out = String::new();
+        for (name, kind, help, value) in metrics {
+            let _ = writeln!(out, "# HELP forge_script_{name} {help}");
+            let _ = writeln!(out, "# TYPE forge_script_{name} {kind}");
+            let _ = writeln!(out, "forge_script_{name} {value}");
+        }
+        out
+    }
+}
+
+async fn handle(State(metrics): State<Arc<BroadcastMetrics>>) -> String {
+    metrics.render()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_prometheus_format() {
+        let metrics = BroadcastMetrics::default();
+        metrics.add_queued(4);
+        metrics.add_sent(2);
+        metrics.add_confirmed(1);
+        metrics.set_base_fee(7);
+
+        let out = metrics.render();
+        assert!(out.contains("# TYPE forge_script_transactions_sent_total counter\n"));
+        assert!(out.contains("forge_script_transactions_queued 2\n"));
+        assert!(out.contains("forge_script_transactions_confirmed_total 1\n"));
+        assert!(out.contains("forge_script_base_fee_wei 7\n"));
+    }
+
+    #[test]
+    fn eta_is_nan_without_progress() {
+        let metrics = BroadcastMetrics::default();
+        metrics.add_queued(4);
+        assert!(metrics.eta(10.).is_nan());
+
+        metrics.add_confirmed(1);
+        assert_eq!(metrics.eta(10.), 30.);
+    }
+
+    #[test]
+    fn dequeues_sent_and_dropped_transactions() {
+        let metrics = BroadcastMetrics::default();
+        metrics.add_queued(3);
+        metrics.add_sent(1);
+        metrics.add_dropped(1);
+        metrics.add_failed(1);
+
+        let out = metrics.render();
+        assert!(out.contains("forge_script_transactions_queued 1\n"));
+        assert!(out.contains("forge_script_transactions_failed_total 2\n"));
+        assert_eq!(metrics.eta(10.), 5.);
+    }
+}
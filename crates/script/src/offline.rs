@@ -0,0 +1,75 @@
+use crate::{explain::redact_url, ScriptArgs};
+use eyre::Result;
+use foundry_evm::opts::EvmOpts;
+
+impl ScriptArgs {
+    /// Ensures the requested features don't need the network when running with `--offline`.
+    ///
+    /// Offline runs are pure local runs: the script is compiled, linked and simulated without an
+    /// RPC, and the cheatcodes which create forks fail.
+    pub(crate) fn check_offline(&self, evm_opts: &EvmOpts) -> Result<()> {
+        let mut unavailable = Vec::new();
+        if let Some(url) = &evm_opts.fork_url {
+            unavailable.push(format!(
+                "forking and RPC gas estimation (--fork-url/--rpc-url {}, or `eth_rpc_url`)",
+                redact_url(url)
+            ));
+        }
+        if self.broadcast {
+            unavailable.push("broadcasting (--broadcast)".to_string());
+        }
+        if self.resume {
+            unavailable.push("resuming (--resume)".to_string());
+        }
+        if self.verify {
+            unavailable.push("verification (--verify)".to_string());
+        }
+        if self.wallets.aws {
+            unavailable.push("AWS KMS signers (--aws)".to_string());
+        }
+
+        if !unavailable.is_empty() {
+            eyre::bail!(
+                "The following requested features are unavailable in offline mode:\n  - {}\n\
+                 Remove them, or run without --offline.",
+                unavailable.join("\n  - ")
+            )
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn can_list_unavailable_offline_features() {
+        let args = ScriptArgs::parse_from(["foundry-cli", "Contract.sol", "--offline"]);
+        assert!(args.check_offline(&EvmOpts::default()).is_ok());
+
+        let args = ScriptArgs::parse_from([
+            "foundry-cli",
+            "Contract.sol",
+            "--offline",
+            "--broadcast",
+            "--verify",
+        ]);
+        let evm_opts =
+            EvmOpts { fork_url: Some("http://localhost:8545".into()), ..Default::default() };
+        let err = args.check_offline(&evm_opts).unwrap_err().to_string();
+        assert!(err.contains("--fork-url/--rpc-url http://localhost:8545"));
+        assert!(err.contains("broadcasting (--broadcast)"));
+        assert!(err.contains("verification (--verify)"));
+        assert!(!err.contains("--resume"));
+
+        let evm_opts = EvmOpts {
+            fork_url: Some("https://eth-mainnet.g.alchemy.com/v2/abcdef123".into()),
+            ..Default::default()
+        };
+        let err = args.check_offline(&evm_opts).unwrap_err().to_string();
+        assert!(err.contains("https://eth-mainnet.g.alchemy.com/********"));
+        assert!(!err.contains("abcdef123"));
+    }
+}
@@ -0,0 +1,313 @@
+use crate::{transaction::TransactionWithMetadata, verify::BroadcastedState, ScriptResult};
+use alloy_primitives::{Address, U256};
+use eyre::Result;
+use foundry_common::{
+    provider::ethers::get_http_provider,
+    shell,
+    types::{ToAlloy, ToEthers},
+};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+    str::FromStr,
+};
+use yansi::Paint;
+
+/// How much the gas used on-chain may differ from the simulated gas, in percent.
+///
+/// The simulated gas of calls is the lowest gas limit they succeed with, which is above the gas
+/// they use because of refunds and of the gas which has to be left to the subcalls.
+const GAS_TOLERANCE_PERCENT: u64 = 10;
+
+/// The storage slots written by a transaction, with their new value, by account.
+type StorageWrites = BTreeMap<Address, BTreeMap<U256, U256>>;
+
+/// What a simulated transaction did, to be compared with its on-chain execution.
+#[derive(Debug, Default)]
+pub struct SimulatedExecution {
+    pub gas_used: u64,
+    pub storage: StorageWrites,
+}
+
+impl SimulatedExecution {
+    pub fn new(result: &ScriptResult) -> Self {
+        let mut storage = StorageWrites::new();
+        for (address, account) in result.state_changeset.iter().flatten() {
+            let slots = account
+                .storage
+                .iter()
+                .filter(|(_, slot)| slot.is_changed())
+                .map(|(key, slot)| (*key, slot.present_value()))
+                .collect::<BTreeMap<_, _>>();
+            if !slots.is_empty() {
+                storage.insert(*address, slots);
+            }
+        }
+        Self { gas_used: result.gas_used, storage }
+    }
+}
+
+/// The simulated executions of the transactions, by RPC, sender and nonce, which identify a
+/// transaction both before and after it's broadcast.
+#[derive(Debug, Default)]
+pub struct SimulatedExecutions(HashMap<(String, Address, U256), SimulatedExecution>);
+
+impl SimulatedExecutions {
+    pub fn insert(&mut self, tx: &TransactionWithMetadata, execution: SimulatedExecution) {
+        if let Some(key) = Self::key(tx) {
+            self.0.insert(key, execution);
+        }
+    }
+
+    fn get(&self, tx: &TransactionWithMetadata) -> Option<&SimulatedExecution> {
+        self.0.get(&Self::key(tx)?)
+    }
+
+    fn key(tx: &TransactionWithMetadata) -> Option<(String, Address, U256)> {
+        let from = tx.transaction.from()?.to_alloy();
+        let nonce = tx.transaction.nonce()?.to_alloy();
+        Some((tx.rpc.clone(), from, nonce))
+    }
+}
+
+/// A difference between the simulation of a transaction and its on-chain execution.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Divergence {
+    Gas {
+        simulated: u64,
+        onchain: u64,
+    },
+    /// A storage slot which was written with a different value, or only in one of them.
+    Storage {
+        address: Address,
+        slot: U256,
+        simulated: Option<U256>,
+        onchain: Option<U256>,
+    },
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |value: &Option<U256>| match value {
+            Some(value) => format!("{value:#x}"),
+            None => "not written".to_string(),
+        };
+        match self {
+            Self::Gas { simulated, onchain } => {
+                write!(f, "gas used: {simulated} simulated, {onchain} on-chain")
+            }
+            Self::Storage { address, slot, simulated, onchain } => write!(
+                f,
+                "SSTORE {address} slot {slot:#x}: {} simulated, {} on-chain",
+                value(simulated),
+                value(onchain)
+            ),
+        }
+    }
+}
+
+/// Compares a simulated execution with the gas used on-chain and the storage changes returned by
+/// the `prestateTracer` in diff mode.
+pub fn diff(simulated: &SimulatedExecution, onchain_gas: u64, trace: &Value) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+    if simulated.gas_used.abs_diff(onchain_gas) * 100 >
+        simulated.gas_used.max(onchain_gas) * GAS_TOLERANCE_PERCENT
+    {
+        divergences.push(Divergence::Gas { simulated: simulated.gas_used, onchain: onchain_gas });
+    }
+
+    let onchain = storage_writes(trace);
+    let addresses = simulated.storage.keys().chain(onchain.keys()).collect::<BTreeSet<_>>();
+    for address in addresses {
+        let simulated_slots = simulated.storage.get(address);
+        let onchain_slots = onchain.get(address);
+        let slots = simulated_slots
+            .into_iter()
+            .chain(onchain_slots)
+            .flat_map(|slots| slots.keys())
+            .collect::<BTreeSet<_>>();
+        for slot in slots {
+            let simulated = simulated_slots.and_then(|slots| slots.get(slot)).copied();
+            let onchain = onchain_slots.and_then(|slots| slots.get(slot)).copied();
+            if simulated != onchain {
+                divergences.push(Divergence::Storage {
+                    address: *address,
+                    slot: *slot,
+                    simulated,
+                    onchain,
+                });
+            }
+        }
+    }
+    divergences
+}
+
+/// Collects the storage writes from the output of the `prestateTracer` in diff mode.
+///
+/// The `pre` state only holds the slots which were changed, and the `post` state omits the ones
+/// which were cleared.
+fn storage_writes(trace: &Value) -> StorageWrites {
+    let slots = |state: &str, address: &str| {
+        trace[state][address]["storage"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(slot, value)| {
+                Some((U256::from_str(slot).ok()?, U256::from_str(value.as_str()?).ok()?))
+            })
+            .collect::<BTreeMap<_, _>>()
+    };
+
+    let mut writes = StorageWrites::new();
+    let accounts = ["pre", "post"]
+        .iter()
+        .filter_map(|state| trace[state].as_object())
+        .flat_map(|accounts| accounts.keys())
+        .collect::<BTreeSet<_>>();
+    for account in accounts {
+        let Ok(address) = Address::from_str(account) else { continue };
+        let mut account_writes = slots("post", account);
+        for slot in slots("pre", account).into_keys() {
+            account_writes.entry(slot).or_insert(U256::ZERO);
+        }
+        if !account_writes.is_empty() {
+            writes.insert(address, account_writes);
+        }
+    }
+    writes
+}
+
+impl BroadcastedState {
+    /// Fetches the traces of the mined transactions and prints where their execution diverged
+    /// from the simulation, e.g. because the state changed in between or the chain behaves
+    /// differently than the local EVM.
+    pub async fn diff_onchain_traces(&self) -> Result<()> {
+        let executions = &self.execution_artifacts.simulated_executions;
+        let mut compared = 0;
+        let mut diverged = 0;
+        for sequence in self.sequence.sequences() {
+            let provider = get_http_provider(sequence.rpc_url());
+            for tx in &sequence.transactions {
+                let (Some(hash), Some(simulated)) = (tx.hash, executions.get(tx)) else { continue };
+                let Some(gas_used) = sequence
+                    .receipts
+                    .iter()
+                    .find(|receipt| receipt.transaction_hash.to_alloy() == hash)
+                    .and_then(|receipt| receipt.gas_used)
+                else {
+                    continue
+                };
+
+                let options =
+                    json!({ "tracer": "prestateTracer", "tracerConfig": { "diffMode": true } });
+                let trace = match provider
+                    .request::<_, Value>("debug_traceTransaction", (hash.to_ethers(), options))
+                    .await
+                {
+                    Ok(trace) => trace,
+                    Err(err) => {
+                        shell::println(Paint::yellow(format!(
+                            "\nCould not trace the transactions on chain {}, the RPC may not \
+                             support `debug_traceTransaction`: {err}",
+                            sequence.chain
+                        )))?;
+                        break
+                    }
+                };
+
+                compared += 1;
+                let divergences = diff(simulated, gas_used.as_u64(), &trace);
+                if divergences.is_empty() {
+                    continue
+                }
+                diverged += 1;
+                shell::println(Paint::yellow(format!(
+                    "\nTransaction {hash:?} on chain {} diverged from its simulation:",
+                    sequence.chain
+                )))?;
+                for divergence in divergences {
+                    shell::println(format!("  {divergence}"))?;
+                }
+            }
+        }
+
+        if compared > 0 && diverged == 0 {
+            shell::println(format!(
+                "\nThe on-chain execution of the {compared} transactions matched their simulation."
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    const TOKEN: Address = address!("00000000000000000000000000000000000000aa");
+
+    fn simulated(gas_used: u64, slots: &[(u64, u64)]) -> SimulatedExecution {
+        let slots = slots.iter().map(|(slot, value)| (U256::from(*slot), U256::from(*value)));
+        SimulatedExecution { gas_used, storage: BTreeMap::from([(TOKEN, slots.collect())]) }
+    }
+
+    #[test]
+    fn can_parse_prestate_diff() {
+        let trace = json!({
+            "pre": { "0x00000000000000000000000000000000000000aa": { "storage": {
+                "0x0000000000000000000000000000000000000000000000000000000000000001": "0x05",
+                "0x0000000000000000000000000000000000000000000000000000000000000002": "0x07",
+            }}},
+            "post": { "0x00000000000000000000000000000000000000aa": { "storage": {
+                "0x0000000000000000000000000000000000000000000000000000000000000001": "0x06",
+            }}},
+        });
+        assert_eq!(
+            storage_writes(&trace),
+            BTreeMap::from([(
+                TOKEN,
+                BTreeMap::from([(U256::from(1), U256::from(6)), (U256::from(2), U256::ZERO)])
+            )])
+        );
+    }
+
+    #[test]
+    fn flags_divergent_sstores() {
+        let trace = json!({
+            "pre": {},
+            "post": { "0x00000000000000000000000000000000000000aa": { "storage": {
+                "0x01": "0x06",
+                "0x03": "0x01",
+            }}},
+        });
+        assert_eq!(
+            diff(&simulated(50_000, &[(1, 6), (2, 1)]), 48_000, &trace),
+            vec![
+                Divergence::Storage {
+                    address: TOKEN,
+                    slot: U256::from(2),
+                    simulated: Some(U256::from(1)),
+                    onchain: None,
+                },
+                Divergence::Storage {
+                    address: TOKEN,
+                    slot: U256::from(3),
+                    simulated: None,
+                    onchain: Some(U256::from(1)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_divergent_gas() {
+        let trace = json!({ "pre": {}, "post": {} });
+        assert!(diff(&simulated(50_000, &[]), 46_000, &trace).is_empty());
+        assert_eq!(
+            diff(&simulated(50_000, &[]), 80_000, &trace),
+            vec![Divergence::Gas { simulated: 50_000, onchain: 80_000 }]
+        );
+    }
+}
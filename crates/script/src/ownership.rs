@@ -0,0 +1,139 @@
+use crate::verify::BroadcastedState;
+use alloy_primitives::{fixed_bytes, Address, Selector, B256};
+use ethers_core::types::{transaction::eip2718::TypedTransaction, TransactionRequest};
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::{provider::ethers::get_http_provider, shell, types::ToEthers};
+use std::collections::BTreeMap;
+use yansi::Paint;
+
+/// `owner()`
+const OWNER: Selector = fixed_bytes!("8da5cb5b");
+/// `hasRole(bytes32,address)`
+const HAS_ROLE: Selector = fixed_bytes!("91d14854");
+
+/// Decodes the address returned by `owner()`, if the call returned one.
+fn decode_owner(output: &[u8]) -> Option<Address> {
+    (output.len() == 32 && output[..12].iter().all(|byte| *byte == 0))
+        .then(|| Address::from_slice(&output[12..]))
+}
+
+/// Decodes the boolean returned by `hasRole`, if the call returned one.
+fn decode_bool(output: &[u8]) -> Option<bool> {
+    (output.len() == 32 && output[..31].iter().all(|byte| *byte == 0) && output[31] <= 1)
+        .then(|| output[31] == 1)
+}
+
+fn has_role_calldata(role: B256, account: Address) -> Vec<u8> {
+    [HAS_ROLE.as_slice(), role.as_slice(), account.into_word().as_slice()].concat()
+}
+
+impl BroadcastedState {
+    /// Reads back the owner and the roles of the deployed contracts, and fails if the admin rights
+    /// didn't end up with the `expected_owner` of the policy.
+    ///
+    /// Contracts without `owner()` or `hasRole`, and the ones owned by the zero address such as
+    /// implementations behind proxies, are skipped.
+    pub async fn verify_ownership(&self) -> Result<()> {
+        let policy = &self.script_config.policy;
+        let Some(expected) = policy.expected_owner else { return Ok(()) };
+        let roles = if policy.owner_roles.is_empty() {
+            BTreeMap::from([(B256::ZERO, "DEFAULT_ADMIN_ROLE".to_string())])
+        } else {
+            policy.owner_roles.clone()
+        };
+
+        let mut failures = Vec::new();
+        let mut checked = 0;
+        for sequence in self.sequence.sequences() {
+            let provider = get_http_provider(sequence.rpc_url());
+            let call = |to: Address, data: Vec<u8>| {
+                let tx: TypedTransaction =
+                    TransactionRequest::new().to(to.to_ethers()).data(data).into();
+                let provider = &provider;
+                async move { provider.call(&tx, None).await.ok() }
+            };
+
+            let contracts = sequence
+                .transactions
+                .iter()
+                .filter(|tx| tx.hash.is_some())
+                .filter_map(|tx| Some((tx.contract_address?, tx.contract_name.clone())));
+            for (address, name) in contracts {
+                let name = name.unwrap_or_else(|| address.to_string());
+                let owner = call(address, OWNER.to_vec()).await.and_then(|out| decode_owner(&out));
+                if let Some(owner) = owner.filter(|owner| !owner.is_zero()) {
+                    checked += 1;
+                    if owner != expected {
+                        failures.push(format!(
+                            "{name} ({address}) on chain {} is owned by {owner}",
+                            sequence.chain
+                        ));
+                    }
+                }
+
+                for (role, role_name) in &roles {
+                    let output = call(address, has_role_calldata(*role, expected)).await;
+                    let Some(has_role) = output.and_then(|out| decode_bool(&out)) else { break };
+                    checked += 1;
+                    if !has_role {
+                        failures.push(format!(
+                            "{expected} doesn't have {role_name} on {name} ({address}) on chain {}",
+                            sequence.chain
+                        ));
+                    }
+                }
+            }
+        }
+
+        shell::println("\n==========================")?;
+        shell::println("Ownership:\n")?;
+        if failures.is_empty() {
+            if checked > 0 {
+                shell::println(Paint::green(format!(
+                    "The admin rights of the deployed contracts are held by {expected}."
+                )))?;
+            } else {
+                shell::println("No deployed contract has an owner or roles.")?;
+            }
+            return Ok(())
+        }
+        for failure in &failures {
+            shell::println(Paint::red(format!("FAIL: {failure}")))?;
+        }
+        eyre::bail!(
+            "The admin rights of {} deployed contracts didn't end up with {expected}.",
+            failures.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn can_decode_outputs() {
+        let owner = address!("00000000000000000000000000000000000000a1");
+        assert_eq!(decode_owner(owner.into_word().as_slice()), Some(owner));
+        assert_eq!(decode_owner(&[]), None);
+        assert_eq!(decode_owner(&[0xff; 32]), None);
+
+        let mut output = [0; 32];
+        assert_eq!(decode_bool(&output), Some(false));
+        output[31] = 1;
+        assert_eq!(decode_bool(&output), Some(true));
+        output[31] = 2;
+        assert_eq!(decode_bool(&output), None);
+    }
+
+    #[test]
+    fn encodes_has_role() {
+        let account = address!("00000000000000000000000000000000000000a1");
+        let calldata = has_role_calldata(B256::ZERO, account);
+        assert_eq!(calldata.len(), 68);
+        assert_eq!(&calldata[..4], HAS_ROLE.as_slice());
+        assert_eq!(&calldata[48..], account.as_slice());
+    }
+}
@@ -0,0 +1,231 @@
+use crate::sequence::ScriptSequence;
+use alloy_primitives::{Address, Bytes, U256, U64};
+use ethers_core::types::{BlockId, BlockNumber, NameOrAddress};
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::{
+    provider::ethers::RetryProvider,
+    shell,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_evm::decode::RevertDecoder;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use yansi::Paint;
+
+/// A call of `eth_simulateV1`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SimulatedCall {
+    from: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to: Option<Address>,
+    value: U256,
+    input: Bytes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gas: Option<U256>,
+}
+
+/// A block simulated by `eth_simulateV1`.
+#[derive(Clone, Debug, Deserialize)]
+struct SimulatedBlock {
+    calls: Vec<CallResult>,
+}
+
+/// The result of a call simulated by `eth_simulateV1`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CallResult {
+    status: U64,
+    #[serde(default)]
+    return_data: Bytes,
+    #[serde(default)]
+    error: Option<CallError>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct CallError {
+    message: String,
+}
+
+/// A transaction of the script which conflicts with the pending state of the chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PendingConflict {
+    /// The sender has in-flight transactions using the nonces of the script.
+    Nonce { sender: Address, nonce: u64, pending: u64 },
+    /// The transaction reverts atop the pending block.
+    Revert { index: usize, reason: String },
+}
+
+impl std::fmt::Display for PendingConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Nonce { sender, nonce, pending } => write!(
+                f,
+                "{sender} has {} in-flight transaction(s) using nonces {nonce} to {}, which the \
+                 script also uses",
+                pending - nonce,
+                pending - 1
+            ),
+            Self::Revert { index, reason } => {
+                write!(f, "transaction {index} reverts atop the pending block: {reason}")
+            }
+        }
+    }
+}
+
+/// Returns the senders whose first transaction of the script uses a nonce taken by one of their
+/// in-flight transactions.
+fn nonce_conflicts(
+    first_nonces: &BTreeMap<Address, u64>,
+    pending_nonces: &BTreeMap<Address, u64>,
+) -> Vec<PendingConflict> {
+    first_nonces
+        .iter()
+        .filter_map(|(sender, nonce)| {
+            let pending = *pending_nonces.get(sender)?;
+            (pending > *nonce).then_some(PendingConflict::Nonce {
+                sender: *sender,
+                nonce: *nonce,
+                pending,
+            })
+        })
+        .collect()
+}
+
+/// Returns the calls of the transactions of `sequence` from `first` on.
+fn simulated_calls(sequence: &ScriptSequence, first: usize) -> Vec<SimulatedCall> {
+    sequence
+        .typed_transactions()
+        .skip(first)
+        .map(|tx| SimulatedCall {
+            from: tx.from().copied().unwrap_or_default().to_alloy(),
+            to: match tx.to() {
+                Some(NameOrAddress::Address(to)) => Some(to.to_alloy()),
+                _ => None,
+            },
+            value: tx.value().copied().unwrap_or_default().to_alloy(),
+            input: tx.data().cloned().unwrap_or_default().0.into(),
+            gas: tx.gas().map(|gas| gas.to_alloy()),
+        })
+        .collect()
+}
+
+/// Returns the reverted calls of a simulation, numbered from `first`.
+fn reverts(blocks: &[SimulatedBlock], first: usize) -> Vec<PendingConflict> {
+    blocks
+        .iter()
+        .flat_map(|block| &block.calls)
+        .enumerate()
+        .filter(|(_, call)| call.status.is_zero())
+        .map(|(i, call)| PendingConflict::Revert {
+            index: first + i,
+            reason: match &call.error {
+                Some(error) if call.return_data.is_empty() => error.message.clone(),
+                _ => RevertDecoder::new().decode(&call.return_data, None),
+            },
+        })
+        .collect()
+}
+
+/// Simulates the transactions of `sequence` from `first` on, in order atop the `pending` block,
+/// and fails if they conflict with in-flight transactions.
+///
+/// The whole bundle is simulated with `eth_simulateV1` when the RPC supports it, otherwise only
+/// the nonces of the senders are checked against their pending ones.
+pub async fn simulate_pending(
+    provider: &RetryProvider,
+    sequence: &ScriptSequence,
+    first: usize,
+) -> Result<()> {
+    let pending = Some(BlockId::Number(BlockNumber::Pending));
+
+    let mut first_nonces = BTreeMap::new();
+    for tx in sequence.typed_transactions().skip(first) {
+        if let (Some(from), Some(nonce)) = (tx.from(), tx.nonce()) {
+            first_nonces.entry(from.to_alloy()).or_insert(nonce.as_u64());
+        }
+    }
+    let mut pending_nonces = BTreeMap::new();
+    for sender in first_nonces.keys() {
+        let nonce = provider.get_transaction_count(sender.to_ethers(), pending).await?;
+        pending_nonces.insert(*sender, nonce.as_u64());
+    }
+    let mut conflicts = nonce_conflicts(&first_nonces, &pending_nonces);
+
+    let calls = simulated_calls(sequence, first);
+    let params = serde_json::json!([
+        { "blockStateCalls": [{ "calls": calls }], "validation": false },
+        "pending",
+    ]);
+    match provider.request::<_, Vec<SimulatedBlock>>("eth_simulateV1", params).await {
+        Ok(blocks) => {
+            conflicts.extend(reverts(&blocks, first));
+            if conflicts.is_empty() {
+                shell::println(format!(
+                    "Simulated {} transactions atop the pending block of chain {}",
+                    calls.len(),
+                    sequence.chain
+                ))?;
+            }
+        }
+        Err(err) => {
+            debug!(%err, "eth_simulateV1 failed");
+            shell::println(Paint::yellow(format!(
+                "The RPC of chain {} can't simulate transactions atop the pending block, only the \
+                 pending nonces of the senders were checked",
+                sequence.chain
+            )))?;
+        }
+    }
+
+    if !conflicts.is_empty() {
+        let conflicts = conflicts.iter().map(|conflict| format!("\n  - {conflict}"));
+        eyre::bail!(
+            "The transactions conflict with the pending state of chain {}:{}",
+            sequence.chain,
+            conflicts.collect::<String>()
+        )
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_nonce_conflicts() {
+        let (a, b) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let first = BTreeMap::from([(a, 5), (b, 3)]);
+        let pending = BTreeMap::from([(a, 7), (b, 3)]);
+        assert_eq!(
+            nonce_conflicts(&first, &pending),
+            vec![PendingConflict::Nonce { sender: a, nonce: 5, pending: 7 }]
+        );
+        assert_eq!(
+            nonce_conflicts(&first, &pending)[0].to_string(),
+            format!("{a} has 2 in-flight transaction(s) using nonces 5 to 6, which the script also uses")
+        );
+    }
+
+    #[test]
+    fn finds_reverts() {
+        let blocks: Vec<SimulatedBlock> = serde_json::from_value(serde_json::json!([{
+            "calls": [
+                { "status": "0x1", "returnData": "0x", "gasUsed": "0x5208", "logs": [] },
+                {
+                    "status": "0x0",
+                    "returnData": "0x",
+                    "gasUsed": "0x5208",
+                    "error": { "code": 3, "message": "execution reverted" }
+                },
+            ]
+        }]))
+        .unwrap();
+        assert_eq!(
+            reverts(&blocks, 2),
+            vec![PendingConflict::Revert { index: 3, reason: "execution reverted".to_string() }]
+        );
+    }
+}
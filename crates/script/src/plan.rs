@@ -0,0 +1,722 @@
+use crate::{
+    broadcast::BundledState,
+    calldata::{self, CalldataCost, CalldataCosts},
+    deposits::L1ToL2Deposit,
+    governance::RehearsedStep,
+    multi_sequence::MultiChainSequence,
+    selection::{self, TxFilter, TxSelection},
+    sequence::{ScriptSequence, ScriptSequenceKind},
+    simulate::PreSimulationState,
+    templates::DeploymentTemplate,
+    tokens::TokenAmount,
+    transaction::TransactionWithMetadata,
+    vanity::VanitySalt,
+    ScriptArgs,
+};
+use alloy_primitives::{keccak256, B256};
+use clap::{Parser, ValueHint};
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_cli::utils::LoadConfig;
+use foundry_common::{
+    fs,
+    provider::ethers::{try_get_http_provider, RpcUrl},
+    shell,
+};
+use foundry_compilers::artifacts::Libraries;
+use foundry_config::Chain;
+use foundry_evm::inspectors::cheatcodes::BroadcastableTransactions;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    iter,
+    path::{Path, PathBuf},
+};
+
+/// The transactions of a script run, written by `forge script plan` and broadcast by
+/// `forge script execute`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ScriptPlan {
+    /// The `forge script` arguments the plan was made with.
+    pub args: Vec<String>,
+    /// Whether the transactions are sent to multiple chains.
+    pub multi: bool,
+    /// The unsigned transactions, per chain.
+    pub sequences: Vec<ScriptSequence>,
+    /// The amounts of tokens of the arguments and parameters, and how they were resolved.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub token_amounts: Vec<TokenAmount>,
+    /// The arguments and parameters which reference the addresses of deployments of the script,
+    /// e.g. `${deployments.Token}`, and what they resolved to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deployment_templates: Vec<DeploymentTemplate>,
+    /// The feature flags read by the script with `vm.isFeatureEnabled`, and whether they were
+    /// enabled.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub features: BTreeMap<String, bool>,
+    /// The CREATE2 salts mined with `--vanity-prefix`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vanity_salts: Vec<VanitySalt>,
+    /// The governance steps which complete the proposals of the script, rehearsed in the
+    /// simulation with `--rehearse-governance`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub governance_rehearsal: Vec<RehearsedStep>,
+    /// The L1→L2 deposits of the transactions, with their outcomes on the L2 if the plan was
+    /// made with `--l2-rpc-url`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deposits: Vec<L1ToL2Deposit>,
+    /// The size and cost of the calldata of the transactions, with their L1 data fees on rollups.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calldata: Vec<CalldataCost>,
+    /// The block the script was simulated at, if the plan was made with `--deterministic`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fork_block_number: Option<u64>,
+    /// A checksum of the rest of the plan, to catch accidental edits of the file, e.g. by a merge,
+    /// before executing it.
+    ///
+    /// Anyone editing the plan can compute it again, so it doesn't protect against deliberate
+    /// changes: review the plan before executing it.
+    #[serde(default)]
+    pub digest: B256,
+}
+
+impl ScriptPlan {
+    /// Removes what differs between two runs of the same script at the same block: the
+    /// timestamps of the sequences and the fees, which are estimated again when broadcasting.
+    pub fn make_deterministic(&mut self) {
+        for cost in &mut self.calldata {
+            cost.l1_fee = None;
+        }
+        for sequence in &mut self.sequences {
+            sequence.timestamp = 0;
+            for tx in &mut sequence.transactions {
+                match &mut tx.transaction {
+                    TypedTransaction::Legacy(tx) => tx.gas_price = None,
+                    TypedTransaction::Eip2930(tx) => tx.tx.gas_price = None,
+                    TypedTransaction::Eip1559(tx) => {
+                        tx.max_fee_per_gas = None;
+                        tx.max_priority_fee_per_gas = None;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the checksum of the plan without its digest: the keccak256 hash of its JSON with
+    /// sorted keys.
+    fn compute_digest(&self) -> Result<B256> {
+        let plan = Self { digest: B256::ZERO, ..self.clone() };
+        let plan = sort_keys(serde_json::to_value(plan)?);
+        Ok(keccak256(serde_json::to_string(&plan)?))
+    }
+
+    /// Records the checksum of the plan.
+    fn seal(&mut self) -> Result<()> {
+        self.digest = self.compute_digest()?;
+        Ok(())
+    }
+
+    /// Fails if the plan doesn't match its checksum, i.e. it was edited since it was written
+    /// without updating the checksum.
+    fn check_digest(&self) -> Result<()> {
+        if self.digest != self.compute_digest()? {
+            eyre::bail!(
+                "The plan doesn't match its checksum, it was edited since it was made. Make it \
+                 again with `forge script plan`"
+            )
+        }
+        Ok(())
+    }
+
+    /// Writes the plan, with the keys of all its maps sorted if it's deterministic, so that the
+    /// plans of identical runs are byte-identical.
+    fn write(&self, path: &Path, deterministic: bool) -> Result<()> {
+        if !deterministic {
+            return Ok(fs::write_json_file(path, self)?)
+        }
+        let plan = sort_keys(serde_json::to_value(self)?);
+        fs::write(path, serde_json::to_string_pretty(&plan)? + "\n")?;
+        Ok(())
+    }
+}
+
+/// Sorts the keys of the objects of the value, recursively.
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut entries = object.into_iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(entries.into_iter().map(|(key, value)| (key, sort_keys(value))).collect())
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+        value => value,
+    }
+}
+
+/// CLI arguments for `forge script plan`.
+#[derive(Clone, Debug, Parser)]
+pub struct PlanArgs {
+    /// Where to write the plan.
+    #[arg(
+        long,
+        short,
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH",
+        default_value = "plan.json"
+    )]
+    out: PathBuf,
+
+    /// Makes the plan reproducible: the script is simulated at a pinned block, the current one
+    /// unless `--fork-block-number` is passed, and the timestamps and fees are left out of the
+    /// plan, so that plans made with the same arguments from the same commit are byte-identical.
+    ///
+    /// Forks created by the script itself are not pinned.
+    #[arg(long)]
+    deterministic: bool,
+
+    /// The script and the `forge script` arguments to simulate it with, e.g.
+    /// `script/Deploy.s.sol --rpc-url mainnet`.
+    #[arg(required = true, allow_hyphen_values = true, trailing_var_arg = true)]
+    args: Vec<String>,
+}
+
+impl PlanArgs {
+    pub async fn run(self) -> Result<()> {
+        let mut args = parse_script_args(&self.args)?;
+        if args.broadcast || args.resume || args.verify {
+            eyre::bail!(
+                "`forge script plan` only simulates the script, broadcast and verify the plan \
+                 with `forge script execute`"
+            )
+        }
+        let fork_block_number =
+            if self.deterministic { args.pin_fork_block().await? } else { None };
+
+        let Some(pre_simulation) = args.pre_simulation().await? else {
+            eyre::bail!("The script has no transactions to plan")
+        };
+        pre_simulation.args.check_contract_sizes(
+            &pre_simulation.execution_result,
+            &pre_simulation.build_data.highlevel_known_contracts,
+        )?;
+        let bundled = pre_simulation.fill_metadata().await?.bundle().await?;
+
+        let mut plan = ScriptPlan {
+            args: self.args,
+            multi: matches!(bundled.sequence, ScriptSequenceKind::Multi(_)),
+            sequences: bundled.sequence.sequences().to_vec(),
+            token_amounts: bundled.script_config.token_amounts.clone(),
+            deployment_templates: bundled.script_config.deployment_templates.clone(),
+            features: bundled
+                .script_config
+                .script_params
+                .as_ref()
+                .map(|params| params.features())
+                .unwrap_or_default(),
+            vanity_salts: bundled
+                .script_config
+                .vanity
+                .as_ref()
+                .map(|vanity| vanity.salts.clone())
+                .unwrap_or_default(),
+            governance_rehearsal: bundled.execution_artifacts.governance_rehearsal.clone(),
+            deposits: bundled.report().await?,
+            calldata: calldata::analyze(bundled.sequence.sequences()).await,
+            fork_block_number,
+        };
+        if self.deterministic {
+            plan.make_deterministic();
+        }
+        plan.seal()?;
+        plan.write(&self.out, self.deterministic)?;
+
+        let count =
+            plan.sequences.iter().map(|sequence| sequence.transactions.len()).sum::<usize>();
+        shell::println(format!(
+            "\nPlan with {count} transactions saved to: {}\nBroadcast it with `forge script \
+             execute {}` and wallet configuration(s), from the same sources and with access to the \
+             same RPC URLs, as the script is simulated again to check the plan.",
+            self.out.display(),
+            self.out.display()
+        ))?;
+        if !plan.calldata.is_empty() {
+            shell::println(format!("\nCalldata:\n{}", CalldataCosts(&plan.calldata)))?;
+        }
+
+        let raw_deployments = plan
+            .sequences
+            .iter()
+            .flat_map(|sequence| {
+                let chain = Chain::from(sequence.chain);
+                sequence
+                    .raw_deployments()
+                    .into_iter()
+                    .map(move |address| format!("\n  - {address} on {chain}"))
+            })
+            .collect::<String>();
+        if !raw_deployments.is_empty() {
+            shell::println(format!(
+                "\nThese contracts are deployed from raw bytecode:{raw_deployments}\nThey aren't \
+                 verified with `--verify`, verify them with `forge verify-contract` once their \
+                 sources are added to the project."
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+/// CLI arguments for `forge script execute`.
+///
+/// The transactions broadcast are the ones of the plan, but the script is still compiled and
+/// simulated again, to check that the plan is current and to get the artifacts for verification:
+/// the sources of the project and the RPC URLs of the plan must be available.
+#[derive(Clone, Debug, Parser)]
+pub struct ExecuteArgs {
+    /// The plan written by `forge script plan`.
+    #[arg(value_hint = ValueHint::FilePath, value_name = "PLAN")]
+    plan: PathBuf,
+
+    /// Only broadcasts these transactions of the plan, numbered from 0 across its chains, e.g.
+    /// `1,4-7`.
+    ///
+    /// The contracts created by the transactions which aren't selected must already be deployed,
+    /// and the senders must be at the nonces of their first selected transactions.
+    #[arg(long, value_name = "INDEXES")]
+    only_txs: Option<TxSelection>,
+
+    /// Doesn't broadcast these transactions of the plan, numbered as with `--only-txs`.
+    #[arg(long, value_name = "INDEXES")]
+    skip_txs: Option<TxSelection>,
+
+    /// `forge script` arguments added to the ones of the plan, e.g. the wallet options and
+    /// `--verify`.
+    #[arg(allow_hyphen_values = true, trailing_var_arg = true)]
+    args: Vec<String>,
+}
+
+impl ExecuteArgs {
+    pub async fn run(self) -> Result<()> {
+        let plan: ScriptPlan = fs::read_json_file(&self.plan)?;
+        plan.check_digest()?;
+        let filter = TxFilter { only: self.only_txs, skip: self.skip_txs };
+
+        // The script is executed again to get the build data for verification, and to check that
+        // the plan is still up to date, but the transactions broadcast are the ones of the plan.
+        let mut args = parse_script_args(&execute_args(&plan.args, &self.args))?;
+        args.planned_salts.clone_from(&plan.vanity_salts);
+        if args.resume {
+            eyre::bail!("`forge script execute` can't be resumed, use `forge script --resume`")
+        }
+
+        let Some(pre_simulation) = args.pre_simulation().await? else {
+            eyre::bail!(
+                "The script has no transactions anymore, it changed since the plan was made"
+            )
+        };
+        let bundled = pre_simulation.load_plan(plan, &filter).await?;
+        bundled.report().await?;
+        bundled.broadcast_and_verify().await
+    }
+}
+
+impl ScriptArgs {
+    /// Pins the fork to the current block, unless `--fork-block-number` was passed, and returns
+    /// the pinned block. Returns `None` without an RPC URL, as the script isn't forked.
+    async fn pin_fork_block(&mut self) -> Result<Option<u64>> {
+        if self.reuse_fork_block.is_some() {
+            eyre::bail!("`--deterministic` can't be used with `--reuse-fork-block`")
+        }
+        if let Some(block) = self.evm_opts.fork_block_number {
+            return Ok(Some(block))
+        }
+        let Some(rpc) = self.load_config().get_rpc_url() else { return Ok(None) };
+        let block = try_get_http_provider(&*rpc?)?.get_block_number().await?.as_u64();
+        shell::println(format!(
+            "Pinned the fork to block {block}, pass `--fork-block-number {block}` to reproduce \
+             the plan"
+        ))?;
+        self.evm_opts.fork_block_number = Some(block);
+        Ok(Some(block))
+    }
+}
+
+/// Returns the `forge script` arguments which broadcast the plan: the ones of the plan, overridden
+/// by the ones passed to `forge script execute`, see `args_override_self`.
+fn execute_args(plan: &[String], args: &[String]) -> Vec<String> {
+    let broadcast = iter::once("--broadcast".to_string());
+    plan.iter().cloned().chain(broadcast).chain(args.iter().cloned()).collect()
+}
+
+/// Fails if the transactions of the plan differ from the ones made by the script executed again,
+/// e.g. because the script, its parameters or the state of the chains changed since the plan was
+/// made.
+///
+/// The transactions are compared chain by chain, in order, as a script switching between forks
+/// interleaves the transactions of their chains. `chains` are the chain IDs of the RPC URLs of the
+/// executed transactions. Only the senders, recipients, values and calldata are compared, as the
+/// gas and fees are estimated again when broadcasting.
+fn check_plan_is_current(
+    sequences: &[ScriptSequence],
+    executed: Option<&BroadcastableTransactions>,
+    chains: &HashMap<RpcUrl, u64>,
+) -> Result<()> {
+    let mut planned = BTreeMap::<u64, Vec<&TransactionWithMetadata>>::new();
+    for sequence in sequences {
+        planned.entry(sequence.chain).or_default().extend(&sequence.transactions);
+    }
+    let mut executed_by_chain = BTreeMap::<u64, Vec<TransactionWithMetadata>>::new();
+    for tx in executed.into_iter().flatten() {
+        let Some(chain) = tx.rpc.as_ref().and_then(|rpc| chains.get(rpc)) else {
+            eyre::bail!("The script now makes a transaction without a known RPC URL")
+        };
+        executed_by_chain
+            .entry(*chain)
+            .or_default()
+            .push(TransactionWithMetadata::from_tx_request(tx.transaction.clone()));
+    }
+
+    let planned_count = planned.values().map(Vec::len).sum::<usize>();
+    let executed_count = executed_by_chain.values().map(Vec::len).sum::<usize>();
+    if planned_count != executed_count {
+        eyre::bail!(
+            "The plan has {planned_count} transactions but the script now makes \
+             {executed_count}, it changed since the plan was made. Make the plan again with \
+             `forge script plan`."
+        )
+    }
+    for chain in planned.keys().chain(executed_by_chain.keys()).collect::<BTreeSet<_>>() {
+        let planned = planned.get(chain).map(Vec::as_slice).unwrap_or_default();
+        let executed = executed_by_chain.get(chain).map(Vec::as_slice).unwrap_or_default();
+        if planned.len() != executed.len() {
+            eyre::bail!(
+                "The plan has {} transactions on chain {chain} but the script now makes {}, it \
+                 changed since the plan was made. Make the plan again with `forge script plan`.",
+                planned.len(),
+                executed.len()
+            )
+        }
+        for (i, (planned, executed)) in planned.iter().zip(executed).enumerate() {
+            let (planned, executed) = (&planned.transaction, &executed.transaction);
+            if planned.from() != executed.from() ||
+                planned.to() != executed.to() ||
+                planned.value().copied().unwrap_or_default() !=
+                    executed.value().copied().unwrap_or_default() ||
+                planned.data().map_or(&[][..], |data| data.as_ref()) !=
+                    executed.data().map_or(&[][..], |data| data.as_ref())
+            {
+                eyre::bail!(
+                    "Transaction {i} of the plan on chain {chain} differs from the one the script \
+                     now makes, it or the state of the chain changed since the plan was made. \
+                     Make the plan again with `forge script plan`."
+                )
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_script_args(args: &[String]) -> Result<ScriptArgs> {
+    let args = iter::once("forge script".to_string()).chain(args.iter().cloned());
+    let args = ScriptArgs::try_parse_from(args)
+        .map_err(|err| eyre::eyre!("Invalid `forge script` arguments:\n{err}"))?;
+    if args.command.is_some() {
+        eyre::bail!("Invalid `forge script` arguments: subcommands can't be nested")
+    }
+    shell::set_shell(shell::Shell::from_args(args.opts.silent, args.json))?;
+    Ok(args)
+}
+
+impl PreSimulationState {
+    /// Replaces the simulated transactions with the ones of the plan, sending them to the RPCs of
+    /// their chains.
+    ///
+    /// Unless some are filtered out, the transactions of the plan must be the ones of this
+    /// execution. Only the transactions selected by `filter` are kept, once checked against the
+    /// chains.
+    async fn load_plan(mut self, plan: ScriptPlan, filter: &TxFilter) -> Result<BundledState> {
+        let mut rpcs = HashMap::new();
+        let mut chains = HashMap::new();
+        for rpc in &self.execution_artifacts.rpc_data.total_rpcs {
+            let provider = try_get_http_provider(rpc)?;
+            let chain = provider.get_chainid().await?.as_u64();
+            rpcs.insert(chain, rpc.clone());
+            chains.insert(rpc.clone(), chain);
+        }
+
+        // Transactions left out by the filter may already be broadcast, which changes what the
+        // script does when executed again.
+        if filter.is_empty() {
+            let executed = self.execution_result.transactions.as_ref();
+            check_plan_is_current(&plan.sequences, executed, &chains)?;
+        }
+
+        let mut sequences = plan.sequences;
+        for sequence in &mut sequences {
+            let Some(rpc) = rpcs.get(&sequence.chain) else {
+                eyre::bail!("The script has no RPC URL for chain {} of the plan", sequence.chain)
+            };
+            sequence.transactions.iter_mut().for_each(|tx| tx.rpc.clone_from(rpc));
+        }
+        if !filter.is_empty() {
+            let (dependencies, nonces) = filter.apply(&mut sequences)?;
+            selection::check_selection(&rpcs, &dependencies, &nonces).await?;
+            let selected =
+                sequences.iter().map(|sequence| sequence.transactions.len()).sum::<usize>();
+            shell::println(format!("Broadcasting {selected} selected transactions of the plan"))?;
+        }
+
+        // The deployments of the plan use the salts mined when it was made.
+        if let Some(vanity) = &mut self.script_config.vanity {
+            vanity.salts = plan.vanity_salts;
+        }
+
+        let config = &self.script_config.config;
+        let sig = &self.args.sig;
+        let target = &self.build_data.build_data.target;
+        let sequence = if plan.multi {
+            let sequence = MultiChainSequence::new(sequences, sig, target, config, false)?;
+            ScriptSequenceKind::Multi(sequence)
+        } else {
+            let Ok([mut sequence]) = <[_; 1]>::try_from(sequences) else {
+                eyre::bail!("Invalid plan: expected the transactions of a single chain")
+            };
+            sequence.paths =
+                Some(ScriptSequence::get_paths(config, sig, target, sequence.chain, false)?);
+
+            // Relink the contracts with the libraries deployed by the plan.
+            self.build_data =
+                self.build_data.relink_with_libraries(Libraries::parse(&sequence.libraries)?)?;
+            ScriptSequenceKind::Single(sequence)
+        };
+
+        let Self {
+            args,
+            script_config,
+            script_wallets,
+            build_data,
+            execution_data,
+            execution_result: _,
+            execution_artifacts,
+        } = self;
+
+        Ok(BundledState {
+            args,
+            script_config,
+            script_wallets,
+            build_data,
+            execution_data,
+            execution_artifacts,
+            sequence,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NestedValue, ScriptSubcommand};
+    use alloy_primitives::{address, Address, Bytes};
+    use alloy_rpc_types::request::TransactionRequest;
+    use ethers_core::types::Eip1559TransactionRequest;
+    use foundry_evm::inspectors::cheatcodes::BroadcastableTransaction;
+
+    const SENDER: Address = address!("4e59b44847b379578588920ca78fbf26c0b4956c");
+
+    fn transaction(input: &'static [u8]) -> TransactionRequest {
+        TransactionRequest {
+            from: Some(SENDER),
+            to: Some(Address::ZERO),
+            input: Some(Bytes::from_static(input)).into(),
+            ..Default::default()
+        }
+    }
+
+    fn planned(input: &'static [u8]) -> ScriptSequence {
+        planned_on(1, &[input])
+    }
+
+    fn planned_on(chain: u64, inputs: &[&'static [u8]]) -> ScriptSequence {
+        let mut sequence = ScriptSequence { chain, ..Default::default() };
+        for input in inputs {
+            sequence
+                .transactions
+                .push_back(TransactionWithMetadata::from_tx_request(transaction(input)));
+        }
+        sequence
+    }
+
+    #[test]
+    fn can_parse_plan_and_execute() {
+        let args = ScriptArgs::parse_from([
+            "foundry-cli",
+            "plan",
+            "-o",
+            "deploy.json",
+            "script/Deploy.s.sol",
+            "--rpc-url",
+            "mainnet",
+        ]);
+        let Some(ScriptSubcommand::Plan(plan)) = args.command else { panic!("expected plan") };
+        assert_eq!(plan.out, PathBuf::from("deploy.json"));
+        assert_eq!(plan.args, ["script/Deploy.s.sol", "--rpc-url", "mainnet"]);
+
+        let args = ScriptArgs::parse_from([
+            "foundry-cli",
+            "execute",
+            "deploy.json",
+            "--ledger",
+            "--verify",
+        ]);
+        let Some(ScriptSubcommand::Execute(execute)) = args.command else {
+            panic!("expected execute")
+        };
+        assert_eq!(execute.plan, PathBuf::from("deploy.json"));
+        assert_eq!(execute.args, ["--ledger", "--verify"]);
+        assert!(execute.only_txs.is_none() && execute.skip_txs.is_none());
+
+        let args = ScriptArgs::parse_from([
+            "foundry-cli",
+            "execute",
+            "--only-txs",
+            "1,4-7",
+            "--skip-txs",
+            "5",
+            "deploy.json",
+            "--ledger",
+        ]);
+        let Some(ScriptSubcommand::Execute(execute)) = args.command else {
+            panic!("expected execute")
+        };
+        assert!(execute.only_txs.unwrap().contains(4));
+        assert!(execute.skip_txs.unwrap().contains(5));
+        assert_eq!(execute.args, ["--ledger"]);
+
+        let args = ScriptArgs::parse_from(["foundry-cli", "script/Deploy.s.sol"]);
+        assert!(args.command.is_none());
+        assert_eq!(args.path, "script/Deploy.s.sol");
+
+        let args = ScriptArgs::parse_from([
+            "foundry-cli",
+            "plan",
+            "--deterministic",
+            "script/Deploy.s.sol",
+            "--fork-block-number",
+            "100",
+        ]);
+        let Some(ScriptSubcommand::Plan(plan)) = args.command else { panic!("expected plan") };
+        assert!(plan.deterministic);
+        assert_eq!(plan.args, ["script/Deploy.s.sol", "--fork-block-number", "100"]);
+    }
+
+    #[test]
+    fn command_line_args_override_the_plan() {
+        let plan = ["script/Deploy.s.sol", "--rpc-url", "mainnet", "--slow"].map(String::from);
+        let cli = ["--rpc-url", "backup", "--verify"].map(String::from);
+        let args = iter::once("forge script".to_string()).chain(execute_args(&plan, &cli));
+        let args = ScriptArgs::try_parse_from(args).unwrap();
+        assert_eq!(args.path, "script/Deploy.s.sol");
+        assert_eq!(args.evm_opts.fork_url.as_deref(), Some("backup"));
+        assert!(args.broadcast && args.verify && args.slow);
+    }
+
+    #[test]
+    fn detects_modified_plans() {
+        let mut plan = ScriptPlan { sequences: vec![planned(&[1])], ..Default::default() };
+        plan.seal().unwrap();
+        assert!(plan.check_digest().is_ok());
+
+        let written = serde_json::to_string(&plan).unwrap();
+        let plan: ScriptPlan = serde_json::from_str(&written).unwrap();
+        assert!(plan.check_digest().is_ok());
+
+        let mut modified = plan.clone();
+        modified.sequences[0].transactions[0].transaction.set_data(vec![2].into());
+        assert!(modified.check_digest().is_err());
+    }
+
+    fn executed(txs: &[(&str, &'static [u8])]) -> BroadcastableTransactions {
+        txs.iter()
+            .map(|(rpc, input)| BroadcastableTransaction {
+                rpc: Some(rpc.to_string()),
+                transaction: transaction(input),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detects_stale_plans() {
+        let sequences = [planned(&[1])];
+        let chains = HashMap::from([("a".to_string(), 1)]);
+        let check = |txs: &[(&str, &'static [u8])]| {
+            check_plan_is_current(&sequences, Some(&executed(txs)), &chains)
+        };
+
+        assert!(check(&[("a", &[1])]).is_ok());
+        let err = check(&[("a", &[2])]).unwrap_err();
+        assert!(err.to_string().contains("Transaction 0 of the plan on chain 1 differs"), "{err}");
+        let err = check(&[("a", &[1]), ("a", &[2])]).unwrap_err();
+        assert!(err.to_string().contains("the script now makes 2"), "{err}");
+        assert!(check(&[("b", &[1])]).is_err());
+        assert!(check_plan_is_current(&sequences, None, &chains).is_err());
+    }
+
+    #[test]
+    fn compares_plans_chain_by_chain() {
+        // the script switches from chain 1 to chain 2 and back
+        let sequences = [planned_on(1, &[&[1], &[3]]), planned_on(2, &[&[2]])];
+        let chains = HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+        let check = |txs: &[(&str, &'static [u8])]| {
+            check_plan_is_current(&sequences, Some(&executed(txs)), &chains)
+        };
+
+        assert!(check(&[("a", &[1]), ("b", &[2]), ("a", &[3])]).is_ok());
+        let err = check(&[("a", &[3]), ("b", &[2]), ("a", &[1])]).unwrap_err();
+        assert!(err.to_string().contains("Transaction 0 of the plan on chain 1 differs"), "{err}");
+        let err = check(&[("a", &[1]), ("a", &[2]), ("a", &[3])]).unwrap_err();
+        assert!(err.to_string().contains("2 transactions on chain 1 but the script now makes 3"));
+    }
+
+    #[test]
+    fn writes_deterministic_plans() {
+        let mut sequence =
+            ScriptSequence { chain: 1, timestamp: 1_710_599_400, ..Default::default() };
+        for name in ["b", "a"] {
+            let value =
+                NestedValue { internal_type: "uint256".to_string(), value: "1".to_string() };
+            sequence.returns.insert(name.to_string(), value);
+        }
+        sequence.transactions.push_back(TransactionWithMetadata {
+            transaction: TypedTransaction::Eip1559(Eip1559TransactionRequest {
+                max_fee_per_gas: Some(100.into()),
+                max_priority_fee_per_gas: Some(1.into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        let mut plan = ScriptPlan {
+            sequences: vec![sequence],
+            fork_block_number: Some(100),
+            ..Default::default()
+        };
+        plan.make_deterministic();
+        let sequence = &plan.sequences[0];
+        assert_eq!(sequence.timestamp, 0);
+        let TypedTransaction::Eip1559(tx) = &sequence.transactions[0].transaction else {
+            panic!("expected an EIP-1559 transaction")
+        };
+        assert!(tx.max_fee_per_gas.is_none() && tx.max_priority_fee_per_gas.is_none());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plan.json");
+        plan.write(&path, true).unwrap();
+        let first = std::fs::read(&path).unwrap();
+        plan.write(&path, true).unwrap();
+        assert_eq!(first, std::fs::read(&path).unwrap());
+        let written = String::from_utf8(first).unwrap();
+        assert!(written.find("\"a\"").unwrap() < written.find("\"b\"").unwrap());
+        assert!(written.contains("\"fork_block_number\": 100"));
+    }
+}
@@ -0,0 +1,355 @@
+use crate::{
+    deny_list::DenyListSource, freeze::FreezeWindow, security::IgnoredCheck,
+    sequence::ScriptSequenceKind,
+};
+use alloy_primitives::{keccak256, utils::format_units, Address, Selector, B256, U256};
+use eyre::{Result, WrapErr};
+use foundry_cli::utils::parse_ether_value;
+use foundry_common::{abi::get_func, types::ToAlloy};
+use foundry_config::{
+    figment::{
+        providers::{Format, Toml},
+        Figment,
+    },
+    Chain, Config,
+};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+/// File name of the transaction policy, looked up in `~/.foundry` and in the project root.
+pub const POLICY_FILE_NAME: &str = "policy.toml";
+
+/// The content of a `policy.toml` file.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    /// The chains transactions may be sent to.
+    chains: Option<Vec<Chain>>,
+    /// The maximum value sent by the transactions of a run on a chain, e.g. `"10ether"`.
+    max_total_value: Option<String>,
+    /// Selectors or signatures of functions that must not be called, e.g. `"kill()"`.
+    #[serde(default)]
+    banned_selectors: Vec<String>,
+    /// The number of confirmations to wait for before a transaction is considered successful.
+    confirmations: Option<usize>,
+    /// Security checks whose findings are not reported, e.g. `"zero-address"`, or only for one
+    /// contract, e.g. `"deployer-owner:0x…"`.
+    #[serde(default)]
+    ignored_checks: Vec<String>,
+    /// The account which must own the deployed contracts once the script ran, e.g. a multisig.
+    expected_owner: Option<Address>,
+    /// The roles the expected owner must hold on the deployed contracts with access control,
+    /// by name, e.g. `"MINTER_ROLE"`, or as hex. Defaults to the default admin role.
+    #[serde(default)]
+    owner_roles: Vec<String>,
+    /// Windows during which broadcasting is frozen, e.g. weekends or token unlock days.
+    #[serde(default)]
+    freeze_windows: Vec<FreezeWindowFile>,
+    /// The keccak256 hash of the token which overrides the freeze windows of this file when
+    /// passed with `--freeze-override`.
+    freeze_override: Option<B256>,
+    /// Lists of flagged addresses, e.g. sanctioned ones, which transactions must not call or send
+    /// value to: files relative to the policy file or `http(s)` URLs, with an address per line or
+    /// a JSON array of addresses.
+    #[serde(default)]
+    deny_lists: Vec<String>,
+    /// Flagged addresses in addition to the deny lists.
+    #[serde(default)]
+    denied_addresses: Vec<Address>,
+    /// Whether broadcasting to the chains of `confirm_chain_ids` fails if the script was compiled
+    /// from a dirty tree or from a commit which isn't pushed, instead of warning.
+    require_clean_git: Option<bool>,
+}
+
+/// A freeze window of a `policy.toml` file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FreezeWindowFile {
+    name: String,
+    /// A cron expression matching the minutes of the window, in UTC, e.g. `"* * * * sat,sun"`.
+    cron: String,
+    /// The chains the window applies to, all of them by default.
+    chains: Option<Vec<Chain>>,
+    reason: Option<String>,
+}
+
+/// The transaction policy of a run, which is enforced before broadcasting.
+///
+/// It combines the global `~/.foundry/policy.toml` with the policy of the project, so that a
+/// project can only tighten the global policy: the allowed chains are intersected, the lowest
+/// maximum value and the highest number of confirmations win, and all banned selectors apply.
+/// The security checks ignored by either policy are not reported, as they are only warnings.
+/// The expected owner of the project takes precedence over the global one. The freeze windows of
+/// both policies apply, each of them can only be overridden with the token of its own file, and
+/// so do the deny lists of both. Either policy may require a clean tree.
+#[derive(Clone, Debug, Default)]
+pub struct TxPolicy {
+    /// The ids of the chains transactions may be sent to, if restricted.
+    pub chains: Option<BTreeSet<u64>>,
+    /// The maximum value sent by the transactions of a run on a chain.
+    pub max_total_value: Option<U256>,
+    /// The banned selectors, and how they were written in the policy.
+    pub banned_selectors: BTreeMap<Selector, String>,
+    /// The number of confirmations to wait for.
+    pub confirmations: usize,
+    /// The security checks whose findings are not reported.
+    pub ignored_checks: BTreeSet<IgnoredCheck>,
+    /// The account which must own the deployed contracts, checked once they're broadcast.
+    pub expected_owner: Option<Address>,
+    /// The roles the expected owner must hold on the deployed contracts with access control.
+    pub owner_roles: BTreeMap<B256, String>,
+    /// The windows during which broadcasting is frozen.
+    pub freeze_windows: Vec<FreezeWindow>,
+    /// The deny lists, read by [TxPolicy::load_deny_lists].
+    pub deny_list_sources: Vec<DenyListSource>,
+    /// The addresses transactions must not interact with, and the deny list they're on.
+    pub denied_addresses: BTreeMap<Address, String>,
+    /// Whether broadcasting to production chains requires a clean and pushed tree.
+    pub require_clean_git: bool,
+    /// The policy files that were loaded.
+    pub files: Vec<PathBuf>,
+}
+
+impl TxPolicy {
+    /// Loads the global policy and the policy at `path`, or the project's `policy.toml` if no
+    /// path is given.
+    pub fn load(root: &Path, path: Option<&Path>) -> Result<Self> {
+        if let Some(path) = path {
+            if !path.exists() {
+                eyre::bail!("Policy file {} does not exist", path.display())
+            }
+        }
+        let global = Config::foundry_dir().map(|dir| dir.join(POLICY_FILE_NAME));
+        let local = path.map(Path::to_path_buf).unwrap_or_else(|| root.join(POLICY_FILE_NAME));
+
+        let mut policy = Self::default();
+        for path in global.into_iter().chain(Some(local)) {
+            if path.exists() && !policy.files.contains(&path) {
+                policy
+                    .merge(&path)
+                    .wrap_err_with(|| format!("Failed to load policy file {}", path.display()))?;
+            }
+        }
+        Ok(policy)
+    }
+
+    fn merge(&mut self, path: &Path) -> Result<()> {
+        let file: PolicyFile = Figment::from(Toml::file(path)).extract()?;
+
+        if let Some(chains) = file.chains {
+            let chains = chains.iter().map(|chain| chain.id()).collect::<BTreeSet<_>>();
+            self.chains = Some(match self.chains.take() {
+                Some(allowed) => allowed.intersection(&chains).copied().collect(),
+                None => chains,
+            });
+        }
+        if let Some(value) = file.max_total_value {
+            let value = parse_ether_value(&value)?;
+            self.max_total_value = Some(self.max_total_value.map_or(value, |max| max.min(value)));
+        }
+        for selector in file.banned_selectors {
+            self.banned_selectors.insert(parse_selector(&selector)?, selector);
+        }
+        self.confirmations = self.confirmations.max(file.confirmations.unwrap_or_default());
+        for check in file.ignored_checks {
+            self.ignored_checks.insert(check.parse()?);
+        }
+        if file.expected_owner.is_some() {
+            self.expected_owner = file.expected_owner;
+        }
+        for role in file.owner_roles {
+            self.owner_roles.insert(parse_role(&role)?, role);
+        }
+        for window in file.freeze_windows {
+            self.freeze_windows.push(FreezeWindow {
+                cron: window.cron.parse()?,
+                name: window.name,
+                chains: window.chains.map(|chains| chains.iter().map(|chain| chain.id()).collect()),
+                reason: window.reason,
+                override_hash: file.freeze_override,
+                file: path.to_path_buf(),
+            });
+        }
+        let dir = path.parent().unwrap_or(Path::new("."));
+        self.deny_list_sources
+            .extend(file.deny_lists.iter().map(|source| DenyListSource::new(source, dir)));
+        for address in file.denied_addresses {
+            self.denied_addresses.insert(address, path.display().to_string());
+        }
+        self.require_clean_git |= file.require_clean_git.unwrap_or_default();
+        self.files.push(path.to_path_buf());
+
+        Ok(())
+    }
+
+    /// Checks the transactions of all sequences against the policy.
+    pub fn check(&self, sequence: &ScriptSequenceKind) -> Result<()> {
+        if self.files.is_empty() {
+            return Ok(())
+        }
+
+        let mut violations = Vec::new();
+        for sequence in sequence.sequences() {
+            if self.chains.as_ref().map_or(false, |chains| !chains.contains(&sequence.chain)) {
+                violations.push(format!("chain {} is not allowed", sequence.chain));
+            }
+
+            let mut total_value = U256::ZERO;
+            for tx in sequence.typed_transactions() {
+                total_value += tx.value().copied().unwrap_or_default().to_alloy();
+
+                let (Some(to), Some(data)) = (tx.to_addr(), tx.data()) else { continue };
+                let Some(selector) = data.get(..4).map(Selector::from_slice) else { continue };
+                if let Some(banned) = self.banned_selectors.get(&selector) {
+                    violations.push(format!(
+                        "call to {} on chain {} uses banned selector `{banned}`",
+                        to.to_alloy(),
+                        sequence.chain
+                    ));
+                }
+            }
+
+            if let Some(max) = self.max_total_value {
+                if total_value > max {
+                    violations.push(format!(
+                        "total value of {} ETH on chain {} exceeds the maximum of {} ETH",
+                        format_ether(total_value),
+                        sequence.chain,
+                        format_ether(max)
+                    ));
+                }
+            }
+        }
+
+        if !violations.is_empty() {
+            let files = self.files.iter().map(|path| path.display().to_string());
+            eyre::bail!(
+                "Transactions violate the policy of {}:\n  - {}",
+                files.collect::<Vec<_>>().join(", "),
+                violations.join("\n  - ")
+            )
+        }
+        Ok(())
+    }
+
+    /// Fails if broadcasting to any of `chains` at `timestamp` falls in a freeze window, unless
+    /// `token` overrides it.
+    pub fn check_freeze_windows(
+        &self,
+        chains: impl IntoIterator<Item = u64>,
+        timestamp: u64,
+        token: Option<&str>,
+    ) -> Result<()> {
+        let chains = chains.into_iter().collect::<BTreeSet<_>>();
+        let frozen = self
+            .freeze_windows
+            .iter()
+            .filter_map(|window| {
+                let chain =
+                    chains.iter().find(|chain| window.freezes(**chain, timestamp, token))?;
+                let reason =
+                    window.reason.as_ref().map(|reason| format!(": {reason}")).unwrap_or_default();
+                Some(format!(
+                    "chain {chain} is frozen by `{}` ({}) of {}{reason}",
+                    window.name,
+                    window.cron,
+                    window.file.display()
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        if !frozen.is_empty() {
+            eyre::bail!(
+                "Broadcasting is frozen by the policy:\n  - {}\nPass the override token of the \
+                 policy with --freeze-override to broadcast anyway.",
+                frozen.join("\n  - ")
+            )
+        }
+        Ok(())
+    }
+}
+
+fn format_ether(value: U256) -> String {
+    format_units(value, 18).unwrap_or_else(|_| value.to_string())
+}
+
+/// Parses a role from its hex representation or from its name, which is hashed like the roles of
+/// OpenZeppelin's `AccessControl`, except for `DEFAULT_ADMIN_ROLE` which is zero.
+fn parse_role(role: &str) -> Result<B256> {
+    if role.starts_with("0x") {
+        role.parse().wrap_err_with(|| format!("invalid role `{role}`"))
+    } else if role == "DEFAULT_ADMIN_ROLE" {
+        Ok(B256::ZERO)
+    } else {
+        Ok(keccak256(role))
+    }
+}
+
+/// Parses a selector from its hex representation or from a function signature.
+fn parse_selector(selector: &str) -> Result<Selector> {
+    if selector.starts_with("0x") {
+        selector.parse().wrap_err_with(|| format!("invalid selector `{selector}`"))
+    } else {
+        Ok(get_func(selector)?.selector())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_banned_selectors() {
+        let selector = parse_selector("transfer(address,uint256)").unwrap();
+        assert_eq!(selector, parse_selector("0xa9059cbb").unwrap());
+        assert!(parse_selector("0xa905").is_err());
+    }
+
+    #[test]
+    fn can_parse_roles() {
+        assert_eq!(parse_role("DEFAULT_ADMIN_ROLE").unwrap(), B256::ZERO);
+        assert_eq!(
+            parse_role("MINTER_ROLE").unwrap(),
+            "0x9f2df0fed2c77648de5860a4cc508cd0818c85b8b8a1ab4ceeef8d981c8956a6"
+                .parse::<B256>()
+                .unwrap()
+        );
+        assert!(parse_role("0x01").is_err());
+    }
+
+    #[test]
+    fn can_load_freeze_windows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(POLICY_FILE_NAME);
+        std::fs::write(
+            &path,
+            format!(
+                r#"
+                freeze_override = "{}"
+
+                [[freeze_windows]]
+                name = "weekend"
+                cron = "* * * * sat,sun"
+                chains = ["mainnet"]
+                reason = "no deployments on weekends"
+                "#,
+                keccak256("incident-42")
+            ),
+        )
+        .unwrap();
+        let mut policy = TxPolicy::default();
+        policy.merge(&path).unwrap();
+
+        // Saturday 2024-03-16 14:30:00 UTC.
+        let saturday = 1_710_599_400;
+        let err = policy.check_freeze_windows([10, 1], saturday, None).unwrap_err().to_string();
+        assert!(err.contains("chain 1 is frozen by `weekend`"), "{err}");
+        assert!(err.contains("no deployments on weekends"), "{err}");
+        assert!(policy.check_freeze_windows([10], saturday, None).is_ok());
+        assert!(policy.check_freeze_windows([1], saturday + 2 * 86_400, None).is_ok());
+        assert!(policy.check_freeze_windows([1], saturday, Some("incident-42")).is_ok());
+    }
+}
@@ -0,0 +1,122 @@
+use alloy_primitives::U256;
+use foundry_common::provider::ethers::RpcUrl;
+use foundry_evm::{
+    backend::{Backend, DatabaseExt, RevertSnapshotAction},
+    fork::{CreateFork, MultiFork},
+    revm::{
+        primitives::{Env, SpecId},
+        JournaledState,
+    },
+};
+use parking_lot::Mutex;
+use std::{collections::HashMap, sync::Arc};
+
+/// A pool of EVM backends, shared by the runners which execute and simulate a script.
+///
+/// All the backends share a single fork handler, so each fork is only created once. A backend is
+/// snapshotted right after it's created, and once a runner is done with it, it's released back to
+/// the pool: the next runner for the same RPC gets it reverted to that snapshot instead of a new
+/// backend.
+///
+/// Clones of the pool share the same fork handler and backends.
+#[derive(Clone, Debug, Default)]
+pub struct BackendPool {
+    inner: Arc<Mutex<BackendPoolInner>>,
+}
+
+#[derive(Debug, Default)]
+struct BackendPoolInner {
+    /// The fork handler shared by all backends, spawned on first use.
+    forks: Option<MultiFork>,
+    /// The backends by the RPC they fork, `None` for the in-memory ones.
+    backends: HashMap<Option<RpcUrl>, PooledBackends>,
+}
+
+#[derive(Debug)]
+struct PooledBackends {
+    /// The id of the snapshot taken right after creating each backend.
+    snapshot: U256,
+    /// The released backends.
+    idle: Vec<Backend>,
+}
+
+impl BackendPool {
+    /// Returns a backend forking `fork_url`, or an in-memory backend if there is none.
+    ///
+    /// A backend released for the same RPC is reverted to its initial state and reused, otherwise
+    /// a new one is created, forking with `create_fork`.
+    pub fn get(
+        &self,
+        fork_url: Option<&RpcUrl>,
+        env: &Env,
+        create_fork: impl FnOnce() -> Option<CreateFork>,
+    ) -> Backend {
+        let mut inner = self.inner.lock();
+        let key = fork_url.cloned();
+        if let Some(pooled) = inner.backends.get_mut(&key) {
+            while let Some(mut backend) = pooled.idle.pop() {
+                let reverted = backend.revert(
+                    pooled.snapshot,
+                    &journaled_state(),
+                    &mut env.clone(),
+                    RevertSnapshotAction::RevertKeep,
+                );
+                if reverted.is_some() {
+                    trace!(target: "script", ?fork_url, "reusing pooled backend");
+                    return backend
+                }
+            }
+        }
+
+        let forks = inner.forks.get_or_insert_with(MultiFork::spawn).clone();
+        let mut backend = Backend::new(forks, fork_url.and_then(|_| create_fork()));
+        let snapshot = backend.snapshot(&journaled_state(), env);
+        inner.backends.entry(key).or_insert_with(|| PooledBackends { snapshot, idle: Vec::new() });
+        backend
+    }
+
+    /// Releases `backend`, returned by [`Self::get`] for `fork_url`, to be reused by the next
+    /// runner for the same RPC.
+    pub fn release(&self, fork_url: Option<&RpcUrl>, backend: Backend) {
+        if let Some(pooled) = self.inner.lock().backends.get_mut(&fork_url.cloned()) {
+            pooled.idle.push(backend);
+        }
+    }
+}
+
+/// Returns an empty journaled state, the snapshots of the pool are taken and reverted outside of
+/// any execution.
+fn journaled_state() -> JournaledState {
+    JournaledState::new(SpecId::LATEST, Default::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::Address;
+    use foundry_evm::revm::{primitives::AccountInfo, DatabaseRef};
+
+    #[test]
+    fn reuses_released_backends() {
+        let pool = BackendPool::default();
+        let env = Env::default();
+        let account = Address::repeat_byte(1);
+
+        let mut backend = pool.get(None, &env, || unreachable!());
+        backend.insert_account_info(account, AccountInfo { nonce: 7, ..Default::default() });
+        assert_eq!(backend.basic_ref(account).unwrap().unwrap().nonce, 7);
+        pool.release(None, backend);
+        assert_eq!(pool.inner.lock().backends[&None].idle.len(), 1);
+
+        // the released backend is reused, reverted to its initial state
+        let backend = pool.get(None, &env, || unreachable!());
+        assert!(pool.inner.lock().backends[&None].idle.is_empty());
+        assert!(backend.basic_ref(account).unwrap().map_or(true, |info| info.nonce == 0));
+
+        // backends still in use aren't shared
+        let other = pool.get(None, &env, || unreachable!());
+        pool.release(None, backend);
+        pool.release(None, other);
+        assert_eq!(pool.inner.lock().backends[&None].idle.len(), 2);
+    }
+}
@@ -0,0 +1,114 @@
+use alloy_primitives::Bytes;
+use eyre::Result;
+use foundry_config::PrecompileConfig;
+use foundry_evm::{
+    executors::Executor,
+    revm::{primitives::Bytecode, DatabaseRef},
+};
+
+/// Length of the code loading the selector of the calldata.
+const HEADER_LEN: usize = 6;
+/// Length of the code jumping to the return block of a function if the selector matches.
+const DISPATCH_LEN: usize = 11;
+/// Length of the code returning a slice of the code.
+const RETURN_LEN: usize = 15;
+
+/// Deploys mocks of the configured precompiles and system contracts of the chain, so that calls
+/// to them don't revert in the simulation.
+///
+/// Addresses which already have code, e.g. system contracts which are deployed on the forked
+/// chain, are left untouched.
+pub fn install_precompiles(
+    executor: &mut Executor,
+    precompiles: &[PrecompileConfig],
+) -> Result<()> {
+    for precompile in precompiles {
+        let mut account = executor.backend.basic_ref(precompile.address)?.unwrap_or_default();
+        if account.code.as_ref().map_or(false, |code| !code.is_empty()) {
+            continue
+        }
+        trace!(target: "script", address=?precompile.address, "mocking precompile");
+        let code = Bytecode::new_raw(mock_code(precompile));
+        account.code_hash = code.hash_slow();
+        account.code = Some(code);
+        executor.backend.insert_account_info(precompile.address, account);
+    }
+    Ok(())
+}
+
+/// Returns code which dispatches on the selector of the calldata and returns the data configured
+/// for it, or the default data.
+///
+/// The returned data is appended to the code and copied from it.
+fn mock_code(precompile: &PrecompileConfig) -> Bytes {
+    let functions = &precompile.functions;
+    let blocks_start = HEADER_LEN + DISPATCH_LEN * functions.len();
+    let data_start = blocks_start + RETURN_LEN + (1 + RETURN_LEN) * functions.len();
+
+    // PUSH1 0 CALLDATALOAD PUSH1 0xe0 SHR
+    let mut code = vec![0x60, 0x00, 0x35, 0x60, 0xe0, 0x1c];
+    let mut blocks = return_block(data_start, precompile.returns.len());
+    let mut data = precompile.returns.to_vec();
+    for (selector, returns) in functions {
+        let dest = blocks_start + blocks.len();
+        // DUP1 PUSH4 selector EQ PUSH2 dest JUMPI
+        code.extend([0x80, 0x63]);
+        code.extend_from_slice(selector.as_slice());
+        code.extend([0x14, 0x61]);
+        code.extend((dest as u16).to_be_bytes());
+        code.push(0x57);
+
+        // JUMPDEST
+        blocks.push(0x5b);
+        blocks.extend(return_block(data_start + data.len(), returns.len()));
+        data.extend_from_slice(returns);
+    }
+    code.extend(blocks);
+    code.extend(data);
+    code.into()
+}
+
+/// PUSH2 len PUSH2 offset PUSH1 0 CODECOPY PUSH2 len PUSH1 0 RETURN
+fn return_block(offset: usize, len: usize) -> Vec<u8> {
+    let [len_hi, len_lo] = (len as u16).to_be_bytes();
+    let [offset_hi, offset_lo] = (offset as u16).to_be_bytes();
+    vec![
+        0x61, len_hi, len_lo, 0x61, offset_hi, offset_lo, 0x60, 0x00, 0x39, 0x61, len_hi, len_lo,
+        0x60, 0x00, 0xf3,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, bytes, Address, U256};
+    use foundry_evm::{backend::Backend, executors::ExecutorBuilder, revm::primitives::Env};
+    use std::collections::BTreeMap;
+
+    const ARB_SYS: Address = address!("0000000000000000000000000000000000000064");
+    const BLOCK_NUMBER: U256 = U256::from_limbs([0xaa, 0, 0, 0]);
+
+    #[test]
+    fn mocks_return_configured_data() {
+        let precompile = PrecompileConfig {
+            address: ARB_SYS,
+            returns: bytes!("01"),
+            functions: BTreeMap::from([
+                ("a3b1b31d".parse().unwrap(), Bytes::from(BLOCK_NUMBER.to_be_bytes_vec())),
+                ("a0c12269".parse().unwrap(), bytes!("0b")),
+            ]),
+        };
+        let mut executor = ExecutorBuilder::new().build(Env::default(), Backend::spawn(None));
+        install_precompiles(&mut executor, &[precompile]).unwrap();
+
+        let call = |calldata: Bytes| {
+            let result = executor.call_raw(Address::ZERO, ARB_SYS, calldata, U256::ZERO).unwrap();
+            assert!(!result.reverted);
+            result.result
+        };
+        assert_eq!(U256::from_be_slice(&call(bytes!("a3b1b31d"))), BLOCK_NUMBER);
+        assert_eq!(call(bytes!("a0c12269")), bytes!("0b"));
+        assert_eq!(call(bytes!("12345678")), bytes!("01"));
+        assert_eq!(call(Bytes::new()), bytes!("01"));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::chain_metadata::ChainMetadata;
 use alloy_primitives::U256;
 use ethers_providers::{Middleware, Provider};
 use eyre::{Result, WrapErr};
@@ -51,6 +52,7 @@ pub struct ProviderInfo {
     pub chain: u64,
     pub gas_price: GasPrice,
     pub is_legacy: bool,
+    pub block_gas_limit: U256,
 }
 
 /// Represents the outcome of a gas price request
@@ -63,11 +65,13 @@ pub enum GasPrice {
 impl ProviderInfo {
     pub async fn new(rpc: &str, mut is_legacy: bool) -> Result<ProviderInfo> {
         let provider = Arc::new(get_http_provider(rpc));
-        let chain = provider.get_chainid().await?.as_u64();
+        let metadata = ChainMetadata::get(rpc, &provider).await?;
+        let chain = metadata.chain_id;
 
         if let Some(chain) = Chain::from(chain).named() {
             is_legacy |= chain.is_legacy();
         };
+        is_legacy |= !metadata.supports_eip1559;
 
         let gas_price = if is_legacy {
             GasPrice::Legacy(
@@ -79,15 +83,17 @@ impl ProviderInfo {
             )
         } else {
             GasPrice::EIP1559(
-                provider
-                    .estimate_eip1559_fees(None)
-                    .await
-                    .wrap_err("Failed to get EIP-1559 fees")
-                    .map(|p| (p.0.to_alloy(), p.1.to_alloy())),
+                metadata.eip1559_fees.ok_or_else(|| eyre::eyre!("Failed to get EIP-1559 fees")),
             )
         };
 
-        Ok(ProviderInfo { provider, chain, gas_price, is_legacy })
+        Ok(ProviderInfo {
+            provider,
+            chain,
+            gas_price,
+            is_legacy,
+            block_gas_limit: metadata.block_gas_limit,
+        })
     }
 
     /// Returns the gas price to use
@@ -1,15 +1,23 @@
 use super::sequence::ScriptSequence;
 use alloy_primitives::TxHash;
 use ethers_core::types::TransactionReceipt;
-use ethers_providers::{Middleware, PendingTransaction};
+use ethers_providers::{JsonRpcError, Middleware, PendingTransaction};
 use eyre::Result;
 use foundry_cli::{init_progress, update_progress, utils::print_receipt};
 use foundry_common::{
     provider::ethers::RetryProvider,
+    tr,
     types::{ToAlloy, ToEthers},
 };
+use foundry_config::ExplorerLinks;
 use futures::StreamExt;
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
+
+/// The maximum number of receipts requested in a single JSON-RPC batch.
+const RECEIPTS_BATCH_SIZE: usize = 100;
+
+/// The maximum number of JSON-RPC batches in flight.
+const CONCURRENT_BATCHES: usize = 4;
 
 /// Convenience enum for internal signalling of transaction status
 enum TxStatus {
@@ -34,17 +42,22 @@ impl From<TransactionReceipt> for TxStatus {
 pub async fn wait_for_pending(
     provider: Arc<RetryProvider>,
     deployment_sequence: &mut ScriptSequence,
+    confirmations: usize,
+    links: Option<&ExplorerLinks>,
 ) -> Result<()> {
     if deployment_sequence.pending.is_empty() {
         return Ok(());
     }
-    println!("##\nChecking previously pending transactions.");
-    clear_pendings(provider, deployment_sequence, None).await
+    println!("{}", tr!("##\nChecking previously pending transactions."));
+    clear_pendings(provider, deployment_sequence, None, confirmations, links).await
 }
 
 /// Traverses a set of pendings and either finds receipts, or clears them from
 /// the deployment sequence.
 ///
+/// Transactions are only considered confirmed once they have `confirmations` confirmations. The
+/// receipts are printed with the `links` to the explorer of the chain.
+///
 /// If no `tx_hashes` are provided, then `deployment_sequence.pending` will be
 /// used. For each `tx_hash`, we check if it has confirmed. If it has
 /// confirmed, we push the receipt (if successful) or push an error (if
@@ -57,15 +70,27 @@ pub async fn clear_pendings(
     provider: Arc<RetryProvider>,
     deployment_sequence: &mut ScriptSequence,
     tx_hashes: Option<Vec<TxHash>>,
+    confirmations: usize,
+    links: Option<&ExplorerLinks>,
 ) -> Result<()> {
-    let to_query = tx_hashes.unwrap_or_else(|| deployment_sequence.pending.clone());
+    let mut to_query = tx_hashes.unwrap_or_else(|| deployment_sequence.pending.clone());
 
     let count = deployment_sequence.pending.len();
 
     trace!("Checking status of {count} pending transactions");
 
-    let futs = to_query.iter().copied().map(|tx| check_tx_status(&provider, tx));
-    let mut tasks = futures::stream::iter(futs).buffer_unordered(10);
+    // Fetch the receipts of the mined transactions in batches first, only the others are polled
+    // one by one.
+    let mut mined: Vec<(TxHash, Result<TxStatus>)> = Vec::new();
+    if confirmations <= 1 {
+        if let Some(receipts) = batch_receipts(&provider, &to_query).await {
+            mined = take_mined(&mut to_query, receipts);
+        }
+    }
+
+    let futs = to_query.iter().copied().map(|tx| check_tx_status(&provider, tx, confirmations));
+    let mut tasks =
+        futures::stream::iter(mined).chain(futures::stream::iter(futs).buffer_unordered(10));
 
     let mut errors: Vec<String> = vec![];
     let mut receipts = Vec::<TransactionReceipt>::with_capacity(count);
@@ -109,7 +134,7 @@ pub async fn clear_pendings(
 
     // print all receipts
     for receipt in receipts {
-        print_receipt(deployment_sequence.chain.into(), &receipt);
+        print_receipt(deployment_sequence.chain.into(), &receipt, links);
         deployment_sequence.add_receipt(receipt);
     }
 
@@ -126,24 +151,87 @@ pub async fn clear_pendings(
     Ok(())
 }
 
+/// Fetches the receipts of `hashes` with JSON-RPC batch requests, a few batches at a time.
+///
+/// Returns `None` if the RPC doesn't support batch requests. Transactions which were not mined yet,
+/// or whose receipt couldn't be decoded, are missing from the returned receipts.
+async fn batch_receipts(
+    provider: &RetryProvider,
+    hashes: &[TxHash],
+) -> Option<HashMap<TxHash, TransactionReceipt>> {
+    let client = provider.as_ref();
+    let batches = hashes.chunks(RECEIPTS_BATCH_SIZE).map(|chunk| async move {
+        let params = chunk.iter().map(|hash| [hash.to_ethers()]).collect::<Vec<_>>();
+        client
+            .batch_request::<_, Option<TransactionReceipt>>("eth_getTransactionReceipt", &params)
+            .await
+    });
+    let mut batches = futures::stream::iter(batches).buffer_unordered(CONCURRENT_BATCHES);
+
+    let mut receipts = HashMap::with_capacity(hashes.len());
+    while let Some(batch) = batches.next().await {
+        match batch {
+            Ok(Some(batch)) => collect_receipts(batch, &mut receipts),
+            Ok(None) => return None,
+            Err(err) => {
+                trace!(%err, "batched receipt request failed");
+                return None
+            }
+        }
+    }
+    Some(receipts)
+}
+
+/// Adds the receipts of the mined transactions of `batch` to `receipts`.
+fn collect_receipts(
+    batch: Vec<Option<Result<Option<TransactionReceipt>, JsonRpcError>>>,
+    receipts: &mut HashMap<TxHash, TransactionReceipt>,
+) {
+    for receipt in batch.into_iter().flatten().flatten().flatten() {
+        receipts.insert(receipt.transaction_hash.to_alloy(), receipt);
+    }
+}
+
+/// Removes the transactions of `hashes` which have a receipt in `receipts`, and returns their
+/// statuses. The other transactions are left to be polled one by one.
+fn take_mined(
+    hashes: &mut Vec<TxHash>,
+    mut receipts: HashMap<TxHash, TransactionReceipt>,
+) -> Vec<(TxHash, Result<TxStatus>)> {
+    let mut mined = Vec::new();
+    hashes.retain(|hash| match receipts.remove(hash) {
+        Some(receipt) => {
+            mined.push((*hash, Ok(receipt.into())));
+            false
+        }
+        None => true,
+    });
+    mined
+}
+
 /// Checks the status of a txhash by first polling for a receipt, then for
 /// mempool inclusion. Returns the tx hash, and a status
 async fn check_tx_status(
     provider: &RetryProvider,
     hash: TxHash,
+    confirmations: usize,
 ) -> (TxHash, Result<TxStatus, eyre::Report>) {
     // We use the inner future so that we can use ? operator in the future, but
     // still neatly return the tuple
     let result = async move {
-        // First check if there's a receipt
+        // First check if there's a receipt, unless we need to wait for more confirmations
         let receipt_opt = provider.get_transaction_receipt(hash.to_ethers()).await?;
         if let Some(receipt) = receipt_opt {
-            return Ok(receipt.into());
+            if confirmations <= 1 {
+                return Ok(receipt.into());
+            }
         }
 
         // If the tx is present in the mempool, run the pending tx future, and
         // assume the next drop is really really real
-        let pending_res = PendingTransaction::new(hash.to_ethers(), provider).await?;
+        let pending_res = PendingTransaction::new(hash.to_ethers(), provider)
+            .confirmations(confirmations.max(1))
+            .await?;
         match pending_res {
             Some(receipt) => Ok(receipt.into()),
             None => Ok(TxStatus::Dropped),
@@ -153,3 +241,31 @@ async fn check_tx_status(
 
     (hash, result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+
+    #[test]
+    fn polls_the_transactions_missing_from_the_batch() {
+        let hashes = [B256::repeat_byte(1), B256::repeat_byte(2), B256::repeat_byte(3)];
+        let receipt = |hash: TxHash| TransactionReceipt {
+            transaction_hash: hash.to_ethers(),
+            status: Some(1.into()),
+            ..Default::default()
+        };
+        // the receipt of the second transaction couldn't be decoded, the third one isn't mined
+        let batch = vec![Some(Ok(Some(receipt(hashes[0])))), None, Some(Ok(None))];
+
+        let mut receipts = HashMap::new();
+        collect_receipts(batch, &mut receipts);
+        let mut to_query = hashes.to_vec();
+        let mined = take_mined(&mut to_query, receipts);
+
+        assert_eq!(mined.len(), 1);
+        assert_eq!(mined[0].0, hashes[0]);
+        assert!(matches!(mined[0].1, Ok(TxStatus::Success(_))));
+        assert_eq!(to_query, &hashes[1..]);
+    }
+}
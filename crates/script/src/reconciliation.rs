@@ -0,0 +1,251 @@
+use crate::{sequence::ScriptSequence, transaction::TransactionWithMetadata};
+use alloy_primitives::{utils::format_units, Address, I256, U256};
+use ethers_core::types::NameOrAddress;
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::{
+    fs,
+    provider::ethers::RetryProvider,
+    shell,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_evm::traces::CallKind;
+use itertools::Itertools;
+use serde::Serialize;
+use std::{collections::BTreeMap, path::PathBuf};
+use yansi::Paint;
+
+/// The file name of the reconciliation report, next to the broadcast log of the sequence.
+pub const RECONCILIATION_FILE: &str = "reconciliation-latest.json";
+
+/// The balances of accounts, by address.
+pub type Balances = BTreeMap<Address, U256>;
+
+/// Returns the senders of the transactions of `sequence` from `first` on, and the recipients of
+/// their value.
+pub fn accounts(sequence: &ScriptSequence, first: usize) -> Vec<Address> {
+    sequence
+        .transactions
+        .iter()
+        .skip(first)
+        .flat_map(|tx| {
+            let from = tx.typed_tx().from().map(|from| from.to_alloy());
+            let value = tx.typed_tx().value().copied().unwrap_or_default();
+            let recipient = if value.is_zero() { None } else { recipient(tx) };
+            from.into_iter().chain(recipient)
+        })
+        .unique()
+        .collect()
+}
+
+/// Returns the balances of `accounts` at the latest block.
+pub async fn snapshot(provider: &RetryProvider, accounts: &[Address]) -> Result<Balances> {
+    let mut balances = Balances::new();
+    for account in accounts {
+        let balance = provider.get_balance(account.to_ethers(), None).await?;
+        balances.insert(*account, balance.to_alloy());
+    }
+    Ok(balances)
+}
+
+/// Returns the account which receives the value of the transaction: the created contract, or the
+/// called one.
+fn recipient(tx: &TransactionWithMetadata) -> Option<Address> {
+    match tx.opcode {
+        CallKind::Create | CallKind::Create2 => tx.contract_address,
+        _ => match tx.typed_tx().to() {
+            Some(NameOrAddress::Address(to)) => Some(to.to_alloy()),
+            _ => None,
+        },
+    }
+}
+
+/// How the balance of an account changed while broadcasting.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountReconciliation {
+    pub address: Address,
+    pub before: U256,
+    pub after: U256,
+    /// The fees paid for the transactions sent by the account, in wei.
+    pub gas_cost: U256,
+    /// The value sent by the successful transactions of the account, in wei.
+    pub value_sent: U256,
+    /// The value received from the successful transactions of the script, in wei.
+    pub value_received: U256,
+    /// The change of the balance which the above doesn't explain, e.g. transfers made by
+    /// contracts or the L1 data fees of rollups, in wei.
+    pub unexpected: I256,
+}
+
+/// The reconciliation of the balances of the senders and recipients of a broadcast sequence.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reconciliation {
+    pub chain: u64,
+    /// The number of reconciled transactions.
+    pub transactions: usize,
+    /// The fees paid for all the transactions, in wei.
+    pub gas_cost: U256,
+    /// The value moved by the successful transactions, in wei.
+    pub value_moved: U256,
+    pub accounts: Vec<AccountReconciliation>,
+}
+
+impl Reconciliation {
+    /// Reconciles the balances from before and after broadcasting the transactions of `sequence`
+    /// from `first` on, with their receipts.
+    pub fn new(
+        sequence: &ScriptSequence,
+        first: usize,
+        before: &Balances,
+        after: &Balances,
+    ) -> Self {
+        let mut accounts = before
+            .keys()
+            .chain(after.keys())
+            .map(|address| {
+                let account = AccountReconciliation {
+                    address: *address,
+                    before: before.get(address).copied().unwrap_or_default(),
+                    after: after.get(address).copied().unwrap_or_default(),
+                    ..Default::default()
+                };
+                (*address, account)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let mut report = Self { chain: sequence.chain, ..Default::default() };
+        for tx in sequence.transactions.iter().skip(first) {
+            let Some(receipt) = tx.hash.and_then(|hash| {
+                sequence.receipts.iter().find(|receipt| receipt.transaction_hash.to_alloy() == hash)
+            }) else {
+                continue
+            };
+            let Some(from) = tx.typed_tx().from().map(|from| from.to_alloy()) else { continue };
+            report.transactions += 1;
+
+            let gas_used = receipt.gas_used.unwrap_or_default().to_alloy();
+            let gas_price = receipt.effective_gas_price.unwrap_or_default().to_alloy();
+            let gas_cost = gas_used * gas_price;
+            report.gas_cost += gas_cost;
+            if let Some(sender) = accounts.get_mut(&from) {
+                sender.gas_cost += gas_cost;
+            }
+
+            let value = tx.typed_tx().value().copied().unwrap_or_default().to_alloy();
+            if value.is_zero() || receipt.status.map_or(true, |status| status.is_zero()) {
+                continue
+            }
+            report.value_moved += value;
+            if let Some(sender) = accounts.get_mut(&from) {
+                sender.value_sent += value;
+            }
+            if let Some(recipient) = recipient(tx).and_then(|to| accounts.get_mut(&to)) {
+                recipient.value_received += value;
+            }
+        }
+
+        for account in accounts.values_mut() {
+            let change = I256::from_raw(account.after) - I256::from_raw(account.before);
+            let expected = I256::from_raw(account.value_received) -
+                I256::from_raw(account.value_sent) -
+                I256::from_raw(account.gas_cost);
+            account.unexpected = change - expected;
+        }
+        report.accounts = accounts.into_values().collect();
+        report
+    }
+
+    /// Prints the report, and writes it next to the broadcast log of `sequence`.
+    pub fn write(&self, sequence: &ScriptSequence) -> Result<Option<PathBuf>> {
+        shell::println(format!("\nBalance reconciliation of chain {}:", self.chain))?;
+        shell::println(format!(
+            "  {} transactions, {} ETH of gas, {} ETH moved",
+            self.transactions,
+            format_ether(self.gas_cost),
+            format_ether(self.value_moved)
+        ))?;
+        for account in &self.accounts {
+            shell::println(format!(
+                "  {}: gas {} ETH, sent {} ETH, received {} ETH",
+                account.address,
+                format_ether(account.gas_cost),
+                format_ether(account.value_sent),
+                format_ether(account.value_received)
+            ))?;
+            if !account.unexpected.is_zero() {
+                shell::println(Paint::yellow(format!(
+                    "    unexpected balance change of {} wei",
+                    account.unexpected
+                )))?;
+            }
+        }
+
+        let Some((path, _)) = &sequence.paths else { return Ok(None) };
+        let path = path.with_file_name(RECONCILIATION_FILE);
+        fs::write_json_file(&path, self)?;
+        shell::println(format!("Reconciliation report written to: {}", path.display()))?;
+        Ok(Some(path))
+    }
+}
+
+pub(crate) fn format_ether(wei: U256) -> String {
+    format_units(wei, 18).unwrap_or_else(|_| wei.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{b256, U64};
+    use alloy_rpc_types::request::TransactionRequest;
+    use ethers_core::types::TransactionReceipt;
+
+    #[test]
+    fn reconciles_balances() {
+        let sender = Address::with_last_byte(1);
+        let recipient = Address::with_last_byte(2);
+        let hash = b256!("0000000000000000000000000000000000000000000000000000000000000001");
+
+        let mut tx = TransactionWithMetadata::from_tx_request(TransactionRequest {
+            from: Some(sender),
+            to: Some(recipient),
+            value: Some(U256::from(100)),
+            nonce: Some(U64::ZERO),
+            ..Default::default()
+        });
+        tx.opcode = CallKind::Call;
+        tx.hash = Some(hash);
+        let receipt = TransactionReceipt {
+            transaction_hash: hash.to_ethers(),
+            gas_used: Some(21_000.into()),
+            effective_gas_price: Some(2.into()),
+            status: Some(1.into()),
+            ..Default::default()
+        };
+        let sequence = ScriptSequence {
+            chain: 1,
+            transactions: [tx].into(),
+            receipts: vec![receipt],
+            ..Default::default()
+        };
+        assert_eq!(accounts(&sequence, 0), vec![sender, recipient]);
+        assert!(accounts(&sequence, 1).is_empty());
+
+        let before = Balances::from([(sender, U256::from(100_000)), (recipient, U256::ZERO)]);
+        // The recipient forwarded 10 wei to the sender.
+        let after = Balances::from([
+            (sender, U256::from(100_000 - 42_000 - 100 + 10)),
+            (recipient, U256::from(90)),
+        ]);
+        let report = Reconciliation::new(&sequence, 0, &before, &after);
+        assert_eq!(report.transactions, 1);
+        assert_eq!(report.gas_cost, U256::from(42_000));
+        assert_eq!(report.value_moved, U256::from(100));
+        assert_eq!(report.accounts[0].gas_cost, U256::from(42_000));
+        assert_eq!(report.accounts[0].value_sent, U256::from(100));
+        assert_eq!(report.accounts[0].unexpected, I256::from_raw(U256::from(10)));
+        assert_eq!(report.accounts[1].value_received, U256::from(100));
+        assert_eq!(report.accounts[1].unexpected, -I256::from_raw(U256::from(10)));
+    }
+}
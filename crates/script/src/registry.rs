@@ -0,0 +1,333 @@
+use crate::{broadcast::BundledState, scaffold::DEPLOYMENTS_DIR, sequence::ScriptSequence};
+use alloy_primitives::Address;
+use eyre::Result;
+use foundry_cli::utils::now;
+use foundry_common::{fs, shell, types::ToAlloy};
+use foundry_config::Chain;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The file which the deployments replaced with `--redeploy` or `--upgrade` are appended to.
+const SUPERSEDED_FILE: &str = "superseded.json";
+
+/// Why a contract of the registry is deployed again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Intent {
+    Redeploy,
+    Upgrade,
+}
+
+/// A deployment of the registry which the script deploys again.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupersededDeployment {
+    pub chain: u64,
+    pub contract: String,
+    pub address: Address,
+    pub superseded_by: Address,
+    pub intent: Option<Intent>,
+    pub timestamp: u64,
+}
+
+/// The addresses of the contracts deployed on a chain, by contract name.
+///
+/// They're read from `deployments/<chain id>.json`, a `{ "<name>": "<address>" }` object as
+/// written by the deploy script, and from the `deployments/<chain>/<name>.json` files holding an
+/// `address`. The former is written once the deployments of a broadcast are mined, see
+/// [record_deployments].
+#[derive(Clone, Debug, Default)]
+pub struct Registry(BTreeMap<String, Address>);
+
+impl Registry {
+    pub fn load(root: &Path, chain: u64) -> Result<Self> {
+        let dir = root.join(DEPLOYMENTS_DIR);
+        let mut registry = BTreeMap::new();
+
+        let book = dir.join(format!("{chain}.json"));
+        if book.is_file() {
+            let book: BTreeMap<String, Value> = fs::read_json_file(&book)?;
+            registry.extend(book.into_iter().filter_map(|(name, address)| {
+                Some((name, Address::from_str(address.as_str()?).ok()?))
+            }));
+        }
+
+        let names = [Chain::from(chain).to_string(), chain.to_string()];
+        for chain_dir in names.iter().map(|name| dir.join(name)).filter(|dir| dir.is_dir()) {
+            for entry in std::fs::read_dir(chain_dir)? {
+                let path = entry?.path();
+                if path.extension().map_or(true, |extension| extension != "json") {
+                    continue
+                }
+                let Some(name) = path.file_stem().map(|stem| stem.to_string_lossy().to_string())
+                else {
+                    continue
+                };
+                let deployment: Value = fs::read_json_file(&path)?;
+                if let Some(address) = deployment["address"].as_str() {
+                    registry.insert(name, Address::from_str(address)?);
+                }
+            }
+        }
+        Ok(Self(registry))
+    }
+
+    /// Loads the registries of all the chains which have deployments, by chain id.
+    pub fn load_all(root: &Path) -> Result<BTreeMap<u64, Self>> {
+        let dir = root.join(DEPLOYMENTS_DIR);
+        if !dir.is_dir() {
+            return Ok(BTreeMap::new())
+        }
+        let mut chains = BTreeSet::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_stem().map(|stem| stem.to_string_lossy().to_string()) else {
+                continue
+            };
+            if path.is_dir() {
+                chains.extend(name.parse::<Chain>().ok().map(|chain| chain.id()));
+            } else if path.extension().map_or(false, |extension| extension == "json") {
+                chains.extend(name.parse::<u64>().ok());
+            }
+        }
+        chains.into_iter().map(|chain| Ok((chain, Self::load(root, chain)?))).collect()
+    }
+
+    /// Returns the deployments of the registry which the transactions of `sequence` which
+    /// weren't broadcast yet deploy again.
+    pub fn superseded(
+        &self,
+        sequence: &ScriptSequence,
+        intent: Option<Intent>,
+    ) -> Vec<SupersededDeployment> {
+        let timestamp = now().as_secs();
+        sequence
+            .transactions
+            .iter()
+            .skip(sequence.receipts.len())
+            .filter(|tx| tx.opcode.is_any_create())
+            .filter_map(|tx| {
+                let contract = tx.contract_name.clone()?;
+                let address = *self.0.get(&contract)?;
+                let superseded_by = tx.contract_address?;
+                (address != superseded_by).then_some(SupersededDeployment {
+                    chain: sequence.chain,
+                    contract,
+                    address,
+                    superseded_by,
+                    intent,
+                    timestamp,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Records the contracts deployed by the mined transactions of the sequence in
+/// `deployments/<chain id>.json`, keeping the other entries, and returns the path of the file.
+///
+/// Only projects with a `deployments` directory, e.g. scaffolded with `forge script init-deploy`,
+/// have a registry.
+pub fn record_deployments(root: &Path, sequence: &ScriptSequence) -> Result<Option<PathBuf>> {
+    let dir = root.join(DEPLOYMENTS_DIR);
+    if !dir.is_dir() {
+        return Ok(None)
+    }
+    let deployed = sequence
+        .transactions
+        .iter()
+        .filter(|tx| tx.opcode.is_any_create())
+        .filter(|tx| {
+            sequence.receipts.iter().any(|receipt| {
+                Some(receipt.transaction_hash.to_alloy()) == tx.hash &&
+                    receipt.status.map_or(true, |status| status.as_u64() == 1)
+            })
+        })
+        .filter_map(|tx| Some((tx.contract_name.clone()?, tx.contract_address?)))
+        .collect::<Vec<_>>();
+    if deployed.is_empty() {
+        return Ok(None)
+    }
+
+    let path = dir.join(format!("{}.json", sequence.chain));
+    let mut book: BTreeMap<String, Value> =
+        if path.is_file() { fs::read_json_file(&path)? } else { BTreeMap::new() };
+    for (contract, address) in deployed {
+        book.insert(contract, address.to_checksum(None).into());
+    }
+    fs::write_json_file(&path, &book)?;
+    Ok(Some(path))
+}
+
+/// Appends the superseded deployments to `deployments/superseded.json`.
+pub fn record_superseded(root: &Path, superseded: &[SupersededDeployment]) -> Result<PathBuf> {
+    let path = root.join(DEPLOYMENTS_DIR).join(SUPERSEDED_FILE);
+    let mut records: Vec<SupersededDeployment> =
+        if path.is_file() { fs::read_json_file(&path)? } else { Vec::new() };
+    records.extend_from_slice(superseded);
+    fs::create_dir_all(root.join(DEPLOYMENTS_DIR))?;
+    fs::write_json_file(&path, &records)?;
+    Ok(path)
+}
+
+impl BundledState {
+    /// Returns the deployments of the registry, as it was before the script ran, which the script
+    /// deploys again, and fails unless `--redeploy` or `--upgrade` tells it's intended.
+    pub fn check_registry(&self) -> Result<Vec<SupersededDeployment>> {
+        let intent = if self.args.redeploy {
+            Some(Intent::Redeploy)
+        } else if self.args.upgrade {
+            Some(Intent::Upgrade)
+        } else {
+            None
+        };
+
+        let mut superseded = Vec::new();
+        for sequence in self.sequence.sequences() {
+            if let Some(registry) = self.script_config.registry.get(&sequence.chain) {
+                superseded.extend(registry.superseded(sequence, intent));
+            }
+        }
+        if superseded.is_empty() {
+            return Ok(superseded)
+        }
+
+        let deployments = superseded
+            .iter()
+            .map(|deployment| {
+                format!(
+                    "  {} on chain {}, deployed at {}",
+                    deployment.contract, deployment.chain, deployment.address
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        if intent.is_none() {
+            eyre::bail!(
+                "The script deploys contracts which are already in the deployments registry:\n\
+                 {deployments}\nPass --redeploy or --upgrade if they should be replaced."
+            )
+        }
+        shell::println(format!("\nReplacing the deployments:\n{deployments}"))?;
+        Ok(superseded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TransactionWithMetadata;
+    use alloy_primitives::{address, B256, U64};
+    use alloy_rpc_types::request::TransactionRequest;
+    use ethers_core::types::TransactionReceipt;
+    use foundry_common::types::ToEthers;
+    use foundry_evm::traces::CallKind;
+
+    #[test]
+    fn loads_registry() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join(DEPLOYMENTS_DIR);
+        fs::create_dir_all(dir.join("mainnet")).unwrap();
+        fs::write(
+            dir.join("1.json"),
+            r#"{ "Counter": "0x00000000000000000000000000000000000000c1", "note": 1 }"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("mainnet/Vault.json"),
+            r#"{ "address": "0x00000000000000000000000000000000000000c2", "abi": [] }"#,
+        )
+        .unwrap();
+
+        let registry = Registry::load(root.path(), 1).unwrap();
+        assert_eq!(
+            registry.0,
+            BTreeMap::from([
+                ("Counter".to_string(), address!("00000000000000000000000000000000000000c1")),
+                ("Vault".to_string(), address!("00000000000000000000000000000000000000c2")),
+            ])
+        );
+        assert!(Registry::load(root.path(), 10).unwrap().0.is_empty());
+
+        fs::write(
+            dir.join("10.json"),
+            r#"{ "Counter": "0x00000000000000000000000000000000000000c3" }"#,
+        )
+        .unwrap();
+        let registries = Registry::load_all(root.path()).unwrap();
+        assert_eq!(registries.keys().copied().collect::<Vec<_>>(), [1, 10]);
+        assert_eq!(registries[&1].0.len(), 2);
+    }
+
+    #[test]
+    fn appends_superseded_deployments() {
+        let root = tempfile::tempdir().unwrap();
+        let deployment = SupersededDeployment {
+            chain: 1,
+            contract: "Counter".to_string(),
+            address: address!("00000000000000000000000000000000000000c1"),
+            superseded_by: address!("00000000000000000000000000000000000000c3"),
+            intent: Some(Intent::Upgrade),
+            timestamp: 0,
+        };
+        record_superseded(root.path(), &[deployment.clone()]).unwrap();
+        let path = record_superseded(root.path(), &[deployment.clone()]).unwrap();
+        let records: Vec<SupersededDeployment> = fs::read_json_file(&path).unwrap();
+        assert_eq!(records, vec![deployment.clone(), deployment]);
+    }
+
+    #[test]
+    fn records_mined_deployments() {
+        let root = tempfile::tempdir().unwrap();
+        let deployment = |contract: &str, address: Address, hash: B256| {
+            let mut tx = TransactionWithMetadata::from_tx_request(TransactionRequest {
+                nonce: Some(U64::ZERO),
+                ..Default::default()
+            });
+            tx.opcode = CallKind::Create;
+            tx.contract_name = Some(contract.to_string());
+            tx.contract_address = Some(address);
+            tx.hash = Some(hash);
+            tx
+        };
+        let counter = address!("00000000000000000000000000000000000000c1");
+        let vault = address!("00000000000000000000000000000000000000c2");
+        let sequence = ScriptSequence {
+            chain: 1,
+            transactions: [
+                deployment("Counter", counter, B256::with_last_byte(1)),
+                deployment("Vault", vault, B256::with_last_byte(2)),
+            ]
+            .into(),
+            // The deployment of the vault wasn't mined.
+            receipts: vec![TransactionReceipt {
+                transaction_hash: B256::with_last_byte(1).to_ethers(),
+                status: Some(1.into()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        // Projects without a registry are left alone.
+        assert_eq!(record_deployments(root.path(), &sequence).unwrap(), None);
+
+        let dir = root.path().join(DEPLOYMENTS_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("1.json"),
+            r#"{ "Token": "0x00000000000000000000000000000000000000c3" }"#,
+        )
+        .unwrap();
+        let path = record_deployments(root.path(), &sequence).unwrap().unwrap();
+        assert_eq!(path, dir.join("1.json"));
+        let registry = Registry::load(root.path(), 1).unwrap();
+        assert_eq!(registry.0.len(), 2);
+        assert_eq!(registry.0["Counter"], counter);
+        assert!(!registry.0.contains_key("Vault"));
+    }
+}
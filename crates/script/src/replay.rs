@@ -0,0 +1,132 @@
+use alloy_primitives::{Address, B256};
+use clap::Parser;
+use eyre::Result;
+use foundry_cli::{
+    opts::RpcOpts,
+    utils::{print_traces, replay_transaction, ReplayOpts},
+};
+use foundry_common::{
+    compile::{ContractSources, ProjectCompiler},
+    provider::alloy::try_get_http_provider,
+    ContractsByArtifact,
+};
+use foundry_compilers::{ArtifactContracts, EvmVersion};
+use foundry_config::{find_project_root_path, Config};
+use foundry_debugger::Debugger;
+use foundry_evm::{
+    opts::EvmOpts,
+    traces::{
+        identifier::{SignaturesIdentifier, TraceIdentifiers},
+        CallTraceDecoderBuilder,
+    },
+};
+use std::str::FromStr;
+
+/// CLI arguments for `forge script replay-tx`.
+#[derive(Clone, Debug, Parser)]
+pub struct ReplayTxArgs {
+    /// The hash of the transaction to replay.
+    #[arg(value_name = "TX_HASH")]
+    tx_hash: B256,
+
+    /// Opens the transaction in the debugger.
+    #[arg(long, short)]
+    debug: bool,
+
+    /// Executes the transaction only with the state from the previous block.
+    ///
+    /// May result in different results than the live execution!
+    #[arg(long, short)]
+    quick: bool,
+
+    /// Label addresses in the trace.
+    ///
+    /// Example: 0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045:vitalik.eth
+    #[arg(long, short)]
+    label: Vec<String>,
+
+    /// The EVM version to use.
+    ///
+    /// Overrides the version specified in the config.
+    #[arg(long)]
+    evm_version: Option<EvmVersion>,
+
+    #[command(flatten)]
+    rpc: RpcOpts,
+}
+
+impl ReplayTxArgs {
+    /// Forks the chain at the block of the transaction and executes it again, decoding the
+    /// contracts it touched with the artifacts of the project, so that e.g. a deployment can be
+    /// inspected in the debugger against the local sources.
+    pub async fn run(self) -> Result<()> {
+        let figment = Config::figment_with_root(find_project_root_path(None)?).merge(self.rpc);
+        let evm_opts = figment.extract::<EvmOpts>()?;
+        let mut config = Config::try_from(figment)?.sanitized();
+
+        // The contracts are matched by their runtime code, so the project has to be compiled
+        // with the same settings as the deployed contracts.
+        let project = config.project()?;
+        let output = ProjectCompiler::new().compile(&project)?;
+        let (sources, contracts) =
+            ContractSources::from_project_output_with_artifacts([output], project.root(), |_| {
+                true
+            })?;
+        let known_contracts = known_contracts(contracts);
+
+        let provider = try_get_http_provider(config.get_rpc_url_or_localhost_http()?)?;
+        let opts = ReplayOpts {
+            quick: self.quick,
+            debug: self.debug,
+            trace_printer: false,
+            evm_version: self.evm_version.or(Some(config.evm_version)),
+        };
+        let (mut result, chain) =
+            replay_transaction(&provider, self.tx_hash, &mut config, evm_opts, opts).await?;
+
+        let labels = self.label.iter().filter_map(|label| {
+            let (address, label) = label.split_once(':')?;
+            Some((Address::from_str(address).ok()?, label.to_string()))
+        });
+        let mut decoder = CallTraceDecoderBuilder::new()
+            .with_labels(labels.chain(config.labels.clone()))
+            .with_signature_identifier(SignaturesIdentifier::new(
+                Config::foundry_cache_dir(),
+                config.offline,
+            )?)
+            .build();
+        let mut identifier =
+            TraceIdentifiers::new().with_local(&known_contracts).with_etherscan(&config, chain)?;
+        for (_, trace) in &result.traces {
+            decoder.identify(trace, &mut identifier);
+        }
+
+        if self.debug {
+            let mut debugger = Debugger::builder()
+                .debug_arena(&result.debug)
+                .decoder(&decoder)
+                .sources(sources)
+                .build();
+            debugger.try_run()?;
+        } else {
+            print_traces(&mut result, &decoder).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects the runtime code of the compiled contracts, which is what the contracts touched by
+/// the transaction are identified with.
+fn known_contracts(contracts: ArtifactContracts) -> ContractsByArtifact {
+    ContractsByArtifact(
+        contracts
+            .into_iter()
+            .filter_map(|(id, contract)| {
+                let abi = contract.abi?;
+                let bytecode = contract.deployed_bytecode?.bytecode?;
+                let code = bytecode.bytes().filter(|code| !code.is_empty())?.clone();
+                Some((id, (abi, code)))
+            })
+            .collect(),
+    )
+}
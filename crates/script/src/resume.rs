@@ -1,6 +1,7 @@
 use crate::{broadcast::BundledState, simulate::PreSimulationState};
 
 use super::{
+    ephemeral::EphemeralDeployer,
     multi_sequence::MultiChainSequence,
     sequence::{ScriptSequence, ScriptSequenceKind},
 };
@@ -53,15 +54,23 @@ impl PreSimulationState {
                 // We might have predeployed libraries from the broadcasting, so we need to
                 // relink the contracts with them, since their mapping is not included in the solc
                 // cache files.
-                self.build_data = self
-                    .build_data
-                    .build_data
-                    .link_with_libraries(Libraries::parse(&seq.libraries)?)?;
+                self.build_data =
+                    self.build_data.relink_with_libraries(Libraries::parse(&seq.libraries)?)?;
             }
             // Library linking is not supported for multi-chain sequences
             ScriptSequenceKind::Multi(_) => {}
         }
 
+        if self.args.ephemeral_deployer {
+            let deployers = sequence.sequences().iter().find_map(|seq| seq.ephemeral_deployer);
+            let Some(address) = deployers else {
+                eyre::bail!("The sequence wasn't broadcast by an ephemeral deployer")
+            };
+            let deployer = EphemeralDeployer::load(&self.script_config.config.cache_path, address)?;
+            self.script_wallets.add_signer(deployer.key())?;
+            self.script_config.ephemeral_deployer = Some(deployer);
+        }
+
         let Self {
             args,
             script_config,
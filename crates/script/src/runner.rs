@@ -77,6 +77,7 @@ impl ScriptRunner {
             mut logs,
             traces: constructor_traces,
             debug: constructor_debug,
+            coverage: constructor_coverage,
             ..
         } = self
             .executor
@@ -84,6 +85,7 @@ impl ScriptRunner {
             .map_err(|err| eyre::eyre!("Failed to deploy script:\n{}", err))?;
 
         traces.extend(constructor_traces.map(|traces| (TraceKind::Deployment, traces)));
+        let mut coverage = constructor_coverage;
 
         // Optionally call the `setUp` function
         let (success, gas_used, labeled_addresses, transactions, debug) = if !setup {
@@ -99,10 +101,15 @@ impl ScriptRunner {
                     debug,
                     gas_used,
                     transactions,
+                    coverage: setup_coverage,
                     ..
                 }) => {
                     traces.extend(setup_traces.map(|traces| (TraceKind::Setup, traces)));
                     logs.extend_from_slice(&setup_logs);
+                    coverage = match (coverage, setup_coverage) {
+                        (Some(hits), Some(setup_hits)) => Some(hits.merge(setup_hits)),
+                        (hits, setup_hits) => hits.or(setup_hits),
+                    };
 
                     self.maybe_correct_nonce(sender_nonce, libraries.len())?;
 
@@ -154,6 +161,7 @@ impl ScriptRunner {
                 traces,
                 debug,
                 address: None,
+                coverage,
                 ..Default::default()
             },
         ))
@@ -193,23 +201,30 @@ impl ScriptRunner {
         if let Some(to) = to {
             self.call(from, to, calldata.unwrap_or_default(), value.unwrap_or(U256::ZERO), true)
         } else if to.is_none() {
-            let (address, gas_used, logs, traces, debug) = match self.executor.deploy(
-                from,
-                calldata.expect("No data for create transaction"),
-                value.unwrap_or(U256::ZERO),
-                None,
-            ) {
-                Ok(DeployResult { address, gas_used, logs, traces, debug, .. }) => {
-                    (address, gas_used, logs, traces, debug)
-                }
-                Err(EvmError::Execution(err)) => {
-                    let ExecutionErr { reason, traces, gas_used, logs, debug, .. } = *err;
-                    println!("{}", Paint::red(format!("\nFailed with `{reason}`:\n")));
+            let (address, gas_used, logs, traces, debug, state_changeset) =
+                match self.executor.deploy(
+                    from,
+                    calldata.expect("No data for create transaction"),
+                    value.unwrap_or(U256::ZERO),
+                    None,
+                ) {
+                    Ok(DeployResult {
+                        address,
+                        gas_used,
+                        logs,
+                        traces,
+                        debug,
+                        state_changeset,
+                        ..
+                    }) => (address, gas_used, logs, traces, debug, state_changeset),
+                    Err(EvmError::Execution(err)) => {
+                        let ExecutionErr { reason, traces, gas_used, logs, debug, .. } = *err;
+                        println!("{}", Paint::red(format!("\nFailed with `{reason}`:\n")));
 
-                    (Address::ZERO, gas_used, logs, traces, debug)
-                }
-                Err(e) => eyre::bail!("Failed deploying contract: {e:?}"),
-            };
+                        (Address::ZERO, gas_used, logs, traces, debug, None)
+                    }
+                    Err(e) => eyre::bail!("Failed deploying contract: {e:?}"),
+                };
 
             Ok(ScriptResult {
                 returned: Bytes::new(),
@@ -225,6 +240,7 @@ impl ScriptRunner {
                     .unwrap_or_default(),
                 debug: vec![debug].into_iter().collect(),
                 address: Some(address),
+                state_changeset,
                 ..Default::default()
             })
         } else {
@@ -259,7 +275,18 @@ impl ScriptRunner {
             res = self.executor.call_raw_committing(from, to, calldata, value)?;
         }
 
-        let RawCallResult { result, reverted, logs, traces, labels, debug, transactions, .. } = res;
+        let RawCallResult {
+            result,
+            reverted,
+            logs,
+            traces,
+            labels,
+            debug,
+            transactions,
+            state_changeset,
+            coverage,
+            ..
+        } = res;
         let breakpoints = res.cheatcodes.map(|cheats| cheats.breakpoints).unwrap_or_default();
 
         Ok(ScriptResult {
@@ -279,6 +306,8 @@ impl ScriptRunner {
             transactions,
             address: None,
             breakpoints,
+            state_changeset,
+            coverage,
         })
     }
 
@@ -0,0 +1,182 @@
+use clap::{Parser, ValueHint};
+use eyre::Result;
+use forge_verify::provider::VerificationProviderType;
+use foundry_common::{fs, shell};
+use foundry_config::{providers::ChainProfileProvider, Chain, Config};
+use std::path::{Path, PathBuf};
+use yansi::Paint;
+
+/// The path of the deployments registry, which the deploy script is allowed to write to.
+pub(crate) const DEPLOYMENTS_DIR: &str = "deployments";
+
+/// The directory of the per-chain params files.
+const PARAMS_DIR: &str = "params";
+
+/// CLI arguments for `forge script init-deploy`.
+#[derive(Clone, Debug, Parser)]
+pub struct InitDeployArgs {
+    /// The root of the project.
+    #[arg(long, value_hint = ValueHint::DirPath, default_value = ".", value_name = "PATH")]
+    root: PathBuf,
+
+    /// The chains to deploy to, a params file and script aliases are created for each of them.
+    #[arg(long = "chain", value_name = "CHAIN", value_delimiter = ',', default_value = "sepolia")]
+    chains: Vec<Chain>,
+
+    /// The verification provider to use on the chains.
+    #[arg(long, value_enum, default_value_t)]
+    verifier: VerificationProviderType,
+
+    /// Overwrite files and settings that already exist.
+    #[arg(long)]
+    force: bool,
+}
+
+impl InitDeployArgs {
+    pub fn run(self) -> Result<()> {
+        let Self { root, chains, verifier, force } = self;
+        if !root.exists() {
+            eyre::bail!("Project root {} does not exist", root.display())
+        }
+        let root = dunce::canonicalize(root)?;
+
+        let config_path = root.join(Config::FILE_NAME);
+        if !config_path.exists() {
+            let config = Config::load_with_root(&root);
+            fs::write(&config_path, config.into_basic().to_string_pretty()?)?;
+        }
+        let config = Config::load_with_root(&root).sanitized();
+
+        let script = config.script.join("deploy").join("Deploy.s.sol");
+        write_file(&root, &script, include_str!("../assets/DeployTemplate.s.sol"), force)?;
+        for chain in &chains {
+            let params = root.join(PARAMS_DIR).join(format!("{chain}.toml"));
+            let contents = format!(
+                "# Parameters of the deployment on {chain}, read with `vm.param*` and \
+                 `vm.paramOr`.\ninitial_number = 0\n"
+            );
+            write_file(&root, &params, &contents, force)?;
+        }
+        write_file(&root, &root.join(DEPLOYMENTS_DIR).join(".gitkeep"), "", force)?;
+
+        let script = script.strip_prefix(&root).unwrap_or(&script).display().to_string();
+        config.update(|doc| {
+            let profile = config.profile.as_str().as_str();
+            let permissions = &mut doc[Config::PROFILE_SECTION][profile]["fs_permissions"];
+            if permissions.is_none() {
+                // keep the default read access to the artifacts
+                let out = config.out.strip_prefix(&root).unwrap_or(&config.out);
+                let mut array = toml_edit::Array::new();
+                array.push(fs_permission("read", &format!("./{}", out.display())));
+                *permissions = toml_edit::value(array);
+            }
+            let deployments = format!("./{DEPLOYMENTS_DIR}");
+            if let Some(permissions) = permissions.as_array_mut() {
+                let exists = permissions.iter().any(|permission| {
+                    let path = permission.as_inline_table().and_then(|table| table.get("path"));
+                    path.and_then(|path| path.as_str()) == Some(deployments.as_str())
+                });
+                if !exists {
+                    permissions.push(fs_permission("read-write", &deployments));
+                }
+            }
+
+            for chain in &chains {
+                let name = chain.to_string();
+                let endpoint = &mut doc["rpc_endpoints"][&name];
+                if force || endpoint.is_none() {
+                    let var = format!("{}_RPC_URL", name.to_uppercase().replace('-', "_"));
+                    *endpoint = toml_edit::value(format!("${{{var}}}"));
+                }
+
+                let chains = &mut doc[Config::PROFILE_SECTION][ChainProfileProvider::CHAIN_KEY];
+                let entry = &mut chains[&name]["verifier"];
+                if force || entry.is_none() {
+                    *entry = toml_edit::value(verifier.to_string());
+                }
+                if let Some(chains) = chains.as_table_mut() {
+                    chains.set_implicit(true);
+                }
+
+                let run = format!("{script} --params {PARAMS_DIR}/{name}.toml --rpc-url {name}");
+                let aliases = [
+                    (format!("plan-{name}"), format!("{run} --diff-plan")),
+                    (format!("deploy-{name}"), format!("{run} --broadcast --verify")),
+                ];
+                for (alias, command) in aliases {
+                    let entry = &mut doc["scripts"][&alias];
+                    if force || entry.is_none() {
+                        *entry = toml_edit::value(command);
+                    }
+                }
+            }
+            true
+        })?;
+
+        shell::println(format!("    {} deployment layout", Paint::green("Initialized")))?;
+        for chain in &chains {
+            shell::println(format!(
+                "Preview the deployment on {chain} with `forge script plan-{chain}`, and \
+                 broadcast it with `forge script deploy-{chain}`."
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+fn fs_permission(access: &str, path: &str) -> toml_edit::InlineTable {
+    let mut permission = toml_edit::InlineTable::new();
+    permission.insert("access", access.into());
+    permission.insert("path", path.into());
+    permission
+}
+
+/// Writes a scaffolded file, unless it already exists and `force` isn't set.
+fn write_file(root: &Path, path: &Path, contents: &str, force: bool) -> Result<()> {
+    let display = path.strip_prefix(root).unwrap_or(path).display();
+    if path.exists() && !force {
+        shell::println(format!("Skipping {display}, it already exists."))?;
+        return Ok(())
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)?;
+    shell::println(format!("Created {display}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_scaffold_deploy_layout() {
+        let root = tempfile::tempdir().unwrap();
+        let args = InitDeployArgs::parse_from([
+            "init-deploy",
+            "--root",
+            root.path().to_str().unwrap(),
+            "--chain",
+            "mainnet,sepolia",
+        ]);
+        args.clone().run().unwrap();
+
+        assert!(root.path().join("script/deploy/Deploy.s.sol").exists());
+        assert!(root.path().join("params/mainnet.toml").exists());
+        assert!(root.path().join("deployments/.gitkeep").exists());
+
+        let config = Config::load_with_root(root.path());
+        assert_eq!(
+            config.scripts["deploy-sepolia"],
+            "script/deploy/Deploy.s.sol --params params/sepolia.toml --rpc-url sepolia \
+             --broadcast --verify"
+        );
+        assert!(config.rpc_endpoints.contains_key("mainnet"));
+        assert_eq!(config.fs_permissions.permissions.len(), 2);
+
+        // running it again keeps the existing settings
+        args.run().unwrap();
+        assert_eq!(Config::load_with_root(root.path()).fs_permissions.permissions.len(), 2);
+    }
+}
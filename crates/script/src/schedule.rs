@@ -0,0 +1,216 @@
+use crate::sequence::{ScriptSequence, ScriptSequenceKind};
+use ethers_core::types::BlockNumber;
+use ethers_providers::Middleware;
+use eyre::{ContextCompat, Result, WrapErr};
+use foundry_common::{
+    provider::ethers::get_http_provider,
+    shell,
+    types::{ToAlloy, ToEthers},
+};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// How often the chain is polled while waiting for a block.
+const BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// When to start broadcasting, passed with `--broadcast-at`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BroadcastAt {
+    /// A unix timestamp, in seconds.
+    Timestamp(u64),
+    /// A block number, so that the first transaction can be included in that block.
+    Block(u64),
+}
+
+impl FromStr for BroadcastAt {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s.split_once(':').unwrap_or(("timestamp", s));
+        let value = value
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid {kind} `{value}`, expected a number"))?;
+        match kind {
+            "timestamp" => Ok(Self::Timestamp(value)),
+            "block" => Ok(Self::Block(value)),
+            _ => Err(format!("unknown schedule `{kind}`, expected `timestamp` or `block`")),
+        }
+    }
+}
+
+impl fmt::Display for BroadcastAt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timestamp(timestamp) => write!(f, "timestamp {timestamp}"),
+            Self::Block(block) => write!(f, "block {block}"),
+        }
+    }
+}
+
+impl BroadcastAt {
+    /// Returns the earliest unix timestamp the broadcast can start at, `now` being the current
+    /// one. The time of a block isn't known ahead, so the broadcast may start at `now`.
+    pub fn earliest_start(self, now: u64) -> u64 {
+        match self {
+            Self::Timestamp(timestamp) => timestamp.max(now),
+            Self::Block(_) => now,
+        }
+    }
+
+    /// Whether the broadcast can start, given the number and timestamp of the latest block of the
+    /// chain.
+    ///
+    /// The transactions are included in the next block at the earliest, so a timestamp is reached
+    /// once a block with that timestamp is mined, and a block once its parent is mined.
+    fn is_reached(self, latest_number: u64, latest_timestamp: u64) -> bool {
+        match self {
+            Self::Timestamp(timestamp) => latest_timestamp >= timestamp,
+            Self::Block(block) => latest_number + 1 >= block,
+        }
+    }
+
+    /// Waits until the transactions of `sequence` can be broadcast, then checks that the nonces
+    /// of the senders still match the ones of the transactions.
+    ///
+    /// Timestamps are first waited for on the local clock, then confirmed against the blocks of
+    /// every chain, as their clocks may drift from the local one.
+    ///
+    /// Gas prices are estimated when sending, so they are up to date as well.
+    pub async fn wait(self, sequence: &ScriptSequenceKind) -> Result<()> {
+        shell::println(format!("\n##\nWaiting for {self} to start broadcasting."))?;
+        match self {
+            BroadcastAt::Timestamp(timestamp) => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                if timestamp > now {
+                    tokio::time::sleep(Duration::from_secs(timestamp - now)).await;
+                }
+            }
+            BroadcastAt::Block(_) => {
+                if sequence.sequences().len() > 1 {
+                    eyre::bail!("--broadcast-at block:<N> can't be used with multiple chains")
+                }
+            }
+        }
+
+        for sequence in sequence.sequences() {
+            let provider = get_http_provider(sequence.rpc_url());
+            loop {
+                let latest =
+                    provider.get_block(BlockNumber::Latest).await?.wrap_err_with(|| {
+                        format!("Failed to get the latest block of {}", sequence.chain)
+                    })?;
+                let number = latest.number.unwrap_or_default().as_u64();
+                if self.is_reached(number, latest.timestamp.as_u64()) {
+                    break
+                }
+                tokio::time::sleep(BLOCK_POLL_INTERVAL).await;
+            }
+        }
+
+        shell::println("Re-validating nonces before broadcasting.")?;
+        for sequence in sequence.sequences() {
+            check_nonces(sequence).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the next nonce of every sender is the nonce of its first pending transaction.
+async fn check_nonces(sequence: &ScriptSequence) -> Result<()> {
+    let mut first_nonces = BTreeMap::new();
+    for tx in sequence.typed_transactions().skip(sequence.receipts.len()) {
+        let (Some(from), Some(nonce)) = (tx.from(), tx.nonce()) else { continue };
+        first_nonces.entry((*from).to_alloy()).or_insert(nonce.as_u64());
+    }
+
+    let provider = get_http_provider(sequence.rpc_url());
+    for (from, expected) in first_nonces {
+        let nonce = provider
+            .get_transaction_count(from.to_ethers(), None)
+            .await
+            .wrap_err_with(|| format!("Failed to get the nonce of {from}"))?
+            .as_u64();
+        if nonce != expected {
+            eyre::bail!(
+                "The nonce of {from} on chain {} changed while waiting to broadcast: expected \
+                 {expected}, got {nonce}. Run the script again to update the transactions.",
+                sequence.chain
+            )
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{freeze::FreezeWindow, policy::TxPolicy, ScriptArgs};
+    use alloy_primitives::keccak256;
+    use clap::Parser;
+
+    // Saturday 2024-03-16 14:30:00 UTC.
+    const SATURDAY: u64 = 1_710_599_400;
+
+    #[test]
+    fn can_parse_broadcast_at() {
+        assert_eq!("1700000000".parse(), Ok(BroadcastAt::Timestamp(1700000000)));
+        assert_eq!("timestamp:1700000000".parse(), Ok(BroadcastAt::Timestamp(1700000000)));
+        assert_eq!("block:19000000".parse(), Ok(BroadcastAt::Block(19000000)));
+        assert!("epoch:1".parse::<BroadcastAt>().is_err());
+        assert!("block:soon".parse::<BroadcastAt>().is_err());
+    }
+
+    #[test]
+    fn waits_for_the_chain_to_reach_the_schedule() {
+        let at = BroadcastAt::Timestamp(SATURDAY);
+        // the local clock may be ahead of the chain
+        assert!(!at.is_reached(100, SATURDAY - 1));
+        assert!(at.is_reached(100, SATURDAY));
+
+        // the transactions are included in the block after the latest one
+        let at = BroadcastAt::Block(100);
+        assert!(!at.is_reached(98, SATURDAY));
+        assert!(at.is_reached(99, 0));
+        assert!(at.is_reached(150, 0));
+    }
+
+    #[test]
+    fn checks_freeze_windows_at_the_scheduled_time() {
+        let policy = TxPolicy {
+            freeze_windows: vec![FreezeWindow {
+                name: "weekend".to_string(),
+                cron: "* * * * 6,0".parse().unwrap(),
+                chains: None,
+                reason: None,
+                override_hash: Some(keccak256("incident-42")),
+                file: "policy.toml".into(),
+            }],
+            ..Default::default()
+        };
+        let check = |args: &[&str], now: u64| {
+            let args = ScriptArgs::parse_from(
+                ["foundry-cli", "script/Deploy.s.sol", "--broadcast"].iter().chain(args),
+            );
+            let start = args.broadcast_at.map_or(now, |at| at.earliest_start(now));
+            policy.check_freeze_windows([1], start, args.freeze_override.as_deref())
+        };
+
+        // scheduled from Thursday for Saturday
+        let thursday = SATURDAY - 2 * 86_400;
+        assert!(check(&[], thursday).is_ok());
+        assert!(check(&["--broadcast-at", &SATURDAY.to_string()], thursday).is_err());
+        assert!(check(
+            &["--broadcast-at", &SATURDAY.to_string(), "--freeze-override", "incident-42"],
+            thursday
+        )
+        .is_ok());
+        // a past target starts now
+        assert!(check(&["--broadcast-at", &thursday.to_string()], SATURDAY).is_err());
+        assert_eq!(BroadcastAt::Block(1).earliest_start(thursday), thursday);
+    }
+}
@@ -0,0 +1,381 @@
+use crate::transaction::TransactionWithMetadata;
+use alloy_primitives::{b256, Address, B256, U256};
+use eyre::Result;
+use foundry_common::{get_contract_name, types::ToAlloy};
+use foundry_evm::{
+    constants::{CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS},
+    traces::{CallKind, CallTraceArena, CallTraceDecoder, Traces},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt,
+    str::FromStr,
+};
+
+/// `OwnershipTransferred(address,address)`
+const OWNERSHIP_TRANSFERRED: B256 =
+    b256!("8be0079c531659141344cd1fd0a4f28419497f9722a3daafe3b4186f6b6457e0");
+/// `RoleGranted(bytes32,address,address)`
+const ROLE_GRANTED: B256 =
+    b256!("2f8788117e7eff1d82e926ec794901d17c78024a50270940304540a733656f0d");
+/// `RoleRevoked(bytes32,address,address)`
+const ROLE_REVOKED: B256 =
+    b256!("f6391f5c32d9c69d2a47ea670b442974b53935d1edc7fd64eb21e047a839171b");
+/// `Upgraded(address)`
+const UPGRADED: B256 = b256!("bc7cd75a20ee27fd9adebab32041f755214dbc6bffa90cc0225b39da2e5c2d3b");
+/// `BeaconUpgraded(address)`
+const BEACON_UPGRADED: B256 =
+    b256!("1cf3b03a6cf19fa2baba4df148e9dcabedea7f8a5c07840e207e5c089be95d3e");
+/// `Initialized(uint8)`
+const INITIALIZED_V4: B256 =
+    b256!("7f26b83ff96e1f2b6a682f133852f6798a09c465da95921460cefb3847402498");
+/// `Initialized(uint64)`
+const INITIALIZED_V5: B256 =
+    b256!("c7f505b2f371ae2175ee4913f4499e1f2633a7b5936321eed1cdaeb6115181d2");
+/// `Approval(address,address,uint256)`
+const APPROVAL: B256 = b256!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925");
+/// `ApprovalForAll(address,address,bool)`
+const APPROVAL_FOR_ALL: B256 =
+    b256!("17307eab39ab6107e8899845ad3d59bd9653f200f220920489ca2b5937696c31");
+
+/// A risky pattern looked for in the simulated transactions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Check {
+    /// A contract deployed by the script is left owned by, or administered by, a sender.
+    DeployerOwner,
+    /// A proxy was pointed to an implementation, but never initialized.
+    UninitializedProxy,
+    /// A function or constructor was called with the zero address as an argument.
+    ZeroAddress,
+    /// An unlimited allowance was given to an account without code.
+    InfiniteApproval,
+}
+
+impl Check {
+    const ALL: [Self; 4] =
+        [Self::DeployerOwner, Self::UninitializedProxy, Self::ZeroAddress, Self::InfiniteApproval];
+
+    fn id(self) -> &'static str {
+        match self {
+            Self::DeployerOwner => "deployer-owner",
+            Self::UninitializedProxy => "uninitialized-proxy",
+            Self::ZeroAddress => "zero-address",
+            Self::InfiniteApproval => "infinite-approval",
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for Check {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL.into_iter().find(|check| check.id() == s).ok_or_else(|| {
+            let checks = Self::ALL.map(Self::id).join(", ");
+            eyre::eyre!("unknown security check `{s}`, expected one of: {checks}")
+        })
+    }
+}
+
+/// A security check silenced in the policy, either entirely or for a single contract.
+///
+/// Written as `<check>` or `<check>:<address>`, e.g. `zero-address:0x…`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct IgnoredCheck {
+    pub check: Check,
+    pub address: Option<Address>,
+}
+
+impl FromStr for IgnoredCheck {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (check, address) = match s.split_once(':') {
+            Some((check, address)) => (check, Some(address.parse()?)),
+            None => (s, None),
+        };
+        Ok(Self { check: check.parse()?, address })
+    }
+}
+
+/// A risky pattern found in the simulated transactions.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Finding {
+    pub check: Check,
+    /// The contract the finding is about.
+    pub address: Address,
+    pub message: String,
+}
+
+impl Finding {
+    /// Whether the finding is silenced by one of the `ignored` checks.
+    pub fn is_ignored(&self, ignored: &BTreeSet<IgnoredCheck>) -> bool {
+        ignored.iter().any(|ignored| {
+            ignored.check == self.check && ignored.address.map_or(true, |a| a == self.address)
+        })
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.check, self.message)
+    }
+}
+
+/// Looks for risky patterns in the traces of the simulated transactions.
+///
+/// The transactions must be added in the order they will be broadcast, since the ownership of a
+/// contract only matters once all of them ran.
+#[derive(Debug, Default)]
+pub struct SecurityChecks {
+    senders: HashSet<Address>,
+    /// Contract -> current owner
+    owners: BTreeMap<Address, Address>,
+    /// Contract -> accounts holding the default admin role
+    admins: BTreeMap<Address, BTreeSet<Address>>,
+    /// Proxy -> implementation or beacon
+    proxies: BTreeMap<Address, Address>,
+    initialized: HashSet<Address>,
+    /// The transaction, token and spender of the unlimited approvals.
+    approvals: Vec<(usize, Address, Address)>,
+    findings: BTreeSet<Finding>,
+}
+
+impl SecurityChecks {
+    /// Adds the traces of the simulated transaction at index `i`.
+    pub async fn add(
+        &mut self,
+        i: usize,
+        tx: &TransactionWithMetadata,
+        traces: &Traces,
+        decoder: &CallTraceDecoder,
+    ) {
+        self.senders.extend(tx.transaction.from().map(|from| from.to_alloy()));
+
+        // constructor arguments are only decoded in the transaction metadata
+        if tx.opcode.is_any_create() {
+            let address = tx.contract_address.unwrap_or_default();
+            let name = tx.contract_name.clone().unwrap_or_else(|| address.to_string());
+            self.check_arguments(address, &format!("new {name}"), tx.arguments.iter().flatten());
+        }
+
+        for (_, arena) in traces {
+            self.add_arena(i, arena, decoder).await;
+        }
+    }
+
+    async fn add_arena(&mut self, i: usize, arena: &CallTraceArena, decoder: &CallTraceDecoder) {
+        decoder.prefetch_signatures(arena.nodes()).await;
+
+        let nodes = arena.nodes();
+        for (idx, node) in nodes.iter().enumerate() {
+            let trace = &node.trace;
+            if trace.address == CHEATCODE_ADDRESS || trace.address == HARDHAT_CONSOLE_ADDRESS {
+                continue
+            }
+
+            if let Some(func) = decoder.decode_function(trace).await.func {
+                let name = func.signature.split('(').next().unwrap_or_default();
+                let function = format!("{}.{name}", contract_name(trace.address, decoder));
+                self.check_arguments(trace.address, &function, &func.args);
+            }
+
+            // logs of delegate calls are emitted by the calling contract
+            let mut emitter = idx;
+            while matches!(nodes[emitter].trace.kind, CallKind::DelegateCall | CallKind::CallCode) {
+                match nodes[emitter].parent {
+                    Some(parent) => emitter = parent,
+                    None => break,
+                }
+            }
+            let emitter = nodes[emitter].trace.address;
+            for log in &node.logs {
+                self.add_log(i, emitter, log.topics(), &log.data);
+            }
+        }
+    }
+
+    fn add_log(&mut self, i: usize, emitter: Address, topics: &[B256], data: &[u8]) {
+        let Some(&event) = topics.first() else { return };
+        let address = |topic: usize| topics.get(topic).map(|topic| Address::from_word(*topic));
+        // the role is the first topic of the role events, zero being the default admin role
+        let is_admin_role = topics.get(1).is_some_and(|role| role.is_zero());
+        let is_max = data.len() == 32 && U256::from_be_slice(data) == U256::MAX;
+
+        if event == OWNERSHIP_TRANSFERRED {
+            if let Some(owner) = address(2) {
+                self.owners.insert(emitter, owner);
+            }
+        } else if event == ROLE_GRANTED && is_admin_role {
+            if let Some(account) = address(2) {
+                self.admins.entry(emitter).or_default().insert(account);
+            }
+        } else if event == ROLE_REVOKED && is_admin_role {
+            if let Some(account) = address(2) {
+                self.admins.entry(emitter).or_default().remove(&account);
+            }
+        } else if event == UPGRADED || event == BEACON_UPGRADED {
+            if let Some(implementation) = address(1) {
+                self.proxies.insert(emitter, implementation);
+            }
+        } else if event == INITIALIZED_V4 || event == INITIALIZED_V5 {
+            self.initialized.insert(emitter);
+        } else if (event == APPROVAL && is_max) ||
+            (event == APPROVAL_FOR_ALL && data.last() == Some(&1))
+        {
+            // ERC721 approvals have the token id as a third topic instead
+            if let (Some(spender), 3) = (address(2), topics.len()) {
+                self.approvals.push((i, emitter, spender));
+            }
+        }
+    }
+
+    fn check_arguments<'a>(
+        &mut self,
+        address: Address,
+        function: &str,
+        args: impl IntoIterator<Item = &'a String>,
+    ) {
+        let zero = Address::ZERO.to_string();
+        for (i, arg) in args.into_iter().enumerate() {
+            if *arg == zero {
+                self.findings.insert(Finding {
+                    check: Check::ZeroAddress,
+                    address,
+                    message: format!("{function} is called with the zero address as argument {i}"),
+                });
+            }
+        }
+    }
+
+    /// Returns the findings of all the transactions.
+    ///
+    /// `has_code` tells whether an account has code once the transaction at the given index and
+    /// all the ones before it ran.
+    pub fn finish(
+        mut self,
+        decoder: &CallTraceDecoder,
+        has_code: impl Fn(usize, Address) -> bool,
+    ) -> Vec<Finding> {
+        for (contract, owner) in &self.owners {
+            if self.senders.contains(owner) {
+                self.findings.insert(Finding {
+                    check: Check::DeployerOwner,
+                    address: *contract,
+                    message: format!(
+                        "{} is owned by the sender {owner}",
+                        contract_name(*contract, decoder)
+                    ),
+                });
+            }
+        }
+        for (contract, admins) in &self.admins {
+            for admin in admins.iter().filter(|admin| self.senders.contains(*admin)) {
+                self.findings.insert(Finding {
+                    check: Check::DeployerOwner,
+                    address: *contract,
+                    message: format!(
+                        "{} has the sender {admin} as default admin",
+                        contract_name(*contract, decoder)
+                    ),
+                });
+            }
+        }
+        for (proxy, implementation) in &self.proxies {
+            if !self.initialized.contains(proxy) {
+                self.findings.insert(Finding {
+                    check: Check::UninitializedProxy,
+                    address: *proxy,
+                    message: format!(
+                        "proxy {proxy} to {} is never initialized",
+                        contract_name(*implementation, decoder)
+                    ),
+                });
+            }
+        }
+        for (i, token, spender) in self.approvals {
+            if !has_code(i, spender) {
+                self.findings.insert(Finding {
+                    check: Check::InfiniteApproval,
+                    address: token,
+                    message: format!(
+                        "{} gives an unlimited allowance to {spender}, which has no code",
+                        contract_name(token, decoder)
+                    ),
+                });
+            }
+        }
+        self.findings.into_iter().collect()
+    }
+}
+
+fn contract_name(address: Address, decoder: &CallTraceDecoder) -> String {
+    decoder
+        .contracts
+        .get(&address)
+        .map(|id| format!("{} ({address})", get_contract_name(id)))
+        .or_else(|| decoder.labels.get(&address).map(|label| format!("{label} ({address})")))
+        .unwrap_or_else(|| address.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::keccak256;
+
+    #[test]
+    fn event_topics_match_their_signatures() {
+        for (topic, signature) in [
+            (OWNERSHIP_TRANSFERRED, "OwnershipTransferred(address,address)"),
+            (ROLE_GRANTED, "RoleGranted(bytes32,address,address)"),
+            (ROLE_REVOKED, "RoleRevoked(bytes32,address,address)"),
+            (UPGRADED, "Upgraded(address)"),
+            (BEACON_UPGRADED, "BeaconUpgraded(address)"),
+            (INITIALIZED_V4, "Initialized(uint8)"),
+            (INITIALIZED_V5, "Initialized(uint64)"),
+            (APPROVAL, "Approval(address,address,uint256)"),
+            (APPROVAL_FOR_ALL, "ApprovalForAll(address,address,bool)"),
+        ] {
+            assert_eq!(topic, keccak256(signature), "{signature}");
+        }
+    }
+
+    #[test]
+    fn flags_ownership_left_with_sender() {
+        let (sender, token, proxy) =
+            (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+        let mut checks = SecurityChecks::default();
+        checks.senders.insert(sender);
+        checks.add_log(0, token, &[OWNERSHIP_TRANSFERRED, B256::ZERO, sender.into_word()], &[]);
+        checks.add_log(0, proxy, &[UPGRADED, token.into_word()], &[]);
+        checks.add_log(1, token, &[APPROVAL, sender.into_word(), sender.into_word()], &[0xff; 32]);
+
+        let findings = checks.finish(&CallTraceDecoder::default(), |_, _| false);
+        let found = findings.iter().map(|f| (f.check, f.address)).collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [
+                (Check::DeployerOwner, token),
+                (Check::UninitializedProxy, proxy),
+                (Check::InfiniteApproval, token)
+            ]
+        );
+
+        let ignored = BTreeSet::from([format!("deployer-owner:{token}").parse().unwrap()]);
+        assert!(findings[0].is_ignored(&ignored));
+        assert!(!findings[1].is_ignored(&ignored));
+    }
+
+    #[test]
+    fn can_parse_ignored_checks() {
+        let ignored: IgnoredCheck = "zero-address".parse().unwrap();
+        assert_eq!(ignored, IgnoredCheck { check: Check::ZeroAddress, address: None });
+        assert!("zero".parse::<IgnoredCheck>().is_err());
+        assert!("zero-address:0x12".parse::<IgnoredCheck>().is_err());
+    }
+}
@@ -0,0 +1,292 @@
+use crate::sequence::ScriptSequence;
+use alloy_primitives::Address;
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::{
+    provider::ethers::try_get_http_provider,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_evm::traces::CallKind;
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::RangeInclusive,
+    str::FromStr,
+};
+
+/// Indexes of transactions of a plan, e.g. `1,4-7`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxSelection(Vec<RangeInclusive<usize>>);
+
+impl FromStr for TxSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let index = |value: &str| {
+            let index = value.trim().parse::<usize>();
+            index.map_err(|_| format!("invalid transaction index `{value}`"))
+        };
+        let ranges = s
+            .split(',')
+            .map(|part| {
+                let (start, end) = match part.split_once('-') {
+                    Some((start, end)) => (index(start)?, index(end)?),
+                    None => (index(part)?, index(part)?),
+                };
+                if start > end {
+                    return Err(format!("invalid range of transactions `{part}`"))
+                }
+                Ok(start..=end)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self(ranges))
+    }
+}
+
+impl TxSelection {
+    pub fn contains(&self, index: usize) -> bool {
+        self.0.iter().any(|range| range.contains(&index))
+    }
+}
+
+/// Which transactions of a plan are broadcast, with `--only-txs` and `--skip-txs`.
+#[derive(Clone, Debug, Default)]
+pub struct TxFilter {
+    pub only: Option<TxSelection>,
+    pub skip: Option<TxSelection>,
+}
+
+/// A selected transaction which uses a contract created by a transaction that isn't.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dependency {
+    pub transaction: usize,
+    pub chain: u64,
+    pub address: Address,
+    pub contract: Option<String>,
+    /// The skipped transaction which creates the contract.
+    pub created_by: usize,
+}
+
+/// The nonce the first selected transaction of a sender expects the sender to be at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpectedNonce {
+    pub transaction: usize,
+    pub chain: u64,
+    pub sender: Address,
+    pub nonce: u64,
+}
+
+impl TxFilter {
+    pub fn is_empty(&self) -> bool {
+        self.only.is_none() && self.skip.is_none()
+    }
+
+    fn includes(&self, index: usize) -> bool {
+        self.only.as_ref().map_or(true, |only| only.contains(index)) &&
+            !self.skip.as_ref().map_or(false, |skip| skip.contains(index))
+    }
+
+    /// Removes the transactions which aren't selected from the sequences, with the transactions
+    /// numbered from 0 across the sequences of the plan.
+    ///
+    /// Fails if a sender would skip a nonce, and returns the contracts created by skipped
+    /// transactions which the selected ones use, and the nonces the senders have to be at, to be
+    /// checked against the chains.
+    pub fn apply(
+        &self,
+        sequences: &mut Vec<ScriptSequence>,
+    ) -> Result<(Vec<Dependency>, Vec<ExpectedNonce>)> {
+        let mut offset = 0;
+        let mut created = Vec::new();
+        let mut dependencies = Vec::new();
+        let mut nonces = BTreeMap::<(u64, Address), Vec<(u64, usize)>>::new();
+        for sequence in sequences.iter_mut() {
+            let chain = sequence.chain;
+            let transactions = std::mem::take(&mut sequence.transactions);
+            let count = transactions.len();
+            for (i, tx) in transactions.into_iter().enumerate() {
+                let index = offset + i;
+                if !self.includes(index) {
+                    if matches!(tx.opcode, CallKind::Create | CallKind::Create2) {
+                        if let Some(address) = tx.contract_address {
+                            created.push((index, chain, address, tx.contract_name.clone()));
+                        }
+                    }
+                    created.extend(
+                        tx.additional_contracts
+                            .iter()
+                            .map(|contract| (index, chain, contract.address, None)),
+                    );
+                    continue
+                }
+
+                let typed = tx.typed_tx();
+                let to = typed.to().and_then(|to| to.as_address()).map(|to| to.to_alloy());
+                let data = typed.data().map(|data| data.to_vec()).unwrap_or_default();
+                for (created_by, _, address, contract) in
+                    created.iter().filter(|(_, created_chain, ..)| *created_chain == chain)
+                {
+                    let used = to == Some(*address) ||
+                        data.windows(20).any(|window| window == address.as_slice());
+                    if used {
+                        dependencies.push(Dependency {
+                            transaction: index,
+                            chain,
+                            address: *address,
+                            contract: contract.clone(),
+                            created_by: *created_by,
+                        });
+                    }
+                }
+                if let (Some(from), Some(nonce)) = (typed.from(), typed.nonce()) {
+                    nonces
+                        .entry((chain, from.to_alloy()))
+                        .or_default()
+                        .push((nonce.as_u64(), index));
+                }
+                sequence.transactions.push_back(tx);
+            }
+            offset += count;
+        }
+        sequences.retain(|sequence| !sequence.transactions.is_empty());
+        if sequences.is_empty() {
+            eyre::bail!("None of the transactions of the plan are selected")
+        }
+
+        let mut expected = Vec::new();
+        for ((chain, sender), mut selected) in nonces {
+            selected.sort_unstable();
+            for pair in selected.windows(2) {
+                let ((nonce, index), (next, next_index)) = (pair[0], pair[1]);
+                if next != nonce + 1 {
+                    eyre::bail!(
+                        "Transactions {index} and {next_index} of {sender} on chain {chain} are \
+                         selected, but not the ones between them, its nonce can't skip them"
+                    )
+                }
+            }
+            let (nonce, transaction) = selected[0];
+            expected.push(ExpectedNonce { transaction, chain, sender, nonce });
+        }
+        Ok((dependencies, expected))
+    }
+}
+
+/// Checks that the contracts the selected transactions depend on are deployed, and that the
+/// senders are at the nonces of their first selected transactions.
+pub async fn check_selection(
+    rpcs: &HashMap<u64, String>,
+    dependencies: &[Dependency],
+    nonces: &[ExpectedNonce],
+) -> Result<()> {
+    let mut providers = HashMap::new();
+    for chain in dependencies.iter().map(|d| d.chain).chain(nonces.iter().map(|n| n.chain)) {
+        if let (Some(rpc), false) = (rpcs.get(&chain), providers.contains_key(&chain)) {
+            providers.insert(chain, try_get_http_provider(rpc)?);
+        }
+    }
+
+    for dependency in dependencies {
+        let Some(provider) = providers.get(&dependency.chain) else { continue };
+        let code = provider.get_code(dependency.address.to_ethers(), None).await?;
+        if code.is_empty() {
+            eyre::bail!(
+                "Transaction {} uses {} at {}, created by transaction {} which isn't selected \
+                 and isn't deployed on chain {}",
+                dependency.transaction,
+                dependency.contract.as_deref().unwrap_or("the contract"),
+                dependency.address,
+                dependency.created_by,
+                dependency.chain
+            )
+        }
+    }
+
+    for expected in nonces {
+        let Some(provider) = providers.get(&expected.chain) else { continue };
+        let nonce =
+            provider.get_transaction_count(expected.sender.to_ethers(), None).await?.as_u64();
+        if nonce != expected.nonce {
+            eyre::bail!(
+                "Transaction {} of {} on chain {} expects nonce {}, but the account is at nonce \
+                 {nonce}. Send the transactions of the plan before it first.",
+                expected.transaction,
+                expected.sender,
+                expected.chain,
+                expected.nonce
+            )
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TransactionWithMetadata;
+    use alloy_primitives::{address, Bytes};
+    use alloy_rpc_types::request::TransactionRequest;
+
+    const SENDER: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+
+    fn tx(nonce: u64, to: Option<Address>, input: Bytes) -> TransactionWithMetadata {
+        let mut tx = TransactionWithMetadata::from_tx_request(TransactionRequest {
+            from: Some(SENDER),
+            to,
+            nonce: Some(nonce.try_into().unwrap()),
+            input: Some(input).into(),
+            ..Default::default()
+        });
+        if to.is_none() {
+            tx.opcode = CallKind::Create;
+            tx.contract_address = Some(SENDER.create(nonce));
+            tx.contract_name = Some(format!("Contract{nonce}"));
+        }
+        tx
+    }
+
+    fn sequences() -> Vec<ScriptSequence> {
+        let library = SENDER.create(0);
+        let transactions = vec![
+            tx(0, None, Bytes::from_static(&[0x60])),
+            tx(1, None, [&[0x60][..], library.as_slice()].concat().into()),
+            tx(2, Some(SENDER.create(1)), Bytes::from_static(&[0x12, 0x34, 0x56, 0x78])),
+        ];
+        vec![ScriptSequence { transactions: transactions.into(), chain: 1, ..Default::default() }]
+    }
+
+    #[test]
+    fn parses_selections() {
+        let selection: TxSelection = "1, 4-7".parse().unwrap();
+        assert!(selection.contains(1) && selection.contains(4) && selection.contains(7));
+        assert!(!selection.contains(0) && !selection.contains(3) && !selection.contains(8));
+        assert!("7-4".parse::<TxSelection>().is_err());
+        assert!("a".parse::<TxSelection>().is_err());
+    }
+
+    #[test]
+    fn selects_transactions() {
+        let filter = TxFilter { only: Some("0-1".parse().unwrap()), skip: None };
+        let mut selected = sequences();
+        let (dependencies, nonces) = filter.apply(&mut selected).unwrap();
+        assert_eq!(selected[0].transactions.len(), 2);
+        assert!(dependencies.is_empty());
+        assert_eq!(nonces, [ExpectedNonce { transaction: 0, chain: 1, sender: SENDER, nonce: 0 }]);
+
+        // The rest of the plan, once the first transactions are sent.
+        let filter = TxFilter { only: None, skip: Some("0".parse().unwrap()) };
+        let mut selected = sequences();
+        let (dependencies, nonces) = filter.apply(&mut selected).unwrap();
+        assert_eq!(dependencies.len(), 1);
+        assert_eq!(dependencies[0].transaction, 1);
+        assert_eq!(dependencies[0].created_by, 0);
+        assert_eq!(dependencies[0].address, SENDER.create(0));
+        assert_eq!(nonces[0].nonce, 1);
+
+        // The sender can't skip a nonce.
+        let filter = TxFilter { only: None, skip: Some("1".parse().unwrap()) };
+        assert!(filter.apply(&mut sequences()).is_err());
+
+        let filter = TxFilter { only: Some("5".parse().unwrap()), skip: None };
+        assert!(filter.apply(&mut sequences()).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 use super::{multi_sequence::MultiChainSequence, NestedValue};
 use crate::{
     transaction::{wrapper, AdditionalContract, TransactionWithMetadata},
-    verify::VerifyBundle,
+    verify::{VerificationReport, VerifiedContract, VerifyBundle},
 };
 use alloy_primitives::{Address, TxHash};
 use ethers_core::types::{transaction::eip2718::TypedTransaction, TransactionReceipt};
@@ -9,12 +9,14 @@ use eyre::{ContextCompat, Result, WrapErr};
 use forge_verify::provider::VerificationProviderType;
 use foundry_cli::utils::{now, Git};
 use foundry_common::{
-    fs, shell,
+    fs, shell, tr,
     types::{ToAlloy, ToEthers},
     SELECTOR_LEN,
 };
 use foundry_compilers::ArtifactId;
 use foundry_config::Config;
+use foundry_evm::traces::CallKind;
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, VecDeque},
@@ -23,6 +25,9 @@ use std::{
 };
 use yansi::Paint;
 
+/// How many verify requests are built ahead of the one being submitted.
+const CONCURRENT_VERIFY_PREPARATIONS: usize = 8;
+
 /// Returns the commit hash of the project if it exists
 pub fn get_commit_hash(root: &Path) -> Option<String> {
     Git::new(root).commit_hash(true, "HEAD").ok()
@@ -54,6 +59,17 @@ impl ScriptSequenceKind {
             ScriptSequenceKind::Multi(sequence) => &mut sequence.deployments,
         }
     }
+
+    /// Returns the path of the broadcast artifact, if the sequence is saved to disk.
+    pub fn broadcast_path(&self) -> Option<&Path> {
+        match self {
+            ScriptSequenceKind::Single(sequence) => {
+                sequence.paths.as_ref().map(|(path, _)| path.as_path())
+            }
+            ScriptSequenceKind::Multi(sequence) => Some(sequence.path.as_path()),
+        }
+    }
+
     /// Updates underlying sequence paths to not be under /dry-run directory.
     pub fn update_paths_to_broadcasted(
         &mut self,
@@ -103,6 +119,9 @@ pub struct ScriptSequence {
     pub timestamp: u64,
     pub chain: u64,
     pub commit: Option<String>,
+    /// The address of the key generated with `--ephemeral-deployer`, which `--resume` loads.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ephemeral_deployer: Option<Address>,
 }
 
 /// Sensitive values from the transactions in a script sequence
@@ -194,8 +213,11 @@ impl ScriptSequence {
         }
 
         if !silent {
-            shell::println(format!("\nTransactions saved to: {}\n", path.display()))?;
-            shell::println(format!("Sensitive values saved to: {}\n", sensitive_path.display()))?;
+            shell::println(tr!("\nTransactions saved to: {path}\n", path = path.display()))?;
+            shell::println(tr!(
+                "Sensitive values saved to: {path}\n",
+                path = sensitive_path.display()
+            ))?;
         }
 
         Ok(())
@@ -259,21 +281,25 @@ impl ScriptSequence {
 
     /// Given the broadcast log, it matches transactions with receipts, and tries to verify any
     /// created contract on etherscan.
+    ///
+    /// Returns a report of the verified and unverifiable contracts.
     pub async fn verify_contracts(
         &mut self,
         config: &Config,
         mut verify: VerifyBundle,
-    ) -> Result<()> {
+    ) -> Result<VerificationReport> {
         trace!(target: "script", "verifying {} contracts [{}]", verify.known_contracts.len(), self.chain);
 
         verify.set_chain(config, self.chain.into());
 
+        let mut report = VerificationReport { chain: self.chain, ..Default::default() };
+
         if verify.etherscan.has_key() ||
-            verify.verifier.verifier != VerificationProviderType::Etherscan
+            verify.verifier.verifier() != VerificationProviderType::Etherscan
         {
             trace!(target: "script", "prepare future verifications");
 
-            let mut future_verifications = Vec::with_capacity(self.receipts.len());
+            let mut verifications = Vec::with_capacity(self.receipts.len());
             let mut unverifiable_contracts = vec![];
 
             // Make sure the receipts have the right order first.
@@ -293,7 +319,7 @@ impl ScriptSequence {
                     (receipt.contract_address.map(|h| h.to_alloy()), tx.typed_tx().data())
                 {
                     match verify.get_verify_args(address, offset, &data.0, &self.libraries) {
-                        Some(verify) => future_verifications.push(verify.run()),
+                        Some(verify) => verifications.push(verify),
                         None => unverifiable_contracts.push(address),
                     };
                 }
@@ -301,31 +327,70 @@ impl ScriptSequence {
                 // Verify potential contracts created during the transaction execution
                 for AdditionalContract { address, init_code, .. } in &tx.additional_contracts {
                     match verify.get_verify_args(*address, 0, init_code, &self.libraries) {
-                        Some(verify) => future_verifications.push(verify.run()),
+                        Some(verify) => verifications.push(verify),
                         None => unverifiable_contracts.push(*address),
                     };
                 }
             }
 
-            trace!(target: "script", "collected {} verification jobs and {} unverifiable contracts", future_verifications.len(), unverifiable_contracts.len());
+            trace!(target: "script", "collected {} verification jobs and {} unverifiable contracts", verifications.len(), unverifiable_contracts.len());
 
+            report.unverifiable.clone_from(&unverifiable_contracts);
             self.check_unverified(unverifiable_contracts, verify);
 
-            let num_verifications = future_verifications.len();
-            println!("##\nStart verification for ({num_verifications}) contracts",);
-            for verification in future_verifications {
-                verification.await?;
+            let num_verifications = verifications.len();
+            println!(
+                "{}",
+                tr!("##\nStart verification for ({count}) contracts", count = num_verifications)
+            );
+            // Building a request reads and compiles the sources of the contract, so the next
+            // requests are built in parallel while one is submitted to the rate-limited API.
+            let mut contracts =
+                verifications.iter().map(VerifiedContract::new).collect::<Vec<_>>().into_iter();
+            let mut verifications = futures::stream::iter(verifications)
+                .map(|verify| tokio::spawn(verify.prepare()))
+                .buffered(CONCURRENT_VERIFY_PREPARATIONS);
+            while let Some(verification) = verifications.next().await {
+                // The requests are submitted with `watch`, so the submission only succeeds once
+                // the verifier reports the contract as verified.
+                verification??.submit().await?;
+                report.verified.extend(contracts.next());
             }
 
-            println!("All ({num_verifications}) contracts were verified!");
+            println!(
+                "{}",
+                tr!("All ({count}) contracts were verified!", count = num_verifications)
+            );
         }
 
-        Ok(())
+        Ok(report)
     }
 
     /// Let the user know if there are any contracts which can not be verified. Also, present some
     /// hints on potential causes.
     fn check_unverified(&self, unverifiable_contracts: Vec<Address>, verify: VerifyBundle) {
+        let raw_deployments = self.raw_deployments();
+        let (raw_deployments, unverifiable_contracts): (Vec<_>, Vec<_>) = unverifiable_contracts
+            .into_iter()
+            .partition(|address| raw_deployments.contains(address));
+
+        if !raw_deployments.is_empty() {
+            println!(
+                "\n{}",
+                Paint::yellow(format!(
+                    "The following contracts were deployed from raw bytecode, which doesn't match any contract of the project: {raw_deployments:?}.\n\n{}",
+                    "Once their sources are added to the project, they can be verified with:"
+                ))
+                .bold(),
+            );
+            for address in raw_deployments {
+                println!(
+                    "\tforge verify-contract {address} <path>:<contract> --chain {}",
+                    self.chain
+                );
+            }
+        }
+
         if !unverifiable_contracts.is_empty() {
             println!(
                 "\n{}",
@@ -351,6 +416,17 @@ impl ScriptSequence {
         }
     }
 
+    /// Returns the addresses of the contracts deployed from raw bytecode, e.g. with
+    /// `vm.deployRawCode`, which isn't the bytecode of any contract of the project.
+    pub fn raw_deployments(&self) -> Vec<Address> {
+        self.transactions
+            .iter()
+            .filter(|tx| matches!(tx.opcode, CallKind::Create | CallKind::Create2))
+            .filter(|tx| tx.contract_name.is_none())
+            .filter_map(|tx| tx.contract_address)
+            .collect()
+    }
+
     /// Returns the first RPC URL of this sequence.
     pub fn rpc_url(&self) -> &str {
         self.transactions.front().expect("empty sequence").rpc.as_str()
@@ -403,4 +479,22 @@ mod tests {
             "522bb704"
         );
     }
+
+    #[test]
+    fn finds_raw_deployments() {
+        let raw = Address::with_last_byte(1);
+        let mut sequence = ScriptSequence::default();
+        for (opcode, contract_name, contract_address) in [
+            (CallKind::Create, Some("Token".to_string()), Some(Address::with_last_byte(2))),
+            (CallKind::Create, None, Some(raw)),
+            (CallKind::Call, None, Some(Address::with_last_byte(3))),
+        ] {
+            let mut tx = TransactionWithMetadata::from_tx_request(Default::default());
+            tx.opcode = opcode;
+            tx.contract_name = contract_name;
+            tx.contract_address = contract_address;
+            sequence.transactions.push_back(tx);
+        }
+        assert_eq!(sequence.raw_deployments(), vec![raw]);
+    }
 }
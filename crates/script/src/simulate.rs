@@ -9,8 +9,19 @@ use super::{
 use crate::{
     broadcast::{estimate_gas, BundledState},
     build::LinkedBuildData,
+    call_graph::CallGraph,
+    deny_list::{check_denied_interactions, OVERRIDES_FILE_NAME},
+    dump_state::{dump_path, AnvilState},
+    events::ScriptEvents,
     execute::{ExecutionArtifacts, ExecutionData},
+    gas_profile::GasProfile,
+    interfaces::{supports_interface, Interface, InterfaceAudit},
+    l1_fees::L1FeeModel,
+    onchain_diff::{SimulatedExecution, SimulatedExecutions},
+    security::{Finding, SecurityChecks},
     sequence::get_commit_hash,
+    state_diff::StorageDiff,
+    trace_export::{export_traces, TraceFormat},
     ScriptArgs, ScriptConfig, ScriptResult,
 };
 use alloy_primitives::{utils::format_units, Address, U256};
@@ -18,15 +29,21 @@ use eyre::{Context, Result};
 use foundry_cheatcodes::{BroadcastableTransactions, ScriptWallets};
 use foundry_cli::utils::{has_different_gas_calc, now};
 use foundry_common::{
-    get_contract_name, provider::ethers::RpcUrl, shell, types::ToAlloy, ContractsByArtifact,
+    get_contract_name, provider::ethers::RpcUrl, shell, tr, types::ToAlloy, ContractsByArtifact,
+};
+use foundry_debugger::Debugger;
+use foundry_evm::{
+    debug::DebugArena,
+    revm::{primitives::KECCAK_EMPTY, DatabaseRef},
+    traces::render_trace_arena,
 };
-use foundry_evm::traces::render_trace_arena;
 use futures::future::join_all;
 use parking_lot::RwLock;
 use std::{
     collections::{BTreeMap, HashMap, VecDeque},
     sync::Arc,
 };
+use yansi::Paint;
 
 /// Same as [ExecutedState], but also contains [ExecutionArtifacts] which are obtained from
 /// [ScriptResult].
@@ -49,10 +66,10 @@ impl PreSimulationState {
     /// left empty.
     ///
     /// Both modes will panic if any of the transactions have None for the `rpc` field.
-    pub async fn fill_metadata(self) -> Result<FilledTransactionsState> {
+    pub async fn fill_metadata(mut self) -> Result<FilledTransactionsState> {
         let transactions = if let Some(txs) = self.execution_result.transactions.as_ref() {
             if self.args.skip_simulation {
-                shell::println("\nSKIPPING ON CHAIN SIMULATION.")?;
+                shell::println(tr!("\nSKIPPING ON CHAIN SIMULATION."))?;
                 self.no_simulation(txs.clone())?
             } else {
                 self.onchain_simulation(txs.clone()).await?
@@ -77,7 +94,7 @@ impl PreSimulationState {
     ///
     /// Collects gas usage and metadata for each transaction.
     pub async fn onchain_simulation(
-        &self,
+        &mut self,
         transactions: BroadcastableTransactions,
     ) -> Result<VecDeque<TransactionWithMetadata>> {
         trace!(target: "script", "executing onchain simulation");
@@ -95,6 +112,12 @@ impl PreSimulationState {
             self.build_address_to_abi_map(&contracts);
 
         let mut final_txs = VecDeque::new();
+        let verbosity = self.script_config.evm_opts.verbosity;
+        let export = self.args.trace_format != TraceFormat::Pretty;
+        let keep_traces =
+            verbosity > 3 || export || self.args.gas_profile || self.args.call_graph.is_some();
+        let security_checks = self.args.security_checks;
+        let deny_list = !self.script_config.policy.denied_addresses.is_empty();
 
         // Executes all transactions from the different forks concurrently.
         let futs = transactions
@@ -115,7 +138,7 @@ impl PreSimulationState {
                     .wrap_err("Internal EVM error during simulation")?;
 
                 if !result.success {
-                    return Ok((None, result.traces));
+                    return Ok((None, result.traces, result.debug, None, Vec::new(), None));
                 }
 
                 let created_contracts = result.get_created_contracts();
@@ -134,7 +157,7 @@ impl PreSimulationState {
                     // We inflate the gas used by the user specified percentage
                     None => {
                         let gas =
-                            U256::from(result.gas_used * self.args.gas_estimate_multiplier / 100);
+                            U256::from(result.gas_used * self.args.gas_estimate_multiplier() / 100);
                         tx.gas = Some(gas);
                     }
                 }
@@ -148,21 +171,39 @@ impl PreSimulationState {
                     is_fixed_gas_limit,
                 )?;
 
-                eyre::Ok((Some(tx), result.traces))
+                let storage_diff = (self.args.state_diff || self.args.html_report.is_some())
+                    .then(|| StorageDiff::new(&result));
+                let logs = if self.args.export_events { result.logs } else { Vec::new() };
+                let execution = self.args.diff_onchain.then(|| SimulatedExecution::new(&result));
+
+                // Only keep the traces around if they're going to be used.
+                let traces = if keep_traces || security_checks || deny_list {
+                    result.traces
+                } else {
+                    Default::default()
+                };
+                eyre::Ok((Some(tx), traces, None, storage_diff, logs, execution))
             })
             .collect::<Vec<_>>();
 
-        if self.script_config.evm_opts.verbosity > 3 {
+        if verbosity > 3 {
             println!("==========================");
             println!("Simulated On-chain Traces:\n");
         }
 
         let mut abort = false;
-        for res in join_all(futs).await {
-            let (tx, traces) = res?;
+        let mut failed_debug = None;
+        let mut simulated_traces = Vec::new();
+        let mut storage_diffs = Vec::new();
+        let mut events = ScriptEvents::default();
+        let mut checks = SecurityChecks::default();
+        let mut executions = SimulatedExecutions::default();
+        let mut denied = Vec::new();
+        for (i, res) in join_all(futs).await.into_iter().enumerate() {
+            let (tx, traces, debug, storage_diff, logs, execution) = res?;
 
             // Transaction will be `None`, if execution didn't pass.
-            if tx.is_none() || self.script_config.evm_opts.verbosity > 3 {
+            if tx.is_none() || verbosity > 3 {
                 for (_, trace) in &traces {
                     println!(
                         "{}",
@@ -171,20 +212,194 @@ impl PreSimulationState {
                 }
             }
 
+            if let Some(tx) = tx.as_ref().filter(|_| security_checks) {
+                checks.add(i, tx, &traces, &self.execution_artifacts.decoder).await;
+            }
+            if let Some(tx) = tx.as_ref().filter(|_| deny_list) {
+                let runner = runners.get(&tx.rpc).expect("invalid rpc url").read();
+                let chain = runner.executor.env.cfg.chain_id;
+                denied.extend(self.script_config.policy.denied_interactions(i, chain, &traces));
+            }
+
+            if keep_traces {
+                simulated_traces.extend(
+                    traces.into_iter().map(|(_, trace)| (format!("transaction {i}"), trace)),
+                );
+            }
+
+            storage_diffs.extend(storage_diff.map(|diff| (i, diff)));
+            events.add_simulated(i, &logs, &self.execution_artifacts.decoder).await;
+
             if let Some(tx) = tx {
+                if let Some(execution) = execution {
+                    executions.insert(&tx, execution);
+                }
                 final_txs.push_back(tx);
             } else {
                 abort = true;
+                failed_debug = failed_debug.or(debug);
             }
         }
 
+        if export {
+            let path = export_traces(
+                simulated_traces.iter().map(|(name, trace)| (name.clone(), trace)),
+                &self.execution_artifacts.decoder,
+                self.args.trace_format,
+                &self.script_config.config,
+                &self.build_data.build_data.target.name,
+                "simulation",
+            )
+            .await?;
+            println!("Simulated traces saved to: {}\n", path.display());
+        }
+
         if abort {
+            if let Some(debug) = failed_debug {
+                self.run_debugger_on_failure(&debug)?;
+            }
             eyre::bail!("Simulated execution failed.")
         }
 
+        check_denied_interactions(
+            &denied,
+            &self.args.allow_denied,
+            self.args.deny_override_reason.as_deref(),
+            &self.build_data.build_data.target.name,
+            &self.script_config.config.broadcast.join(OVERRIDES_FILE_NAME),
+            now().as_secs(),
+        )?;
+
+        if security_checks {
+            let findings = checks.finish(&self.execution_artifacts.decoder, |i, address| {
+                let runner = runners.get(&final_txs[i].rpc).expect("invalid rpc url").read();
+                runner
+                    .executor
+                    .backend
+                    .basic_ref(address)
+                    .ok()
+                    .flatten()
+                    .map_or(false, |account| account.code_hash != KECCAK_EMPTY)
+            });
+            self.show_security_findings(&findings)?;
+        }
+
+        if security_checks {
+            let interfaces = Interface::all(&contracts);
+            let mut audit = InterfaceAudit::default();
+            for tx in &final_txs {
+                let runner = runners.get(&tx.rpc).expect("invalid rpc url").read();
+                let info = tx
+                    .contract_address
+                    .and_then(|address| address_to_abi.get(&address).map(|info| (address, info)));
+                if let Some((address, info)) = info {
+                    audit.add_contract(address, &info.contract_name, info.abi, &interfaces, |id| {
+                        supports_interface(&runner.executor, runner.sender, address, id)
+                    });
+                }
+                if let (Some(to), Some(data)) = (tx.transaction.to_addr(), tx.transaction.data()) {
+                    audit.add_call(to.to_alloy(), data, |facet| {
+                        address_to_abi.get(&facet).map(|info| info.abi)
+                    });
+                }
+            }
+            audit.finish()?;
+        }
+
+        if self.args.check_upgrades {
+            self.check_upgrades(&final_txs).await?;
+        }
+
+        let state_diff = self.label_state_diff(&storage_diffs);
+        if self.args.state_diff {
+            self.show_state_diff(&state_diff)?;
+        }
+
+        if self.args.gas_profile {
+            let mut profile = GasProfile::default();
+            for (_, trace) in &simulated_traces {
+                profile.analyze(trace, &self.execution_artifacts.decoder).await;
+            }
+            let path = profile.save(
+                &self.script_config.config.cache_path,
+                &self.build_data.build_data.target.name,
+            )?;
+            println!("\n==========================");
+            println!("Simulated Gas Profile:\n");
+            println!("{profile}");
+            println!("Gas profile saved to: {}\n", path.display());
+        }
+
+        if let Some(format) = self.args.call_graph {
+            let mut graph = CallGraph::default();
+            for (_, trace) in &simulated_traces {
+                graph.add(trace, &self.execution_artifacts.decoder).await;
+            }
+            let path = graph.save(
+                format,
+                &self.script_config.config.cache_path,
+                &self.build_data.build_data.target.name,
+            )?;
+            println!("Call graph saved to: {}\n", path.display());
+        }
+
+        if let Some(path) = &self.args.dump_state {
+            for runner in runners.values() {
+                let runner = runner.read();
+                let chain = runner.executor.env.cfg.chain_id;
+                let path = dump_path(path, chain, runners.len() > 1);
+                AnvilState::new(&runner)?.save(&path)?;
+                println!("State of chain {chain} dumped to: {}\n", path.display());
+            }
+        }
+
+        if let Some(governance) = self.args.rehearse_governance {
+            self.rehearse_governance(governance, &final_txs, &runners)?;
+        }
+
+        self.execution_artifacts.events = events;
+        self.execution_artifacts.simulated_executions = executions;
+        self.execution_artifacts.state_diff = state_diff;
+
         Ok(final_txs)
     }
 
+    /// Prints the findings of the security checks which aren't ignored by the policy.
+    fn show_security_findings(&mut self, findings: &[Finding]) -> Result<()> {
+        let ignored = &self.script_config.policy.ignored_checks;
+        let findings =
+            findings.iter().filter(|finding| !finding.is_ignored(ignored)).collect::<Vec<_>>();
+        if findings.is_empty() {
+            return Ok(())
+        }
+
+        shell::println("\n==========================")?;
+        shell::println("Security checks:\n")?;
+        for finding in findings {
+            shell::println(Paint::yellow(format!("Warning: {finding}")))?;
+        }
+        shell::println(
+            "\nChecks can be silenced in the policy file with `ignored_checks = [\"<check>\"]`, or \
+             \"<check>:<address>\" for a single contract.\n",
+        )?;
+        self.execution_artifacts
+            .warnings
+            .extend(findings.iter().map(|finding| finding.to_string()));
+        Ok(())
+    }
+
+    /// Opens the debugger at the revert of the first transaction which failed the simulation.
+    fn run_debugger_on_failure(&self, debug: &[DebugArena]) -> Result<()> {
+        let mut debugger = Debugger::builder()
+            .debug_arenas(debug)
+            .decoder(&self.execution_artifacts.decoder)
+            .sources(self.build_data.build_data.sources.clone())
+            .start_at_end(true)
+            .build();
+        debugger.try_run()?;
+        Ok(())
+    }
+
     /// Build mapping from contract address to its ABI, code and contract name.
     fn build_address_to_abi_map<'a>(
         &self,
@@ -226,7 +441,7 @@ impl PreSimulationState {
             .map(|rpc| async move {
                 let mut script_config = self.script_config.clone();
                 script_config.evm_opts.fork_url = Some(rpc.clone());
-                let runner = script_config.get_runner().await?;
+                let runner = script_config.get_runner(self.args.debug_on_failure).await?;
                 Ok((rpc.clone(), runner))
             })
             .collect::<Vec<_>>();
@@ -278,6 +493,9 @@ impl FilledTransactionsState {
         }
 
         let mut total_gas_per_rpc: HashMap<RpcUrl, U256> = HashMap::new();
+        // The L1 fee model of each rollup, and the L1 data fee of each of its transactions.
+        let mut l1_fee_models: HashMap<RpcUrl, Option<L1FeeModel>> = HashMap::new();
+        let mut l1_fees_per_rpc: HashMap<RpcUrl, Vec<(String, U256, U256)>> = HashMap::new();
 
         // Batches sequence of transactions from different rpcs.
         let mut new_sequence = VecDeque::new();
@@ -292,8 +510,9 @@ impl FilledTransactionsState {
             let tx_rpc = tx.rpc.clone();
             let provider_info = manager.get_or_init_provider(&tx.rpc, self.args.legacy).await?;
 
-            // Handles chain specific requirements.
-            tx.change_type(provider_info.is_legacy);
+            // Handles chain specific requirements. Transactions paying their fees in a fee
+            // currency extend EIP-1559 ones, even on chains which are otherwise legacy.
+            tx.change_type(provider_info.is_legacy && self.args.fee_currency.is_none());
             tx.transaction.set_chain_id(provider_info.chain);
 
             if !self.args.skip_simulation {
@@ -317,7 +536,7 @@ impl FilledTransactionsState {
                     if let Err(err) = estimate_gas(
                         typed_tx,
                         &provider_info.provider,
-                        self.args.gas_estimate_multiplier,
+                        self.args.gas_estimate_multiplier(),
                     )
                     .await
                     {
@@ -328,8 +547,43 @@ impl FilledTransactionsState {
                     }
                 }
 
+                let gas = (*typed_tx.gas().expect("gas is set")).to_alloy();
+                if gas > provider_info.block_gas_limit {
+                    shell::println(Paint::yellow(format!(
+                        "Warning: a transaction needs {gas} gas, more than the block gas limit \
+                         {} of chain {}.",
+                        provider_info.block_gas_limit, provider_info.chain
+                    )))?;
+                }
                 let total_gas = total_gas_per_rpc.entry(tx_rpc.clone()).or_insert(U256::ZERO);
-                *total_gas += (*typed_tx.gas().expect("gas is set")).to_alloy();
+                *total_gas += gas;
+
+                let model = match l1_fee_models.get(&tx_rpc) {
+                    Some(model) => *model,
+                    None => {
+                        let provider = &*provider_info.provider;
+                        let model = L1FeeModel::detect(provider, provider_info.chain).await;
+                        l1_fee_models.insert(tx_rpc.clone(), model);
+                        model
+                    }
+                };
+                if let Some(model) = model {
+                    match model.estimate(&*provider_info.provider, tx.typed_tx()).await {
+                        Ok(fee) => {
+                            let label = tx
+                                .contract_name
+                                .clone()
+                                .filter(|name| !name.is_empty())
+                                .or_else(|| tx.function.clone())
+                                .unwrap_or_default();
+                            l1_fees_per_rpc
+                                .entry(tx_rpc.clone())
+                                .or_default()
+                                .push((label, gas, fee));
+                        }
+                        Err(err) => trace!("L1 fee estimation failed: {err}"),
+                    }
+                }
             }
 
             new_sequence.push_back(tx);
@@ -373,12 +627,33 @@ impl FilledTransactionsState {
                         .trim_end_matches('.')
                 ))?;
                 shell::println(format!("\nEstimated total gas used for script: {total_gas}"))?;
-                shell::println(format!(
-                    "\nEstimated amount required: {} ETH",
-                    format_units(total_gas.saturating_mul(per_gas), 18)
-                        .unwrap_or_else(|_| "[Could not calculate]".to_string())
-                        .trim_end_matches('0')
-                ))?;
+
+                let mut amount = total_gas.saturating_mul(per_gas);
+                if let Some(model) = l1_fee_models.get(&rpc).copied().flatten() {
+                    let fees = l1_fees_per_rpc.remove(&rpc).unwrap_or_default();
+                    let total_l1_fee =
+                        fees.iter().fold(U256::ZERO, |total, (_, _, fee)| total + *fee);
+                    shell::println("\nEstimated cost per transaction:")?;
+                    for (i, (label, gas, l1_fee)) in fees.iter().enumerate() {
+                        let l2_fee = gas.saturating_mul(per_gas);
+                        let total = if model.is_extra() { l2_fee + *l1_fee } else { l2_fee };
+                        shell::println(format!(
+                            "  [{i}] {label}: {} ETH (L1 data: {} ETH)",
+                            format_eth(total),
+                            format_eth(*l1_fee)
+                        ))?;
+                    }
+                    shell::println(format!(
+                        "\nEstimated L1 data fee: {} ETH{}",
+                        format_eth(total_l1_fee),
+                        if model.is_extra() { "" } else { ", included in the gas used" }
+                    ))?;
+                    if model.is_extra() {
+                        amount += total_l1_fee;
+                    }
+                }
+
+                shell::println(format!("\nEstimated amount required: {} ETH", format_eth(amount)))?;
                 shell::println("\n==========================")?;
             }
         }
@@ -450,6 +725,19 @@ impl FilledTransactionsState {
             libraries,
             chain,
             commit,
+            ephemeral_deployer: self
+                .script_config
+                .ephemeral_deployer
+                .as_ref()
+                .map(|deployer| deployer.address()),
         })
     }
 }
+
+/// Formats an amount of wei in ether, e.g. for cost estimates.
+fn format_eth(amount: U256) -> String {
+    format_units(amount, 18)
+        .unwrap_or_else(|_| "[Could not calculate]".to_string())
+        .trim_end_matches('0')
+        .to_string()
+}
@@ -0,0 +1,245 @@
+use crate::build::LinkedBuildData;
+use eyre::Result;
+use foundry_common::shell;
+use foundry_compilers::ArtifactId;
+use foundry_config::Config;
+use std::{collections::BTreeSet, fmt};
+use yansi::Paint;
+
+/// Share of the size limit, in percent, from which a contract is reported as nearing it.
+const NEAR_LIMIT_PERCENT: usize = 90;
+
+/// The unused functions listed per contract, at most.
+const MAX_LISTED_FUNCTIONS: usize = 10;
+
+/// A remedy for a contract which exceeds or nears the size limit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Remedy {
+    /// A library whose functions are all internal, so they're inlined in the contract.
+    ExternalizeLibrary(String),
+    /// Public functions which no other contract or script of the project calls.
+    UnusedFunctions(Vec<String>),
+    /// Revert strings embedded in the code, which custom errors would replace.
+    RevertStrings {
+        count: usize,
+        bytes: usize,
+    },
+    /// The optimizer favors the cost of calls over the size of the code.
+    LowerOptimizerRuns(usize),
+    EnableOptimizer,
+}
+
+impl fmt::Display for Remedy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ExternalizeLibrary(library) => write!(
+                f,
+                "make the functions of library `{library}` public or external, so it's deployed \
+                 and linked instead of inlined"
+            ),
+            Self::UnusedFunctions(functions) => write!(
+                f,
+                "remove or make internal the public functions no other contract or script calls: \
+                 {}",
+                functions.join(", ")
+            ),
+            Self::RevertStrings { count, bytes } => write!(
+                f,
+                "replace the revert strings with custom errors, {count} strings take about \
+                 {bytes} bytes"
+            ),
+            Self::LowerOptimizerRuns(runs) => write!(
+                f,
+                "lower `optimizer_runs` ({runs}), which trades the gas of calls for code size"
+            ),
+            Self::EnableOptimizer => write!(f, "enable the optimizer"),
+        }
+    }
+}
+
+/// The analysis of a contract which exceeds or nears the size limit.
+#[derive(Clone, Debug)]
+pub struct SizeAdvice {
+    pub name: String,
+    pub size: usize,
+    pub limit: usize,
+    pub remedies: Vec<Remedy>,
+}
+
+/// Returns the number of revert string chunks pushed by `code`, and their size.
+///
+/// Any push of at least 4 bytes of printable ASCII, once right-trimmed of zeros, counts as one.
+fn revert_string_weight(code: &[u8]) -> (usize, usize) {
+    let (mut count, mut bytes) = (0, 0);
+    let mut pc = 0;
+    while pc < code.len() {
+        let opcode = code[pc];
+        pc += 1;
+        // PUSH1..PUSH32
+        if (0x60..=0x7f).contains(&opcode) {
+            let len = (opcode - 0x5f) as usize;
+            let data = &code[pc..(pc + len).min(code.len())];
+            pc += len;
+            let end = data.iter().rposition(|byte| *byte != 0).map_or(0, |i| i + 1);
+            let text = &data[..end];
+            if text.len() >= 4 && text.iter().all(|byte| (0x20..0x7f).contains(byte)) {
+                count += 1;
+                bytes += text.len();
+            }
+        }
+    }
+    (count, bytes)
+}
+
+/// Returns the libraries of `libraries` which `source` uses, with `using .. for` or a call.
+fn used_libraries(source: &str, libraries: &BTreeSet<String>) -> Vec<String> {
+    libraries
+        .iter()
+        .filter(|library| {
+            source.contains(&format!("using {library} for")) ||
+                source.contains(&format!("{library}."))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Returns whether `source` declares the library `name`.
+fn declares_library(source: &str, name: &str) -> bool {
+    source.split_whitespace().collect::<Vec<_>>().windows(2).any(|words| {
+        words[0] == "library" &&
+            words[1].split(|c: char| !c.is_alphanumeric() && c != '_').next() == Some(name)
+    })
+}
+
+fn is_script_or_test(id: &ArtifactId, config: &Config) -> bool {
+    let path = id.source.to_string_lossy();
+    path.ends_with(".s.sol") ||
+        path.ends_with(".t.sol") ||
+        id.source.starts_with(config.__root.0.join(&config.script)) ||
+        id.source.starts_with(config.__root.0.join(&config.test))
+}
+
+impl LinkedBuildData {
+    /// Analyses the contracts of the project which exceed or near the size limit, and suggests
+    /// how to make them smaller.
+    pub fn size_advice(&self, config: &Config, limit: usize) -> Vec<SizeAdvice> {
+        let sources = &self.build_data.sources;
+        let source_of = |name: &str| {
+            let id = *sources.ids_by_name.get(name)?.first()?;
+            Some((id, sources.get(id)?.0.as_str()))
+        };
+
+        let libraries = self
+            .build_data
+            .linker
+            .contracts
+            .iter()
+            .filter(|(id, contract)| {
+                contract.abi.as_ref().map_or(false, |abi| abi.functions.is_empty()) &&
+                    source_of(&id.name)
+                        .map_or(false, |(_, source)| declares_library(source, &id.name))
+            })
+            .map(|(id, _)| id.name.clone())
+            .collect::<BTreeSet<_>>();
+
+        let mut advice = Vec::new();
+        for (id, contract) in &self.highlevel_known_contracts {
+            let Some(code) = contract.deployed_bytecode.bytecode.as_ref() else { continue };
+            let Some(code) = code.object.as_bytes() else { continue };
+            if code.len() * 100 < limit * NEAR_LIMIT_PERCENT || is_script_or_test(id, config) {
+                continue
+            }
+
+            let mut remedies = Vec::new();
+            if let Some((file_id, source)) = source_of(&id.name) {
+                remedies.extend(
+                    used_libraries(source, &libraries).into_iter().map(Remedy::ExternalizeLibrary),
+                );
+
+                let functions = contract
+                    .abi
+                    .functions()
+                    .map(|function| function.name.as_str())
+                    .filter(|name| {
+                        sources.sources_by_id.iter().all(|(other, (source, _))| {
+                            *other == file_id || !source.contains(&format!(".{name}"))
+                        })
+                    })
+                    .collect::<BTreeSet<_>>();
+                if !functions.is_empty() {
+                    let functions = functions
+                        .into_iter()
+                        .take(MAX_LISTED_FUNCTIONS)
+                        .map(|name| name.to_string())
+                        .collect();
+                    remedies.push(Remedy::UnusedFunctions(functions));
+                }
+            }
+
+            let (count, bytes) = revert_string_weight(code);
+            if count > 0 {
+                remedies.push(Remedy::RevertStrings { count, bytes });
+            }
+            if !config.optimizer {
+                remedies.push(Remedy::EnableOptimizer);
+            } else if config.optimizer_runs > 200 {
+                remedies.push(Remedy::LowerOptimizerRuns(config.optimizer_runs));
+            }
+
+            advice.push(SizeAdvice { name: id.name.clone(), size: code.len(), limit, remedies });
+        }
+        advice.sort_by(|a, b| b.size.cmp(&a.size));
+        advice
+    }
+}
+
+/// Prints the size advice as part of the build report.
+pub fn print_size_advice(advice: &[SizeAdvice]) -> Result<()> {
+    if advice.is_empty() {
+        return Ok(())
+    }
+    shell::println("\n==========================")?;
+    shell::println("Contract size advisor:\n")?;
+    for contract in advice {
+        let line = format!("{}: {} of {} bytes", contract.name, contract.size, contract.limit);
+        if contract.size > contract.limit {
+            shell::println(Paint::red(format!("{line}, above the limit")))?;
+        } else {
+            shell::println(Paint::yellow(format!("{line}, near the limit")))?;
+        }
+        for remedy in &contract.remedies {
+            shell::println(format!("  - {remedy}"))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weighs_revert_strings() {
+        // PUSH1 0x80, PUSH32 "Ownable: caller is not the owner", PUSH4 "abc\0", PUSH2 0x6e6f
+        let mut code = vec![0x60, 0x80, 0x7f];
+        code.extend_from_slice(b"Ownable: caller is not the owner");
+        code.extend_from_slice(&[0x63, b'a', b'b', b'c', 0x00, 0x61, b'n', b'o']);
+        assert_eq!(revert_string_weight(&code), (1, 32));
+
+        // A truncated push at the end of the code.
+        assert_eq!(revert_string_weight(&[0x7f, b'a', b'b']), (0, 0));
+    }
+
+    #[test]
+    fn finds_inlined_libraries() {
+        let libraries = BTreeSet::from(["Math".to_string(), "SafeCast".to_string()]);
+        let source =
+            "contract Vault {\n    using SafeCast for uint256;\n    uint x = Math.max(1, 2);\n}";
+        assert_eq!(used_libraries(source, &libraries), vec!["Math", "SafeCast"]);
+        assert!(used_libraries("contract A {}", &libraries).is_empty());
+
+        assert!(declares_library("pragma solidity ^0.8.0;\nlibrary Math {", "Math"));
+        assert!(declares_library("library Math{}", "Math"));
+        assert!(!declares_library("contract Math {}", "Math"));
+    }
+}
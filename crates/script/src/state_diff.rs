@@ -0,0 +1,467 @@
+use crate::{build::LinkedBuildData, simulate::PreSimulationState, ScriptResult};
+use alloy_primitives::{keccak256, Address, B256, U256};
+use eyre::Result;
+use foundry_common::{fs, get_contract_name, shell};
+use foundry_compilers::artifacts::{Storage, StorageLayout, StorageType};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Write,
+    path::Path,
+    str::FromStr,
+};
+
+/// Static arrays longer than this only have their first elements labeled.
+const MAX_LABELED_ARRAY_ELEMENTS: u64 = 1024;
+
+/// Mappings nested deeper than this are not labeled, as every level multiplies the number of
+/// slots to hash by the number of candidate keys.
+const MAX_MAPPING_DEPTH: usize = 2;
+
+/// The storage slots changed by a simulated transaction.
+#[derive(Debug, Default)]
+pub struct StorageDiff {
+    /// The previous and the new value of the changed slots, by account.
+    pub slots: BTreeMap<Address, BTreeMap<U256, (U256, U256)>>,
+    /// Words which may be keys of the changed mapping entries: the addresses taking part in the
+    /// transaction and the arguments of its calls.
+    pub keys: BTreeSet<B256>,
+}
+
+impl StorageDiff {
+    pub fn new(result: &ScriptResult) -> Self {
+        let mut diff = Self::default();
+        for (address, account) in result.state_changeset.iter().flatten() {
+            let slots = account
+                .storage
+                .iter()
+                .filter(|(_, slot)| slot.is_changed())
+                .map(|(key, slot)| (*key, (slot.original_value(), slot.present_value())))
+                .collect::<BTreeMap<_, _>>();
+            if !slots.is_empty() {
+                diff.slots.insert(*address, slots);
+            }
+        }
+        if diff.slots.is_empty() {
+            return diff
+        }
+
+        for (_, arena) in &result.traces {
+            for node in arena.nodes() {
+                let trace = &node.trace;
+                diff.keys.insert(trace.address.into_word());
+                diff.keys.insert(trace.caller.into_word());
+                if !trace.kind.is_any_create() && trace.data.len() > 4 {
+                    diff.keys.extend(trace.data[4..].chunks_exact(32).map(B256::from_slice));
+                }
+            }
+        }
+        diff.keys.extend((0..16u64).map(|i| B256::from(U256::from(i))));
+        diff
+    }
+}
+
+/// The labels of the storage slots of a contract, derived from its storage layout.
+#[derive(Debug, Default)]
+struct SlotLabels {
+    labels: HashMap<U256, String>,
+    /// The slots holding the elements of dynamic arrays and the data of long strings and bytes.
+    regions: Vec<DataRegion>,
+}
+
+/// Consecutive slots starting at a hashed slot.
+#[derive(Debug)]
+struct DataRegion {
+    start: U256,
+    slots_per_item: U256,
+    label: String,
+    /// Whether the region holds the data of a string or bytes, instead of array elements.
+    bytes: bool,
+}
+
+impl SlotLabels {
+    /// Labels the variables of `layout` as `<contract>.<variable>`, using `keys` as the candidate
+    /// keys of the mappings.
+    fn new(contract: &str, layout: &StorageLayout, keys: &BTreeSet<B256>) -> Self {
+        let mut labels = Self::default();
+        for variable in &layout.storage {
+            let Ok(slot) = U256::from_str(&variable.slot) else { continue };
+            let label = format!("{contract}.{}", variable.label);
+            labels.add(layout, &variable.storage_type, slot, label, keys, 0);
+        }
+        labels
+    }
+
+    fn add(
+        &mut self,
+        layout: &StorageLayout,
+        ty: &str,
+        slot: U256,
+        label: String,
+        keys: &BTreeSet<B256>,
+        depth: usize,
+    ) {
+        let Some(storage_type) = layout.types.get(ty) else { return self.insert(slot, label) };
+        match storage_type.encoding.as_str() {
+            "mapping" => {
+                if depth >= MAX_MAPPING_DEPTH {
+                    return
+                }
+                let (Some(key_type), Some(value_type)) = (&storage_type.key, &storage_type.value)
+                else {
+                    return
+                };
+                for key in keys {
+                    let Some(name) = format_key(layout, key_type, key) else { continue };
+                    let mut preimage = key.to_vec();
+                    preimage.extend_from_slice(&slot.to_be_bytes::<32>());
+                    let entry = U256::from_be_bytes(keccak256(preimage).0);
+                    self.add(
+                        layout,
+                        value_type,
+                        entry,
+                        format!("{label}[{name}]"),
+                        keys,
+                        depth + 1,
+                    );
+                }
+            }
+            "dynamic_array" => {
+                let item_bytes = storage_type
+                    .other
+                    .get("base")
+                    .and_then(|base| base.as_str())
+                    .map_or(32, |base| number_of_bytes(layout.types.get(base)));
+                self.regions.push(DataRegion {
+                    start: data_slot(slot),
+                    slots_per_item: U256::from(item_bytes.div_ceil(32).max(1)),
+                    label: label.clone(),
+                    bytes: false,
+                });
+                self.insert(slot, format!("{label}.length"));
+            }
+            "bytes" => {
+                self.regions.push(DataRegion {
+                    start: data_slot(slot),
+                    slots_per_item: U256::from(1),
+                    label: label.clone(),
+                    bytes: true,
+                });
+                self.insert(slot, label);
+            }
+            _ => {
+                if let Some(members) = storage_type.other.get("members") {
+                    let members: Vec<Storage> =
+                        serde_json::from_value(members.clone()).unwrap_or_default();
+                    for member in members {
+                        let Ok(offset) = U256::from_str(&member.slot) else { continue };
+                        let label = format!("{label}.{}", member.label);
+                        self.add(layout, &member.storage_type, slot + offset, label, keys, depth);
+                    }
+                } else if let Some(base) = storage_type.other.get("base").and_then(|b| b.as_str()) {
+                    self.add_static_array(layout, storage_type, base, slot, label, keys, depth);
+                } else {
+                    let slots = number_of_bytes(Some(storage_type)).div_ceil(32).max(1);
+                    for i in 0..slots {
+                        let label = if i == 0 { label.clone() } else { format!("{label} (+{i})") };
+                        self.insert(slot + U256::from(i), label);
+                    }
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_static_array(
+        &mut self,
+        layout: &StorageLayout,
+        storage_type: &StorageType,
+        base: &str,
+        slot: U256,
+        label: String,
+        keys: &BTreeSet<B256>,
+        depth: usize,
+    ) {
+        let total_bytes = number_of_bytes(Some(storage_type));
+        let item_bytes = number_of_bytes(layout.types.get(base)).max(1);
+        if item_bytes >= 32 {
+            let slots_per_item = item_bytes.div_ceil(32);
+            let len = total_bytes / (slots_per_item * 32);
+            for i in 0..len.min(MAX_LABELED_ARRAY_ELEMENTS) {
+                let item = slot + U256::from(i * slots_per_item);
+                self.add(layout, base, item, format!("{label}[{i}]"), keys, depth);
+            }
+        } else {
+            // small items are packed, starting a new slot when the next one doesn't fit
+            let items_per_slot = 32 / item_bytes;
+            let slots = total_bytes / 32;
+            for i in 0..slots.min(MAX_LABELED_ARRAY_ELEMENTS) {
+                let first = i * items_per_slot;
+                let last = first + items_per_slot - 1;
+                self.insert(slot + U256::from(i), format!("{label}[{first}..={last}]"));
+            }
+        }
+    }
+
+    /// Labels `slot`, which packed variables share.
+    fn insert(&mut self, slot: U256, label: String) {
+        self.labels
+            .entry(slot)
+            .and_modify(|labels| {
+                labels.push_str(", ");
+                labels.push_str(&label);
+            })
+            .or_insert(label);
+    }
+
+    fn get(&self, slot: U256) -> Option<String> {
+        if let Some(label) = self.labels.get(&slot) {
+            return Some(label.clone())
+        }
+        // the regions start at a hash, so any slot shortly after one belongs to it
+        let max_offset = U256::from(u32::MAX);
+        self.regions.iter().find_map(|region| {
+            let offset = slot.checked_sub(region.start).filter(|offset| *offset < max_offset)?;
+            Some(if region.bytes {
+                format!("{} (data {offset})", region.label)
+            } else {
+                let index = offset / region.slots_per_item;
+                match offset % region.slots_per_item {
+                    rem if rem.is_zero() => format!("{}[{index}]", region.label),
+                    rem => format!("{}[{index}] (+{rem})", region.label),
+                }
+            })
+        })
+    }
+}
+
+/// The slot where the data of the dynamic array or long bytes at `slot` starts.
+fn data_slot(slot: U256) -> U256 {
+    U256::from_be_bytes(keccak256(slot.to_be_bytes::<32>()).0)
+}
+
+fn number_of_bytes(storage_type: Option<&StorageType>) -> u64 {
+    storage_type.and_then(|ty| ty.number_of_bytes.parse().ok()).unwrap_or(32)
+}
+
+/// Formats `key` as a key of type `key_type`, or returns `None` if it can't be one.
+fn format_key(layout: &StorageLayout, key_type: &str, key: &B256) -> Option<String> {
+    let size = number_of_bytes(layout.types.get(key_type)) as usize;
+    let value = U256::from_be_bytes(key.0);
+    let fits = size >= 32 || value.bit_len() <= size * 8;
+    if key_type.starts_with("t_address") || key_type.starts_with("t_contract") {
+        fits.then(|| Address::from_word(*key).to_checksum(None))
+    } else if key_type == "t_bool" {
+        (value <= U256::from(1)).then(|| (!value.is_zero()).to_string())
+    } else if key_type.starts_with("t_uint") || key_type.starts_with("t_enum") {
+        fits.then(|| value.to_string())
+    } else if key_type.starts_with("t_string") || key_type.starts_with("t_bytes_") {
+        // keys of dynamic types are hashed as a whole, and can't be recovered from words
+        None
+    } else {
+        Some(key.to_string())
+    }
+}
+
+/// The storage layout of an artifact, which is only present if it was requested from the
+/// compiler.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LayoutArtifact {
+    storage_layout: Option<StorageLayout>,
+}
+
+/// A storage slot changed by a simulated transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlotChange {
+    /// The index of the transaction.
+    pub transaction: usize,
+    pub address: Address,
+    /// The name or label of the contract, if known.
+    pub contract: Option<String>,
+    /// The state variable of the slot, or the slot itself if its contract has no storage layout.
+    pub label: String,
+    pub previous: U256,
+    pub present: U256,
+}
+
+impl PreSimulationState {
+    /// Labels the storage slots changed by each simulated transaction with the state variables
+    /// of the contracts they belong to.
+    pub fn label_state_diff(&self, diffs: &[(usize, StorageDiff)]) -> Vec<SlotChange> {
+        let decoder = &self.execution_artifacts.decoder;
+        let mut layouts = HashMap::new();
+        let mut changes = Vec::new();
+
+        for (i, diff) in diffs {
+            for (address, slots) in &diff.slots {
+                let contract = decoder.contracts.get(address);
+                let name = contract
+                    .map(|id| get_contract_name(id))
+                    .or_else(|| decoder.labels.get(address).map(String::as_str));
+
+                let labels = contract.and_then(|id| {
+                    let layout = layouts.entry(id.clone()).or_insert_with(|| {
+                        storage_layout(&self.script_config.config.__root.0, &self.build_data, id)
+                    });
+                    let layout = layout.as_ref()?;
+                    Some(SlotLabels::new(get_contract_name(id), layout, &diff.keys))
+                });
+                for (slot, (previous, present)) in slots {
+                    let label = labels
+                        .as_ref()
+                        .and_then(|labels| labels.get(*slot))
+                        .unwrap_or_else(|| format!("0x{slot:x}"));
+                    changes.push(SlotChange {
+                        transaction: *i,
+                        address: *address,
+                        contract: name.map(str::to_string),
+                        label,
+                        previous: *previous,
+                        present: *present,
+                    });
+                }
+            }
+        }
+        changes
+    }
+
+    /// Prints the storage slots changed by each simulated transaction.
+    pub fn show_state_diff(&self, changes: &[SlotChange]) -> Result<()> {
+        let mut output = String::new();
+        let mut current = None;
+        for change in changes {
+            if current.map_or(true, |(i, _)| i != change.transaction) {
+                let _ = writeln!(output, "Transaction {}:", change.transaction);
+            }
+            if current != Some((change.transaction, change.address)) {
+                match &change.contract {
+                    Some(name) => {
+                        let _ = writeln!(output, "  {name} ({}):", change.address);
+                    }
+                    None => {
+                        let _ = writeln!(output, "  {}:", change.address);
+                    }
+                }
+            }
+            current = Some((change.transaction, change.address));
+            let _ = writeln!(
+                output,
+                "    {}: 0x{:x} → 0x{:x}",
+                change.label, change.previous, change.present
+            );
+        }
+
+        if output.is_empty() {
+            return Ok(())
+        }
+        shell::println("\n==========================")?;
+        shell::println("Simulated State Diff:\n")?;
+        shell::println(output)?;
+        Ok(())
+    }
+}
+
+/// Reads the storage layout of the contract with the identifier `id` from its artifact.
+pub fn storage_layout(
+    root: &Path,
+    build_data: &LinkedBuildData,
+    id: &str,
+) -> Option<StorageLayout> {
+    let (artifact, _) = build_data
+        .highlevel_known_contracts
+        .iter()
+        .find(|(artifact, _)| artifact.identifier() == id)?;
+    let path = root.join(&artifact.path);
+    match fs::read_json_file::<LayoutArtifact>(&path) {
+        Ok(artifact) => artifact.storage_layout.filter(|layout| !layout.storage.is_empty()),
+        Err(err) => {
+            warn!(%err, ?path, "failed to read the storage layout");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout() -> StorageLayout {
+        let variable = |label: &str, offset: u64, slot: &str, ty: &str| {
+            serde_json::json!({
+                "astId": 1,
+                "contract": "src/Vault.sol:Vault",
+                "label": label,
+                "offset": offset,
+                "slot": slot,
+                "type": ty,
+            })
+        };
+        let mapping = "t_mapping(t_address,t_uint256)";
+        let array = "t_array(t_uint256)dyn_storage";
+        serde_json::from_value(serde_json::json!({
+            "storage": [
+                variable("owner", 0, "0", "t_address"),
+                variable("paused", 20, "0", "t_bool"),
+                variable("totalAssets", 0, "1", "t_uint256"),
+                variable("owners", 0, "2", mapping),
+                variable("queue", 0, "3", array),
+            ],
+            "types": {
+                "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+                "t_bool": { "encoding": "inplace", "label": "bool", "numberOfBytes": "1" },
+                "t_uint256": { "encoding": "inplace", "label": "uint256", "numberOfBytes": "32" },
+                mapping: {
+                    "encoding": "mapping",
+                    "key": "t_address",
+                    "label": "mapping(address => uint256)",
+                    "numberOfBytes": "32",
+                    "value": "t_uint256",
+                },
+                array: {
+                    "base": "t_uint256",
+                    "encoding": "dynamic_array",
+                    "label": "uint256[]",
+                    "numberOfBytes": "32",
+                },
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn can_label_storage_slots() {
+        let owner = Address::repeat_byte(0xab);
+        let keys = BTreeSet::from([owner.into_word(), B256::from(U256::from(7))]);
+        let labels = SlotLabels::new("Vault", &layout(), &keys);
+
+        assert_eq!(labels.get(U256::ZERO).unwrap(), "Vault.owner, Vault.paused");
+        assert_eq!(labels.get(U256::from(1)).unwrap(), "Vault.totalAssets");
+
+        let mut preimage = owner.into_word().to_vec();
+        preimage.extend_from_slice(&U256::from(2).to_be_bytes::<32>());
+        let entry = U256::from_be_bytes(keccak256(preimage).0);
+        assert_eq!(
+            labels.get(entry).unwrap(),
+            format!("Vault.owners[{}]", owner.to_checksum(None))
+        );
+
+        assert_eq!(labels.get(U256::from(3)).unwrap(), "Vault.queue.length");
+        let item = data_slot(U256::from(3)) + U256::from(5);
+        assert_eq!(labels.get(item).unwrap(), "Vault.queue[5]");
+
+        assert!(labels.get(U256::from(4)).is_none());
+    }
+
+    #[test]
+    fn only_formats_keys_of_their_type() {
+        let layout = layout();
+        let address = Address::repeat_byte(0xab).into_word();
+        let word = B256::repeat_byte(0xab);
+        assert!(format_key(&layout, "t_address", &address).is_some());
+        assert!(format_key(&layout, "t_address", &word).is_none());
+        assert_eq!(format_key(&layout, "t_bool", &B256::from(U256::from(1))).unwrap(), "true");
+        assert!(format_key(&layout, "t_bool", &address).is_none());
+        assert!(format_key(&layout, "t_string_memory_ptr", &word).is_none());
+    }
+}
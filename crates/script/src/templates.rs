@@ -0,0 +1,346 @@
+use crate::{build::LinkedBuildData, ScriptArgs};
+use alloy_primitives::{keccak256, Address, B256};
+use eyre::Result;
+use foundry_common::shell;
+use foundry_evm::{
+    constants::DEFAULT_CREATE2_DEPLOYER,
+    inspectors::cheatcodes::{BroadcastableTransactions, ScriptParams},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+
+/// Start of the placeholders of the addresses of the contracts deployed by the script.
+const PLACEHOLDER_START: &str = "${deployments.";
+
+/// Start of the placeholders of the salts mined for the CREATE2 deployments of the script with
+/// `--vanity-prefix`.
+const SALT_PLACEHOLDER_START: &str = "${salts.";
+
+/// The most executions of the script while resolving the templates.
+///
+/// Each execution resolves one more level of CREATE2 deployments whose init code references
+/// other CREATE2 deployments, the addresses of CREATE deployments are known after the first.
+pub const MAX_TEMPLATE_PASSES: usize = 5;
+
+/// A script argument or parameter.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TemplateInput {
+    Argument(usize),
+    Param(String),
+}
+
+impl fmt::Display for TemplateInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Argument(i) => write!(f, "argument {i}"),
+            Self::Param(name) => write!(f, "param {name}"),
+        }
+    }
+}
+
+/// A script argument or parameter which references the addresses of contracts deployed by the
+/// script, e.g. `${deployments.Token}`, or their mined salts, e.g. `${salts.Token}`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentTemplate {
+    pub input: TemplateInput,
+    pub template: String,
+    /// The value the template resolved to in the last execution of the script.
+    pub value: String,
+}
+
+/// Returns the names of the deployments referenced by the placeholders of `template` starting
+/// with `placeholder`.
+fn referenced_names<'a>(template: &'a str, placeholder: &str) -> Result<Vec<&'a str>> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find(placeholder) {
+        rest = &rest[start + placeholder.len()..];
+        let end = rest
+            .find('}')
+            .ok_or_else(|| eyre::eyre!("unterminated placeholder in `{template}`"))?;
+        let name = &rest[..end];
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            eyre::bail!("invalid contract name `{name}` in `{template}`")
+        }
+        names.push(name);
+        rest = &rest[end + 1..];
+    }
+    Ok(names)
+}
+
+/// Replaces the placeholders of `template` starting with `placeholder` with the values returned
+/// by `value_of`.
+fn substitute<T: fmt::Display>(
+    template: &str,
+    placeholder: &str,
+    value_of: impl Fn(&str) -> Result<T>,
+) -> Result<String> {
+    let mut value = template.to_string();
+    for name in referenced_names(template, placeholder)? {
+        let substitute = value_of(name)?;
+        value = value.replace(&format!("{placeholder}{name}}}"), &substitute.to_string());
+    }
+    Ok(value)
+}
+
+/// The address substituted before the deployment is predicted. It's arbitrary, but not zero so
+/// that constructors checking against the zero address don't revert.
+fn provisional_address(name: &str) -> Address {
+    Address::from_word(keccak256(format!("deployments.{name}")))
+}
+
+/// The salt substituted before it's mined.
+fn provisional_salt(name: &str) -> B256 {
+    keccak256(format!("salts.{name}"))
+}
+
+/// A contract created by a transaction of the script.
+#[derive(Clone, Debug)]
+pub struct Deployment {
+    pub name: String,
+    pub address: Address,
+    /// The salt and the init code hash, if it's deployed through the CREATE2 deployer.
+    pub create2: Option<(B256, B256)>,
+}
+
+/// Returns the contracts created by the transactions of the script.
+///
+/// Only contracts created by the transactions themselves are found, either directly or through
+/// the CREATE2 deployer, not those created by the contracts they call.
+pub fn find_deployments(
+    transactions: Option<&BroadcastableTransactions>,
+    build_data: &LinkedBuildData,
+) -> Vec<Deployment> {
+    let mut deployments = Vec::new();
+    for tx in transactions.into_iter().flatten() {
+        let Some(input) = tx.transaction.input.clone().into_input() else { continue };
+        let (address, init_code, create2) = match tx.transaction.to {
+            None => {
+                let (Some(from), Some(nonce)) = (tx.transaction.from, tx.transaction.nonce) else {
+                    continue
+                };
+                (from.create(nonce.to::<u64>()), &input[..], None)
+            }
+            Some(to) if to == DEFAULT_CREATE2_DEPLOYER && input.len() >= 32 => {
+                let (salt, init_code) = input.split_at(32);
+                let salt = B256::from_slice(salt);
+                let init_code_hash = keccak256(init_code);
+                let address = DEFAULT_CREATE2_DEPLOYER.create2(salt, init_code_hash);
+                (address, init_code, Some((salt, init_code_hash)))
+            }
+            Some(_) => continue,
+        };
+        let name = build_data.highlevel_known_contracts.iter().find_map(|(id, contract)| {
+            let code = contract.bytecode.bytes()?;
+            (!code.is_empty() && init_code.starts_with(code)).then(|| id.name.clone())
+        });
+        if let Some(name) = name {
+            deployments.push(Deployment { name, address, create2 });
+        }
+    }
+    deployments
+}
+
+/// Returns the addresses of the contracts created by the transactions of the script, by name.
+pub fn predict_deployments(
+    transactions: Option<&BroadcastableTransactions>,
+    build_data: &LinkedBuildData,
+) -> BTreeMap<String, Vec<Address>> {
+    let mut deployments = BTreeMap::<String, Vec<Address>>::new();
+    for deployment in find_deployments(transactions, build_data) {
+        deployments.entry(deployment.name).or_default().push(deployment.address);
+    }
+    deployments
+}
+
+impl ScriptArgs {
+    /// Finds the script arguments and parameters which reference deployments of the script, and
+    /// substitutes provisional addresses and salts for the first execution.
+    pub(crate) fn prepare_deployment_templates(
+        &mut self,
+        params: Option<&ScriptParams>,
+    ) -> Result<Vec<DeploymentTemplate>> {
+        let mut inputs = self
+            .args
+            .iter_mut()
+            .enumerate()
+            .map(|(i, arg)| (TemplateInput::Argument(i), arg))
+            .collect::<Vec<_>>();
+        let mut params = params.map(|params| params.inner.lock());
+        if let Some(params) = &mut params {
+            inputs.extend(
+                params
+                    .values
+                    .iter_mut()
+                    .map(|(name, value)| (TemplateInput::Param(name.clone()), value)),
+            );
+        }
+
+        let mut templates = Vec::new();
+        let mut salted = Vec::new();
+        for (input, value) in inputs {
+            if !value.contains(PLACEHOLDER_START) && !value.contains(SALT_PLACEHOLDER_START) {
+                continue
+            }
+            let template = value.clone();
+            let salt_of = |name: &str| {
+                if !self.vanity_contracts.iter().any(|contract| contract == name) {
+                    eyre::bail!("the salt of {name} isn't mined, select it with --vanity-contract")
+                }
+                Ok(provisional_salt(name))
+            };
+            *value = substitute(&template, PLACEHOLDER_START, |name| Ok(provisional_address(name)))
+                .and_then(|value| substitute(&value, SALT_PLACEHOLDER_START, salt_of))
+                .map_err(|err| eyre::eyre!("{input}: {err}"))?;
+            salted.extend(
+                referenced_names(&template, SALT_PLACEHOLDER_START)?.into_iter().map(String::from),
+            );
+            templates.push(DeploymentTemplate { input, template, value: value.clone() });
+        }
+        if let Some(contract) =
+            self.vanity_contracts.iter().find(|contract| !salted.contains(contract))
+        {
+            eyre::bail!(
+                "The script must read the salt of {contract} from a `${{salts.{contract}}}` \
+                 argument or parameter to deploy it at a vanity address"
+            )
+        }
+        Ok(templates)
+    }
+}
+
+/// Resolves the templates with the addresses of the deployments of the last execution and the
+/// salts mined for the next one, and writes them to the script arguments and parameters.
+///
+/// Returns whether any of them changed, in which case the script has to be executed again.
+pub fn resolve_templates(
+    templates: &mut [DeploymentTemplate],
+    deployments: &BTreeMap<String, Vec<Address>>,
+    salts: &BTreeMap<String, B256>,
+    args: &mut [String],
+    params: Option<&ScriptParams>,
+) -> Result<bool> {
+    let mut changed = false;
+    for template in templates {
+        let value = substitute(&template.template, PLACEHOLDER_START, |name| {
+            match deployments.get(name) {
+                Some(addresses) if addresses.len() == 1 => Ok(addresses[0]),
+                Some(_) => eyre::bail!("{name} is deployed more than once by the script"),
+                None => eyre::bail!("the script doesn't deploy a contract named {name}"),
+            }
+        })
+        .and_then(|value| {
+            substitute(&value, SALT_PLACEHOLDER_START, |name| {
+                salts.get(name).copied().ok_or_else(|| eyre::eyre!("no salt was mined for {name}"))
+            })
+        })
+        .map_err(|err| eyre::eyre!("{}: {err}", template.input))?;
+        if value == template.value {
+            continue
+        }
+        match &template.input {
+            TemplateInput::Argument(i) => args[*i] = value.clone(),
+            TemplateInput::Param(name) => {
+                if let Some(params) = params {
+                    params.inner.lock().values.insert(name.clone(), value.clone());
+                }
+            }
+        }
+        template.value = value;
+        changed = true;
+    }
+    Ok(changed)
+}
+
+/// Prints how the templates were resolved.
+pub fn print_deployment_templates(templates: &[DeploymentTemplate]) -> Result<()> {
+    if templates.is_empty() {
+        return Ok(())
+    }
+    shell::println("\nDeployment templates:")?;
+    for template in templates {
+        shell::println(format!(
+            "  {}: {} = {}",
+            template.input, template.template, template.value
+        ))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    const TOKEN: Address = address!("5FbDB2315678afecb367f032d93F642f64180aa3");
+    const VAULT: Address = address!("e7f1725E7734CE288F8367e1Bb143E90bb3F0512");
+
+    #[test]
+    fn substitutes_placeholders() {
+        let template = "${deployments.Token},${deployments.Vault}";
+        assert_eq!(referenced_names(template, PLACEHOLDER_START).unwrap(), ["Token", "Vault"]);
+        let value = substitute(template, PLACEHOLDER_START, |name| {
+            Ok(if name == "Token" { TOKEN } else { VAULT })
+        })
+        .unwrap();
+        assert_eq!(value, format!("{TOKEN},{VAULT}"));
+
+        assert!(referenced_names("${deployments.Token", PLACEHOLDER_START).is_err());
+        assert!(referenced_names("${deployments.}", PLACEHOLDER_START).is_err());
+        assert!(referenced_names("${env.Token}", PLACEHOLDER_START).unwrap().is_empty());
+        assert!(referenced_names(template, SALT_PLACEHOLDER_START).unwrap().is_empty());
+        assert_ne!(provisional_address("Token"), Address::ZERO);
+    }
+
+    #[test]
+    fn resolves_templates() {
+        let mut args = vec!["${deployments.Token}".to_string(), "1".to_string()];
+        let mut script = ScriptArgs { args: args.clone(), ..Default::default() };
+        let mut templates = script.prepare_deployment_templates(None).unwrap();
+        assert_eq!(templates.len(), 1);
+        assert_eq!(script.args[0], provisional_address("Token").to_string());
+
+        let deployments = BTreeMap::from([("Token".to_string(), vec![TOKEN])]);
+        let salts = BTreeMap::new();
+        assert!(resolve_templates(&mut templates, &deployments, &salts, &mut args, None).unwrap());
+        assert_eq!(args[0], TOKEN.to_string());
+        // Converged once the addresses stop changing.
+        assert!(!resolve_templates(&mut templates, &deployments, &salts, &mut args, None).unwrap());
+
+        let deployments = BTreeMap::from([("Token".to_string(), vec![TOKEN, VAULT])]);
+        assert!(resolve_templates(&mut templates, &deployments, &salts, &mut args, None).is_err());
+        assert!(
+            resolve_templates(&mut templates, &BTreeMap::new(), &salts, &mut args, None).is_err()
+        );
+    }
+
+    #[test]
+    fn resolves_salt_templates() {
+        let mut args = vec!["${salts.Token}".to_string()];
+        let mut script = ScriptArgs { args: args.clone(), ..Default::default() };
+        // The salts of contracts which aren't selected aren't mined.
+        assert!(script.prepare_deployment_templates(None).is_err());
+
+        let mut script = ScriptArgs {
+            args: args.clone(),
+            vanity_contracts: vec!["Token".to_string()],
+            ..Default::default()
+        };
+        let mut templates = script.prepare_deployment_templates(None).unwrap();
+        assert_eq!(script.args[0], provisional_salt("Token").to_string());
+
+        let salt = B256::with_last_byte(7);
+        let salts = BTreeMap::from([("Token".to_string(), salt)]);
+        assert!(
+            resolve_templates(&mut templates, &BTreeMap::new(), &salts, &mut args, None).unwrap()
+        );
+        assert_eq!(args[0], salt.to_string());
+
+        // Selected contracts must read their salts.
+        let mut script =
+            ScriptArgs { vanity_contracts: vec!["Token".to_string()], ..Default::default() };
+        assert!(script.prepare_deployment_templates(None).is_err());
+    }
+}
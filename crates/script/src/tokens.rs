@@ -0,0 +1,180 @@
+use alloy_primitives::{utils::ParseUnits, Address, U256};
+use eyre::{Result, WrapErr};
+use foundry_common::shell;
+use foundry_config::{
+    figment::{
+        providers::{Format, Toml},
+        Figment,
+    },
+    Chain,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path, str::FromStr};
+
+/// File name of the token list, looked up in the project root.
+pub const TOKENS_FILE_NAME: &str = "tokens.toml";
+
+/// A token on one chain.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Token {
+    pub address: Address,
+    pub decimals: u8,
+}
+
+/// The tokens which amounts can be expressed in, e.g. `"1000 USDC"`, by symbol and chain.
+///
+/// ```toml
+/// [USDC]
+/// mainnet = { address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", decimals = 6 }
+/// 196 = { address = "0x74b7F16337b8972027F6196A17a631aC6dE26d22", decimals = 6 }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TokenList(BTreeMap<String, BTreeMap<u64, Token>>);
+
+/// An amount of tokens which was resolved to its raw value.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenAmount {
+    /// Where the amount was given, e.g. `param fees.max` or `argument 0`.
+    pub source: String,
+    pub input: String,
+    pub symbol: String,
+    pub chain: u64,
+    pub token: Address,
+    pub decimals: u8,
+    pub amount: U256,
+}
+
+impl TokenList {
+    /// Loads the token list from `path`, or from `tokens.toml` in the project root if it exists.
+    pub fn load(root: &Path, path: Option<&Path>) -> Result<Self> {
+        if let Some(path) = path {
+            if !path.exists() {
+                eyre::bail!("Token list {} does not exist", path.display())
+            }
+        }
+        let path = path.map(Path::to_path_buf).unwrap_or_else(|| root.join(TOKENS_FILE_NAME));
+        if !path.exists() {
+            return Ok(Self::default())
+        }
+
+        let file: BTreeMap<String, BTreeMap<String, Token>> = Figment::from(Toml::file(&path))
+            .extract()
+            .wrap_err_with(|| format!("Failed to load token list {}", path.display()))?;
+        let mut tokens = BTreeMap::new();
+        for (symbol, chains) in file {
+            let mut by_chain = BTreeMap::new();
+            for (chain, token) in chains {
+                let chain = Chain::from_str(&chain)
+                    .map_err(|_| eyre::eyre!("unknown chain {chain:?} for token {symbol}"))?;
+                by_chain.insert(chain.id(), token);
+            }
+            tokens.insert(symbol, by_chain);
+        }
+        Ok(Self(tokens))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Resolves `input` to the raw amount of tokens if it's an amount of a token of the list,
+    /// e.g. `1000 USDC` or `0.5 WETH`, and returns `None` otherwise.
+    pub fn resolve(&self, source: &str, input: &str, chain: u64) -> Result<Option<TokenAmount>> {
+        let mut parts = input.split_whitespace();
+        let (Some(value), Some(symbol), None) = (parts.next(), parts.next(), parts.next()) else {
+            return Ok(None)
+        };
+        let Some(chains) = self.0.get(symbol) else { return Ok(None) };
+        let Some(token) = chains.get(&chain) else {
+            eyre::bail!("{source}: {symbol} isn't in the token list for chain {chain}")
+        };
+
+        let value = value.replace('_', "");
+        let fraction = value.split_once('.').map_or(0, |(_, fraction)| fraction.len());
+        let amount = match ParseUnits::parse_units(&value, token.decimals) {
+            Ok(ParseUnits::U256(amount)) if fraction <= token.decimals as usize => amount,
+            _ => eyre::bail!(
+                "{source}: invalid amount {input:?}, {symbol} has {} decimals",
+                token.decimals
+            ),
+        };
+        Ok(Some(TokenAmount {
+            source: source.to_string(),
+            input: input.to_string(),
+            symbol: symbol.to_string(),
+            chain,
+            token: token.address,
+            decimals: token.decimals,
+            amount,
+        }))
+    }
+}
+
+/// Prints how the token amounts were resolved.
+pub fn print_token_amounts(amounts: &[TokenAmount]) -> Result<()> {
+    if amounts.is_empty() {
+        return Ok(())
+    }
+    shell::println("\nToken amounts:")?;
+    for amount in amounts {
+        shell::println(format!(
+            "  {}: {} = {} ({} {} on chain {}, {} decimals)",
+            amount.source,
+            amount.input,
+            amount.amount,
+            amount.symbol,
+            amount.token,
+            amount.chain,
+            amount.decimals
+        ))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    fn token_list() -> TokenList {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(
+            root.path().join(TOKENS_FILE_NAME),
+            r#"
+            [USDC]
+            mainnet = { address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", decimals = 6 }
+            [WETH]
+            1 = { address = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", decimals = 18 }
+            "#,
+        )
+        .unwrap();
+        TokenList::load(root.path(), None).unwrap()
+    }
+
+    #[test]
+    fn resolves_token_amounts() {
+        let tokens = token_list();
+        let amount = tokens.resolve("argument 0", "1_000 USDC", 1).unwrap().unwrap();
+        assert_eq!(amount.amount, U256::from(1_000_000_000u64));
+        assert_eq!(amount.token, address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"));
+        assert_eq!(
+            tokens.resolve("param", "0.5 WETH", 1).unwrap().unwrap().amount,
+            U256::from(500_000_000_000_000_000u64)
+        );
+
+        assert_eq!(tokens.resolve("param", "1000", 1).unwrap(), None);
+        assert_eq!(tokens.resolve("param", "hello world", 1).unwrap(), None);
+        assert!(tokens.resolve("param", "1000 USDC", 10).is_err());
+        assert!(tokens.resolve("param", "0.0000001 USDC", 1).is_err());
+        assert!(tokens.resolve("param", "-1 USDC", 1).is_err());
+    }
+
+    #[test]
+    fn empty_without_token_list() {
+        let root = tempfile::tempdir().unwrap();
+        assert!(TokenList::load(root.path(), None).unwrap().is_empty());
+        assert!(TokenList::load(root.path(), Some(&root.path().join("missing.toml"))).is_err());
+    }
+}
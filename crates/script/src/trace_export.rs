@@ -0,0 +1,73 @@
+use clap::ValueEnum;
+use eyre::Result;
+use foundry_cli::utils::now;
+use foundry_common::fs;
+use foundry_config::Config;
+use foundry_evm::traces::{
+    export::{export_trace_arena, ExportedCall, Speedscope},
+    CallTraceArena, CallTraceDecoder,
+};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// The format of the traces printed by the script.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum TraceFormat {
+    /// Trace trees printed to the terminal.
+    #[default]
+    Pretty,
+    /// Decoded call trees written to a JSON file.
+    Json,
+    /// Flamegraphs of the gas usage written to a speedscope file.
+    Flamegraph,
+}
+
+/// A decoded call tree, named after the trace it was exported from.
+#[derive(Serialize)]
+struct NamedCall {
+    name: String,
+    call: ExportedCall,
+}
+
+/// Writes the traces in `format` to the `traces` directory of the project cache, and returns the
+/// path of the file.
+///
+/// The file is named after the script `contract` and the `stage` of the traces, e.g.
+/// `Deploy-simulation-1700000000.json`.
+pub async fn export_traces<'a>(
+    traces: impl IntoIterator<Item = (String, &'a CallTraceArena)>,
+    decoder: &CallTraceDecoder,
+    format: TraceFormat,
+    config: &Config,
+    contract: &str,
+    stage: &str,
+) -> Result<PathBuf> {
+    let mut calls = Vec::new();
+    for (name, arena) in traces {
+        if let Some(call) = export_trace_arena(arena, decoder).await {
+            calls.push(NamedCall { name, call });
+        }
+    }
+
+    let dir = config.cache_path.join("traces");
+    fs::create_dir_all(&dir)?;
+    let file_name = format!("{contract}-{stage}-{}", now().as_secs());
+    let path = match format {
+        TraceFormat::Pretty => eyre::bail!("pretty traces are printed, not exported"),
+        TraceFormat::Json => {
+            let path = dir.join(format!("{file_name}.json"));
+            fs::write_json_file(&path, &calls)?;
+            path
+        }
+        TraceFormat::Flamegraph => {
+            let mut speedscope = Speedscope::new(format!("{contract} {stage}"));
+            for NamedCall { name, call } in &calls {
+                speedscope.add_profile(name.clone(), call);
+            }
+            let path = dir.join(format!("{file_name}.speedscope.json"));
+            fs::write_json_file(&path, &speedscope)?;
+            path
+        }
+    };
+    Ok(path)
+}
@@ -209,7 +209,7 @@ impl TransactionWithMetadata {
             info.abi.functions().find(|function| function.selector() == selector)
         } else {
             // This CALL is made to an external contract; try to decode it from the given decoder.
-            decoder.functions.get(selector).and_then(|v| v.first())
+            decoder.function(&target, selector).and_then(|v| v.first())
         };
         if let Some(function) = function {
             self.function = Some(function.signature());
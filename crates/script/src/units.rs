@@ -0,0 +1,224 @@
+use alloy_primitives::{hex, keccak256};
+use eyre::Result;
+use foundry_common::{compile::SkipBuildFilters, fs};
+use foundry_compilers::{FileFilter, Graph, Project, ProjectCompileOutput};
+use parking_lot::Mutex;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The name of the directory of the compiler caches of the units, in the cache directory of the
+/// project.
+const UNITS_CACHE_DIR_NAME: &str = "script-units";
+
+/// Compiles the input files of `project` which match `filter` in concurrent solc runs, one per
+/// independent compilation unit, running at most `jobs` of them at a time.
+///
+/// Each unit is compiled by a project returned by `unit_project`, with its own compiler cache, so
+/// that only the units with changed files are recompiled. The compiler cache of `project` is not
+/// updated.
+///
+/// Returns `None` if the files can't be compiled concurrently, e.g. if they form a single unit, in
+/// which case the project should be compiled in a single run.
+pub fn compile_units(
+    project: &Project,
+    graph: &Graph,
+    filter: Option<&SkipBuildFilters>,
+    jobs: usize,
+    unit_project: impl Fn() -> Result<Project> + Sync,
+) -> Result<Option<Vec<ProjectCompileOutput>>> {
+    if jobs < 2 {
+        return Ok(None)
+    }
+    let files = project
+        .paths
+        .input_files()
+        .into_iter()
+        .filter(|file| filter.map_or(true, |filter| filter.is_match(file)));
+    let units = compilation_units(graph, files);
+    if units.len() < 2 || has_name_conflicts(&units) {
+        return Ok(None)
+    }
+
+    let cache_dir = project.paths.cache.with_file_name(UNITS_CACHE_DIR_NAME);
+    fs::create_dir_all(&cache_dir)?;
+    let caches = units
+        .iter()
+        .map(|unit| cache_dir.join(format!("{}.json", unit_id(unit))))
+        .collect::<Vec<_>>();
+    trace!(target: "script", units=units.len(), jobs, "compiling independent units");
+
+    let next = AtomicUsize::new(0);
+    let outputs = Mutex::new(Vec::with_capacity(units.len()));
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(units.len()) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(unit) = units.get(index) else { break };
+                let output = compile_unit(&unit_project, unit, &caches[index]);
+                outputs.lock().push((index, output));
+            });
+        }
+    });
+
+    let mut outputs = outputs.into_inner();
+    outputs.sort_by_key(|(index, _)| *index);
+    let outputs = outputs.into_iter().map(|(_, output)| output).collect::<Result<Vec<_>>>()?;
+
+    // the units change as imports are added and removed, drop the caches of the previous ones
+    for entry in std::fs::read_dir(&cache_dir)?.flatten() {
+        if !caches.contains(&entry.path()) {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+
+    Ok(Some(outputs))
+}
+
+/// Compiles the files of `unit`, using the compiler cache at `cache`.
+fn compile_unit(
+    unit_project: impl Fn() -> Result<Project>,
+    unit: &BTreeSet<PathBuf>,
+    cache: &Path,
+) -> Result<ProjectCompileOutput> {
+    let mut project = unit_project()?;
+    project.paths.cache = cache.to_path_buf();
+    let output = project.compile_files(unit.iter().cloned())?;
+    if output.has_compiler_errors() {
+        eyre::bail!("{output}")
+    }
+    Ok(output)
+}
+
+/// Splits `files` into independent compilation units: the sets of files which don't import, and
+/// aren't imported by, any file of the other sets, together with their imports.
+///
+/// Every file belongs to exactly one unit. The units are sorted.
+pub fn compilation_units(
+    graph: &Graph,
+    files: impl IntoIterator<Item = PathBuf>,
+) -> Vec<BTreeSet<PathBuf>> {
+    let mut sets = DisjointSets::default();
+    for file in files {
+        let imports = graph.imports(&file).into_iter().cloned().collect::<Vec<_>>();
+        let file = sets.insert(file);
+        for import in imports {
+            let import = sets.insert(import);
+            sets.union(file, import);
+        }
+    }
+    sets.into_sets()
+}
+
+/// Whether files of different units have the same name. Their artifacts would be written to the
+/// same path, as the compiler only tells them apart within a run.
+fn has_name_conflicts(units: &[BTreeSet<PathBuf>]) -> bool {
+    let mut names = HashMap::new();
+    for (index, unit) in units.iter().enumerate() {
+        for name in unit.iter().filter_map(|file| file.file_name()) {
+            if *names.entry(name).or_insert(index) != index {
+                return true
+            }
+        }
+    }
+    false
+}
+
+/// Returns an identifier of `unit` which is stable as long as its files don't change.
+fn unit_id(unit: &BTreeSet<PathBuf>) -> String {
+    let mut input = Vec::new();
+    for file in unit {
+        input.extend_from_slice(file.to_string_lossy().as_bytes());
+        input.push(b'\n');
+    }
+    hex::encode(&keccak256(input)[..8])
+}
+
+/// Disjoint sets of paths, see <https://en.wikipedia.org/wiki/Disjoint-set_data_structure>.
+#[derive(Default)]
+struct DisjointSets {
+    ids: HashMap<PathBuf, usize>,
+    paths: Vec<PathBuf>,
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    /// Inserts `path` in a set of its own if it's not in any set yet, and returns its id.
+    fn insert(&mut self, path: PathBuf) -> usize {
+        if let Some(id) = self.ids.get(&path) {
+            return *id
+        }
+        let id = self.paths.len();
+        self.ids.insert(path.clone(), id);
+        self.paths.push(path);
+        self.parents.push(id);
+        id
+    }
+
+    /// Returns the id of the representative of the set of `id`.
+    fn find(&mut self, mut id: usize) -> usize {
+        while self.parents[id] != id {
+            self.parents[id] = self.parents[self.parents[id]];
+            id = self.parents[id];
+        }
+        id
+    }
+
+    /// Merges the sets of `a` and `b`.
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parents[a] = b;
+    }
+
+    fn into_sets(mut self) -> Vec<BTreeSet<PathBuf>> {
+        let mut sets = BTreeMap::<_, BTreeSet<_>>::new();
+        for id in 0..self.paths.len() {
+            let root = self.find(id);
+            sets.entry(root).or_default().insert(std::mem::take(&mut self.paths[id]));
+        }
+        let mut sets = sets.into_values().collect::<Vec<_>>();
+        sets.sort();
+        sets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_config::Config;
+
+    #[test]
+    fn splits_sources_into_independent_units() {
+        let root = tempfile::tempdir().unwrap();
+        let src = root.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        for (name, content) in [
+            ("Lib.sol", "library Lib {}"),
+            ("A.sol", "import \"./Lib.sol\"; contract A {}"),
+            ("B.sol", "import \"./Lib.sol\"; contract B {}"),
+            ("C.sol", "contract C {}"),
+        ] {
+            fs::write(src.join(name), format!("pragma solidity ^0.8.0;\n{content}\n")).unwrap();
+        }
+        let project = Config::with_root(root.path()).project().unwrap();
+        let graph = Graph::resolve(&project.paths).unwrap();
+
+        let units = compilation_units(&graph, project.paths.input_files());
+        let names = units
+            .iter()
+            .map(|unit| {
+                unit.iter().map(|file| file.file_name().unwrap().to_str().unwrap()).collect()
+            })
+            .collect::<Vec<Vec<_>>>();
+        assert_eq!(names, [vec!["A.sol", "B.sol", "Lib.sol"], vec!["C.sol"]]);
+        assert!(!has_name_conflicts(&units));
+        assert_ne!(unit_id(&units[0]), unit_id(&units[1]));
+
+        // only the files matching the filter are compiled, with their imports
+        let units = compilation_units(&graph, [src.join("A.sol")]);
+        assert_eq!(units.len(), 1);
+        assert_eq!(units[0].len(), 2);
+    }
+}
@@ -0,0 +1,397 @@
+use crate::{
+    scaffold::DEPLOYMENTS_DIR, simulate::PreSimulationState, state_diff::storage_layout,
+    transaction::TransactionWithMetadata, verify::BroadcastedState,
+};
+use alloy_primitives::{b256, fixed_bytes, Address, Selector, B256, U256};
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::{
+    bytecode_diff_score, fs, get_contract_name,
+    provider::ethers::get_http_provider,
+    shell,
+    types::{ToAlloy, ToEthers},
+};
+use foundry_compilers::artifacts::{Storage, StorageLayout};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use yansi::Paint;
+
+/// The EIP-1967 implementation slot of proxies.
+const IMPLEMENTATION_SLOT: B256 =
+    b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+/// `upgradeTo(address)`
+const UPGRADE_TO: Selector = fixed_bytes!("3659cfe6");
+/// `upgradeToAndCall(address,bytes)`
+const UPGRADE_TO_AND_CALL: Selector = fixed_bytes!("4f1ef286");
+/// `ProxyAdmin.upgrade(address,address)`
+const UPGRADE: Selector = fixed_bytes!("99a88ec4");
+/// `ProxyAdmin.upgradeAndCall(address,address,bytes)`
+const UPGRADE_AND_CALL: Selector = fixed_bytes!("9623609d");
+
+/// The directory of the deployments registry where the storage layouts of the deployed contracts
+/// are recorded, to check the upgrades of the proxies pointing to them.
+const LAYOUTS_DIR: &str = "layouts";
+
+/// Returns the proxy and the new implementation of a call upgrading a proxy, either directly or
+/// through its admin.
+pub(crate) fn decode_upgrade(to: Address, data: &[u8]) -> Option<(Address, Address)> {
+    let word = |i: usize| data.get(4 + 32 * i..4 + 32 * (i + 1)).map(B256::from_slice);
+    let selector = Selector::from_slice(data.get(..4)?);
+    if selector == UPGRADE_TO || selector == UPGRADE_TO_AND_CALL {
+        Some((to, Address::from_word(word(0)?)))
+    } else if selector == UPGRADE || selector == UPGRADE_AND_CALL {
+        Some((Address::from_word(word(0)?), Address::from_word(word(1)?)))
+    } else {
+        None
+    }
+}
+
+/// A change of the storage layout of an implementation which the proxies upgraded to it don't
+/// support.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LayoutChange {
+    /// No variable is at the slot and offset of a variable of the old layout anymore.
+    Removed { variable: String, slot: U256 },
+    /// The variable at a slot and offset has a different type.
+    Retyped { variable: String, from: String, to: String },
+    /// A storage gap doesn't end at the same slot anymore, which shifts the variables after it.
+    GapResized { variable: String },
+    /// The variable at a slot and offset was renamed, which is compatible but worth a look.
+    Renamed { from: String, to: String },
+}
+
+impl LayoutChange {
+    fn is_breaking(&self) -> bool {
+        !matches!(self, Self::Renamed { .. })
+    }
+}
+
+impl fmt::Display for LayoutChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Removed { variable, slot } => {
+                write!(f, "`{variable}` at slot {slot} was removed or moved")
+            }
+            Self::Retyped { variable, from, to } => {
+                write!(f, "`{variable}` changed type from `{from}` to `{to}`")
+            }
+            Self::GapResized { variable } => {
+                write!(f, "storage gap `{variable}` doesn't end at the same slot anymore")
+            }
+            Self::Renamed { from, to } => write!(f, "`{from}` was renamed to `{to}`"),
+        }
+    }
+}
+
+/// Compares the storage layout of the implementation of a proxy with the one it's upgraded to.
+///
+/// Variables may be appended, and storage gaps (`__gap`) may shrink to make room for new
+/// variables as long as they end at the same slot.
+pub fn compare_layouts(old: &StorageLayout, new: &StorageLayout) -> Vec<LayoutChange> {
+    let variables = new
+        .storage
+        .iter()
+        .filter_map(|variable| Some(((parse_slot(variable)?, variable.offset), variable)))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut changes = Vec::new();
+    for variable in &old.storage {
+        let Some(slot) = parse_slot(variable) else { continue };
+        if is_gap(variable) {
+            let end = slot + slots(old, variable);
+            let gap =
+                new.storage.iter().filter(|gap| is_gap(gap)).any(|gap| {
+                    parse_slot(gap).map_or(false, |start| start + slots(new, gap) == end)
+                });
+            if !gap {
+                changes.push(LayoutChange::GapResized { variable: variable.label.clone() });
+            }
+            continue
+        }
+
+        let Some(new_variable) = variables.get(&(slot, variable.offset)) else {
+            changes.push(LayoutChange::Removed { variable: variable.label.clone(), slot });
+            continue
+        };
+        let (from, to) = (type_label(old, variable), type_label(new, new_variable));
+        if from != to || bytes(old, variable) != bytes(new, new_variable) {
+            changes.push(LayoutChange::Retyped { variable: variable.label.clone(), from, to });
+        } else if variable.label != new_variable.label {
+            changes.push(LayoutChange::Renamed {
+                from: variable.label.clone(),
+                to: new_variable.label.clone(),
+            });
+        }
+    }
+    changes
+}
+
+fn parse_slot(variable: &Storage) -> Option<U256> {
+    U256::from_str(&variable.slot).ok()
+}
+
+fn is_gap(variable: &Storage) -> bool {
+    variable.label.starts_with("__gap")
+}
+
+fn bytes(layout: &StorageLayout, variable: &Storage) -> u64 {
+    layout
+        .types
+        .get(&variable.storage_type)
+        .and_then(|ty| ty.number_of_bytes.parse().ok())
+        .unwrap_or(32)
+}
+
+/// The number of slots taken by a variable.
+fn slots(layout: &StorageLayout, variable: &Storage) -> U256 {
+    U256::from(((bytes(layout, variable) + 31) / 32).max(1))
+}
+
+/// The type of a variable, without the contract which defines structs and enums, as the new
+/// implementation is usually a different contract.
+fn type_label(layout: &StorageLayout, variable: &Storage) -> String {
+    let label = layout
+        .types
+        .get(&variable.storage_type)
+        .map_or(variable.storage_type.as_str(), |ty| ty.label.as_str());
+    label
+        .split(' ')
+        .map(|word| match word.split_once('.') {
+            Some((_, name)) if !name.is_empty() => name,
+            _ => word,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The path where the storage layout of a contract deployed on `chain` is recorded.
+fn layout_path(root: &Path, chain: u64, address: Address) -> PathBuf {
+    root.join(DEPLOYMENTS_DIR)
+        .join(LAYOUTS_DIR)
+        .join(chain.to_string())
+        .join(format!("{address}.json"))
+}
+
+impl PreSimulationState {
+    /// Checks that the proxies upgraded by the transactions support the storage layout of their
+    /// new implementation, and fails before anything is broadcast otherwise.
+    ///
+    /// The layout of the current implementation of a proxy is the one recorded when it was
+    /// deployed, or the one of the artifact matching its code.
+    pub async fn check_upgrades(&self, txs: &VecDeque<TransactionWithMetadata>) -> Result<()> {
+        let root = &self.script_config.config.__root.0;
+        let decoder = &self.execution_artifacts.decoder;
+        let contracts = self.build_data.get_flattened_contracts(true);
+
+        let mut breaking = Vec::new();
+        for tx in txs {
+            let (Some(to), Some(data)) = (tx.transaction.to_addr(), tx.transaction.data()) else {
+                continue
+            };
+            let Some((proxy, implementation)) = decode_upgrade(to.to_alloy(), data) else {
+                continue
+            };
+            let Some(new_id) = decoder.contracts.get(&implementation) else { continue };
+            let Some(new_layout) = storage_layout(root, &self.build_data, new_id) else { continue };
+
+            let provider = get_http_provider(&tx.rpc);
+            let current = provider
+                .get_storage_at(proxy.to_ethers(), IMPLEMENTATION_SLOT.to_ethers(), None)
+                .await?
+                .to_alloy();
+            let current = Address::from_word(current);
+            // proxies deployed by the script have no implementation yet
+            if current.is_zero() {
+                continue
+            }
+
+            let chain = provider.get_chainid().await?.as_u64();
+            let recorded = layout_path(root, chain, current);
+            let old_layout = if recorded.is_file() {
+                Some(fs::read_json_file::<StorageLayout>(&recorded)?)
+            } else {
+                let code = provider.get_code(current.to_ethers(), None).await?;
+                contracts
+                    .iter()
+                    .map(|(id, (_, known_code))| (id, bytecode_diff_score(known_code, &code)))
+                    .filter(|(id, score)| {
+                        *score <= 0.1 && (*score == 0.0 || id.identifier() != *new_id)
+                    })
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .and_then(|(id, _)| storage_layout(root, &self.build_data, &id.identifier()))
+            };
+            let Some(old_layout) = old_layout else {
+                shell::println(Paint::yellow(format!(
+                    "Warning: the storage layout of the implementation {current} of proxy {proxy} \
+                     is unknown, its upgrade to {} can't be checked.",
+                    get_contract_name(new_id)
+                )))?;
+                continue
+            };
+
+            for change in compare_layouts(&old_layout, &new_layout) {
+                let message =
+                    format!("proxy {proxy} upgraded to {}: {change}", get_contract_name(new_id));
+                if change.is_breaking() {
+                    breaking.push(message);
+                } else {
+                    shell::println(Paint::yellow(format!("Warning: {message}")))?;
+                }
+            }
+        }
+
+        if !breaking.is_empty() {
+            eyre::bail!(
+                "The storage layouts of the new implementations are incompatible with their \
+                 proxies:\n  {}\nRun without --check-upgrades to broadcast anyway.",
+                breaking.join("\n  ")
+            )
+        }
+        Ok(())
+    }
+}
+
+impl BroadcastedState {
+    /// Records the storage layout of the deployed contracts in the deployments registry, so that
+    /// upgrades of proxies pointing to them can be checked once the sources have changed.
+    pub fn record_layouts(&self) -> Result<()> {
+        let root = &self.script_config.config.__root.0;
+        if !root.join(DEPLOYMENTS_DIR).is_dir() {
+            return Ok(())
+        }
+        let decoder = &self.execution_artifacts.decoder;
+        for sequence in self.sequence.sequences() {
+            for tx in sequence.transactions.iter().filter(|tx| tx.hash.is_some()) {
+                let Some(address) = tx.contract_address else { continue };
+                let Some(id) = decoder.contracts.get(&address) else { continue };
+                let Some(layout) = storage_layout(root, &self.build_data, id) else { continue };
+                let path = layout_path(root, sequence.chain, address);
+                fs::create_dir_all(path.parent().expect("layout path has a parent"))?;
+                fs::write_json_file(&path, &layout)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    fn layout(variables: &[(&str, u64, &str)]) -> StorageLayout {
+        let storage = variables
+            .iter()
+            .map(|(label, slot, ty)| {
+                serde_json::json!({
+                    "astId": 1,
+                    "contract": "src/Vault.sol:Vault",
+                    "label": label,
+                    "offset": 0,
+                    "slot": slot.to_string(),
+                    "type": ty,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::from_value(serde_json::json!({
+            "storage": storage,
+            "types": {
+                "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+                "t_uint256": { "encoding": "inplace", "label": "uint256", "numberOfBytes": "32" },
+                "t_struct(Position)1_storage": {
+                    "encoding": "inplace",
+                    "label": "struct VaultV1.Position",
+                    "numberOfBytes": "64",
+                },
+                "t_struct(Position)2_storage": {
+                    "encoding": "inplace",
+                    "label": "struct VaultV2.Position",
+                    "numberOfBytes": "64",
+                },
+                "t_array(t_uint256)48_storage": {
+                    "encoding": "inplace",
+                    "label": "uint256[48]",
+                    "numberOfBytes": "1536",
+                },
+                "t_array(t_uint256)47_storage": {
+                    "encoding": "inplace",
+                    "label": "uint256[47]",
+                    "numberOfBytes": "1504",
+                },
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn can_decode_upgrades() {
+        let proxy = address!("00000000000000000000000000000000000000a1");
+        let implementation = address!("00000000000000000000000000000000000000b1");
+        let mut data = UPGRADE_TO.to_vec();
+        data.extend_from_slice(implementation.into_word().as_slice());
+        assert_eq!(decode_upgrade(proxy, &data), Some((proxy, implementation)));
+
+        let admin = address!("00000000000000000000000000000000000000c1");
+        let mut data = UPGRADE.to_vec();
+        data.extend_from_slice(proxy.into_word().as_slice());
+        data.extend_from_slice(implementation.into_word().as_slice());
+        assert_eq!(decode_upgrade(admin, &data), Some((proxy, implementation)));
+
+        assert_eq!(decode_upgrade(proxy, &UPGRADE_TO.0), None);
+    }
+
+    #[test]
+    fn allows_appended_variables_and_shrunk_gaps() {
+        let old = layout(&[
+            ("owner", 0, "t_address"),
+            ("position", 1, "t_struct(Position)1_storage"),
+            ("__gap", 3, "t_array(t_uint256)48_storage"),
+        ]);
+        let new = layout(&[
+            ("owner", 0, "t_address"),
+            ("position", 1, "t_struct(Position)2_storage"),
+            ("fee", 3, "t_uint256"),
+            ("__gap", 4, "t_array(t_uint256)47_storage"),
+            ("admin", 51, "t_address"),
+        ]);
+        assert_eq!(compare_layouts(&old, &new), vec![]);
+    }
+
+    #[test]
+    fn flags_incompatible_layouts() {
+        let old = layout(&[
+            ("owner", 0, "t_address"),
+            ("total", 1, "t_uint256"),
+            ("fee", 2, "t_uint256"),
+            ("__gap", 3, "t_array(t_uint256)48_storage"),
+        ]);
+        let new = layout(&[
+            ("owner", 0, "t_uint256"),
+            ("totalAssets", 1, "t_uint256"),
+            ("__gap", 2, "t_array(t_uint256)48_storage"),
+        ]);
+        let changes = compare_layouts(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                LayoutChange::Retyped {
+                    variable: "owner".to_string(),
+                    from: "address".to_string(),
+                    to: "uint256".to_string(),
+                },
+                LayoutChange::Renamed { from: "total".to_string(), to: "totalAssets".to_string() },
+                LayoutChange::Retyped {
+                    variable: "fee".to_string(),
+                    from: "uint256".to_string(),
+                    to: "uint256[48]".to_string(),
+                },
+                LayoutChange::GapResized { variable: "__gap".to_string() },
+            ]
+        );
+        assert!(!changes[1].is_breaking());
+    }
+}
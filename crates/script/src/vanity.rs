@@ -0,0 +1,298 @@
+use crate::{
+    build::LinkedBuildData, scaffold::DEPLOYMENTS_DIR, sequence::ScriptSequence, templates,
+};
+use alloy_primitives::{Address, B256};
+use eyre::Result;
+use foundry_cli::utils::now;
+use foundry_common::{fs, shell};
+use foundry_evm::{
+    constants::DEFAULT_CREATE2_DEPLOYER, inspectors::cheatcodes::BroadcastableTransactions,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// The file of the deployments registry the mined salts are appended to.
+const SALTS_FILE: &str = "salts.json";
+
+/// How many salts a thread tries between checks of the bounds.
+const CHECK_INTERVAL: u64 = 4096;
+
+/// The hex prefix of the addresses to mine salts for, e.g. `0x0000`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VanityPrefix(Vec<u8>);
+
+impl FromStr for VanityPrefix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let prefix = s.strip_prefix("0x").unwrap_or(s);
+        if prefix.is_empty() || prefix.len() > 40 {
+            return Err(format!("the vanity prefix must have 1 to 40 hex digits, got `{s}`"))
+        }
+        let nibbles = prefix
+            .chars()
+            .map(|c| c.to_digit(16).map(|digit| digit as u8))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| format!("the vanity prefix `{s}` isn't hex"))?;
+        Ok(Self(nibbles))
+    }
+}
+
+impl VanityPrefix {
+    pub fn matches(&self, address: &Address) -> bool {
+        self.0.iter().enumerate().all(|(i, nibble)| {
+            let byte = address[i / 2];
+            let actual = if i % 2 == 0 { byte >> 4 } else { byte & 0xf };
+            actual == *nibble
+        })
+    }
+}
+
+/// A salt mined for a CREATE2 deployment of the script.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VanitySalt {
+    pub contract: String,
+    pub salt: B256,
+    pub address: Address,
+    pub init_code_hash: B256,
+}
+
+/// A mined salt recorded in the deployments registry once its deployment was broadcast.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaltRecord {
+    pub chain: u64,
+    #[serde(flatten)]
+    pub salt: VanitySalt,
+    pub timestamp: u64,
+}
+
+/// Mines the salts of the CREATE2 deployments selected with `--vanity-contract`.
+#[derive(Clone, Debug)]
+pub struct VanityMiner {
+    pub prefix: VanityPrefix,
+    pub contracts: Vec<String>,
+    pub timeout: Duration,
+    pub max_attempts: Option<u64>,
+    /// The salts of the last execution of the script.
+    pub salts: Vec<VanitySalt>,
+}
+
+impl VanityMiner {
+    /// Returns the salts of the selected contracts for the next execution of the script: the
+    /// salts of this execution if they already give matching addresses, the known salts of the
+    /// same init codes, e.g. the ones of a plan, or newly mined ones.
+    pub fn mine_salts(
+        &mut self,
+        transactions: Option<&BroadcastableTransactions>,
+        build_data: &LinkedBuildData,
+    ) -> Result<BTreeMap<String, B256>> {
+        let deployments = templates::find_deployments(transactions, build_data);
+        let mut salts = Vec::new();
+        for contract in &self.contracts {
+            let mut create2 = deployments
+                .iter()
+                .filter(|deployment| &deployment.name == contract)
+                .filter_map(|deployment| deployment.create2.map(|create2| (deployment, create2)));
+            let (Some((deployment, (salt, init_code_hash))), None) =
+                (create2.next(), create2.next())
+            else {
+                eyre::bail!("{contract} must be deployed once with CREATE2 to mine its salt")
+            };
+            let salt = if self.prefix.matches(&deployment.address) {
+                VanitySalt {
+                    contract: contract.clone(),
+                    salt,
+                    address: deployment.address,
+                    init_code_hash,
+                }
+            } else if let Some(salt) = self.salts.iter().find(|salt| {
+                &salt.contract == contract &&
+                    salt.init_code_hash == init_code_hash &&
+                    self.prefix.matches(&salt.address)
+            }) {
+                salt.clone()
+            } else {
+                shell::println(format!("Mining a CREATE2 salt for {contract}..."))?;
+                let (salt, address) = self.mine(init_code_hash)?;
+                VanitySalt { contract: contract.clone(), salt, address, init_code_hash }
+            };
+            salts.push(salt);
+        }
+        self.salts = salts;
+        Ok(self.salts.iter().map(|salt| (salt.contract.clone(), salt.salt)).collect())
+    }
+
+    /// Mines a salt for which the CREATE2 deployer deploys the init code to an address with the
+    /// prefix, on all cores.
+    fn mine(&self, init_code_hash: B256) -> Result<(B256, Address)> {
+        mine_salt(
+            &self.prefix,
+            DEFAULT_CREATE2_DEPLOYER,
+            init_code_hash,
+            self.timeout,
+            self.max_attempts,
+        )
+        .ok_or_else(|| {
+            eyre::eyre!(
+                "No salt gives an address starting with the vanity prefix within the bounds, \
+                 raise --vanity-timeout or --vanity-max-attempts, or shorten the prefix"
+            )
+        })
+    }
+}
+
+/// Tries salts on all cores until one gives an address with the prefix, for `timeout` or
+/// `max_attempts` salts at most.
+pub fn mine_salt(
+    prefix: &VanityPrefix,
+    deployer: Address,
+    init_code_hash: B256,
+    timeout: Duration,
+    max_attempts: Option<u64>,
+) -> Option<(B256, Address)> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get()) as u64;
+    let base = B256::random();
+    let found = AtomicBool::new(false);
+    let attempts = AtomicU64::new(0);
+    let start = Instant::now();
+
+    std::thread::scope(|scope| {
+        let handles = (0..threads)
+            .map(|thread| {
+                let (found, attempts) = (&found, &attempts);
+                scope.spawn(move || {
+                    // The threads try disjoint counters in the last 8 bytes of the salt.
+                    let mut salt = base;
+                    let mut counter = thread;
+                    loop {
+                        for _ in 0..CHECK_INTERVAL {
+                            salt[24..].copy_from_slice(&counter.to_be_bytes());
+                            let address = deployer.create2(salt, init_code_hash);
+                            if prefix.matches(&address) {
+                                found.store(true, Ordering::Relaxed);
+                                return Some((salt, address))
+                            }
+                            counter = counter.wrapping_add(threads);
+                        }
+                        let tried = attempts.fetch_add(CHECK_INTERVAL, Ordering::Relaxed);
+                        if found.load(Ordering::Relaxed) ||
+                            start.elapsed() > timeout ||
+                            max_attempts.map_or(false, |max| tried + CHECK_INTERVAL >= max)
+                        {
+                            return None
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().filter_map(|handle| handle.join().ok().flatten()).next()
+    })
+}
+
+/// Prints the mined salts.
+pub fn print_vanity_salts(salts: &[VanitySalt]) -> Result<()> {
+    if salts.is_empty() {
+        return Ok(())
+    }
+    shell::println("\nVanity salts:")?;
+    for salt in salts {
+        shell::println(format!("  {}: {} -> {}", salt.contract, salt.salt, salt.address))?;
+    }
+    Ok(())
+}
+
+/// Returns the salts of the deployments broadcast on the chain of `sequence`.
+pub fn broadcast_salts(sequence: &ScriptSequence, salts: &[VanitySalt]) -> Vec<SaltRecord> {
+    let timestamp = now().as_secs();
+    salts
+        .iter()
+        .filter(|salt| {
+            sequence.transactions.iter().any(|tx| tx.contract_address == Some(salt.address))
+        })
+        .map(|salt| SaltRecord { chain: sequence.chain, salt: salt.clone(), timestamp })
+        .collect()
+}
+
+/// Appends the salts to `deployments/salts.json`.
+pub fn record_salts(root: &Path, salts: &[SaltRecord]) -> Result<PathBuf> {
+    let path = root.join(DEPLOYMENTS_DIR).join(SALTS_FILE);
+    let mut records: Vec<SaltRecord> =
+        if path.is_file() { fs::read_json_file(&path)? } else { Vec::new() };
+    records.extend_from_slice(salts);
+    fs::create_dir_all(root.join(DEPLOYMENTS_DIR))?;
+    fs::write_json_file(&path, &records)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, keccak256};
+
+    #[test]
+    fn parses_prefixes() {
+        let prefix: VanityPrefix = "0x00a".parse().unwrap();
+        assert!(prefix.matches(&address!("00a0000000000000000000000000000000000001")));
+        assert!(prefix.matches(&address!("00aF000000000000000000000000000000000001")));
+        assert!(!prefix.matches(&address!("00b0000000000000000000000000000000000001")));
+        assert!(!prefix.matches(&address!("10a0000000000000000000000000000000000001")));
+
+        assert!("".parse::<VanityPrefix>().is_err());
+        assert!("0xg0".parse::<VanityPrefix>().is_err());
+        assert!("0".repeat(41).parse::<VanityPrefix>().is_err());
+    }
+
+    #[test]
+    fn mines_salts() {
+        let prefix: VanityPrefix = "0x00".parse().unwrap();
+        let init_code_hash = keccak256([0x60, 0x80]);
+        let (salt, address) = mine_salt(
+            &prefix,
+            DEFAULT_CREATE2_DEPLOYER,
+            init_code_hash,
+            Duration::from_secs(60),
+            None,
+        )
+        .unwrap();
+        assert!(prefix.matches(&address));
+        assert_eq!(DEFAULT_CREATE2_DEPLOYER.create2(salt, init_code_hash), address);
+
+        // A 40 digit prefix can't be mined within a few attempts.
+        let prefix: VanityPrefix = "0".repeat(40).parse().unwrap();
+        let mined = mine_salt(
+            &prefix,
+            DEFAULT_CREATE2_DEPLOYER,
+            init_code_hash,
+            Duration::from_secs(60),
+            Some(CHECK_INTERVAL),
+        );
+        assert_eq!(mined, None);
+    }
+
+    #[test]
+    fn appends_salts() {
+        let root = tempfile::tempdir().unwrap();
+        let record = SaltRecord {
+            chain: 1,
+            salt: VanitySalt {
+                contract: "Token".to_string(),
+                salt: B256::with_last_byte(7),
+                address: address!("0000000000000000000000000000000000000007"),
+                init_code_hash: B256::ZERO,
+            },
+            timestamp: 0,
+        };
+        record_salts(root.path(), &[record.clone()]).unwrap();
+        let path = record_salts(root.path(), &[record.clone()]).unwrap();
+        let records: Vec<SaltRecord> = fs::read_json_file(&path).unwrap();
+        assert_eq!(records, vec![record.clone(), record]);
+    }
+}
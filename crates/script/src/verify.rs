@@ -1,4 +1,5 @@
 use crate::{
+    broadcast::SendTransactionsKind,
     build::LinkedBuildData,
     execute::{ExecutionArtifacts, ExecutionData},
     sequence::ScriptSequenceKind,
@@ -7,12 +8,18 @@ use crate::{
 
 use alloy_primitives::Address;
 use eyre::Result;
-use forge_verify::{RetryArgs, VerifierArgs, VerifyArgs};
+use forge_verify::{retry::DEFAULT_RETRY_DELAY, RetryArgs, VerifierArgs, VerifyArgs};
 use foundry_cli::opts::{EtherscanOpts, OKLinkOpts, ProjectPathsArgs};
-use foundry_common::ContractsByArtifact;
+use foundry_common::{
+    fs,
+    provider::ethers::{estimate_block_time, try_get_http_provider},
+    shell, ContractsByArtifact,
+};
 use foundry_compilers::{info::ContractInfo, Project};
 use foundry_config::{Chain, Config};
 use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// State after we have broadcasted the script.
 /// It is assumed that at this point [BroadcastedState::sequence] contains receipts for all
@@ -24,28 +31,78 @@ pub struct BroadcastedState {
     pub execution_data: ExecutionData,
     pub execution_artifacts: ExecutionArtifacts,
     pub sequence: ScriptSequenceKind,
+    pub send_kind: SendTransactionsKind,
+    /// Path of the verification report, if contracts were verified.
+    pub verification_report: Option<PathBuf>,
 }
 
 impl BroadcastedState {
-    pub async fn verify(self) -> Result<()> {
-        let Self { args, script_config, build_data, mut sequence, .. } = self;
+    pub async fn verify(&mut self) -> Result<()> {
+        let Self { args, script_config, build_data, sequence, .. } = self;
 
         let verify = VerifyBundle::new(
             &script_config.config.project()?,
             &script_config.config,
             build_data.get_flattened_contracts(false),
             args.retry,
-            args.verifier,
+            args.verifier.clone(),
         );
 
+        let mut reports = Vec::with_capacity(sequence.sequences().len());
         for sequence in sequence.sequences_mut() {
-            sequence.verify_contracts(&script_config.config, verify.clone()).await?;
+            let mut verify = verify.clone();
+            // Poll the explorer at the pace of the chain, unless a delay was passed.
+            if verify.retry.delay == DEFAULT_RETRY_DELAY {
+                let provider = try_get_http_provider(sequence.rpc_url())?;
+                if let Some(block_time) = estimate_block_time(&provider).await {
+                    verify.retry = verify.retry.scale_to_block_time(block_time);
+                }
+            }
+            reports.push(sequence.verify_contracts(&script_config.config, verify).await?);
+        }
+
+        if let Some(path) = sequence.broadcast_path() {
+            let path = path.with_extension("verification.json");
+            fs::write_json_file(&path, &reports)?;
+            shell::println(format!("\nVerification report saved to: {}\n", path.display()))?;
+            self.verification_report = Some(path);
+        }
+
+        if let Some(api) = self.args.ipfs_api.clone() {
+            self.pin_to_ipfs(&api, &reports).await?;
         }
 
         Ok(())
     }
 }
 
+/// Outcome of verifying the contracts deployed by a single [ScriptSequence].
+///
+/// [ScriptSequence]: crate::sequence::ScriptSequence
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub chain: u64,
+    pub verified: Vec<VerifiedContract>,
+    pub unverifiable: Vec<Address>,
+}
+
+/// A contract that was verified.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VerifiedContract {
+    pub address: Address,
+    pub contract: String,
+}
+
+impl VerifiedContract {
+    pub fn new(args: &VerifyArgs) -> Self {
+        let contract = match &args.contract.path {
+            Some(path) => format!("{path}:{}", args.contract.name),
+            None => args.contract.name.clone(),
+        };
+        Self { address: args.address, contract }
+    }
+}
+
 /// Data struct to help `ScriptSequence` verify contracts on `etherscan`.
 #[derive(Clone)]
 pub struct VerifyBundle {
@@ -118,7 +175,7 @@ impl VerifyBundle {
             // If it's a CREATE2, the tx.data comes with a 32-byte salt in the beginning
             // of the transaction
             if data.split_at(create2_offset).1.starts_with(bytecode) {
-                let constructor_args = data.split_at(create2_offset + bytecode.len()).1.to_vec();
+                let constructor_args = &data[create2_offset + bytecode.len()..];
 
                 let contract = ContractInfo {
                     path: Some(
@@ -0,0 +1,99 @@
+use alloy_primitives::keccak256;
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_common::{fs, provider::ethers::try_get_http_provider, shell};
+use foundry_config::Config;
+use foundry_evm::opts::EvmOpts;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The name of the file, in the RPC cache directory, which holds the fork blocks of the last
+/// simulations.
+const FORK_BLOCKS_FILE_NAME: &str = "script-fork-blocks.json";
+
+/// The block a simulation forked from, and when.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct ForkBlock {
+    block: u64,
+    timestamp: u64,
+}
+
+/// The fork blocks of the last simulations, by hash of their RPC URL so that API keys aren't
+/// written to disk.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ForkBlocks(BTreeMap<String, ForkBlock>);
+
+impl ForkBlocks {
+    fn path() -> Option<PathBuf> {
+        Some(Config::foundry_rpc_cache_dir()?.join(FORK_BLOCKS_FILE_NAME))
+    }
+
+    fn key(url: &str) -> String {
+        keccak256(url).to_string()
+    }
+
+    /// Returns the block the last simulation against `url` forked from, if it's at most
+    /// `max_age` seconds old.
+    fn get(&self, url: &str, max_age: u64, now: u64) -> Option<u64> {
+        let fork = self.0.get(&Self::key(url))?;
+        (now.saturating_sub(fork.timestamp) <= max_age).then_some(fork.block)
+    }
+
+    fn insert(&mut self, url: &str, block: u64, now: u64) {
+        self.0.insert(Self::key(url), ForkBlock { block, timestamp: now });
+    }
+}
+
+/// Pins the fork of a simulation to the block of the previous simulation against the same RPC,
+/// if it's at most `max_age` seconds old, or to the latest block otherwise.
+///
+/// Forks of a pinned block are cached on disk, so that consecutive simulations read the state
+/// fetched by the previous ones instead of downloading it again.
+pub async fn pin_fork_block(evm_opts: &mut EvmOpts, max_age: u64) -> Result<()> {
+    let Some(url) = &evm_opts.fork_url else { return Ok(()) };
+    if evm_opts.fork_block_number.is_some() {
+        return Ok(())
+    }
+    let Some(path) = ForkBlocks::path() else { return Ok(()) };
+
+    let mut blocks: ForkBlocks = fs::read_json_file(&path).unwrap_or_default();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let block = if let Some(block) = blocks.get(url, max_age, now) {
+        shell::println(format!("Reusing fork block {block} of the previous simulation."))?;
+        block
+    } else {
+        let block = try_get_http_provider(url)?.get_block_number().await?.as_u64();
+        blocks.insert(url, block, now);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write_json_file(&path, &blocks)?;
+        block
+    };
+    evm_opts.fork_block_number = Some(block);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_reuse_recent_fork_blocks() {
+        let mut blocks = ForkBlocks::default();
+        let url = "https://eth-mainnet.example.com/v2/secret";
+        assert_eq!(blocks.get(url, 60, 1_000), None);
+
+        blocks.insert(url, 19_000_000, 1_000);
+        assert_eq!(blocks.get(url, 60, 1_060), Some(19_000_000));
+        assert_eq!(blocks.get(url, 60, 1_061), None);
+        assert_eq!(blocks.get("http://localhost:8545", 60, 1_000), None);
+
+        let json = serde_json::to_string(&blocks).unwrap();
+        assert!(!json.contains("secret"));
+    }
+}
@@ -0,0 +1,140 @@
+use crate::hooks::{self, Hook};
+use ethers_core::types::BlockNumber;
+use ethers_providers::Middleware;
+use eyre::Result;
+use foundry_cli::utils::now;
+use foundry_common::{
+    provider::ethers::{estimate_block_time, poll_interval, RetryProvider},
+    shell,
+};
+use foundry_config::HookCommand;
+use serde::Serialize;
+use std::{path::PathBuf, time::Duration};
+use yansi::Paint;
+
+/// The JSON payload written to the stdin of the `chain_halt` hook, when the chain halts and when
+/// it produces blocks again.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HaltPayload<'a> {
+    pub hook: Hook,
+    pub script: &'a str,
+    pub chain: u64,
+    /// Whether the chain halted, or resumed producing blocks.
+    pub halted: bool,
+    /// The latest block of the chain.
+    pub block: u64,
+    /// How long the chain went without new blocks, in seconds.
+    pub stalled_for: u64,
+}
+
+/// Pauses broadcasting while the chain produces no blocks, e.g. during an outage of the
+/// sequencer of an L2, instead of sending transactions which can't be included.
+#[derive(Clone, Debug)]
+pub struct ChainWatchdog {
+    chain: u64,
+    /// How long without new blocks the chain is considered halted.
+    threshold: Duration,
+    interval: Duration,
+    hook: Option<(PathBuf, HookCommand, String)>,
+}
+
+impl ChainWatchdog {
+    /// Returns a watchdog considering the chain halted after `blocks` times its block time without
+    /// new blocks, or `None` if the block time can't be estimated.
+    pub async fn new(provider: &RetryProvider, chain: u64, blocks: u32) -> Option<Self> {
+        let block_time = estimate_block_time(provider).await?;
+        // Some chains only produce blocks with transactions, or at a low resolution.
+        let threshold = (block_time * blocks).max(Duration::from_secs(1));
+        Some(Self { chain, threshold, interval: poll_interval(block_time), hook: None })
+    }
+
+    /// Runs the `chain_halt` hook with the halts of the chain.
+    pub fn with_hook(mut self, root: PathBuf, command: HookCommand, script: String) -> Self {
+        self.hook = Some((root, command, script));
+        self
+    }
+
+    /// Returns how long the chain has been halted for, if its latest block at `timestamp` is older
+    /// than the threshold at `now`, both in seconds since the epoch.
+    fn halted_for(&self, now: u64, timestamp: u64) -> Option<Duration> {
+        let stalled = Duration::from_secs(now.saturating_sub(timestamp));
+        (stalled > self.threshold).then_some(stalled)
+    }
+
+    /// Waits until the chain produces blocks again, if it's halted.
+    pub async fn wait_while_halted(&self, provider: &RetryProvider) -> Result<()> {
+        let Some(latest) = provider.get_block(BlockNumber::Latest).await? else { return Ok(()) };
+        let halted_at = latest.number.unwrap_or_default().as_u64();
+        let Some(stalled) = self.halted_for(now().as_secs(), latest.timestamp.as_u64()) else {
+            return Ok(())
+        };
+
+        shell::println(Paint::yellow(format!(
+            "Chain {} produced no block for {}s since block {halted_at}, pausing the broadcast \
+             until blocks flow again.",
+            self.chain,
+            stalled.as_secs()
+        )))?;
+        self.notify(true, halted_at, stalled);
+
+        loop {
+            tokio::time::sleep(self.interval).await;
+            // The RPC may be down as well while the chain is halted.
+            match provider.get_block(BlockNumber::Latest).await {
+                Ok(Some(block)) => {
+                    let number = block.number.unwrap_or_default().as_u64();
+                    if number > halted_at {
+                        let stalled = Duration::from_secs(
+                            block.timestamp.as_u64().saturating_sub(latest.timestamp.as_u64()),
+                        );
+                        shell::println(Paint::green(format!(
+                            "Chain {} produced block {number}, resuming the broadcast.",
+                            self.chain
+                        )))?;
+                        self.notify(false, number, stalled);
+                        return Ok(())
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => warn!(%err, chain = self.chain, "failed to get the latest block"),
+            }
+        }
+    }
+
+    fn notify(&self, halted: bool, block: u64, stalled: Duration) {
+        let Some((root, command, script)) = &self.hook else { return };
+        let payload = HaltPayload {
+            hook: Hook::ChainHalt,
+            script,
+            chain: self.chain,
+            halted,
+            block,
+            stalled_for: stalled.as_secs(),
+        };
+        // The broadcast continues without the notification.
+        if let Err(err) = hooks::run_hook(root, Hook::ChainHalt, command, &payload) {
+            warn!(%err, "chain_halt hook failed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_halts() {
+        let watchdog = ChainWatchdog {
+            chain: 10,
+            threshold: Duration::from_secs(20),
+            interval: Duration::from_secs(1),
+            hook: None,
+        };
+        assert_eq!(watchdog.halted_for(1_000, 990), None);
+        assert_eq!(watchdog.halted_for(1_000, 980), None);
+        assert_eq!(watchdog.halted_for(1_000, 900), Some(Duration::from_secs(100)));
+        // Clocks ahead of the machine's aren't halts.
+        assert_eq!(watchdog.halted_for(1_000, 1_010), None);
+    }
+}
@@ -0,0 +1,297 @@
+use crate::{RetryArgs, VerifierArgs, VerifyArgs};
+use alloy_primitives::Address;
+use clap::{Parser, ValueHint};
+use eyre::{Result, WrapErr};
+use foundry_cli::{
+    opts::{EtherscanOpts, OKLinkOpts, RpcOpts},
+    utils::{self, LoadConfig},
+};
+use foundry_common::fs;
+use foundry_config::{figment, impl_figment_convert_cast, Chain, Config};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// The directory of the deployments registry, in the project root.
+const DEPLOYMENTS_DIR: &str = "deployments";
+
+/// CLI arguments for `forge verify-deployments`.
+#[derive(Clone, Debug, Parser)]
+pub struct VerifyDeploymentsArgs {
+    /// Only report the verification status of the deployments, without submitting them.
+    #[arg(long)]
+    pub check: bool,
+
+    /// Verify only these contracts of the registry.
+    #[arg(long = "contract", value_name = "NAME")]
+    pub contracts: Vec<String>,
+
+    /// The project's root path.
+    ///
+    /// By default root of the Git repository, if in one,
+    /// or the current working directory.
+    #[arg(long, value_hint = ValueHint::DirPath, value_name = "PATH")]
+    pub root: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub etherscan: EtherscanOpts,
+
+    #[command(flatten)]
+    pub oklink: OKLinkOpts,
+
+    #[command(flatten)]
+    pub rpc: RpcOpts,
+
+    #[command(flatten)]
+    pub retry: RetryArgs,
+
+    #[command(flatten)]
+    pub verifier: VerifierArgs,
+}
+
+impl_figment_convert_cast!(VerifyDeploymentsArgs);
+
+impl figment::Provider for VerifyDeploymentsArgs {
+    fn metadata(&self) -> figment::Metadata {
+        figment::Metadata::named("Verify Deployments Provider")
+    }
+
+    fn data(
+        &self,
+    ) -> Result<figment::value::Map<figment::Profile, figment::value::Dict>, figment::Error> {
+        let mut dict = self.etherscan.dict();
+        dict.extend(self.rpc.dict());
+        if let Some(root) = self.root.as_ref() {
+            dict.insert("root".to_string(), figment::value::Value::serialize(root)?);
+        }
+        Ok(figment::value::Map::from([(Config::selected_profile(), dict)]))
+    }
+}
+
+/// A deployment of the registry, read from `deployments/<chain>/<name>.json`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deployment {
+    /// The name of the file.
+    #[serde(skip)]
+    pub name: String,
+    pub address: Address,
+    /// The contract identifier, `<path>:<name>`, if the file isn't named after the contract.
+    #[serde(default)]
+    pub contract: Option<String>,
+    /// The ABI-encoded constructor arguments.
+    #[serde(default)]
+    pub constructor_args: Option<String>,
+}
+
+/// Reads the deployments of the chain from `deployments/<chain name>/` and
+/// `deployments/<chain id>/`.
+///
+/// Files without an `address`, such as the ones of other tools, are skipped.
+pub fn load_deployments(root: &Path, chain: Chain) -> Result<Vec<Deployment>> {
+    let dir = root.join(DEPLOYMENTS_DIR);
+    let mut deployments = Vec::new();
+    for chain_dir in [dir.join(chain.to_string()), dir.join(chain.id().to_string())] {
+        if !chain_dir.is_dir() {
+            continue;
+        }
+        let mut paths = fs::files_with_ext(&chain_dir, "json");
+        paths.sort();
+        for path in paths {
+            let Some(name) = path.file_stem().map(|stem| stem.to_string_lossy().to_string()) else {
+                continue;
+            };
+            let deployment: serde_json::Value = fs::read_json_file(&path)?;
+            if deployment.get("address").is_none() {
+                trace!(target: "forge::verify", ?path, "not a deployment");
+                continue;
+            }
+            let mut deployment: Deployment = serde_json::from_value(deployment)
+                .wrap_err_with(|| format!("Invalid deployment {}", path.display()))?;
+            deployment.name = name;
+            if !deployments.contains(&deployment) {
+                deployments.push(deployment);
+            }
+        }
+    }
+    Ok(deployments)
+}
+
+impl VerifyDeploymentsArgs {
+    /// Checks the verification status of the deployments of the registry on the chain, and
+    /// submits the unverified ones.
+    pub async fn run(self) -> Result<()> {
+        let config = self.load_config_emit_warnings();
+        let root = &config.__root.0;
+        let chain = match self.etherscan.chain.or(config.chain) {
+            Some(chain) => chain,
+            None => utils::get_chain(None, utils::get_provider(&config)?).await?,
+        };
+
+        let mut deployments = load_deployments(root, chain)?;
+        if !self.contracts.is_empty() {
+            deployments.retain(|deployment| self.contracts.contains(&deployment.name));
+        }
+        if deployments.is_empty() {
+            eyre::bail!(
+                "No deployments on {chain} in {}",
+                root.join(DEPLOYMENTS_DIR).join(chain.to_string()).display()
+            )
+        }
+        println!("Checking {} deployments on {chain} ({}).", deployments.len(), chain.id());
+
+        let guess_constructor_args = config.get_rpc_url().is_some();
+        let (mut verified, mut unverified, mut submitted) = (0, 0, 0);
+        let mut failed = Vec::new();
+        for deployment in &deployments {
+            let Deployment { name, address, .. } = deployment;
+            let mut args = self.verify_args(deployment, chain, guess_constructor_args)?;
+            let status = match args.is_verified().await {
+                Ok(status) => status,
+                Err(err) => {
+                    warn!(target: "forge::verify", %name, %err, "failed to check verification");
+                    None
+                }
+            };
+            match status {
+                Some(true) => {
+                    println!("  {name} {address}: verified");
+                    verified += 1;
+                    continue;
+                }
+                Some(false) => println!("  {name} {address}: not verified"),
+                None => println!("  {name} {address}: unknown"),
+            }
+            if self.check {
+                unverified += 1;
+                continue;
+            }
+
+            // The status was checked already, unless the verifier can't tell.
+            args.skip_is_verified_check = status.is_some();
+            let result = match args.prepare().await {
+                Ok(prepared) => prepared.submit().await,
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(()) => submitted += 1,
+                Err(err) => {
+                    println!("  {name} {address}: failed to verify: {err:#}");
+                    failed.push(name.clone());
+                }
+            }
+        }
+
+        if self.check {
+            println!("\n{verified} verified, {unverified} not verified.");
+            if unverified > 0 {
+                println!("Run `forge verify-deployments` without `--check` to verify them.");
+            }
+        } else {
+            println!(
+                "\n{verified} verified already, {submitted} submitted, {} failed.",
+                failed.len()
+            );
+        }
+        if !failed.is_empty() {
+            eyre::bail!("Failed to verify: {}", failed.join(", "))
+        }
+        Ok(())
+    }
+
+    /// Returns the arguments of `forge verify-contract` for the deployment.
+    fn verify_args(
+        &self,
+        deployment: &Deployment,
+        chain: Chain,
+        guess_constructor_args: bool,
+    ) -> Result<VerifyArgs> {
+        let contract = deployment.contract.as_deref().unwrap_or(&deployment.name);
+        Ok(VerifyArgs {
+            address: deployment.address,
+            contract: contract
+                .parse()
+                .map_err(|err| eyre::eyre!("invalid contract of {}: {err}", deployment.name))?,
+            constructor_args: deployment.constructor_args.clone(),
+            constructor_args_path: None,
+            guess_constructor_args: guess_constructor_args && deployment.constructor_args.is_none(),
+            compiler_version: None,
+            num_of_optimizations: None,
+            flatten: false,
+            force: false,
+            skip_is_verified_check: false,
+            watch: true,
+            libraries: Vec::new(),
+            root: self.root.clone(),
+            show_standard_json_input: false,
+            via_ir: false,
+            evm_version: None,
+            etherscan: EtherscanOpts { key: self.etherscan.key.clone(), chain: Some(chain) },
+            oklink: self.oklink.clone(),
+            rpc: self.rpc.clone(),
+            retry: self.retry,
+            verifier: self.verifier.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn loads_deployments() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join(DEPLOYMENTS_DIR);
+        std::fs::create_dir_all(dir.join("mainnet")).unwrap();
+        std::fs::create_dir_all(dir.join("1")).unwrap();
+        std::fs::write(
+            dir.join("mainnet/Token.json"),
+            r#"{"address":"0x5FbDB2315678afecb367f032d93F642f64180aa3","constructorArgs":"0x01"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("1/Vault.json"),
+            r#"{"address":"0xe7f1725E7734CE288F8367e1Bb143E90bb3F0512","contract":"src/Vault.sol:VaultV2"}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("1/.chainId"), "1").unwrap();
+        std::fs::write(dir.join("1/solcInputs.json"), r#"{"language":"Solidity"}"#).unwrap();
+
+        let deployments = load_deployments(root.path(), Chain::mainnet()).unwrap();
+        assert_eq!(
+            deployments,
+            [
+                Deployment {
+                    name: "Token".to_string(),
+                    address: address!("5FbDB2315678afecb367f032d93F642f64180aa3"),
+                    contract: None,
+                    constructor_args: Some("0x01".to_string()),
+                },
+                Deployment {
+                    name: "Vault".to_string(),
+                    address: address!("e7f1725E7734CE288F8367e1Bb143E90bb3F0512"),
+                    contract: Some("src/Vault.sol:VaultV2".to_string()),
+                    constructor_args: None,
+                },
+            ]
+        );
+        assert!(load_deployments(root.path(), Chain::from(196)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn can_parse_verify_deployments() {
+        let args = VerifyDeploymentsArgs::parse_from([
+            "foundry-cli",
+            "--chain",
+            "196",
+            "--contract",
+            "Token",
+            "--verifier",
+            "oklink",
+        ]);
+        assert_eq!(args.etherscan.chain, Some(Chain::from(196)));
+        assert_eq!(args.contracts, ["Token"]);
+        assert!(!args.check);
+    }
+}
@@ -1,6 +1,7 @@
 use super::{provider::VerificationProvider, VerifyArgs, VerifyCheckArgs};
 use crate::retry::RETRY_CHECK_ON_VERIFY;
 use alloy_json_abi::Function;
+use alloy_primitives::Address;
 use ethers_providers::Middleware;
 use eyre::{eyre, Context, OptionExt, Result};
 use foundry_block_explorers::{
@@ -9,8 +10,8 @@ use foundry_block_explorers::{
     verify::{CodeFormat, VerifyContract},
     Client,
 };
-use foundry_cli::utils::{self, get_cached_entry_by_name, read_constructor_args_file, LoadConfig};
-use foundry_common::{abi::encode_function_args, retry::Retry, types::ToEthers};
+use foundry_cli::utils::{self, get_cached_entry_by_name, read_constructor_args, LoadConfig};
+use foundry_common::{abi::encode_function_args, retry::Retry, tr, types::ToEthers};
 use foundry_compilers::{
     artifacts::{BytecodeObject, CompactContract},
     cache::CacheEntry,
@@ -39,6 +40,8 @@ pub static RE_BUILD_COMMIT: Lazy<Regex> =
 pub struct EtherscanVerificationProvider {
     /// Memoized cached entry of the target contract
     cached_entry: Option<(PathBuf, CacheEntry, CompactContract)>,
+    /// The request built by [VerificationProvider::prepare]
+    prepared: Option<(Client, VerifyContract)>,
 }
 
 /// The contract source provider for [EtherscanVerificationProvider]
@@ -61,16 +64,27 @@ impl VerificationProvider for EtherscanVerificationProvider {
         Ok(())
     }
 
+    async fn prepare(&mut self, args: &VerifyArgs) -> Result<()> {
+        self.prepared = Some(self.prepare_request(args).await?);
+        Ok(())
+    }
+
     async fn verify(&mut self, args: VerifyArgs) -> Result<()> {
-        let (etherscan, verify_args) = self.prepare_request(&args).await?;
+        let (etherscan, verify_args) = match self.prepared.take() {
+            Some(prepared) => prepared,
+            None => self.prepare_request(&args).await?,
+        };
 
         if !args.skip_is_verified_check
-            && self.is_contract_verified(&etherscan, &verify_args).await?
+            && self.is_contract_verified(&etherscan, verify_args.address).await?
         {
             println!(
-                "\nContract [{}] {:?} is already verified. Skipping verification.",
-                verify_args.contract_name,
-                verify_args.address.to_checksum(None)
+                "{}",
+                tr!(
+                    "\nContract [{name}] {address} is already verified. Skipping verification.",
+                    name = verify_args.contract_name,
+                    address = format!("{:?}", verify_args.address.to_checksum(None)),
+                )
             );
 
             return Ok(());
@@ -82,8 +96,12 @@ impl VerificationProvider for EtherscanVerificationProvider {
         let resp = retry
             .run_async(|| async {
                 println!(
-                    "\nSubmitting verification for [{}] {}.",
-                    verify_args.contract_name, verify_args.address
+                    "{}",
+                    tr!(
+                        "\nSubmitting verification for [{name}] {address}.",
+                        name = verify_args.contract_name,
+                        address = verify_args.address,
+                    )
                 );
                 let resp = etherscan
                     .submit_contract_verification(&verify_args)
@@ -112,8 +130,12 @@ impl VerificationProvider for EtherscanVerificationProvider {
 
                     warn!("Failed verify submission: {:?}", resp);
                     eprintln!(
-                        "Encountered an error verifying this contract:\nResponse: `{}`\nDetails: `{}`",
-                        resp.message, resp.result
+                        "{}",
+                        tr!(
+                            "Encountered an error verifying this contract:\nResponse: `{response}`\nDetails: `{details}`",
+                            response = resp.message,
+                            details = resp.result,
+                        )
                     );
                     std::process::exit(1);
                 }
@@ -124,15 +146,19 @@ impl VerificationProvider for EtherscanVerificationProvider {
 
         if let Some(resp) = resp {
             println!(
-                "Submitted contract for verification:\n\tResponse: `{}`\n\tGUID: `{}`\n\tURL: {}",
-                resp.message,
-                resp.result,
-                etherscan.address_url(args.address)
+                "{}",
+                tr!(
+                    "Submitted contract for verification:\n\tResponse: `{response}`\n\tGUID: `{guid}`\n\tURL: {url}",
+                    response = resp.message,
+                    guid = resp.result,
+                    url = etherscan.address_url(args.address),
+                )
             );
 
             if args.watch {
                 let check_args = VerifyCheckArgs {
                     id: resp.result,
+                    address: Some(args.address),
                     etherscan: args.etherscan,
                     oklink: args.oklink,
                     retry: RETRY_CHECK_ON_VERIFY,
@@ -142,7 +168,7 @@ impl VerificationProvider for EtherscanVerificationProvider {
                 return self.check(check_args).await;
             }
         } else {
-            println!("Contract source code already verified");
+            println!("{}", tr!("Contract source code already verified"));
         }
 
         Ok(())
@@ -169,8 +195,12 @@ impl VerificationProvider for EtherscanVerificationProvider {
                     trace!(target: "forge::verify", ?resp, "Received verification response");
 
                     eprintln!(
-                        "Contract verification status:\nResponse: `{}`\nDetails: `{}`",
-                        resp.message, resp.result
+                        "{}",
+                        tr!(
+                            "Contract verification status:\nResponse: `{response}`\nDetails: `{details}`",
+                            response = resp.message,
+                            details = resp.result,
+                        )
                     );
 
                     if resp.result == "Pending in queue" {
@@ -182,17 +212,17 @@ impl VerificationProvider for EtherscanVerificationProvider {
                     }
 
                     if resp.result == "Already Verified" {
-                        println!("Contract source code already verified");
+                        println!("{}", tr!("Contract source code already verified"));
                         return Ok(());
                     }
 
                     if resp.status == "0" {
-                        println!("Contract failed to verify.");
+                        println!("{}", tr!("Contract failed to verify."));
                         std::process::exit(1);
                     }
 
                     if resp.result == "Pass - Verified" {
-                        println!("Contract successfully verified");
+                        println!("{}", tr!("Contract successfully verified"));
                     }
 
                     Ok(())
@@ -202,6 +232,17 @@ impl VerificationProvider for EtherscanVerificationProvider {
             .await
             .wrap_err("Checking verification result failed:")
     }
+
+    async fn is_verified(&mut self, args: &VerifyArgs) -> Result<Option<bool>> {
+        let config = args.try_load_config_emit_warnings()?;
+        let etherscan = self.client(
+            args.etherscan.chain.unwrap_or_default(),
+            args.verifier.verifier_url.as_deref(),
+            args.etherscan.key().as_deref(),
+            &config,
+        )?;
+        Ok(Some(self.is_contract_verified(&etherscan, args.address).await?))
+    }
 }
 
 impl EtherscanVerificationProvider {
@@ -259,12 +300,8 @@ impl EtherscanVerificationProvider {
     }
 
     /// Queries the etherscan API to verify if the contract is already verified.
-    async fn is_contract_verified(
-        &self,
-        etherscan: &Client,
-        verify_contract: &VerifyContract,
-    ) -> Result<bool> {
-        let check = etherscan.contract_abi(verify_contract.address).await;
+    async fn is_contract_verified(&self, etherscan: &Client, address: Address) -> Result<bool> {
+        let check = etherscan.contract_abi(address).await;
 
         if let Err(err) = check {
             match err {
@@ -464,10 +501,10 @@ impl EtherscanVerificationProvider {
             };
             let encoded_args = encode_function_args(
                 &func,
-                read_constructor_args_file(constructor_args_path.to_path_buf())?,
+                read_constructor_args(constructor_args_path, &constructor.inputs)?,
             )?;
-            let encoded_args = hex::encode(encoded_args);
-            return Ok(Some(encoded_args[8..].into()));
+            // skip the selector
+            return Ok(Some(hex::encode(&encoded_args[4..])));
         }
         if args.guess_constructor_args {
             return Ok(Some(self.guess_constructor_args(args, project, config).await?));
@@ -11,40 +11,168 @@ use foundry_cli::{
     utils,
     utils::LoadConfig,
 };
+use foundry_common::tr;
 use foundry_compilers::{info::ContractInfo, EvmVersion};
-use foundry_config::{figment, impl_figment_convert, impl_figment_convert_cast, Config};
+use foundry_config::{
+    figment, impl_figment_convert, impl_figment_convert_cast, Chain, Config, VerifierPluginConfig,
+};
 use provider::VerificationProviderType;
 use reqwest::Url;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+mod deployments;
 mod etherscan;
 use etherscan::EtherscanVerificationProvider;
 
 mod oklink;
 
+pub mod plugin;
+use plugin::PluginVerificationProvider;
+
 pub mod provider;
 use provider::VerificationProvider;
 
 pub mod retry;
+mod setup;
 mod sourcify;
 
+pub use deployments::VerifyDeploymentsArgs;
 pub use retry::RetryArgs;
+pub use setup::VerifySetupArgs;
 
 /// Verification provider arguments
 #[derive(Clone, Debug, Parser)]
 pub struct VerifierArgs {
     /// The contract verification provider to use.
-    #[arg(long, help_heading = "Verifier options", default_value = "etherscan", value_enum)]
-    pub verifier: VerificationProviderType,
+    ///
+    /// Defaults to the `verifier` of the config, or to `etherscan`.
+    #[arg(long, help_heading = "Verifier options", value_enum)]
+    pub verifier: Option<VerificationProviderType>,
 
-    /// The verifier URL, if using a custom provider
+    /// The verifier URL, if using a custom provider.
+    ///
+    /// `{chain}`, `{chainid}` and `{address}` are replaced with the chain name, the chain id and
+    /// the address of the contract. With the OKLink verifier, the chain name is OKLink's short
+    /// name of the chain, e.g. `eth`.
     #[arg(long, help_heading = "Verifier options", env = "VERIFIER_URL")]
     pub verifier_url: Option<String>,
+
+    /// The verification provider plugin to use, as declared in `[verifier_plugins]`.
+    #[arg(long, help_heading = "Verifier options", value_name = "NAME")]
+    pub verifier_plugin: Option<String>,
+
+    /// The plugin selected by `verifier_plugin`, resolved by [VerifierArgs::apply_config].
+    #[arg(skip)]
+    pub plugin: Option<VerifierPluginConfig>,
 }
 
 impl Default for VerifierArgs {
     fn default() -> Self {
-        VerifierArgs { verifier: VerificationProviderType::Etherscan, verifier_url: None }
+        VerifierArgs { verifier: None, verifier_url: None, verifier_plugin: None, plugin: None }
+    }
+}
+
+impl VerifierArgs {
+    /// Uses the `verifier` and `verifier_url` of the config, unless they were passed on the
+    /// command line, and resolves the verifier plugin.
+    ///
+    /// The `verifier` of the config can also be the name of a plugin.
+    pub fn apply_config(&mut self, config: &Config) -> Result<()> {
+        if let Some(verifier) = &config.verifier {
+            if self.verifier.is_none() && self.verifier_plugin.is_none() {
+                if config.verifier_plugins.contains_key(verifier) {
+                    self.verifier_plugin = Some(verifier.clone());
+                } else {
+                    self.verifier = Some(
+                        verifier
+                            .parse()
+                            .map_err(|err| eyre::eyre!("invalid `verifier` in config: {err}"))?,
+                    );
+                }
+            }
+        }
+        if self.verifier_url.is_none() {
+            self.verifier_url = config.verifier_url.clone();
+        }
+
+        // the verifier of the explorer of a custom chain
+        if let Some(definition) = config.chain_definition(None) {
+            if let Some(verifier) = &definition.verifier {
+                if self.verifier.is_none() && self.verifier_plugin.is_none() {
+                    self.verifier = Some(
+                        verifier
+                            .parse()
+                            .map_err(|err| eyre::eyre!("invalid verifier of the chain: {err}"))?,
+                    );
+                }
+            }
+            if self.verifier_url.is_none() && self.verifier() != VerificationProviderType::Etherscan
+            {
+                self.verifier_url = definition.explorer_api;
+            }
+        }
+
+        if let Some(name) = &self.verifier_plugin {
+            let Some(plugin) = config.verifier_plugins.get(name) else {
+                eyre::bail!("unknown verifier plugin `{name}`, declare it in `[verifier_plugins]`")
+            };
+            let mut plugin = plugin.clone();
+            // Commands with a relative path are relative to the project root.
+            let command = Path::new(&plugin.command);
+            if command.is_relative() && command.components().count() > 1 {
+                plugin.command = config.__root.0.join(command).to_string_lossy().into_owned();
+            }
+            self.verifier = Some(VerificationProviderType::Plugin);
+            self.plugin = Some(plugin);
+        }
+        Ok(())
+    }
+
+    /// Returns the verifier to use, `etherscan` unless another one was passed or configured.
+    pub fn verifier(&self) -> VerificationProviderType {
+        self.verifier.clone().unwrap_or_default()
+    }
+
+    /// Returns the verification provider, which is the plugin if one is selected.
+    pub fn client(&self, key: &Option<String>) -> Result<Box<dyn VerificationProvider>> {
+        match (&self.verifier_plugin, &self.plugin) {
+            (Some(name), Some(plugin)) => {
+                Ok(Box::new(PluginVerificationProvider::new(name.clone(), plugin.clone())))
+            }
+            _ => self.verifier().client(key),
+        }
+    }
+
+    /// Replaces the `{chain}`, `{chainid}` and `{address}` variables of the verifier URL, so that
+    /// a single URL covers all the chains of a verifier, e.g.
+    /// `https://www.oklink.com/api/explorer/v1/contract/verify/async/api/{chain}/`.
+    ///
+    /// With the OKLink verifier, `{chain}` is OKLink's short name of the chain, e.g. `eth`.
+    pub fn render_url(&mut self, chain: Chain, address: Option<Address>) -> Result<()> {
+        let is_oklink = self.verifier() == VerificationProviderType::Oklink;
+        let Some(url) = &mut self.verifier_url else { return Ok(()) };
+        if url.contains("{chain}") {
+            let name = if is_oklink {
+                oklink::chain_short_name(chain)
+                    .ok_or_else(|| {
+                        eyre::eyre!(
+                            "OKLink's name of {chain} is unknown, set it in the verifier URL"
+                        )
+                    })?
+                    .to_string()
+            } else {
+                chain.to_string()
+            };
+            *url = url.replace("{chain}", &name);
+        }
+        *url = url.replace("{chainid}", &chain.id().to_string());
+        if url.contains("{address}") {
+            let address = address.ok_or_else(|| {
+                eyre::eyre!("The verifier URL {url} needs the address of the contract")
+            })?;
+            *url = url.replace("{address}", &address.to_string());
+        }
+        Ok(())
     }
 }
 
@@ -67,6 +195,9 @@ pub struct VerifyArgs {
     pub constructor_args: Option<String>,
 
     /// The path to a file containing the constructor arguments.
+    ///
+    /// A JSON file can hold an object of the arguments by name, e.g. `{"supply": "1000"}`,
+    /// which are checked against the constructor.
     #[arg(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
     pub constructor_args_path: Option<PathBuf>,
 
@@ -178,7 +309,32 @@ impl figment::Provider for VerifyArgs {
 impl VerifyArgs {
     /// Run the verify command to submit the contract's source code for verification on etherscan
     pub async fn run(mut self) -> Result<()> {
+        if self.show_standard_json_input {
+            self.configure().await?;
+            let args =
+                EtherscanVerificationProvider::default().create_verify_request(&self, None).await?;
+            println!("{}", args.source);
+            return Ok(());
+        }
+
+        self.prepare().await?.submit().await
+    }
+
+    /// Builds the verify request of the contract, without submitting it.
+    ///
+    /// Requests of multiple contracts can be prepared concurrently, and then submitted one at a
+    /// time with [PreparedVerification::submit].
+    pub async fn prepare(mut self) -> Result<PreparedVerification> {
+        let chain = self.configure().await?;
+        let mut provider = self.verification_provider()?;
+        provider.prepare(&self).await?;
+        Ok(PreparedVerification { args: self, chain, provider })
+    }
+
+    /// Applies the config, and resolves the chain of the contract and its etherscan key.
+    async fn configure(&mut self) -> Result<Chain> {
         let config = self.load_config_emit_warnings();
+        self.verifier.apply_config(&config)?;
 
         if self.guess_constructor_args && config.get_rpc_url().is_none() {
             eyre::bail!(
@@ -198,17 +354,42 @@ impl VerifyArgs {
 
         self.etherscan.chain = Some(chain);
         self.etherscan.key = config.get_etherscan_config_with_chain(Some(chain))?.map(|c| c.key);
+        self.verifier.render_url(chain, Some(self.address))?;
+        Ok(chain)
+    }
 
-        if self.show_standard_json_input {
-            let args =
-                EtherscanVerificationProvider::default().create_verify_request(&self, None).await?;
-            println!("{}", args.source);
-            return Ok(());
-        }
+    /// Returns whether the contract is verified already, or `None` if the verifier can't tell.
+    pub async fn is_verified(&mut self) -> Result<Option<bool>> {
+        self.configure().await?;
+        self.verification_provider()?.is_verified(self).await
+    }
+
+    /// Returns the configured verification provider
+    pub fn verification_provider(&self) -> Result<Box<dyn VerificationProvider>> {
+        self.verifier.client(&self.etherscan.key())
+    }
+}
+
+/// A verify request built by [VerifyArgs::prepare], ready to be submitted.
+pub struct PreparedVerification {
+    args: VerifyArgs,
+    chain: Chain,
+    provider: Box<dyn VerificationProvider>,
+}
 
-        let verifier_url = self.verifier.verifier_url.clone();
-        println!("Start verifying contract `{}` deployed on {chain}", self.address);
-        self.verifier.verifier.client(&self.etherscan.key())?.verify(self).await.map_err(|err| {
+impl PreparedVerification {
+    /// Submits the request to the verification provider.
+    pub async fn submit(mut self) -> Result<()> {
+        let verifier_url = self.args.verifier.verifier_url.clone();
+        println!(
+            "{}",
+            tr!(
+                "Start verifying contract `{address}` deployed on {chain}",
+                address = self.args.address,
+                chain = self.chain,
+            )
+        );
+        self.provider.verify(self.args).await.map_err(|err| {
             if let Some(verifier_url) = verifier_url {
                  match Url::parse(&verifier_url) {
                     Ok(url) => {
@@ -229,11 +410,6 @@ impl VerifyArgs {
             err
         })
     }
-
-    /// Returns the configured verification provider
-    pub fn verification_provider(&self) -> Result<Box<dyn VerificationProvider>> {
-        self.verifier.verifier.client(&self.etherscan.key())
-    }
 }
 
 /// Check verification status arguments
@@ -246,6 +422,10 @@ pub struct VerifyCheckArgs {
     /// For Sourcify - Contract Address.
     id: String,
 
+    /// The address of the contract, which replaces `{address}` in the verifier URL.
+    #[arg(long)]
+    address: Option<Address>,
+
     #[command(flatten)]
     retry: RetryArgs,
 
@@ -263,9 +443,21 @@ impl_figment_convert_cast!(VerifyCheckArgs);
 
 impl VerifyCheckArgs {
     /// Run the verify command to submit the contract's source code for verification on etherscan
-    pub async fn run(self) -> Result<()> {
-        println!("Checking verification status on {}", self.etherscan.chain.unwrap_or_default());
-        self.verifier.verifier.client(&self.etherscan.key())?.check(self).await
+    pub async fn run(mut self) -> Result<()> {
+        let config = self.load_config_emit_warnings();
+        self.verifier.apply_config(&config)?;
+        // the Sourcify verification ID is the address of the contract
+        let address = self.address.or_else(|| self.id.parse().ok());
+        self.verifier.render_url(self.etherscan.chain.unwrap_or_default(), address)?;
+
+        println!(
+            "{}",
+            tr!(
+                "Checking verification status on {chain}",
+                chain = self.etherscan.chain.unwrap_or_default(),
+            )
+        );
+        self.verifier.client(&self.etherscan.key())?.check(self).await
     }
 }
 
@@ -310,4 +502,86 @@ mod tests {
         ]);
         assert!(args.via_ir);
     }
+
+    #[test]
+    fn can_apply_config_verifier() {
+        let config = Config {
+            verifier: Some("oklink".to_string()),
+            verifier_url: Some("https://www.oklink.com/api/".to_string()),
+            ..Default::default()
+        };
+
+        let mut args = VerifierArgs::default();
+        args.apply_config(&config).unwrap();
+        assert_eq!(args.verifier(), VerificationProviderType::Oklink);
+        assert_eq!(args.verifier_url.as_deref(), Some("https://www.oklink.com/api/"));
+
+        let mut args = VerifierArgs::parse_from(["foundry-cli", "--verifier", "sourcify"]);
+        args.apply_config(&config).unwrap();
+        assert_eq!(args.verifier(), VerificationProviderType::Sourcify);
+
+        // An explicit `--verifier etherscan` isn't replaced by the config.
+        let mut args = VerifierArgs::parse_from(["foundry-cli", "--verifier", "etherscan"]);
+        args.apply_config(&config).unwrap();
+        assert_eq!(args.verifier(), VerificationProviderType::Etherscan);
+
+        let config = Config { verifier: Some("unknown".to_string()), ..Default::default() };
+        assert!(VerifierArgs::default().apply_config(&config).is_err());
+    }
+
+    #[test]
+    fn can_apply_config_verifier_plugin() {
+        let plugin = VerifierPluginConfig { command: "bin/tronscan".to_string(), args: vec![] };
+        let config = Config {
+            verifier: Some("tronscan".to_string()),
+            verifier_plugins: [("tronscan".to_string(), plugin)].into(),
+            ..Default::default()
+        };
+
+        let mut args = VerifierArgs::default();
+        args.apply_config(&config).unwrap();
+        assert_eq!(args.verifier(), VerificationProviderType::Plugin);
+        assert_eq!(args.verifier_plugin.as_deref(), Some("tronscan"));
+        assert_eq!(
+            args.plugin.unwrap().command,
+            config.__root.0.join("bin/tronscan").to_string_lossy()
+        );
+
+        let mut args = VerifierArgs::parse_from(["foundry-cli", "--verifier-plugin", "unknown"]);
+        assert!(args.apply_config(&config).is_err());
+    }
+
+    #[test]
+    fn can_render_verifier_url() {
+        let mut args = VerifierArgs::parse_from([
+            "foundry-cli",
+            "--verifier-url",
+            "https://example.com/api/{chain}/{chainid}/{address}",
+        ]);
+        args.render_url(Chain::mainnet(), Some(Address::ZERO)).unwrap();
+        assert_eq!(
+            args.verifier_url.as_deref(),
+            Some("https://example.com/api/mainnet/1/0x0000000000000000000000000000000000000000")
+        );
+
+        let mut args = VerifierArgs::parse_from([
+            "foundry-cli",
+            "--verifier",
+            "oklink",
+            "--verifier-url",
+            "https://www.oklink.com/api/explorer/v1/contract/verify/async/api/{chain}/",
+        ]);
+        args.render_url(Chain::from_id(196), None).unwrap();
+        assert_eq!(
+            args.verifier_url.as_deref(),
+            Some("https://www.oklink.com/api/explorer/v1/contract/verify/async/api/xlayer/")
+        );
+
+        let mut args = VerifierArgs::parse_from([
+            "foundry-cli",
+            "--verifier-url",
+            "https://example.com/{address}",
+        ]);
+        assert!(args.render_url(Chain::mainnet(), None).is_err());
+    }
 }
@@ -6,7 +6,7 @@ use foundry_compilers::{
     AggregatedCompilerOutput, CompilerInput, Project, Solc,
 };
 use semver::{BuildMetadata, Version};
-use std::{collections::BTreeMap, path::Path};
+use std::{collections::BTreeMap, path::Path, sync::Arc};
 
 #[derive(Debug)]
 pub struct OklinkFlattenedSource;
@@ -27,7 +27,8 @@ impl OklinkSourceProvider for OklinkFlattenedSource {
             bch,
         );
 
-        let source = project.flatten(target).wrap_err("Failed to flatten contract")?;
+        // The flattened source is shared with the dry run instead of copied, it can be large.
+        let source = Source::new(project.flatten(target).wrap_err("Failed to flatten contract")?);
 
         if !args.force {
             // solc dry run of flattened code
@@ -40,6 +41,7 @@ impl OklinkSourceProvider for OklinkFlattenedSource {
             })?;
         }
 
+        let source = Arc::try_unwrap(source.content).unwrap_or_else(|content| (*content).clone());
         let name = args.contract.name.clone();
         Ok((source, name, CodeFormat::SingleFile))
     }
@@ -62,7 +64,7 @@ impl OklinkFlattenedSource {
     /// multiple interfaces with the same name.
     fn check_flattened(
         &self,
-        content: impl Into<String>,
+        content: Source,
         version: &Version,
         contract_path: &Path,
     ) -> Result<()> {
@@ -72,7 +74,7 @@ impl OklinkFlattenedSource {
 
         let input = CompilerInput {
             language: "Solidity".to_string(),
-            sources: BTreeMap::from([("contract.sol".into(), Source::new(content))]),
+            sources: BTreeMap::from([("contract.sol".into(), content)]),
             settings: Default::default(),
         };
 
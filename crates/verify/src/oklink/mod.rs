@@ -1,6 +1,7 @@
 use super::{provider::VerificationProvider, VerifyArgs, VerifyCheckArgs};
 use crate::retry::RETRY_CHECK_ON_VERIFY;
 use alloy_json_abi::Function;
+use alloy_primitives::Address;
 use eyre::{eyre, Context, Result};
 use foundry_block_explorers::{
     errors::EtherscanError,
@@ -8,8 +9,8 @@ use foundry_block_explorers::{
     verify::{CodeFormat, VerifyContract},
     Client,
 };
-use foundry_cli::utils::{get_cached_entry_by_name, read_constructor_args_file, LoadConfig};
-use foundry_common::{abi::encode_function_args, retry::Retry};
+use foundry_cli::utils::{get_cached_entry_by_name, read_constructor_args, LoadConfig};
+use foundry_common::{abi::encode_function_args, retry::Retry, tr};
 use foundry_compilers::{
     artifacts::CompactContract, cache::CacheEntry, info::ContractInfo, Project, Solc,
 };
@@ -32,11 +33,28 @@ pub static RE_BUILD_COMMIT: Lazy<Regex> =
 
 pub static BASE_URL: &str = "https://www.oklink.com/";
 
+/// OKLink's short names of the chains it indexes by chain id, as used in its API urls.
+const CHAIN_SHORT_NAMES: &[(u64, &str)] = &[
+    (1, "eth"),
+    (5, "ethgoerli"),
+    (56, "bsc"),
+    (137, "polygon"),
+    (195, "xlayer_testnet"),
+    (196, "xlayer"),
+];
+
+/// Returns OKLink's short name of the chain, e.g. `eth` for mainnet.
+pub fn chain_short_name(chain: Chain) -> Option<&'static str> {
+    CHAIN_SHORT_NAMES.iter().find(|(id, _)| *id == chain.id()).map(|(_, name)| *name)
+}
+
 #[derive(Clone, Debug, Default)]
 #[non_exhaustive]
 pub struct OKLinkVerificationProvider {
     /// Memoized cached entry of the target contract
     cached_entry: Option<(PathBuf, CacheEntry, CompactContract)>,
+    /// The request built by [VerificationProvider::prepare]
+    prepared: Option<(Client, VerifyContract)>,
 }
 
 /// The contract source provider for [OKLinkVerificationProvider]
@@ -59,14 +77,27 @@ impl VerificationProvider for OKLinkVerificationProvider {
         Ok(())
     }
 
+    async fn prepare(&mut self, args: &VerifyArgs) -> Result<()> {
+        self.prepared = Some(self.prepare_request(args).await?);
+        Ok(())
+    }
+
     async fn verify(&mut self, args: VerifyArgs) -> Result<()> {
-        let (oklink, verify_args) = self.prepare_request(&args).await?;
+        let (oklink, verify_args) = match self.prepared.take() {
+            Some(prepared) => prepared,
+            None => self.prepare_request(&args).await?,
+        };
 
-        if !args.skip_is_verified_check && self.is_contract_verified(&oklink, &verify_args).await? {
+        if !args.skip_is_verified_check &&
+            self.is_contract_verified(&oklink, verify_args.address).await?
+        {
             println!(
-                "\nContract [{}] {:?} is already verified. Skipping verification.",
-                verify_args.contract_name,
-                verify_args.address.to_checksum(None)
+                "{}",
+                tr!(
+                    "\nContract [{name}] {address} is already verified. Skipping verification.",
+                    name = verify_args.contract_name,
+                    address = format!("{:?}", verify_args.address.to_checksum(None)),
+                )
             );
             return Ok(());
         }
@@ -77,8 +108,12 @@ impl VerificationProvider for OKLinkVerificationProvider {
         let resp = retry
             .run_async(|| async {
                 println!(
-                    "\nSubmitting verification for [{}] {}.",
-                    verify_args.contract_name, verify_args.address
+                    "{}",
+                    tr!(
+                        "\nSubmitting verification for [{name}] {address}.",
+                        name = verify_args.contract_name,
+                        address = verify_args.address,
+                    )
                 );
 
                 let resp = oklink
@@ -108,8 +143,12 @@ impl VerificationProvider for OKLinkVerificationProvider {
 
                     warn!("Failed verify submission: {:?}", resp);
                     eprintln!(
-                        "Encountered an error verifying this contract:\nResponse: `{}`\nDetails: `{}`",
-                        resp.message, resp.result
+                        "{}",
+                        tr!(
+                            "Encountered an error verifying this contract:\nResponse: `{response}`\nDetails: `{details}`",
+                            response = resp.message,
+                            details = resp.result,
+                        )
                     );
                     std::process::exit(1);
                 }
@@ -120,13 +159,18 @@ impl VerificationProvider for OKLinkVerificationProvider {
 
         if let Some(resp) = resp {
             println!(
-                "Submitted contract for verification:\n\tResponse: `{}`\n\tGUID: `{}`",
-                resp.message, resp.result,
+                "{}",
+                tr!(
+                    "Submitted contract for verification:\n\tResponse: `{response}`\n\tGUID: `{guid}`",
+                    response = resp.message,
+                    guid = resp.result,
+                )
             );
 
             if args.watch {
                 let check_args = VerifyCheckArgs {
                     id: resp.result,
+                    address: Some(args.address),
                     etherscan: args.etherscan,
                     oklink: args.oklink,
                     retry: RETRY_CHECK_ON_VERIFY,
@@ -136,7 +180,7 @@ impl VerificationProvider for OKLinkVerificationProvider {
                 return self.check(check_args).await;
             }
         } else {
-            println!("Contract source code already verified");
+            println!("{}", tr!("Contract source code already verified"));
         }
 
         Ok(())
@@ -161,8 +205,12 @@ impl VerificationProvider for OKLinkVerificationProvider {
                     trace!(target: "forge::verify", ?resp, "Received verification response");
 
                     eprintln!(
-                        "Contract verification status:\nResponse: `{}`\nDetails: `{}`",
-                        resp.message, resp.result
+                        "{}",
+                        tr!(
+                            "Contract verification status:\nResponse: `{response}`\nDetails: `{details}`",
+                            response = resp.message,
+                            details = resp.result,
+                        )
                     );
 
                     if resp.result == "Pending in queue" {
@@ -174,17 +222,17 @@ impl VerificationProvider for OKLinkVerificationProvider {
                     }
 
                     if resp.result == "Already Verified" {
-                        println!("Contract source code already verified");
+                        println!("{}", tr!("Contract source code already verified"));
                         return Ok(());
                     }
 
                     if resp.status == "0" {
-                        println!("Contract failed to verify.");
+                        println!("{}", tr!("Contract failed to verify."));
                         std::process::exit(1);
                     }
 
                     if resp.result == "Pass - Verified" {
-                        println!("Contract successfully verified");
+                        println!("{}", tr!("Contract successfully verified"));
                     }
 
                     Ok(())
@@ -194,6 +242,15 @@ impl VerificationProvider for OKLinkVerificationProvider {
             .await
             .wrap_err("Checking verification result failed:")
     }
+
+    async fn is_verified(&mut self, args: &VerifyArgs) -> Result<Option<bool>> {
+        let oklink = self.client(
+            args.etherscan.chain.unwrap_or_default(),
+            args.verifier.verifier_url.as_deref(),
+            args.oklink.key().as_deref(),
+        )?;
+        Ok(Some(self.is_contract_verified(&oklink, args.address).await?))
+    }
 }
 
 impl OKLinkVerificationProvider {
@@ -249,12 +306,8 @@ impl OKLinkVerificationProvider {
     }
 
     /// Queries the oklink API to verify if the contract is already verified.
-    async fn is_contract_verified(
-        &self,
-        oklink: &Client,
-        verify_contract: &VerifyContract,
-    ) -> Result<bool> {
-        let check = oklink.contract_abi(verify_contract.address).await;
+    async fn is_contract_verified(&self, oklink: &Client, address: Address) -> Result<bool> {
+        let check = oklink.contract_abi(address).await;
         if let Err(err) = check {
             match err {
                 EtherscanError::ContractCodeNotVerified(_) => return Ok(false),
@@ -431,10 +484,10 @@ impl OKLinkVerificationProvider {
             };
             let encoded_args = encode_function_args(
                 &func,
-                read_constructor_args_file(constructor_args_path.to_path_buf())?,
+                read_constructor_args(constructor_args_path, &constructor.inputs)?,
             )?;
-            let encoded_args = hex::encode(encoded_args);
-            return Ok(Some(encoded_args[8..].into()));
+            // skip the selector
+            return Ok(Some(hex::encode(&encoded_args[4..])));
         }
 
         Ok(args.constructor_args.clone())
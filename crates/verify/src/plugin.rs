@@ -0,0 +1,302 @@
+use super::{
+    etherscan::EtherscanVerificationProvider, provider::VerificationProvider, VerifyArgs,
+    VerifyCheckArgs,
+};
+use crate::retry::RETRY_CHECK_ON_VERIFY;
+use alloy_primitives::Address;
+use eyre::{eyre, Context, Result};
+use foundry_block_explorers::verify::VerifyContract;
+use foundry_common::{retry::Retry, tr};
+use foundry_config::VerifierPluginConfig;
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// The action a plugin is run for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PluginAction {
+    /// Submits the contract for verification.
+    Verify,
+    /// Checks the status of a submitted verification.
+    Check,
+}
+
+/// The JSON request written to the stdin of a plugin.
+///
+/// The verify request is the one sent to Etherscan-like explorers, so plugins of such explorers
+/// only need to forward it.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginRequest {
+    pub action: PluginAction,
+    pub chain: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    /// The id of the verification returned by the plugin, when checking its status.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verifier_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request: Option<VerifyContract>,
+}
+
+/// The status of a verification, as reported by a plugin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PluginStatus {
+    /// The verification was submitted, and its status can be checked with the returned id.
+    Submitted,
+    Pending,
+    Verified,
+    AlreadyVerified,
+    Failed,
+}
+
+/// The JSON response a plugin writes to its stdout.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginResponse {
+    pub status: PluginStatus,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+    /// The page of the contract on the explorer.
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+/// Runs the plugin with the request on its stdin, and parses the response on its stdout.
+///
+/// The stderr of the plugin is inherited, so it can report its progress.
+pub fn run_plugin(
+    name: &str,
+    plugin: &VerifierPluginConfig,
+    request: &PluginRequest,
+) -> Result<PluginResponse> {
+    trace!(target: "forge::verify", name, ?request, "running verifier plugin");
+
+    let mut child = Command::new(&plugin.command)
+        .args(&plugin.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .wrap_err_with(|| format!("Failed to run verifier plugin `{name}` ({})", plugin.command))?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    stdin.write_all(&serde_json::to_vec(request)?)?;
+    drop(stdin);
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        eyre::bail!("Verifier plugin `{name}` failed with {}", output.status)
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    serde_json::from_str(stdout.trim()).wrap_err_with(|| {
+        format!("Verifier plugin `{name}` returned an invalid response:\n{}", stdout.trim())
+    })
+}
+
+/// A verification provider implemented by an executable declared in `[verifier_plugins]`.
+#[derive(Clone, Debug)]
+pub struct PluginVerificationProvider {
+    name: String,
+    plugin: VerifierPluginConfig,
+    /// The request built by [VerificationProvider::prepare]
+    prepared: Option<VerifyContract>,
+}
+
+impl PluginVerificationProvider {
+    pub fn new(name: String, plugin: VerifierPluginConfig) -> Self {
+        Self { name, plugin, prepared: None }
+    }
+
+    async fn prepare_request(&self, args: &VerifyArgs) -> Result<VerifyContract> {
+        EtherscanVerificationProvider::default().create_verify_request(args, None).await
+    }
+}
+
+#[async_trait::async_trait]
+impl VerificationProvider for PluginVerificationProvider {
+    async fn preflight_check(&mut self, args: VerifyArgs) -> Result<()> {
+        let _ = self.prepare_request(&args).await?;
+        Ok(())
+    }
+
+    async fn prepare(&mut self, args: &VerifyArgs) -> Result<()> {
+        self.prepared = Some(self.prepare_request(args).await?);
+        Ok(())
+    }
+
+    async fn verify(&mut self, args: VerifyArgs) -> Result<()> {
+        let verify_args = match self.prepared.take() {
+            Some(prepared) => prepared,
+            None => self.prepare_request(&args).await?,
+        };
+        let request = PluginRequest {
+            action: PluginAction::Verify,
+            chain: args.etherscan.chain.unwrap_or_default().id(),
+            address: Some(args.address),
+            id: None,
+            verifier_url: args.verifier.verifier_url.clone(),
+            api_key: args.etherscan.key(),
+            request: Some(verify_args),
+        };
+
+        println!(
+            "{}",
+            tr!(
+                "\nSubmitting verification for [{name}] {address}.",
+                name = args.contract.name,
+                address = args.address,
+            )
+        );
+        let retry: Retry = args.retry.into();
+        let resp = retry
+            .run_async(|| async { run_plugin(&self.name, &self.plugin, &request) }.boxed())
+            .await?;
+
+        match resp.status {
+            PluginStatus::AlreadyVerified => {
+                println!("{}", tr!("Contract source code already verified"));
+            }
+            PluginStatus::Verified => {
+                println!("{}", tr!("Contract successfully verified"));
+            }
+            PluginStatus::Failed => {
+                eyre::bail!(
+                    "Verifier plugin `{}` failed to verify the contract: {}",
+                    self.name,
+                    resp.message.unwrap_or_default()
+                )
+            }
+            PluginStatus::Submitted | PluginStatus::Pending => {
+                let id = resp.id.ok_or_else(|| {
+                    eyre!(
+                        "Verifier plugin `{}` didn't return the id of the verification",
+                        self.name
+                    )
+                })?;
+                println!(
+                    "{}",
+                    tr!(
+                        "Submitted contract for verification:\n\tResponse: `{response}`\n\tGUID: `{guid}`\n\tURL: {url}",
+                        response = resp.message.unwrap_or_default(),
+                        guid = id,
+                        url = resp.url.unwrap_or_default(),
+                    )
+                );
+
+                if args.watch {
+                    let check_args = VerifyCheckArgs {
+                        id,
+                        address: Some(args.address),
+                        etherscan: args.etherscan,
+                        oklink: args.oklink,
+                        retry: RETRY_CHECK_ON_VERIFY,
+                        verifier: args.verifier,
+                    };
+                    return self.check(check_args).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn check(&self, args: VerifyCheckArgs) -> Result<()> {
+        let request = PluginRequest {
+            action: PluginAction::Check,
+            chain: args.etherscan.chain.unwrap_or_default().id(),
+            address: None,
+            id: Some(args.id.clone()),
+            verifier_url: args.verifier.verifier_url.clone(),
+            api_key: args.etherscan.key(),
+            request: None,
+        };
+        let retry: Retry = args.retry.into();
+        let resp = retry
+            .run_async(|| {
+                async {
+                    let resp = run_plugin(&self.name, &self.plugin, &request)?;
+                    if matches!(resp.status, PluginStatus::Submitted | PluginStatus::Pending) {
+                        return Err(eyre!("Verification is still pending..."));
+                    }
+                    Ok(resp)
+                }
+                .boxed()
+            })
+            .await
+            .wrap_err("Checking verification result failed:")?;
+
+        match resp.status {
+            PluginStatus::Failed => {
+                eyre::bail!("Contract failed to verify: {}", resp.message.unwrap_or_default())
+            }
+            PluginStatus::AlreadyVerified => {
+                println!("{}", tr!("Contract source code already verified"));
+            }
+            _ => println!("{}", tr!("Contract successfully verified")),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> PluginRequest {
+        PluginRequest {
+            action: PluginAction::Check,
+            chain: 728126428,
+            address: None,
+            id: Some("guid".to_string()),
+            verifier_url: None,
+            api_key: Some("key".to_string()),
+            request: None,
+        }
+    }
+
+    #[test]
+    fn serializes_requests() {
+        assert_eq!(
+            serde_json::to_value(request()).unwrap(),
+            serde_json::json!({ "action": "check", "chain": 728126428, "id": "guid", "apiKey": "key" })
+        );
+    }
+
+    #[test]
+    fn parses_responses() {
+        let resp: PluginResponse =
+            serde_json::from_str(r#"{"status":"already-verified","message":"ok"}"#).unwrap();
+        assert_eq!(resp.status, PluginStatus::AlreadyVerified);
+        assert_eq!(resp.message.as_deref(), Some("ok"));
+        assert!(serde_json::from_str::<PluginResponse>(r#"{"status":"unknown"}"#).is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn runs_plugins() {
+        let plugin = VerifierPluginConfig {
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                r#"grep -q '"action":"check"' && echo '{"status":"verified","id":"guid"}'"#
+                    .to_string(),
+            ],
+        };
+        let resp = run_plugin("test", &plugin, &request()).unwrap();
+        assert_eq!(resp.status, PluginStatus::Verified);
+        assert_eq!(resp.id.as_deref(), Some("guid"));
+
+        let plugin = VerifierPluginConfig { command: "false".to_string(), args: vec![] };
+        assert!(run_plugin("test", &plugin, &request()).is_err());
+    }
+}
@@ -8,7 +8,7 @@ use std::{fmt, str::FromStr};
 
 /// An abstraction for various verification providers such as etherscan, sourcify, blockscout, oklink
 #[async_trait]
-pub trait VerificationProvider {
+pub trait VerificationProvider: Send {
     /// This should ensure the verify request can be prepared successfully.
     ///
     /// Caution: Implementers must ensure that this _never_ sends the actual verify request
@@ -18,11 +18,24 @@ pub trait VerificationProvider {
     /// fails due to misconfiguration.
     async fn preflight_check(&mut self, args: VerifyArgs) -> Result<()>;
 
+    /// Builds the verify request ahead of [`VerificationProvider::verify`], which then submits the
+    /// prepared request instead of building it again.
+    ///
+    /// Building a request reads, and possibly compiles, the sources of the contract, so the
+    /// requests of multiple contracts can be prepared in parallel and submitted one at a time.
+    async fn prepare(&mut self, args: &VerifyArgs) -> Result<()>;
+
     /// Sends the actual verify request for the targeted contract.
     async fn verify(&mut self, args: VerifyArgs) -> Result<()>;
 
     /// Checks whether the contract is verified.
     async fn check(&self, args: VerifyCheckArgs) -> Result<()>;
+
+    /// Returns whether the contract of the [`VerifyArgs`] is verified already, without building
+    /// the verify request, or `None` if the provider can't tell.
+    async fn is_verified(&mut self, _args: &VerifyArgs) -> Result<Option<bool>> {
+        Ok(None)
+    }
 }
 
 impl FromStr for VerificationProviderType {
@@ -54,6 +67,9 @@ impl fmt::Display for VerificationProviderType {
             VerificationProviderType::Oklink => {
                 write!(f, "oklink")?;
             }
+            VerificationProviderType::Plugin => {
+                write!(f, "plugin")?;
+            }
         };
         Ok(())
     }
@@ -66,6 +82,9 @@ pub enum VerificationProviderType {
     Sourcify,
     Blockscout,
     Oklink,
+    /// A plugin declared in `[verifier_plugins]`, selected with `--verifier-plugin`.
+    #[value(skip)]
+    Plugin,
 }
 
 impl VerificationProviderType {
@@ -85,6 +104,9 @@ impl VerificationProviderType {
                 Ok(Box::<EtherscanVerificationProvider>::default())
             }
             VerificationProviderType::Oklink => Ok(Box::<OKLinkVerificationProvider>::default()),
+            VerificationProviderType::Plugin => {
+                eyre::bail!("the verifier plugin must be selected with --verifier-plugin")
+            }
         }
     }
 }
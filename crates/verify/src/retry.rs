@@ -8,6 +8,9 @@ pub const RETRY_CHECK_ON_VERIFY: RetryArgs = RetryArgs { retries: 8, delay: 15 }
 /// Retry config used when waiting for a created contract
 pub const RETRY_VERIFY_ON_CREATE: RetryArgs = RetryArgs { retries: 15, delay: 5 };
 
+/// The default delay between verification attempts, in seconds.
+pub const DEFAULT_RETRY_DELAY: u32 = 5;
+
 /// Retry arguments for contract verification.
 #[derive(Clone, Copy, Debug, Parser)]
 #[command(about = "Allows to use retry arguments for contract verification")] // override doc
@@ -24,7 +27,7 @@ pub struct RetryArgs {
     #[arg(
         long,
         value_parser = RangedU64ValueParser::<u32>::new().range(0..=30),
-        default_value = "5",
+        default_value_t = DEFAULT_RETRY_DELAY,
     )]
     pub delay: u32,
 }
@@ -35,6 +38,16 @@ impl Default for RetryArgs {
     }
 }
 
+impl RetryArgs {
+    /// Scales the delay between the attempts to the block time of the chain, since explorers
+    /// index contracts as fast as blocks are produced, keeping the total time waited.
+    pub fn scale_to_block_time(self, block_time: Duration) -> Self {
+        let delay = (block_time.as_secs() / 2).clamp(1, 30) as u32;
+        let retries = (self.retries * self.delay).div_ceil(delay).max(1);
+        Self { retries, delay }
+    }
+}
+
 impl From<RetryArgs> for Retry {
     fn from(r: RetryArgs) -> Self {
         Retry::new(r.retries, Some(Duration::from_secs(r.delay as u64)))
@@ -59,4 +72,15 @@ mod tests {
         assert_eq!(args.retries, 10);
         assert_eq!(args.delay, 10);
     }
+
+    #[test]
+    fn scales_to_block_time() {
+        let args = RetryArgs { retries: 5, delay: DEFAULT_RETRY_DELAY };
+        let fast = args.scale_to_block_time(Duration::from_secs(2));
+        assert_eq!((fast.retries, fast.delay), (25, 1));
+        let slow = args.scale_to_block_time(Duration::from_secs(12));
+        assert_eq!((slow.retries, slow.delay), (5, 6));
+        let subsecond = args.scale_to_block_time(Duration::from_millis(250));
+        assert_eq!(subsecond.delay, 1);
+    }
 }
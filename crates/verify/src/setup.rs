@@ -0,0 +1,356 @@
+use crate::{oklink, provider::VerificationProviderType, sourcify::SOURCIFY_URL};
+use clap::Parser;
+use dialoguer::Select;
+use eyre::{Result, WrapErr};
+use foundry_cli::{
+    opts::{EtherscanOpts, OKLinkOpts, RpcOpts},
+    utils::{self, LoadConfig},
+};
+use foundry_config::{figment, impl_figment_convert_cast, Chain, Config};
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+use std::{env, fmt, time::Duration};
+
+/// The environment variables holding the API keys.
+const ETHERSCAN_API_KEY: &str = "ETHERSCAN_API_KEY";
+const OKLINK_API_KEY: &str = "OKLINK_API_KEY";
+
+/// Parts of the error messages of Etherscan-compatible APIs which don't know the requested action,
+/// e.g. `Error! Missing Or invalid Action name`.
+const UNSUPPORTED_MESSAGES: &[&str] =
+    &["invalid action", "invalid module", "unknown action", "unknown module", "not supported"];
+
+/// How long to wait for an explorer to respond.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// CLI arguments for `forge verify-setup`.
+#[derive(Clone, Debug, Parser)]
+pub struct VerifySetupArgs {
+    /// The OKLink short name of the chain, e.g. `eth`, if it's not known to Foundry.
+    #[arg(long, value_name = "NAME")]
+    pub oklink_chain: Option<String>,
+
+    /// The Blockscout API URL of the chain, e.g. `https://eth.blockscout.com/api`.
+    ///
+    /// Blockscout instances are self-hosted, so it's only probed if this is set.
+    #[arg(long, value_name = "URL")]
+    pub blockscout_url: Option<String>,
+
+    /// Don't prompt, and use the first explorer that is ready.
+    #[arg(long, short)]
+    pub yes: bool,
+
+    #[command(flatten)]
+    pub rpc: RpcOpts,
+
+    #[command(flatten)]
+    pub etherscan: EtherscanOpts,
+
+    #[command(flatten)]
+    pub oklink: OKLinkOpts,
+}
+
+impl_figment_convert_cast!(VerifySetupArgs);
+
+impl figment::Provider for VerifySetupArgs {
+    fn metadata(&self) -> figment::Metadata {
+        figment::Metadata::named("Verify Setup Provider")
+    }
+
+    fn data(
+        &self,
+    ) -> Result<figment::value::Map<figment::Profile, figment::value::Dict>, figment::Error> {
+        let mut dict = self.etherscan.dict();
+        dict.extend(self.rpc.dict());
+        Ok(figment::value::Map::from([(Config::selected_profile(), dict)]))
+    }
+}
+
+impl VerifySetupArgs {
+    /// Probes the explorers of the chain and writes the verifier config of the selected one to
+    /// `foundry.toml`.
+    pub async fn run(self) -> Result<()> {
+        let config = self.load_config_emit_warnings();
+        let config_path = config.get_config_path();
+        if !config_path.exists() {
+            eyre::bail!("No foundry.toml found in {}", config.__root.0.display())
+        }
+
+        let chain = match self.etherscan.chain.or(config.chain) {
+            Some(chain) => chain,
+            None => utils::get_chain(None, utils::get_provider(&config)?).await?,
+        };
+        println!("Setting up verification for chain {chain} ({}).", chain.id());
+
+        let client = Client::builder().timeout(PROBE_TIMEOUT).build()?;
+        let (oklink, etherscan, blockscout, sourcify) = futures::join!(
+            self.probe_oklink(&client, chain),
+            probe_etherscan(&client, &config, chain),
+            self.probe_blockscout(&client),
+            probe_sourcify(&client, chain),
+        );
+        let probes = [oklink, etherscan, blockscout, sourcify];
+
+        println!("\nExplorers:");
+        for probe in &probes {
+            println!("  {:<10}  {}", probe.verifier.to_string(), probe.status);
+        }
+
+        let ready =
+            probes.iter().filter(|probe| probe.status == ProbeStatus::Ready).collect::<Vec<_>>();
+        if ready.is_empty() {
+            eyre::bail!("None of the explorers is ready to verify contracts on {chain}.")
+        }
+        let probe = if self.yes || ready.len() == 1 {
+            ready[0]
+        } else {
+            let items = ready.iter().map(|probe| probe.verifier.to_string()).collect::<Vec<_>>();
+            let index = Select::new()
+                .with_prompt("Which explorer should be used to verify contracts?")
+                .items(&items)
+                .default(0)
+                .interact()?;
+            ready[index]
+        };
+
+        probe.write_config(&config, chain).wrap_err("failed to update foundry.toml")?;
+        println!(
+            "\nConfigured {} to verify contracts on {chain} in {}.",
+            probe.verifier,
+            config_path.display()
+        );
+        if let Some(key) = probe.api_key_var() {
+            if env::var(key).map_or(true, |value| value.is_empty()) {
+                println!("Set `{key}` in your environment or `.env` file to use it.");
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn probe_oklink(&self, client: &Client, chain: Chain) -> Probe {
+        let short_name = self.oklink_chain.as_deref().or_else(|| oklink::chain_short_name(chain));
+        let Some(short_name) = short_name else {
+            return Probe::new(
+                VerificationProviderType::Oklink,
+                None,
+                ProbeStatus::Skipped("unknown chain, pass --oklink-chain".to_string()),
+            )
+        };
+
+        let url =
+            format!("{}api/explorer/v1/contract/verify/async/api/{short_name}/", oklink::BASE_URL);
+        let key = self.oklink.key();
+        let status = match probe_api(client, &url, key.as_deref()).await {
+            ProbeStatus::Ready if key.is_none() => ProbeStatus::MissingKey(OKLINK_API_KEY),
+            status => status,
+        };
+        Probe::new(VerificationProviderType::Oklink, Some(url), status)
+    }
+
+    async fn probe_blockscout(&self, client: &Client) -> Probe {
+        let Some(url) = self.blockscout_url.clone() else {
+            return Probe::new(
+                VerificationProviderType::Blockscout,
+                None,
+                ProbeStatus::Skipped("pass --blockscout-url to probe it".to_string()),
+            )
+        };
+        let status = probe_api(client, &url, None).await;
+        Probe::new(VerificationProviderType::Blockscout, Some(url), status)
+    }
+}
+
+/// The result of probing an explorer.
+#[derive(Debug)]
+struct Probe {
+    verifier: VerificationProviderType,
+    /// The API url of the explorer for the chain.
+    url: Option<String>,
+    status: ProbeStatus,
+}
+
+impl Probe {
+    fn new(verifier: VerificationProviderType, url: Option<String>, status: ProbeStatus) -> Self {
+        Self { verifier, url, status }
+    }
+
+    /// Returns the environment variable holding the explorer's API key, if it needs one.
+    fn api_key_var(&self) -> Option<&'static str> {
+        match self.verifier {
+            VerificationProviderType::Etherscan => Some(ETHERSCAN_API_KEY),
+            VerificationProviderType::Oklink => Some(OKLINK_API_KEY),
+            VerificationProviderType::Blockscout |
+            VerificationProviderType::Sourcify |
+            VerificationProviderType::Plugin => None,
+        }
+    }
+
+    /// Writes the `[etherscan]` entry and the `[profile.chain.<chain>]` verifier settings for this
+    /// explorer. API keys are only ever referenced by their environment variable.
+    fn write_config(&self, config: &Config, chain: Chain) -> Result<()> {
+        let verifier = self.verifier.to_string();
+        let mut settings = vec![("verifier", verifier.as_str())];
+        match self.verifier {
+            VerificationProviderType::Etherscan => {
+                let key = format!("${{{ETHERSCAN_API_KEY}}}");
+                config.update_etherscan(chain, &key, self.url.as_deref())?;
+            }
+            VerificationProviderType::Oklink | VerificationProviderType::Blockscout => {
+                settings.extend(self.url.as_deref().map(|url| ("verifier_url", url)));
+            }
+            VerificationProviderType::Sourcify | VerificationProviderType::Plugin => {}
+        }
+        config.update_chain_profile(chain, settings)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ProbeStatus {
+    /// The explorer indexes the chain and the API key, if required, is valid.
+    Ready,
+    /// The explorer requires an API key, but none was found in the given environment variable.
+    MissingKey(&'static str),
+    /// The explorer rejected the API key.
+    InvalidKey,
+    /// The explorer doesn't index the chain.
+    Unsupported,
+    /// The explorer was not probed.
+    Skipped(String),
+    /// The explorer could not be reached or returned an unexpected response.
+    Unreachable(String),
+}
+
+impl fmt::Display for ProbeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ready => f.write_str("ready"),
+            Self::MissingKey(var) => write!(f, "API key missing, set `{var}`"),
+            Self::InvalidKey => f.write_str("API key rejected"),
+            Self::Unsupported => f.write_str("chain not supported"),
+            Self::Skipped(reason) => write!(f, "skipped, {reason}"),
+            Self::Unreachable(err) => write!(f, "unreachable: {err}"),
+        }
+    }
+}
+
+async fn probe_etherscan(client: &Client, config: &Config, chain: Chain) -> Probe {
+    let (url, key) = match config.get_etherscan_config_with_chain(Some(chain)) {
+        Ok(Some(etherscan)) => (Some(etherscan.api_url), Some(etherscan.key)),
+        _ => (chain.etherscan_urls().map(|(api, _)| api.to_string()), None),
+    };
+    let status = match &url {
+        Some(url) => match probe_api(client, url, key.as_deref()).await {
+            ProbeStatus::Ready if key.is_none() => ProbeStatus::MissingKey(ETHERSCAN_API_KEY),
+            status => status,
+        },
+        None => ProbeStatus::Unsupported,
+    };
+    Probe::new(VerificationProviderType::Etherscan, url, status)
+}
+
+async fn probe_sourcify(client: &Client, chain: Chain) -> Probe {
+    let url = SOURCIFY_URL.to_string();
+    let status = match fetch_json(client.get(format!("{url}chains"))).await {
+        Ok(chains) => {
+            let supported = chains.as_array().map_or(false, |chains| {
+                chains.iter().any(|c| {
+                    c["chainId"].as_u64() == Some(chain.id()) &&
+                        c["supported"].as_bool().unwrap_or(true)
+                })
+            });
+            if supported {
+                ProbeStatus::Ready
+            } else {
+                ProbeStatus::Unsupported
+            }
+        }
+        Err(err) => ProbeStatus::Unreachable(err.to_string()),
+    };
+    Probe::new(VerificationProviderType::Sourcify, Some(url), status)
+}
+
+/// Probes an Etherscan-compatible API by looking up the ABI of the zero address.
+///
+/// Returns [`ProbeStatus::Ready`] if the API responds and doesn't reject the given key.
+async fn probe_api(client: &Client, url: &str, key: Option<&str>) -> ProbeStatus {
+    let mut query = vec![
+        ("module", "contract"),
+        ("action", "getabi"),
+        ("address", "0x0000000000000000000000000000000000000000"),
+    ];
+    query.extend(key.map(|key| ("apikey", key)));
+
+    let response = match client.get(url).query(&query).send().await {
+        Ok(response) => response,
+        Err(err) => return ProbeStatus::Unreachable(err.to_string()),
+    };
+    let status = response.status();
+    match response.text().await {
+        Ok(body) => api_status(status, &body, key.is_some()),
+        Err(err) => ProbeStatus::Unreachable(err.to_string()),
+    }
+}
+
+/// Tells from the response of an Etherscan-compatible API to a `getabi` request whether it's
+/// ready to verify contracts.
+fn api_status(status: StatusCode, body: &str, has_key: bool) -> ProbeStatus {
+    // an API without the chain, or without the contract module
+    if status == StatusCode::NOT_FOUND {
+        return ProbeStatus::Unsupported
+    }
+    if !status.is_success() {
+        return ProbeStatus::Unreachable(format!("HTTP status {status}"))
+    }
+    let Ok(response) = serde_json::from_str::<Value>(body) else {
+        return ProbeStatus::Unreachable("the response is not JSON".to_string())
+    };
+
+    let message = ["message", "msg", "result"]
+        .iter()
+        .filter_map(|field| response[field].as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+
+    if UNSUPPORTED_MESSAGES.iter().any(|unsupported| message.contains(unsupported)) {
+        ProbeStatus::Unsupported
+    } else if has_key && (message.contains("api key") || message.contains("apikey")) {
+        ProbeStatus::InvalidKey
+    } else {
+        ProbeStatus::Ready
+    }
+}
+
+async fn fetch_json(request: reqwest::RequestBuilder) -> Result<Value> {
+    Ok(request.send().await?.error_for_status()?.json().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_tell_api_status() {
+        let ok = r#"{"status":"0","message":"NOTOK","result":"Contract source code not verified"}"#;
+        assert_eq!(api_status(StatusCode::OK, ok, true), ProbeStatus::Ready);
+
+        let invalid_key = r#"{"status":"0","message":"NOTOK","result":"Invalid API Key"}"#;
+        assert_eq!(api_status(StatusCode::OK, invalid_key, true), ProbeStatus::InvalidKey);
+        assert_eq!(api_status(StatusCode::OK, invalid_key, false), ProbeStatus::Ready);
+
+        let unknown_action =
+            r#"{"status":"0","message":"NOTOK","result":"Error! Missing Or invalid Action name"}"#;
+        assert_eq!(api_status(StatusCode::OK, unknown_action, true), ProbeStatus::Unsupported);
+        assert_eq!(api_status(StatusCode::NOT_FOUND, "", true), ProbeStatus::Unsupported);
+
+        assert!(matches!(
+            api_status(StatusCode::BAD_GATEWAY, "", true),
+            ProbeStatus::Unreachable(_)
+        ));
+        assert!(matches!(
+            api_status(StatusCode::OK, "<html></html>", true),
+            ProbeStatus::Unreachable(_)
+        ));
+    }
+}
@@ -1,4 +1,5 @@
 use super::{provider::VerificationProvider, VerifyArgs, VerifyCheckArgs};
+use alloy_primitives::Bytes;
 use async_trait::async_trait;
 use eyre::Result;
 use foundry_cli::utils::{get_cached_entry_by_name, LoadConfig};
@@ -14,7 +15,10 @@ pub static SOURCIFY_URL: &str = "https://sourcify.dev/server/";
 /// The type that can verify a contract on `sourcify`
 #[derive(Clone, Debug, Default)]
 #[non_exhaustive]
-pub struct SourcifyVerificationProvider;
+pub struct SourcifyVerificationProvider {
+    /// The request built by [VerificationProvider::prepare]
+    prepared: Option<SourcifyVerifyRequest>,
+}
 
 #[async_trait]
 impl VerificationProvider for SourcifyVerificationProvider {
@@ -23,11 +27,21 @@ impl VerificationProvider for SourcifyVerificationProvider {
         Ok(())
     }
 
+    async fn prepare(&mut self, args: &VerifyArgs) -> Result<()> {
+        self.prepared = Some(self.prepare_request(args)?);
+        Ok(())
+    }
+
     async fn verify(&mut self, args: VerifyArgs) -> Result<()> {
-        let body = self.prepare_request(&args)?;
+        let body = match self.prepared.take() {
+            Some(body) => body,
+            None => self.prepare_request(&args)?,
+        };
 
         trace!("submitting verification request {:?}", body);
 
+        // Serialize the sources once, retries share the same buffer.
+        let body = Bytes::from(serde_json::to_vec(&body)?);
         let client = reqwest::Client::new();
 
         let retry: Retry = args.retry.into();
@@ -42,7 +56,7 @@ impl VerificationProvider for SourcifyVerificationProvider {
                     let response = client
                         .post(args.verifier.verifier_url.as_deref().unwrap_or(SOURCIFY_URL))
                         .header("Content-Type", "application/json")
-                        .body(serde_json::to_string(&body)?)
+                        .body(body.0.clone())
                         .send()
                         .await?;
 
@@ -184,7 +198,7 @@ metadata output can be enabled via `extra_output = ["metadata"]` in `foundry.tom
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 pub struct SourcifyVerifyRequest {
     address: String,
     chain: String,
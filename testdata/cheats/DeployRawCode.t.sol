@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract Payable {
+    uint256 public immutable received;
+
+    constructor() payable {
+        received = msg.value;
+    }
+}
+
+contract RawDeployer {
+    function deployAndRevert(Vm vm, bytes memory initCode) external {
+        vm.deployRawCode(initCode, 0);
+        revert("reverted");
+    }
+}
+
+contract DeployRawCodeTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testDeployRawCode() public {
+        vm.deal(address(this), 1 ether);
+        uint64 nonce = vm.getNonce(address(this));
+        address deployed = vm.deployRawCode(type(Payable).creationCode, 1 ether);
+
+        assertEq(deployed, computeCreateAddress(address(this), nonce));
+        assertEq(vm.getNonce(address(this)), nonce + 1);
+        assertEq(Payable(deployed).received(), 1 ether);
+        assertEq(deployed.balance, 1 ether);
+    }
+
+    function testDeployRawCodeIsRevertedWithItsFrame() public {
+        RawDeployer deployer = new RawDeployer();
+        address deployed = computeCreateAddress(address(deployer), 1);
+
+        try deployer.deployAndRevert(vm, type(Payable).creationCode) {} catch {}
+
+        assertEq(deployed.code.length, 0);
+        assertEq(vm.getNonce(address(deployer)), 1);
+    }
+
+    function computeCreateAddress(address deployer, uint256 nonce) internal pure returns (address) {
+        require(nonce > 0 && nonce < 0x80, "unsupported nonce");
+        return address(uint160(uint256(keccak256(abi.encodePacked(bytes1(0xd6), bytes1(0x94), deployer, uint8(nonce))))));
+    }
+}
@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract L2AliasTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testComputeL2Alias() public {
+        address l1 = 0x4200000000000000000000000000000000000007;
+        address l2 = vm.computeL2Alias(l1);
+        assertEq(l2, 0x5311000000000000000000000000000000001118);
+        assertEq(vm.undoL2Alias(l2), l1);
+    }
+
+    function testComputeL2AliasWraps() public {
+        address l1 = 0xFFfFfFffFFfffFFfFFfFFFFFffFFFffffFfFFFfF;
+        assertEq(vm.computeL2Alias(l1), 0x1111000000000000000000000000000000001110);
+        assertEq(vm.undoL2Alias(vm.computeL2Alias(l1)), l1);
+    }
+}
@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract PayableConstructor {
+    uint256 public immutable received;
+
+    constructor() payable {
+        received = msg.value;
+    }
+}
+
+contract UnusedOverrides {
+    Vm constant vm = Vm(address(bytes20(uint160(uint256(keccak256("hevm cheat code"))))));
+
+    function overrideWithoutDeploying() external {
+        vm.overrideNextDeployment(Vm.DeployOverrides({value: 1 ether, gasLimit: 0}));
+    }
+}
+
+contract OverrideNextDeploymentTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    address constant BROADCASTER = address(0x1234);
+
+    function setUp() public {
+        vm.deal(BROADCASTER, 10 ether);
+    }
+
+    function testOverrideNextDeploymentValue() public {
+        vm.overrideNextDeployment(Vm.DeployOverrides({value: 1 ether, gasLimit: 500_000}));
+        vm.startBroadcast(BROADCASTER);
+        PayableConstructor first = new PayableConstructor{salt: bytes32(uint256(1))}();
+        PayableConstructor second = new PayableConstructor{salt: bytes32(uint256(2))}();
+        vm.stopBroadcast();
+
+        assertEq(first.received(), 1 ether);
+        assertEq(address(first).balance, 1 ether);
+        // The overrides only apply to the next deployment.
+        assertEq(second.received(), 0);
+        assertEq(BROADCASTER.balance, 9 ether);
+    }
+
+    function testOverrideNextDeploymentCreate() public {
+        vm.overrideNextDeployment(Vm.DeployOverrides({value: 2 ether, gasLimit: 0}));
+        vm.broadcast(BROADCASTER);
+        PayableConstructor deployed = new PayableConstructor();
+
+        assertEq(deployed.received(), 2 ether);
+        assertEq(BROADCASTER.balance, 8 ether);
+    }
+
+    function testUnusedOverridesRevert() public {
+        UnusedOverrides unused = new UnusedOverrides();
+        vm.expectRevert("overrideNextDeployment: the overrides weren't used by a broadcast deployment");
+        unused.overrideWithoutDeploying();
+
+        // The overrides don't carry over to a later deployment.
+        vm.broadcast(BROADCASTER);
+        PayableConstructor deployed = new PayableConstructor();
+        assertEq(deployed.received(), 0);
+        assertEq(BROADCASTER.balance, 10 ether);
+    }
+}
@@ -18,6 +18,7 @@ interface Vm {
     struct ChainInfo { uint256 forkId; uint256 chainId; }
     struct AccountAccess { ChainInfo chainInfo; AccountAccessKind kind; address account; address accessor; bool initialized; uint256 oldBalance; uint256 newBalance; bytes deployedCode; uint256 value; bytes data; bool reverted; StorageAccess[] storageAccesses; uint64 depth; }
     struct StorageAccess { address account; bytes32 slot; bool isWrite; bytes32 previousValue; bytes32 newValue; bool reverted; }
+    struct DeployOverrides { uint256 value; uint64 gasLimit; }
     function _expectCheatcodeRevert() external;
     function _expectCheatcodeRevert(bytes4 revertData) external;
     function _expectCheatcodeRevert(bytes calldata revertData) external;
@@ -154,6 +155,7 @@ interface Vm {
     function computeCreate2Address(bytes32 salt, bytes32 initCodeHash, address deployer) external pure returns (address);
     function computeCreate2Address(bytes32 salt, bytes32 initCodeHash) external pure returns (address);
     function computeCreateAddress(address deployer, uint256 nonce) external pure returns (address);
+    function computeL2Alias(address l1Address) external pure returns (address l2Address);
     function cool(address target) external;
     function copyFile(string calldata from, string calldata to) external returns (uint64 copied);
     function createDir(string calldata path, bool recursive) external;
@@ -169,6 +171,7 @@ interface Vm {
     function deal(address account, uint256 newBalance) external;
     function deleteSnapshot(uint256 snapshotId) external returns (bool success);
     function deleteSnapshots() external;
+    function deployRawCode(bytes calldata initCode, uint256 value) external returns (address deployed);
     function deriveKey(string calldata mnemonic, uint32 index) external pure returns (uint256 privateKey);
     function deriveKey(string calldata mnemonic, string calldata derivationPath, uint32 index) external pure returns (uint256 privateKey);
     function deriveKey(string calldata mnemonic, uint32 index, string calldata language) external pure returns (uint256 privateKey);
@@ -238,6 +241,7 @@ interface Vm {
     function getNonce(Wallet calldata wallet) external returns (uint64 nonce);
     function getRecordedLogs() external returns (Log[] memory logs);
     function isDir(string calldata path) external returns (bool result);
+    function isFeatureEnabled(string calldata name) external view returns (bool enabled);
     function isFile(string calldata path) external returns (bool result);
     function isPersistent(address account) external view returns (bool persistent);
     function keyExists(string calldata json, string calldata key) external view returns (bool);
@@ -254,6 +258,21 @@ interface Vm {
     function mockCallRevert(address callee, uint256 msgValue, bytes calldata data, bytes calldata revertData) external;
     function mockCall(address callee, bytes calldata data, bytes calldata returnData) external;
     function mockCall(address callee, uint256 msgValue, bytes calldata data, bytes calldata returnData) external;
+    function overrideNextDeployment(DeployOverrides calldata overrides) external;
+    function paramAddress(string calldata name) external view returns (address value);
+    function paramBool(string calldata name) external view returns (bool value);
+    function paramBytes(string calldata name) external view returns (bytes memory value);
+    function paramBytes32(string calldata name) external view returns (bytes32 value);
+    function paramInt(string calldata name) external view returns (int256 value);
+    function paramOr(string calldata name, bool defaultValue) external view returns (bool value);
+    function paramOr(string calldata name, uint256 defaultValue) external view returns (uint256 value);
+    function paramOr(string calldata name, int256 defaultValue) external view returns (int256 value);
+    function paramOr(string calldata name, address defaultValue) external view returns (address value);
+    function paramOr(string calldata name, bytes32 defaultValue) external view returns (bytes32 value);
+    function paramOr(string calldata name, string calldata defaultValue) external view returns (string memory value);
+    function paramOr(string calldata name, bytes calldata defaultValue) external view returns (bytes memory value);
+    function paramString(string calldata name) external view returns (string memory value);
+    function paramUint(string calldata name) external view returns (uint256 value);
     function parseAddress(string calldata stringifiedValue) external pure returns (address parsedValue);
     function parseBool(string calldata stringifiedValue) external pure returns (bool parsedValue);
     function parseBytes(string calldata stringifiedValue) external pure returns (bytes memory parsedValue);
@@ -384,6 +403,7 @@ interface Vm {
     function trim(string calldata input) external pure returns (string memory output);
     function tryFfi(string[] calldata commandInput) external returns (FfiResult memory result);
     function txGasPrice(uint256 newGasPrice) external;
+    function undoL2Alias(address l2Address) external pure returns (address l1Address);
     function unixTime() external returns (uint256 milliseconds);
     function warp(uint256 newTimestamp) external;
     function writeFile(string calldata path, string calldata data) external;